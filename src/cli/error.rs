@@ -27,6 +27,14 @@ impl From<serde_yaml::Error> for CliError {
     }
 }
 
+impl From<serde_json::Error> for CliError {
+    fn from(e: serde_json::Error) -> Self {
+        Self {
+            msg: format!("serde_json::Error: {}", e),
+        }
+    }
+}
+
 impl From<std::io::Error> for CliError {
     fn from(e: std::io::Error) -> Self {
         Self {
@@ -2,19 +2,94 @@
 
 mod error;
 mod state;
+mod url;
 
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
 use std::str::FromStr;
 
 use nipart::{
-    NetworkCommitQueryOption, NipartApplyOption, NipartConnection, NipartEvent,
-    NipartLogLevel, NipartQueryOption,
+    NetworkAuditRecord, NetworkCommit, NetworkCommitQueryOption, NetworkState,
+    NipartApplyOption, NipartConnection, NipartEvent, NipartLogLevel,
+    NipartQueryOption,
 };
 
-use crate::{error::CliError, state::state_from_file};
+use crate::{
+    error::CliError,
+    state::{
+        filter_state_section, state_from_file, state_from_file_or_url,
+        state_to_string,
+    },
+};
+
+const CLI_NAME: &str = "nipc";
+const EXIT_CODE_DRIFTED: i32 = 1;
+const EXIT_CODE_DIFFERENT: i32 = 2;
 
 #[tokio::main]
 async fn main() -> Result<(), CliError> {
-    let matches = clap::Command::new("nipc")
+    let matches = gen_cli().get_matches();
+
+    let mut log_builder = env_logger::Builder::new();
+    log_builder.filter(Some("nipart"), log::LevelFilter::Debug);
+    log_builder.filter(None, log::LevelFilter::Debug);
+    log_builder.init();
+
+    if let Some(m) = matches.subcommand_matches("plugin") {
+        handle_plugin(m).await?;
+    } else if let Some(m) = matches.subcommand_matches("ping") {
+        handle_ping(m).await?;
+    } else if let Some(m) = matches.subcommand_matches("show") {
+        handle_show(m).await?;
+    } else if let Some(m) = matches.subcommand_matches("log") {
+        handle_log(m).await?;
+    } else if let Some(matches) = matches.subcommand_matches("debug") {
+        handle_debug(matches).await?;
+    } else if let Some(matches) = matches.subcommand_matches("daemon") {
+        handle_daemon_cmd(matches).await?;
+    } else if let Some(matches) = matches.subcommand_matches("apply") {
+        handle_apply(matches).await?;
+    } else if let Some(matches) = matches.subcommand_matches("track") {
+        handle_track_cmd(matches).await?;
+    } else if let Some(matches) = matches.subcommand_matches("diff") {
+        handle_diff(matches).await?;
+    } else if let Some(matches) = matches.subcommand_matches("gen-conf") {
+        handle_gen_conf(matches)?;
+    } else if let Some(matches) = matches.subcommand_matches("validate") {
+        handle_validate(matches)?;
+    } else if let Some(matches) = matches.subcommand_matches("completion") {
+        handle_completion(matches);
+    } else if matches.subcommand_matches("schema").is_some() {
+        handle_schema()?;
+    } else if let Some(matches) = matches.subcommand_matches("audit") {
+        handle_audit(matches)?;
+    }
+
+    Ok(())
+}
+
+// Shared by subcommands printing network state
+fn output_format_arg() -> clap::Arg {
+    clap::Arg::new("OUTPUT")
+        .long("output")
+        .short('o')
+        .alias("format")
+        .value_parser(clap::builder::PossibleValuesParser::new([
+            "yaml", "json",
+        ]))
+        .default_value("yaml")
+        .help("Output format")
+}
+
+fn output_format(matches: &clap::ArgMatches) -> &str {
+    matches
+        .get_one::<String>("OUTPUT")
+        .map(|f| f.as_str())
+        .unwrap_or("yaml")
+}
+
+fn gen_cli() -> clap::Command {
+    clap::Command::new(CLI_NAME)
         .about("CLI to Nipart daemon")
         .arg_required_else_help(true)
         .subcommand(
@@ -26,21 +101,257 @@ async fn main() -> Result<(), CliError> {
                     clap::Command::new("show").alias("s").about("Show plugins"),
                 ),
         )
+        .subcommand(
+            clap::Command::new("ping")
+                .about("Check whether nipart daemon is alive")
+                .arg(
+                    clap::Arg::new("VERBOSE")
+                        .short('v')
+                        .long("verbose")
+                        .action(clap::ArgAction::SetTrue)
+                        .help(
+                            "Show daemon version, uptime, schema version \
+                            and loaded plugins",
+                        ),
+                ),
+        )
         .subcommand(
             clap::Command::new("show")
                 .alias("s")
-                .about("Query network state"),
+                .about("Query network state")
+                .arg(
+                    clap::Arg::new("FORCE_REFRESH")
+                        .long("force-refresh")
+                        .action(clap::ArgAction::SetTrue)
+                        .help("Bypass daemon cache and query plugins"),
+                )
+                .arg(
+                    clap::Arg::new("SINCE")
+                        .long("since")
+                        .value_name("COMMIT_ID")
+                        .help(
+                            "Only show properties of specified commit which \
+                            running network state no longer matches",
+                        ),
+                )
+                .arg(output_format_arg())
+                .arg(
+                    clap::Arg::new("ONLY")
+                        .long("only")
+                        .value_parser(clap::builder::PossibleValuesParser::new(
+                            ["interfaces", "routes", "dns", "route-rules"],
+                        ))
+                        .help("Only show specified section"),
+                )
+                .arg(
+                    clap::Arg::new("RUNNING_VS_SAVED")
+                        .long("running-vs-saved")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "SINCE", "ONLY", "OUTPUT", "MINIMAL",
+                        ])
+                        .help(
+                            "Print summary of drift between running and \
+                            saved network state, exit with 1 if drifted",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("MINIMAL")
+                        .long("minimal")
+                        .action(clap::ArgAction::SetTrue)
+                        .help(
+                            "Strip query only and default properties, \
+                            suitable for saving as desired state",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("CONFIG")
+                        .long("config")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with_all([
+                            "FORCE_REFRESH",
+                            "SINCE",
+                            "RUNNING_VS_SAVED",
+                        ])
+                        .help(
+                            "Show persistent configuration of backend \
+                            (NetworkManager keyfiles) instead of running \
+                            network state, no daemon required",
+                        ),
+                ),
         )
         .subcommand(
             clap::Command::new("apply")
                 .alias("set")
                 .alias("a")
                 .about("Apply network config")
+                .arg(
+                    clap::Arg::new("STATE_FILE")
+                        .required(true)
+                        .index(1)
+                        .num_args(1..)
+                        .help(
+                            "Network state files in YAML or JSON format, \
+                            use `-` to read from stdin, http:// or \
+                            https:// URL to fetch from server. Multiple \
+                            files are merged in order, later file \
+                            overrides earlier one: interfaces merged by \
+                            name, routes and route rules appended",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("FETCH_TIMEOUT")
+                        .long("fetch-timeout")
+                        .value_name("SECONDS")
+                        .value_parser(clap::value_parser!(u32))
+                        .help(
+                            "Timeout of fetching state from URL, default \
+                            is 10 seconds",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("CHECKSUM")
+                        .long("checksum")
+                        .value_name("sha256:HEX")
+                        .help(
+                            "Verify the SHA256 checksum of state fetched \
+                            from URL. The TLS certificate of HTTPS server is \
+                            always verified against system CA store \
+                            regardless of this option",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("FORCE")
+                        .long("force")
+                        .action(clap::ArgAction::SetTrue)
+                        .help(
+                            "Apply even desired state changes nothing to \
+                            current network state",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("RESTORE_PERMANENT_MAC")
+                        .long("restore-permanent-mac")
+                        .action(clap::ArgAction::SetTrue)
+                        .help(
+                            "Restore permanent MAC address of interface \
+                            brought down or detached from bond",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("CONFIRM_TIMEOUT")
+                        .long("confirm-timeout")
+                        .value_parser(clap::value_parser!(u32))
+                        .conflicts_with("PERSISTENT_ONLY")
+                        .help(
+                            "Require confirmation within specified seconds \
+                            after applied, otherwise rollback",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("TIMEOUT")
+                        .long("timeout")
+                        .value_name("SECONDS")
+                        .value_parser(clap::value_parser!(u32))
+                        .help(
+                            "Override default timeout(30 seconds) of this \
+                            apply. Larger timeout also extends the \
+                            verification retries(every 1 second) \
+                            proportionally",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("PERSISTENT_ONLY")
+                        .long("persistent-only")
+                        .action(clap::ArgAction::SetTrue)
+                        .help(
+                            "Only store desired state as new commit without \
+                            changing running network state, useful for \
+                            pre-staging configuration before reboot",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("SKIP_PRE_QUERY")
+                        .long("skip-pre-query")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("CONFIRM_TIMEOUT")
+                        .help(
+                            "Apply without querying current network state \
+                            first, desired state should be fully defined. \
+                            Verification still runs",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("KEEP_DHCP_LEASE")
+                        .long("keep-dhcp-lease")
+                        .action(clap::ArgAction::SetTrue)
+                        .help(
+                            "Keep DHCP client of removed interface for fast \
+                            re-adding",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("PARALLEL")
+                        .long("parallel")
+                        .value_name("MAX")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .help(
+                            "Apply independent interfaces in up to MAX \
+                            concurrent sub-applies",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("PRUNE")
+                        .long("prune")
+                        .action(clap::ArgAction::SetTrue)
+                        .help(
+                            "Remove virtual interfaces not mentioned in \
+                            desired state, physical interfaces are never \
+                            removed",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("TRACE")
+                        .long("trace")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("CONFIRM_TIMEOUT")
+                        .help(
+                            "Print timeline of events exchanged between \
+                            daemon and plugins during this apply",
+                        ),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("gen-conf")
+                .alias("gc")
+                .about("Generate backend configure files without applying")
                 .arg(
                     clap::Arg::new("STATE_FILE")
                         .required(true)
                         .index(1)
                         .help("Network state file"),
+                )
+                .arg(
+                    clap::Arg::new("OUTPUT_DIR")
+                        .short('o')
+                        .long("output")
+                        .help(
+                            "Store NetworkManager keyfiles into \
+                            `system-connections` folder of specified \
+                            folder instead of printing",
+                        ),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("validate")
+                .alias("v")
+                .about("Validate network state files without applying")
+                .arg(
+                    clap::Arg::new("STATE_FILE")
+                        .required(true)
+                        .num_args(1..)
+                        .index(1)
+                        .help("Network state files"),
                 ),
         )
         .subcommand(
@@ -90,8 +401,58 @@ async fn main() -> Result<(), CliError> {
                     clap::Command::new("show")
                         .alias("s")
                         .about("Show all network commits"),
+                )
+                .subcommand(
+                    clap::Command::new("rollback")
+                        .about(
+                            "Roll back to specified commit, reverting all \
+                            later commits",
+                        )
+                        .arg(
+                            clap::Arg::new("COMMIT_ID")
+                                .required(true)
+                                .help("ID or unique ID prefix of commit"),
+                        )
+                        .arg(
+                            clap::Arg::new("DRY_RUN")
+                                .long("dry-run")
+                                .action(clap::ArgAction::SetTrue)
+                                .help(
+                                    "Only show the saved network state \
+                                    resulting from the rollback without \
+                                    applying it",
+                                ),
+                        ),
                 ),
         )
+        .subcommand(
+            clap::Command::new("diff")
+                .alias("d")
+                .arg_required_else_help(true)
+                .about("Show difference of network states")
+                .arg(
+                    clap::Arg::new("AGAINST_SAVED")
+                        .long("against-saved")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("STATE_FILES")
+                        .help(
+                            "Show properties of saved network state \
+                            differing from running network state",
+                        ),
+                )
+                .arg(
+                    clap::Arg::new("STATE_FILES")
+                        .index(1)
+                        .num_args(2)
+                        .value_names(["OLD_FILE", "NEW_FILE"])
+                        .help(
+                            "Show changes required to turn OLD_FILE into \
+                            NEW_FILE without contacting daemon, exit with \
+                            2 if they differ",
+                        ),
+                )
+                .arg(output_format_arg()),
+        )
         .subcommand(
             clap::Command::new("debug")
                 .about(
@@ -104,30 +465,34 @@ async fn main() -> Result<(), CliError> {
                         .help("YAML file path for event to sent"),
                 ),
         )
-        .get_matches();
-
-    let mut log_builder = env_logger::Builder::new();
-    log_builder.filter(Some("nipart"), log::LevelFilter::Debug);
-    log_builder.filter(None, log::LevelFilter::Debug);
-    log_builder.init();
-
-    if let Some(m) = matches.subcommand_matches("plugin") {
-        handle_plugin(m).await?;
-    } else if matches.subcommand_matches("show").is_some() {
-        handle_show().await?;
-    } else if let Some(m) = matches.subcommand_matches("log") {
-        handle_log(m).await?;
-    } else if let Some(matches) = matches.subcommand_matches("debug") {
-        handle_debug(matches).await?;
-    } else if let Some(matches) = matches.subcommand_matches("daemon") {
-        handle_daemon_cmd(matches).await?;
-    } else if let Some(matches) = matches.subcommand_matches("apply") {
-        handle_apply(matches).await?;
-    } else if let Some(matches) = matches.subcommand_matches("track") {
-        handle_track_cmd(matches).await?;
-    }
-
-    Ok(())
+        .subcommand(
+            clap::Command::new("completion")
+                .alias("completions")
+                .about("Generate shell completion script")
+                .arg(
+                    clap::Arg::new("SHELL")
+                        .required(true)
+                        .index(1)
+                        .value_parser(clap::value_parser!(clap_complete::Shell))
+                        .help("Shell type, e.g. bash, zsh or fish"),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("schema")
+                .about("Print JSON schema of network state for editors"),
+        )
+        .subcommand(
+            clap::Command::new("audit")
+                .about("Show audit log of committed network states")
+                .arg(
+                    clap::Arg::new("COUNT")
+                        .short('n')
+                        .long("count")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("10")
+                        .help("Show specified count of latest records"),
+                ),
+        )
 }
 
 async fn handle_plugin(matches: &clap::ArgMatches) -> Result<(), CliError> {
@@ -141,23 +506,230 @@ async fn handle_plugin(matches: &clap::ArgMatches) -> Result<(), CliError> {
     }
 }
 
-async fn handle_show() -> Result<(), CliError> {
+async fn handle_ping(matches: &clap::ArgMatches) -> Result<(), CliError> {
     let mut conn = NipartConnection::new().await?;
-    let replies = conn.query_net_state(NipartQueryOption::default()).await?;
-    println!("{}", serde_yaml::to_string(&replies)?);
+    let info = conn.ping_with_details().await?;
+    if matches.get_flag("VERBOSE") {
+        println!("{}", serde_yaml::to_string(&info)?);
+    } else {
+        println!("pong");
+    }
+    Ok(())
+}
+
+async fn handle_show(matches: &clap::ArgMatches) -> Result<(), CliError> {
+    let mut state = if matches.get_flag("CONFIG") {
+        NetworkState::from_running_config()?
+    } else {
+        let mut conn = NipartConnection::new().await?;
+        conn.negotiate_compression().await?;
+        if matches.get_flag("RUNNING_VS_SAVED") {
+            return handle_drift_summary(&mut conn).await;
+        }
+        let mut opt = NipartQueryOption::default();
+        opt.force_refresh = matches.get_flag("FORCE_REFRESH");
+        opt.since_commit = matches.get_one::<String>("SINCE").cloned();
+        conn.query_net_state(opt).await?
+    };
+    if matches.get_flag("MINIMAL") {
+        state = state.minimize();
+    }
+    if let Some(section) = matches.get_one::<String>("ONLY") {
+        state = filter_state_section(state, section.as_str())?;
+    }
+    println!("{}", state_to_string(&state, output_format(matches))?);
     Ok(())
 }
 
 async fn handle_apply(matches: &clap::ArgMatches) -> Result<(), CliError> {
     let mut conn = NipartConnection::new().await?;
+    conn.negotiate_compression().await?;
+    let file_paths: Vec<&String> = matches
+        .get_many::<String>("STATE_FILE")
+        .unwrap_or_default()
+        .collect();
+    if file_paths.iter().filter(|f| f.as_str() == "-").count() > 1 {
+        return Err("Cannot read stdin more than once".into());
+    }
+    if file_paths.iter().any(|f| f.as_str() == "-")
+        && matches.contains_id("CONFIRM_TIMEOUT")
+    {
+        return Err("Cannot read confirmation from stdin when state is also \
+            read from stdin"
+            .into());
+    }
+    let checksum = matches.get_one::<String>("CHECKSUM").map(|c| c.as_str());
+    if checksum.is_some()
+        && (file_paths.len() != 1 || !url::is_url(file_paths[0]))
+    {
+        return Err("The --checksum option requires single URL".into());
+    }
+    let fetch_timeout = matches
+        .get_one::<u32>("FETCH_TIMEOUT")
+        .copied()
+        .unwrap_or(url::DEFAULT_FETCH_TIMEOUT);
+    let mut state = if file_paths.len() == 1 {
+        state_from_file_or_url(file_paths[0], fetch_timeout, checksum)?
+    } else {
+        let mut states = Vec::new();
+        for file_path in file_paths {
+            states.push(state_from_file_or_url(
+                file_path,
+                fetch_timeout,
+                None,
+            )?);
+        }
+        NetworkState::merge_desired_states(states.as_slice())
+    };
+    if matches.get_flag("PRUNE") {
+        let cur_state =
+            conn.query_net_state(NipartQueryOption::default()).await?;
+        // Interfaces ignored in saved network state are never pruned
+        let mut commit_opt = NetworkCommitQueryOption::default();
+        commit_opt.count = 1;
+        commit_opt.persisted_only = true;
+        let saved_ignored: Vec<String> = conn
+            .query_commits(commit_opt)
+            .await?
+            .first()
+            .map(|c| c.state.ignored_iface_names())
+            .unwrap_or_default();
+        let keep: Vec<&str> = state
+            .interfaces
+            .iter()
+            .map(|i| i.name())
+            .chain(saved_ignored.iter().map(|n| n.as_str()))
+            .collect();
+        let prune_state = cur_state.absent_all_except(keep.as_slice());
+        for iface in prune_state.interfaces.iter() {
+            state.append_interface_data(iface.clone());
+        }
+    }
+    if let Some(timeout) = matches.get_one::<u32>("TIMEOUT") {
+        conn.set_timeout(timeout.saturating_mul(1000));
+    }
+    let mut opt = NipartApplyOption::default();
+    opt.restore_permanent_mac = matches.get_flag("RESTORE_PERMANENT_MAC");
+    opt.force = matches.get_flag("FORCE");
+    opt.persistent_only = matches.get_flag("PERSISTENT_ONLY");
+    opt.skip_pre_query = matches.get_flag("SKIP_PRE_QUERY");
+    opt.keep_dhcp_lease = matches.get_flag("KEEP_DHCP_LEASE");
+    opt.parallel = matches.get_one::<u32>("PARALLEL").copied();
+    let warnings = if let Some(confirm_timeout) =
+        matches.get_one::<u32>("CONFIRM_TIMEOUT").copied()
+    {
+        opt.confirm_timeout = Some(confirm_timeout);
+        let uuid = conn
+            .apply_net_state_with_confirm(state.clone(), opt)
+            .await?;
+        println!(
+            "Applied, type `yes` within {confirm_timeout} seconds to \
+            confirm, anything else to rollback"
+        );
+        if read_confirmation().await? {
+            conn.confirm_apply(uuid).await?
+        } else {
+            conn.rollback_apply(uuid).await?;
+            println!("Rolled back");
+            return Ok(());
+        }
+    } else if matches.get_flag("TRACE") {
+        let (warnings, trace) =
+            conn.apply_net_state_with_trace(state.clone(), opt).await?;
+        for entry in trace {
+            eprintln!("TRACE: {entry}");
+        }
+        warnings
+    } else {
+        conn.apply_net_state(state.clone(), opt).await?
+    };
+    for warning in warnings {
+        eprintln!("WARNING: {warning}");
+    }
+    println!("{}", serde_yaml::to_string(&state)?);
+    Ok(())
+}
+
+fn handle_gen_conf(matches: &clap::ArgMatches) -> Result<(), CliError> {
     let file_path = matches.get_one::<String>("STATE_FILE").unwrap();
     let state = state_from_file(file_path)?;
-    conn.apply_net_state(state.clone(), NipartApplyOption::default())
-        .await?;
-    println!("{}", serde_yaml::to_string(&state)?);
+    let confs = state.gen_conf()?;
+    if let Some(output_dir) = matches.get_one::<String>("OUTPUT_DIR") {
+        let conn_dir =
+            std::path::Path::new(output_dir).join("system-connections");
+        std::fs::create_dir_all(&conn_dir)?;
+        for (file_name, content) in
+            confs.values().flat_map(|files| files.iter())
+        {
+            let file_path = conn_dir.join(file_name);
+            // NetworkManager ignores keyfiles readable by others
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .mode(0o600)
+                .open(&file_path)?
+                .write_all(content.as_bytes())?;
+            println!("Generated {}", file_path.display());
+        }
+    } else {
+        println!("{}", serde_yaml::to_string(&confs)?);
+    }
     Ok(())
 }
 
+fn handle_validate(matches: &clap::ArgMatches) -> Result<(), CliError> {
+    let mut failed = false;
+    for file_path in
+        matches.get_many::<String>("STATE_FILE").unwrap_or_default()
+    {
+        match state_from_file(file_path)
+            .map_err(|e| e.to_string())
+            .and_then(|state| state.validate().map_err(|e| e.msg))
+        {
+            Ok(()) => println!("{file_path}: valid"),
+            Err(e) => {
+                failed = true;
+                eprintln!("{file_path}: {e}");
+            }
+        }
+    }
+    if failed {
+        Err("Invalid network state found".into())
+    } else {
+        Ok(())
+    }
+}
+
+fn handle_completion(matches: &clap::ArgMatches) {
+    if let Some(shell) = matches.get_one::<clap_complete::Shell>("SHELL") {
+        clap_complete::generate(
+            *shell,
+            &mut gen_cli(),
+            CLI_NAME,
+            &mut std::io::stdout(),
+        );
+    }
+}
+
+fn handle_schema() -> Result<(), CliError> {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&NetworkState::json_schema())?
+    );
+    Ok(())
+}
+
+async fn read_confirmation() -> Result<bool, CliError> {
+    let line = tokio::task::spawn_blocking(|| {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line).map(|_| line)
+    })
+    .await
+    .map_err(|e| CliError::from(format!("Failed to read stdin: {e}")))??;
+    Ok(line.trim() == "yes")
+}
+
 async fn handle_debug(matches: &clap::ArgMatches) -> Result<(), CliError> {
     let mut conn = NipartConnection::new().await?;
     let event_file_path = matches.get_one::<String>("EVENT").unwrap();
@@ -180,10 +752,71 @@ async fn handle_log(matches: &clap::ArgMatches) -> Result<(), CliError> {
         let log_level = NipartLogLevel::from_str(log_level_str.as_str())?;
         let replies = conn.set_log_level(log_level).await?;
         println!("{}", serde_yaml::to_string(&replies)?);
+    } else if let Some(m) = matches.subcommand_matches("rollback") {
+        if !m.get_flag("DRY_RUN") {
+            return Err("Rollback is only supported with --dry-run \
+                currently"
+                .into());
+        }
+        let commit_id = m
+            .get_one::<String>("COMMIT_ID")
+            .ok_or("COMMIT_ID undefined")?;
+        let state = preview_rollback(&mut conn, commit_id).await?;
+        println!("{}", serde_yaml::to_string(&state)?);
     }
     Ok(())
 }
 
+// Saved network state after reverting all commits later than specified
+// commit. The revert state holds `absent` for interfaces created by later
+// commits, hence cannot simply use the state stored in specified commit.
+async fn preview_rollback(
+    conn: &mut NipartConnection,
+    commit_id: &str,
+) -> Result<NetworkState, CliError> {
+    let mut opt = NetworkCommitQueryOption::default();
+    opt.persisted_only = true;
+    let commits = conn.query_commits(opt).await?;
+    gen_rollback_state(commits.as_slice(), commit_id)
+}
+
+// The `commits` should be sorted with newest first.
+fn gen_rollback_state(
+    commits: &[NetworkCommit],
+    commit_id: &str,
+) -> Result<NetworkState, CliError> {
+    let Some(head) = commits.first() else {
+        return Err("No saved network state found".into());
+    };
+    let matched: Vec<_> = commits
+        .iter()
+        .filter(|c| c.id.starts_with(commit_id))
+        .collect();
+    let target = match matched.as_slice() {
+        [c] => c,
+        [] => return Err(format!("Commit {commit_id} not found").into()),
+        _ => {
+            return Err(format!(
+                "Commit ID prefix {commit_id} is ambiguous, matching {}",
+                matched
+                    .iter()
+                    .map(|c| c.id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+            .into())
+        }
+    };
+    if target.id == head.id {
+        log::info!("Commit {} is already the latest commit", target.id);
+        return Ok(head.state.clone());
+    }
+    let revert_state = head.state.generate_revert(&target.state)?;
+    let mut state = head.state.clone();
+    state.merge_desire(&revert_state);
+    Ok(state)
+}
+
 async fn handle_daemon_cmd(matches: &clap::ArgMatches) -> Result<(), CliError> {
     let mut conn = NipartConnection::new().await?;
     if matches.subcommand_matches("stop").is_some() {
@@ -203,7 +836,373 @@ async fn handle_track_cmd(matches: &clap::ArgMatches) -> Result<(), CliError> {
     Ok(())
 }
 
+async fn handle_diff(matches: &clap::ArgMatches) -> Result<(), CliError> {
+    if let Some(file_paths) = matches.get_many::<String>("STATE_FILES") {
+        let file_paths: Vec<&String> = file_paths.collect();
+        return handle_offline_diff(
+            file_paths[0],
+            file_paths[1],
+            output_format(matches),
+        );
+    }
+    if !matches.get_flag("AGAINST_SAVED") {
+        return Err(
+            "Please specify what to compare, e.g. --against-saved".into()
+        );
+    }
+    let mut conn = NipartConnection::new().await?;
+    let Some(diff_state) = query_saved_diff(&mut conn).await? else {
+        println!("No saved network state found");
+        return Ok(());
+    };
+    if diff_state.is_empty() {
+        println!("Running network state matches saved network state");
+    } else {
+        println!("{}", state_to_string(&diff_state, output_format(matches))?);
+    }
+    Ok(())
+}
+
+// Exit with EXIT_CODE_DIFFERENT if two state files differ, so it could be
+// used as CI gate.
+fn handle_offline_diff(
+    old_file: &str,
+    new_file: &str,
+    format: &str,
+) -> Result<(), CliError> {
+    let old_state = state_from_file(old_file)?;
+    let new_state = state_from_file(new_file)?;
+    let (exit_code, output) = gen_offline_diff(&old_state, &new_state, format)?;
+    println!("{output}");
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+    Ok(())
+}
+
+// Return exit code and output of comparing two network states.
+fn gen_offline_diff(
+    old_state: &NetworkState,
+    new_state: &NetworkState,
+    format: &str,
+) -> Result<(i32, String), CliError> {
+    let diff_state = new_state.gen_full_diff(old_state)?;
+    if diff_state.is_empty() {
+        Ok((0, "No difference found".to_string()))
+    } else {
+        Ok((EXIT_CODE_DIFFERENT, state_to_string(&diff_state, format)?))
+    }
+}
+
+// Return properties of saved network state which running network state no
+// longer matches, or None if no saved network state.
+async fn query_saved_diff(
+    conn: &mut NipartConnection,
+) -> Result<Option<NetworkState>, CliError> {
+    let mut opt = NetworkCommitQueryOption::default();
+    opt.count = 1;
+    opt.persisted_only = true;
+    // Commits are sorted with newest first, each persisted commit holds the
+    // full saved state merged with all its ancestors.
+    let saved_state = match conn.query_commits(opt).await?.into_iter().next() {
+        Some(commit) => commit.state,
+        None => return Ok(None),
+    };
+    let running_state =
+        conn.query_net_state(NipartQueryOption::default()).await?;
+    Ok(Some(saved_state.gen_diff(&running_state)?))
+}
+
+// Concise summary for monitoring tools, exit with EXIT_CODE_DRIFTED if
+// running network state drifted from saved one.
+async fn handle_drift_summary(
+    conn: &mut NipartConnection,
+) -> Result<(), CliError> {
+    let Some(diff_state) = query_saved_diff(conn).await? else {
+        println!("No saved network state found");
+        return Ok(());
+    };
+    let (exit_code, summary) = gen_drift_summary(&diff_state);
+    println!("{summary}");
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+    Ok(())
+}
+
+// Return exit code and summary of drift holding by specified diff state.
+fn gen_drift_summary(diff_state: &NetworkState) -> (i32, String) {
+    if diff_state.is_empty() {
+        return (0, "in-sync".to_string());
+    }
+    let summary = format!(
+        "drifted\ninterfaces: {}\nroutes: {}\nroute-rules: {}\ndns: {}",
+        diff_state.interfaces.to_vec().len(),
+        diff_state.routes.config.as_ref().map(Vec::len).unwrap_or(0),
+        diff_state.rules.config.as_ref().map(Vec::len).unwrap_or(0),
+        usize::from(diff_state.dns.is_some())
+    );
+    (EXIT_CODE_DRIFTED, summary)
+}
+
 fn read_event_from_file(file_path: &str) -> Result<NipartEvent, CliError> {
     let fd = std::fs::File::open(file_path)?;
     Ok(serde_yaml::from_reader(fd)?)
 }
+
+fn handle_audit(matches: &clap::ArgMatches) -> Result<(), CliError> {
+    let count = matches.get_one::<usize>("COUNT").copied().unwrap_or(10);
+    let content =
+        match std::fs::read_to_string(NetworkAuditRecord::DEFAULT_PATH) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                println!("No audit record found");
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+    let lines: Vec<&str> =
+        content.lines().filter(|l| !l.trim().is_empty()).collect();
+    let mut records: Vec<NetworkAuditRecord> = Vec::new();
+    for line in &lines[lines.len().saturating_sub(count)..] {
+        records.push(serde_json::from_str(line)?);
+    }
+    println!("{}", serde_yaml::to_string(&records)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use nipart::{NetworkCommit, NetworkState};
+
+    use super::{
+        gen_cli, gen_drift_summary, gen_offline_diff, gen_rollback_state,
+        output_format, CLI_NAME, EXIT_CODE_DIFFERENT, EXIT_CODE_DRIFTED,
+    };
+
+    #[test]
+    fn test_gen_bash_completion() {
+        let mut output: Vec<u8> = Vec::new();
+        clap_complete::generate(
+            clap_complete::Shell::Bash,
+            &mut gen_cli(),
+            CLI_NAME,
+            &mut output,
+        );
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(!output.is_empty());
+        assert!(output.contains("apply"));
+    }
+
+    #[test]
+    fn test_completions_cover_all_subcommands() {
+        let matches = gen_cli()
+            .try_get_matches_from([CLI_NAME, "completions", "fish"])
+            .unwrap();
+        let sub_matches = matches.subcommand_matches("completion").unwrap();
+        assert_eq!(
+            sub_matches.get_one::<clap_complete::Shell>("SHELL"),
+            Some(&clap_complete::Shell::Fish)
+        );
+
+        let subcommands: Vec<String> = gen_cli()
+            .get_subcommands()
+            .map(|c| c.get_name().to_string())
+            .collect();
+        for shell in [
+            clap_complete::Shell::Bash,
+            clap_complete::Shell::Zsh,
+            clap_complete::Shell::Fish,
+        ] {
+            let mut output: Vec<u8> = Vec::new();
+            clap_complete::generate(
+                shell,
+                &mut gen_cli(),
+                CLI_NAME,
+                &mut output,
+            );
+            let output = String::from_utf8(output).unwrap();
+            for subcommand in subcommands.iter() {
+                assert!(
+                    output.contains(subcommand.as_str()),
+                    "{shell} completion missing {subcommand}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_output_format_arg() {
+        for (args, format) in [
+            (vec!["show"], "yaml"),
+            (vec!["show", "-o", "json"], "json"),
+            (vec!["show", "--output", "yaml"], "yaml"),
+            (vec!["diff", "--against-saved", "-o", "json"], "json"),
+        ] {
+            let matches = gen_cli()
+                .try_get_matches_from([CLI_NAME].into_iter().chain(args))
+                .unwrap();
+            let (_, sub_matches) = matches.subcommand().unwrap();
+            assert_eq!(output_format(sub_matches), format);
+        }
+        assert!(gen_cli()
+            .try_get_matches_from([CLI_NAME, "show", "-o", "xml"])
+            .is_err());
+    }
+
+    fn gen_saved_state() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 1500",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_drift_summary_in_sync() {
+        let saved_state = gen_saved_state();
+        let diff_state = saved_state.gen_diff(&gen_saved_state()).unwrap();
+
+        let (exit_code, summary) = gen_drift_summary(&diff_state);
+
+        assert_eq!(exit_code, 0);
+        assert_eq!(summary, "in-sync");
+    }
+
+    #[test]
+    fn test_drift_summary_drifted() {
+        let saved_state = gen_saved_state();
+        let running_state = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 9000",
+        )
+        .unwrap();
+        let diff_state = saved_state.gen_diff(&running_state).unwrap();
+
+        let (exit_code, summary) = gen_drift_summary(&diff_state);
+
+        assert_eq!(exit_code, EXIT_CODE_DRIFTED);
+        assert!(summary.starts_with("drifted\n"));
+        assert!(summary.contains("interfaces: 1\n"));
+        assert!(summary.contains("routes: 0\n"));
+    }
+
+    #[test]
+    fn test_offline_diff_exit_code() {
+        let old_state = gen_saved_state();
+        let new_state = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 1500
+            - name: dummy2
+              type: dummy
+              state: up",
+        )
+        .unwrap();
+
+        let (exit_code, output) =
+            gen_offline_diff(&old_state, &old_state, "yaml").unwrap();
+        assert_eq!(exit_code, 0);
+        assert_eq!(output, "No difference found");
+
+        let (exit_code, output) =
+            gen_offline_diff(&old_state, &new_state, "yaml").unwrap();
+        assert_eq!(exit_code, EXIT_CODE_DIFFERENT);
+        assert!(output.contains("dummy2"));
+        assert!(!output.contains("dummy1"));
+    }
+
+    fn gen_commit(id: &str, yaml: &str) -> NetworkCommit {
+        let mut commit = NetworkCommit::default();
+        commit.id = id.to_string();
+        commit.persisted = true;
+        commit.state = NetworkState::new_from_yaml(yaml).unwrap();
+        commit
+    }
+
+    // Newest first, each persisted commit holds the full saved state.
+    fn gen_commits() -> Vec<NetworkCommit> {
+        vec![
+            gen_commit(
+                "c3",
+                r"---
+                interfaces:
+                - name: dummy1
+                  type: dummy
+                  state: up
+                  mtu: 9000
+                - name: dummy2
+                  type: dummy
+                  state: up
+                - name: dummy3
+                  type: dummy
+                  state: up",
+            ),
+            gen_commit(
+                "c2",
+                r"---
+                interfaces:
+                - name: dummy1
+                  type: dummy
+                  state: up
+                  mtu: 1500
+                - name: dummy2
+                  type: dummy
+                  state: up",
+            ),
+            gen_commit(
+                "c1",
+                r"---
+                interfaces:
+                - name: dummy1
+                  type: dummy
+                  state: up
+                  mtu: 1500",
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_rollback_dry_run_state() {
+        let commits = gen_commits();
+
+        let state = gen_rollback_state(commits.as_slice(), "c1").unwrap();
+
+        let ifaces = state.interfaces.to_vec();
+        assert_eq!(ifaces.len(), 1);
+        assert_eq!(ifaces[0].name(), "dummy1");
+        assert_eq!(ifaces[0].base_iface().mtu, Some(1500));
+        // Rolling back does not touch the stored commits
+        assert_eq!(commits[0].state.interfaces.to_vec().len(), 3);
+    }
+
+    #[test]
+    fn test_rollback_dry_run_to_latest_commit() {
+        let commits = gen_commits();
+
+        let state = gen_rollback_state(commits.as_slice(), "c3").unwrap();
+
+        assert_eq!(state, commits[0].state);
+    }
+
+    #[test]
+    fn test_rollback_dry_run_unknown_commit() {
+        let commits = gen_commits();
+
+        assert!(gen_rollback_state(commits.as_slice(), "c9").is_err());
+        // Prefix matching all commits is ambiguous
+        assert!(gen_rollback_state(commits.as_slice(), "c").is_err());
+    }
+}
@@ -4,19 +4,264 @@ use std::io::Read;
 
 use nipart::NetworkState;
 
-use crate::error::CliError;
+use crate::{
+    error::CliError,
+    url::{fetch_url, is_url, DEFAULT_FETCH_TIMEOUT},
+};
 
+// The `file_path` could also be http:// or https:// URL.
 pub(crate) fn state_from_file(
     file_path: &str,
 ) -> Result<NetworkState, CliError> {
-    let mut content = String::new();
+    state_from_file_or_url(file_path, DEFAULT_FETCH_TIMEOUT, None)
+}
+
+pub(crate) fn state_from_file_or_url(
+    file_path: &str,
+    fetch_timeout: u32,
+    checksum: Option<&str>,
+) -> Result<NetworkState, CliError> {
     if file_path == "-" {
-        std::io::stdin().read_to_string(&mut content)?;
+        state_from_reader(std::io::stdin(), "stdin")
+    } else if is_url(file_path) {
+        let content = fetch_url(file_path, fetch_timeout, checksum)?;
+        state_from_reader(content.as_bytes(), file_path)
     } else {
-        std::fs::File::open(file_path)?.read_to_string(&mut content)?;
-    };
+        state_from_reader(std::fs::File::open(file_path)?, file_path)
+    }
+}
+
+// The `source` is only used in error message.
+fn state_from_reader<R: Read>(
+    mut reader: R,
+    source: &str,
+) -> Result<NetworkState, CliError> {
+    let mut content = String::new();
+    reader.read_to_string(&mut content)?;
     // Replace non-breaking space '\u{A0}'  to normal space
     let content = content.replace('\u{A0}', " ");
 
-    Ok(NetworkState::new_from_yaml(&content)?)
+    // JSON is valid YAML, but JSON parser provides better error message,
+    // hence try JSON first and fall back to YAML.
+    let json_err = match NetworkState::new_from_json(&content) {
+        Ok(state) => return Ok(state),
+        Err(e) => e,
+    };
+    match NetworkState::new_from_yaml(&content) {
+        Ok(state) => Ok(state),
+        Err(yaml_err) => {
+            // Only show JSON error when content looks like JSON
+            if content.trim_start().starts_with('{') {
+                Err(format!(
+                    "Failed to parse network state from {source} as \
+                    JSON: {}",
+                    json_err.msg
+                )
+                .into())
+            } else {
+                Err(format!(
+                    "Failed to parse network state from {source} as \
+                    neither JSON nor YAML: {}",
+                    yaml_err.msg
+                )
+                .into())
+            }
+        }
+    }
+}
+
+// Only keep specified section of network state
+pub(crate) fn filter_state_section(
+    state: NetworkState,
+    section: &str,
+) -> Result<NetworkState, CliError> {
+    let mut ret = NetworkState::new();
+    match section {
+        "interfaces" => ret.interfaces = state.interfaces,
+        "routes" => ret.routes = state.routes,
+        "dns" => ret.dns = state.dns,
+        "route-rules" => ret.rules = state.rules,
+        _ => {
+            return Err(
+                format!("Unknown network state section {section}").into()
+            )
+        }
+    }
+    Ok(ret)
+}
+
+// Secrets are always hidden regardless of output format.
+pub(crate) fn state_to_string(
+    state: &NetworkState,
+    format: &str,
+) -> Result<String, CliError> {
+    let mut state = state.clone();
+    state.hide_secrets();
+    match format {
+        "json" => Ok(serde_json::to_string_pretty(&state)?),
+        "yaml" => Ok(serde_yaml::to_string(&state)?),
+        _ => Err(format!("Unknown output format {format}").into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nipart::NetworkState;
+
+    use super::{filter_state_section, state_from_reader, state_to_string};
+
+    const SECTIONS: [&str; 4] = ["interfaces", "routes", "dns", "route-rules"];
+
+    #[test]
+    fn test_state_from_stdin_yaml() {
+        let stdin = b"---\ninterfaces:\n- name: dummy1\n  type: dummy\n";
+
+        let state = state_from_reader(stdin.as_slice(), "stdin").unwrap();
+
+        assert!(state.interfaces.kernel_ifaces.contains_key("dummy1"));
+    }
+
+    #[test]
+    fn test_state_from_stdin_json() {
+        let stdin = br#"
+            {"interfaces": [{"name": "dummy1", "type": "dummy"}]}"#;
+
+        let state = state_from_reader(stdin.as_slice(), "stdin").unwrap();
+
+        assert!(state.interfaces.kernel_ifaces.contains_key("dummy1"));
+    }
+
+    #[test]
+    fn test_state_from_stdin_invalid_json() {
+        let stdin = br#"{"interfaces": [{"name": "dummy1""#;
+
+        let result = state_from_reader(stdin.as_slice(), "stdin");
+
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert!(e.to_string().contains("from stdin as JSON"));
+        }
+    }
+
+    #[test]
+    fn test_state_from_stdin_yaml_flow_mapping() {
+        // Looks like JSON but only valid as YAML
+        let stdin = b"{interfaces: [{name: dummy1, type: dummy}]}";
+
+        let state = state_from_reader(stdin.as_slice(), "stdin").unwrap();
+
+        assert!(state.interfaces.kernel_ifaces.contains_key("dummy1"));
+    }
+
+    #[test]
+    fn test_state_from_stdin_neither_json_nor_yaml() {
+        let stdin = b"interfaces:\n- name: dummy1\n  type: [dummy";
+
+        let result = state_from_reader(stdin.as_slice(), "stdin");
+
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert!(e
+                .to_string()
+                .contains("from stdin as neither JSON nor YAML"));
+        }
+    }
+
+    fn gen_state() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+            routes:
+              config:
+              - destination: 198.51.100.0/24
+                next-hop-interface: dummy1
+            dns-resolver:
+              config:
+                server:
+                - 192.0.2.1
+            route-rules:
+              config:
+              - ip-to: 192.0.2.0/24
+                route-table: 200",
+        )
+        .unwrap()
+    }
+
+    // Sections holding data, in the order of `SECTIONS`
+    fn non_empty_sections(state: &NetworkState) -> Vec<&'static str> {
+        let mut ret = Vec::new();
+        if !state.interfaces.is_empty() {
+            ret.push(SECTIONS[0]);
+        }
+        if !state.routes.is_empty() {
+            ret.push(SECTIONS[1]);
+        }
+        if state.dns.as_ref().map(|d| !d.is_empty()) == Some(true) {
+            ret.push(SECTIONS[2]);
+        }
+        if !state.rules.is_empty() {
+            ret.push(SECTIONS[3]);
+        }
+        ret
+    }
+
+    #[test]
+    fn test_show_format_and_section() {
+        for format in ["yaml", "json"] {
+            for section in SECTIONS {
+                let state = filter_state_section(gen_state(), section).unwrap();
+                let output = state_to_string(&state, format).unwrap();
+                let state =
+                    state_from_reader(output.as_bytes(), "output").unwrap();
+
+                assert_eq!(non_empty_sections(&state), vec![section]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_show_empty_section() {
+        let mut state = gen_state();
+        state.routes = Default::default();
+        for format in ["yaml", "json"] {
+            let state = filter_state_section(state.clone(), "routes").unwrap();
+            let output = state_to_string(&state, format).unwrap();
+            let state = state_from_reader(output.as_bytes(), "output").unwrap();
+
+            assert!(non_empty_sections(&state).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_show_hide_secrets_in_all_formats() {
+        let secret = "2001:db8:1::3";
+        let state = NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv6:
+                enabled: true
+                addr-gen-mode: stable-privacy
+                stable-secret: {secret}"
+        ))
+        .unwrap();
+        for format in ["yaml", "json"] {
+            let output = state_to_string(&state, format).unwrap();
+
+            assert!(!output.contains(secret));
+        }
+        let output = state_to_string(&state, "json").unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&output).is_ok());
+    }
+
+    #[test]
+    fn test_show_invalid_format_and_section() {
+        assert!(filter_state_section(gen_state(), "hostname").is_err());
+        assert!(state_to_string(&gen_state(), "xml").is_err());
+    }
 }
@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::error::CliError;
+
+const CURL_BIN: &str = "curl";
+const SHA256SUM_BIN: &str = "sha256sum";
+const SHA256_PREFIX: &str = "sha256:";
+
+pub(crate) const DEFAULT_FETCH_TIMEOUT: u32 = 10;
+
+// Only explicit http:// and https:// are treated as URL, everything else is
+// local file path.
+pub(crate) fn is_url(path: &str) -> bool {
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+// Allowed protocols of URL and its redirections. HTTPS URL is never
+// redirected to plain HTTP, so content is always protected by TLS.
+fn curl_proto(url: &str) -> &'static str {
+    if url.starts_with("https://") {
+        "=https"
+    } else {
+        "=http,https"
+    }
+}
+
+// Fetch content of URL using curl. The TLS certificate of HTTPS server is
+// always verified against system CA store, HTTPS URL is only allowed to
+// redirect to HTTPS, HTTP URL is only allowed to redirect to HTTP(S).
+pub(crate) fn fetch_url(
+    url: &str,
+    timeout: u32,
+    checksum: Option<&str>,
+) -> Result<String, CliError> {
+    log::debug!("Fetching {url} with timeout {timeout} seconds");
+    let output = Command::new(CURL_BIN)
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--location",
+            "--proto",
+            curl_proto(url),
+            "--proto-redir",
+            curl_proto(url),
+            "--max-time",
+            timeout.to_string().as_str(),
+            "--",
+            url,
+        ])
+        .output()
+        .map_err(|e| format!("Failed to execute {CURL_BIN}: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to fetch {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    if let Some(checksum) = checksum {
+        verify_sha256(url, &output.stdout, checksum)?;
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|e| format!("Content of {url} is not valid UTF-8: {e}").into())
+}
+
+fn verify_sha256(
+    url: &str,
+    content: &[u8],
+    checksum: &str,
+) -> Result<(), CliError> {
+    let Some(expected) = checksum.strip_prefix(SHA256_PREFIX) else {
+        return Err(format!(
+            "Invalid checksum {checksum}, should be in the format of \
+            {SHA256_PREFIX}<hex>"
+        )
+        .into());
+    };
+    let mut child = Command::new(SHA256SUM_BIN)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to execute {SHA256SUM_BIN}: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(content)?;
+    }
+    let output = child.wait_with_output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let Some(actual) = stdout.split_whitespace().next() else {
+        return Err(format!("Got empty output from {SHA256SUM_BIN}").into());
+    };
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(format!(
+            "Checksum mismatch for {url}: expecting {SHA256_PREFIX}{expected}, \
+            got {SHA256_PREFIX}{actual}"
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    use super::{curl_proto, fetch_url, CURL_BIN, SHA256SUM_BIN};
+
+    const TEST_STATE: &str =
+        "interfaces:\n- name: dummy1\n  type: dummy\n  state: up\n";
+    const TEST_STATE_SHA256: &str = "sha256:\
+        5db29904fb825a62577e9c520955909d6264f0111b7c0d85ee89b8ae4f8b535d";
+
+    // Reply single HTTP request with specified response, return the URL
+    fn start_mock_http_server(response: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut reader = BufReader::new(&stream);
+                let mut line = String::new();
+                // Request headers end with empty line
+                while reader.read_line(&mut line).unwrap_or_default() > 2 {
+                    line.clear();
+                }
+                stream.write_all(response.as_bytes()).ok();
+            }
+        });
+        format!("http://{addr}/state.yml")
+    }
+
+    // The curl and sha256sum are runtime dependencies documented in README,
+    // skip the test if not installed.
+    fn missing_bins(bins: &[&str]) -> bool {
+        for bin in bins {
+            if std::process::Command::new(bin)
+                .arg("--version")
+                .output()
+                .is_err()
+            {
+                eprintln!("Skipping test as {bin} is not installed");
+                return true;
+            }
+        }
+        false
+    }
+
+    fn http_response(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\n\
+            Connection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    #[test]
+    fn test_fetch_url() {
+        if missing_bins(&[CURL_BIN]) {
+            return;
+        }
+        let url =
+            start_mock_http_server(http_response("200 OK", "", TEST_STATE));
+
+        assert_eq!(fetch_url(&url, 5, None).unwrap(), TEST_STATE);
+    }
+
+    #[test]
+    fn test_fetch_url_with_checksum() {
+        if missing_bins(&[CURL_BIN, SHA256SUM_BIN]) {
+            return;
+        }
+        let url =
+            start_mock_http_server(http_response("200 OK", "", TEST_STATE));
+
+        assert_eq!(
+            fetch_url(&url, 5, Some(TEST_STATE_SHA256)).unwrap(),
+            TEST_STATE
+        );
+    }
+
+    #[test]
+    fn test_fetch_url_checksum_mismatch() {
+        if missing_bins(&[CURL_BIN, SHA256SUM_BIN]) {
+            return;
+        }
+        let url = start_mock_http_server(http_response(
+            "200 OK",
+            "",
+            "interfaces: []\n",
+        ));
+
+        assert!(fetch_url(&url, 5, Some(TEST_STATE_SHA256)).is_err());
+    }
+
+    #[test]
+    fn test_fetch_url_http_error() {
+        if missing_bins(&[CURL_BIN]) {
+            return;
+        }
+        let url =
+            start_mock_http_server(http_response("404 Not Found", "", ""));
+
+        assert!(fetch_url(&url, 5, None).is_err());
+    }
+
+    #[test]
+    fn test_fetch_url_refuse_redirect_to_non_http() {
+        if missing_bins(&[CURL_BIN]) {
+            return;
+        }
+        let url = start_mock_http_server(http_response(
+            "302 Found",
+            "Location: file:///etc/passwd\r\n",
+            "",
+        ));
+
+        assert!(fetch_url(&url, 5, None).is_err());
+    }
+
+    #[test]
+    fn test_https_url_only_redirect_to_https() {
+        assert_eq!(curl_proto("https://192.0.2.1/state.yml"), "=https");
+        assert_eq!(curl_proto("http://192.0.2.1/state.yml"), "=http,https");
+    }
+}
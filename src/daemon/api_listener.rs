@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::{Arc, Mutex};
 
 use nipart::{
@@ -79,9 +79,23 @@ async fn handle_client(
 ) {
     let (switch_to_api_tx, mut switch_to_api_rx) =
         tokio::sync::mpsc::channel(MPSC_CHANNLE_SIZE);
+    // Apply actions waiting confirmation from this client
+    let mut pending_confirms: HashSet<u128> = HashSet::new();
     loop {
         tokio::select! {
-            Ok(mut event) = np_conn.recv::<NipartEvent>() => {
+            result = np_conn.recv::<NipartEvent>() => {
+                let mut event = match result {
+                    Ok(e) => e,
+                    Err(e) if e.kind == ErrorKind::IpcClosed => {
+                        rollback_pending_confirms(
+                            &pending_confirms, &use_to_switch).await;
+                        break;
+                    }
+                    Err(e) => {
+                        log::debug!("handle_client(): {e}");
+                        continue;
+                    }
+                };
                 log::trace!("handle_client(): from user {event:?}");
                 if event.plugin != NipartPluginEvent::None {
                     log::debug!(
@@ -105,6 +119,14 @@ async fn handle_client(
                     continue;
                 }
 
+                if matches!(
+                    event.user,
+                    NipartUserEvent::ConfirmApply
+                        | NipartUserEvent::RollbackApply
+                ) {
+                    pending_confirms.remove(&event.uuid);
+                }
+
                 // Redirect user request to Commander
                 event.dst = NipartEventAddress::Commander;
                 if let Ok(mut queue) =  tracking_queue.lock() {
@@ -120,12 +142,17 @@ async fn handle_client(
             }
             Some(event) = switch_to_api_rx.recv() => {
                 log::trace!("handle_client(): to user {event:?}");
+                if event.user == NipartUserEvent::ApplyNetStateWaitConfirm {
+                    pending_confirms.insert(event.uuid);
+                }
                 if let Err(e) = np_conn.send(&event).await {
                     if e.kind == ErrorKind::IpcClosed {
                         log::info!(
                             "Discard event {} {:?} as user disconnected",
                             event.uuid, event.user
                         );
+                        rollback_pending_confirms(
+                            &pending_confirms, &use_to_switch).await;
                     } else {
                         log::warn!(
                             "Failed to send reply to user {event:?}: {e}"
@@ -138,6 +165,31 @@ async fn handle_client(
     }
 }
 
+// Client disconnected without confirming apply actions, request commander
+// to rollback.
+async fn rollback_pending_confirms(
+    pending_confirms: &HashSet<u128>,
+    use_to_switch: &Sender<NipartEvent>,
+) {
+    for uuid in pending_confirms {
+        log::info!(
+            "Client disconnected before confirming apply action {uuid}, \
+            requesting rollback"
+        );
+        let event = NipartEvent::new_with_uuid(
+            *uuid,
+            NipartUserEvent::RollbackApply,
+            NipartPluginEvent::None,
+            NipartEventAddress::User,
+            NipartEventAddress::Commander,
+            nipart::DEFAULT_TIMEOUT,
+        );
+        if let Err(e) = use_to_switch.send(event).await {
+            log::warn!("Failed to request rollback of {uuid}: {e}");
+        }
+    }
+}
+
 fn clean_up_tracking_queue(
     tracking_queue: Arc<Mutex<BTreeMap<u128, Sender<NipartEvent>>>>,
 ) {
@@ -192,7 +244,10 @@ async fn send_reply_to_client(
         if let Err(e) = tx.send(event.clone()).await {
             log::warn!("Failed to reply event to user {e}");
         }
-        if event.is_log() {
+        // Keep tracking as more replies will come
+        if event.is_log()
+            || event.user == NipartUserEvent::ApplyNetStateWaitConfirm
+        {
             if let Ok(mut queue) = tracking_queue.lock() {
                 queue.insert(event.uuid, tx);
             }
@@ -201,3 +256,122 @@ async fn send_reply_to_client(
         log::debug!("Discarding event for disconnected user {event:?}");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nipart::{NetworkState, NipartApplyOption, DEFAULT_TIMEOUT};
+
+    use super::*;
+
+    async fn start_client(
+        name: &str,
+    ) -> (
+        NipartConnection,
+        Arc<Mutex<BTreeMap<u128, Sender<NipartEvent>>>>,
+        Receiver<NipartEvent>,
+        tokio::task::JoinHandle<()>,
+    ) {
+        let name = format!("{name}_{}", std::process::id());
+        let listener = NipartConnectionListener::new_abstract(&name).unwrap();
+        let client = NipartConnection::new_abstract(&name).unwrap();
+        let np_conn = listener.accept().await.unwrap();
+        let tracking_queue = Arc::new(Mutex::new(BTreeMap::new()));
+        let (api_to_switch_tx, api_to_switch_rx) =
+            tokio::sync::mpsc::channel(MPSC_CHANNLE_SIZE);
+        let handle = tokio::spawn(handle_client(
+            tracking_queue.clone(),
+            api_to_switch_tx,
+            np_conn,
+        ));
+        (client, tracking_queue, api_to_switch_rx, handle)
+    }
+
+    // Send apply request and wait daemon asking for confirmation
+    async fn apply_and_wait_confirm(
+        client: &mut NipartConnection,
+        tracking_queue: Arc<Mutex<BTreeMap<u128, Sender<NipartEvent>>>>,
+        api_to_switch_rx: &mut Receiver<NipartEvent>,
+    ) -> u128 {
+        let request = NipartEvent::new(
+            NipartUserEvent::ApplyNetState(
+                Box::new(NetworkState::new()),
+                NipartApplyOption::default(),
+            ),
+            NipartPluginEvent::None,
+            NipartEventAddress::User,
+            NipartEventAddress::Daemon,
+            DEFAULT_TIMEOUT,
+        );
+        client.send(&request).await.unwrap();
+        let event = api_to_switch_rx.recv().await.unwrap();
+        assert_eq!(event.uuid, request.uuid);
+
+        send_reply_to_client(
+            tracking_queue,
+            NipartEvent::new_with_uuid(
+                request.uuid,
+                NipartUserEvent::ApplyNetStateWaitConfirm,
+                NipartPluginEvent::None,
+                NipartEventAddress::Daemon,
+                NipartEventAddress::User,
+                DEFAULT_TIMEOUT,
+            ),
+        )
+        .await;
+        let reply = client.recv::<NipartEvent>().await.unwrap();
+        assert_eq!(reply.user, NipartUserEvent::ApplyNetStateWaitConfirm);
+        request.uuid
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_before_confirm_request_rollback() {
+        let (mut client, tracking_queue, mut api_to_switch_rx, handle) =
+            start_client("nipart_test_disconnect_before_confirm").await;
+        let uuid = apply_and_wait_confirm(
+            &mut client,
+            tracking_queue,
+            &mut api_to_switch_rx,
+        )
+        .await;
+
+        drop(client);
+
+        let event = api_to_switch_rx.recv().await.unwrap();
+        assert_eq!(event.uuid, uuid);
+        assert_eq!(event.user, NipartUserEvent::RollbackApply);
+        assert_eq!(event.dst, NipartEventAddress::Commander);
+        handle.await.unwrap();
+        assert!(api_to_switch_rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_after_confirm_no_rollback() {
+        let (mut client, tracking_queue, mut api_to_switch_rx, handle) =
+            start_client("nipart_test_disconnect_after_confirm").await;
+        let uuid = apply_and_wait_confirm(
+            &mut client,
+            tracking_queue,
+            &mut api_to_switch_rx,
+        )
+        .await;
+
+        client
+            .send(&NipartEvent::new_with_uuid(
+                uuid,
+                NipartUserEvent::ConfirmApply,
+                NipartPluginEvent::None,
+                NipartEventAddress::User,
+                NipartEventAddress::Daemon,
+                DEFAULT_TIMEOUT,
+            ))
+            .await
+            .unwrap();
+        let event = api_to_switch_rx.recv().await.unwrap();
+        assert_eq!(event.user, NipartUserEvent::ConfirmApply);
+
+        drop(client);
+
+        handle.await.unwrap();
+        assert!(api_to_switch_rx.recv().await.is_none());
+    }
+}
@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use nipart::{NetworkState, NipartQueryOption};
+
+// Even without any change notification from monitor plugin, the cached
+// network state should not be older than this.
+const NET_STATE_CACHE_MAX_AGE: Duration = Duration::from_secs(30);
+
+const HOSTNAME_PATH: &str = "/proc/sys/kernel/hostname";
+
+// Monitor plugin only reports link, address and route changes. DNS and OVS
+// database changes are detected by modification time of these files.
+const OUT_OF_BAND_FILES: [&str; 3] = [
+    "/etc/resolv.conf",
+    "/etc/openvswitch/conf.db",
+    "/var/lib/openvswitch/conf.db",
+];
+
+// Snapshot of network configurations not covered by monitor plugin. Cached
+// network state is discarded when snapshot taken before query mismatches
+// current one.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct NetStateFingerprint {
+    hostname: Option<String>,
+    files: Vec<Option<SystemTime>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct NetStateCache {
+    // Increased on every invalidation, query started before invalidation
+    // should not be stored into cache.
+    generation: u64,
+    entries: Vec<(
+        NipartQueryOption,
+        NetworkState,
+        Instant,
+        NetStateFingerprint,
+    )>,
+    hostname_path: PathBuf,
+    out_of_band_files: Vec<PathBuf>,
+}
+
+impl Default for NetStateCache {
+    fn default() -> Self {
+        Self {
+            generation: 0,
+            entries: Vec::new(),
+            hostname_path: PathBuf::from(HOSTNAME_PATH),
+            out_of_band_files: OUT_OF_BAND_FILES
+                .iter()
+                .map(PathBuf::from)
+                .collect(),
+        }
+    }
+}
+
+impl NetStateCache {
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub(crate) fn fingerprint(&self) -> NetStateFingerprint {
+        NetStateFingerprint {
+            hostname: std::fs::read_to_string(&self.hostname_path)
+                .ok()
+                .map(|s| s.trim().to_string()),
+            files: self
+                .out_of_band_files
+                .iter()
+                .map(|f| std::fs::metadata(f).and_then(|m| m.modified()).ok())
+                .collect(),
+        }
+    }
+
+    pub(crate) fn get(&self, opt: &NipartQueryOption) -> Option<NetworkState> {
+        let opt = cache_key(opt);
+        let (_, state, _, fingerprint) =
+            self.entries.iter().find(|(o, _, time, _)| {
+                o == &opt && time.elapsed() < NET_STATE_CACHE_MAX_AGE
+            })?;
+        if fingerprint != &self.fingerprint() {
+            log::debug!(
+                "Cached network state is outdated due to hostname, DNS or \
+                OVS database change"
+            );
+            None
+        } else {
+            Some(state.clone())
+        }
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        opt: &NipartQueryOption,
+        state: NetworkState,
+        generation: u64,
+        fingerprint: NetStateFingerprint,
+    ) {
+        if generation != self.generation {
+            log::debug!(
+                "Discard outdated query result from cache generation \
+                {generation}, current {}",
+                self.generation
+            );
+            return;
+        }
+        let opt = cache_key(opt);
+        self.entries.retain(|(o, _, _, _)| o != &opt);
+        self.entries.push((opt, state, Instant::now(), fingerprint));
+    }
+
+    pub(crate) fn invalidate(&mut self) {
+        if !self.entries.is_empty() {
+            log::debug!("Network state cache invalidated");
+        }
+        self.generation = self.generation.wrapping_add(1);
+        self.entries.clear();
+    }
+}
+
+fn cache_key(opt: &NipartQueryOption) -> NipartQueryOption {
+    let mut opt = opt.clone();
+    opt.force_refresh = false;
+    opt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TmpDir(PathBuf);
+
+    impl TmpDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir()
+                .join(format!("nipartd_cache_{name}_{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TmpDir {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    fn gen_cache(dir: &TmpDir) -> NetStateCache {
+        let hostname_path = dir.0.join("hostname");
+        let resolv_path = dir.0.join("resolv.conf");
+        std::fs::write(&hostname_path, "host-a\n").unwrap();
+        std::fs::write(&resolv_path, "nameserver 192.0.2.1\n").unwrap();
+        NetStateCache {
+            hostname_path,
+            out_of_band_files: vec![resolv_path, dir.0.join("conf.db")],
+            ..Default::default()
+        }
+    }
+
+    fn fill_cache(cache: &mut NetStateCache) -> NipartQueryOption {
+        let opt = NipartQueryOption::default();
+        let fingerprint = cache.fingerprint();
+        cache.insert(
+            &opt,
+            NetworkState::default(),
+            cache.generation(),
+            fingerprint,
+        );
+        assert!(cache.get(&opt).is_some());
+        opt
+    }
+
+    #[test]
+    fn test_cache_invalidated_by_monitor_event() {
+        let dir = TmpDir::new("monitor");
+        let mut cache = gen_cache(&dir);
+        let opt = fill_cache(&mut cache);
+
+        cache.invalidate();
+
+        assert!(cache.get(&opt).is_none());
+    }
+
+    #[test]
+    fn test_cache_discard_query_started_before_invalidation() {
+        let dir = TmpDir::new("generation");
+        let mut cache = gen_cache(&dir);
+        let opt = NipartQueryOption::default();
+        let generation = cache.generation();
+        let fingerprint = cache.fingerprint();
+
+        cache.invalidate();
+        cache.insert(&opt, NetworkState::default(), generation, fingerprint);
+
+        assert!(cache.get(&opt).is_none());
+    }
+
+    #[test]
+    fn test_cache_outdated_by_hostname_change() {
+        let dir = TmpDir::new("hostname");
+        let mut cache = gen_cache(&dir);
+        let opt = fill_cache(&mut cache);
+
+        std::fs::write(&cache.hostname_path, "host-b\n").unwrap();
+
+        assert!(cache.get(&opt).is_none());
+    }
+
+    #[test]
+    fn test_cache_outdated_by_dns_change() {
+        let dir = TmpDir::new("dns");
+        let mut cache = gen_cache(&dir);
+        let opt = fill_cache(&mut cache);
+
+        let resolv_path = cache.out_of_band_files[0].clone();
+        let file = std::fs::File::options()
+            .write(true)
+            .open(&resolv_path)
+            .unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+
+        assert!(cache.get(&opt).is_none());
+    }
+
+    #[test]
+    fn test_cache_outdated_by_ovsdb_created() {
+        let dir = TmpDir::new("ovsdb");
+        let mut cache = gen_cache(&dir);
+        let opt = fill_cache(&mut cache);
+
+        std::fs::write(&cache.out_of_band_files[1], "").unwrap();
+
+        assert!(cache.get(&opt).is_none());
+    }
+}
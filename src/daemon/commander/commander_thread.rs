@@ -1,12 +1,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use nipart::{
-    NipartError, NipartEvent, NipartEventAddress, NipartLogEntry,
+    NetworkState, NipartError, NipartEvent, NipartEventAddress, NipartLogEntry,
     NipartLogLevel, NipartPluginEvent, NipartUserEvent,
 };
 use tokio::sync::mpsc::{Receiver, Sender};
 
-use super::{WorkFlow, WorkFlowQueue};
+use super::{dispatch::start_dispatch_scripts, WorkFlow, WorkFlowQueue};
 use crate::PluginRoles;
 
 // Check the session queue every 5 seconds
@@ -152,6 +152,16 @@ async fn process_plugin_event(
                 process_workflow_queue(workflow_queue, commander_to_switch)
                     .await?;
             }
+            NipartPluginEvent::RunDispatchScripts(scripts) => {
+                start_dispatch_scripts(
+                    event.uuid,
+                    *scripts,
+                    commander_to_switch.clone(),
+                );
+            }
+            NipartPluginEvent::NetStateChanged => {
+                workflow_queue.net_state_cache.invalidate();
+            }
             _ => {
                 log::error!("Unknown user event {event:?}");
             }
@@ -166,6 +176,20 @@ async fn process_user_event(
     commander_to_switch: &mut Sender<NipartEvent>,
     plugin_roles: &PluginRoles,
 ) -> Result<(), NipartError> {
+    if matches!(
+        event.user,
+        NipartUserEvent::ConfirmApply | NipartUserEvent::RollbackApply
+    ) {
+        let uuid = event.uuid;
+        if let Err(e) = workflow_queue.add_apply_confirm(event) {
+            let mut error_event: NipartEvent = e.into();
+            error_event.uuid = uuid;
+            commander_to_switch.send(error_event).await?;
+        }
+        return process_workflow_queue(workflow_queue, commander_to_switch)
+            .await;
+    }
+
     let all_plugins_count = plugin_roles.all_plugin_count();
     let (workflow, share_data) = match event.user {
         NipartUserEvent::QueryPluginInfo => WorkFlow::new_query_plugin_info(
@@ -173,6 +197,9 @@ async fn process_user_event(
             all_plugins_count,
             event.timeout,
         ),
+        NipartUserEvent::Ping => {
+            WorkFlow::new_ping(event.uuid, all_plugins_count, event.timeout)
+        }
         NipartUserEvent::QueryLogLevel => WorkFlow::new_query_log_level(
             event.uuid,
             all_plugins_count,
@@ -187,20 +214,49 @@ async fn process_user_event(
         NipartUserEvent::Quit => {
             WorkFlow::new_quit(event.uuid, all_plugins_count, event.timeout)
         }
-        NipartUserEvent::QueryNetState(opt) => WorkFlow::new_query_net_state(
-            opt,
-            event.uuid,
-            plugin_roles,
-            event.timeout,
-        ),
+        NipartUserEvent::QueryNetState(opt) if opt.since_commit.is_some() => {
+            WorkFlow::new_query_net_state_since_commit(
+                opt,
+                event.uuid,
+                plugin_roles,
+                event.timeout,
+            )
+        }
+        NipartUserEvent::QueryNetState(opt) => {
+            if !opt.force_refresh {
+                if let Some(state) = workflow_queue.net_state_cache.get(&opt) {
+                    return reply_cached_net_state(
+                        event.uuid,
+                        state,
+                        event.timeout,
+                        commander_to_switch,
+                    )
+                    .await;
+                }
+            }
+            WorkFlow::new_query_net_state(
+                opt,
+                event.uuid,
+                plugin_roles,
+                event.timeout,
+            )
+        }
         NipartUserEvent::ApplyNetState(des, opt) => {
-            WorkFlow::new_apply_net_state(
+            match WorkFlow::new_apply_net_state(
                 *des,
                 opt,
                 event.uuid,
                 plugin_roles,
                 event.timeout,
-            )
+            ) {
+                Ok(w) => w,
+                Err(e) => {
+                    let mut error_event: NipartEvent = e.into();
+                    error_event.uuid = event.uuid;
+                    commander_to_switch.send(error_event).await?;
+                    return Ok(());
+                }
+            }
         }
         NipartUserEvent::QueryCommits(opt) => {
             WorkFlow::new_query_commits(opt, event.uuid, event.timeout)
@@ -214,6 +270,33 @@ async fn process_user_event(
     process_workflow_queue(workflow_queue, commander_to_switch).await
 }
 
+async fn reply_cached_net_state(
+    uuid: u128,
+    state: NetworkState,
+    timeout: u32,
+    commander_to_switch: &mut Sender<NipartEvent>,
+) -> Result<(), NipartError> {
+    log_to_user(
+        uuid,
+        NipartLogLevel::Debug,
+        "Replying network state from cache".to_string(),
+        commander_to_switch,
+    )
+    .await;
+    let event = NipartEvent::new_with_uuid(
+        uuid,
+        NipartUserEvent::QueryNetStateReply(Box::new(state)),
+        NipartPluginEvent::None,
+        NipartEventAddress::Daemon,
+        NipartEventAddress::User,
+        timeout,
+    );
+    if let Err(e) = commander_to_switch.send(event).await {
+        log::error!("{e}");
+    }
+    Ok(())
+}
+
 async fn log_to_user(
     uuid: u128,
     level: NipartLogLevel,
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use nipart::{
+    ErrorKind, NipartApplyOption, NipartError, NipartEvent, NipartEventAddress,
+    NipartPluginEvent, NipartUserEvent,
+};
+
+use super::{Task, TaskKind, WorkFlow, WorkFlowQueue, WorkFlowShareData};
+
+pub(crate) fn process_apply_confirm(
+    task: &Task,
+    _share_data: &mut WorkFlowShareData,
+) -> Result<Vec<NipartEvent>, NipartError> {
+    if task
+        .replies
+        .iter()
+        .any(|r| matches!(r.user, NipartUserEvent::RollbackApply))
+    {
+        Err(NipartError::new(
+            ErrorKind::RolledBack,
+            "Rolled back as requested by user".to_string(),
+        ))
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+impl Task {
+    pub(crate) fn gen_request_wait_confirm(&self) -> NipartEvent {
+        NipartEvent::new_with_uuid(
+            self.uuid,
+            NipartUserEvent::ApplyNetStateWaitConfirm,
+            NipartPluginEvent::None,
+            NipartEventAddress::Daemon,
+            NipartEventAddress::User,
+            self.timeout,
+        )
+    }
+}
+
+impl WorkFlowQueue {
+    // Only accept confirmation for workflow which is waiting for it.
+    pub(crate) fn add_apply_confirm(
+        &mut self,
+        event: NipartEvent,
+    ) -> Result<(), NipartError> {
+        match self.workflows.get_mut(&event.uuid) {
+            Some(workflow)
+                if matches!(
+                    workflow.cur_task().map(|t| &t.kind),
+                    Some(TaskKind::WaitConfirm)
+                ) =>
+            {
+                workflow.add_reply(event);
+                Ok(())
+            }
+            _ => Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "No apply action is waiting confirmation for {}",
+                    crate::u128_to_uuid_string(event.uuid)
+                ),
+            )),
+        }
+    }
+}
+
+impl WorkFlow {
+    pub(crate) fn is_waiting_confirm(&self) -> bool {
+        matches!(
+            self.cur_task().map(|t| &t.kind),
+            Some(TaskKind::WaitConfirm)
+        )
+    }
+
+    // When failed or expired during waiting user confirmation, apply the
+    // revert of desired state as new apply action. Caller should release the
+    // locks beforehand.
+    pub(crate) fn gen_rollback_events(
+        &self,
+        share_data: &WorkFlowShareData,
+    ) -> Vec<NipartEvent> {
+        if !self.is_waiting_confirm() {
+            return Vec::new();
+        }
+        let (Some(desired_state), Some(pre_apply_state)) = (
+            share_data.desired_state.as_ref(),
+            share_data.pre_apply_state.as_ref(),
+        ) else {
+            log::error!(
+                "BUG: gen_rollback_events() got None in share data \
+                {share_data:?}"
+            );
+            return Vec::new();
+        };
+        let revert_state = match desired_state.generate_revert(pre_apply_state)
+        {
+            Ok(s) => s,
+            Err(e) => {
+                log::error!("Failed to generate revert state: {e}");
+                return Vec::new();
+            }
+        };
+        log::info!("Rolling back {self}");
+        vec![NipartEvent::new(
+            NipartUserEvent::ApplyNetState(
+                Box::new(revert_state),
+                NipartApplyOption::default(),
+            ),
+            NipartPluginEvent::None,
+            NipartEventAddress::Daemon,
+            NipartEventAddress::Commander,
+            nipart::DEFAULT_TIMEOUT,
+        )]
+    }
+}
@@ -22,7 +22,10 @@ impl WorkFlow {
             plugin_count,
             timeout,
         )];
-        let share_data = WorkFlowShareData::default();
+        let share_data = WorkFlowShareData {
+            changes_net_state: true,
+            ..Default::default()
+        };
 
         let call_backs: Vec<Option<TaskCallBackFn>> = vec![None];
 
@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use nipart::{
+    ErrorKind, MergedNetworkState, NipartDispatchPhase, NipartDispatchScript,
+    NipartError, NipartEvent, NipartEventAddress, NipartLogEntry,
+    NipartLogLevel, NipartPluginEvent, NipartUserEvent,
+};
+use tokio::sync::mpsc::Sender;
+
+use super::{Task, WorkFlowShareData};
+
+// Failure of pre-apply dispatch script aborts the apply
+pub(crate) fn run_dispatch_scripts(
+    task: &Task,
+    _share_data: &mut WorkFlowShareData,
+) -> Result<Vec<NipartEvent>, NipartError> {
+    for reply in task.replies.as_slice() {
+        if let NipartUserEvent::Error(e) = &reply.user {
+            return Err(e.clone());
+        }
+    }
+    Ok(Vec::new())
+}
+
+// The network state is already applied and verified, failure of post-apply
+// dispatch script should not trigger rollback, only warn user instead.
+pub(crate) fn run_post_apply_dispatch_scripts(
+    task: &Task,
+    _share_data: &mut WorkFlowShareData,
+) -> Result<Vec<NipartEvent>, NipartError> {
+    let mut events = Vec::new();
+    for reply in task.replies.as_slice() {
+        if let NipartUserEvent::Error(e) = &reply.user {
+            log::warn!("{e}");
+            events.push(
+                NipartLogEntry::new(NipartLogLevel::Warn, e.to_string())
+                    .to_event(task.uuid, NipartEventAddress::Commander),
+            );
+        }
+    }
+    Ok(events)
+}
+
+impl Task {
+    pub(crate) fn gen_request_run_dispatch_scripts(
+        &self,
+        phase: NipartDispatchPhase,
+        share_data: &WorkFlowShareData,
+    ) -> Vec<NipartEvent> {
+        let scripts = match share_data.merged_state.as_ref() {
+            Some(s) => s.get_dispatch_scripts(phase),
+            None => {
+                log::error!(
+                    "BUG: gen_request_run_dispatch_scripts() got None for \
+                    merge_state in share data {share_data:?}"
+                );
+                MergedNetworkState::default().get_dispatch_scripts(phase)
+            }
+        };
+        // The daemon is the one executing scripts, use `Daemon` as source to
+        // avoid event discarded by switch as dead loop.
+        vec![NipartEvent::new_with_uuid(
+            self.uuid,
+            NipartUserEvent::None,
+            NipartPluginEvent::RunDispatchScripts(Box::new(scripts)),
+            NipartEventAddress::Daemon,
+            NipartEventAddress::Commander,
+            self.timeout,
+        )]
+    }
+}
+
+pub(crate) fn start_dispatch_scripts(
+    uuid: u128,
+    scripts: Vec<NipartDispatchScript>,
+    sender: Sender<NipartEvent>,
+) {
+    tokio::spawn(async move {
+        let mut reply = match exec_dispatch_scripts(scripts.as_slice()).await {
+            Ok(()) => NipartEvent::new(
+                NipartUserEvent::None,
+                NipartPluginEvent::RunDispatchScriptsReply,
+                NipartEventAddress::Daemon,
+                NipartEventAddress::Commander,
+                nipart::DEFAULT_TIMEOUT,
+            ),
+            Err(e) => NipartEvent::new(
+                NipartUserEvent::Error(e),
+                NipartPluginEvent::RunDispatchScriptsReply,
+                NipartEventAddress::Daemon,
+                NipartEventAddress::Commander,
+                nipart::DEFAULT_TIMEOUT,
+            ),
+        };
+        reply.uuid = uuid;
+        if let Err(e) = sender.send(reply).await {
+            log::error!("Failed to reply {e}");
+        }
+    });
+}
+
+async fn exec_dispatch_scripts(
+    scripts: &[NipartDispatchScript],
+) -> Result<(), NipartError> {
+    for script in scripts {
+        exec_dispatch_script(script).await?;
+    }
+    Ok(())
+}
+
+async fn exec_dispatch_script(
+    script: &NipartDispatchScript,
+) -> Result<(), NipartError> {
+    log::debug!("Running dispatch script of interface {}", script.iface);
+    let mut cmd = tokio::process::Command::new("bash");
+    cmd.arg("-c")
+        .arg(script.script.as_str())
+        .env("NIPART_IFACE", script.iface.as_str())
+        .kill_on_drop(true);
+
+    let output = match tokio::time::timeout(
+        std::time::Duration::from_secs(script.timeout.into()),
+        cmd.output(),
+    )
+    .await
+    {
+        Ok(Ok(o)) => o,
+        Ok(Err(e)) => {
+            return Err(NipartError::new(
+                ErrorKind::DependencyError,
+                format!(
+                    "Failed to execute dispatch script of interface {}: {e}",
+                    script.iface
+                ),
+            ));
+        }
+        Err(_) => {
+            return Err(NipartError::new(
+                ErrorKind::Timeout,
+                format!(
+                    "Dispatch script of interface {} does not finish \
+                    in {} seconds",
+                    script.iface, script.timeout
+                ),
+            ));
+        }
+    };
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let e = NipartError::new(
+            ErrorKind::PluginFailure,
+            format!(
+                "Dispatch script of interface {} failed with {}, \
+                stdout: {}, stderr: {}",
+                script.iface,
+                output.status,
+                String::from_utf8_lossy(&output.stdout).trim(),
+                String::from_utf8_lossy(&output.stderr).trim(),
+            ),
+        );
+        log::error!("{}", e);
+        Err(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nipart::{
+        ErrorKind, NipartDispatchScript, NipartError, NipartEvent,
+        NipartEventAddress, NipartLogLevel, NipartPluginEvent, NipartUserEvent,
+    };
+
+    use super::{
+        exec_dispatch_script, run_dispatch_scripts,
+        run_post_apply_dispatch_scripts,
+    };
+    use crate::commander::{Task, TaskKind, WorkFlowShareData};
+
+    const TEST_UUID: u128 = 1;
+    const TEST_TIMEOUT: u32 = 5000;
+
+    fn gen_failed_task() -> Task {
+        let mut task = Task::new(
+            TEST_UUID,
+            TaskKind::RunDispatchScripts(
+                nipart::NipartDispatchPhase::PostApply,
+            ),
+            1,
+            TEST_TIMEOUT,
+        );
+        task.add_reply(NipartEvent::new_with_uuid(
+            TEST_UUID,
+            NipartUserEvent::Error(NipartError::new(
+                ErrorKind::PluginFailure,
+                "Dispatch script of interface eth1 failed".to_string(),
+            )),
+            NipartPluginEvent::RunDispatchScriptsReply,
+            NipartEventAddress::Daemon,
+            NipartEventAddress::Commander,
+            TEST_TIMEOUT,
+        ));
+        task
+    }
+
+    #[test]
+    fn test_pre_apply_dispatch_failure_abort_apply() {
+        let result = run_dispatch_scripts(
+            &gen_failed_task(),
+            &mut WorkFlowShareData::default(),
+        );
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::PluginFailure);
+    }
+
+    #[test]
+    fn test_post_apply_dispatch_failure_only_warn() {
+        let events = run_post_apply_dispatch_scripts(
+            &gen_failed_task(),
+            &mut WorkFlowShareData::default(),
+        )
+        .unwrap();
+
+        assert_eq!(events.len(), 1);
+        if let NipartUserEvent::Log(log) = &events[0].user {
+            assert_eq!(log.level, NipartLogLevel::Warn);
+            assert!(log.message.contains("eth1"));
+        } else {
+            panic!("Expecting warning log, but got {:?}", events[0]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_script_timeout() {
+        let script = NipartDispatchScript::new(
+            "eth1".to_string(),
+            "sleep 10".to_string(),
+            1,
+        );
+
+        let result = exec_dispatch_script(&script).await;
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::Timeout);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_script_failure_captures_output() {
+        let script = NipartDispatchScript::new(
+            "eth1".to_string(),
+            "echo \"iface is $NIPART_IFACE\"; exit 1".to_string(),
+            5,
+        );
+
+        let e = exec_dispatch_script(&script).await.unwrap_err();
+
+        assert_eq!(e.kind, ErrorKind::PluginFailure);
+        assert!(e.msg.contains("iface is eth1"));
+    }
+}
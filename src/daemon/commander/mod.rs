@@ -1,14 +1,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
+mod cache;
 mod commander_thread;
 mod commit;
+mod confirm;
 mod dhcp;
+mod dispatch;
 mod log_level;
 mod plugin;
 mod state;
 mod task;
 mod workflow;
 
+pub(crate) use self::cache::{NetStateCache, NetStateFingerprint};
 pub(crate) use self::commander_thread::start_commander_thread;
 pub(crate) use self::task::{Task, TaskKind};
 pub(crate) use self::workflow::{
@@ -1,8 +1,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use nipart::{
-    NipartError, NipartEvent, NipartEventAddress, NipartPluginEvent,
-    NipartUserEvent,
+    NipartDaemonInfo, NipartError, NipartEvent, NipartEventAddress,
+    NipartPluginEvent, NipartPluginInfo, NipartUserEvent,
 };
 
 use super::{Task, TaskCallBackFn, TaskKind, WorkFlow, WorkFlowShareData};
@@ -30,6 +30,24 @@ impl WorkFlow {
         )
     }
 
+    pub(crate) fn new_ping(
+        uuid: u128,
+        plugin_count: usize,
+        timeout: u32,
+    ) -> (Self, WorkFlowShareData) {
+        let tasks = vec![Task::new(
+            uuid,
+            TaskKind::QueryPluginInfo,
+            plugin_count,
+            timeout,
+        )];
+        let share_data = WorkFlowShareData::default();
+
+        let call_backs: Vec<Option<TaskCallBackFn>> = vec![Some(ping)];
+
+        (WorkFlow::new("ping", uuid, tasks, call_backs), share_data)
+    }
+
     pub(crate) fn new_quit(
         uuid: u128,
         plugin_count: usize,
@@ -50,6 +68,17 @@ fn query_plugin_info(
     task: &Task,
     _share_data: &mut WorkFlowShareData,
 ) -> Result<Vec<NipartEvent>, NipartError> {
+    Ok(vec![NipartEvent::new_with_uuid(
+        task.uuid,
+        NipartUserEvent::QueryPluginInfoReply(collect_plugin_infos(task)),
+        NipartPluginEvent::None,
+        NipartEventAddress::Daemon,
+        NipartEventAddress::User,
+        task.timeout,
+    )])
+}
+
+fn collect_plugin_infos(task: &Task) -> Vec<NipartPluginInfo> {
     let mut plugin_infos = Vec::new();
     for reply in &task.replies {
         if let NipartPluginEvent::QueryPluginInfoReply(i) = &reply.plugin {
@@ -60,9 +89,26 @@ fn query_plugin_info(
             );
         }
     }
+    plugin_infos.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+    plugin_infos
+}
+
+fn ping(
+    task: &Task,
+    _share_data: &mut WorkFlowShareData,
+) -> Result<Vec<NipartEvent>, NipartError> {
+    let uptime_secs = crate::DAEMON_START_TIME
+        .get()
+        .map(|t| t.elapsed().as_secs())
+        .unwrap_or_default();
+    let info = NipartDaemonInfo::new(
+        env!("CARGO_PKG_VERSION").to_string(),
+        uptime_secs,
+        collect_plugin_infos(task),
+    );
     Ok(vec![NipartEvent::new_with_uuid(
         task.uuid,
-        NipartUserEvent::QueryPluginInfoReply(plugin_infos),
+        NipartUserEvent::PingReply(Box::new(info)),
         NipartPluginEvent::None,
         NipartEventAddress::Daemon,
         NipartEventAddress::User,
@@ -110,3 +156,67 @@ impl Task {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nipart::{
+        NipartEvent, NipartEventAddress, NipartPluginEvent, NipartPluginInfo,
+        NipartRole, NipartUserEvent,
+    };
+
+    use crate::commander::WorkFlow;
+
+    const TEST_UUID: u128 = 1;
+    const TEST_TIMEOUT: u32 = 5000;
+
+    fn gen_plugin_info_reply(
+        name: &str,
+        roles: Vec<NipartRole>,
+    ) -> NipartEvent {
+        NipartEvent::new_with_uuid(
+            TEST_UUID,
+            NipartUserEvent::None,
+            NipartPluginEvent::QueryPluginInfoReply(NipartPluginInfo::new(
+                name.to_string(),
+                roles,
+            )),
+            NipartEventAddress::Unicast(name.to_string()),
+            NipartEventAddress::Commander,
+            TEST_TIMEOUT,
+        )
+    }
+
+    #[test]
+    fn test_ping_reply_include_demo_plugin() {
+        let (mut workflow, mut share_data) =
+            WorkFlow::new_ping(TEST_UUID, 2, TEST_TIMEOUT);
+
+        let events = workflow.process(&mut share_data).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].plugin, NipartPluginEvent::QueryPluginInfo);
+
+        workflow.add_reply(gen_plugin_info_reply(
+            "nispor",
+            vec![NipartRole::QueryAndApply],
+        ));
+        workflow.add_reply(gen_plugin_info_reply(
+            "demo",
+            vec![NipartRole::Monitor],
+        ));
+        let events = workflow.process(&mut share_data).unwrap();
+
+        assert!(workflow.is_done());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].dst, NipartEventAddress::User);
+        if let NipartUserEvent::PingReply(info) = &events[0].user {
+            assert_eq!(info.schema_version, nipart::CUR_SCHEMA_VERSION);
+            assert_eq!(info.plugins.len(), 2);
+            // Plugins are sorted by name
+            assert_eq!(info.plugins[0].name, "demo");
+            assert_eq!(info.plugins[0].roles, vec![NipartRole::Monitor]);
+            assert_eq!(info.plugins[1].name, "nispor");
+        } else {
+            panic!("Expecting PingReply, got {:?}", events[0].user);
+        }
+    }
+}
@@ -1,17 +1,23 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use nipart::{
-    ErrorKind, MergedNetworkState, NetworkState, NipartApplyOption,
-    NipartError, NipartEvent, NipartEventAddress, NipartLockEntry,
-    NipartLockOption, NipartPluginEvent, NipartQueryOption, NipartRole,
-    NipartUserEvent,
+    ErrorKind, MergedNetworkState, NetworkCommitQueryOption, NetworkState,
+    NetworkStateConflict, NipartApplyOption, NipartDispatchPhase, NipartError,
+    NipartEvent, NipartEventAddress, NipartLockEntry, NipartLockMode,
+    NipartLockOption, NipartLogEntry, NipartLogLevel, NipartPluginEvent,
+    NipartQueryOption, NipartRole, NipartUserEvent, DEFAULT_TIMEOUT,
 };
 
-use super::{Task, TaskCallBackFn, TaskKind, WorkFlow, WorkFlowShareData};
+use super::{
+    confirm::process_apply_confirm,
+    dispatch::{run_dispatch_scripts, run_post_apply_dispatch_scripts},
+    Task, TaskCallBackFn, TaskKind, WorkFlow, WorkFlowShareData,
+};
 use crate::PluginRoles;
 
 const VERIFY_RETRY_COUNT: u32 = 5;
 const VERIFY_RETRY_INTERVAL: u32 = 1000;
+const DEFAULT_WAIT_IP_TIMEOUT: u32 = 5;
 
 impl WorkFlow {
     pub(crate) fn new_query_net_state(
@@ -23,16 +29,24 @@ impl WorkFlow {
         // Also include DHCP plugin
         let plugin_count = plugins.get_plugin_count(NipartRole::QueryAndApply)
             + plugins.get_plugin_count(NipartRole::Dhcp);
-        let tasks = vec![Task::new(
-            uuid,
-            TaskKind::QueryNetState(opt),
-            plugin_count,
-            timeout,
-        )];
-        let share_data = WorkFlowShareData::default();
+        // Shared lock on whole network state, so concurrent queries do not
+        // block each other but wait for ongoing applies.
+        let tasks = vec![
+            Task::new(uuid, TaskKind::Lock, 1, timeout),
+            Task::new(
+                uuid,
+                TaskKind::QueryNetState(opt.clone()),
+                plugin_count,
+                timeout,
+            ),
+        ];
+        let share_data = WorkFlowShareData {
+            query_option: Some(opt.clone()),
+            ..Default::default()
+        };
 
         let call_backs: Vec<Option<TaskCallBackFn>> =
-            vec![Some(query_net_state)];
+            vec![Some(process_lock_reply), Some(query_net_state)];
 
         (
             WorkFlow::new("query_net_state", uuid, tasks, call_backs),
@@ -40,27 +54,147 @@ impl WorkFlow {
         )
     }
 
-    pub(crate) fn new_apply_net_state(
-        des_state: NetworkState,
-        opt: NipartApplyOption,
+    pub(crate) fn new_query_net_state_since_commit(
+        opt: NipartQueryOption,
         uuid: u128,
         plugins: &PluginRoles,
         timeout: u32,
     ) -> (Self, WorkFlowShareData) {
         let plugin_count = plugins.get_plugin_count(NipartRole::QueryAndApply)
             + plugins.get_plugin_count(NipartRole::Dhcp);
-
-        let mut tasks = vec![
+        let mut plugin_opt = opt.clone();
+        plugin_opt.since_commit = None;
+        let tasks = vec![
+            Task::new(uuid, TaskKind::Lock, 1, timeout),
+            // Only single tracking plugin allowed for now.
             Task::new(
                 uuid,
-                TaskKind::QueryRelatedNetState,
+                TaskKind::QueryCommits(NetworkCommitQueryOption::default()),
+                1,
+                timeout,
+            ),
+            Task::new(
+                uuid,
+                TaskKind::QueryNetState(plugin_opt),
                 plugin_count,
                 timeout,
             ),
+        ];
+        // The `queried_state` is not set by this workflow, hence the result
+        // will not be stored into cache.
+        let share_data = WorkFlowShareData {
+            query_option: Some(opt),
+            ..Default::default()
+        };
+
+        let call_backs: Vec<Option<TaskCallBackFn>> = vec![
+            Some(process_lock_reply),
+            Some(store_commit_state),
+            Some(query_net_state_since_commit),
+        ];
+
+        (
+            WorkFlow::new(
+                "query_net_state_since_commit",
+                uuid,
+                tasks,
+                call_backs,
+            ),
+            share_data,
+        )
+    }
+
+    pub(crate) fn new_apply_net_state(
+        des_state: NetworkState,
+        opt: NipartApplyOption,
+        uuid: u128,
+        plugins: &PluginRoles,
+        timeout: u32,
+    ) -> Result<(Self, WorkFlowShareData), NipartError> {
+        // For desired state only holding DNS, routes, route rules or
+        // hostname, we skip DHCP plugin to reduce the blast radius.
+        let skip_iface_apply = des_state.interfaces.is_empty();
+        check_required_roles(&des_state, plugins)?;
+        let skip_dhcp = plugins.get_plugin_count(NipartRole::Dhcp) == 0;
+        if skip_dhcp && !skip_iface_apply {
+            log::info!(
+                "No plugin provides role {}, skipping DHCP apply",
+                NipartRole::Dhcp
+            );
+        }
+        let plugin_count = if skip_iface_apply {
+            log::info!(
+                "Desired state holds no interface, skipping interface apply"
+            );
+            plugins.get_plugin_count(NipartRole::QueryAndApply)
+        } else {
+            plugins.get_plugin_count(NipartRole::QueryAndApply)
+                + plugins.get_plugin_count(NipartRole::Dhcp)
+        };
+
+        // Interface with `wait-ip` might need more time to get IP address
+        let verify_retry_count = if des_state
+            .interfaces
+            .iter()
+            .any(|i| i.is_up() && i.base_iface().wait_ip.is_some())
+        {
+            let wait_ip_timeout =
+                opt.wait_ip_timeout.unwrap_or(DEFAULT_WAIT_IP_TIMEOUT);
+            std::cmp::max(
+                VERIFY_RETRY_COUNT,
+                wait_ip_timeout.saturating_mul(1000) / VERIFY_RETRY_INTERVAL,
+            )
+        } else {
+            VERIFY_RETRY_COUNT
+        };
+        // Timeout larger than default also extends the verification retry
+        // proportionally, retry interval is unchanged.
+        let verify_retry_count = std::cmp::max(
+            verify_retry_count,
+            (u64::from(VERIFY_RETRY_COUNT) * u64::from(timeout)
+                / u64::from(DEFAULT_TIMEOUT))
+            .try_into()
+            .unwrap_or(u32::MAX),
+        );
+
+        if opt.persistent_only {
+            return Ok(Self::new_apply_net_state_persistent_only(
+                des_state,
+                opt,
+                uuid,
+                plugin_count,
+                skip_dhcp,
+                timeout,
+            ));
+        }
+
+        if opt.skip_pre_query && opt.confirm_timeout.is_some() {
+            let e = NipartError::new(
+                ErrorKind::InvalidArgument,
+                "Cannot skip pre-apply query when confirmation is required \
+                as rollback needs the pre-apply network state"
+                    .to_string(),
+            );
+            log::error!("{}", e);
+            return Err(e);
+        }
+
+        let confirm_timeout = opt.confirm_timeout;
+        // The client of confirm mode is expecting the `WaitConfirm` reply,
+        // hence never skip apply workflow for no-op desired state.
+        let apply_force = opt.force || confirm_timeout.is_some();
+
+        let mut tasks = vec![
             Task::new(uuid, TaskKind::Lock, 1, timeout),
             Task::new(
                 uuid,
-                TaskKind::ApplyNetState(opt),
+                TaskKind::RunDispatchScripts(NipartDispatchPhase::PreApply),
+                1,
+                timeout,
+            ),
+            Task::new(
+                uuid,
+                TaskKind::ApplyNetState(opt.clone()),
                 plugin_count,
                 timeout,
             ),
@@ -71,26 +205,145 @@ impl WorkFlow {
             plugin_count,
             timeout,
         );
-        verify_task.set_retry(VERIFY_RETRY_COUNT, VERIFY_RETRY_INTERVAL);
+        verify_task.set_retry(verify_retry_count, VERIFY_RETRY_INTERVAL);
+        verify_task.step = "verify";
 
         tasks.push(verify_task);
+        tasks.push(Task::new(
+            uuid,
+            TaskKind::RunDispatchScripts(NipartDispatchPhase::PostApply),
+            1,
+            timeout,
+        ));
+        if let Some(confirm_timeout) = confirm_timeout {
+            tasks.push(Task::new(
+                uuid,
+                TaskKind::WaitConfirm,
+                1,
+                confirm_timeout.saturating_mul(1000),
+            ));
+        }
         tasks.push(Task::new(uuid, TaskKind::Commit, 1, timeout));
 
-        let share_data = WorkFlowShareData {
+        let mut share_data = WorkFlowShareData {
             desired_state: Some(des_state),
+            changes_net_state: true,
+            apply_force,
+            skip_iface_apply,
+            skip_dhcp,
+            apply_reply_count: plugin_count,
+            query_apply_plugin_count: plugins
+                .get_plugin_count(NipartRole::QueryAndApply),
             ..Default::default()
         };
+        if opt.trace {
+            share_data.enable_trace();
+        }
 
-        let call_backs: Vec<Option<TaskCallBackFn>> = vec![
-            Some(pre_apply_query_related_state),
-            None,
+        let mut call_backs: Vec<Option<TaskCallBackFn>> = vec![
+            Some(process_lock_reply),
+            Some(run_dispatch_scripts),
             Some(apply_net_state),
             Some(post_apply_query_related_state),
+            Some(run_post_apply_dispatch_scripts),
+        ];
+        if confirm_timeout.is_some() {
+            call_backs.push(Some(process_apply_confirm));
+        }
+        call_backs.push(Some(post_commit_net_state));
+
+        if opt.skip_pre_query {
+            merge_without_pre_query(&mut share_data)?;
+        } else {
+            tasks.insert(
+                0,
+                Task::new(
+                    uuid,
+                    TaskKind::QueryRelatedNetState,
+                    plugin_count,
+                    timeout,
+                ),
+            );
+            call_backs.insert(0, Some(pre_apply_query_related_state));
+            // Only single tracking plugin allowed for now.
+            if plugins.get_plugin_count(NipartRole::Track) > 0 {
+                let mut commit_opt = NetworkCommitQueryOption::default();
+                commit_opt.count = 1;
+                commit_opt.persisted_only = true;
+                tasks.insert(
+                    0,
+                    Task::new(
+                        uuid,
+                        TaskKind::QueryCommits(commit_opt),
+                        1,
+                        timeout,
+                    ),
+                );
+                call_backs.insert(0, Some(store_saved_ignored_ifaces));
+            }
+        }
+
+        Ok((
+            WorkFlow::new("apply_net_state", uuid, tasks, call_backs),
+            share_data,
+        ))
+    }
+
+    // Still query current network state to validate desired state and
+    // lock related interfaces, but only commit the desired state.
+    fn new_apply_net_state_persistent_only(
+        des_state: NetworkState,
+        opt: NipartApplyOption,
+        uuid: u128,
+        plugin_count: usize,
+        skip_dhcp: bool,
+        timeout: u32,
+    ) -> (Self, WorkFlowShareData) {
+        if opt.confirm_timeout.is_some() {
+            log::warn!(
+                "Ignoring confirm timeout as persistent only apply does \
+                not change running network state"
+            );
+        }
+        let skip_iface_apply = des_state.interfaces.is_empty();
+        let tasks = vec![
+            Task::new(
+                uuid,
+                TaskKind::QueryRelatedNetState,
+                plugin_count,
+                timeout,
+            ),
+            Task::new(uuid, TaskKind::Lock, 1, timeout),
+            Task::new(uuid, TaskKind::Commit, 1, timeout),
+        ];
+        // The running network state is not changed, hence no need to
+        // invalidate cache. Desired state matching running network state
+        // is still required to be committed.
+        let mut share_data = WorkFlowShareData {
+            desired_state: Some(des_state),
+            changes_net_state: false,
+            apply_force: true,
+            skip_iface_apply,
+            skip_dhcp,
+            ..Default::default()
+        };
+        if opt.trace {
+            share_data.enable_trace();
+        }
+
+        let call_backs: Vec<Option<TaskCallBackFn>> = vec![
+            Some(pre_apply_query_related_state),
+            Some(process_lock_reply),
             Some(post_commit_net_state),
         ];
 
         (
-            WorkFlow::new("apply_net_state", uuid, tasks, call_backs),
+            WorkFlow::new(
+                "apply_net_state_persistent_only",
+                uuid,
+                tasks,
+                call_backs,
+            ),
             share_data,
         )
     }
@@ -98,8 +351,12 @@ impl WorkFlow {
 
 fn query_net_state(
     task: &Task,
-    _share_data: &mut WorkFlowShareData,
+    share_data: &mut WorkFlowShareData,
 ) -> Result<Vec<NipartEvent>, NipartError> {
+    let mut events: Vec<NipartEvent> = share_data
+        .gen_unlock_event(task.uuid, task.timeout)
+        .into_iter()
+        .collect();
     let event = if task.replies.is_empty() {
         NipartEvent::new_with_uuid(
             task.uuid,
@@ -113,7 +370,19 @@ fn query_net_state(
             task.timeout,
         )
     } else {
-        let state = get_state_from_replies(task.replies.as_slice());
+        let (state, conflicts) =
+            get_state_and_conflicts_from_replies(task.replies.as_slice());
+        // Let user know plugins disagree on the network state
+        for conflict in conflicts {
+            events.push(
+                NipartLogEntry::new(
+                    NipartLogLevel::Warn,
+                    format!("Plugins reported conflicting value: {conflict}"),
+                )
+                .to_event(task.uuid, NipartEventAddress::Commander),
+            );
+        }
+        share_data.queried_state = Some(state.clone());
         NipartEvent::new_with_uuid(
             task.uuid,
             NipartUserEvent::QueryNetStateReply(Box::new(state)),
@@ -123,14 +392,88 @@ fn query_net_state(
             task.timeout,
         )
     };
-    Ok(vec![event])
+    events.push(event);
+    Ok(events)
+}
+
+fn store_commit_state(
+    task: &Task,
+    share_data: &mut WorkFlowShareData,
+) -> Result<Vec<NipartEvent>, NipartError> {
+    let commit_id = share_data
+        .query_option
+        .as_ref()
+        .and_then(|o| o.since_commit.as_deref())
+        .unwrap_or_default();
+    for reply in task.replies.as_slice() {
+        if let NipartPluginEvent::QueryCommitsReply(commits) = &reply.plugin {
+            if let Some(commit) = commits.iter().find(|c| c.id == commit_id) {
+                share_data.commit_state = Some(commit.state.clone());
+                return Ok(Vec::new());
+            }
+        }
+    }
+    Err(NipartError::new(
+        ErrorKind::InvalidArgument,
+        format!("Commit {commit_id} not found"),
+    ))
+}
+
+fn query_net_state_since_commit(
+    task: &Task,
+    share_data: &mut WorkFlowShareData,
+) -> Result<Vec<NipartEvent>, NipartError> {
+    if task.replies.is_empty() {
+        return Err(NipartError::new(
+            ErrorKind::Timeout,
+            "Not plugin replied the query network state call".into(),
+        ));
+    }
+    let commit_state = if let Some(s) = share_data.commit_state.as_ref() {
+        s
+    } else {
+        return Err(NipartError::new(
+            ErrorKind::Bug,
+            format!("Got None for commit_state in share data {share_data:?}"),
+        ));
+    };
+    let running_state = get_state_from_replies(task.replies.as_slice());
+    let diff_state = commit_state.gen_diff(&running_state)?;
+    let mut events: Vec<NipartEvent> = share_data
+        .gen_unlock_event(task.uuid, task.timeout)
+        .into_iter()
+        .collect();
+    events.push(NipartEvent::new_with_uuid(
+        task.uuid,
+        NipartUserEvent::QueryNetStateReply(Box::new(diff_state)),
+        NipartPluginEvent::None,
+        NipartEventAddress::Daemon,
+        NipartEventAddress::User,
+        task.timeout,
+    ));
+    Ok(events)
+}
+
+fn store_saved_ignored_ifaces(
+    task: &Task,
+    share_data: &mut WorkFlowShareData,
+) -> Result<Vec<NipartEvent>, NipartError> {
+    for reply in task.replies.as_slice() {
+        if let NipartPluginEvent::QueryCommitsReply(commits) = &reply.plugin {
+            if let Some(commit) = commits.first() {
+                share_data.saved_ignored_ifaces =
+                    commit.state.ignored_iface_names();
+            }
+        }
+    }
+    Ok(Vec::new())
 }
 
 fn pre_apply_query_related_state(
     task: &Task,
     share_data: &mut WorkFlowShareData,
 ) -> Result<Vec<NipartEvent>, NipartError> {
-    let cur_state = get_state_from_replies(task.replies.as_slice());
+    let mut cur_state = get_state_from_replies(task.replies.as_slice());
 
     let des_state = if let Some(d) = share_data.desired_state.as_ref() {
         d.clone()
@@ -140,16 +483,131 @@ fn pre_apply_query_related_state(
             format!("Got None for desired_state in share data {share_data:?}",),
         ));
     };
+    cur_state.mark_ifaces_as_ignored(
+        share_data.saved_ignored_ifaces.as_slice(),
+        &des_state,
+    );
+
+    if !share_data.apply_force && des_state.is_noop(&cur_state)? {
+        share_data.skip_remaining_tasks = true;
+        share_data.changes_net_state = false;
+        return Ok(vec![
+            NipartLogEntry::new(
+                NipartLogLevel::Info,
+                "Desired state changes nothing, skipping apply".to_string(),
+            )
+            .to_event(task.uuid, NipartEventAddress::Commander),
+            gen_apply_reply(task, share_data),
+        ]);
+    }
 
-    let merged_state =
-        MergedNetworkState::new(des_state, cur_state.clone(), false, false)?;
+    let (merged_state, warnings) = MergedNetworkState::new_with_warnings(
+        des_state,
+        cur_state.clone(),
+        false,
+        false,
+    )?;
 
     share_data.merged_state = Some(merged_state);
+    share_data.apply_warnings = warnings;
     share_data.pre_apply_state = Some(cur_state);
 
     Ok(Vec::new())
 }
 
+// Fail early instead of waiting for replies which never come when plugin of
+// required role is missing.
+fn check_required_roles(
+    des_state: &NetworkState,
+    plugins: &PluginRoles,
+) -> Result<(), NipartError> {
+    if plugins.get_plugin_count(NipartRole::QueryAndApply) == 0 {
+        let e = NipartError::new(
+            ErrorKind::NotSupportedError,
+            format!(
+                "No plugin provides role {}, cannot apply network state",
+                NipartRole::QueryAndApply
+            ),
+        );
+        log::error!("{}", e);
+        return Err(e);
+    }
+    if plugins.get_plugin_count(NipartRole::Dhcp) == 0 {
+        if let Some(iface) = des_state.interfaces.iter().find(|i| {
+            i.is_up()
+                && (i
+                    .base_iface()
+                    .ipv4
+                    .as_ref()
+                    .map(|i| i.enabled && i.dhcp == Some(true))
+                    .unwrap_or_default()
+                    || i.base_iface()
+                        .ipv6
+                        .as_ref()
+                        .map(|i| i.enabled && i.dhcp == Some(true))
+                        .unwrap_or_default())
+        }) {
+            let e = NipartError::new(
+                ErrorKind::NotSupportedError,
+                format!(
+                    "Interface {} requires DHCP but no plugin provides \
+                    role {}",
+                    iface.name(),
+                    NipartRole::Dhcp
+                ),
+            );
+            log::error!("{}", e);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+// Merge desired state against empty network state for apply without
+// querying current network state. No-op check is skipped as there is nothing
+// to compare with.
+fn merge_without_pre_query(
+    share_data: &mut WorkFlowShareData,
+) -> Result<(), NipartError> {
+    let des_state = if let Some(d) = share_data.desired_state.as_ref() {
+        d.clone()
+    } else {
+        return Err(NipartError::new(
+            ErrorKind::Bug,
+            format!("Got None for desired_state in share data {share_data:?}",),
+        ));
+    };
+    log::info!("Skipping pre-apply query of current network state");
+
+    let (merged_state, warnings) = MergedNetworkState::new_with_warnings(
+        des_state,
+        NetworkState::new(),
+        false,
+        false,
+    )?;
+    share_data.merged_state = Some(merged_state);
+    share_data.apply_warnings = warnings;
+    Ok(())
+}
+
+// Locker replies error naming the lock holder when failed to acquire lock
+// in time.
+fn process_lock_reply(
+    task: &Task,
+    share_data: &mut WorkFlowShareData,
+) -> Result<Vec<NipartEvent>, NipartError> {
+    for reply in task.replies.as_slice() {
+        if let NipartUserEvent::Error(e) = &reply.user {
+            return Err(e.clone());
+        }
+    }
+    share_data.locked_entries = gen_lock_entries(share_data)
+        .into_iter()
+        .map(|(entry, _)| entry)
+        .collect();
+    Ok(Vec::new())
+}
+
 // Since we have verification process afterwards, here we only log errors
 // from plugins
 fn apply_net_state(
@@ -181,21 +639,58 @@ fn post_apply_query_related_state(
     };
 
     merged_state.verify(&post_apply_state)?;
-    Ok(Vec::new())
+
+    // Running network state is settled, let queries proceed while remaining
+    // tasks, for example waiting confirmation, are running.
+    if !share_data
+        .locked_entries
+        .contains(&NipartLockEntry::NetState)
+    {
+        return Ok(Vec::new());
+    }
+    share_data
+        .locked_entries
+        .retain(|e| e != &NipartLockEntry::NetState);
+    Ok(vec![NipartEvent::new_with_uuid(
+        task.uuid,
+        NipartUserEvent::None,
+        NipartPluginEvent::Unlock(Box::new(vec![NipartLockEntry::NetState])),
+        NipartEventAddress::Commander,
+        NipartEventAddress::Locker,
+        task.timeout,
+    )])
 }
 
 fn post_commit_net_state(
     task: &Task,
-    _share_data: &mut WorkFlowShareData,
+    share_data: &mut WorkFlowShareData,
 ) -> Result<Vec<NipartEvent>, NipartError> {
-    Ok(vec![NipartEvent::new_with_uuid(
+    // Release locks so that pending lock requests could proceed
+    let mut events: Vec<NipartEvent> = share_data
+        .gen_unlock_event(task.uuid, task.timeout)
+        .into_iter()
+        .collect();
+    events.push(gen_apply_reply(task, share_data));
+    Ok(events)
+}
+
+// Include the timeline of events when tracing is enabled
+fn gen_apply_reply(task: &Task, share_data: &WorkFlowShareData) -> NipartEvent {
+    let warnings = share_data.apply_warnings.clone();
+    NipartEvent::new_with_uuid(
         task.uuid,
-        NipartUserEvent::ApplyNetStateReply,
+        match share_data.trace.as_ref() {
+            Some(trace) => NipartUserEvent::ApplyNetStateTraceReply(
+                warnings,
+                trace.clone(),
+            ),
+            None => NipartUserEvent::ApplyNetStateReply(warnings),
+        },
         NipartPluginEvent::None,
         NipartEventAddress::Daemon,
         NipartEventAddress::User,
         task.timeout,
-    )])
+    )
 }
 
 impl Task {
@@ -247,6 +742,9 @@ impl Task {
             NipartEventAddress::Group(NipartRole::QueryAndApply),
             self.timeout,
         ));
+        if share_data.skip_iface_apply || share_data.skip_dhcp {
+            return ret;
+        }
         // TODO: Only query DHCP config for related  interfaces
         ret.push(NipartEvent::new_with_uuid(
             self.uuid,
@@ -260,7 +758,7 @@ impl Task {
     }
 
     pub(crate) fn gen_request_apply(
-        &self,
+        &mut self,
         opt: NipartApplyOption,
         share_data: &WorkFlowShareData,
     ) -> Vec<NipartEvent> {
@@ -275,15 +773,31 @@ impl Task {
                 MergedNetworkState::default()
             }
         };
-        let dhcp_changes = merged_state.get_dhcp_changes();
-        ret.push(NipartEvent::new_with_uuid(
-            self.uuid,
-            NipartUserEvent::None,
-            NipartPluginEvent::ApplyNetState(Box::new(merged_state), opt),
-            NipartEventAddress::Commander,
-            NipartEventAddress::Group(NipartRole::QueryAndApply),
-            self.timeout,
-        ));
+        let dhcp_changes = merged_state.get_dhcp_changes(!opt.keep_dhcp_lease);
+        let sub_states = match opt.parallel {
+            Some(max) => merged_state.split_for_parallel_apply(max as usize),
+            None => vec![merged_state],
+        };
+        // Every QueryAndApply plugin replies to each sub-apply. Reset from
+        // share data as this function is invoked again on retry.
+        self.expected_reply_count = share_data.apply_reply_count
+            + (sub_states.len() - 1) * share_data.query_apply_plugin_count;
+        for sub_state in sub_states {
+            ret.push(NipartEvent::new_with_uuid(
+                self.uuid,
+                NipartUserEvent::None,
+                NipartPluginEvent::ApplyNetState(
+                    Box::new(sub_state),
+                    opt.clone(),
+                ),
+                NipartEventAddress::Commander,
+                NipartEventAddress::Group(NipartRole::QueryAndApply),
+                self.timeout,
+            ));
+        }
+        if share_data.skip_iface_apply || share_data.skip_dhcp {
+            return ret;
+        }
         ret.push(NipartEvent::new_with_uuid(
             self.uuid,
             NipartUserEvent::None,
@@ -299,51 +813,20 @@ impl Task {
         &self,
         share_data: &WorkFlowShareData,
     ) -> Vec<NipartEvent> {
-        let merged_state = match share_data.merged_state.as_ref() {
-            Some(s) => s.clone(),
-            None => {
-                log::error!(
-                    "BUG: gen_request_lock() got None for \
-                    merge_state in share data {share_data:?}"
-                );
-                MergedNetworkState::default()
-            }
-        };
-        let mut locks = Vec::new();
-        for iface in merged_state
-            .interfaces
-            .iter()
-            .filter_map(|i| i.for_apply.as_ref())
-        {
-            locks.push((
-                NipartLockEntry::new_iface(
-                    iface.name().to_string(),
-                    iface.iface_type(),
-                ),
-                NipartLockOption::new(self.timeout),
-            ));
-        }
-
-        if merged_state.dns.is_changed() {
-            locks.push((
-                NipartLockEntry::Dns,
-                NipartLockOption::new(self.timeout),
-            ));
-        }
-
-        if merged_state.routes.is_changed() {
-            locks.push((
-                NipartLockEntry::Route,
-                NipartLockOption::new(self.timeout),
-            ));
-        }
-
-        if merged_state.rules.is_changed() {
-            locks.push((
-                NipartLockEntry::RouteRule,
-                NipartLockOption::new(self.timeout),
-            ));
-        }
+        let locks: Vec<(NipartLockEntry, NipartLockOption)> =
+            gen_lock_entries(share_data)
+                .into_iter()
+                .map(|(entry, mode)| {
+                    // Task timeout is in milliseconds
+                    (
+                        entry,
+                        NipartLockOption::new_with_mode(
+                            self.timeout.div_ceil(1000),
+                            mode,
+                        ),
+                    )
+                })
+                .collect();
         vec![NipartEvent::new_with_uuid(
             self.uuid,
             NipartUserEvent::None,
@@ -355,9 +838,91 @@ impl Task {
     }
 }
 
+// Workflow without merged state is a query which holds shared lock of
+// whole network state. Apply holds exclusive locks of changed entries, and
+// intent exclusive lock of whole network state when changing running network
+// state.
+fn gen_lock_entries(
+    share_data: &WorkFlowShareData,
+) -> Vec<(NipartLockEntry, NipartLockMode)> {
+    let Some(merged_state) = share_data.merged_state.as_ref() else {
+        return vec![(NipartLockEntry::NetState, NipartLockMode::Shared)];
+    };
+    let mut ret: Vec<(NipartLockEntry, NipartLockMode)> =
+        get_lock_entries(merged_state)
+            .into_iter()
+            .map(|entry| (entry, NipartLockMode::Exclusive))
+            .collect();
+    if share_data.changes_net_state {
+        ret.push((NipartLockEntry::NetState, NipartLockMode::IntentExclusive));
+    }
+    ret
+}
+
+impl WorkFlowShareData {
+    // Release all locks held by workflow, the locks are forgotten afterwards
+    // hence released only once.
+    pub(crate) fn gen_unlock_event(
+        &mut self,
+        uuid: u128,
+        timeout: u32,
+    ) -> Option<NipartEvent> {
+        if self.locked_entries.is_empty() {
+            return None;
+        }
+        Some(NipartEvent::new_with_uuid(
+            uuid,
+            NipartUserEvent::None,
+            NipartPluginEvent::Unlock(Box::new(std::mem::take(
+                &mut self.locked_entries,
+            ))),
+            NipartEventAddress::Commander,
+            NipartEventAddress::Locker,
+            timeout,
+        ))
+    }
+}
+
+fn get_lock_entries(merged_state: &MergedNetworkState) -> Vec<NipartLockEntry> {
+    let mut locks = Vec::new();
+    for iface in merged_state
+        .interfaces
+        .iter()
+        .filter_map(|i| i.for_apply.as_ref())
+    {
+        locks.push(NipartLockEntry::new_iface(
+            iface.name().to_string(),
+            iface.iface_type(),
+        ));
+    }
+
+    if merged_state.dns.is_changed() {
+        locks.push(NipartLockEntry::Dns);
+    }
+
+    if merged_state.routes.is_changed() {
+        locks.push(NipartLockEntry::Route);
+    }
+
+    if merged_state.rules.is_changed() {
+        locks.push(NipartLockEntry::RouteRule);
+    }
+    locks
+}
+
 fn get_state_from_replies(replies: &[NipartEvent]) -> NetworkState {
+    get_state_and_conflicts_from_replies(replies).0
+}
+
+fn get_state_and_conflicts_from_replies(
+    replies: &[NipartEvent],
+) -> (NetworkState, Vec<NetworkStateConflict>) {
     let mut states = Vec::new();
     for reply in replies {
+        // Plugin failed to query replies error with empty state
+        if let NipartUserEvent::Error(e) = &reply.user {
+            log::warn!("{e}");
+        }
         if let NipartPluginEvent::QueryNetStateReply(state, priority) =
             &reply.plugin
         {
@@ -373,7 +938,8 @@ fn get_state_from_replies(replies: &[NipartEvent]) -> NetworkState {
             );
         }
     }
-    let mut state = NetworkState::merge_states(states);
+    let (mut state, conflicts) =
+        NetworkState::merge_states_with_conflicts(states);
 
     for reply in replies {
         if let NipartPluginEvent::QueryDhcpConfigReply(dhcp_confs) =
@@ -382,5 +948,738 @@ fn get_state_from_replies(replies: &[NipartEvent]) -> NetworkState {
             state.fill_dhcp_config(dhcp_confs);
         }
     }
-    state
+    (state, conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use nipart::{
+        MergedNetworkState, NetworkCommit, NetworkState, NipartApplyOption,
+        NipartDhcpConfig, NipartEvent, NipartEventAddress, NipartLockEntry,
+        NipartLockMode, NipartPluginEvent, NipartQueryOption, NipartRole,
+        NipartUserEvent, DEFAULT_TIMEOUT,
+    };
+
+    use super::{
+        apply_net_state, post_apply_query_related_state, process_lock_reply,
+        VERIFY_RETRY_COUNT, VERIFY_RETRY_INTERVAL,
+    };
+    use crate::commander::{
+        Task, TaskCallBackFn, TaskKind, WorkFlow, WorkFlowShareData,
+    };
+    use crate::PluginRoles;
+
+    const TEST_UUID: u128 = 1;
+    const TEST_TIMEOUT: u32 = 5000;
+
+    fn gen_plugin_reply(plugin: NipartPluginEvent) -> NipartEvent {
+        NipartEvent::new_with_uuid(
+            TEST_UUID,
+            NipartUserEvent::None,
+            plugin,
+            NipartEventAddress::Unicast("test".to_string()),
+            NipartEventAddress::Commander,
+            TEST_TIMEOUT,
+        )
+    }
+
+    fn get_lock_requests(
+        events: &[NipartEvent],
+    ) -> Vec<(NipartLockEntry, NipartLockMode)> {
+        let mut ret = Vec::new();
+        for event in events {
+            if let NipartPluginEvent::Lock(locks) = &event.plugin {
+                ret.extend(locks.iter().map(|(e, o)| (e.clone(), o.mode)));
+            }
+        }
+        ret
+    }
+
+    fn get_unlocked(events: &[NipartEvent]) -> Vec<NipartLockEntry> {
+        let mut ret = Vec::new();
+        for event in events {
+            if let NipartPluginEvent::Unlock(entries) = &event.plugin {
+                ret.extend(entries.iter().cloned());
+            }
+        }
+        ret
+    }
+
+    #[test]
+    fn test_query_take_shared_lock() {
+        let mut plugins = PluginRoles::default();
+        plugins.insert("test", vec![NipartRole::QueryAndApply]);
+        let (mut workflow, mut share_data) = WorkFlow::new_query_net_state(
+            NipartQueryOption::default(),
+            TEST_UUID,
+            &plugins,
+            TEST_TIMEOUT,
+        );
+
+        let events = workflow.process(&mut share_data).unwrap();
+        assert_eq!(
+            get_lock_requests(&events),
+            vec![(NipartLockEntry::NetState, NipartLockMode::Shared)]
+        );
+
+        workflow.add_reply(gen_plugin_reply(NipartPluginEvent::LockReply));
+        let events = workflow.process(&mut share_data).unwrap();
+        assert!(events
+            .iter()
+            .any(|e| matches!(e.plugin, NipartPluginEvent::QueryNetState(_))));
+
+        workflow.add_reply(gen_plugin_reply(
+            NipartPluginEvent::QueryNetStateReply(
+                Box::new(NetworkState::new()),
+                0,
+            ),
+        ));
+        let events = workflow.process(&mut share_data).unwrap();
+        assert_eq!(get_unlocked(&events), vec![NipartLockEntry::NetState]);
+        assert!(events
+            .iter()
+            .any(|e| matches!(e.user, NipartUserEvent::QueryNetStateReply(_))));
+        assert!(workflow.is_done());
+        assert!(share_data.locked_entries.is_empty());
+    }
+
+    fn gen_dummies_state(dummies: &[(&str, u64)]) -> NetworkState {
+        let mut yaml = String::from("interfaces:\n");
+        for (name, mtu) in dummies {
+            yaml.push_str(&format!(
+                "- name: {name}\n  type: dummy\n  state: up\n  mtu: {mtu}\n"
+            ));
+        }
+        NetworkState::new_from_yaml(&yaml).unwrap()
+    }
+
+    fn gen_commit(id: &str, state: NetworkState) -> NetworkCommit {
+        let mut commit = NetworkCommit::default();
+        commit.id = id.to_string();
+        commit.persisted = true;
+        commit.state = state;
+        commit
+    }
+
+    // Return reply events of querying running network state since
+    // specified commit.
+    fn query_since_commit(
+        commit_id: &str,
+        running_state: NetworkState,
+    ) -> (Vec<NipartEvent>, bool) {
+        let mut plugins = PluginRoles::default();
+        plugins.insert("test", vec![NipartRole::QueryAndApply]);
+        let mut opt = NipartQueryOption::default();
+        opt.since_commit = Some(commit_id.to_string());
+        let (mut workflow, mut share_data) =
+            WorkFlow::new_query_net_state_since_commit(
+                opt,
+                TEST_UUID,
+                &plugins,
+                TEST_TIMEOUT,
+            );
+
+        // Commits are sorted with newest first, each holds the full state
+        // merged with its ancestors.
+        let commits = vec![
+            gen_commit(
+                "c2",
+                gen_dummies_state(&[("dummy1", 1500), ("dummy2", 1500)]),
+            ),
+            gen_commit("c1", gen_dummies_state(&[("dummy1", 1500)])),
+        ];
+        workflow.process(&mut share_data).unwrap();
+        workflow.add_reply(gen_plugin_reply(
+            NipartPluginEvent::QueryCommitsReply(Box::new(commits)),
+        ));
+        let events = workflow.process(&mut share_data).unwrap();
+        if workflow.is_fail() {
+            return (events, false);
+        }
+        workflow.add_reply(gen_plugin_reply(
+            NipartPluginEvent::QueryNetStateReply(Box::new(running_state), 0),
+        ));
+        (workflow.process(&mut share_data).unwrap(), true)
+    }
+
+    fn get_query_reply(events: &[NipartEvent]) -> NetworkState {
+        events
+            .iter()
+            .find_map(|e| match &e.user {
+                NipartUserEvent::QueryNetStateReply(s) => Some(*s.clone()),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_query_since_commit_only_post_commit_changes() {
+        // dummy2 changed after commit c2
+        let running = gen_dummies_state(&[("dummy1", 1500), ("dummy2", 9000)]);
+
+        let (events, succeeded) = query_since_commit("c2", running.clone());
+        assert!(succeeded);
+        let diff_state = get_query_reply(&events);
+        assert!(diff_state.interfaces.kernel_ifaces.get("dummy1").is_none());
+        assert_eq!(
+            diff_state
+                .interfaces
+                .kernel_ifaces
+                .get("dummy2")
+                .and_then(|i| i.base_iface().mtu),
+            Some(1500)
+        );
+
+        // Commit c1 knows nothing about dummy2
+        let (events, succeeded) = query_since_commit("c1", running);
+        assert!(succeeded);
+        assert!(get_query_reply(&events).is_empty());
+    }
+
+    #[test]
+    fn test_query_since_commit_not_found() {
+        let (events, succeeded) = query_since_commit(
+            "not_exist",
+            gen_dummies_state(&[("dummy1", 1500)]),
+        );
+
+        assert!(!succeeded);
+        assert!(events.iter().any(|e| e.is_err()));
+    }
+
+    #[test]
+    fn test_failed_apply_release_locks() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up",
+        )
+        .unwrap();
+        let mut share_data = WorkFlowShareData {
+            merged_state: Some(
+                MergedNetworkState::new(
+                    desired.clone(),
+                    NetworkState::new(),
+                    false,
+                    false,
+                )
+                .unwrap(),
+            ),
+            desired_state: Some(desired),
+            changes_net_state: true,
+            ..Default::default()
+        };
+        let tasks = vec![
+            Task::new(TEST_UUID, TaskKind::Lock, 1, TEST_TIMEOUT),
+            Task::new(
+                TEST_UUID,
+                TaskKind::QueryRelatedNetState,
+                1,
+                TEST_TIMEOUT,
+            ),
+        ];
+        let call_backs: Vec<Option<TaskCallBackFn>> = vec![
+            Some(process_lock_reply),
+            Some(post_apply_query_related_state),
+        ];
+        let mut workflow = WorkFlow::new("test", TEST_UUID, tasks, call_backs);
+
+        let events = workflow.process(&mut share_data).unwrap();
+        let locks = get_lock_requests(&events);
+        assert!(locks.contains(&(
+            NipartLockEntry::NetState,
+            NipartLockMode::IntentExclusive
+        )));
+        assert!(locks.iter().any(|(e, m)| matches!(
+            e,
+            NipartLockEntry::Interface(i) if i.0 == "dummy1"
+        ) && *m
+            == NipartLockMode::Exclusive));
+
+        workflow.add_reply(gen_plugin_reply(NipartPluginEvent::LockReply));
+        workflow.process(&mut share_data).unwrap();
+        assert_eq!(share_data.locked_entries.len(), locks.len());
+
+        // dummy1 not found in post apply state, verification fails
+        workflow.add_reply(gen_plugin_reply(
+            NipartPluginEvent::QueryNetStateReply(
+                Box::new(NetworkState::new()),
+                0,
+            ),
+        ));
+        let events = workflow.process(&mut share_data).unwrap();
+        assert!(workflow.is_fail());
+        let unlocked = get_unlocked(&events);
+        assert_eq!(unlocked.len(), locks.len());
+        for (entry, _) in locks {
+            assert!(unlocked.contains(&entry));
+        }
+        assert!(events.iter().any(|e| e.is_err()));
+        assert!(share_data.locked_entries.is_empty());
+    }
+
+    #[test]
+    fn test_parallel_apply_many_dummies() {
+        const DUMMY_COUNT: usize = 256;
+        const PARALLEL: usize = 8;
+        const PLUGIN_COUNT: usize = 2;
+
+        let mut yaml = String::from("interfaces:\n");
+        for i in 0..DUMMY_COUNT {
+            yaml.push_str(&format!(
+                "- name: dummy{i}\n  type: dummy\n  state: up\n"
+            ));
+        }
+        let desired = NetworkState::new_from_yaml(&yaml).unwrap();
+        let mut share_data = WorkFlowShareData {
+            merged_state: Some(
+                MergedNetworkState::new(
+                    desired.clone(),
+                    NetworkState::new(),
+                    false,
+                    false,
+                )
+                .unwrap(),
+            ),
+            desired_state: Some(desired.clone()),
+            changes_net_state: true,
+            skip_dhcp: true,
+            apply_reply_count: PLUGIN_COUNT,
+            query_apply_plugin_count: PLUGIN_COUNT,
+            ..Default::default()
+        };
+        let mut opt = NipartApplyOption::default();
+        opt.parallel = Some(PARALLEL as u32);
+        let mut task = Task::new(
+            TEST_UUID,
+            TaskKind::ApplyNetState(opt.clone()),
+            PLUGIN_COUNT,
+            TEST_TIMEOUT,
+        );
+
+        let events = task.gen_request_apply(opt, &share_data);
+
+        let mut applied: Vec<String> = Vec::new();
+        for event in events.iter() {
+            if let NipartPluginEvent::ApplyNetState(state, _) = &event.plugin {
+                applied.extend(
+                    state
+                        .interfaces
+                        .kernel_ifaces
+                        .values()
+                        .filter(|i| i.for_apply.is_some())
+                        .map(|i| i.merged.name().to_string()),
+                );
+            }
+        }
+        assert_eq!(events.len(), PARALLEL);
+        // Every dummy is applied by exactly one sub-apply
+        applied.sort_unstable();
+        let mut expected: Vec<String> =
+            (0..DUMMY_COUNT).map(|i| format!("dummy{i}")).collect();
+        expected.sort_unstable();
+        assert_eq!(applied, expected);
+
+        // Each plugin replies to every sub-apply in arbitrary order
+        assert_eq!(task.expected_reply_count, PARALLEL * PLUGIN_COUNT);
+        for _ in 0..PARALLEL * PLUGIN_COUNT {
+            assert!(!task.is_done());
+            task.add_reply(gen_plugin_reply(
+                NipartPluginEvent::ApplyNetStateReply,
+            ));
+        }
+        assert!(task.is_done());
+        apply_net_state(&task, &mut share_data).unwrap();
+
+        // All dummies found after concurrent sub-applies
+        let mut verify_task = Task::new(
+            TEST_UUID,
+            TaskKind::QueryRelatedNetState,
+            1,
+            TEST_TIMEOUT,
+        );
+        verify_task.add_reply(gen_plugin_reply(
+            NipartPluginEvent::QueryNetStateReply(Box::new(desired), 0),
+        ));
+        post_apply_query_related_state(&verify_task, &mut share_data).unwrap();
+    }
+
+    #[test]
+    fn test_apply_dns_only_skip_iface_apply() {
+        let mut plugins = PluginRoles::default();
+        plugins.insert("test", vec![NipartRole::QueryAndApply]);
+        plugins.insert("dhcp", vec![NipartRole::Dhcp]);
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            dns-resolver:
+              config:
+                server:
+                - 192.0.2.1",
+        )
+        .unwrap();
+        let opt = NipartApplyOption::default();
+        let (mut workflow, mut share_data) = WorkFlow::new_apply_net_state(
+            desired,
+            opt.clone(),
+            TEST_UUID,
+            &plugins,
+            TEST_TIMEOUT,
+        )
+        .unwrap();
+        assert!(share_data.skip_iface_apply);
+
+        // Pre-apply query does not involve DHCP plugin
+        let events = workflow.process(&mut share_data).unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0].plugin,
+            NipartPluginEvent::QueryRelatedNetState(_)
+        ));
+
+        workflow.add_reply(gen_plugin_reply(
+            NipartPluginEvent::QueryNetStateReply(
+                Box::new(NetworkState::new()),
+                0,
+            ),
+        ));
+        let events = workflow.process(&mut share_data).unwrap();
+        let locks = get_lock_requests(&events);
+        assert!(
+            locks.contains(&(NipartLockEntry::Dns, NipartLockMode::Exclusive))
+        );
+        assert!(!locks
+            .iter()
+            .any(|(e, _)| matches!(e, NipartLockEntry::Interface(_))));
+
+        let mut apply_task = Task::new(
+            TEST_UUID,
+            TaskKind::ApplyNetState(opt.clone()),
+            1,
+            TEST_TIMEOUT,
+        );
+        let events = apply_task.gen_request_apply(opt, &share_data);
+        assert_eq!(events.len(), 1);
+        if let NipartPluginEvent::ApplyNetState(merged_state, _) =
+            &events[0].plugin
+        {
+            assert!(merged_state.interfaces.kernel_ifaces.is_empty());
+        } else {
+            panic!("Expecting ApplyNetState, got {:?}", events[0].plugin);
+        }
+    }
+
+    #[test]
+    fn test_apply_persistent_only_no_running_state_change() {
+        let mut plugins = PluginRoles::default();
+        plugins.insert("test", vec![NipartRole::QueryAndApply]);
+        let desired = gen_dummies_state(&[("dummy1", 9000)]);
+        let mut opt = NipartApplyOption::default();
+        opt.persistent_only = true;
+        let (mut workflow, mut share_data) = WorkFlow::new_apply_net_state(
+            desired.clone(),
+            opt,
+            TEST_UUID,
+            &plugins,
+            TEST_TIMEOUT,
+        )
+        .unwrap();
+        assert!(!share_data.changes_net_state);
+
+        let mut all_events = workflow.process(&mut share_data).unwrap();
+        workflow.add_reply(gen_plugin_reply(
+            NipartPluginEvent::QueryNetStateReply(
+                Box::new(gen_dummies_state(&[("dummy1", 1500)])),
+                0,
+            ),
+        ));
+        let events = workflow.process(&mut share_data).unwrap();
+        // Exclusive lock on changed interface, but not the whole network
+        // state as running network state is untouched.
+        let locks = get_lock_requests(&events);
+        assert!(locks
+            .iter()
+            .any(|(e, _)| matches!(e, NipartLockEntry::Interface(_))));
+        assert!(!locks.iter().any(|(e, _)| *e == NipartLockEntry::NetState));
+        all_events.extend(events);
+
+        workflow.add_reply(gen_plugin_reply(NipartPluginEvent::LockReply));
+        let events = workflow.process(&mut share_data).unwrap();
+        assert!(events.iter().any(|e| e.plugin
+            == NipartPluginEvent::Commit(Box::new(desired.clone()))));
+        all_events.extend(events);
+
+        workflow.add_reply(gen_plugin_reply(NipartPluginEvent::CommitReply));
+        let events = workflow.process(&mut share_data).unwrap();
+        assert!(events
+            .iter()
+            .any(|e| matches!(e.user, NipartUserEvent::ApplyNetStateReply(_))));
+        all_events.extend(events);
+
+        assert!(workflow.is_done());
+        assert!(!all_events.iter().any(|e| matches!(
+            e.plugin,
+            NipartPluginEvent::ApplyNetState(_, _)
+                | NipartPluginEvent::ApplyDhcpConfig(_)
+        )));
+    }
+
+    #[test]
+    fn test_apply_timeout_propagate_to_tasks() {
+        let mut plugins = PluginRoles::default();
+        plugins.insert("test", vec![NipartRole::QueryAndApply]);
+        let timeout = DEFAULT_TIMEOUT * 4;
+        let (workflow, _) = WorkFlow::new_apply_net_state(
+            gen_dummies_state(&[("dummy1", 1500)]),
+            NipartApplyOption::default(),
+            TEST_UUID,
+            &plugins,
+            timeout,
+        )
+        .unwrap();
+
+        assert!(workflow.tasks.iter().all(|t| t.timeout == timeout));
+        // Verification retry extended proportionally with same interval
+        let verify_task =
+            workflow.tasks.iter().find(|t| t.step == "verify").unwrap();
+        assert_eq!(verify_task.max_retry_count, VERIFY_RETRY_COUNT * 4);
+        assert_eq!(verify_task.retry_interval_mills, VERIFY_RETRY_INTERVAL);
+    }
+
+    fn gen_skip_pre_query_workflow(
+        confirm_timeout: Option<u32>,
+    ) -> Result<(WorkFlow, WorkFlowShareData), nipart::NipartError> {
+        let mut plugins = PluginRoles::default();
+        plugins.insert("test", vec![NipartRole::QueryAndApply]);
+        let mut opt = NipartApplyOption::default();
+        opt.skip_pre_query = true;
+        opt.confirm_timeout = confirm_timeout;
+        WorkFlow::new_apply_net_state(
+            gen_dummies_state(&[("dummy1", 1500)]),
+            opt,
+            TEST_UUID,
+            &plugins,
+            TEST_TIMEOUT,
+        )
+    }
+
+    #[test]
+    fn test_apply_skip_pre_query() {
+        let (workflow, share_data) = gen_skip_pre_query_workflow(None).unwrap();
+
+        // Lock is the first task, no pre-apply query
+        assert!(matches!(workflow.tasks[0].kind, TaskKind::Lock));
+        let query_tasks: Vec<&Task> = workflow
+            .tasks
+            .iter()
+            .filter(|t| matches!(t.kind, TaskKind::QueryRelatedNetState))
+            .collect();
+        // Only the verification is still querying related network state
+        assert_eq!(query_tasks.len(), 1);
+        assert_eq!(query_tasks[0].step, "verify");
+        // Merged against empty current state
+        assert!(share_data
+            .merged_state
+            .as_ref()
+            .unwrap()
+            .interfaces
+            .kernel_ifaces["dummy1"]
+            .is_changed());
+    }
+
+    #[test]
+    fn test_apply_skip_pre_query_with_confirm() {
+        let result = gen_skip_pre_query_workflow(Some(60));
+
+        assert_eq!(
+            result.err().map(|e| e.kind),
+            Some(nipart::ErrorKind::InvalidArgument)
+        );
+    }
+
+    fn gen_remove_dhcp_iface_apply_events(
+        keep_dhcp_lease: bool,
+    ) -> Vec<NipartEvent> {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: absent",
+        )
+        .unwrap();
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv4:
+                enabled: true
+                dhcp: true",
+        )
+        .unwrap();
+        let share_data = WorkFlowShareData {
+            merged_state: Some(
+                MergedNetworkState::new(desired.clone(), current, false, false)
+                    .unwrap(),
+            ),
+            desired_state: Some(desired),
+            changes_net_state: true,
+            apply_reply_count: 2,
+            query_apply_plugin_count: 1,
+            ..Default::default()
+        };
+        let mut opt = NipartApplyOption::default();
+        opt.keep_dhcp_lease = keep_dhcp_lease;
+        let mut task = Task::new(
+            TEST_UUID,
+            TaskKind::ApplyNetState(opt.clone()),
+            2,
+            TEST_TIMEOUT,
+        );
+        task.gen_request_apply(opt, &share_data)
+    }
+
+    #[test]
+    fn test_remove_dhcp_iface_release_lease() {
+        let events = gen_remove_dhcp_iface_apply_events(false);
+
+        let dhcp_event = events
+            .iter()
+            .find(|e| e.dst == NipartEventAddress::Dhcp)
+            .unwrap();
+        if let NipartPluginEvent::ApplyDhcpConfig(confs) = &dhcp_event.plugin {
+            assert_eq!(confs.len(), 1);
+            if let NipartDhcpConfig::V4(conf) = &confs[0] {
+                assert_eq!(conf.iface, "dummy1");
+                assert!(!conf.enabled);
+            } else {
+                panic!("Expecting DHCPv4 config, got {:?}", confs[0]);
+            }
+        } else {
+            panic!("Expecting ApplyDhcpConfig, got {:?}", dhcp_event.plugin);
+        }
+    }
+
+    #[test]
+    fn test_remove_dhcp_iface_keep_lease() {
+        let events = gen_remove_dhcp_iface_apply_events(true);
+
+        for event in events.iter().filter(|e| e.dst == NipartEventAddress::Dhcp)
+        {
+            if let NipartPluginEvent::ApplyDhcpConfig(confs) = &event.plugin {
+                assert!(confs.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_apply_dhcp_without_dhcp_plugin() {
+        let mut plugins = PluginRoles::default();
+        plugins.insert("test", vec![NipartRole::QueryAndApply]);
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ipv4:
+                enabled: true
+                dhcp: true",
+        )
+        .unwrap();
+
+        let result = WorkFlow::new_apply_net_state(
+            desired,
+            NipartApplyOption::default(),
+            TEST_UUID,
+            &plugins,
+            TEST_TIMEOUT,
+        );
+
+        let e = result.err().unwrap();
+        assert_eq!(e.kind, nipart::ErrorKind::NotSupportedError);
+        assert!(e.msg.contains("eth1"));
+        assert!(e.msg.contains(&NipartRole::Dhcp.to_string()));
+    }
+
+    #[test]
+    fn test_apply_static_ip_without_dhcp_plugin() {
+        let mut plugins = PluginRoles::default();
+        plugins.insert("test", vec![NipartRole::QueryAndApply]);
+
+        let (_, share_data) = WorkFlow::new_apply_net_state(
+            gen_dummies_state(&[("dummy1", 1500)]),
+            NipartApplyOption::default(),
+            TEST_UUID,
+            &plugins,
+            TEST_TIMEOUT,
+        )
+        .unwrap();
+
+        assert!(share_data.skip_dhcp);
+        assert!(!share_data.skip_iface_apply);
+    }
+
+    #[test]
+    fn test_apply_trace_timeline_in_order() {
+        let mut plugins = PluginRoles::default();
+        plugins.insert("test", vec![NipartRole::QueryAndApply]);
+        let desired = gen_dummies_state(&[("dummy1", 1500)]);
+        let mut opt = NipartApplyOption::default();
+        opt.trace = true;
+        let (mut workflow, mut share_data) = WorkFlow::new_apply_net_state(
+            desired.clone(),
+            opt,
+            TEST_UUID,
+            &plugins,
+            TEST_TIMEOUT,
+        )
+        .unwrap();
+
+        workflow.process(&mut share_data).unwrap();
+        for reply in [
+            NipartPluginEvent::QueryNetStateReply(
+                Box::new(NetworkState::new()),
+                0,
+            ),
+            NipartPluginEvent::LockReply,
+            NipartPluginEvent::RunDispatchScriptsReply,
+            NipartPluginEvent::ApplyNetStateReply,
+            NipartPluginEvent::QueryNetStateReply(Box::new(desired), 0),
+            NipartPluginEvent::RunDispatchScriptsReply,
+        ] {
+            workflow.add_reply(gen_plugin_reply(reply));
+            workflow.process(&mut share_data).unwrap();
+        }
+        workflow.add_reply(gen_plugin_reply(NipartPluginEvent::CommitReply));
+        let events = workflow.process(&mut share_data).unwrap();
+        assert!(workflow.is_done());
+
+        let timeline = events
+            .iter()
+            .find_map(|e| {
+                if let NipartUserEvent::ApplyNetStateTraceReply(_, timeline) =
+                    &e.user
+                {
+                    Some(timeline.clone())
+                } else {
+                    None
+                }
+            })
+            .unwrap();
+        let steps: Vec<&str> =
+            timeline.iter().map(|e| e.step.as_str()).collect();
+        let positions: Vec<usize> =
+            ["query", "lock", "apply", "verify", "commit"]
+                .iter()
+                .map(|step| steps.iter().position(|s| s == step).unwrap())
+                .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+        assert!(timeline
+            .windows(2)
+            .all(|w| w[0].elapsed_millis <= w[1].elapsed_millis));
+    }
 }
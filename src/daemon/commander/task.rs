@@ -3,8 +3,8 @@
 use std::time::SystemTime;
 
 use nipart::{
-    NetworkCommitQueryOption, NipartApplyOption, NipartDhcpLease, NipartEvent,
-    NipartLogLevel, NipartQueryOption,
+    NetworkCommitQueryOption, NipartApplyOption, NipartDhcpLease,
+    NipartDispatchPhase, NipartEvent, NipartLogLevel, NipartQueryOption,
 };
 
 use super::WorkFlowShareData;
@@ -14,6 +14,9 @@ use crate::u128_to_uuid_string;
 pub(crate) struct Task {
     pub(crate) uuid: u128,
     pub(crate) kind: TaskKind,
+    /// Label of workflow step used by event tracing, default to
+    /// [TaskKind::step_name()]
+    pub(crate) step: &'static str,
     pub(crate) expected_reply_count: usize,
     pub(crate) replies: Vec<NipartEvent>,
     /// timeout in seconds
@@ -39,28 +42,23 @@ impl Task {
     ) -> Self {
         Self {
             uuid,
+            step: kind.step_name(),
             kind,
             expected_reply_count,
             replies: Vec::new(),
             timeout,
-            deadline: {
-                SystemTime::now()
-                    .checked_add(std::time::Duration::from_millis(
-                        timeout.into(),
-                    ))
-                    .unwrap_or_else(|| {
-                        log::warn!(
-                            "Timeout {timeout} has cause SystemTime overflow"
-                        );
-                        SystemTime::now()
-                    })
-            },
+            deadline: gen_deadline(timeout),
             retry_interval_mills: 0,
             retry_count: 0,
             max_retry_count: 0,
         }
     }
 
+    // Count timeout from now instead of task creation
+    pub(crate) fn reset_deadline(&mut self) {
+        self.deadline = gen_deadline(self.timeout);
+    }
+
     pub(crate) fn is_expired(&self) -> bool {
         SystemTime::now() >= self.deadline && !self.is_done()
     }
@@ -103,7 +101,7 @@ impl Task {
     }
 
     pub(crate) fn gen_request(
-        &self,
+        &mut self,
         share_data: &WorkFlowShareData,
     ) -> Vec<NipartEvent> {
         let mut events = match &self.kind {
@@ -132,6 +130,10 @@ impl Task {
             }
             TaskKind::Commit => self.gen_request_commit(share_data),
             TaskKind::Lock => self.gen_request_lock(share_data),
+            TaskKind::RunDispatchScripts(phase) => {
+                self.gen_request_run_dispatch_scripts(*phase, share_data)
+            }
+            TaskKind::WaitConfirm => vec![self.gen_request_wait_confirm()],
         };
         if self.retry_count != 0 {
             for event in &mut events {
@@ -156,6 +158,8 @@ pub(crate) enum TaskKind {
     QueryCommits(NetworkCommitQueryOption),
     Commit,
     Lock,
+    RunDispatchScripts(NipartDispatchPhase),
+    WaitConfirm,
 }
 
 impl std::fmt::Display for TaskKind {
@@ -176,7 +180,37 @@ impl std::fmt::Display for TaskKind {
                 Self::QueryCommits(_) => "task_kind.query_commits",
                 Self::Commit => "task_kind.commit",
                 Self::Lock => "task_kind.lock",
+                Self::RunDispatchScripts(_) => "task_kind.run_dispatch_scripts",
+                Self::WaitConfirm => "task_kind.wait_confirm",
             }
         )
     }
 }
+
+impl TaskKind {
+    pub(crate) fn step_name(&self) -> &'static str {
+        match self {
+            Self::QueryPluginInfo => "query_plugin_info",
+            Self::QueryNetState(_) | Self::QueryRelatedNetState => "query",
+            Self::ApplyNetState(_) => "apply",
+            Self::QueryLogLevel => "query_log_level",
+            Self::ChangeLogLevel(_) => "change_log_level",
+            Self::ApplyDhcpLease(_) => "apply_dhcp_lease",
+            Self::Quit => "quit",
+            Self::QueryCommits(_) => "query_commits",
+            Self::Commit => "commit",
+            Self::Lock => "lock",
+            Self::RunDispatchScripts(_) => "dispatch",
+            Self::WaitConfirm => "wait_confirm",
+        }
+    }
+}
+
+fn gen_deadline(timeout: u32) -> SystemTime {
+    SystemTime::now()
+        .checked_add(std::time::Duration::from_millis(timeout.into()))
+        .unwrap_or_else(|| {
+            log::warn!("Timeout {timeout} has cause SystemTime overflow");
+            SystemTime::now()
+        })
+}
@@ -1,13 +1,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::collections::HashMap;
+use std::time::Instant;
 
 use nipart::{
     ErrorKind, MergedNetworkState, NetworkState, NipartError, NipartEvent,
-    NipartEventAddress, NipartPluginEvent, NipartUserEvent, DEFAULT_TIMEOUT,
+    NipartEventAddress, NipartLockEntry, NipartPluginEvent, NipartQueryOption,
+    NipartTraceEntry, NipartUserEvent, DEFAULT_TIMEOUT,
 };
 
-use super::Task;
+use super::{NetStateCache, NetStateFingerprint, Task, TaskKind};
 use crate::u128_to_uuid_string;
 
 pub(crate) type TaskCallBackFn =
@@ -18,6 +20,73 @@ pub(crate) struct WorkFlowShareData {
     pub(crate) desired_state: Option<NetworkState>,
     pub(crate) pre_apply_state: Option<NetworkState>,
     pub(crate) merged_state: Option<MergedNetworkState>,
+    /// Warnings raised when merging desired state of `apply_net_state`
+    /// workflow, will be included in `ApplyNetStateReply`
+    pub(crate) apply_warnings: Vec<String>,
+    /// Query option of `query_net_state` workflow
+    pub(crate) query_option: Option<NipartQueryOption>,
+    /// Result of `query_net_state` workflow, will be stored into
+    /// [NetStateCache] once workflow finished
+    pub(crate) queried_state: Option<NetworkState>,
+    /// Generation of [NetStateCache] when workflow started
+    pub(crate) cache_generation: u64,
+    /// Hostname, DNS and OVS database snapshot of [NetStateCache] when
+    /// `query_net_state` workflow started
+    pub(crate) cache_fingerprint: NetStateFingerprint,
+    /// Whether this workflow might change network state, if so
+    /// [NetStateCache] will be invalidated on start and finish
+    pub(crate) changes_net_state: bool,
+    /// [nipart::NipartApplyOption.force] of `apply_net_state` workflow
+    pub(crate) apply_force: bool,
+    /// Desired state of `apply_net_state` workflow holds no interface,
+    /// hence DHCP plugin is not involved and no interface is locked
+    pub(crate) skip_iface_apply: bool,
+    /// No plugin provides [nipart::NipartRole::Dhcp] and no interface in
+    /// desired state requires DHCP, hence DHCP plugin is not involved
+    pub(crate) skip_dhcp: bool,
+    /// Reply count expected from single `ApplyNetState` request
+    pub(crate) apply_reply_count: usize,
+    /// Count of plugins with [nipart::NipartRole::QueryAndApply], each of
+    /// them replies to every sub-apply of parallel apply
+    pub(crate) query_apply_plugin_count: usize,
+    /// Set by task callback to finish the workflow without running
+    /// remaining tasks
+    pub(crate) skip_remaining_tasks: bool,
+    /// Network state stored in commit requested by
+    /// [NipartQueryOption.since_commit]
+    pub(crate) commit_state: Option<NetworkState>,
+    /// Interfaces marked as `state: ignore` in saved network state, kept
+    /// untouched by `apply_net_state` workflow unless desired state
+    /// mentions them
+    pub(crate) saved_ignored_ifaces: Vec<String>,
+    /// Timeline of events when [nipart::NipartApplyOption.trace] is set,
+    /// will be included in `ApplyNetStateTraceReply`
+    pub(crate) trace: Option<Vec<NipartTraceEntry>>,
+    /// Start time of traced workflow
+    pub(crate) trace_start: Option<Instant>,
+    /// Lock entries granted to this workflow and not released yet
+    pub(crate) locked_entries: Vec<NipartLockEntry>,
+}
+
+impl WorkFlowShareData {
+    pub(crate) fn enable_trace(&mut self) {
+        self.trace = Some(Vec::new());
+        self.trace_start = Some(Instant::now());
+    }
+
+    pub(crate) fn record_trace(&mut self, step: &str, event: &NipartEvent) {
+        if let Some(trace) = self.trace.as_mut() {
+            let elapsed_millis = self
+                .trace_start
+                .map(|s| s.elapsed().as_millis())
+                .unwrap_or_default()
+                .try_into()
+                .unwrap_or(u64::MAX);
+            let entry = NipartTraceEntry::new(elapsed_millis, step, event);
+            log::info!("Trace {}: {entry}", event.uuid);
+            trace.push(entry);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -29,6 +98,7 @@ pub(crate) struct WorkFlow {
     pub(crate) cur_task_idx: usize,
     init_request_sent: bool,
     is_fail: bool,
+    is_finished_early: bool,
 }
 
 impl std::fmt::Display for WorkFlow {
@@ -53,15 +123,16 @@ impl WorkFlow {
             cur_task_idx: 0,
             init_request_sent: false,
             is_fail: false,
+            is_finished_early: false,
         }
     }
 
     pub(crate) fn gen_cur_task_request_event(
-        &self,
+        &mut self,
         share_data: &mut WorkFlowShareData,
     ) -> Result<Vec<NipartEvent>, NipartError> {
-        if let Some(task) = self.cur_task() {
-            Ok(task.gen_request(share_data))
+        if let Some(task) = self.cur_task_mut() {
+            Ok(tag_request_events(task, share_data))
         } else {
             Err(NipartError::new(
                 ErrorKind::Bug,
@@ -105,7 +176,7 @@ impl WorkFlow {
                     if cur_task.can_retry() {
                         log::debug!("Retry on error {e}");
                         cur_task.retry();
-                        return Ok(cur_task.gen_request(share_data));
+                        return Ok(tag_request_events(cur_task, share_data));
                     }
                 }
                 Err(e)
@@ -128,13 +199,30 @@ impl WorkFlow {
     }
 
     pub(crate) fn is_done(&self) -> bool {
-        self.tasks.len() == self.cur_task_idx + 1 && self.cur_task_is_done()
+        self.is_finished_early
+            || (self.tasks.len() == self.cur_task_idx + 1
+                && self.cur_task_is_done())
     }
 
     pub(crate) fn is_fail(&self) -> bool {
         self.is_fail
     }
 
+    // Release all locks still held by this workflow
+    fn gen_unlock_events(
+        &self,
+        share_data: &mut WorkFlowShareData,
+    ) -> Vec<NipartEvent> {
+        let timeout = self
+            .cur_task()
+            .map(|t| t.timeout)
+            .unwrap_or(DEFAULT_TIMEOUT);
+        share_data
+            .gen_unlock_event(self.uuid, timeout)
+            .into_iter()
+            .collect()
+    }
+
     pub(crate) fn add_reply(&mut self, reply: NipartEvent) {
         if let Some(task) = self.cur_task_mut() {
             task.add_reply(reply);
@@ -155,7 +243,27 @@ impl WorkFlow {
         }
 
         if self.is_expired() {
-            return Ok(vec![NipartEvent::new(
+            let mut ret = self.gen_unlock_events(share_data);
+            if self.is_waiting_confirm() {
+                ret.extend(self.gen_rollback_events(share_data));
+                ret.push(NipartEvent::new_with_uuid(
+                    self.uuid,
+                    NipartUserEvent::Error(NipartError::new(
+                        ErrorKind::RolledBack,
+                        format!(
+                            "No confirmation received in time, rolled back \
+                            {} {}",
+                            self.uuid, self.kind
+                        ),
+                    )),
+                    NipartPluginEvent::None,
+                    NipartEventAddress::Daemon,
+                    NipartEventAddress::User,
+                    DEFAULT_TIMEOUT,
+                ));
+                return Ok(ret);
+            }
+            ret.push(NipartEvent::new(
                 NipartUserEvent::Error(NipartError::new(
                     ErrorKind::Timeout,
                     format!("Timeout on action {} {}", self.uuid, self.kind),
@@ -164,21 +272,47 @@ impl WorkFlow {
                 NipartEventAddress::Daemon,
                 NipartEventAddress::User,
                 DEFAULT_TIMEOUT,
-            )]);
+            ));
+            return Ok(ret);
         }
 
         if self.cur_task_is_done() {
             match self.cur_task_callback(share_data) {
-                Ok(events) => ret.extend(events),
+                Ok(events) => {
+                    if let Some(task) = self.cur_task() {
+                        for event in events.iter() {
+                            share_data.record_trace(task.step, event);
+                        }
+                    }
+                    ret.extend(events);
+                    if share_data.skip_remaining_tasks {
+                        log::debug!(
+                            "Workflow {self} finished without running \
+                            remaining tasks"
+                        );
+                        self.is_finished_early = true;
+                        return Ok(ret);
+                    }
+                }
                 Err(e) => {
                     self.is_fail = true;
+                    // Release locks before rollback, otherwise the revert
+                    // apply will wait for locks held by this failed workflow
+                    let mut ret = self.gen_unlock_events(share_data);
+                    ret.extend(self.gen_rollback_events(share_data));
                     let mut error_event: NipartEvent = e.into();
                     error_event.uuid = self.uuid;
-                    return Ok(vec![error_event]);
+                    ret.push(error_event);
+                    return Ok(ret);
                 }
             }
             if self.cur_task_idx + 1 < self.tasks.len() {
                 self.cur_task_idx += 1;
+                if let Some(task) = self.cur_task_mut() {
+                    if matches!(task.kind, TaskKind::WaitConfirm) {
+                        task.reset_deadline();
+                    }
+                }
                 ret.extend(self.gen_cur_task_request_event(share_data)?);
             }
         }
@@ -187,10 +321,24 @@ impl WorkFlow {
     }
 }
 
+// Label request events with workflow step and record them when tracing.
+fn tag_request_events(
+    task: &mut Task,
+    share_data: &mut WorkFlowShareData,
+) -> Vec<NipartEvent> {
+    let mut events = task.gen_request(share_data);
+    for event in events.iter_mut() {
+        event.step = Some(task.step.to_string());
+        share_data.record_trace(task.step, event);
+    }
+    events
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct WorkFlowQueue {
     pub(crate) workflows: HashMap<u128, WorkFlow>,
     pub(crate) share_data: HashMap<u128, WorkFlowShareData>,
+    pub(crate) net_state_cache: NetStateCache,
 }
 
 impl WorkFlowQueue {
@@ -200,20 +348,33 @@ impl WorkFlowQueue {
         Self {
             workflows: HashMap::with_capacity(Self::INIT_CAPACITY),
             share_data: HashMap::with_capacity(Self::INIT_CAPACITY),
+            net_state_cache: NetStateCache::default(),
         }
     }
 
     pub(crate) fn add_workflow(
         &mut self,
         workflow: WorkFlow,
-        share_data: WorkFlowShareData,
+        mut share_data: WorkFlowShareData,
     ) {
+        if share_data.changes_net_state {
+            self.net_state_cache.invalidate();
+        }
+        share_data.cache_generation = self.net_state_cache.generation();
+        if share_data.query_option.is_some() {
+            share_data.cache_fingerprint = self.net_state_cache.fingerprint();
+        }
         self.share_data.insert(workflow.uuid, share_data);
         self.workflows.insert(workflow.uuid, workflow);
     }
 
     pub(crate) fn add_reply(&mut self, reply: NipartEvent) {
         if let Some(workflow) = self.workflows.get_mut(&reply.uuid) {
+            if let (Some(share_data), Some(task)) =
+                (self.share_data.get_mut(&reply.uuid), workflow.cur_task())
+            {
+                share_data.record_trace(task.step, &reply);
+            }
             workflow.add_reply(reply);
         }
     }
@@ -255,9 +416,33 @@ impl WorkFlowQueue {
                 } else if workflow.is_expired() {
                     log::debug!("Workflow {workflow} expired");
                 }
+                if let Some(share_data) = self.share_data.remove(&uuid) {
+                    self.update_net_state_cache(&workflow, share_data);
+                }
             }
         }
 
         Ok(ret)
     }
+
+    fn update_net_state_cache(
+        &mut self,
+        workflow: &WorkFlow,
+        share_data: WorkFlowShareData,
+    ) {
+        if share_data.changes_net_state {
+            self.net_state_cache.invalidate();
+        } else if workflow.is_done() && !workflow.is_fail() {
+            if let (Some(opt), Some(state)) =
+                (share_data.query_option.as_ref(), share_data.queried_state)
+            {
+                self.net_state_cache.insert(
+                    opt,
+                    state,
+                    share_data.cache_generation,
+                    share_data.cache_fingerprint,
+                );
+            }
+        }
+    }
 }
@@ -18,8 +18,12 @@ use self::switch::start_event_switch_thread;
 pub(crate) const DEFAULT_LOG_LEVEL: log::LevelFilter = log::LevelFilter::Debug;
 pub(crate) const MPSC_CHANNLE_SIZE: usize = 64;
 
+pub(crate) static DAEMON_START_TIME: std::sync::OnceLock<std::time::Instant> =
+    std::sync::OnceLock::new();
+
 #[tokio::main(flavor = "multi_thread", worker_threads = 50)]
 async fn main() -> Result<(), NipartError> {
+    DAEMON_START_TIME.get_or_init(std::time::Instant::now);
     init_logger();
 
     // TODO: Find a way to refresh plugins in switch
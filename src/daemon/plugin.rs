@@ -11,6 +11,7 @@ use nipart::{
 use nipart_plugin_baize::NipartPluginBaize;
 use nipart_plugin_mozim::NipartPluginMozim;
 use nipart_plugin_nispor::NipartPluginNispor;
+use nipart_plugin_ovs::NipartPluginOvs;
 use nipart_plugin_sima::NipartPluginSima;
 use nipart_plugin_smith::NipartPluginSmith;
 use tokio::sync::mpsc::{Receiver, Sender};
@@ -160,6 +161,7 @@ impl Plugins {
 
     async fn load_native_plugins(&mut self) -> Result<(), NipartError> {
         self.insert(start_plugin::<NipartPluginNispor>().await?);
+        self.insert(start_plugin::<NipartPluginOvs>().await?);
         self.insert(start_plugin::<NipartPluginMozim>().await?);
         self.insert(start_plugin::<NipartPluginBaize>().await?);
         self.insert(start_plugin::<NipartPluginSima>().await?);
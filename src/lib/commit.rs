@@ -46,3 +46,30 @@ impl Default for NetworkCommit {
         }
     }
 }
+
+/// Record appended to the audit log by tracking plugin for every committed
+/// network state, stored as JSON lines in
+/// [NetworkAuditRecord::DEFAULT_PATH].
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct NetworkAuditRecord {
+    /// Time of commit
+    pub time: SystemTime,
+    /// ID of the commit created
+    pub commit_id: String,
+    /// Desired NetworkState of this commit with secrets hidden
+    pub state: NetworkState,
+}
+
+impl NetworkAuditRecord {
+    pub const DEFAULT_PATH: &'static str = "/var/lib/nipart/audit/audit.jsonl";
+
+    pub fn new(commit_id: String, mut state: NetworkState) -> Self {
+        state.hide_secrets();
+        Self {
+            time: SystemTime::now(),
+            commit_id,
+            state,
+        }
+    }
+}
@@ -5,6 +5,8 @@ use std::net::{Ipv4Addr, Ipv6Addr};
 use serde::{Deserialize, Serialize};
 
 const DEFAULT_DHCP_TIMEOUT: u32 = 30;
+// Route metric of DHCP gateway for interface with `autoconnect-priority: 0`
+const AUTOCONNECT_PRIORITY_BASE_METRIC: i64 = 1000;
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub enum NipartDhcpConfig {
@@ -67,6 +69,9 @@ pub struct NipartDhcpConfigV4 {
     pub client_id: Option<String>,
     pub enabled: bool,
     pub timeout: u32,
+    /// Route metric for default gateway learned from DHCP
+    #[serde(default)]
+    pub route_metric: Option<u32>,
 }
 
 impl NipartDhcpConfigV4 {
@@ -77,6 +82,12 @@ impl NipartDhcpConfigV4 {
             ..Default::default()
         }
     }
+
+    /// Higher `autoconnect-priority` means lower route metric
+    pub fn route_metric_from_priority(priority: i32) -> u32 {
+        (AUTOCONNECT_PRIORITY_BASE_METRIC - i64::from(priority))
+            .clamp(1, i64::from(u32::MAX)) as u32
+    }
 }
 
 impl Default for NipartDhcpConfigV4 {
@@ -86,6 +97,7 @@ impl Default for NipartDhcpConfigV4 {
             client_id: None,
             enabled: false,
             timeout: DEFAULT_DHCP_TIMEOUT,
+            route_metric: None,
         }
     }
 }
@@ -115,6 +127,12 @@ pub struct NipartDhcpLeaseV4 {
     pub prefix_length: u8,
     pub server_ip: Ipv4Addr,
     pub lease_time: u32,
+    #[serde(default)]
+    pub gateways: Vec<Ipv4Addr>,
+    /// Route metric for default gateways, copied from
+    /// [NipartDhcpConfigV4.route_metric]
+    #[serde(default)]
+    pub route_metric: Option<u32>,
 }
 
 impl NipartDhcpLeaseV4 {
@@ -131,6 +149,8 @@ impl NipartDhcpLeaseV4 {
             prefix_length,
             server_ip,
             lease_time,
+            gateways: Vec::new(),
+            route_metric: None,
         }
     }
 }
@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+use crate::{DispatchConfig, MergedNetworkState};
+
+#[derive(
+    Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default,
+)]
+#[non_exhaustive]
+pub enum NipartDispatchPhase {
+    /// Before applying the network state
+    #[default]
+    PreApply,
+    /// After the applied network state been verified
+    PostApply,
+}
+
+impl std::fmt::Display for NipartDispatchPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::PreApply => "pre_apply",
+                Self::PostApply => "post_apply",
+            }
+        )
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct NipartDispatchScript {
+    pub iface: String,
+    /// Bash script content
+    pub script: String,
+    /// Timeout in seconds
+    pub timeout: u32,
+}
+
+impl NipartDispatchScript {
+    pub fn new(iface: String, script: String, timeout: u32) -> Self {
+        Self {
+            iface,
+            script,
+            timeout,
+        }
+    }
+}
+
+impl std::fmt::Display for NipartDispatchScript {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "dispatch_script:{}", self.iface)
+    }
+}
+
+impl MergedNetworkState {
+    /// Get dispatch scripts for interfaces changed by this apply.
+    pub fn get_dispatch_scripts(
+        &self,
+        phase: NipartDispatchPhase,
+    ) -> Vec<NipartDispatchScript> {
+        let mut ret = Vec::new();
+        for merged_iface in self.interfaces.iter().filter(|i| i.is_changed()) {
+            let iface = &merged_iface.merged;
+            let dispatch = match iface.base_iface().dispatch.as_ref() {
+                Some(d) => d,
+                None => continue,
+            };
+            let script = if iface.is_up() {
+                match phase {
+                    NipartDispatchPhase::PreApply => {
+                        dispatch.pre_activation.as_ref()
+                    }
+                    NipartDispatchPhase::PostApply => {
+                        dispatch.post_activation.as_ref()
+                    }
+                }
+            } else {
+                match phase {
+                    NipartDispatchPhase::PreApply => {
+                        dispatch.pre_deactivation.as_ref()
+                    }
+                    NipartDispatchPhase::PostApply => {
+                        dispatch.post_deactivation.as_ref()
+                    }
+                }
+            };
+            if let Some(script) = script.filter(|s| !s.is_empty()) {
+                ret.push(NipartDispatchScript::new(
+                    iface.name().to_string(),
+                    script.to_string(),
+                    dispatch.timeout.unwrap_or(DispatchConfig::DEFAULT_TIMEOUT),
+                ));
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        DispatchConfig, MergedNetworkState, NetworkState, NipartDispatchPhase,
+    };
+
+    #[test]
+    fn test_dispatch_script_timeout() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              dispatch:
+                post-activation: echo dummy1
+                timeout: 30
+            - name: dummy2
+              type: dummy
+              state: up
+              dispatch:
+                post-activation: echo dummy2",
+        )
+        .unwrap();
+        let merged =
+            MergedNetworkState::new(desired, NetworkState::new(), false, false)
+                .unwrap();
+
+        let mut scripts =
+            merged.get_dispatch_scripts(NipartDispatchPhase::PostApply);
+        scripts.sort_unstable_by(|a, b| a.iface.cmp(&b.iface));
+
+        assert_eq!(scripts.len(), 2);
+        assert_eq!(scripts[0].iface, "dummy1");
+        assert_eq!(scripts[0].timeout, 30);
+        assert_eq!(scripts[1].iface, "dummy2");
+        assert_eq!(scripts[1].timeout, DispatchConfig::DEFAULT_TIMEOUT);
+        assert!(merged
+            .get_dispatch_scripts(NipartDispatchPhase::PreApply)
+            .is_empty());
+    }
+}
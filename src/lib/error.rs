@@ -20,6 +20,8 @@ pub enum ErrorKind {
     KernelIntegerRoundedError,
     SrIovVfNotFound,
     Timeout,
+    /// Changes has been reverted because of missing user confirmation
+    RolledBack,
 }
 
 impl std::fmt::Display for ErrorKind {
@@ -35,12 +37,52 @@ impl std::fmt::Display for ErrorKind {
 pub struct NipartError {
     pub kind: ErrorKind,
     pub msg: String,
+    /// The first difference found by verification, only set for
+    /// [ErrorKind::VerificationError] raised when comparing desired and
+    /// current state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_diff: Option<Box<NipartVerifyDiff>>,
 }
 
 impl NipartError {
     pub fn new(kind: ErrorKind, msg: String) -> Self {
-        Self { kind, msg }
+        Self {
+            kind,
+            msg,
+            verify_diff: None,
+        }
     }
+
+    pub(crate) fn new_verify_diff(
+        reference: String,
+        desired: &serde_json::Value,
+        current: &serde_json::Value,
+    ) -> Self {
+        Self {
+            kind: ErrorKind::VerificationError,
+            msg: format!(
+                "Verification failure: {reference} desire '{desired}', \
+                current '{current}'"
+            ),
+            verify_diff: Some(Box::new(NipartVerifyDiff {
+                reference,
+                desired: desired.clone(),
+                current: current.clone(),
+            })),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[non_exhaustive]
+/// Property mismatch found when verifying applied network state
+pub struct NipartVerifyDiff {
+    /// Path to the mismatched property, e.g. `eth1.interface.mtu`
+    pub reference: String,
+    /// Desired value of the property
+    pub desired: serde_json::Value,
+    /// Current value of the property
+    pub current: serde_json::Value,
 }
 
 impl std::error::Error for NipartError {}
@@ -71,3 +113,48 @@ impl From<std::net::AddrParseError> for NipartError {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{ErrorKind, MergedNetworkState, NetworkState, NipartError};
+
+    #[test]
+    fn test_verify_mismatch_holds_diff() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 9000",
+        )
+        .unwrap();
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 1500",
+        )
+        .unwrap();
+        let merged =
+            MergedNetworkState::new(desired, current.clone(), false, false)
+                .unwrap();
+
+        let e = merged.verify(&current).unwrap_err();
+        assert_eq!(e.kind, ErrorKind::VerificationError);
+        let diff = e.verify_diff.as_ref().unwrap();
+        assert!(diff.reference.contains("dummy1"));
+        assert!(diff.reference.ends_with("mtu"));
+        assert_eq!(diff.desired, json!(9000));
+        assert_eq!(diff.current, json!(1500));
+
+        // The diff is kept when passing error through IPC
+        let new_e: NipartError =
+            serde_json::from_str(&serde_json::to_string(&e).unwrap()).unwrap();
+        assert_eq!(new_e.verify_diff, e.verify_diff);
+    }
+}
@@ -6,10 +6,14 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     NetworkCommit, NetworkCommitQueryOption, NetworkState, NipartApplyOption,
-    NipartError, NipartLogEntry, NipartLogLevel, NipartPluginEvent,
-    NipartPluginInfo, NipartQueryOption, NipartRole,
+    NipartDaemonInfo, NipartError, NipartLogEntry, NipartLogLevel,
+    NipartPluginEvent, NipartPluginInfo, NipartQueryOption, NipartRole,
 };
 
+/// Schema version of events between user and daemon. Increased when
+/// incompatible change introduced.
+pub const CUR_SCHEMA_VERSION: u32 = 1;
+
 #[derive(
     Deserialize, Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash,
 )]
@@ -64,6 +68,10 @@ pub struct NipartEvent {
     /// When Daemon received event with non-zero `postpone_millis`,
     /// it will postponed the process of this event. Often used for retry.
     pub postpone_millis: u32,
+    /// Label of workflow step this event belongs to, for example `lock`,
+    /// `apply` or `verify`. Set by daemon for events requested by workflow.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
 }
 
 impl std::fmt::Display for NipartEvent {
@@ -71,7 +79,7 @@ impl std::fmt::Display for NipartEvent {
         write!(
             f,
             "uuid:{} user:{} plugin:{} \
-            src:{} dst:{} timeout:{}ms{}",
+            src:{} dst:{} timeout:{}ms{}{}",
             self.uuid,
             self.user,
             self.plugin,
@@ -82,6 +90,11 @@ impl std::fmt::Display for NipartEvent {
                 format!(" postpone {}ms", self.postpone_millis)
             } else {
                 String::new()
+            },
+            if let Some(step) = self.step.as_ref() {
+                format!(" step:{step}")
+            } else {
+                String::new()
             }
         )
     }
@@ -104,6 +117,7 @@ impl NipartEvent {
             dst,
             timeout,
             postpone_millis: 0,
+            step: None,
         }
     }
 
@@ -123,6 +137,7 @@ impl NipartEvent {
             dst,
             timeout,
             postpone_millis: 0,
+            step: None,
         }
     }
 
@@ -173,6 +188,10 @@ pub enum NipartUserEvent {
     QueryPluginInfo,
     QueryPluginInfoReply(Vec<NipartPluginInfo>),
 
+    /// Query daemon version, uptime and loaded plugins.
+    Ping,
+    PingReply(Box<NipartDaemonInfo>),
+
     ChangeLogLevel(NipartLogLevel),
     QueryLogLevel,
     QueryLogLevelReply(HashMap<String, NipartLogLevel>),
@@ -181,7 +200,18 @@ pub enum NipartUserEvent {
     QueryNetStateReply(Box<NetworkState>),
 
     ApplyNetState(Box<NetworkState>, NipartApplyOption),
-    ApplyNetStateReply,
+    /// Holding warnings raised when sanitizing and merging desired state.
+    ApplyNetStateReply(Vec<String>),
+    /// Reply of `ApplyNetState` with [crate::NipartApplyOption.trace] set,
+    /// holding warnings and the timeline of events of this apply.
+    ApplyNetStateTraceReply(Vec<String>, Vec<NipartTraceEntry>),
+    /// Daemon notify user that desired state applied and verified, waiting
+    /// user to send `ConfirmApply` or `RollbackApply` with the same uuid.
+    ApplyNetStateWaitConfirm,
+    /// User confirm the apply action, reply with `ApplyNetStateReply`.
+    ConfirmApply,
+    /// User request rollback of the apply action, reply with `Error`.
+    RollbackApply,
 
     QueryCommits(NetworkCommitQueryOption),
     QueryCommitsReply(Box<Vec<NetworkCommit>>),
@@ -201,13 +231,23 @@ impl std::fmt::Display for NipartUserEvent {
                 Self::Error(_) => "error",
                 Self::QueryPluginInfo => "query_plugin_info",
                 Self::QueryPluginInfoReply(_) => "query_plugin_info_reply",
+                Self::Ping => "ping",
+                Self::PingReply(_) => "ping_reply",
                 Self::ChangeLogLevel(_) => "change_log_level",
                 Self::QueryLogLevel => "query_log_level",
                 Self::QueryLogLevelReply(_) => "query_log_level_reply",
                 Self::QueryNetState(_) => "query_netstate",
                 Self::QueryNetStateReply(_) => "query_netstate_reply",
                 Self::ApplyNetState(_, _) => "apply_netstate",
-                Self::ApplyNetStateReply => "apply_netstate_reply",
+                Self::ApplyNetStateReply(_) => "apply_netstate_reply",
+                Self::ApplyNetStateTraceReply(_, _) => {
+                    "apply_netstate_trace_reply"
+                }
+                Self::ApplyNetStateWaitConfirm => {
+                    "apply_netstate_wait_confirm"
+                }
+                Self::ConfirmApply => "confirm_apply",
+                Self::RollbackApply => "rollback_apply",
                 Self::QueryCommits(_) => "query_commits",
                 Self::QueryCommitsReply(_) => "query_commits_reply",
                 Self::Log(_) => "log",
@@ -215,3 +255,47 @@ impl std::fmt::Display for NipartUserEvent {
         )
     }
 }
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+/// Single hop of event within a workflow, recorded by daemon when
+/// [crate::NipartApplyOption.trace] is set.
+pub struct NipartTraceEntry {
+    /// Milliseconds since workflow started
+    pub elapsed_millis: u64,
+    /// Workflow step, for example `query`, `lock`, `apply`, `verify` or
+    /// `commit`
+    pub step: String,
+    pub src: NipartEventAddress,
+    pub dst: NipartEventAddress,
+    /// Name of user event and plugin event
+    pub event: String,
+}
+
+impl NipartTraceEntry {
+    pub fn new(elapsed_millis: u64, step: &str, event: &NipartEvent) -> Self {
+        Self {
+            elapsed_millis,
+            step: step.to_string(),
+            src: event.src.clone(),
+            dst: event.dst.clone(),
+            event: if matches!(event.plugin, NipartPluginEvent::None) {
+                event.user.to_string()
+            } else if matches!(event.user, NipartUserEvent::None) {
+                event.plugin.to_string()
+            } else {
+                format!("{}/{}", event.user, event.plugin)
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for NipartTraceEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "+{}ms {} {} -> {}: {}",
+            self.elapsed_millis, self.step, self.src, self.dst, self.event
+        )
+    }
+}
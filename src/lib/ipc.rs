@@ -10,9 +10,9 @@ use tokio::net::UnixStream;
 
 use crate::{
     ErrorKind, NetworkCommit, NetworkCommitQueryOption, NetworkState,
-    NipartApplyOption, NipartError, NipartEvent, NipartEventAddress,
-    NipartLogLevel, NipartPluginEvent, NipartPluginInfo, NipartQueryOption,
-    NipartUserEvent,
+    NipartApplyOption, NipartDaemonInfo, NipartError, NipartEvent,
+    NipartEventAddress, NipartLogLevel, NipartPluginEvent, NipartPluginInfo,
+    NipartQueryOption, NipartTraceEntry, NipartUserEvent,
 };
 
 pub const DEFAULT_TIMEOUT: u32 = 30000;
@@ -24,6 +24,10 @@ pub struct NipartConnection {
     pub path: String,
     pub(crate) socket: UnixStream,
     pub buffer: HashMap<u128, NipartEvent>,
+    // Whether we advertise support of compressed message to remote
+    pub(crate) compression: bool,
+    // Whether remote advertised support of compressed message
+    pub(crate) peer_compression: bool,
 }
 
 impl NipartConnection {
@@ -31,6 +35,26 @@ impl NipartConnection {
     // Only accept size smaller than 10 MiB
     pub const IPC_MAX_SIZE: usize = 1024 * 1024 * 10;
     const EVENT_BUFFER_SIZE: usize = 1024;
+    const CONNECT_RETRY_INITIAL_INTERVAL: u64 = 50;
+    const CONNECT_RETRY_MAX_INTERVAL: u64 = 1000;
+    /// Message larger than this size in bytes is compressed when both ends
+    /// support compression. Query result of ethernet interface with IP,
+    /// ethtool and route is about 2.4 KiB in JSON, so this threshold means
+    /// around 26 interfaces. Measured with zstd 1.5 level 3, the 242 KiB
+    /// JSON of 100 interfaces compressed to 2.2 KiB and 2.4 MiB of 1000
+    /// interfaces to 20 KiB, at about 3 GB/s on single core. Most IPC
+    /// messages are small requests and replies, this threshold skips them.
+    pub const IPC_COMPRESS_THRESHOLD: usize = 1024 * 64;
+    // JSON of network state is highly repetitive, hence allowing larger
+    // size after decompression.
+    const IPC_MAX_DECOMPRESSED_SIZE: usize = Self::IPC_MAX_SIZE * 10;
+    const IPC_COMPRESS_LEVEL: i32 = 3;
+    // The highest bits of message size header are used as flags as
+    // message size never exceeds IPC_MAX_SIZE.
+    const HEADER_FLAG_COMPRESSED: usize = 1 << (usize::BITS - 1);
+    const HEADER_FLAG_ACCEPT_COMPRESSED: usize = 1 << (usize::BITS - 2);
+    const HEADER_FLAGS: usize =
+        Self::HEADER_FLAG_COMPRESSED | Self::HEADER_FLAG_ACCEPT_COMPRESSED;
 
     pub async fn new() -> Result<Self, NipartError> {
         Self::new_with_path(Self::DEFAULT_SOCKET_PATH).await
@@ -52,15 +76,85 @@ impl NipartConnection {
         ))
     }
 
+    /// Similar to [NipartConnection::new()], but retry with exponential
+    /// backoff (from 50 milliseconds up to 1 second interval) when failed to
+    /// connect, for example daemon is still starting. Fail if still cannot
+    /// connect after `deadline` milliseconds.
+    pub async fn connect_with_retry(
+        deadline: u32,
+    ) -> Result<Self, NipartError> {
+        Self::connect_with_path_and_retry(Self::DEFAULT_SOCKET_PATH, deadline)
+            .await
+    }
+
+    /// Similar to [NipartConnection::new_with_path()], but retry like
+    /// [NipartConnection::connect_with_retry()].
+    pub async fn connect_with_path_and_retry(
+        socket_path: &str,
+        deadline: u32,
+    ) -> Result<Self, NipartError> {
+        let deadline = tokio::time::Instant::now()
+            + Duration::from_millis(deadline.into());
+        let mut interval = Self::CONNECT_RETRY_INITIAL_INTERVAL;
+        loop {
+            match Self::new_with_path(socket_path).await {
+                Ok(conn) => return Ok(conn),
+                Err(e) => {
+                    let now = tokio::time::Instant::now();
+                    if now >= deadline {
+                        return Err(NipartError::new(
+                            ErrorKind::Timeout,
+                            format!("Timeout on connecting daemon: {e}"),
+                        ));
+                    }
+                    log::debug!("{e}, retry in {interval} milliseconds");
+                    tokio::time::sleep(std::cmp::min(
+                        Duration::from_millis(interval),
+                        deadline - now,
+                    ))
+                    .await;
+                    interval = std::cmp::min(
+                        interval * 2,
+                        Self::CONNECT_RETRY_MAX_INTERVAL,
+                    );
+                }
+            }
+        }
+    }
+
     pub(crate) fn new_with_stream(path: &str, stream: UnixStream) -> Self {
         Self {
             path: path.to_string(),
             socket: stream,
             buffer: HashMap::with_capacity(Self::EVENT_BUFFER_SIZE),
             timeout: DEFAULT_TIMEOUT,
+            compression: false,
+            peer_compression: false,
         }
     }
 
+    /// Ping daemon to check whether it supports compressed message, if so,
+    /// advertise support of compression in the header of every following
+    /// message and compress message larger than
+    /// [NipartConnection::IPC_COMPRESS_THRESHOLD] using zstd.
+    /// The daemon enables compression on its side once received message
+    /// with this advertisement. Daemon without compression support is
+    /// never sent compressed message or advertisement.
+    /// Return whether compression is enabled.
+    pub async fn negotiate_compression(&mut self) -> Result<bool, NipartError> {
+        let info = self.ping_with_details().await?;
+        if info.ipc_compression {
+            self.compression = true;
+            self.peer_compression = true;
+        } else {
+            log::debug!(
+                "Daemon {} does not support IPC compression",
+                info.version
+            );
+        }
+        Ok(info.ipc_compression)
+    }
+
     pub fn new_abstract(name: &str) -> Result<Self, NipartError> {
         let addr =
             std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())
@@ -125,6 +219,31 @@ impl NipartConnection {
         }
     }
 
+    /// Query daemon version, uptime, schema version and loaded plugins
+    /// with their roles.
+    pub async fn ping_with_details(
+        &mut self,
+    ) -> Result<NipartDaemonInfo, NipartError> {
+        let request = NipartEvent::new(
+            NipartUserEvent::Ping,
+            NipartPluginEvent::None,
+            NipartEventAddress::User,
+            NipartEventAddress::Daemon,
+            self.timeout,
+        );
+        self.send(&request).await?;
+        let event = self.recv_reply(request.uuid, self.timeout).await?;
+
+        if let NipartUserEvent::PingReply(i) = event.user {
+            Ok(*i)
+        } else {
+            Err(NipartError::new(
+                ErrorKind::Bug,
+                format!("Invalid reply {event:?} for Ping"),
+            ))
+        }
+    }
+
     pub async fn query_log_level(
         &mut self,
     ) -> Result<HashMap<String, NipartLogLevel>, NipartError> {
@@ -173,16 +292,51 @@ impl NipartConnection {
     pub async fn query_net_state(
         &mut self,
         option: NipartQueryOption,
+    ) -> Result<NetworkState, NipartError> {
+        self.query_net_state_with_timeout(option, self.timeout)
+            .await
+    }
+
+    /// Same as [NipartConnection::query_net_state()] but use specified
+    /// timeout in milliseconds instead of [NipartConnection.timeout].
+    /// The whole query, including sending request to daemon, is aborted
+    /// with [ErrorKind::Timeout] when timeout reached, partial results are
+    /// not returned.
+    /// To cancel the query, drop the returned future, for example via
+    /// `tokio::select!`. After timeout or cancellation, the connection might
+    /// hold partial reply, please create new connection for further actions.
+    pub async fn query_net_state_with_timeout(
+        &mut self,
+        option: NipartQueryOption,
+        timeout_ms: u32,
     ) -> Result<NetworkState, NipartError> {
         let request = NipartEvent::new(
             NipartUserEvent::QueryNetState(option),
             NipartPluginEvent::None,
             NipartEventAddress::User,
             NipartEventAddress::Daemon,
-            self.timeout,
+            timeout_ms,
         );
-        self.send(&request).await?;
-        let event = self.recv_reply(request.uuid, self.timeout).await?;
+        let event = match tokio::time::timeout(
+            Duration::from_millis(timeout_ms.into()),
+            async {
+                self.send(&request).await?;
+                self.recv_reply(request.uuid, timeout_ms).await
+            },
+        )
+        .await
+        {
+            Ok(result) => result?,
+            Err(_) => {
+                return Err(NipartError::new(
+                    ErrorKind::Timeout,
+                    format!(
+                        "Timeout on querying network state after \
+                        {timeout_ms} milliseconds"
+                    ),
+                ));
+            }
+        };
         if let NipartUserEvent::QueryNetStateReply(s) = event.user {
             Ok(*s)
         } else {
@@ -193,11 +347,98 @@ impl NipartConnection {
         }
     }
 
+    /// Apply network state and return the warnings raised when sanitizing
+    /// and merging desired state.
     pub async fn apply_net_state(
         &mut self,
         state: NetworkState,
         option: NipartApplyOption,
-    ) -> Result<(), NipartError> {
+    ) -> Result<Vec<String>, NipartError> {
+        if option.confirm_timeout.is_some() {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                "Please use apply_net_state_with_confirm() for apply \
+                option with confirm_timeout defined"
+                    .to_string(),
+            ));
+        }
+        let request = NipartEvent::new(
+            NipartUserEvent::ApplyNetState(Box::new(state), option),
+            NipartPluginEvent::None,
+            NipartEventAddress::User,
+            NipartEventAddress::Daemon,
+            self.timeout,
+        );
+        self.send(&request).await?;
+        let event = self.recv_reply(request.uuid, self.timeout).await?;
+        match event.user {
+            NipartUserEvent::ApplyNetStateReply(warnings)
+            | NipartUserEvent::ApplyNetStateTraceReply(warnings, _) => {
+                Ok(warnings)
+            }
+            _ => Err(NipartError::new(
+                ErrorKind::Bug,
+                format!("Invalid reply {event:?} for ApplyNetState"),
+            )),
+        }
+    }
+
+    /// Apply network state with [NipartApplyOption.trace] enforced, return
+    /// the warnings raised when sanitizing and merging desired state along
+    /// with the timeline of events exchanged during this apply.
+    pub async fn apply_net_state_with_trace(
+        &mut self,
+        state: NetworkState,
+        mut option: NipartApplyOption,
+    ) -> Result<(Vec<String>, Vec<NipartTraceEntry>), NipartError> {
+        if option.confirm_timeout.is_some() {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                "Tracing apply with confirm_timeout is not supported"
+                    .to_string(),
+            ));
+        }
+        option.trace = true;
+        let request = NipartEvent::new(
+            NipartUserEvent::ApplyNetState(Box::new(state), option),
+            NipartPluginEvent::None,
+            NipartEventAddress::User,
+            NipartEventAddress::Daemon,
+            self.timeout,
+        );
+        self.send(&request).await?;
+        let event = self.recv_reply(request.uuid, self.timeout).await?;
+        if let NipartUserEvent::ApplyNetStateTraceReply(warnings, trace) =
+            event.user
+        {
+            Ok((warnings, trace))
+        } else {
+            Err(NipartError::new(
+                ErrorKind::Bug,
+                format!("Invalid reply {event:?} for ApplyNetState"),
+            ))
+        }
+    }
+
+    /// Apply network state and return the uuid of this apply action once
+    /// daemon applied and verified it. User should invoke
+    /// [NipartConnection::confirm_apply()] or
+    /// [NipartConnection::rollback_apply()] with returned uuid within
+    /// `confirm_timeout` seconds using the same connection, otherwise daemon
+    /// will revert the changes. Dropping this connection also triggers
+    /// rollback.
+    pub async fn apply_net_state_with_confirm(
+        &mut self,
+        state: NetworkState,
+        option: NipartApplyOption,
+    ) -> Result<u128, NipartError> {
+        if option.confirm_timeout.is_none() {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                "The confirm_timeout of apply option is not defined"
+                    .to_string(),
+            ));
+        }
         let request = NipartEvent::new(
             NipartUserEvent::ApplyNetState(Box::new(state), option),
             NipartPluginEvent::None,
@@ -207,8 +448,8 @@ impl NipartConnection {
         );
         self.send(&request).await?;
         let event = self.recv_reply(request.uuid, self.timeout).await?;
-        if let NipartUserEvent::ApplyNetStateReply = event.user {
-            Ok(())
+        if let NipartUserEvent::ApplyNetStateWaitConfirm = event.user {
+            Ok(request.uuid)
         } else {
             Err(NipartError::new(
                 ErrorKind::Bug,
@@ -217,6 +458,59 @@ impl NipartConnection {
         }
     }
 
+    /// Confirm the apply action and return the warnings raised when
+    /// sanitizing and merging desired state.
+    pub async fn confirm_apply(
+        &mut self,
+        uuid: u128,
+    ) -> Result<Vec<String>, NipartError> {
+        let mut request = NipartEvent::new(
+            NipartUserEvent::ConfirmApply,
+            NipartPluginEvent::None,
+            NipartEventAddress::User,
+            NipartEventAddress::Daemon,
+            self.timeout,
+        );
+        request.uuid = uuid;
+        self.send(&request).await?;
+        let event = self.recv_reply(request.uuid, self.timeout).await?;
+        match event.user {
+            NipartUserEvent::ApplyNetStateReply(warnings)
+            | NipartUserEvent::ApplyNetStateTraceReply(warnings, _) => {
+                Ok(warnings)
+            }
+            _ => Err(NipartError::new(
+                ErrorKind::Bug,
+                format!("Invalid reply {event:?} for ConfirmApply"),
+            )),
+        }
+    }
+
+    /// Request daemon to revert the apply action. The reply error of
+    /// `ErrorKind::RolledBack` is treated as success.
+    pub async fn rollback_apply(
+        &mut self,
+        uuid: u128,
+    ) -> Result<(), NipartError> {
+        let mut request = NipartEvent::new(
+            NipartUserEvent::RollbackApply,
+            NipartPluginEvent::None,
+            NipartEventAddress::User,
+            NipartEventAddress::Daemon,
+            self.timeout,
+        );
+        request.uuid = uuid;
+        self.send(&request).await?;
+        match self.recv_reply(request.uuid, self.timeout).await {
+            Err(e) if e.kind == ErrorKind::RolledBack => Ok(()),
+            Err(e) => Err(e),
+            Ok(event) => Err(NipartError::new(
+                ErrorKind::Bug,
+                format!("Invalid reply {event:?} for RollbackApply"),
+            )),
+        }
+    }
+
     pub async fn stop_daemon(&mut self) -> Result<(), NipartError> {
         let request = NipartEvent::new(
             NipartUserEvent::Quit,
@@ -263,8 +557,33 @@ impl NipartConnection {
                 format!("Failed to generate JSON string for {data:?}: {e}",),
             )
         })?;
-        let data = json_str.as_bytes();
-        let length = &data.len().to_ne_bytes();
+        let mut header: usize = 0;
+        let compressed_data;
+        let mut data = json_str.as_bytes();
+        if self.compression {
+            header |= Self::HEADER_FLAG_ACCEPT_COMPRESSED;
+            if self.peer_compression
+                && data.len() > Self::IPC_COMPRESS_THRESHOLD
+            {
+                compressed_data =
+                    zstd::bulk::compress(data, Self::IPC_COMPRESS_LEVEL)
+                        .map_err(|e| {
+                            NipartError::new(
+                                ErrorKind::Bug,
+                                format!("Failed to compress IPC message: {e}"),
+                            )
+                        })?;
+                log::trace!(
+                    "Compressed IPC message from {} bytes to {} bytes",
+                    data.len(),
+                    compressed_data.len()
+                );
+                data = compressed_data.as_slice();
+                header |= Self::HEADER_FLAG_COMPRESSED;
+            }
+        }
+        header |= data.len();
+        let length = &header.to_ne_bytes();
         self.socket.write_all(length).await.map_err(|e| {
             if e.kind() == std::io::ErrorKind::BrokenPipe {
                 NipartError::new(
@@ -316,16 +635,17 @@ impl NipartConnection {
                 .await
                 {
                     Ok(Ok(event)) => {
-                        if event.is_log() {
-                            event.emit_log();
-                            continue;
-                        }
+                        // Log events should not extend the timeout
                         let elapsed = now.elapsed();
                         if elapsed >= remain_time {
                             remain_time = Duration::ZERO;
                         } else {
                             remain_time -= elapsed;
                         }
+                        if event.is_log() {
+                            event.emit_log();
+                            continue;
+                        }
                         if event.uuid == uuid {
                             return event.into_result();
                         } else {
@@ -364,12 +684,26 @@ impl NipartConnection {
             .read_exact(&mut message_size_bytes)
             .await
             .map_err(|e| {
-                NipartError::new(
-                    ErrorKind::Bug,
-                    format!("Failed to read socket message length: {e}"),
-                )
+                if e.kind() == std::io::ErrorKind::UnexpectedEof {
+                    NipartError::new(
+                        ErrorKind::IpcClosed,
+                        "IPC connection closed by other end".to_string(),
+                    )
+                } else {
+                    NipartError::new(
+                        ErrorKind::Bug,
+                        format!("Failed to read socket message length: {e}"),
+                    )
+                }
             })?;
-        let message_size = usize::from_ne_bytes(message_size_bytes);
+        let header = usize::from_ne_bytes(message_size_bytes);
+        let message_size = header & !Self::HEADER_FLAGS;
+        if header & Self::HEADER_FLAG_ACCEPT_COMPRESSED > 0 {
+            self.peer_compression = true;
+            // Mirror the remote, so daemon and plugins use compression
+            // when client requested
+            self.compression = true;
+        }
         if message_size == 0 {
             return Err(NipartError::new(
                 ErrorKind::IpcClosed,
@@ -405,6 +739,22 @@ impl NipartConnection {
                 ));
             }
         }
+        if header & Self::HEADER_FLAG_COMPRESSED > 0 {
+            buffer = zstd::bulk::decompress(
+                &buffer,
+                Self::IPC_MAX_DECOMPRESSED_SIZE,
+            )
+            .map_err(|e| {
+                NipartError::new(
+                    ErrorKind::IpcMessageTooLarge,
+                    format!(
+                        "Failed to decompress IPC message, might exceeded \
+                        the maximum support size({}) after decompression: {e}",
+                        Self::IPC_MAX_DECOMPRESSED_SIZE
+                    ),
+                )
+            })?;
+        }
         let ret = serde_json::from_slice::<T>(&buffer).map_err(|e| {
             NipartError::new(
                 ErrorKind::Bug,
@@ -422,3 +772,144 @@ impl NipartConnection {
         ret
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::NipartConnection;
+    use crate::{ErrorKind, NetworkState, NipartEvent, NipartQueryOption};
+
+    const IFACE_COUNT: usize = 2000;
+
+    fn gen_large_state() -> NetworkState {
+        let mut yaml = String::from("interfaces:\n");
+        for i in 0..IFACE_COUNT {
+            yaml.push_str(&format!(
+                "- name: dummy{i}\n  type: dummy\n  state: up\n  \
+                mtu: 1500\n  ipv4:\n    enabled: true\n    address:\n    \
+                - ip: 10.{}.{}.1\n      prefix-length: 24\n",
+                i / 256,
+                i % 256
+            ));
+        }
+        NetworkState::new_from_yaml(&yaml).unwrap()
+    }
+
+    fn new_conn_pair() -> (NipartConnection, NipartConnection) {
+        let (client, daemon) = tokio::net::UnixStream::pair().unwrap();
+        (
+            NipartConnection::new_with_stream("client", client),
+            NipartConnection::new_with_stream("daemon", daemon),
+        )
+    }
+
+    // The message is larger than socket buffer, hence sending and receiving
+    // concurrently.
+    async fn send_and_recv(
+        sender: &mut NipartConnection,
+        receiver: &mut NipartConnection,
+        state: &NetworkState,
+    ) -> NetworkState {
+        let (sent, received) =
+            tokio::join!(sender.send(state), receiver.recv::<NetworkState>());
+        sent.unwrap();
+        received.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ipc_compressed_large_state_round_trip() {
+        let state = gen_large_state();
+        assert!(
+            serde_json::to_string(&state).unwrap().len()
+                > NipartConnection::IPC_COMPRESS_THRESHOLD
+        );
+        let (mut client, mut daemon) = new_conn_pair();
+        // Simulate negotiate_compression() confirmed by daemon
+        client.compression = true;
+        client.peer_compression = true;
+
+        let received = send_and_recv(&mut client, &mut daemon, &state).await;
+        assert_eq!(received.interfaces.kernel_ifaces.len(), IFACE_COUNT);
+        assert_eq!(
+            serde_json::to_value(&received).unwrap(),
+            serde_json::to_value(&state).unwrap()
+        );
+        assert!(daemon.compression);
+        assert!(daemon.peer_compression);
+
+        let received = send_and_recv(&mut daemon, &mut client, &state).await;
+        assert_eq!(
+            serde_json::to_value(&received).unwrap(),
+            serde_json::to_value(&state).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ipc_no_compression_without_negotiation() {
+        let state = gen_large_state();
+        let (mut client, mut daemon) = new_conn_pair();
+
+        let received = send_and_recv(&mut client, &mut daemon, &state).await;
+        assert_eq!(received.interfaces.kernel_ifaces.len(), IFACE_COUNT);
+        assert!(!daemon.compression);
+        assert!(!daemon.peer_compression);
+    }
+
+    #[tokio::test]
+    async fn test_query_net_state_timeout() {
+        let (mut client, mut daemon) = new_conn_pair();
+        // Mock of wedged daemon which never replies the query
+        let daemon_handle = tokio::spawn(async move {
+            let request = daemon.recv::<NipartEvent>().await;
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            request
+        });
+
+        let start = std::time::Instant::now();
+        let result = client
+            .query_net_state_with_timeout(NipartQueryOption::default(), 100)
+            .await;
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::Timeout);
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        daemon_handle.abort();
+    }
+
+    fn gen_socket_path(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("nipart_ipc_{name}_{}.sock", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        path.to_string_lossy().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_retry_socket_appear_later() {
+        let path = gen_socket_path("retry");
+        // Mock of daemon which creates its socket after a short delay
+        let daemon_path = path.clone();
+        let daemon_handle = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            let listener = tokio::net::UnixListener::bind(daemon_path).unwrap();
+            listener.accept().await.unwrap();
+        });
+
+        assert!(NipartConnection::new_with_path(&path).await.is_err());
+        let result =
+            NipartConnection::connect_with_path_and_retry(&path, 5000).await;
+
+        assert!(result.is_ok());
+        daemon_handle.await.unwrap();
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_retry_deadline() {
+        let path = gen_socket_path("deadline");
+
+        let start = std::time::Instant::now();
+        let result =
+            NipartConnection::connect_with_path_and_retry(&path, 200).await;
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::Timeout);
+        assert!(start.elapsed() < std::time::Duration::from_secs(2));
+    }
+}
@@ -2,6 +2,7 @@
 
 mod commit;
 mod dhcp;
+mod dispatch;
 mod error;
 mod event;
 mod ipc;
@@ -19,21 +20,30 @@ mod state_options;
 #[allow(dead_code, unused_imports, unexpected_cfgs)]
 mod state;
 
-pub use self::commit::{NetworkCommit, NetworkCommitQueryOption};
+pub use self::commit::{
+    NetworkAuditRecord, NetworkCommit, NetworkCommitQueryOption,
+};
 pub use self::dhcp::{
     NipartDhcpConfig, NipartDhcpConfigV4, NipartDhcpConfigV6, NipartDhcpLease,
     NipartDhcpLeaseV4, NipartDhcpLeaseV6,
 };
-pub use self::error::{ErrorKind, NipartError};
-pub use self::event::{NipartEvent, NipartEventAddress, NipartUserEvent};
+pub use self::dispatch::{NipartDispatchPhase, NipartDispatchScript};
+pub use self::error::{ErrorKind, NipartError, NipartVerifyDiff};
+pub use self::event::{
+    NipartEvent, NipartEventAddress, NipartTraceEntry, NipartUserEvent,
+    CUR_SCHEMA_VERSION,
+};
 pub use self::ipc::{NipartConnection, DEFAULT_TIMEOUT};
-pub use self::lock::{NipartLockEntry, NipartLockOption};
+pub use self::lock::{NipartLockEntry, NipartLockMode, NipartLockOption};
 pub use self::logging::{NipartLogEntry, NipartLogLevel};
 pub use self::monitor::{
     NipartAddressMonitorKind, NipartAddressMonitorRule, NipartLinkMonitorKind,
     NipartLinkMonitorRule, NipartMonitorEvent, NipartMonitorRule,
+    NipartRouteMonitorKind, NipartRouteMonitorRule,
+};
+pub use self::plugin::{
+    NipartDaemonInfo, NipartPluginEvent, NipartPluginInfo, NipartRole,
 };
-pub use self::plugin::{NipartPluginEvent, NipartPluginInfo, NipartRole};
 pub use self::plugin_external::{NipartExternalPlugin, NipartPluginRunner};
 pub use self::plugin_ipc::NipartConnectionListener;
 pub use self::plugin_native::NipartNativePlugin;
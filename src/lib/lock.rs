@@ -11,6 +11,10 @@ pub enum NipartLockEntry {
     Dns,
     Route,
     RouteRule,
+    /// The whole network state. Query holds it in
+    /// [NipartLockMode::Shared] mode while apply changing running network
+    /// state holds it in [NipartLockMode::IntentExclusive] mode.
+    NetState,
 }
 
 impl std::fmt::Display for NipartLockEntry {
@@ -20,6 +24,7 @@ impl std::fmt::Display for NipartLockEntry {
             Self::Dns => write!(f, "lock.dns"),
             Self::Route => write!(f, "lock.route"),
             Self::RouteRule => write!(f, "lock.route_rule"),
+            Self::NetState => write!(f, "lock.net_state"),
         }
     }
 }
@@ -30,20 +35,107 @@ impl NipartLockEntry {
     }
 }
 
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize,
+)]
+#[non_exhaustive]
+pub enum NipartLockMode {
+    /// Multiple sessions can hold the shared lock of the same entry at the
+    /// same time, suitable for read-only actions.
+    Shared,
+    /// Only single session can hold the exclusive lock of an entry, suitable
+    /// for actions changing the network state.
+    #[default]
+    Exclusive,
+    /// Held on [NipartLockEntry::NetState] by session holding exclusive
+    /// locks of some entries. Multiple sessions can hold it at the same
+    /// time, hence applies of unrelated entries still run concurrently,
+    /// but it conflicts with shared lock, hence query waits for ongoing
+    /// applies and apply waits for ongoing queries.
+    IntentExclusive,
+}
+
+impl std::fmt::Display for NipartLockMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Shared => write!(f, "shared"),
+            Self::Exclusive => write!(f, "exclusive"),
+            Self::IntentExclusive => write!(f, "intent_exclusive"),
+        }
+    }
+}
+
+impl NipartLockMode {
+    /// Whether two sessions can hold the lock of the same entry in these
+    /// modes at the same time.
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::Shared, Self::Shared)
+                | (Self::IntentExclusive, Self::IntentExclusive)
+        )
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 #[non_exhaustive]
 pub struct NipartLockOption {
     pub timeout_seconds: u32,
+    #[serde(default)]
+    pub mode: NipartLockMode,
 }
 
 impl std::fmt::Display for NipartLockOption {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "lock_option.timeout:{}s", self.timeout_seconds)
+        write!(
+            f,
+            "lock_option.timeout:{}s,mode:{}",
+            self.timeout_seconds, self.mode
+        )
     }
 }
 
 impl NipartLockOption {
+    /// Exclusive lock
     pub fn new(timeout_seconds: u32) -> Self {
-        Self { timeout_seconds }
+        Self {
+            timeout_seconds,
+            mode: NipartLockMode::Exclusive,
+        }
+    }
+
+    pub fn new_shared(timeout_seconds: u32) -> Self {
+        Self {
+            timeout_seconds,
+            mode: NipartLockMode::Shared,
+        }
+    }
+
+    pub fn new_with_mode(timeout_seconds: u32, mode: NipartLockMode) -> Self {
+        Self {
+            timeout_seconds,
+            mode,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NipartLockMode;
+
+    #[test]
+    fn test_lock_mode_compatible() {
+        let shared = NipartLockMode::Shared;
+        let exclusive = NipartLockMode::Exclusive;
+        let intent = NipartLockMode::IntentExclusive;
+
+        assert!(shared.is_compatible(&shared));
+        assert!(intent.is_compatible(&intent));
+        assert!(!shared.is_compatible(&intent));
+        assert!(!intent.is_compatible(&shared));
+        for mode in [shared, exclusive, intent] {
+            assert!(!exclusive.is_compatible(&mode));
+            assert!(!mode.is_compatible(&exclusive));
+        }
     }
 }
@@ -11,6 +11,7 @@ use crate::NipartEventAddress;
 pub enum NipartMonitorRule {
     Link(NipartLinkMonitorRule),
     Address(NipartAddressMonitorRule),
+    Route(NipartRouteMonitorRule),
 }
 
 impl std::fmt::Display for NipartMonitorRule {
@@ -18,6 +19,7 @@ impl std::fmt::Display for NipartMonitorRule {
         match self {
             Self::Link(rule) => write!(f, "{rule}"),
             Self::Address(rule) => write!(f, "{rule}"),
+            Self::Route(rule) => write!(f, "{rule}"),
         }
     }
 }
@@ -31,6 +33,10 @@ pub enum NipartMonitorEvent {
     LinkDown(String),
     /// IP address been removed
     AddressRemove(IpAddr),
+    /// Route been added, holding route destination and table ID
+    RouteAdd(String, u32),
+    /// Route been removed, holding route destination and table ID
+    RouteRemove(String, u32),
 }
 
 impl std::fmt::Display for NipartMonitorEvent {
@@ -39,6 +45,12 @@ impl std::fmt::Display for NipartMonitorEvent {
             Self::LinkUp(iface) => write!(f, "link_up:{iface}"),
             Self::LinkDown(iface) => write!(f, "link_down:{iface}"),
             Self::AddressRemove(ip) => write!(f, "addr_remove:{ip}"),
+            Self::RouteAdd(dst, table_id) => {
+                write!(f, "route_add:{dst}:table:{table_id}")
+            }
+            Self::RouteRemove(dst, table_id) => {
+                write!(f, "route_remove:{dst}:table:{table_id}")
+            }
         }
     }
 }
@@ -172,3 +184,88 @@ impl std::fmt::Display for NipartAddressMonitorKind {
         )
     }
 }
+
+#[derive(
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Copy,
+)]
+#[non_exhaustive]
+pub enum NipartRouteMonitorKind {
+    Add,
+    Remove,
+}
+
+impl std::fmt::Display for NipartRouteMonitorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Add => "add",
+                Self::Remove => "remove",
+            }
+        )
+    }
+}
+
+/// Monitor on the route add/remove event of routing table
+#[derive(
+    Deserialize, Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[non_exhaustive]
+pub struct NipartRouteMonitorRule {
+    pub kind: NipartRouteMonitorKind,
+    /// Who requested this monitor rule
+    pub requester: NipartEventAddress,
+    /// Event ID for tracing the source of this request
+    pub uuid: u128,
+    /// Only monitor routes whose destination is inside of this prefix,
+    /// e.g. `198.51.100.0/24`. Default route is only matched by `0.0.0.0/0`
+    /// or `::/0`. None means all routes.
+    pub destination: Option<String>,
+    /// Only monitor routes in this route table. None means all tables.
+    pub table_id: Option<u32>,
+}
+
+impl std::fmt::Display for NipartRouteMonitorRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "route_monitor: uuid:{}, kind:{}, requester:{}, destination:{}, \
+            table_id:{}",
+            self.uuid,
+            self.kind,
+            self.requester,
+            self.destination.as_deref().unwrap_or("any"),
+            self.table_id
+                .map(|t| t.to_string())
+                .unwrap_or_else(|| "any".to_string()),
+        )
+    }
+}
+
+impl NipartRouteMonitorRule {
+    pub fn new(
+        kind: NipartRouteMonitorKind,
+        requester: NipartEventAddress,
+        uuid: u128,
+        destination: Option<String>,
+        table_id: Option<u32>,
+    ) -> Self {
+        Self {
+            kind,
+            requester,
+            uuid,
+            destination,
+            table_id,
+        }
+    }
+}
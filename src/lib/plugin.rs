@@ -4,9 +4,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     MergedNetworkState, NetworkCommit, NetworkCommitQueryOption, NetworkState,
-    NipartApplyOption, NipartDhcpConfig, NipartDhcpLease, NipartLockEntry,
-    NipartLockOption, NipartLogLevel, NipartMonitorEvent, NipartMonitorRule,
-    NipartQueryOption,
+    NipartApplyOption, NipartDhcpConfig, NipartDhcpLease, NipartDispatchScript,
+    NipartLockEntry, NipartLockOption, NipartLogLevel, NipartMonitorEvent,
+    NipartMonitorRule, NipartQueryOption,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -16,6 +16,46 @@ pub struct NipartPluginInfo {
     pub roles: Vec<NipartRole>,
 }
 
+impl NipartPluginInfo {
+    pub fn new(name: String, roles: Vec<NipartRole>) -> Self {
+        Self { name, roles }
+    }
+}
+
+/// Diagnostic information of nipart daemon and its loaded plugins.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[non_exhaustive]
+pub struct NipartDaemonInfo {
+    /// Version of nipart daemon.
+    pub version: String,
+    /// Seconds since nipart daemon started.
+    pub uptime_secs: u64,
+    /// Schema version of IPC events, see [crate::CUR_SCHEMA_VERSION].
+    pub schema_version: u32,
+    pub plugins: Vec<NipartPluginInfo>,
+    /// Whether daemon supports zstd compressed IPC message, see
+    /// [crate::NipartConnection::negotiate_compression()]. Daemon without
+    /// this support does not reply it, hence default to false.
+    #[serde(default)]
+    pub ipc_compression: bool,
+}
+
+impl NipartDaemonInfo {
+    pub fn new(
+        version: String,
+        uptime_secs: u64,
+        plugins: Vec<NipartPluginInfo>,
+    ) -> Self {
+        Self {
+            version,
+            uptime_secs,
+            schema_version: crate::CUR_SCHEMA_VERSION,
+            plugins,
+            ipc_compression: true,
+        }
+    }
+}
+
 #[derive(
     Serialize,
     Deserialize,
@@ -103,6 +143,9 @@ pub enum NipartPluginEvent {
     RemoveMonitorRule(Box<NipartMonitorRule>),
     /// Monitor plugin notify. No reply required.
     GotMonitorEvent(Box<NipartMonitorEvent>),
+    /// Monitor plugin notify commander on any link, address or route
+    /// changes. No reply required.
+    NetStateChanged,
 
     QueryCommits(NetworkCommitQueryOption),
     QueryCommitsReply(Box<Vec<NetworkCommit>>),
@@ -124,14 +167,27 @@ pub enum NipartPluginEvent {
     ResumeTracking,
 
     /// Request lock on specified entries, reply required.
+    /// The locker grants all requested entries at once or none of them.
+    /// Request which cannot be granted immediately is queued until
+    /// conflicting locks released or request timeout. Pending requests are
+    /// granted in order of arrival: a shared lock request is queued behind
+    /// earlier exclusive lock request of the same entry, so writers will not
+    /// be starved by continuous readers.
+    /// When request timeout, the locker replies [Self::LockReply] along
+    /// with [crate::NipartUserEvent::Error] of [crate::ErrorKind::Timeout]
+    /// naming the sessions holding conflicting locks.
     Lock(Box<Vec<(NipartLockEntry, NipartLockOption)>>),
     /// Request unlock on specified entries, no reply required.
     /// Cannot unlock other event's entry.
     Unlock(Box<Vec<NipartLockEntry>>),
 
-    // TBD: do we need to indicate who is currently taking lock when fails
     /// Indicate all requested lock entries has been locked as requested.
     LockReply,
+
+    /// Commander request daemon to run specified dispatch scripts.
+    RunDispatchScripts(Box<Vec<NipartDispatchScript>>),
+    /// Indicate all requested dispatch scripts finished successfully.
+    RunDispatchScriptsReply,
 }
 
 impl std::fmt::Display for NipartPluginEvent {
@@ -188,6 +244,7 @@ impl std::fmt::Display for NipartPluginEvent {
             Self::GotMonitorEvent(event) => {
                 write!(f, "got_monitor_event:{event}")
             }
+            Self::NetStateChanged => write!(f, "net_state_changed"),
             Self::QueryCommits(_) => write!(f, "query_commits"),
             Self::QueryCommitsReply(_) => write!(f, "query_commits_reply"),
             Self::Commit(_) => write!(f, "commit"),
@@ -201,6 +258,19 @@ impl std::fmt::Display for NipartPluginEvent {
             Self::Lock(_) => write!(f, "lock"),
             Self::Unlock(_) => write!(f, "unlock"),
             Self::LockReply => write!(f, "lock_reply"),
+            Self::RunDispatchScripts(scripts) => write!(
+                f,
+                "run_dispatch_scripts:{}",
+                scripts
+                    .as_slice()
+                    .iter()
+                    .map(|s| s.iface.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(",")
+            ),
+            Self::RunDispatchScriptsReply => {
+                write!(f, "run_dispatch_scripts_reply")
+            }
         }
     }
 }
@@ -220,6 +290,7 @@ impl NipartPluginEvent {
                 | Self::QueryCommitsReply(_)
                 | Self::CommitReply
                 | Self::LockReply
+                | Self::RunDispatchScriptsReply
         )
     }
 }
@@ -51,6 +51,16 @@ def fix_relative_import():
         + ["s/ deserializer::/ state::deserializer::/g", "{}", ";"],
         check=True,
     )
+    subprocess.run(
+        f"find {SCRIPT_DIR} -type f -name *.rs -exec sed -i -e".split()
+        + ["s/crate::{ovsdb::/crate::{state::ovsdb::/g", "{}", ";"],
+        check=True,
+    )
+    subprocess.run(
+        f"find {SCRIPT_DIR} -type f -name *.rs -exec sed -i -e".split()
+        + ["s/    ovsdb::db::/    state::ovsdb::db::/g", "{}", ";"],
+        check=True,
+    )
 
 
 def exposing_merged_xxx():
@@ -174,6 +184,26 @@ def change_merged_network_state_props_to_public():
         ],
         check=True,
     )
+    subprocess.run(
+        f"find {SCRIPT_DIR} -type f -name *.rs -exec sed -i -e".split()
+        + [
+            "s/pub(crate) ovn: MergedOvnConfiguration/"
+            "pub ovn: MergedOvnConfiguration/g",
+            "{}",
+            ";",
+        ],
+        check=True,
+    )
+    subprocess.run(
+        f"find {SCRIPT_DIR} -type f -name *.rs -exec sed -i -e".split()
+        + [
+            "s/pub(crate) ovsdb: MergedOvsDbGlobalConfig/"
+            "pub ovsdb: MergedOvsDbGlobalConfig/g",
+            "{}",
+            ";",
+        ],
+        check=True,
+    )
     subprocess.run(
         f"find {SCRIPT_DIR} -type f -name *.rs -exec sed -i -e".split()
         + [
@@ -348,6 +378,45 @@ def expose_is_userspace():
     )
 
 
+def expose_iface_parent():
+    subprocess.run(
+        f"sed -i -e".split()
+        + [
+            r"s/pub(crate) fn parent(&self) -> Option<&str> {$/"
+            r"pub fn parent(\&self) -> Option<\&str> {/",
+            f"{SCRIPT_DIR}/iface.rs",
+        ],
+        check=True,
+    )
+
+
+def expose_ovsdb_funcs():
+    subprocess.run(
+        f"find {SCRIPT_DIR}/ovsdb -type f -name *.rs -exec sed -i -e".split()
+        + ["s/pub(crate) fn ovsdb_/pub fn ovsdb_/g", "{}", ";"],
+        check=True,
+    )
+    subprocess.run(
+        f"sed -i -e".split()
+        + [
+            r"s/pub(crate) use \(.\+::ovsdb_.\+\)$/pub use \1/g",
+            f"{SCRIPT_DIR}/ovsdb/mod.rs",
+        ],
+        check=True,
+    )
+
+
+def expose_isolate_ovn():
+    subprocess.run(
+        f"sed -i -e".split()
+        + [
+            "s/pub(crate) fn isolate_ovn/pub fn isolate_ovn/",
+            f"{SCRIPT_DIR}/query_apply/ovs.rs",
+        ],
+        check=True,
+    )
+
+
 def main():
     for file in os.listdir(NMSTATE_RUST_CODE_DIR):
         if file not in DENY_LIST:
@@ -375,7 +444,10 @@ def main():
     serde_merged_state()
     expose_ip_enabled_defined()
     expose_is_userspace()
+    expose_iface_parent()
     expose_merged_xxx_is_changed_func()
+    expose_ovsdb_funcs()
+    expose_isolate_ovn()
 
 
 main()
@@ -7,21 +7,46 @@ use crate::{ErrorKind, MergedInterfaces, NipartError};
 #[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
+/// Bash scripts invoked by nipart daemon around the apply of specified
+/// interface. The script is executed with root privilege of daemon, with
+/// interface name stored in `NIPART_IFACE` environment variable. Any user
+/// able to apply network state could hence run arbitrary command as root.
+/// Script taking longer than [DispatchConfig.timeout] will be killed.
 pub struct DispatchConfig {
+    /// Dispatch bash script content to be invoked before interface
+    /// activation. Failure of this script will abort the apply.
+    /// Setting to empty string will remove the dispatch script
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_activation: Option<String>,
     /// Dispatch bash script content to be invoked after interface activation
-    /// finished by network backend. Nmstate will append additional lines
-    /// to make sure this script is only invoked for specified interface when
-    /// backend interface activation finished.
+    /// verified. Failure of this script only generates warning as the network
+    /// state is already applied.
     /// Setting to empty string will remove the dispatch script
     #[serde(skip_serializing_if = "Option::is_none")]
     pub post_activation: Option<String>,
-    /// Dispatch bash script content to be invoked after interface deactivation
-    /// finished by network backend. Nmstate will append additional lines
-    /// to make sure this script is only invoked for specified interface when
-    /// backend interface deactivation finished.
+    /// Dispatch bash script content to be invoked before interface
+    /// deactivation. Failure of this script will abort the apply.
+    /// Setting to empty string will remove the dispatch script
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_deactivation: Option<String>,
+    /// Dispatch bash script content to be invoked after interface
+    /// deactivation verified. Failure of this script only generates warning
+    /// as the network state is already applied.
     /// Setting to empty string will remove the dispatch script
     #[serde(skip_serializing_if = "Option::is_none")]
     pub post_deactivation: Option<String>,
+    /// Timeout in seconds of each dispatch script.
+    /// Default to [DispatchConfig::DEFAULT_TIMEOUT].
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    pub timeout: Option<u32>,
+}
+
+impl DispatchConfig {
+    pub const DEFAULT_TIMEOUT: u32 = 10;
 }
 
 impl MergedInterfaces {
@@ -57,6 +57,19 @@ const SUPPORTED_DNS_OPTS_WITH_VALUE: [&str; 3] =
 ///      - trust-ad
 ///      - rotate
 /// ```
+/// Split DNS for VPN interface, queries for `corp.example.com` are only
+/// sent to the name server of `wg0`:
+/// ```yml
+/// ---
+/// dns-resolver:
+///   config:
+///     per-interface:
+///     - name: wg0
+///       server:
+///       - 198.51.100.1
+///       search:
+///       - corp.example.com
+/// ```
 /// To purge all static DNS configuration:
 /// ```yml
 /// ---
@@ -115,6 +128,15 @@ pub struct DnsClientState {
     /// To remove all existing search, please use `Some(Vec::new())`.
     /// If undefined(set to `None`), will preserve current config.
     pub options: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "per-interface")]
+    /// DNS config scoped to specified interface (split DNS), e.g.
+    /// systemd-resolved per-link DNS. Queries for domains in the `search`
+    /// of an entry are only routed to the name servers of that interface.
+    /// To remove all existing per-interface config, please use
+    /// `Some(Vec::new())`.
+    /// If undefined(set to `None`), will preserve current config.
+    /// Serialize and deserialize to/from `per-interface`.
+    pub per_interface: Option<Vec<DnsInterfaceConfig>>,
     #[serde(skip)]
     // Lower is better
     pub(crate) priority: Option<i32>,
@@ -126,45 +148,48 @@ impl DnsClientState {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.server.is_none() && self.search.is_none() && self.options.is_none()
+        self.server.is_none()
+            && self.search.is_none()
+            && self.options.is_none()
+            && self.per_interface.is_none()
     }
 
     pub(crate) fn is_null(&self) -> bool {
         self.server.as_ref().map(|s| s.len()).unwrap_or_default() == 0
             && self.search.as_ref().map(|s| s.len()).unwrap_or_default() == 0
             && self.options.as_ref().map(|s| s.len()).unwrap_or_default() == 0
+            && self
+                .per_interface
+                .as_ref()
+                .map(|s| s.len())
+                .unwrap_or_default()
+                == 0
     }
 
     // sanitize the IP addresses.
     pub(crate) fn sanitize(&mut self) -> Result<(), NipartError> {
         if let Some(srvs) = self.server.as_mut() {
-            let mut sanitized_srvs = Vec::new();
-            for srv in srvs {
-                if is_ipv6_addr(srv.as_str()) {
-                    let splits: Vec<&str> = srv.split('%').collect();
-                    if splits.len() == 2 {
-                        if let Ok(ip_addr) = splits[0].parse::<Ipv6Addr>() {
-                            sanitized_srvs
-                                .push(format!("{}%{}", ip_addr, splits[1]));
-                        }
-                    } else if let Ok(ip_addr) = srv.parse::<Ipv6Addr>() {
-                        sanitized_srvs.push(ip_addr.to_string());
-                    } else {
-                        return Err(NipartError::new(
-                            ErrorKind::InvalidArgument,
-                            format!("Invalid DNS server string {srv}",),
-                        ));
-                    }
-                } else if let Ok(ip_addr) = srv.parse::<Ipv4Addr>() {
-                    sanitized_srvs.push(ip_addr.to_string());
-                } else {
+            *srvs = sanitize_dns_servers(srvs)?;
+        }
+        if let Some(iface_confs) = self.per_interface.as_mut() {
+            for iface_conf in iface_confs.iter_mut() {
+                if let Some(srvs) = iface_conf.server.as_mut() {
+                    *srvs = sanitize_dns_servers(srvs)?;
+                }
+            }
+            iface_confs.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+            for i in 1..iface_confs.len() {
+                if iface_confs[i - 1].name == iface_confs[i].name {
                     return Err(NipartError::new(
                         ErrorKind::InvalidArgument,
-                        format!("Invalid DNS server string {srv}",),
+                        format!(
+                            "Duplicate per-interface DNS config for \
+                            interface {}",
+                            iface_confs[i].name
+                        ),
                     ));
                 }
             }
-            self.server = Some(sanitized_srvs);
         }
         if let Some(opts) = self.options.as_ref() {
             for opt in opts {
@@ -206,6 +231,59 @@ impl DnsClientState {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[non_exhaustive]
+#[serde(deny_unknown_fields)]
+/// DNS config scoped to single interface
+pub struct DnsInterfaceConfig {
+    /// Interface name
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Name server IP address list of this interface.
+    pub server: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Domains routed to the name servers of this interface.
+    pub search: Option<Vec<String>>,
+}
+
+impl DnsInterfaceConfig {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+fn sanitize_dns_servers(srvs: &[String]) -> Result<Vec<String>, NipartError> {
+    let mut sanitized_srvs = Vec::new();
+    for srv in srvs {
+        if is_ipv6_addr(srv.as_str()) {
+            let splits: Vec<&str> = srv.split('%').collect();
+            if splits.len() == 2 {
+                if let Ok(ip_addr) = splits[0].parse::<Ipv6Addr>() {
+                    sanitized_srvs.push(format!("{}%{}", ip_addr, splits[1]));
+                }
+            } else if let Ok(ip_addr) = srv.parse::<Ipv6Addr>() {
+                sanitized_srvs.push(ip_addr.to_string());
+            } else {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!("Invalid DNS server string {srv}",),
+                ));
+            }
+        } else if let Ok(ip_addr) = srv.parse::<Ipv4Addr>() {
+            sanitized_srvs.push(ip_addr.to_string());
+        } else {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!("Invalid DNS server string {srv}",),
+            ));
+        }
+    }
+    Ok(sanitized_srvs)
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
 pub struct MergedDnsState {
     pub(crate) desired: Option<DnsState>,
@@ -213,6 +291,7 @@ pub struct MergedDnsState {
     pub(crate) servers: Vec<String>,
     pub(crate) searches: Vec<String>,
     pub(crate) options: Vec<String>,
+    pub(crate) per_interface: Vec<DnsInterfaceConfig>,
 }
 
 impl MergedDnsState {
@@ -238,6 +317,12 @@ impl MergedDnsState {
             .and_then(|c| c.options.clone())
             .unwrap_or_default();
 
+        let mut per_interface = current
+            .config
+            .as_ref()
+            .and_then(|c| c.per_interface.clone())
+            .unwrap_or_default();
+
         let mut desired = match desired {
             Some(d) => d,
             None => {
@@ -247,6 +332,7 @@ impl MergedDnsState {
                     servers,
                     searches,
                     options,
+                    per_interface,
                 });
             }
         };
@@ -256,13 +342,11 @@ impl MergedDnsState {
         if let Some(conf) = desired.config.as_ref() {
             //  * `server`, `search` and `options` are None. Equal to desire
             //  state `config: {}`, means purging
-            if conf.server.is_none()
-                && conf.search.is_none()
-                && conf.options.is_none()
-            {
+            if conf.is_empty() {
                 servers.clear();
                 searches.clear();
                 options.clear();
+                per_interface.clear();
             } else {
                 if let Some(des_srvs) = conf.server.as_ref() {
                     servers.clear();
@@ -276,6 +360,10 @@ impl MergedDnsState {
                     options.clear();
                     options.extend_from_slice(des_opts);
                 }
+                if let Some(des_ifaces) = conf.per_interface.as_ref() {
+                    per_interface.clear();
+                    per_interface.extend_from_slice(des_ifaces);
+                }
             }
         }
 
@@ -285,6 +373,7 @@ impl MergedDnsState {
             servers,
             searches,
             options,
+            per_interface,
         })
     }
 
@@ -308,9 +397,17 @@ impl MergedDnsState {
             .and_then(|c| c.options.clone())
             .unwrap_or_default();
 
+        let cur_per_iface = self
+            .current
+            .config
+            .as_ref()
+            .and_then(|c| c.per_interface.clone())
+            .unwrap_or_default();
+
         self.servers != cur_servers
             || self.searches != cur_searches
             || self.options != cur_options
+            || self.per_interface != cur_per_iface
     }
 
     pub(crate) fn is_search_or_option_only(&self) -> bool {
@@ -320,6 +417,50 @@ impl MergedDnsState {
 }
 
 impl MergedNetworkState {
+    // Interface of per-interface DNS config should exist and be valid for
+    // holding DNS config.
+    pub(crate) fn validate_dns_per_interface(&self) -> Result<(), NipartError> {
+        if !self.dns.is_changed() {
+            return Ok(());
+        }
+        for iface_conf in self.dns.per_interface.as_slice() {
+            let iface = match self
+                .interfaces
+                .kernel_ifaces
+                .get(iface_conf.name.as_str())
+            {
+                Some(i) if !i.merged.is_absent() => i,
+                _ => {
+                    let e = NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!(
+                            "Interface {} of per-interface DNS config \
+                            does not exist",
+                            iface_conf.name
+                        ),
+                    );
+                    log::error!("{}", e);
+                    return Err(e);
+                }
+            };
+            if !iface.is_iface_valid_for_dns(false)
+                && !iface.is_iface_valid_for_dns(true)
+            {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Interface {} has both IPv4 and IPv6 disabled, \
+                        hence cannot hold per-interface DNS config",
+                        iface_conf.name
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
     // * Specified interface is valid for hold IPv6 DNS config.
     // * Cannot have more than one IPv6 link-local DNS interface.
     pub(crate) fn validate_ipv6_link_local_address_dns_srv(
@@ -419,3 +560,75 @@ impl MergedInterface {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        DnsInterfaceConfig, ErrorKind, MergedNetworkState, NetworkState,
+    };
+
+    const VPN_IFACE_YAML: &str = r"---
+        interfaces:
+        - name: wg0
+          type: dummy
+          state: up
+          ipv4:
+            enabled: true
+            address:
+            - ip: 192.0.2.2
+              prefix-length: 24";
+
+    fn gen_split_dns_state(iface: &str) -> NetworkState {
+        let mut state = NetworkState::new_from_yaml(VPN_IFACE_YAML).unwrap();
+        state.dns = Some(
+            serde_yaml::from_str(&format!(
+                r"---
+                config:
+                  per-interface:
+                  - name: {iface}
+                    server:
+                    - 198.51.100.1
+                    search:
+                    - corp.example.com"
+            ))
+            .unwrap(),
+        );
+        state
+    }
+
+    #[test]
+    fn test_dns_split_dns_for_vpn_iface() {
+        let desired = gen_split_dns_state("wg0");
+        let current = NetworkState::new_from_yaml(VPN_IFACE_YAML).unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired.clone(), current, false, false)
+                .unwrap();
+
+        assert!(merged.dns.is_changed());
+        let mut expected = DnsInterfaceConfig::new("wg0");
+        expected.server = Some(vec!["198.51.100.1".to_string()]);
+        expected.search = Some(vec!["corp.example.com".to_string()]);
+        assert_eq!(merged.dns.per_interface, vec![expected]);
+
+        merged.dns.verify(desired.dns.unwrap()).unwrap();
+        let result = merged.dns.verify(Default::default());
+        assert_eq!(
+            result.err().map(|e| e.kind),
+            Some(ErrorKind::VerificationError)
+        );
+    }
+
+    #[test]
+    fn test_dns_split_dns_iface_not_exist() {
+        let desired = gen_split_dns_state("wg1");
+        let current = NetworkState::new_from_yaml(VPN_IFACE_YAML).unwrap();
+
+        let result = MergedNetworkState::new(desired, current, false, false);
+
+        assert_eq!(
+            result.err().map(|e| e.kind),
+            Some(ErrorKind::InvalidArgument)
+        );
+    }
+}
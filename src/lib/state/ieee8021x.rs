@@ -2,7 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::NetworkState;
+use crate::{ErrorKind, NetworkState, NipartError};
 
 #[derive(Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -53,6 +53,122 @@ pub struct Ieee8021XConfig {
 }
 
 impl Ieee8021XConfig {
+    const CERT_BASED_EAP_METHODS: [&'static str; 1] = ["tls"];
+    const SUPPORTED_EAP_METHODS: [&'static str; 7] =
+        ["leap", "md5", "tls", "peap", "ttls", "pwd", "fast"];
+    const SUPPORTED_PHASE2_AUTHS: [&'static str; 8] = [
+        "pap", "chap", "mschap", "mschapv2", "gtc", "otp", "md5", "tls",
+    ];
+
+    pub(crate) fn is_cert_based(&self) -> bool {
+        self.eap.as_ref().map(|eaps| {
+            eaps.iter()
+                .any(|e| Self::CERT_BASED_EAP_METHODS.contains(&e.as_str()))
+        }) == Some(true)
+    }
+
+    // The certificate and private key are mandatory for certificate based
+    // EAP methods.
+    pub(crate) fn sanitize(
+        &mut self,
+        iface_name: &str,
+    ) -> Result<(), NipartError> {
+        // Treat hidden secrets as not defined, so round trip of query result
+        // or stored commit will not use the placeholder as password.
+        if self.private_key_password.as_deref()
+            == Some(NetworkState::PASSWORD_HID_BY_NMSTATE)
+        {
+            self.private_key_password = None;
+        }
+        if self.password.as_deref()
+            == Some(NetworkState::PASSWORD_HID_BY_NMSTATE)
+        {
+            self.password = None;
+        }
+        self.validate_values(iface_name)?;
+
+        if !self.is_cert_based() {
+            return Ok(());
+        }
+        for (prop_name, value) in [
+            ("client-cert", self.client_cert.as_deref()),
+            ("private-key", self.private_key.as_deref()),
+        ] {
+            if value.map(|v| v.trim().is_empty()).unwrap_or(true) {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "The 802.1x `{prop_name}` of interface {iface_name} \
+                        should not be empty when certificate based EAP \
+                        method is used"
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    // The wpa_supplicant configuration file supports neither escaping nor
+    // multiple line value, reject values could break out of the quoted
+    // string and unknown EAP methods.
+    fn validate_values(&self, iface_name: &str) -> Result<(), NipartError> {
+        for eap in self.eap.as_deref().unwrap_or_default() {
+            if !Self::SUPPORTED_EAP_METHODS.contains(&eap.as_str()) {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Unsupported 802.1x EAP method {eap:?} of interface \
+                        {iface_name}, supported methods are: {}",
+                        Self::SUPPORTED_EAP_METHODS.join(", ")
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+        }
+        if let Some(phase2) = self.phase2_auth.as_deref() {
+            if !Self::SUPPORTED_PHASE2_AUTHS.contains(&phase2) {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Unsupported 802.1x phase2-auth {phase2:?} of \
+                        interface {iface_name}, supported values are: {}",
+                        Self::SUPPORTED_PHASE2_AUTHS.join(", ")
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+        }
+        for (prop_name, value) in [
+            ("identity", self.identity.as_deref()),
+            ("ca-cert", self.ca_cert.as_deref()),
+            ("client-cert", self.client_cert.as_deref()),
+            ("private-key", self.private_key.as_deref()),
+            ("private-key-password", self.private_key_password.as_deref()),
+            ("password", self.password.as_deref()),
+        ] {
+            if value
+                .map(|v| v.chars().any(|c| c == '"' || c.is_control()))
+                .unwrap_or_default()
+            {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "The 802.1x `{prop_name}` of interface {iface_name} \
+                        should not contain double quote, newline or other \
+                        control characters"
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn hide_secrets(&mut self) {
         if self.private_key_password.is_some() {
             self.private_key_password =
@@ -85,3 +201,102 @@ impl std::fmt::Debug for Ieee8021XConfig {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Ieee8021XConfig;
+    use crate::{ErrorKind, NetworkState};
+
+    fn gen_conf() -> Ieee8021XConfig {
+        serde_yaml::from_str(
+            r"---
+            ca-cert: /etc/pki/802-1x-test/ca.crt
+            client-cert: /etc/pki/802-1x-test/client.example.org.crt
+            eap-methods:
+              - tls
+            identity: client.example.org
+            private-key: /etc/pki/802-1x-test/client.example.org.key
+            private-key-password: password",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_8021x_deserialize() {
+        let conf = gen_conf();
+
+        assert_eq!(conf.eap, Some(vec!["tls".to_string()]));
+        assert_eq!(conf.identity.as_deref(), Some("client.example.org"));
+        assert_eq!(conf.private_key_password.as_deref(), Some("password"));
+        assert_eq!(conf.password, None);
+    }
+
+    #[test]
+    fn test_8021x_hide_secrets() {
+        let mut conf = gen_conf();
+        conf.password = Some("password2".to_string());
+
+        assert!(!format!("{conf:?}").contains("password2"));
+
+        conf.hide_secrets();
+        assert_eq!(
+            conf.private_key_password.as_deref(),
+            Some(NetworkState::PASSWORD_HID_BY_NMSTATE)
+        );
+        assert_eq!(
+            conf.password.as_deref(),
+            Some(NetworkState::PASSWORD_HID_BY_NMSTATE)
+        );
+        assert!(!serde_yaml::to_string(&conf).unwrap().contains("password2"));
+    }
+
+    #[test]
+    fn test_8021x_hidden_secrets_treated_as_not_set() {
+        let mut conf = gen_conf();
+        conf.hide_secrets();
+
+        conf.sanitize("eth1").unwrap();
+
+        assert_eq!(conf.private_key_password, None);
+        assert_eq!(conf.password, None);
+    }
+
+    #[test]
+    fn test_8021x_reject_quote_and_newline() {
+        for identity in ["a\"b", "a\nnetwork={", "a\rb"] {
+            let mut conf = gen_conf();
+            conf.identity = Some(identity.to_string());
+
+            let result = conf.sanitize("eth1");
+            assert!(result.is_err());
+            if let Err(e) = result {
+                assert_eq!(e.kind, ErrorKind::InvalidArgument);
+            }
+        }
+    }
+
+    #[test]
+    fn test_8021x_reject_unknown_eap_method() {
+        let mut conf = gen_conf();
+        conf.eap = Some(vec!["tls\n}\nnetwork={".to_string()]);
+
+        let result = conf.sanitize("eth1");
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::InvalidArgument);
+        }
+    }
+
+    #[test]
+    fn test_8021x_reject_unknown_phase2_auth() {
+        let mut conf = gen_conf();
+        conf.eap = Some(vec!["peap".to_string()]);
+        conf.phase2_auth = Some("mschapv2\"\nidentity=\"x".to_string());
+
+        let result = conf.sanitize("eth1");
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::InvalidArgument);
+        }
+    }
+}
@@ -6,10 +6,11 @@ use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::{
     BaseInterface, BondInterface, DummyInterface, ErrorKind, EthernetInterface,
-    HsrInterface, InfiniBandInterface, IpsecInterface, LinuxBridgeInterface,
-    LoopbackInterface, MacSecInterface, MacVlanInterface, MacVtapInterface,
-    NipartError, OvsBridgeInterface, OvsInterface, VlanInterface, VrfInterface,
-    VxlanInterface, XfrmInterface,
+    GreInterface, GreTapInterface, HsrInterface, InfiniBandInterface,
+    IpVlanInterface, IpsecInterface, LinuxBridgeInterface, LoopbackInterface,
+    MacSecInterface, MacVlanInterface, MacVtapInterface, NipartError,
+    OvsBridgeInterface, OvsInterface, TunInterface, VlanInterface,
+    VrfInterface, VxlanInterface, WireguardInterface, XfrmInterface,
 };
 
 use super::json::merge_json_value;
@@ -33,12 +34,22 @@ pub enum InterfaceType {
     /// Ethernet interface.
     /// Deserialize and serialize from/to 'ethernet'.
     Ethernet,
+    /// GRE tunnel interface.
+    /// Deserialize and serialize from/to 'gre'.
+    Gre,
+    /// GRE TAP tunnel interface.
+    /// Deserialize and serialize from/to 'gretap'.
+    #[serde(rename = "gretap")]
+    GreTap,
     /// HSR interface.
     /// Deserialize and serialize from/to 'hsr'.
     Hsr,
     /// Loopback interface.
     /// Deserialize and serialize from/to 'loopback'.
     Loopback,
+    /// IP VLAN interface.
+    /// Deserialize and serialize from/to 'ip-vlan'.
+    IpVlan,
     /// MAC VLAN interface.
     /// Deserialize and serialize from/to 'mac-vlan'.
     MacVlan,
@@ -67,7 +78,7 @@ pub enum InterfaceType {
     /// Deserialize and serialize from/to 'infiniband'.
     #[serde(rename = "infiniband")]
     InfiniBand,
-    /// TUN interface. Only used for query, will be ignored when applying.
+    /// TUN/TAP interface.
     /// Deserialize and serialize from/to 'tun'.
     Tun,
     /// MACsec interface.
@@ -78,6 +89,9 @@ pub enum InterfaceType {
     Ipsec,
     /// Linux Xfrm kernel interface
     Xfrm,
+    /// WireGuard interface.
+    /// Deserialize and serialize from/to 'wireguard'.
+    Wireguard,
     /// Unknown interface.
     Unknown,
     /// Reserved for future use.
@@ -102,8 +116,11 @@ impl std::fmt::Display for InterfaceType {
                 InterfaceType::LinuxBridge => "linux-bridge",
                 InterfaceType::Dummy => "dummy",
                 InterfaceType::Ethernet => "ethernet",
+                InterfaceType::Gre => "gre",
+                InterfaceType::GreTap => "gretap",
                 InterfaceType::Hsr => "hsr",
                 InterfaceType::Loopback => "loopback",
+                InterfaceType::IpVlan => "ip-vlan",
                 InterfaceType::MacVlan => "mac-vlan",
                 InterfaceType::MacVtap => "mac-vtap",
                 InterfaceType::OvsBridge => "ovs-bridge",
@@ -118,6 +135,7 @@ impl std::fmt::Display for InterfaceType {
                 InterfaceType::MacSec => "macsec",
                 InterfaceType::Ipsec => "ipsec",
                 InterfaceType::Xfrm => "xfrm",
+                InterfaceType::Wireguard => "wireguard",
                 InterfaceType::Other(ref s) => s,
             }
         )
@@ -152,8 +170,8 @@ pub enum InterfaceState {
     /// Deserialize and serialize from/to 'up'.
     Up,
     /// For apply action, down means configuration still exist but
-    /// deactivate. The virtual interface will be removed and other interface
-    /// will be reverted to down state or up with IP disabled state.
+    /// deactivate. The link will be set to administrative down, virtual
+    /// interface will not be removed, use `Absent` for removal.
     /// Deserialize and serialize from/to 'down'.
     Down,
     /// Only for apply action to remove configuration and deactivate the
@@ -262,6 +280,8 @@ pub enum Interface {
     Vlan(Box<VlanInterface>),
     /// VxLAN interface.
     Vxlan(Box<VxlanInterface>),
+    /// IP VLAN interface.
+    IpVlan(Box<IpVlanInterface>),
     /// MAC VLAN interface.
     MacVlan(Box<MacVlanInterface>),
     /// MAC VTAP interface.
@@ -278,6 +298,14 @@ pub enum Interface {
     Ipsec(Box<IpsecInterface>),
     /// Linux xfrm interface
     Xfrm(Box<XfrmInterface>),
+    /// TUN/TAP interface
+    Tun(Box<TunInterface>),
+    /// GRE tunnel interface
+    Gre(Box<GreInterface>),
+    /// GRE TAP tunnel interface
+    GreTap(Box<GreTapInterface>),
+    /// WireGuard interface
+    Wireguard(Box<WireguardInterface>),
 }
 
 impl<'de> Deserialize<'de> for Interface {
@@ -354,6 +382,11 @@ impl<'de> Deserialize<'de> for Interface {
                     .map_err(serde::de::Error::custom)?;
                 Ok(Interface::OvsBridge(Box::new(inner)))
             }
+            Some(InterfaceType::IpVlan) => {
+                let inner = IpVlanInterface::deserialize(v)
+                    .map_err(serde::de::Error::custom)?;
+                Ok(Interface::IpVlan(Box::new(inner)))
+            }
             Some(InterfaceType::MacVlan) => {
                 let inner = MacVlanInterface::deserialize(v)
                     .map_err(serde::de::Error::custom)?;
@@ -399,6 +432,26 @@ impl<'de> Deserialize<'de> for Interface {
                     .map_err(serde::de::Error::custom)?;
                 Ok(Interface::Xfrm(Box::new(inner)))
             }
+            Some(InterfaceType::Tun) => {
+                let inner = TunInterface::deserialize(v)
+                    .map_err(serde::de::Error::custom)?;
+                Ok(Interface::Tun(Box::new(inner)))
+            }
+            Some(InterfaceType::Gre) => {
+                let inner = GreInterface::deserialize(v)
+                    .map_err(serde::de::Error::custom)?;
+                Ok(Interface::Gre(Box::new(inner)))
+            }
+            Some(InterfaceType::GreTap) => {
+                let inner = GreTapInterface::deserialize(v)
+                    .map_err(serde::de::Error::custom)?;
+                Ok(Interface::GreTap(Box::new(inner)))
+            }
+            Some(InterfaceType::Wireguard) => {
+                let inner = WireguardInterface::deserialize(v)
+                    .map_err(serde::de::Error::custom)?;
+                Ok(Interface::Wireguard(Box::new(inner)))
+            }
             Some(iface_type) => {
                 log::warn!("Unsupported interface type {}", iface_type);
                 let inner = UnknownInterface::deserialize(v)
@@ -477,6 +530,11 @@ impl Interface {
                 new_iface.base = iface.base.clone_name_type_only();
                 Self::Bond(Box::new(new_iface))
             }
+            Self::IpVlan(iface) => {
+                let mut new_iface = IpVlanInterface::new();
+                new_iface.base = iface.base.clone_name_type_only();
+                Self::IpVlan(Box::new(new_iface))
+            }
             Self::MacVlan(iface) => {
                 let mut new_iface = MacVlanInterface::new();
                 new_iface.base = iface.base.clone_name_type_only();
@@ -524,6 +582,26 @@ impl Interface {
                 new_iface.base = iface.base.clone_name_type_only();
                 Self::Xfrm(Box::new(new_iface))
             }
+            Self::Tun(iface) => {
+                let mut new_iface = TunInterface::new();
+                new_iface.base = iface.base.clone_name_type_only();
+                Self::Tun(Box::new(new_iface))
+            }
+            Self::Gre(iface) => {
+                let mut new_iface = GreInterface::new();
+                new_iface.base = iface.base.clone_name_type_only();
+                Self::Gre(Box::new(new_iface))
+            }
+            Self::GreTap(iface) => {
+                let mut new_iface = GreTapInterface::new();
+                new_iface.base = iface.base.clone_name_type_only();
+                Self::GreTap(Box::new(new_iface))
+            }
+            Self::Wireguard(iface) => {
+                let mut new_iface = WireguardInterface::new();
+                new_iface.base = iface.base.clone_name_type_only();
+                Self::Wireguard(Box::new(new_iface))
+            }
             Self::Unknown(iface) => {
                 let mut new_iface = UnknownInterface::new();
                 new_iface.base = iface.base.clone_name_type_only();
@@ -614,6 +692,7 @@ impl Interface {
             Self::Dummy(iface) => &iface.base,
             Self::OvsBridge(iface) => &iface.base,
             Self::OvsInterface(iface) => &iface.base,
+            Self::IpVlan(iface) => &iface.base,
             Self::MacVlan(iface) => &iface.base,
             Self::MacVtap(iface) => &iface.base,
             Self::Vrf(iface) => &iface.base,
@@ -622,6 +701,10 @@ impl Interface {
             Self::MacSec(iface) => &iface.base,
             Self::Ipsec(iface) => &iface.base,
             Self::Xfrm(iface) => &iface.base,
+            Self::Tun(iface) => &iface.base,
+            Self::Gre(iface) => &iface.base,
+            Self::GreTap(iface) => &iface.base,
+            Self::Wireguard(iface) => &iface.base,
             Self::Unknown(iface) => &iface.base,
         }
     }
@@ -637,6 +720,7 @@ impl Interface {
             Self::Dummy(iface) => &mut iface.base,
             Self::OvsInterface(iface) => &mut iface.base,
             Self::OvsBridge(iface) => &mut iface.base,
+            Self::IpVlan(iface) => &mut iface.base,
             Self::MacVlan(iface) => &mut iface.base,
             Self::MacVtap(iface) => &mut iface.base,
             Self::Vrf(iface) => &mut iface.base,
@@ -645,6 +729,10 @@ impl Interface {
             Self::MacSec(iface) => &mut iface.base,
             Self::Ipsec(iface) => &mut iface.base,
             Self::Xfrm(iface) => &mut iface.base,
+            Self::Tun(iface) => &mut iface.base,
+            Self::Gre(iface) => &mut iface.base,
+            Self::GreTap(iface) => &mut iface.base,
+            Self::Wireguard(iface) => &mut iface.base,
             Self::Unknown(iface) => &mut iface.base,
         }
     }
@@ -690,7 +778,11 @@ impl Interface {
             Interface::OvsInterface(iface) => iface.sanitize(is_desired)?,
             Interface::OvsBridge(iface) => iface.sanitize(is_desired)?,
             Interface::Vrf(iface) => iface.sanitize(is_desired)?,
-            Interface::Bond(iface) => iface.sanitize()?,
+            Interface::Bond(iface) => iface.sanitize(is_desired)?,
+            Interface::IpVlan(iface) => iface.sanitize(is_desired)?,
+            Interface::Gre(iface) => iface.sanitize(is_desired)?,
+            Interface::GreTap(iface) => iface.sanitize(is_desired)?,
+            Interface::Wireguard(iface) => iface.sanitize(is_desired)?,
             Interface::MacVlan(iface) => iface.sanitize(is_desired)?,
             Interface::MacVtap(iface) => iface.sanitize(is_desired)?,
             Interface::Loopback(iface) => iface.sanitize(is_desired)?,
@@ -702,11 +794,14 @@ impl Interface {
         Ok(())
     }
 
-    pub(crate) fn parent(&self) -> Option<&str> {
+    pub fn parent(&self) -> Option<&str> {
         match self {
             Interface::Vlan(vlan) => vlan.parent(),
             Interface::Vxlan(vxlan) => vxlan.parent(),
             Interface::OvsInterface(ovs) => ovs.parent(),
+            Interface::IpVlan(vlan) => vlan.parent(),
+            Interface::Gre(gre) => gre.parent(),
+            Interface::GreTap(gre) => gre.parent(),
             Interface::MacVlan(vlan) => vlan.parent(),
             Interface::MacVtap(vtap) => vtap.parent(),
             Interface::InfiniBand(ib) => ib.parent(),
@@ -816,6 +911,7 @@ impl MergedInterface {
         self.post_inter_ifaces_process_vrf()?;
         self.post_inter_ifaces_process_bond()?;
         self.post_inter_ifaces_process_vlan();
+        self.post_inter_ifaces_process_tun()?;
 
         if let Some(apply_iface) = self.for_apply.as_mut() {
             apply_iface.sanitize(true)?;
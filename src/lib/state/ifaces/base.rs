@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::{
     DispatchConfig, ErrorKind, EthtoolConfig, Ieee8021XConfig,
@@ -20,6 +20,11 @@ pub struct BaseInterface {
     /// if `profile_name` not defined, this will be used as profile name.
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    /// Name of the connection profile, used as `[connection] id` of
+    /// generated NetworkManager keyfile. Default to interface name.
+    /// Kernel only backend does not store profile, hence this property
+    /// only changes how desired interface is identified there.
+    /// Serialize and deserialize to/from `profile-name`.
     pub profile_name: Option<String>,
     #[serde(
         skip_serializing_if = "crate::state::serializer::is_option_string_empty"
@@ -62,9 +67,13 @@ pub struct BaseInterface {
     #[serde(
         skip_serializing_if = "Option::is_none",
         default,
-        deserialize_with = "crate::state::deserializer::option_u64_or_string"
+        deserialize_with = "option_mtu_or_inherit"
     )]
     /// Maximum transmission unit.
+    /// When applying, setting to `auto` or `inherit` will use the MTU of
+    /// parent interface(e.g. base interface of VLAN) or the MTU of controller
+    /// for port interface. Resolved to concrete MTU before applying and
+    /// verifying.
     pub mtu: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     /// Minimum MTU allowed. Ignored during apply.
@@ -79,6 +88,18 @@ pub struct BaseInterface {
     /// network interface activated.
     /// Serialize and deserialize to/from `wait-ip`.
     pub wait_ip: Option<WaitIp>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// Milliseconds to wait for carrier after bringing link up and before
+    /// configuring IP addresses and routes. Useful for switch ports slow to
+    /// get carrier. On timeout, apply continues with warning logged, but
+    /// verification of [BaseInterface::wait_ip] will still fail if IP not
+    /// acquired in time. Not stored in kernel, hence not shown in query.
+    /// Serialize and deserialize to/from `wait-carrier`.
+    pub wait_carrier: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     /// IPv4 information.
     /// Hided if interface is not allowed to hold IP information(e.g. port of
@@ -111,10 +132,33 @@ pub struct BaseInterface {
     /// Serialize and deserialize to/from `accpet-all-mac-addresses`.
     pub accept_all_mac_addresses: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    /// Copy the MAC address from specified interface.
+    /// Copy the MAC address from specified interface. For bond and linux
+    /// bridge, the specified interface should be its port. Resolved to
+    /// concrete MAC address before applying and verifying.
     /// Ignored during serializing.
     /// Deserialize from `copy-mac-from`.
     pub copy_mac_from: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        rename = "copy-ip-config-from"
+    )]
+    /// Copy the IPv4 and IPv6 configurations(addresses and DHCP/autoconf
+    /// flags) from specified interface. Resolved to concrete IP
+    /// configurations before applying and verifying. The `ipv4` and `ipv6`
+    /// of this interface will be overridden.
+    /// Ignored during serializing.
+    /// Deserialize from `copy-ip-config-from`.
+    pub copy_ip_from: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_bool_or_string"
+    )]
+    /// When set to true along with `copy-ip-config-from`, disable IPv4 and
+    /// IPv6 on the source interface after copying, so the IP is moved instead
+    /// of duplicated. Only valid for applying.
+    /// Serialize and deserialize to/from `copy-ip-config-disable-source`.
+    pub copy_ip_config_disable_source: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "ovs-db")]
     /// Interface specific OpenvSwitch database configurations.
     pub ovsdb: Option<OvsDbIfaceConfig>,
@@ -131,6 +175,24 @@ pub struct BaseInterface {
     /// Dispatch script configurations
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dispatch: Option<DispatchConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Rename this existing kernel interface to specified name while
+    /// preserving its configuration. The interface will be set to
+    /// administrative down during renaming. Only valid for applying.
+    /// Serialize and deserialize to/from `rename-to`.
+    pub rename_to: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_i32_or_string"
+    )]
+    /// Preference of this interface when multiple interfaces got default
+    /// gateway from DHCP, higher value means more preferred. Nipart daemon
+    /// will use lower route metric for default gateway of interface with
+    /// higher priority. Ignored if `auto-route-metric` of IP configuration
+    /// is defined. Not stored in kernel, hence not shown in query.
+    /// Serialize and deserialize to/from `autoconnect-priority`.
+    pub autoconnect_priority: Option<i32>,
     #[serde(skip)]
     pub controller_type: Option<InterfaceType>,
     // The interface lowest up_priority will be activated first.
@@ -146,9 +208,14 @@ pub struct BaseInterface {
 }
 
 impl BaseInterface {
+    // Placeholder of `auto` or `inherit` MTU before resolved to MTU of parent
+    // or controller
+    pub(crate) const MTU_INHERIT: u64 = u64::MAX;
+
     // Besides normal HashMap merging:
     //  * the IP stacks need extra care
     //  * `copy_mac_from` is skip_serializing
+    //  * `copy_ip_from` is skip_serializing
     //  * `permanent_mac_address` is skip_serializing
     pub(crate) fn special_merge(&mut self, desired: &Self, current: &Self) {
         if let Some(ipv4) = self.ipv4.as_mut() {
@@ -170,6 +237,9 @@ impl BaseInterface {
                 .clone_from(&current.permanent_mac_address);
         }
         self.copy_mac_from.clone_from(&desired.copy_mac_from);
+        self.copy_ip_from.clone_from(&desired.copy_ip_from);
+        self.copy_ip_config_disable_source
+            .clone_from(&desired.copy_ip_config_disable_source);
     }
 
     fn has_controller(&self) -> bool {
@@ -216,6 +286,9 @@ impl BaseInterface {
         if let Some(conf) = self.ieee8021x.as_mut() {
             conf.hide_secrets();
         }
+        if let Some(ipv6_conf) = self.ipv6.as_mut() {
+            ipv6_conf.hide_secrets();
+        }
     }
 
     pub(crate) fn is_ipv4_enabled(&self) -> bool {
@@ -238,8 +311,16 @@ impl BaseInterface {
         self.max_mtu = None;
         self.min_mtu = None;
         self.copy_mac_from = None;
+        self.copy_ip_from = None;
+        self.copy_ip_config_disable_source = None;
         self.driver = None;
 
+        if is_desired {
+            if let Some(conf) = self.ieee8021x.as_mut() {
+                conf.sanitize(self.name.as_str())?;
+            }
+        }
+
         if let Some(ipv4_conf) = self.ipv4.as_mut() {
             ipv4_conf.sanitize(is_desired)?;
         }
@@ -267,6 +348,7 @@ impl BaseInterface {
 
         if !self.can_have_ip() {
             self.wait_ip = None;
+            self.wait_carrier = None;
         }
 
         if is_desired
@@ -301,6 +383,22 @@ fn default_iface_type() -> InterfaceType {
     InterfaceType::Unknown
 }
 
+fn option_mtu_or_inherit<'de, D>(
+    deserializer: D,
+) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<serde_json::Value>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(serde_json::Value::String(s)) if s == "auto" || s == "inherit" => {
+            Ok(Some(BaseInterface::MTU_INHERIT))
+        }
+        Some(v) => crate::state::deserializer::option_u64_or_string(v)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
 impl MergedInterface {
     pub(crate) fn post_inter_ifaces_process_base_iface(
         &mut self,
@@ -308,14 +406,18 @@ impl MergedInterface {
         self.post_inter_ifaces_process_ip()?;
         self.post_inter_ifaces_process_mptcp()?;
         self.post_inter_ifaces_process_ethtool();
+        self.resolve_ethtool_ring_max()?;
+        self.process_ethtool_fec()?;
+        self.process_ethtool_channels()?;
         self.validate_mtu()?;
         self.validate_can_have_ip()?;
         Ok(())
     }
 
     fn validate_mtu(&self) -> Result<(), NipartError> {
+        // Use `for_apply` as `auto` MTU is resolved there
         if let (Some(desired), Some(current)) = (
-            self.desired.as_ref().map(|i| i.base_iface()),
+            self.for_apply.as_ref().map(|i| i.base_iface()),
             self.current.as_ref().map(|i| i.base_iface()),
         ) {
             if let (Some(desire_mtu), Some(min_mtu), Some(max_mtu)) =
@@ -116,15 +116,61 @@ impl BondInterface {
         }
     }
 
-    pub(crate) fn sanitize(&mut self) -> Result<(), NipartError> {
+    pub(crate) fn sanitize(
+        &mut self,
+        is_desired: bool,
+    ) -> Result<(), NipartError> {
+        if is_desired {
+            if let Some(bond_conf) = self.bond.as_mut() {
+                if bond_conf.ad_info.is_some() {
+                    log::info!(
+                        "Ignoring query only bond ad-info of interface {}",
+                        self.base.name.as_str()
+                    );
+                    bond_conf.ad_info = None;
+                }
+            }
+        }
         self.sort_ports();
         self.sort_ports_config();
         self.drop_empty_arp_ip_target();
         self.make_ad_actor_system_mac_upper_case();
+        self.assign_auto_queue_id();
         self.check_overlap_queue_id()?;
         Ok(())
     }
 
+    // Assign smallest unused non-zero queue ID to ports requesting
+    // `queue-id: auto` in the order of port name.
+    fn assign_auto_queue_id(&mut self) {
+        let ports_conf =
+            match self.bond.as_mut().and_then(|b| b.ports_config.as_mut()) {
+                Some(p) => p,
+                None => return,
+            };
+        let mut used_qids: Vec<u16> = ports_conf
+            .iter()
+            .filter_map(|p| p.queue_id)
+            .filter(|q| *q != 0 && *q != BondPortConfig::QUEUE_ID_AUTO)
+            .collect();
+        let mut next_qid = 1u16;
+        for port_conf in ports_conf
+            .iter_mut()
+            .filter(|p| p.queue_id == Some(BondPortConfig::QUEUE_ID_AUTO))
+        {
+            while used_qids.contains(&next_qid) {
+                next_qid += 1;
+            }
+            log::debug!(
+                "Assigning queue-id {next_qid} to port {} of bond {}",
+                port_conf.name,
+                self.base.name
+            );
+            port_conf.queue_id = Some(next_qid);
+            used_qids.push(next_qid);
+        }
+    }
+
     // In kernel code drivers/net/bonding/bond_options.c
     // bond_option_queue_id_set(), kernel is not allowing multiple bond port
     // holding the same queue ID, hence we raise error when queue id overlapped.
@@ -526,6 +572,11 @@ pub struct BondConfig {
     /// names specified in `port` and `ports-config` conflict with each
     /// other.
     pub ports_config: Option<Vec<BondPortConfig>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Deserialize and serialize from/to `ad-info`.
+    /// Query only, 802.3ad(LACP) aggregation status for debugging why
+    /// bond ports are not aggregating. Ignored when applying.
+    pub ad_info: Option<BondAdInfo>,
 }
 
 impl BondConfig {
@@ -534,6 +585,38 @@ impl BondConfig {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+/// Query only 802.3ad(LACP) information of active aggregator.
+pub struct BondAdInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Active aggregator ID.
+    pub aggregator: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Number of ports in active aggregator.
+    pub num_ports: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// System MAC address of local(actor) LACP system.
+    pub actor_system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Aggregation key of local(actor) LACP system.
+    pub actor_key: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// System MAC address of LACP partner. All zero means no LACPDU
+    /// received from partner.
+    pub partner_mac: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Aggregation key of LACP partner.
+    pub partner_key: Option<u16>,
+}
+
+impl BondAdInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
 #[non_exhaustive]
 #[serde(remote = "BondAdSelect", rename_all = "kebab-case")]
@@ -1494,9 +1577,13 @@ pub struct BondPortConfig {
     #[serde(
         skip_serializing_if = "Option::is_none",
         default,
-        deserialize_with = "crate::state::deserializer::option_u16_or_string"
+        deserialize_with = "option_queue_id_or_auto"
     )]
     /// Deserialize and serialize from/to `queue-id`.
+    /// When applying, `None` means preserving current queue ID, `0` means
+    /// using default queue(no queue mapping), setting to `auto` will assign
+    /// the smallest non-zero queue ID not used by other ports in
+    /// desired `ports-config` sorted by port name.
     pub queue_id: Option<u16>,
 }
 
@@ -1513,6 +1600,9 @@ impl std::fmt::Display for BondPortConfig {
 }
 
 impl BondPortConfig {
+    // Placeholder of `auto` before resolved to unused queue ID
+    pub(crate) const QUEUE_ID_AUTO: u16 = u16::MAX;
+
     pub fn new() -> Self {
         Self::default()
     }
@@ -1522,3 +1612,111 @@ impl BondPortConfig {
             || (self.queue_id.is_some() && self.queue_id != current.queue_id)
     }
 }
+
+fn option_queue_id_or_auto<'de, D>(
+    deserializer: D,
+) -> Result<Option<u16>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<serde_json::Value>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(serde_json::Value::String(s)) if s == "auto" => {
+            Ok(Some(BondPortConfig::QUEUE_ID_AUTO))
+        }
+        Some(v) => crate::state::deserializer::option_u16_or_string(v)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BondInterface;
+    use crate::ErrorKind;
+
+    fn gen_bond(ports_config: &str) -> BondInterface {
+        serde_yaml::from_str(&format!(
+            r"---
+            name: bond99
+            type: bond
+            state: up
+            link-aggregation:
+              mode: balance-rr
+              port:
+              - eth1
+              - eth2
+              - eth3
+              ports-config:{ports_config}"
+        ))
+        .unwrap()
+    }
+
+    fn get_queue_ids(bond: &BondInterface) -> Vec<(&str, Option<u16>)> {
+        bond.bond
+            .as_ref()
+            .and_then(|b| b.ports_config.as_ref())
+            .map(|ports| {
+                ports
+                    .iter()
+                    .map(|p| (p.name.as_str(), p.queue_id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_bond_port_auto_queue_id() {
+        let mut bond = gen_bond(
+            r"
+              - name: eth3
+                queue-id: auto
+              - name: eth1
+                queue-id: auto
+              - name: eth2
+                queue-id: auto",
+        );
+
+        bond.sanitize(true).unwrap();
+
+        assert_eq!(
+            get_queue_ids(&bond),
+            vec![("eth1", Some(1)), ("eth2", Some(2)), ("eth3", Some(3))]
+        );
+    }
+
+    #[test]
+    fn test_bond_port_auto_queue_id_skip_used() {
+        let mut bond = gen_bond(
+            r"
+              - name: eth1
+                queue-id: auto
+              - name: eth2
+                queue-id: 1
+              - name: eth3
+                queue-id: 0",
+        );
+
+        bond.sanitize(true).unwrap();
+
+        assert_eq!(
+            get_queue_ids(&bond),
+            vec![("eth1", Some(2)), ("eth2", Some(1)), ("eth3", Some(0))]
+        );
+    }
+
+    #[test]
+    fn test_bond_port_overlap_queue_id() {
+        let mut bond = gen_bond(
+            r"
+              - name: eth1
+                queue-id: 2
+              - name: eth2
+                queue-id: 2",
+        );
+
+        assert_eq!(
+            bond.sanitize(true).unwrap_err().kind,
+            ErrorKind::InvalidArgument
+        );
+    }
+}
@@ -9,6 +9,9 @@ use serde::{
 
 use crate::{ErrorKind, NipartError};
 
+const BRIDGE_VLAN_ID_MIN: u16 = 1;
+const BRIDGE_VLAN_ID_MAX: u16 = 4094;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 #[non_exhaustive]
@@ -46,6 +49,47 @@ impl BridgePortVlanConfig {
         Self::default()
     }
 
+    /// Trunk port carrying all VLANs from 2 to 4094 except specified ones.
+    /// VLAN 1 is left out as it is the default PVID of linux bridge.
+    /// The trunk tags are stored as compressed ranges, hence large trunk
+    /// port does not need enormous list of VLAN IDs.
+    pub fn new_trunk_all_except(excludes: &[u16]) -> Result<Self, NipartError> {
+        for vid in excludes {
+            validate_vlan_id(*vid)?;
+        }
+        let mut trunk_tags = Vec::new();
+        let mut range_start: Option<u16> = None;
+        for vid in (BRIDGE_VLAN_ID_MIN + 1)..=BRIDGE_VLAN_ID_MAX {
+            if excludes.contains(&vid) {
+                if let Some(start) = range_start.take() {
+                    trunk_tags
+                        .push(BridgePortTrunkTag::new_range(start, vid - 1));
+                }
+            } else if range_start.is_none() {
+                range_start = Some(vid);
+            }
+        }
+        if let Some(start) = range_start {
+            trunk_tags
+                .push(BridgePortTrunkTag::new_range(start, BRIDGE_VLAN_ID_MAX));
+        }
+        if trunk_tags.is_empty() {
+            let e = NipartError::new(
+                ErrorKind::InvalidArgument,
+                "Bridge VLAN trunk excluding all VLANs from 2 to 4094 \
+                holds no trunk tag"
+                    .to_string(),
+            );
+            log::error!("{}", e);
+            return Err(e);
+        }
+        Ok(Self {
+            mode: Some(BridgePortVlanMode::Trunk),
+            trunk_tags: Some(trunk_tags),
+            ..Default::default()
+        })
+    }
+
     pub fn is_changed(&self, current: &Self) -> bool {
         (self.enable_native.is_some()
             && self.enable_native != current.enable_native)
@@ -98,8 +142,59 @@ impl BridgePortVlanConfig {
         }
     }
 
+    // Merge continuous sorted VLAN IDs into ranges, so verification and
+    // reporting use the same canonical form regardless how user expressed
+    // the trunk tags. Should be invoked after `flatten_vlan_ranges()` and
+    // `sort_trunk_tags()`.
+    pub(crate) fn compress_vlan_ranges(&mut self) {
+        if let Some(trunk_tags) = &self.trunk_tags {
+            let mut new_trunk_tags = Vec::new();
+            let mut cur_range: Option<(u16, u16)> = None;
+            for trunk_tag in trunk_tags {
+                let (min, max) = trunk_tag.get_vlan_tag_range();
+                cur_range = match cur_range {
+                    Some((start, end)) if end.checked_add(1) == Some(min) => {
+                        Some((start, max))
+                    }
+                    Some((start, end)) => {
+                        new_trunk_tags
+                            .push(BridgePortTrunkTag::new_range(start, end));
+                        Some((min, max))
+                    }
+                    None => Some((min, max)),
+                };
+            }
+            if let Some((start, end)) = cur_range {
+                new_trunk_tags.push(BridgePortTrunkTag::new_range(start, end));
+            }
+            self.trunk_tags = Some(new_trunk_tags);
+        }
+    }
+
     pub(crate) fn sanitize(&self, is_desired: bool) -> Result<(), NipartError> {
         if is_desired {
+            if let Some(tag) = self.tag {
+                // Tag 0 means no native VLAN
+                if tag != 0 {
+                    validate_vlan_id(tag)?;
+                }
+            }
+            for trunk_tag in self.trunk_tags.as_deref().unwrap_or_default() {
+                let (min, max) = trunk_tag.get_vlan_tag_range();
+                if min > max {
+                    let e = NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!(
+                            "Bridge VLAN trunk tag {trunk_tag} has minimum \
+                            VLAN ID larger than maximum"
+                        ),
+                    );
+                    log::error!("{}", e);
+                    return Err(e);
+                }
+                validate_vlan_id(min)?;
+                validate_vlan_id(max)?;
+            }
             if self.mode == Some(BridgePortVlanMode::Trunk)
                 && self.tag.is_some()
                 && self.tag != Some(0)
@@ -278,6 +373,16 @@ where
 }
 
 impl BridgePortTrunkTag {
+    /// Create [BridgePortTrunkTag::Id] if `min` equal to `max`, otherwise
+    /// [BridgePortTrunkTag::IdRange].
+    pub fn new_range(min: u16, max: u16) -> Self {
+        if min == max {
+            Self::Id(min)
+        } else {
+            Self::IdRange(BridgePortVlanRange { min, max })
+        }
+    }
+
     pub fn get_vlan_tag_range(&self) -> (u16, u16) {
         match self {
             Self::Id(min) => (*min, *min),
@@ -343,3 +448,78 @@ fn validate_overlap_trunk_tags(
     }
     Ok(())
 }
+
+fn validate_vlan_id(vid: u16) -> Result<(), NipartError> {
+    if !(BRIDGE_VLAN_ID_MIN..=BRIDGE_VLAN_ID_MAX).contains(&vid) {
+        let e = NipartError::new(
+            ErrorKind::InvalidArgument,
+            format!(
+                "Bridge VLAN ID {vid} is out of range, should be in range \
+                [{BRIDGE_VLAN_ID_MIN}, {BRIDGE_VLAN_ID_MAX}]"
+            ),
+        );
+        log::error!("{}", e);
+        return Err(e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BridgePortTrunkTag, BridgePortVlanConfig, BridgePortVlanMode};
+    use crate::ErrorKind;
+
+    #[test]
+    fn test_bridge_vlan_trunk_all_except() {
+        let vlan_conf =
+            BridgePortVlanConfig::new_trunk_all_except(&[100, 4094]).unwrap();
+
+        assert_eq!(vlan_conf.mode, Some(BridgePortVlanMode::Trunk));
+        assert_eq!(
+            vlan_conf.trunk_tags,
+            Some(vec![
+                BridgePortTrunkTag::new_range(2, 99),
+                BridgePortTrunkTag::new_range(101, 4093),
+            ])
+        );
+
+        let yaml = serde_yaml::to_string(&vlan_conf).unwrap();
+        let new_vlan_conf: BridgePortVlanConfig =
+            serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(new_vlan_conf, vlan_conf);
+    }
+
+    #[test]
+    fn test_bridge_vlan_trunk_all_except_invalid_id() {
+        let result = BridgePortVlanConfig::new_trunk_all_except(&[4095]);
+
+        assert_eq!(
+            result.err().map(|e| e.kind),
+            Some(ErrorKind::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn test_bridge_vlan_compress_ranges() {
+        let mut vlan_conf: BridgePortVlanConfig = serde_yaml::from_str(
+            r"---
+            mode: trunk
+            trunk-tags:
+            - id: 2
+            - id: 3
+            - id: 4
+            - id: 10",
+        )
+        .unwrap();
+
+        vlan_conf.compress_vlan_ranges();
+
+        assert_eq!(
+            vlan_conf.trunk_tags,
+            Some(vec![
+                BridgePortTrunkTag::new_range(2, 4),
+                BridgePortTrunkTag::Id(10),
+            ])
+        );
+    }
+}
@@ -9,7 +9,9 @@ use serde::{
     Serializer,
 };
 
-use crate::MergedInterface;
+use crate::{
+    state::not_synced::log_warn, ErrorKind, MergedInterface, NipartError,
+};
 
 const ETHTOOL_FEATURE_CLI_ALIAS: [(&str, &str); 17] = [
     ("rx", "rx-checksum"),
@@ -115,6 +117,9 @@ impl Serialize for EthtoolFeatureConfig {
 ///       rx-max: 256
 ///       tx: 256
 ///       tx-max: 256
+///     channels:
+///       combined: 4
+///       combined-max: 8
 /// ```
 pub struct EthtoolConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -125,8 +130,11 @@ pub struct EthtoolConfig {
         default,
         deserialize_with = "parse_ethtool_feature"
     )]
-    /// The protocol offload and other features of specified network device.
+    /// The protocol offload and other features of specified network device
+    /// indexed by kernel feature name, for example `tx-checksum-ipv4`.
     /// Only changeable features are included when querying.
+    /// Desired features which are unknown or fixed by driver are ignored with
+    /// warning.
     pub feature: Option<EthtoolFeatureConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     /// The coalescing settings of the specified network device.
@@ -134,6 +142,13 @@ pub struct EthtoolConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     /// The rx/tx ring parameters of the specified network device.
     pub ring: Option<EthtoolRingConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// The Forward Error Correction settings of the specified network
+    /// device.
+    pub fec: Option<EthtoolFecConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// The channel (queue) counts of the specified network device.
+    pub channels: Option<EthtoolChannelsConfig>,
 }
 
 impl EthtoolConfig {
@@ -142,6 +157,30 @@ impl EthtoolConfig {
     }
 
     // There are some alias on ethtool features.
+    // Current features only contains changeable ones, remove desired features
+    // which are fixed by driver or unknown to kernel.
+    pub(crate) fn remove_unchangeable_features(
+        &mut self,
+        cur_features: &EthtoolFeatureConfig,
+        log_warn: bool,
+    ) {
+        if let Some(features) = self.feature.as_mut() {
+            features.data.retain(|name, _| {
+                if cur_features.data.contains_key(name) {
+                    true
+                } else {
+                    if log_warn {
+                        log_warn(format!(
+                            "Ignoring ethtool feature {name} as it is \
+                            unknown or fixed by driver"
+                        ));
+                    }
+                    false
+                }
+            });
+        }
+    }
+
     pub(crate) fn apply_feature_alias(&mut self) {
         if let Some(features) = self.feature.as_mut() {
             for (cli_alias, kernel_name) in ETHTOOL_FEATURE_CLI_ALIAS {
@@ -357,12 +396,9 @@ impl EthtoolCoalesceConfig {
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 #[non_exhaustive]
 pub struct EthtoolRingConfig {
-    #[serde(
-        skip_serializing_if = "Option::is_none",
-        default,
-        deserialize_with = "crate::state::deserializer::option_u32_or_string"
-    )]
-    pub rx: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Setting to `max` will use the maximum size reported by driver.
+    pub rx: Option<EthtoolRingSize>,
     #[serde(
         skip_serializing_if = "Option::is_none",
         default,
@@ -398,25 +434,330 @@ pub struct EthtoolRingConfig {
     )]
     /// Deserialize and serialize from/to `rx-mini-max`.
     pub rx_mini_max: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Setting to `max` will use the maximum size reported by driver.
+    pub tx: Option<EthtoolRingSize>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// Deserialize and serialize from/to `tx-max`.
+    pub tx_max: Option<u32>,
+}
+
+impl EthtoolRingConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Replace `max` with the maximum size reported by driver
+    fn resolve_max(
+        &mut self,
+        iface_name: &str,
+        current: Option<&Self>,
+    ) -> Result<(), NipartError> {
+        if self.rx == Some(EthtoolRingSize::Max) {
+            self.rx = Some(EthtoolRingSize::Value(get_ring_max(
+                iface_name,
+                "rx",
+                current.and_then(|c| c.rx_max),
+            )?));
+        }
+        if self.tx == Some(EthtoolRingSize::Max) {
+            self.tx = Some(EthtoolRingSize::Value(get_ring_max(
+                iface_name,
+                "tx",
+                current.and_then(|c| c.tx_max),
+            )?));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[non_exhaustive]
+/// Size of ethtool rx or tx ring.
+pub enum EthtoolRingSize {
+    /// The maximum size reported by driver, resolved before applying.
+    /// Deserialize and serialize from/to `max`.
+    Max,
+    /// Explicit size. Deserialize and serialize from/to unsigned integer.
+    Value(u32),
+}
+
+impl Serialize for EthtoolRingSize {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Max => serializer.serialize_str("max"),
+            Self::Value(v) => serializer.serialize_u32(*v),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for EthtoolRingSize {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::String(s) if s == "max" => Ok(Self::Max),
+            v => crate::state::deserializer::option_u32_or_string(v)
+                .map_err(de::Error::custom)?
+                .map(Self::Value)
+                .ok_or_else(|| {
+                    de::Error::custom(
+                        "Invalid ethtool ring size, should be unsigned \
+                        integer or `max`",
+                    )
+                }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+/// The channels (queues) of network device. Changing channel count might
+/// reset the RSS indirection table of driver to default.
+pub struct EthtoolChannelsConfig {
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// Number of rx channels.
+    pub rx: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// Maximum number of rx channels reported by driver. Query only,
+    /// ignored in desired state.
+    /// Deserialize and serialize from/to `rx-max`.
+    pub rx_max: Option<u32>,
     #[serde(
         skip_serializing_if = "Option::is_none",
         default,
         deserialize_with = "crate::state::deserializer::option_u32_or_string"
     )]
+    /// Number of tx channels.
     pub tx: Option<u32>,
     #[serde(
         skip_serializing_if = "Option::is_none",
         default,
         deserialize_with = "crate::state::deserializer::option_u32_or_string"
     )]
+    /// Maximum number of tx channels reported by driver. Query only,
+    /// ignored in desired state.
     /// Deserialize and serialize from/to `tx-max`.
     pub tx_max: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// Number of channels used for link interrupts, SR-IOV coordination
+    /// and etc.
+    pub other: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// Maximum number of other channels reported by driver. Query only,
+    /// ignored in desired state.
+    /// Deserialize and serialize from/to `other-max`.
+    pub other_max: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// Number of combined channels.
+    pub combined: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// Maximum number of combined channels reported by driver. Query only,
+    /// ignored in desired state.
+    /// Deserialize and serialize from/to `combined-max`.
+    pub combined_max: Option<u32>,
 }
 
-impl EthtoolRingConfig {
+impl EthtoolChannelsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn remove_query_only(&mut self) {
+        self.rx_max = None;
+        self.tx_max = None;
+        self.other_max = None;
+        self.combined_max = None;
+    }
+
+    fn is_changed(&self, current: Option<&Self>) -> bool {
+        let Some(current) = current else {
+            return true;
+        };
+        (self.rx.is_some() && self.rx != current.rx)
+            || (self.tx.is_some() && self.tx != current.tx)
+            || (self.other.is_some() && self.other != current.other)
+            || (self.combined.is_some() && self.combined != current.combined)
+    }
+
+    // Validate desired channel counts against the maximum reported by
+    // driver.
+    fn validate(
+        &self,
+        iface_name: &str,
+        current: Option<&Self>,
+    ) -> Result<(), NipartError> {
+        for (prop_name, count, max) in [
+            ("rx", self.rx, current.and_then(|c| c.rx_max)),
+            ("tx", self.tx, current.and_then(|c| c.tx_max)),
+            ("other", self.other, current.and_then(|c| c.other_max)),
+            (
+                "combined",
+                self.combined,
+                current.and_then(|c| c.combined_max),
+            ),
+        ] {
+            let Some(count) = count else {
+                continue;
+            };
+            let e = match max {
+                None | Some(0) if count != 0 => NipartError::new(
+                    ErrorKind::NotSupportedError,
+                    format!(
+                        "Interface {iface_name} driver does not support \
+                        ethtool {prop_name} channels"
+                    ),
+                ),
+                Some(max) if count > max => NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Desired ethtool {prop_name} channel count {count} \
+                        of interface {iface_name} exceeds the maximum {max} \
+                        reported by driver"
+                    ),
+                ),
+                _ => continue,
+            };
+            log::error!("{}", e);
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct EthtoolFecConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Desired FEC mode. When querying, this is the FEC mode currently
+    /// active on the link. Verification accepts any concrete mode chosen by
+    /// driver when `auto` is desired.
+    pub mode: Option<EthtoolFecMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// FEC modes supported by driver. Query only, ignored in desired state.
+    pub supported: Option<Vec<EthtoolFecMode>>,
+}
+
+impl EthtoolFecConfig {
     pub fn new() -> Self {
         Self::default()
     }
+
+    // Validate desired mode against the supported modes reported by driver.
+    // Driver not reporting supported modes is not treated as error.
+    fn validate_mode(
+        &self,
+        iface_name: &str,
+        current: Option<&Self>,
+    ) -> Result<(), NipartError> {
+        if let (Some(mode), Some(supported)) = (
+            self.mode.as_ref(),
+            current.and_then(|c| c.supported.as_ref()),
+        ) {
+            if !supported.contains(mode) {
+                let e = NipartError::new(
+                    ErrorKind::NotSupportedError,
+                    format!(
+                        "Interface {iface_name} driver does not support \
+                        ethtool FEC mode {mode}, supported modes are: {}",
+                        supported
+                            .iter()
+                            .map(|m| m.to_string())
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Debug, Eq, PartialEq, Clone, Copy, Default,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum EthtoolFecMode {
+    /// Let driver choose FEC mode based on link mode.
+    /// Serialize and deserialize to/from `auto`.
+    #[default]
+    Auto,
+    /// Serialize and deserialize to/from `off`.
+    Off,
+    /// Reed-Solomon FEC.
+    /// Serialize and deserialize to/from `rs`.
+    Rs,
+    /// BaseR(also known as FireCode) FEC.
+    /// Serialize and deserialize to/from `baser`.
+    Baser,
+}
+
+impl std::fmt::Display for EthtoolFecMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Auto => "auto",
+                Self::Off => "off",
+                Self::Rs => "rs",
+                Self::Baser => "baser",
+            }
+        )
+    }
+}
+
+fn get_ring_max(
+    iface_name: &str,
+    prop_name: &str,
+    max: Option<u32>,
+) -> Result<u32, NipartError> {
+    max.ok_or_else(|| {
+        NipartError::new(
+            ErrorKind::NotSupportedError,
+            format!(
+                "Interface {iface_name} driver does not report maximum \
+                ethtool ring {prop_name} size, cannot use `max`"
+            ),
+        )
+    })
 }
 
 fn parse_ethtool_feature<'de, D>(
@@ -482,7 +823,108 @@ where
 }
 
 impl MergedInterface {
+    pub(crate) fn resolve_ethtool_ring_max(
+        &mut self,
+    ) -> Result<(), NipartError> {
+        let cur_ring = self
+            .current
+            .as_ref()
+            .and_then(|i| i.base_iface().ethtool.as_ref())
+            .and_then(|e| e.ring.as_ref());
+        for iface in [self.for_apply.as_mut(), self.for_verify.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            let iface_name = iface.name().to_string();
+            if let Some(ring) = iface
+                .base_iface_mut()
+                .ethtool
+                .as_mut()
+                .and_then(|e| e.ring.as_mut())
+            {
+                ring.resolve_max(iface_name.as_str(), cur_ring)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn process_ethtool_fec(&mut self) -> Result<(), NipartError> {
+        let cur_fec = self
+            .current
+            .as_ref()
+            .and_then(|i| i.base_iface().ethtool.as_ref())
+            .and_then(|e| e.fec.as_ref());
+        if let Some(for_apply) = self.for_apply.as_mut() {
+            let iface_name = for_apply.name().to_string();
+            if let Some(fec) = for_apply
+                .base_iface_mut()
+                .ethtool
+                .as_mut()
+                .and_then(|e| e.fec.as_mut())
+            {
+                fec.supported = None;
+                fec.validate_mode(iface_name.as_str(), cur_fec)?;
+            }
+        }
+        if let Some(fec) = self
+            .for_verify
+            .as_mut()
+            .and_then(|i| i.base_iface_mut().ethtool.as_mut())
+            .and_then(|e| e.fec.as_mut())
+        {
+            fec.supported = None;
+            // Driver will report the concrete mode it settled on
+            if fec.mode == Some(EthtoolFecMode::Auto) {
+                fec.mode = None;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn process_ethtool_channels(
+        &mut self,
+    ) -> Result<(), NipartError> {
+        let cur_channels = self
+            .current
+            .as_ref()
+            .and_then(|i| i.base_iface().ethtool.as_ref())
+            .and_then(|e| e.channels.as_ref());
+        if let Some(for_apply) = self.for_apply.as_mut() {
+            let iface_name = for_apply.name().to_string();
+            if let Some(channels) = for_apply
+                .base_iface_mut()
+                .ethtool
+                .as_mut()
+                .and_then(|e| e.channels.as_mut())
+            {
+                channels.remove_query_only();
+                channels.validate(iface_name.as_str(), cur_channels)?;
+                if channels.is_changed(cur_channels) {
+                    log_warn(format!(
+                        "Changing ethtool channels of interface \
+                        {iface_name} might reset its RSS indirection table \
+                        to driver default"
+                    ));
+                }
+            }
+        }
+        if let Some(channels) = self
+            .for_verify
+            .as_mut()
+            .and_then(|i| i.base_iface_mut().ethtool.as_mut())
+            .and_then(|e| e.channels.as_mut())
+        {
+            channels.remove_query_only();
+        }
+        Ok(())
+    }
+
     pub(crate) fn post_inter_ifaces_process_ethtool(&mut self) {
+        let cur_features = self
+            .current
+            .as_ref()
+            .and_then(|i| i.base_iface().ethtool.as_ref())
+            .and_then(|e| e.feature.as_ref());
         if let Some(ethtool_conf) = self
             .for_apply
             .as_mut()
@@ -490,6 +932,9 @@ impl MergedInterface {
             .and_then(|b| b.ethtool.as_mut())
         {
             ethtool_conf.apply_feature_alias();
+            if let Some(cur_features) = cur_features {
+                ethtool_conf.remove_unchangeable_features(cur_features, true);
+            }
         }
         if let Some(ethtool_conf) = self
             .for_verify
@@ -498,6 +943,355 @@ impl MergedInterface {
             .and_then(|b| b.ethtool.as_mut())
         {
             ethtool_conf.apply_feature_alias();
+            if let Some(cur_features) = cur_features {
+                ethtool_conf.remove_unchangeable_features(cur_features, false);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        EthtoolChannelsConfig, EthtoolFecConfig, EthtoolFecMode,
+        EthtoolRingConfig, EthtoolRingSize,
+    };
+    use crate::{ErrorKind, MergedNetworkState, NetworkState};
+
+    fn gen_ring(yaml: &str) -> EthtoolRingConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    // Mock of driver reported ring sizes
+    fn gen_cur_ring() -> EthtoolRingConfig {
+        gen_ring(
+            r"---
+            rx: 256
+            rx-max: 4096
+            tx: 256
+            tx-max: 1024",
+        )
+    }
+
+    #[test]
+    fn test_ethtool_ring_max_serde() {
+        let ring = gen_ring(
+            r"---
+            rx: max
+            tx: 4294967295",
+        );
+
+        assert_eq!(ring.rx, Some(EthtoolRingSize::Max));
+        assert_eq!(ring.tx, Some(EthtoolRingSize::Value(u32::MAX)));
+        assert_eq!(
+            serde_yaml::to_string(&ring).unwrap(),
+            "rx: max\ntx: 4294967295\n"
+        );
+    }
+
+    #[test]
+    fn test_ethtool_ring_size_from_string() {
+        let ring = gen_ring(
+            r#"---
+            rx: "512""#,
+        );
+
+        assert_eq!(ring.rx, Some(EthtoolRingSize::Value(512)));
+    }
+
+    #[test]
+    fn test_ethtool_ring_resolve_max() {
+        let mut ring = gen_ring(
+            r"---
+            rx: max
+            tx: max",
+        );
+
+        ring.resolve_max("eth1", Some(&gen_cur_ring())).unwrap();
+
+        assert_eq!(ring.rx, Some(EthtoolRingSize::Value(4096)));
+        assert_eq!(ring.tx, Some(EthtoolRingSize::Value(1024)));
+    }
+
+    #[test]
+    fn test_ethtool_ring_resolve_max_keep_explicit_value() {
+        let mut ring = gen_ring(
+            r"---
+            rx: 4294967295
+            tx: 512",
+        );
+
+        ring.resolve_max("eth1", Some(&gen_cur_ring())).unwrap();
+
+        assert_eq!(ring.rx, Some(EthtoolRingSize::Value(u32::MAX)));
+        assert_eq!(ring.tx, Some(EthtoolRingSize::Value(512)));
+    }
+
+    #[test]
+    fn test_ethtool_ring_resolve_max_without_driver_max() {
+        let mut ring = gen_ring(
+            r"---
+            rx: max",
+        );
+        let mut cur_ring = gen_cur_ring();
+        cur_ring.rx_max = None;
+
+        let result = ring.resolve_max("eth1", Some(&cur_ring));
+
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::NotSupportedError);
+        }
+    }
+
+    fn gen_eth1_features(tx_checksum_ipv4: bool) -> NetworkState {
+        let mut state = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ethtool:
+                feature:
+                  rx-checksum: true
+                  tx-checksum-ipv4: false",
+        )
+        .unwrap();
+        if let Some(features) = state
+            .interfaces
+            .kernel_ifaces
+            .get_mut("eth1")
+            .and_then(|i| i.base_iface_mut().ethtool.as_mut())
+            .and_then(|e| e.feature.as_mut())
+        {
+            features.insert("tx-checksum-ipv4".to_string(), tx_checksum_ipv4);
         }
+        state
+    }
+
+    #[test]
+    fn test_ethtool_toggle_feature_round_trip() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ethtool:
+                feature:
+                  tx-checksum-ipv4: true",
+        )
+        .unwrap();
+
+        let merged = MergedNetworkState::new(
+            desired,
+            gen_eth1_features(false),
+            false,
+            false,
+        )
+        .unwrap();
+
+        let apply_features = merged.interfaces.kernel_ifaces["eth1"]
+            .for_apply
+            .as_ref()
+            .and_then(|i| i.base_iface().ethtool.as_ref())
+            .and_then(|e| e.feature.as_ref())
+            .unwrap();
+        assert_eq!(apply_features.get("tx-checksum-ipv4"), Some(&true));
+        assert!(merged.verify(&gen_eth1_features(true)).is_ok());
+        assert!(merged.verify(&gen_eth1_features(false)).is_err());
+    }
+
+    #[test]
+    fn test_ethtool_fixed_feature_not_fail_verify() {
+        // tx-tcp-segmentation is fixed by driver, hence not included in
+        // current features.
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ethtool:
+                feature:
+                  tx-checksum-ipv4: true
+                  tx-tcp-segmentation: false",
+        )
+        .unwrap();
+
+        let merged = MergedNetworkState::new(
+            desired,
+            gen_eth1_features(false),
+            false,
+            false,
+        )
+        .unwrap();
+
+        let apply_features = merged.interfaces.kernel_ifaces["eth1"]
+            .for_apply
+            .as_ref()
+            .and_then(|i| i.base_iface().ethtool.as_ref())
+            .and_then(|e| e.feature.as_ref())
+            .unwrap();
+        assert!(apply_features.get("tx-tcp-segmentation").is_none());
+        assert!(merged.verify(&gen_eth1_features(true)).is_ok());
+    }
+
+    // Mock of FEC capable driver settled on `mode`
+    fn gen_fec_state(mode: &str) -> NetworkState {
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ethtool:
+                fec:
+                  mode: {mode}
+                  supported:
+                  - auto
+                  - off
+                  - rs"
+        ))
+        .unwrap()
+    }
+
+    fn merge_fec(
+        desired_mode: &str,
+        current: &NetworkState,
+    ) -> Result<MergedNetworkState, crate::NipartError> {
+        let desired = NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ethtool:
+                fec:
+                  mode: {desired_mode}"
+        ))
+        .unwrap();
+        MergedNetworkState::new(desired, current.clone(), false, false)
+    }
+
+    #[test]
+    fn test_ethtool_fec_round_trip() {
+        let fec: EthtoolFecConfig = serde_yaml::from_str(
+            r"---
+            mode: rs
+            supported:
+            - auto
+            - rs
+            - baser",
+        )
+        .unwrap();
+        assert_eq!(fec.mode, Some(EthtoolFecMode::Rs));
+        let new_fec: EthtoolFecConfig =
+            serde_yaml::from_str(&serde_yaml::to_string(&fec).unwrap())
+                .unwrap();
+        assert_eq!(new_fec, fec);
+
+        let merged = merge_fec("rs", &gen_fec_state("off")).unwrap();
+        merged.verify(&gen_fec_state("rs")).unwrap();
+        assert_eq!(
+            merged.verify(&gen_fec_state("off")).unwrap_err().kind,
+            ErrorKind::VerificationError
+        );
+    }
+
+    #[test]
+    fn test_ethtool_fec_auto_verify_with_concrete_mode() {
+        let merged = merge_fec("auto", &gen_fec_state("off")).unwrap();
+
+        merged.verify(&gen_fec_state("rs")).unwrap();
+    }
+
+    #[test]
+    fn test_ethtool_fec_unsupported_mode() {
+        let result = merge_fec("baser", &gen_fec_state("off"));
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::NotSupportedError);
+    }
+
+    // Mock of multi-queue driver supporting up to 8 combined channels
+    fn gen_channels_state(combined: u32) -> NetworkState {
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ethtool:
+                channels:
+                  combined: {combined}
+                  combined-max: 8
+                  rx-max: 0
+                  tx-max: 0"
+        ))
+        .unwrap()
+    }
+
+    fn merge_channels(
+        desired_channels: &str,
+        current: &NetworkState,
+    ) -> Result<MergedNetworkState, crate::NipartError> {
+        let desired = NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ethtool:
+                channels:
+                  {desired_channels}"
+        ))
+        .unwrap();
+        MergedNetworkState::new(desired, current.clone(), false, false)
+    }
+
+    #[test]
+    fn test_ethtool_channels_round_trip() {
+        let channels: EthtoolChannelsConfig = serde_yaml::from_str(
+            r"---
+            combined: 4
+            combined-max: 8",
+        )
+        .unwrap();
+        assert_eq!(channels.combined, Some(4));
+        let new_channels: EthtoolChannelsConfig =
+            serde_yaml::from_str(&serde_yaml::to_string(&channels).unwrap())
+                .unwrap();
+        assert_eq!(new_channels, channels);
+
+        let merged =
+            merge_channels("combined: 4", &gen_channels_state(2)).unwrap();
+        let apply_channels = merged.interfaces.kernel_ifaces["eth1"]
+            .for_apply
+            .as_ref()
+            .and_then(|i| i.base_iface().ethtool.as_ref())
+            .and_then(|e| e.channels.as_ref())
+            .unwrap();
+        assert_eq!(apply_channels.combined, Some(4));
+        assert_eq!(apply_channels.combined_max, None);
+
+        merged.verify(&gen_channels_state(4)).unwrap();
+        assert_eq!(
+            merged.verify(&gen_channels_state(2)).unwrap_err().kind,
+            ErrorKind::VerificationError
+        );
+    }
+
+    #[test]
+    fn test_ethtool_channels_exceed_max() {
+        let result = merge_channels("combined: 16", &gen_channels_state(2));
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidArgument);
+    }
+
+    #[test]
+    fn test_ethtool_channels_not_supported_by_driver() {
+        let result = merge_channels("rx: 2", &gen_channels_state(2));
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::NotSupportedError);
     }
 }
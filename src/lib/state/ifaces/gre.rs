@@ -0,0 +1,341 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BaseInterface, ErrorKind, InterfaceType, NipartError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+/// Linux kernel GRE tunnel interface. The example yaml output of
+/// [crate::NetworkState] with a GRE interface would be:
+/// ```yaml
+/// ---
+/// interfaces:
+///   - name: gre1
+///     type: gre
+///     state: up
+///     gre:
+///       local: 192.0.2.1
+///       remote: 192.0.2.2
+///       ttl: 64
+///       key: 100
+/// ```
+/// Creating new GRE interface is not supported yet.
+pub struct GreInterface {
+    #[serde(flatten)]
+    pub base: BaseInterface,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gre: Option<GreConfig>,
+}
+
+impl Default for GreInterface {
+    fn default() -> Self {
+        Self {
+            base: BaseInterface {
+                iface_type: InterfaceType::Gre,
+                ..Default::default()
+            },
+            gre: None,
+        }
+    }
+}
+
+impl GreInterface {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn sanitize(&self, is_desired: bool) -> Result<(), NipartError> {
+        if is_desired {
+            if let Some(conf) = self.gre.as_ref() {
+                conf.validate(self.base.name.as_str())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn parent(&self) -> Option<&str> {
+        self.gre.as_ref().and_then(|cfg| cfg.base_iface.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+/// Linux kernel GRE TAP (ethernet over GRE) tunnel interface. The example
+/// yaml output of [crate::NetworkState] with a GRE TAP interface would be:
+/// ```yaml
+/// ---
+/// interfaces:
+///   - name: gretap1
+///     type: gretap
+///     state: up
+///     gre:
+///       local: 192.0.2.1
+///       remote: 192.0.2.2
+///       ikey: 100
+///       okey: 101
+/// ```
+/// Creating new GRE TAP interface is not supported yet.
+pub struct GreTapInterface {
+    #[serde(flatten)]
+    pub base: BaseInterface,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gre: Option<GreConfig>,
+}
+
+impl Default for GreTapInterface {
+    fn default() -> Self {
+        Self {
+            base: BaseInterface {
+                iface_type: InterfaceType::GreTap,
+                ..Default::default()
+            },
+            gre: None,
+        }
+    }
+}
+
+impl GreTapInterface {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn sanitize(&self, is_desired: bool) -> Result<(), NipartError> {
+        if is_desired {
+            if let Some(conf) = self.gre.as_ref() {
+                conf.validate(self.base.name.as_str())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn parent(&self) -> Option<&str> {
+        self.gre.as_ref().and_then(|cfg| cfg.base_iface.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct GreConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Interface used for sending tunnel packets.
+    pub base_iface: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local: Option<std::net::IpAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<std::net::IpAddr>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u8_or_string"
+    )]
+    /// Time to live of tunnel packets. 0 means inherit from inner packet.
+    pub ttl: Option<u8>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u8_or_string"
+    )]
+    /// Type of service of tunnel packets. 1 means inherit from inner
+    /// packet.
+    pub tos: Option<u8>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// GRE key used for both directions. Cannot be used with `ikey` or
+    /// `okey`.
+    pub key: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// GRE key for incoming packets.
+    pub ikey: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// GRE key for outgoing packets.
+    pub okey: Option<u32>,
+}
+
+impl GreConfig {
+    fn validate(&self, iface_name: &str) -> Result<(), NipartError> {
+        if self.key.is_some() && (self.ikey.is_some() || self.okey.is_some()) {
+            let e = NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "GRE interface {iface_name} cannot have `key` defined \
+                    along with `ikey` or `okey`"
+                ),
+            );
+            log::error!("{}", e);
+            return Err(e);
+        }
+        if let (Some(local), Some(remote)) = (self.local, self.remote) {
+            if local.is_ipv4() != remote.is_ipv4() {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "GRE interface {iface_name} has local address \
+                        {local} and remote address {remote} in different \
+                        IP family"
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ErrorKind, Interface, InterfaceType, MergedNetworkState, NetworkState,
+    };
+
+    fn gen_gre_state() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: gre1
+              type: gre
+              state: up
+              gre:
+                local: 192.0.2.1
+                remote: 192.0.2.2
+                ttl: 64
+                tos: 1
+                key: 100
+            - name: gretap1
+              type: gretap
+              state: up
+              gre:
+                local: 2001:db8::1
+                remote: 2001:db8::2
+                ikey: 100
+                okey: 101",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_gre_round_trip() {
+        let state = gen_gre_state();
+
+        let iface = &state.interfaces.kernel_ifaces["gre1"];
+        assert_eq!(iface.iface_type(), InterfaceType::Gre);
+        if let Interface::Gre(gre_iface) = iface {
+            let conf = gre_iface.gre.as_ref().unwrap();
+            assert_eq!(conf.local, Some("192.0.2.1".parse().unwrap()));
+            assert_eq!(conf.remote, Some("192.0.2.2".parse().unwrap()));
+            assert_eq!(conf.ttl, Some(64));
+            assert_eq!(conf.tos, Some(1));
+            assert_eq!(conf.key, Some(100));
+        } else {
+            panic!("Expecting GRE interface, got {iface:?}");
+        }
+
+        let iface = &state.interfaces.kernel_ifaces["gretap1"];
+        assert_eq!(iface.iface_type(), InterfaceType::GreTap);
+        if let Interface::GreTap(gre_iface) = iface {
+            let conf = gre_iface.gre.as_ref().unwrap();
+            assert_eq!(conf.local, Some("2001:db8::1".parse().unwrap()));
+            assert_eq!(conf.ikey, Some(100));
+            assert_eq!(conf.okey, Some(101));
+            assert_eq!(conf.key, None);
+        } else {
+            panic!("Expecting GRE TAP interface, got {iface:?}");
+        }
+
+        let yaml = serde_yaml::to_string(&state).unwrap();
+        assert!(yaml.contains("type: gre\n"));
+        assert!(yaml.contains("type: gretap"));
+        assert_eq!(NetworkState::new_from_yaml(&yaml).unwrap(), state);
+    }
+
+    #[test]
+    fn test_gre_verify_existing() {
+        let current = gen_gre_state();
+        let merged = MergedNetworkState::new(
+            current.clone(),
+            current.clone(),
+            false,
+            false,
+        )
+        .unwrap();
+        merged.verify(&current).unwrap();
+    }
+
+    #[test]
+    fn test_gre_key_overrides_ikey_okey() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: gretap1
+              type: gretap
+              gre:
+                key: 200",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_gre_state(), false, false)
+                .unwrap();
+        if let Interface::GreTap(gre_iface) =
+            &merged.interfaces.kernel_ifaces["gretap1"].merged
+        {
+            let conf = gre_iface.gre.as_ref().unwrap();
+            assert_eq!(conf.key, Some(200));
+            assert_eq!(conf.ikey, None);
+            assert_eq!(conf.okey, None);
+        } else {
+            panic!("Expecting GRE TAP interface");
+        }
+    }
+
+    #[test]
+    fn test_gre_key_with_ikey() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: gre1
+              type: gre
+              gre:
+                key: 100
+                ikey: 101",
+        )
+        .unwrap();
+
+        let result =
+            MergedNetworkState::new(desired, gen_gre_state(), false, false);
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidArgument);
+    }
+
+    #[test]
+    fn test_gre_mixed_ip_family() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: gre1
+              type: gre
+              gre:
+                local: 192.0.2.1
+                remote: 2001:db8::2",
+        )
+        .unwrap();
+
+        let result =
+            MergedNetworkState::new(desired, gen_gre_state(), false, false);
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidArgument);
+    }
+}
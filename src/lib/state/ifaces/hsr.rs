@@ -2,7 +2,9 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::{BaseInterface, InterfaceType, NipartError};
+use crate::{
+    state::not_synced::log_warn, BaseInterface, InterfaceType, NipartError,
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
@@ -54,7 +56,11 @@ impl HsrInterface {
             if let Some(conf) = &mut self.hsr {
                 if let Some(address) = &mut conf.supervision_address {
                     address.as_mut().make_ascii_uppercase();
-                    log::warn!("The supervision-address is read-only, ignoring it on desired state.");
+                    log_warn(
+                        "The supervision-address is read-only, ignoring it \
+                        on desired state."
+                            .to_string(),
+                    );
                 }
             }
         }
@@ -7,6 +7,7 @@ use serde::{
 };
 
 use crate::{
+    state::not_synced::{log_warn, prefer_mac_identifier_matches},
     ErrorKind, EthernetInterface, Interface, InterfaceIdentifier,
     InterfaceState, InterfaceType, MergedInterface, NipartError,
 };
@@ -169,6 +170,9 @@ impl Interfaces {
             if let Interface::Ipsec(ipsec_iface) = iface {
                 ipsec_iface.hide_secrets();
             }
+            if let Interface::Wireguard(wg_iface) = iface {
+                wg_iface.hide_secrets();
+            }
         }
     }
 
@@ -385,51 +389,67 @@ impl Interfaces {
             // If `permanent_mac_address` got no matches, fallback to
             // `mac_address`
             for use_permanent_addr in [true, false] {
-                for cur_iface in current.kernel_ifaces.values() {
-                    if iface.iface_type() != InterfaceType::Unknown
-                        && iface.iface_type() != cur_iface.iface_type()
+                let matched_ifaces: Vec<&Interface> = current
+                    .kernel_ifaces
+                    .values()
+                    .filter(|cur_iface| {
+                        iface.iface_type() == InterfaceType::Unknown
+                            || iface.iface_type() == cur_iface.iface_type()
+                    })
+                    .filter(|cur_iface| {
+                        let cur_mac_addr = if use_permanent_addr {
+                            cur_iface
+                                .base_iface()
+                                .permanent_mac_address
+                                .as_deref()
+                        } else {
+                            cur_iface.base_iface().mac_address.as_deref()
+                        };
+                        cur_mac_addr.map(|m| m.to_ascii_uppercase()).as_deref()
+                            == Some(&mac_address)
+                    })
+                    .collect();
+                let matched_ifaces =
+                    prefer_mac_identifier_matches(iface.name(), matched_ifaces);
+                if matched_ifaces.len() > 1 {
+                    let mut names: Vec<&str> =
+                        matched_ifaces.iter().map(|i| i.name()).collect();
+                    names.sort_unstable();
+                    return Err(NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!(
+                            "Desired interface {} has \
+                            `identifier: mac-address` with MAC address \
+                            {mac_address}, but multiple interfaces are \
+                            holding that MAC address: {}",
+                            iface.name(),
+                            names.join(", ")
+                        ),
+                    ));
+                }
+                if let Some(cur_iface) = matched_ifaces.first() {
+                    let mut new_iface = if iface.iface_type()
+                        == InterfaceType::Unknown
                     {
-                        continue;
-                    }
-                    let cur_mac_addr = if use_permanent_addr {
-                        cur_iface
-                            .base_iface()
-                            .permanent_mac_address
-                            .as_deref()
-                            .map(|m| m.to_ascii_uppercase())
+                        let mut new_iface_value = serde_json::to_value(iface)?;
+                        if let Some(obj) = new_iface_value.as_object_mut() {
+                            obj.insert(
+                                "type".to_string(),
+                                serde_json::Value::String(
+                                    cur_iface.iface_type().to_string(),
+                                ),
+                            );
+                        }
+                        Interface::deserialize(new_iface_value)?
                     } else {
-                        cur_iface
-                            .base_iface()
-                            .mac_address
-                            .as_deref()
-                            .map(|m| m.to_ascii_uppercase())
+                        iface.clone()
                     };
-                    if cur_mac_addr.as_deref() == Some(&mac_address) {
-                        let mut new_iface = if iface.iface_type()
-                            == InterfaceType::Unknown
-                        {
-                            let mut new_iface_value =
-                                serde_json::to_value(iface)?;
-                            if let Some(obj) = new_iface_value.as_object_mut() {
-                                obj.insert(
-                                    "type".to_string(),
-                                    serde_json::Value::String(
-                                        cur_iface.iface_type().to_string(),
-                                    ),
-                                );
-                            }
-                            Interface::deserialize(new_iface_value)?
-                        } else {
-                            iface.clone()
-                        };
-                        new_iface.base_iface_mut().profile_name =
-                            Some(iface.base_iface().name.clone());
-                        new_iface.base_iface_mut().name =
-                            cur_iface.name().to_string();
-                        changed_ifaces.push(new_iface);
-                        has_match = true;
-                        break;
-                    }
+                    new_iface.base_iface_mut().profile_name =
+                        Some(iface.base_iface().name.clone());
+                    new_iface.base_iface_mut().name =
+                        cur_iface.name().to_string();
+                    changed_ifaces.push(new_iface);
+                    has_match = true;
                 }
                 if has_match {
                     break;
@@ -545,7 +565,10 @@ impl Interfaces {
         for iface_name in iface_names_to_add {
             let mut iface = EthernetInterface::default();
             iface.base.name.clone_from(&iface_name);
-            log::warn!("Assuming undefined port {} as ethernet", iface_name);
+            log_warn(format!(
+                "Assuming undefined port {} as ethernet",
+                iface_name
+            ));
             self.kernel_ifaces
                 .insert(iface_name, Interface::Ethernet(Box::new(iface)));
         }
@@ -557,10 +580,10 @@ impl Interfaces {
         let mut new_ifaces = Vec::new();
         for iface in self.kernel_ifaces.values() {
             if let Interface::Unknown(iface) = &iface {
-                log::warn!(
+                log_warn(format!(
                     "Setting unknown type interface {} to ethernet",
                     iface.base.name.as_str()
-                );
+                ));
                 let iface_value = match serde_json::to_value(iface) {
                     Ok(mut v) => {
                         if let Some(v) = v.as_object_mut() {
@@ -775,12 +798,17 @@ impl MergedInterfaces {
 
     // Contains all the smart modifications, validations among interfaces
     fn process(&mut self) -> Result<(), NipartError> {
+        self.preserve_unknown_type_ifaces()?;
         self.process_allow_extra_ovs_patch_ports_for_apply();
         self.apply_copy_mac_from()?;
+        self.apply_copy_ip_from()?;
         self.validate_controller_and_port_list_confliction()?;
+        self.validate_controller_not_removed()?;
         self.handle_changed_ports()?;
+        self.process_bond_mode_change();
         self.resolve_port_iface_controller_type()?;
         self._set_up_priority()?;
+        self.resolve_mtu_inherit()?;
         self.check_overbook_ports()?;
         self.check_infiniband_as_ports()?;
         self.mark_orphan_interface_as_absent()?;
@@ -829,6 +857,20 @@ impl MergedInterfaces {
             if let Some(src_iface_name) =
                 &merged_iface.merged.base_iface().copy_mac_from
             {
+                // Bond and linux bridge can only copy MAC from its port
+                if let Some(ports) = merged_iface.merged.ports() {
+                    if !ports.contains(&src_iface_name.as_str()) {
+                        let e = NipartError::new(
+                            ErrorKind::InvalidArgument,
+                            format!(
+                                "The copy-mac-from interface {src_iface_name} \
+                                is not port of {iface_name}"
+                            ),
+                        );
+                        log::error!("{}", e);
+                        return Err(e);
+                    }
+                }
                 if let Some(src_iface) =
                     self.kernel_ifaces.get(src_iface_name).map(|i| &i.merged)
                 {
@@ -996,3 +1038,82 @@ fn get_ignored_ifaces(
 
     ignored_ifaces
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, MergedNetworkState, NetworkState};
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              mac-address: 00:23:45:67:89:1A
+            - name: eth2
+              type: ethernet
+              state: up
+              mac-address: 00:23:45:67:89:1B",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bridge_copy_mac_from_first_port() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: br0
+              type: linux-bridge
+              state: up
+              copy-mac-from: eth1
+              bridge:
+                port:
+                - name: eth1
+                - name: eth2",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        let br0 = &merged.interfaces.kernel_ifaces["br0"];
+        let apply_iface = br0.for_apply.as_ref().unwrap().base_iface();
+        assert_eq!(
+            apply_iface.mac_address.as_deref(),
+            Some("00:23:45:67:89:1A")
+        );
+        assert!(apply_iface.copy_mac_from.is_none());
+        let verify_iface = br0.for_verify.as_ref().unwrap().base_iface();
+        assert_eq!(
+            verify_iface.mac_address.as_deref(),
+            Some("00:23:45:67:89:1A")
+        );
+    }
+
+    #[test]
+    fn test_bridge_copy_mac_from_non_port() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: br0
+              type: linux-bridge
+              state: up
+              copy-mac-from: eth2
+              bridge:
+                port:
+                - name: eth1",
+        )
+        .unwrap();
+
+        let result =
+            MergedNetworkState::new(desired, gen_current(), false, false);
+
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::InvalidArgument);
+        }
+    }
+}
@@ -0,0 +1,223 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BaseInterface, ErrorKind, InterfaceType, NipartError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+/// Linux kernel IP VLAN interface. The example yaml output of
+/// [crate::NetworkState] with a ip vlan interface would be:
+/// ```yaml
+/// ---
+/// interfaces:
+///   - name: ipvlan0
+///     type: ip-vlan
+///     state: up
+///     ip-vlan:
+///       base-iface: eth1
+///       mode: l3s
+///       flags:
+///         - private
+/// ```
+pub struct IpVlanInterface {
+    #[serde(flatten)]
+    pub base: BaseInterface,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "ip-vlan")]
+    /// Deserialize and serialize from/to `ip-vlan`.
+    pub ip_vlan: Option<IpVlanConfig>,
+}
+
+impl Default for IpVlanInterface {
+    fn default() -> Self {
+        Self {
+            base: BaseInterface {
+                iface_type: InterfaceType::IpVlan,
+                ..Default::default()
+            },
+            ip_vlan: None,
+        }
+    }
+}
+
+impl IpVlanInterface {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn sanitize(&self, is_desired: bool) -> Result<(), NipartError> {
+        if is_desired {
+            if let Some(flags) =
+                self.ip_vlan.as_ref().and_then(|c| c.flags.as_ref())
+            {
+                if flags.contains(&IpVlanFlag::Private)
+                    && flags.contains(&IpVlanFlag::Vepa)
+                {
+                    let e = NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!(
+                            "IP VLAN interface {} cannot have both private \
+                            and vepa flags",
+                            self.base.name
+                        ),
+                    );
+                    log::error!("{}", e);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn parent(&self) -> Option<&str> {
+        self.ip_vlan.as_ref().map(|cfg| cfg.base_iface.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct IpVlanConfig {
+    pub base_iface: String,
+    pub mode: IpVlanMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Isolation flags of IP VLAN. The `private` and `vepa` flags are
+    /// mutually exclusive. Kernel default to `bridge` when not defined.
+    pub flags: Option<Vec<IpVlanFlag>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum IpVlanMode {
+    /// Deserialize and serialize from/to `l2`.
+    #[serde(rename = "l2")]
+    L2,
+    /// Deserialize and serialize from/to `l3`.
+    #[serde(rename = "l3")]
+    L3,
+    /// Deserialize and serialize from/to `l3s`.
+    #[serde(rename = "l3s")]
+    L3S,
+    Unknown,
+}
+
+impl Default for IpVlanMode {
+    fn default() -> Self {
+        Self::Unknown
+    }
+}
+
+impl std::fmt::Display for IpVlanMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::L2 => "l2",
+                Self::L3 => "l3",
+                Self::L3S => "l3s",
+                Self::Unknown => "unknown",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum IpVlanFlag {
+    /// Deserialize and serialize from/to `bridge`.
+    Bridge,
+    /// Deserialize and serialize from/to `private`.
+    Private,
+    /// Deserialize and serialize from/to `vepa`.
+    Vepa,
+}
+
+impl std::fmt::Display for IpVlanFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Bridge => "bridge",
+                Self::Private => "private",
+                Self::Vepa => "vepa",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IpVlanFlag, IpVlanMode};
+    use crate::{
+        ErrorKind, Interface, InterfaceType, MergedNetworkState, NetworkState,
+    };
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up",
+        )
+        .unwrap()
+    }
+
+    fn gen_ip_vlan_state(flags: &str) -> NetworkState {
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+            - name: ipvlan0
+              type: ip-vlan
+              state: up
+              ip-vlan:
+                base-iface: eth1
+                mode: l3s
+                flags: {flags}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ip_vlan_round_trip() {
+        let state = gen_ip_vlan_state("[private]");
+        let iface = &state.interfaces.kernel_ifaces["ipvlan0"];
+        assert_eq!(iface.iface_type(), InterfaceType::IpVlan);
+        assert_eq!(iface.parent(), Some("eth1"));
+        if let Interface::IpVlan(ip_vlan_iface) = iface {
+            let conf = ip_vlan_iface.ip_vlan.as_ref().unwrap();
+            assert_eq!(conf.mode, IpVlanMode::L3S);
+            assert_eq!(conf.flags, Some(vec![IpVlanFlag::Private]));
+        } else {
+            panic!("Expecting IP VLAN interface, got {iface:?}");
+        }
+
+        let yaml = serde_yaml::to_string(&state).unwrap();
+        assert!(yaml.contains("type: ip-vlan"));
+        assert_eq!(NetworkState::new_from_yaml(&yaml).unwrap(), state);
+
+        let merged =
+            MergedNetworkState::new(state.clone(), gen_current(), false, false)
+                .unwrap();
+        merged.verify(&state).unwrap();
+    }
+
+    #[test]
+    fn test_ip_vlan_private_and_vepa_flags() {
+        let result = MergedNetworkState::new(
+            gen_ip_vlan_state("[private, vepa]"),
+            gen_current(),
+            false,
+            false,
+        );
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidArgument);
+    }
+}
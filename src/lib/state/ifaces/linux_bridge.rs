@@ -122,9 +122,7 @@ impl LinuxBridgeInterface {
             self.sanitize_stp_opts()?;
         }
         self.use_upper_case_of_mac_address();
-        self.flatten_port_vlan_ranges();
-        self.sort_port_vlans();
-        self.remove_runtime_only_timers();
+        // Validate VLAN ID ranges before expanding them
         if let Some(port_confs) = self
             .bridge
             .as_ref()
@@ -136,6 +134,10 @@ impl LinuxBridgeInterface {
                 }
             }
         }
+        self.flatten_port_vlan_ranges();
+        self.sort_port_vlans();
+        self.compress_port_vlan_ranges();
+        self.remove_runtime_only_timers();
         Ok(())
     }
 
@@ -180,6 +182,21 @@ impl LinuxBridgeInterface {
         }
     }
 
+    fn compress_port_vlan_ranges(&mut self) {
+        if let Some(port_confs) = self
+            .bridge
+            .as_mut()
+            .and_then(|br_conf| br_conf.port.as_mut())
+        {
+            for port_conf in port_confs {
+                port_conf
+                    .vlan
+                    .as_mut()
+                    .map(BridgePortVlanConfig::compress_vlan_ranges);
+            }
+        }
+    }
+
     fn sort_ports(&mut self) {
         if let Some(ref mut br_conf) = self.bridge {
             if let Some(ref mut port_confs) = &mut br_conf.port {
@@ -534,11 +551,20 @@ pub struct LinuxBridgeOptions {
     pub stp: Option<LinuxBridgeStpOptions>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vlan_protocol: Option<VlanProtocol>,
+    /// The default PVID assigned to newly attached ports.
+    ///  * Undefined: preserve current setting.
+    ///  * `1`: kernel default, ports are untagged member of VLAN 1.
+    ///  * `0`: ports have no default untagged VLAN, only the VLANs defined
+    ///    in port VLAN configuration are allowed.
+    ///
+    /// Value other than 1 requires VLAN filtering enabled.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vlan_default_pvid: Option<u16>,
 }
 
 impl LinuxBridgeOptions {
+    const VLAN_DEFAULT_PVID_MAX: u16 = 4094;
+
     pub fn new() -> Self {
         Self::default()
     }
@@ -583,6 +609,20 @@ impl LinuxBridgeOptions {
         linux_bridge: &LinuxBridgeInterface,
     ) -> Result<(), NipartError> {
         if let Some(pvid) = self.vlan_default_pvid {
+            if pvid > Self::VLAN_DEFAULT_PVID_MAX {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Linux bridge {} has invalid vlan-default-pvid \
+                        {pvid}, should be in the range of 0 to {}, 0 means \
+                        no default untagged VLAN",
+                        linux_bridge.base.name.as_str(),
+                        Self::VLAN_DEFAULT_PVID_MAX
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
             if pvid != 1 && !linux_bridge.vlan_filtering_is_enabled() {
                 return Err(NipartError::new(
                     ErrorKind::InvalidArgument,
@@ -818,3 +858,86 @@ where
 
     deserializer.deserialize_any(IntegerOrString(PhantomData))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Interface, MergedNetworkState, NetworkState};
+
+    fn gen_state(pvid: u16, vlan_filtering: bool) -> NetworkState {
+        let port_vlan = if vlan_filtering {
+            "
+                  vlan:
+                    mode: trunk
+                    trunk-tags:
+                    - id: 100"
+        } else {
+            ""
+        };
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+            - name: br0
+              type: linux-bridge
+              state: up
+              bridge:
+                options:
+                  vlan-default-pvid: {pvid}
+                port:
+                - name: eth1{port_vlan}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_vlan_default_pvid_0_round_trip() {
+        let desired = gen_state(0, true);
+        let merged = MergedNetworkState::new(
+            desired.clone(),
+            gen_state(1, true),
+            false,
+            false,
+        )
+        .unwrap();
+
+        let Some(Interface::LinuxBridge(br_iface)) =
+            merged.interfaces.kernel_ifaces["br0"].for_apply.as_ref()
+        else {
+            panic!("br0 should be changed");
+        };
+        assert_eq!(
+            br_iface
+                .bridge
+                .as_ref()
+                .and_then(|b| b.options.as_ref())
+                .and_then(|o| o.vlan_default_pvid),
+            Some(0)
+        );
+        // PVID 0 should not be treated as unset
+        assert_eq!(
+            merged.verify(&gen_state(1, true)).unwrap_err().kind,
+            ErrorKind::VerificationError
+        );
+        merged.verify(&gen_state(0, true)).unwrap();
+
+        let new_desired = NetworkState::new_from_yaml(
+            &serde_yaml::to_string(&desired).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(new_desired, desired);
+    }
+
+    #[test]
+    fn test_vlan_default_pvid_0_without_vlan_filtering() {
+        let result = MergedNetworkState::new(
+            gen_state(0, false),
+            gen_state(1, false),
+            false,
+            false,
+        );
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidArgument);
+    }
+}
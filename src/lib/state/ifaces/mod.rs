@@ -4,12 +4,15 @@ mod bridge_vlan;
 mod dummy;
 mod ethernet;
 mod ethtool;
+mod gre;
 mod hsr;
 pub(crate) mod inter_ifaces;
+mod ip_vlan;
 mod ipsec;
 mod loopback;
 mod vrf;
 mod vxlan;
+mod wireguard;
 mod xfrm;
 // The pub(crate) is only for unit test
 mod infiniband;
@@ -20,14 +23,16 @@ mod mac_vtap;
 mod macsec;
 mod ovs;
 mod sriov;
+mod tun;
 mod vlan;
 
 pub use self::xfrm::XfrmInterface;
 pub use base::*;
 pub use bond::{
-    BondAdSelect, BondAllPortsActive, BondArpAllTargets, BondArpValidate,
-    BondConfig, BondFailOverMac, BondInterface, BondLacpRate, BondMode,
-    BondOptions, BondPortConfig, BondPrimaryReselect, BondXmitHashPolicy,
+    BondAdInfo, BondAdSelect, BondAllPortsActive, BondArpAllTargets,
+    BondArpValidate, BondConfig, BondFailOverMac, BondInterface, BondLacpRate,
+    BondMode, BondOptions, BondPortConfig, BondPrimaryReselect,
+    BondXmitHashPolicy,
 };
 pub use bridge_vlan::{
     BridgePortTrunkTag, BridgePortVlanConfig, BridgePortVlanMode,
@@ -38,13 +43,16 @@ pub use ethernet::{
     EthernetConfig, EthernetDuplex, EthernetInterface, VethConfig,
 };
 pub use ethtool::{
-    EthtoolCoalesceConfig, EthtoolConfig, EthtoolFeatureConfig,
-    EthtoolPauseConfig, EthtoolRingConfig,
+    EthtoolChannelsConfig, EthtoolCoalesceConfig, EthtoolConfig,
+    EthtoolFeatureConfig, EthtoolFecConfig, EthtoolFecMode, EthtoolPauseConfig,
+    EthtoolRingConfig, EthtoolRingSize,
 };
+pub use gre::{GreConfig, GreInterface, GreTapInterface};
 pub use hsr::{HsrConfig, HsrInterface, HsrProtocol};
 pub use infiniband::{InfiniBandConfig, InfiniBandInterface, InfiniBandMode};
 pub use inter_ifaces::MergedInterfaces;
 pub use inter_ifaces::*;
+pub use ip_vlan::{IpVlanConfig, IpVlanFlag, IpVlanInterface, IpVlanMode};
 pub use ipsec::{
     IpsecInterface, LibreswanAddressFamily, LibreswanConfig,
     LibreswanConnectionType,
@@ -62,11 +70,14 @@ pub use macsec::{
 pub use ovs::{
     OvsBridgeBondConfig, OvsBridgeBondMode, OvsBridgeBondPortConfig,
     OvsBridgeConfig, OvsBridgeInterface, OvsBridgeOptions, OvsBridgePortConfig,
-    OvsBridgeStpOptions, OvsDpdkConfig, OvsInterface, OvsPatchConfig,
+    OvsBridgeStpOptions, OvsDpdkConfig, OvsInterface, OvsInterfaceType,
+    OvsPatchConfig,
 };
 pub use sriov::{SrIovConfig, SrIovVfConfig};
+pub use tun::{TunConfig, TunInterface, TunMode};
 pub use vlan::{
     VlanConfig, VlanInterface, VlanProtocol, VlanRegistrationProtocol,
 };
 pub use vrf::{VrfConfig, VrfInterface};
 pub use vxlan::{VxlanConfig, VxlanInterface};
+pub use wireguard::{WireguardConfig, WireguardInterface, WireguardPeerConfig};
@@ -5,9 +5,9 @@ use std::collections::HashSet;
 use serde::{de, Deserialize, Deserializer, Serialize};
 
 use crate::{
-    BaseInterface, BridgePortVlanConfig, ErrorKind, Interface, InterfaceState,
-    InterfaceType, LinuxBridgeStpOptions, MergedInterface, MergedInterfaces,
-    NipartError, OvsDbIfaceConfig,
+    state::not_synced::log_warn, BaseInterface, BridgePortVlanConfig,
+    ErrorKind, Interface, InterfaceState, InterfaceType, LinuxBridgeStpOptions,
+    MergedInterface, MergedInterfaces, NipartError, OvsDbIfaceConfig,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -117,11 +117,11 @@ impl OvsBridgeInterface {
     ) -> Result<(), NipartError> {
         if let Some(mtu) = self.base.mtu.as_ref() {
             if is_desired {
-                log::warn!(
+                log_warn(format!(
                     "OVS Bridge {} could not hold 'mtu:{mtu}' configuration \
                     as it only exists in OVS database, ignoring",
                     self.base.name.as_str()
-                );
+                ));
             }
         }
         if let Some(mac) = self.base.mac_address.as_ref() {
@@ -140,8 +140,21 @@ impl OvsBridgeInterface {
         self.base.mtu = None;
         self.base.ipv4 = None;
         self.base.ipv6 = None;
+        // These are not for apply or verify
+        if let Some(br_conf) = self.bridge.as_mut() {
+            br_conf.datapath_id = None;
+            br_conf.flow_count = None;
+        }
         self.sort_ports();
 
+        if is_desired {
+            if let Some(opts) =
+                self.bridge.as_ref().and_then(|b| b.options.as_ref())
+            {
+                opts.sanitize(self.base.name.as_str())?;
+            }
+        }
+
         if let Some(port_confs) = self
             .bridge
             .as_ref()
@@ -276,6 +289,18 @@ pub struct OvsBridgeConfig {
     )]
     /// Serialize to 'port'. Deserialize from `port` or `ports`.
     pub ports: Option<Vec<OvsBridgePortConfig>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Datapath ID of bridge reported by ovs-vswitchd.
+    /// Only for querying, ignored during apply.
+    /// Absent when ovs-vswitchd is not running.
+    /// Serialize and deserialize to/from `datapath-id`.
+    pub datapath_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Number of OpenFlow flows installed in bridge.
+    /// Only for querying, ignored during apply.
+    /// Absent when ovs-vswitchd is not running.
+    /// Serialize and deserialize to/from `flow-count`.
+    pub flow_count: Option<u64>,
 }
 
 impl OvsBridgeConfig {
@@ -308,18 +333,74 @@ pub struct OvsBridgeOptions {
     /// Deserialize and serialize from/to `mcast-snooping-enable`.
     pub mcast_snooping_enable: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    /// Behavior of OVS bridge when no OpenFlow controller connected, could
+    /// be `standalone` or `secure`. Empty string means OVS default
+    /// (`standalone`).
     /// Deserialize and serialize from/to `fail-mode`.
     pub fail_mode: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    /// Set to `netdev` for DPDK.
-    /// Deserialize and serialize from/to `datapath`.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "datapath-type")]
+    /// Datapath type of OVS bridge, could be `system` or `netdev`.
+    /// Set to `netdev` for DPDK. Empty string means OVS default (`system`).
+    /// Serialize to `datapath`.
+    /// Deserialize from `datapath` or `datapath-type`.
     pub datapath: Option<String>,
 }
 
 impl OvsBridgeOptions {
+    pub const FAIL_MODE_STANDALONE: &'static str = "standalone";
+    pub const FAIL_MODE_SECURE: &'static str = "secure";
+    pub const DATAPATH_SYSTEM: &'static str = "system";
+    pub const DATAPATH_NETDEV: &'static str = "netdev";
+
     pub fn new() -> Self {
         Self::default()
     }
+
+    pub(crate) fn sanitize(&self, br_name: &str) -> Result<(), NipartError> {
+        if let Some(fail_mode) = self.fail_mode.as_deref() {
+            match fail_mode {
+                "" | Self::FAIL_MODE_STANDALONE => (),
+                Self::FAIL_MODE_SECURE => {
+                    log_warn(format!(
+                        "OVS bridge {br_name} with fail-mode secure will drop \
+                        all traffic unless OpenFlow controller is connected \
+                        or flows are added"
+                    ));
+                }
+                _ => {
+                    let e = NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!(
+                            "Invalid fail-mode '{fail_mode}' for OVS bridge \
+                            {br_name}, only support '{}' or '{}'",
+                            Self::FAIL_MODE_STANDALONE,
+                            Self::FAIL_MODE_SECURE
+                        ),
+                    );
+                    log::error!("{}", e);
+                    return Err(e);
+                }
+            }
+        }
+        if let Some(datapath) = self.datapath.as_deref() {
+            if !["", Self::DATAPATH_SYSTEM, Self::DATAPATH_NETDEV]
+                .contains(&datapath)
+            {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Invalid datapath '{datapath}' for OVS bridge \
+                        {br_name}, only support '{}' or '{}'",
+                        Self::DATAPATH_SYSTEM,
+                        Self::DATAPATH_NETDEV
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -398,6 +479,14 @@ impl OvsBridgePortConfig {
 pub struct OvsInterface {
     #[serde(flatten)]
     pub base: BaseInterface,
+    /// Explicit type of OVS interface. When not defined, the type is
+    /// inferred from `patch` and `dpdk` section.
+    /// Serialize and deserialize to/from `ovs-interface-type`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        rename = "ovs-interface-type"
+    )]
+    pub ovs_iface_type: Option<OvsInterfaceType>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub patch: Option<OvsPatchConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -410,6 +499,7 @@ impl Default for OvsInterface {
         base.iface_type = InterfaceType::OvsInterface;
         Self {
             base,
+            ovs_iface_type: None,
             patch: None,
             dpdk: None,
         }
@@ -442,7 +532,11 @@ impl OvsInterface {
     // OVS patch interface cannot have MTU or IP configuration
     // OVS DPDK `n_rxq_desc` and `n_txq_desc` should be power of 2 within
     // 1-4096.
+    // Explicit `ovs-interface-type` should match `patch` and `dpdk` section.
     pub(crate) fn sanitize(&self, is_desired: bool) -> Result<(), NipartError> {
+        if is_desired {
+            self.validate_ovs_iface_type()?;
+        }
         if is_desired && self.patch.is_some() {
             if self.base.mtu.is_some() {
                 let e = NipartError::new(
@@ -480,6 +574,79 @@ impl OvsInterface {
     pub(crate) fn is_ovs_patch_port(&self) -> bool {
         self.patch.is_some()
     }
+
+    fn validate_ovs_iface_type(&self) -> Result<(), NipartError> {
+        let Some(ovs_iface_type) = self.ovs_iface_type else {
+            return Ok(());
+        };
+        let err_msg = match ovs_iface_type {
+            OvsInterfaceType::Patch if self.patch.is_none() => {
+                Some("requires `patch` section")
+            }
+            OvsInterfaceType::Dpdk if self.dpdk.is_none() => {
+                Some("requires `dpdk` section")
+            }
+            OvsInterfaceType::Patch if self.dpdk.is_some() => {
+                Some("cannot hold `dpdk` section")
+            }
+            OvsInterfaceType::Dpdk if self.patch.is_some() => {
+                Some("cannot hold `patch` section")
+            }
+            OvsInterfaceType::Internal | OvsInterfaceType::System
+                if self.patch.is_some() || self.dpdk.is_some() =>
+            {
+                Some("cannot hold `patch` or `dpdk` section")
+            }
+            _ => None,
+        };
+        if let Some(err_msg) = err_msg {
+            let e = NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "OVS interface {} with `ovs-interface-type: \
+                    {ovs_iface_type}` {err_msg}",
+                    self.base.name.as_str()
+                ),
+            );
+            log::error!("{}", e);
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+/// Type of OVS interface stored in `type` column of OVSDB `Interface` table
+pub enum OvsInterfaceType {
+    /// OVS internal interface.
+    /// Serialize and deserialize to/from `internal`.
+    Internal,
+    /// Kernel network interface attached to OVS bridge.
+    /// Serialize and deserialize to/from `system`.
+    System,
+    /// OVS patch interface connecting two OVS bridges.
+    /// Serialize and deserialize to/from `patch`.
+    Patch,
+    /// OVS DPDK interface.
+    /// Serialize and deserialize to/from `dpdk`.
+    Dpdk,
+}
+
+impl std::fmt::Display for OvsInterfaceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Internal => "internal",
+                Self::System => "system",
+                Self::Patch => "patch",
+                Self::Dpdk => "dpdk",
+            }
+        )
+    }
 }
 
 /// The example yaml output of OVS bond:
@@ -660,6 +827,15 @@ pub struct OvsDpdkConfig {
     /// Must be power of 2 in the range of 1 to 4096.
     /// Setting to 0 means remove this setting from OVS database.
     pub n_txq_desc: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        rename = "pmd-rxq-affinity"
+    )]
+    /// Pin RX queues to PMD threads, for example `0:3,1:7` pins queue 0 to
+    /// core 3 and queue 1 to core 7. Setting to empty string means remove
+    /// this setting from OVS database.
+    /// Deserialize and serialize from/to `pmd-rxq-affinity`.
+    pub pmd_rxq_affinity: Option<String>,
 }
 
 const POWER_2_BETWEEN_1_4096: [u32; 13] =
@@ -686,6 +862,12 @@ fn validate_dpdk_queue_desc(
 impl OvsDpdkConfig {
     pub(crate) fn sanitize(&self, is_desired: bool) -> Result<(), NipartError> {
         if is_desired {
+            if self.rx_queue == Some(0) {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    "OVS DPDK rx-queue should be bigger than 0".to_string(),
+                ));
+            }
             if let Some(n_rxq_desc) = self.n_rxq_desc {
                 validate_dpdk_queue_desc(n_rxq_desc, "n_rxq_desc")?;
             }
@@ -706,11 +888,11 @@ impl MergedInterface {
             && self.merged.iface_type() == InterfaceType::OvsBridge
             && self.merged.ports().map(|p| p.is_empty()).unwrap_or(true)
         {
-            log::warn!(
+            log_warn(format!(
                 "OVS bridge {} cannot exist with empty port list, adding a \
                 OVS internal interface with the same name",
                 self.merged.name()
-            );
+            ));
             let iface_name = self.merged.name().to_string();
 
             if let (
@@ -858,3 +1040,128 @@ where
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{OvsInterface, OvsInterfaceType};
+    use crate::{ErrorKind, Interface, MergedNetworkState, NetworkState};
+
+    fn get_ovs_iface_type(
+        merged: &MergedNetworkState,
+        iface_name: &str,
+    ) -> Option<OvsInterfaceType> {
+        match merged.interfaces.kernel_ifaces[iface_name]
+            .for_apply
+            .as_ref()
+        {
+            Some(Interface::OvsInterface(i)) => i.ovs_iface_type,
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_create_ovs_internal_iface() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: ovs0
+              type: ovs-interface
+              state: up
+              ovs-interface-type: internal
+            - name: ovs-br0
+              type: ovs-bridge
+              state: up
+              bridge:
+                port:
+                - name: ovs0",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, NetworkState::new(), false, false)
+                .unwrap();
+
+        assert_eq!(
+            get_ovs_iface_type(&merged, "ovs0"),
+            Some(OvsInterfaceType::Internal)
+        );
+    }
+
+    #[test]
+    fn test_create_ovs_patch_iface() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: patch0
+              type: ovs-interface
+              state: up
+              ovs-interface-type: patch
+              patch:
+                peer: patch1
+            - name: ovs-br0
+              type: ovs-bridge
+              state: up
+              bridge:
+                port:
+                - name: patch0
+            - name: patch1
+              type: ovs-interface
+              state: up
+              ovs-interface-type: patch
+              patch:
+                peer: patch0
+            - name: ovs-br1
+              type: ovs-bridge
+              state: up
+              bridge:
+                port:
+                - name: patch1",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, NetworkState::new(), false, false)
+                .unwrap();
+
+        for iface_name in ["patch0", "patch1"] {
+            assert_eq!(
+                get_ovs_iface_type(&merged, iface_name),
+                Some(OvsInterfaceType::Patch)
+            );
+        }
+    }
+
+    #[test]
+    fn test_ovs_iface_type_mismatch_config() {
+        for yaml in [
+            r"---
+            name: patch0
+            type: ovs-interface
+            ovs-interface-type: patch",
+            r"---
+            name: dpdk0
+            type: ovs-interface
+            ovs-interface-type: dpdk",
+            r"---
+            name: ovs0
+            type: ovs-interface
+            ovs-interface-type: internal
+            patch:
+              peer: patch1",
+            r"---
+            name: ovs0
+            type: ovs-interface
+            ovs-interface-type: patch
+            patch:
+              peer: patch1
+            dpdk:
+              devargs: 0000:af:00.1",
+        ] {
+            let iface: OvsInterface = serde_yaml::from_str(yaml).unwrap();
+            assert_eq!(
+                iface.sanitize(true).unwrap_err().kind,
+                ErrorKind::InvalidArgument
+            );
+        }
+    }
+}
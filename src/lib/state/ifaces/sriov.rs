@@ -60,6 +60,8 @@ pub struct SrIovConfig {
         deserialize_with = "crate::state::deserializer::option_u32_or_string"
     )]
     /// The number of VFs enabled on PF.
+    /// Changing this value will recreate all VFs with default configuration,
+    /// hence VF count is applied before the [SrIovVfConfig] of `vfs`.
     /// Deserialize and serialize from/to `total-vfs`.
     pub total_vfs: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -80,6 +82,7 @@ impl SrIovConfig {
 
     // * Convert VF MAC address to upper case
     // * Sort by VF ID
+    // * Validate VF TX rate range
     pub(crate) fn sanitize(&mut self) -> Result<(), NipartError> {
         if let Some(vfs) = self.vfs.as_mut() {
             for vf in vfs.iter_mut() {
@@ -87,6 +90,23 @@ impl SrIovConfig {
                     address.make_ascii_uppercase()
                 }
 
+                // The `max-tx-rate: 0` means no limit
+                if let (Some(min_rate), Some(max_rate)) =
+                    (vf.min_tx_rate, vf.max_tx_rate)
+                {
+                    if max_rate != 0 && min_rate > max_rate {
+                        let e = NipartError::new(
+                            ErrorKind::InvalidArgument,
+                            format!(
+                                "The min-tx-rate {min_rate} should be less \
+                                than or equal to max-tx-rate {max_rate}"
+                            ),
+                        );
+                        log::error!("VF ID {}: {}", vf.id, e);
+                        return Err(e);
+                    }
+                }
+
                 if let Some(VlanProtocol::Ieee8021Ad) = vf.vlan_proto {
                     if vf.vlan_id.unwrap_or_default() == 0
                         && vf.qos.unwrap_or_default() == 0
@@ -178,13 +198,15 @@ pub struct SrIovVfConfig {
         default,
         deserialize_with = "crate::state::deserializer::option_u32_or_string"
     )]
-    /// Deserialize and serialize from/to `min_tx_rate`.
+    /// Minimum TX rate in Mbps, 0 means no limit.
+    /// Deserialize and serialize from/to `min-tx-rate`.
     pub min_tx_rate: Option<u32>,
     #[serde(
         skip_serializing_if = "Option::is_none",
         default,
         deserialize_with = "crate::state::deserializer::option_u32_or_string"
     )]
+    /// Maximum TX rate in Mbps, 0 means no limit.
     /// Deserialize and serialize from/to `max-tx-rate`.
     pub max_tx_rate: Option<u32>,
     #[serde(
@@ -0,0 +1,301 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BaseInterface, ErrorKind, Interface, InterfaceType, MergedInterface,
+    NipartError,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+/// Linux kernel TUN/TAP interface. The example yaml output of
+/// [crate::NetworkState] with a tap interface would be:
+/// ```yaml
+/// ---
+/// interfaces:
+///   - name: tap0
+///     type: tun
+///     state: up
+///     tun:
+///       mode: tap
+///       owner: 1000
+///       group: 1000
+///       multi-queue: true
+///       pi: false
+///       vnet-hdr: true
+/// ```
+pub struct TunInterface {
+    #[serde(flatten)]
+    pub base: BaseInterface,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tun: Option<TunConfig>,
+}
+
+impl Default for TunInterface {
+    fn default() -> Self {
+        Self {
+            base: BaseInterface {
+                iface_type: InterfaceType::Tun,
+                ..Default::default()
+            },
+            tun: None,
+        }
+    }
+}
+
+impl TunInterface {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct TunConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// TUN (layer 3) or TAP (layer 2) device. Cannot be changed after
+    /// creation. Default to `tun` when creating new interface.
+    pub mode: Option<TunMode>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// UID of user allowed to attach to this device.
+    pub owner: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// GID of group allowed to attach to this device.
+    pub group: Option<u32>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_bool_or_string"
+    )]
+    /// Allow multiple queues. Cannot be changed after creation.
+    pub multi_queue: Option<bool>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_bool_or_string"
+    )]
+    /// Prepend packet information header to each packet.
+    /// Cannot be changed after creation.
+    pub pi: Option<bool>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_bool_or_string"
+    )]
+    /// Prepend virtio net header to each packet.
+    /// Cannot be changed after creation.
+    pub vnet_hdr: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum TunMode {
+    /// Deserialize and serialize from/to `tun`.
+    Tun,
+    /// Deserialize and serialize from/to `tap`.
+    Tap,
+}
+
+impl Default for TunMode {
+    fn default() -> Self {
+        Self::Tun
+    }
+}
+
+impl std::fmt::Display for TunMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Tun => "tun",
+                Self::Tap => "tap",
+            }
+        )
+    }
+}
+
+impl MergedInterface {
+    // Kernel does not allow changing TUN/TAP flags of existing device,
+    // raise error instead of silently ignoring them.
+    pub(crate) fn post_inter_ifaces_process_tun(
+        &self,
+    ) -> Result<(), NipartError> {
+        if self.merged.is_absent() {
+            return Ok(());
+        }
+        if let (
+            Some(Interface::Tun(des_iface)),
+            Some(Interface::Tun(cur_iface)),
+        ) = (self.desired.as_ref(), self.current.as_ref())
+        {
+            if let (Some(des_conf), Some(cur_conf)) =
+                (des_iface.tun.as_ref(), cur_iface.tun.as_ref())
+            {
+                let mut changed: Vec<&str> = Vec::new();
+                if des_conf.mode.is_some()
+                    && des_conf.mode.unwrap_or_default()
+                        != cur_conf.mode.unwrap_or_default()
+                {
+                    changed.push("mode");
+                }
+                if des_conf.multi_queue.is_some()
+                    && des_conf.multi_queue.unwrap_or_default()
+                        != cur_conf.multi_queue.unwrap_or_default()
+                {
+                    changed.push("multi-queue");
+                }
+                if des_conf.pi.is_some()
+                    && des_conf.pi.unwrap_or_default()
+                        != cur_conf.pi.unwrap_or_default()
+                {
+                    changed.push("pi");
+                }
+                if des_conf.vnet_hdr.is_some()
+                    && des_conf.vnet_hdr.unwrap_or_default()
+                        != cur_conf.vnet_hdr.unwrap_or_default()
+                {
+                    changed.push("vnet-hdr");
+                }
+                if !changed.is_empty() {
+                    let e = NipartError::new(
+                        ErrorKind::NotSupportedError,
+                        format!(
+                            "Changing {} of existing TUN/TAP interface {} \
+                            is not supported by kernel, please remove the \
+                            interface first",
+                            changed.join(", "),
+                            des_iface.base.name
+                        ),
+                    );
+                    log::error!("{}", e);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TunMode;
+    use crate::{
+        ErrorKind, Interface, InterfaceType, MergedNetworkState, NetworkState,
+    };
+
+    fn gen_tap_state(multi_queue: bool) -> NetworkState {
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: tap0
+              type: tun
+              state: up
+              tun:
+                mode: tap
+                owner: 1000
+                group: 1000
+                multi-queue: {multi_queue}
+                pi: false
+                vnet-hdr: true"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tun_round_trip() {
+        let state = gen_tap_state(true);
+        let iface = &state.interfaces.kernel_ifaces["tap0"];
+        assert_eq!(iface.iface_type(), InterfaceType::Tun);
+        if let Interface::Tun(tun_iface) = iface {
+            let conf = tun_iface.tun.as_ref().unwrap();
+            assert_eq!(conf.mode, Some(TunMode::Tap));
+            assert_eq!(conf.owner, Some(1000));
+            assert_eq!(conf.group, Some(1000));
+            assert_eq!(conf.multi_queue, Some(true));
+            assert_eq!(conf.pi, Some(false));
+            assert_eq!(conf.vnet_hdr, Some(true));
+        } else {
+            panic!("Expecting TUN interface, got {iface:?}");
+        }
+
+        let yaml = serde_yaml::to_string(&state).unwrap();
+        assert!(yaml.contains("type: tun"));
+        assert!(yaml.contains("mode: tap"));
+        assert_eq!(NetworkState::new_from_yaml(&yaml).unwrap(), state);
+    }
+
+    #[test]
+    fn test_tun_create_and_remove() {
+        let desired = gen_tap_state(true);
+        let merged = MergedNetworkState::new(
+            desired.clone(),
+            NetworkState::default(),
+            false,
+            false,
+        )
+        .unwrap();
+        let merged_iface = &merged.interfaces.kernel_ifaces["tap0"];
+        assert!(merged_iface.current.is_none());
+        assert!(matches!(
+            merged_iface.for_apply.as_ref(),
+            Some(Interface::Tun(_))
+        ));
+
+        let absent = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: tap0
+              type: tun
+              state: absent",
+        )
+        .unwrap();
+        let merged =
+            MergedNetworkState::new(absent, desired, false, false).unwrap();
+        assert!(merged.interfaces.kernel_ifaces["tap0"].merged.is_absent());
+    }
+
+    #[test]
+    fn test_tun_change_owner_of_existing() {
+        let current = gen_tap_state(true);
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: tap0
+              type: tun
+              tun:
+                owner: 1001",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, current, false, false).unwrap();
+        assert!(merged.interfaces.kernel_ifaces["tap0"].is_changed());
+    }
+
+    #[test]
+    fn test_tun_change_immutable_option_of_existing() {
+        let result = MergedNetworkState::new(
+            gen_tap_state(false),
+            gen_tap_state(true),
+            false,
+            false,
+        );
+
+        let e = result.unwrap_err();
+        assert_eq!(e.kind, ErrorKind::NotSupportedError);
+        assert!(e.msg.contains("multi-queue"));
+        assert!(e.msg.contains("tap0"));
+    }
+}
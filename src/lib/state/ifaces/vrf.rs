@@ -3,8 +3,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    BaseInterface, ErrorKind, Interface, InterfaceType, MergedInterface,
-    NipartError,
+    state::not_synced::log_warn, BaseInterface, ErrorKind, Interface,
+    InterfaceType, MergedInterface, NipartError,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -71,11 +71,11 @@ impl VrfInterface {
         // interface.
         if is_desired {
             if let Some(mac) = self.base.mac_address.as_ref() {
-                log::warn!(
+                log_warn(format!(
                     "Ignoring MAC address {mac} of VRF interface {} \
                     as it is a layer 3(IP) interface",
                     self.base.name.as_str()
-                );
+                ));
             }
         }
         self.base.mac_address = None;
@@ -0,0 +1,389 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BaseInterface, ErrorKind, InterfaceType, NetworkState, NipartError,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+/// Linux kernel WireGuard interface. The example yaml output of
+/// [crate::NetworkState] with a WireGuard interface would be:
+/// ```yaml
+/// ---
+/// interfaces:
+///   - name: wg0
+///     type: wireguard
+///     state: up
+///     wireguard:
+///       public-key: T7Xlxw3Emq+Ku5jF4HbKfIA56Ddfyl+Eyb3L5P+T5XI=
+///       listen-port: 51820
+///       fwmark: 100
+///       peers:
+///         - public-key: 7yV2wXRxg2b0uEnHGT85UbknNDOWy+aftbdbMpO+hBk=
+///           endpoint: 192.0.2.2:51820
+///           allowed-ips:
+///             - 198.51.100.0/24
+///           persistent-keepalive: 25
+/// ```
+pub struct WireguardInterface {
+    #[serde(flatten)]
+    pub base: BaseInterface,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wireguard: Option<WireguardConfig>,
+}
+
+impl Default for WireguardInterface {
+    fn default() -> Self {
+        Self {
+            base: BaseInterface {
+                iface_type: InterfaceType::Wireguard,
+                ..Default::default()
+            },
+            wireguard: None,
+        }
+    }
+}
+
+impl WireguardInterface {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn hide_secrets(&mut self) {
+        if let Some(conf) = self.wireguard.as_mut() {
+            conf.hide_secrets();
+        }
+    }
+
+    pub(crate) fn sanitize(
+        &mut self,
+        is_desired: bool,
+    ) -> Result<(), NipartError> {
+        if let Some(conf) = self.wireguard.as_mut() {
+            if is_desired {
+                if conf.public_key.is_some() {
+                    log::info!(
+                        "Ignoring query-only `public-key` of WireGuard \
+                        interface {}",
+                        self.base.name.as_str()
+                    );
+                }
+                conf.public_key = None;
+                // Treat hidden secrets as not defined, so round trip of
+                // query result will not override existing keys.
+                if conf.private_key.as_deref()
+                    == Some(NetworkState::PASSWORD_HID_BY_NMSTATE)
+                {
+                    conf.private_key = None;
+                }
+                for peer in conf.peers.as_mut().into_iter().flatten() {
+                    if peer.preshared_key.as_deref()
+                        == Some(NetworkState::PASSWORD_HID_BY_NMSTATE)
+                    {
+                        peer.preshared_key = None;
+                    }
+                }
+                for peer in conf.peers.as_deref().unwrap_or_default() {
+                    if peer.public_key.is_empty() {
+                        let e = NipartError::new(
+                            ErrorKind::InvalidArgument,
+                            format!(
+                                "WireGuard interface {} has peer without \
+                                `public-key` defined",
+                                self.base.name.as_str()
+                            ),
+                        );
+                        log::error!("{}", e);
+                        return Err(e);
+                    }
+                }
+            }
+            if let Some(peers) = conf.peers.as_mut() {
+                peers.sort_unstable_by(|a, b| a.public_key.cmp(&b.public_key));
+                for allowed_ips in
+                    peers.iter_mut().filter_map(|p| p.allowed_ips.as_mut())
+                {
+                    allowed_ips.sort_unstable();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct WireguardConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Base64 encoded private key. Never included in query result.
+    pub private_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Base64 encoded public key derived from private key. Query only,
+    /// ignored when applying.
+    pub public_key: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u16_or_string"
+    )]
+    /// UDP port for listening. Kernel will choose a random port if not
+    /// defined.
+    pub listen_port: Option<u16>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    /// Firewall mark set on outgoing packets. Setting to 0 remove the mark.
+    pub fwmark: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Peers of this interface. When defined, undefined peers will be
+    /// removed.
+    pub peers: Option<Vec<WireguardPeerConfig>>,
+}
+
+impl WireguardConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn hide_secrets(&mut self) {
+        if self.private_key.is_some() {
+            self.private_key =
+                Some(NetworkState::PASSWORD_HID_BY_NMSTATE.to_string());
+        }
+        for peer in self.peers.as_mut().into_iter().flatten() {
+            peer.hide_secrets();
+        }
+    }
+}
+
+impl std::fmt::Debug for WireguardConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WireguardConfig")
+            .field(
+                "private_key",
+                &self
+                    .private_key
+                    .as_ref()
+                    .map(|_| NetworkState::PASSWORD_HID_BY_NMSTATE),
+            )
+            .field("public_key", &self.public_key)
+            .field("listen_port", &self.listen_port)
+            .field("fwmark", &self.fwmark)
+            .field("peers", &self.peers)
+            .finish()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct WireguardPeerConfig {
+    /// Base64 encoded public key of peer.
+    pub public_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Base64 encoded preshared key. Never included in query result.
+    pub preshared_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Peer address and UDP port.
+    pub endpoint: Option<std::net::SocketAddr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// List of IP networks in the format of `address/prefix-length` allowed
+    /// for this peer.
+    pub allowed_ips: Option<Vec<String>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u16_or_string"
+    )]
+    /// Interval in seconds for sending keepalive packets. 0 means disabled.
+    pub persistent_keepalive: Option<u16>,
+}
+
+impl WireguardPeerConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn hide_secrets(&mut self) {
+        if self.preshared_key.is_some() {
+            self.preshared_key =
+                Some(NetworkState::PASSWORD_HID_BY_NMSTATE.to_string());
+        }
+    }
+}
+
+impl std::fmt::Debug for WireguardPeerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WireguardPeerConfig")
+            .field("public_key", &self.public_key)
+            .field(
+                "preshared_key",
+                &self
+                    .preshared_key
+                    .as_ref()
+                    .map(|_| NetworkState::PASSWORD_HID_BY_NMSTATE),
+            )
+            .field("endpoint", &self.endpoint)
+            .field("allowed_ips", &self.allowed_ips)
+            .field("persistent_keepalive", &self.persistent_keepalive)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ErrorKind, Interface, InterfaceType, MergedNetworkState, NetworkState,
+    };
+
+    const PEER_PUB_KEY: &str = "7yV2wXRxg2b0uEnHGT85UbknNDOWy+aftbdbMpO+hBk=";
+
+    fn gen_desired(endpoint: &str) -> NetworkState {
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: wg0
+              type: wireguard
+              state: up
+              wireguard:
+                private-key: yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=
+                listen-port: 51820
+                fwmark: 100
+                peers:
+                - public-key: {PEER_PUB_KEY}
+                  preshared-key: FpCyhws9cxwWoV4xELtfJvjJN+zQVRPISllRWgeopVE=
+                  endpoint: {endpoint}
+                  allowed-ips:
+                  - 198.51.100.0/24
+                  persistent-keepalive: 25"
+        ))
+        .unwrap()
+    }
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: wg0
+              type: wireguard
+              state: up
+              wireguard:
+                public-key: T7Xlxw3Emq+Ku5jF4HbKfIA56Ddfyl+Eyb3L5P+T5XI=
+                listen-port: 51820
+                fwmark: 100
+                peers:
+                - public-key: {PEER_PUB_KEY}
+                  endpoint: 192.0.2.2:51820
+                  allowed-ips:
+                  - 198.51.100.0/24
+                  persistent-keepalive: 25"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_wireguard_round_trip() {
+        let state = gen_desired("192.0.2.2:51820");
+        let iface = &state.interfaces.kernel_ifaces["wg0"];
+        assert_eq!(iface.iface_type(), InterfaceType::Wireguard);
+        if let Interface::Wireguard(wg_iface) = iface {
+            let conf = wg_iface.wireguard.as_ref().unwrap();
+            assert_eq!(conf.listen_port, Some(51820));
+            assert_eq!(conf.fwmark, Some(100));
+            let peer = &conf.peers.as_ref().unwrap()[0];
+            assert_eq!(peer.public_key, PEER_PUB_KEY);
+            assert_eq!(peer.persistent_keepalive, Some(25));
+            assert_eq!(peer.endpoint, Some("192.0.2.2:51820".parse().unwrap()));
+        } else {
+            panic!("Expecting WireGuard interface, got {iface:?}");
+        }
+
+        let yaml = serde_yaml::to_string(&state).unwrap();
+        assert!(yaml.contains("listen-port: 51820"));
+        assert!(yaml.contains("persistent-keepalive: 25"));
+        assert_eq!(NetworkState::new_from_yaml(&yaml).unwrap(), state);
+    }
+
+    #[test]
+    fn test_wireguard_hide_secrets() {
+        let mut state = gen_desired("192.0.2.2:51820");
+        let debug_str = format!("{state:?}");
+        assert!(!debug_str.contains("FpCyhws9cxwWoV4xELtfJvjJN"));
+        assert!(!debug_str.contains("yAnz5TF"));
+
+        state.hide_secrets();
+
+        if let Interface::Wireguard(wg_iface) =
+            &state.interfaces.kernel_ifaces["wg0"]
+        {
+            let conf = wg_iface.wireguard.as_ref().unwrap();
+            assert_eq!(
+                conf.private_key.as_deref(),
+                Some(NetworkState::PASSWORD_HID_BY_NMSTATE)
+            );
+            assert_eq!(
+                conf.peers.as_ref().unwrap()[0].preshared_key.as_deref(),
+                Some(NetworkState::PASSWORD_HID_BY_NMSTATE)
+            );
+        } else {
+            panic!("Expecting WireGuard interface");
+        }
+    }
+
+    #[test]
+    fn test_wireguard_verify() {
+        let merged = MergedNetworkState::new(
+            gen_desired("192.0.2.2:51820"),
+            NetworkState::default(),
+            false,
+            false,
+        )
+        .unwrap();
+        merged.verify(&gen_current()).unwrap();
+    }
+
+    #[test]
+    fn test_wireguard_verify_endpoint_mismatch() {
+        let merged = MergedNetworkState::new(
+            gen_desired("192.0.2.3:51820"),
+            NetworkState::default(),
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            merged.verify(&gen_current()).unwrap_err().kind,
+            ErrorKind::VerificationError
+        );
+    }
+
+    #[test]
+    fn test_wireguard_peer_without_public_key() {
+        let desired = NetworkState::new_from_yaml(
+            r#"---
+            interfaces:
+            - name: wg0
+              type: wireguard
+              wireguard:
+                peers:
+                - public-key: ""
+                  endpoint: 192.0.2.2:51820"#,
+        )
+        .unwrap();
+
+        let result = MergedNetworkState::new(
+            desired,
+            NetworkState::default(),
+            false,
+            false,
+        );
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidArgument);
+    }
+}
@@ -7,8 +7,8 @@ use std::str::FromStr;
 use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::{
-    BaseInterface, DnsClientState, ErrorKind, MergedInterface,
-    MptcpAddressFlag, NipartError, RouteRuleEntry,
+    state::not_synced::log_warn, BaseInterface, DnsClientState, ErrorKind,
+    MergedInterface, MptcpAddressFlag, NipartError, RouteRuleEntry,
 };
 
 const AF_INET: u8 = 2;
@@ -16,6 +16,8 @@ const AF_INET6: u8 = 10;
 const IPV4_ADDR_LEN: usize = 32;
 const IPV6_ADDR_LEN: usize = 128;
 const FOREVER: &str = "forever";
+const VALID_ARP_IGNORE: [u8; 5] = [0, 1, 2, 3, 8];
+const MAX_ARP_ANNOUNCE: u8 = 2;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[non_exhaustive]
@@ -85,6 +87,39 @@ struct InterfaceIp {
     pub auto_route_metric: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "addr-gen-mode")]
     pub addr_gen_mode: Option<Ipv6AddrGenMode>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "stable-secret")]
+    pub stable_secret: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "accept-ra")]
+    pub accept_ra: Option<Ipv6AcceptRa>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_bool_or_string"
+    )]
+    pub forwarding: Option<bool>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        rename = "dad-transmits",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+    )]
+    pub dad_transmits: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "rp-filter")]
+    pub rp_filter: Option<Ipv4RpFilter>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        rename = "arp-ignore",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u8_or_string"
+    )]
+    pub arp_ignore: Option<u8>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        rename = "arp-announce",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u8_or_string"
+    )]
+    pub arp_announce: Option<u8>,
     #[serde(
         skip_serializing_if = "Option::is_none",
         rename = "allow-extra-address"
@@ -168,6 +203,9 @@ pub struct InterfaceIpv4 {
     /// allows extra IP address found as long as desired IP address matched.
     /// When set to false, the verification process of nmstate do exact equal
     /// check on IP address.
+    /// Allowing extra IP address weakens verification: address added
+    /// out-of-band (e.g. keepalived VIP) or left over by previous apply will
+    /// not be reported. Set to false if exact match is required.
     /// Serialize/deserialize to/from `allow-extra-address`
     pub allow_extra_address: Option<bool>,
     /// Metric for routes retrieved from DHCP server.
@@ -190,6 +228,21 @@ pub struct InterfaceIpv4 {
     /// If not defined, current non-dynamic hostname will be used.
     /// Deserialize from `dhcp-custom-hostname`
     pub dhcp_custom_hostname: Option<String>,
+    /// Whether to forward IPv4 packets received on this interface,
+    /// the `net.ipv4.conf.<iface>.forwarding` sysctl.
+    pub forwarding: Option<bool>,
+    /// Reverse path filtering mode, the `net.ipv4.conf.<iface>.rp_filter`
+    /// sysctl.
+    /// Serialize and deserialize to/from `rp-filter`.
+    pub rp_filter: Option<Ipv4RpFilter>,
+    /// ARP reply mode, the `net.ipv4.conf.<iface>.arp_ignore` sysctl.
+    /// Valid values are 0 to 3 and 8.
+    /// Serialize and deserialize to/from `arp-ignore`.
+    pub arp_ignore: Option<u8>,
+    /// ARP source address announce mode, the
+    /// `net.ipv4.conf.<iface>.arp_announce` sysctl. Valid values are 0 to 2.
+    /// Serialize and deserialize to/from `arp-announce`.
+    pub arp_announce: Option<u8>,
     pub(crate) dns: Option<DnsClientState>,
     pub(crate) rules: Option<Vec<RouteRuleEntry>>,
 }
@@ -348,9 +401,10 @@ impl InterfaceIpv4 {
             self.auto_table_id = None;
             self.auto_route_metric = None;
             if is_desired && self.dhcp_client_id.is_some() {
-                log::warn!(
+                log_warn(
                     "Ignoring `dhcp-client-id` setting when DHCPv4 is \
                     disabled"
+                        .to_string(),
                 );
             }
             self.dhcp_client_id = None;
@@ -363,11 +417,11 @@ impl InterfaceIpv4 {
                     self.dhcp_custom_hostname.as_deref()
                 {
                     if !custom_hostname.is_empty() {
-                        log::warn!(
+                        log_warn(format!(
                             "Ignoring `dhcp-custom-hostname: \
                             {custom_hostname}` as `dhcp-send-hostname` is \
                             disabled"
-                        );
+                        ));
                     }
                 }
             }
@@ -378,6 +432,33 @@ impl InterfaceIpv4 {
                 addr.mptcp_flags = None;
             }
         }
+        if let Some(arp_ignore) = self.arp_ignore {
+            if !VALID_ARP_IGNORE.contains(&arp_ignore) {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Invalid IPv4 arp-ignore {arp_ignore}, should be \
+                        0, 1, 2, 3 or 8"
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+        }
+        if let Some(arp_announce) = self.arp_announce {
+            if arp_announce > MAX_ARP_ANNOUNCE {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Invalid IPv4 arp-announce {arp_announce}, should \
+                        be in the range of 0 to {}",
+                        MAX_ARP_ANNOUNCE
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+        }
         Ok(())
     }
 }
@@ -400,6 +481,16 @@ impl<'de> Deserialize<'de> for InterfaceIpv4 {
                     "dhcp-duid is not allowed for IPv4",
                 ));
             }
+            if v_map.contains_key("stable-secret") {
+                return Err(serde::de::Error::custom(
+                    "stable-secret is not allowed for IPv4",
+                ));
+            }
+            if v_map.contains_key("accept-ra") {
+                return Err(serde::de::Error::custom(
+                    "accept-ra is not allowed for IPv4",
+                ));
+            }
         }
 
         let ip: InterfaceIp = match serde_json::from_value(v) {
@@ -429,6 +520,10 @@ impl From<InterfaceIp> for InterfaceIpv4 {
             auto_route_metric: ip.auto_route_metric,
             dhcp_send_hostname: ip.dhcp_send_hostname,
             dhcp_custom_hostname: ip.dhcp_custom_hostname,
+            forwarding: ip.forwarding,
+            rp_filter: ip.rp_filter,
+            arp_ignore: ip.arp_ignore,
+            arp_announce: ip.arp_announce,
             ..Default::default()
         }
     }
@@ -454,6 +549,10 @@ impl From<InterfaceIpv4> for InterfaceIp {
             auto_route_metric: ip.auto_route_metric,
             dhcp_send_hostname: ip.dhcp_send_hostname,
             dhcp_custom_hostname: ip.dhcp_custom_hostname,
+            forwarding: ip.forwarding,
+            rp_filter: ip.rp_filter,
+            arp_ignore: ip.arp_ignore,
+            arp_announce: ip.arp_announce,
             ..Default::default()
         }
     }
@@ -499,6 +598,13 @@ pub struct InterfaceIpv6 {
     /// IPv6 address generation mode.
     /// Serialize and deserialize to/from `addr-gen-mode`.
     pub addr_gen_mode: Option<Ipv6AddrGenMode>,
+    /// Secret used to generate the stable privacy interface identifier
+    /// defined by RFC 7217, in the format of IPv6 address. Only valid with
+    /// `addr-gen-mode: stable-privacy`, if `addr-gen-mode` is not defined,
+    /// `stable-privacy` will be used. Hidden in query result and not
+    /// verified after applying.
+    /// Serialize and deserialize to/from `stable-secret`.
+    pub stable_secret: Option<String>,
     /// IPv6 addresses. Will be ignored when applying with
     /// DHCPv6 or autoconf is enabled.
     /// When applying with `None`, current IP address will be preserved.
@@ -528,6 +634,8 @@ pub struct InterfaceIpv6 {
     /// found as long as desired IP address matched.
     /// When set to false, the verification process of nmstate do exact equal
     /// check on IP address.
+    /// Allowing extra IP address weakens verification: address added
+    /// out-of-band or left over by previous apply will not be reported.
     /// Ignored when serializing.
     /// Deserialize from `allow-extra-address`.
     pub allow_extra_address: Option<bool>,
@@ -548,6 +656,22 @@ pub struct InterfaceIpv6 {
     /// If not defined, current non-dynamic hostname will be used.
     /// Deserialize from `dhcp-custom-hostname`
     pub dhcp_custom_hostname: Option<String>,
+    /// Whether to accept IPv6 router advertisement on this interface,
+    /// the `net.ipv6.conf.<iface>.accept_ra` sysctl.
+    /// Serialize and deserialize to/from `accept-ra`.
+    pub accept_ra: Option<Ipv6AcceptRa>,
+    /// Whether to forward IPv6 packets received on this interface,
+    /// the `net.ipv6.conf.<iface>.forwarding` sysctl.
+    pub forwarding: Option<bool>,
+    /// Amount of duplicate address detection probes to send, the
+    /// `net.ipv6.conf.<iface>.dad_transmits` sysctl. Setting to 0 disables
+    /// DAD, new IPv6 address is usable immediately which is useful for fast
+    /// bring-up on point-to-point links. Otherwise new IPv6 address stays
+    /// tentative for roughly a second per probe, `wait-ip` and verification
+    /// only pass after DAD finished, consider larger `wait-ip-timeout` when
+    /// increasing this value.
+    /// Serialize and deserialize to/from `dad-transmits`.
+    pub dad_transmits: Option<u32>,
 
     pub(crate) dns: Option<DnsClientState>,
     pub(crate) rules: Option<Vec<RouteRuleEntry>>,
@@ -559,6 +683,13 @@ impl InterfaceIpv6 {
         Self::default()
     }
 
+    pub(crate) fn hide_secrets(&mut self) {
+        if self.stable_secret.is_some() {
+            self.stable_secret =
+                Some(crate::NetworkState::PASSWORD_HID_BY_NMSTATE.to_string());
+        }
+    }
+
     pub(crate) fn is_auto(&self) -> bool {
         self.enabled && (self.dhcp == Some(true) || self.autoconf == Some(true))
     }
@@ -641,11 +772,10 @@ impl InterfaceIpv6 {
                 if let IpAddr::V6(ip_addr) = addr.ip {
                     if is_ipv6_unicast_link_local(&ip_addr) {
                         if is_desired {
-                            log::warn!(
+                            log_warn(format!(
                                 "Ignoring IPv6 link local address {}/{}",
-                                &addr.ip,
-                                addr.prefix_length
-                            );
+                                &addr.ip, addr.prefix_length
+                            ));
                         }
                         false
                     } else {
@@ -694,17 +824,64 @@ impl InterfaceIpv6 {
             }
             sanitize_ipv6_token_to_string(token)?;
         }
+        // Treat hidden secret as not defined, so round trip of query result
+        // or stored commit will not write the placeholder to kernel.
+        if is_desired
+            && self.stable_secret.as_deref()
+                == Some(crate::NetworkState::PASSWORD_HID_BY_NMSTATE)
+        {
+            self.stable_secret = None;
+        }
+        if let Some(secret) = self.stable_secret.as_deref() {
+            if is_desired {
+                match self.addr_gen_mode.as_ref() {
+                    None => {
+                        self.addr_gen_mode =
+                            Some(Ipv6AddrGenMode::StablePrivacy);
+                    }
+                    Some(Ipv6AddrGenMode::StablePrivacy) => (),
+                    Some(mode) => {
+                        return Err(NipartError::new(
+                            ErrorKind::InvalidArgument,
+                            format!(
+                                "IPv6 stable-secret is only valid for \
+                                addr-gen-mode stable-privacy, but got \
+                                addr-gen-mode {mode}"
+                            ),
+                        ));
+                    }
+                }
+                if Ipv6Addr::from_str(secret).is_err() {
+                    return Err(NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        "Invalid IPv6 stable-secret, should be in the \
+                        format of IPv6 address"
+                            .to_string(),
+                    ));
+                }
+            }
+        }
+        if is_desired
+            && self.forwarding == Some(true)
+            && self.accept_ra == Some(Ipv6AcceptRa::Always)
+        {
+            log_warn(
+                "IPv6 forwarding is enabled with `accept-ra: always`, the \
+                router advertisement will be accepted even acting as router"
+                    .to_string(),
+            );
+        }
         if self.dhcp_send_hostname == Some(false) {
             if is_desired {
                 if let Some(custom_hostname) =
                     self.dhcp_custom_hostname.as_deref()
                 {
                     if !custom_hostname.is_empty() {
-                        log::warn!(
+                        log_warn(format!(
                             "Ignoring `dhcp-custom-hostname: \
                             {custom_hostname}` as `dhcp-send-hostname` is \
                             disabled"
-                        );
+                        ));
                     }
                 }
             }
@@ -787,6 +964,13 @@ impl<'de> Deserialize<'de> for InterfaceIpv6 {
                     "dhcp-client-id is not allowed for IPv6",
                 ));
             }
+            for key in ["rp-filter", "arp-ignore", "arp-announce"] {
+                if v_map.contains_key(key) {
+                    return Err(serde::de::Error::custom(format!(
+                        "{key} is not allowed for IPv6"
+                    )));
+                }
+            }
         }
         let ip: InterfaceIp = match serde_json::from_value(v) {
             Ok(i) => i,
@@ -813,11 +997,15 @@ impl From<InterfaceIp> for InterfaceIpv6 {
             auto_gateway: ip.auto_gateway,
             auto_table_id: ip.auto_table_id,
             addr_gen_mode: ip.addr_gen_mode,
+            stable_secret: ip.stable_secret,
             allow_extra_address: ip.allow_extra_address,
             auto_route_metric: ip.auto_route_metric,
             token: ip.token,
             dhcp_send_hostname: ip.dhcp_send_hostname,
             dhcp_custom_hostname: ip.dhcp_custom_hostname,
+            accept_ra: ip.accept_ra,
+            forwarding: ip.forwarding,
+            dad_transmits: ip.dad_transmits,
             ..Default::default()
         }
     }
@@ -841,11 +1029,15 @@ impl From<InterfaceIpv6> for InterfaceIp {
             auto_gateway: ip.auto_gateway,
             auto_table_id: ip.auto_table_id,
             addr_gen_mode: ip.addr_gen_mode,
+            stable_secret: ip.stable_secret,
             allow_extra_address: ip.allow_extra_address,
             auto_route_metric: ip.auto_route_metric,
             token: ip.token,
             dhcp_send_hostname: ip.dhcp_send_hostname,
             dhcp_custom_hostname: ip.dhcp_custom_hostname,
+            accept_ra: ip.accept_ra,
+            forwarding: ip.forwarding,
+            dad_transmits: ip.dad_transmits,
             ..Default::default()
         }
     }
@@ -1064,6 +1256,14 @@ pub enum Ipv6AddrGenMode {
     /// Serialize and deserialize to/from `stable-privacy`.
     #[serde(rename = "stable-privacy", alias = "STABLE-PRIVACY")]
     StablePrivacy,
+    /// Stable privacy with random secret generated by kernel.
+    /// Serialize and deserialize to/from `random`.
+    #[serde(rename = "random", alias = "RANDOM")]
+    Random,
+    /// Do not generate link-local address.
+    /// Serialize and deserialize to/from `none`.
+    #[serde(rename = "none", alias = "NONE")]
+    None,
     /// Backend specific
     #[serde(untagged)]
     Other(String),
@@ -1077,12 +1277,74 @@ impl std::fmt::Display for Ipv6AddrGenMode {
             match self {
                 Ipv6AddrGenMode::Eui64 => "eui64",
                 Ipv6AddrGenMode::StablePrivacy => "stable-privacy",
+                Ipv6AddrGenMode::Random => "random",
+                Ipv6AddrGenMode::None => "none",
                 Ipv6AddrGenMode::Other(s) => s,
             }
         )
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+/// Reverse path filtering mode of IPv4
+pub enum Ipv4RpFilter {
+    /// No source validation.
+    /// Serialize and deserialize to/from `disabled`.
+    Disabled,
+    /// Strict mode as defined in RFC3704 Strict Reverse Path.
+    /// Serialize and deserialize to/from `strict`.
+    Strict,
+    /// Loose mode as defined in RFC3704 Loose Reverse Path.
+    /// Serialize and deserialize to/from `loose`.
+    Loose,
+}
+
+impl std::fmt::Display for Ipv4RpFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Disabled => "disabled",
+                Self::Strict => "strict",
+                Self::Loose => "loose",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+/// Whether to accept IPv6 router advertisement
+pub enum Ipv6AcceptRa {
+    /// Do not accept router advertisement.
+    /// Serialize and deserialize to/from `never`.
+    Never,
+    /// Accept router advertisement if forwarding is disabled.
+    /// Serialize and deserialize to/from `enabled-unless-forwarding`.
+    EnabledUnlessForwarding,
+    /// Accept router advertisement even if forwarding is enabled.
+    /// Serialize and deserialize to/from `always`.
+    Always,
+}
+
+impl std::fmt::Display for Ipv6AcceptRa {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Never => "never",
+                Self::EnabledUnlessForwarding => "enabled-unless-forwarding",
+                Self::Always => "always",
+            }
+        )
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 #[non_exhaustive]
@@ -1409,3 +1671,441 @@ fn apply_ip_prefix_len(ip: IpAddr, prefix_length: usize) -> IpAddr {
         .into(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        InterfaceIpv4, InterfaceIpv6, Ipv4RpFilter, Ipv6AcceptRa,
+        Ipv6AddrGenMode,
+    };
+    use crate::{ErrorKind, MergedNetworkState, NetworkState};
+
+    const TEST_SECRET: &str = "2001:db8:1::3";
+
+    fn gen_ipv6() -> InterfaceIpv6 {
+        serde_yaml::from_str(
+            r"---
+            enabled: true
+            addr-gen-mode: stable-privacy
+            stable-secret: 2001:db8:1::3",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ipv6_stable_secret_round_trip() {
+        let ipv6 = gen_ipv6();
+        assert_eq!(ipv6.stable_secret.as_deref(), Some(TEST_SECRET));
+
+        let new_ipv6: InterfaceIpv6 =
+            serde_yaml::from_str(&serde_yaml::to_string(&ipv6).unwrap())
+                .unwrap();
+        assert_eq!(new_ipv6, ipv6);
+    }
+
+    #[test]
+    fn test_ipv6_stable_secret_hidden() {
+        let mut ipv6 = gen_ipv6();
+        ipv6.hide_secrets();
+
+        assert_eq!(
+            ipv6.stable_secret.as_deref(),
+            Some(NetworkState::PASSWORD_HID_BY_NMSTATE)
+        );
+        assert!(!serde_yaml::to_string(&ipv6).unwrap().contains(TEST_SECRET));
+    }
+
+    #[test]
+    fn test_ipv6_hidden_stable_secret_treated_as_not_set() {
+        let mut ipv6 = gen_ipv6();
+        ipv6.hide_secrets();
+
+        ipv6.sanitize(true).unwrap();
+
+        assert_eq!(ipv6.stable_secret, None);
+        assert_eq!(ipv6.addr_gen_mode, Some(Ipv6AddrGenMode::StablePrivacy));
+    }
+
+    #[test]
+    fn test_ipv6_invalid_stable_secret() {
+        let mut ipv6 = gen_ipv6();
+        ipv6.stable_secret = Some("not-ipv6".to_string());
+
+        assert!(ipv6.sanitize(true).is_err());
+    }
+
+    fn gen_verify_result_with_extra_addr(
+        allow_extra_address: bool,
+    ) -> Result<(), crate::NipartError> {
+        let desired = NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv4:
+                enabled: true
+                dhcp: false
+                allow-extra-address: {allow_extra_address}
+                address:
+                - ip: 192.0.2.1
+                  prefix-length: 24
+              ipv6:
+                enabled: true
+                dhcp: false
+                autoconf: false
+                allow-extra-address: {allow_extra_address}
+                address:
+                - ip: 2001:db8::1
+                  prefix-length: 64"
+        ))
+        .unwrap();
+        // Extra addresses added out-of-band, e.g. keepalived VIP
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv4:
+                enabled: true
+                dhcp: false
+                address:
+                - ip: 192.0.2.1
+                  prefix-length: 24
+                - ip: 192.0.2.100
+                  prefix-length: 32
+              ipv6:
+                enabled: true
+                dhcp: false
+                autoconf: false
+                address:
+                - ip: 2001:db8::1
+                  prefix-length: 64
+                - ip: 2001:db8::100
+                  prefix-length: 128",
+        )
+        .unwrap();
+        let merged =
+            MergedNetworkState::new(desired, current.clone(), false, false)
+                .unwrap();
+        merged.verify(&current)
+    }
+
+    #[test]
+    fn test_verify_extra_addr_with_allow_extra_address() {
+        gen_verify_result_with_extra_addr(true).unwrap();
+    }
+
+    #[test]
+    fn test_verify_extra_addr_without_allow_extra_address() {
+        let result = gen_verify_result_with_extra_addr(false);
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::VerificationError);
+        }
+    }
+
+    #[test]
+    fn test_ipv6_sysctl_round_trip() {
+        for (accept_ra, value) in [
+            ("never", Ipv6AcceptRa::Never),
+            (
+                "enabled-unless-forwarding",
+                Ipv6AcceptRa::EnabledUnlessForwarding,
+            ),
+            ("always", Ipv6AcceptRa::Always),
+        ] {
+            for forwarding in [true, false] {
+                let ipv6: InterfaceIpv6 = serde_yaml::from_str(&format!(
+                    r"---
+                    enabled: true
+                    accept-ra: {accept_ra}
+                    forwarding: {forwarding}"
+                ))
+                .unwrap();
+                assert_eq!(ipv6.accept_ra, Some(value));
+                assert_eq!(ipv6.forwarding, Some(forwarding));
+
+                let new_ipv6: InterfaceIpv6 = serde_yaml::from_str(
+                    &serde_yaml::to_string(&ipv6).unwrap(),
+                )
+                .unwrap();
+                assert_eq!(new_ipv6, ipv6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ipv4_forwarding_round_trip() {
+        for forwarding in [true, false] {
+            let ipv4: InterfaceIpv4 = serde_yaml::from_str(&format!(
+                r"---
+                enabled: true
+                forwarding: {forwarding}"
+            ))
+            .unwrap();
+            assert_eq!(ipv4.forwarding, Some(forwarding));
+
+            let new_ipv4: InterfaceIpv4 =
+                serde_yaml::from_str(&serde_yaml::to_string(&ipv4).unwrap())
+                    .unwrap();
+            assert_eq!(new_ipv4, ipv4);
+        }
+    }
+
+    #[test]
+    fn test_ipv4_accept_ra_not_allowed() {
+        let result = serde_yaml::from_str::<InterfaceIpv4>(
+            r"---
+            enabled: true
+            accept-ra: always",
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_verify_ipv6_forwarding_mismatch() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv6:
+                enabled: true
+                accept-ra: always
+                forwarding: true",
+        )
+        .unwrap();
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv6:
+                enabled: true
+                accept-ra: always
+                forwarding: false",
+        )
+        .unwrap();
+        let merged =
+            MergedNetworkState::new(desired, current.clone(), false, false)
+                .unwrap();
+
+        let result = merged.verify(&current);
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::VerificationError);
+        }
+    }
+
+    #[test]
+    fn test_ipv4_rp_filter_arp_ignore_round_trip() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv4:
+                enabled: false
+                rp-filter: loose
+                arp-ignore: 1",
+        )
+        .unwrap();
+        let ipv4 = desired.interfaces.kernel_ifaces["dummy1"]
+            .base_iface()
+            .ipv4
+            .clone()
+            .unwrap();
+        assert_eq!(ipv4.rp_filter, Some(Ipv4RpFilter::Loose));
+        assert_eq!(ipv4.arp_ignore, Some(1));
+
+        let new_ipv4: InterfaceIpv4 =
+            serde_yaml::from_str(&serde_yaml::to_string(&ipv4).unwrap())
+                .unwrap();
+        assert_eq!(new_ipv4, ipv4);
+
+        // Queried back with the same sysctl values
+        let current = desired.clone();
+        let merged =
+            MergedNetworkState::new(desired, current.clone(), false, false)
+                .unwrap();
+        merged.verify(&current).unwrap();
+    }
+
+    #[test]
+    fn test_ipv4_invalid_arp_ignore_and_announce() {
+        let mut ipv4: InterfaceIpv4 = serde_yaml::from_str(
+            r"---
+            enabled: false
+            arp-ignore: 4",
+        )
+        .unwrap();
+        assert_eq!(
+            ipv4.sanitize(true).unwrap_err().kind,
+            ErrorKind::InvalidArgument
+        );
+
+        let mut ipv4: InterfaceIpv4 = serde_yaml::from_str(
+            r"---
+            enabled: false
+            arp-announce: 3",
+        )
+        .unwrap();
+        assert_eq!(
+            ipv4.sanitize(true).unwrap_err().kind,
+            ErrorKind::InvalidArgument
+        );
+    }
+
+    #[test]
+    fn test_ipv6_rp_filter_not_allowed() {
+        let result = serde_yaml::from_str::<InterfaceIpv6>(
+            r"---
+            enabled: true
+            rp-filter: strict",
+        );
+        assert!(result.is_err());
+    }
+
+    fn gen_dad_transmits_state(dad_transmits: u32) -> NetworkState {
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv6:
+                enabled: true
+                dad-transmits: {dad_transmits}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ipv6_dad_transmits_round_trip() {
+        let desired = gen_dad_transmits_state(0);
+        let ipv6 = desired.interfaces.kernel_ifaces["dummy1"]
+            .base_iface()
+            .ipv6
+            .clone()
+            .unwrap();
+        assert_eq!(ipv6.dad_transmits, Some(0));
+
+        let new_ipv6: InterfaceIpv6 =
+            serde_yaml::from_str(&serde_yaml::to_string(&ipv6).unwrap())
+                .unwrap();
+        assert_eq!(new_ipv6, ipv6);
+
+        // Queried back with the same sysctl value
+        let current = desired.clone();
+        let merged =
+            MergedNetworkState::new(desired, current.clone(), false, false)
+                .unwrap();
+        merged.verify(&current).unwrap();
+
+        // Kernel default is 1
+        let current = gen_dad_transmits_state(1);
+        let merged = MergedNetworkState::new(
+            gen_dad_transmits_state(0),
+            current.clone(),
+            false,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            merged.verify(&current).unwrap_err().kind,
+            ErrorKind::VerificationError
+        );
+    }
+
+    #[test]
+    fn test_ipv6_negative_dad_transmits() {
+        let result = serde_yaml::from_str::<InterfaceIpv6>(
+            r"---
+            enabled: true
+            dad-transmits: -1",
+        );
+        assert!(result.is_err());
+    }
+
+    fn gen_addr_gen_mode_state(mode: &str) -> NetworkState {
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv6:
+                enabled: true
+                addr-gen-mode: {mode}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ipv6_addr_gen_mode_round_trip() {
+        for (mode, value) in [
+            ("eui64", Ipv6AddrGenMode::Eui64),
+            ("stable-privacy", Ipv6AddrGenMode::StablePrivacy),
+            ("random", Ipv6AddrGenMode::Random),
+            ("none", Ipv6AddrGenMode::None),
+            ("NONE", Ipv6AddrGenMode::None),
+        ] {
+            let desired = gen_addr_gen_mode_state(mode);
+            let ipv6 = desired.interfaces.kernel_ifaces["dummy1"]
+                .base_iface()
+                .ipv6
+                .clone()
+                .unwrap();
+            assert_eq!(ipv6.addr_gen_mode, Some(value.clone()));
+
+            let yaml = serde_yaml::to_string(&ipv6).unwrap();
+            assert!(yaml.contains(&format!("addr-gen-mode: {value}")));
+            let new_ipv6: InterfaceIpv6 = serde_yaml::from_str(&yaml).unwrap();
+            assert_eq!(new_ipv6, ipv6);
+
+            // Queried back with the same sysctl value
+            let current = desired.clone();
+            let merged =
+                MergedNetworkState::new(desired, current.clone(), false, false)
+                    .unwrap();
+            merged.verify(&current).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_ipv6_addr_gen_mode_none_not_other() {
+        let ipv6: InterfaceIpv6 = serde_yaml::from_str(
+            r"---
+            enabled: true
+            addr-gen-mode: none",
+        )
+        .unwrap();
+        assert_ne!(
+            ipv6.addr_gen_mode,
+            Some(Ipv6AddrGenMode::Other("none".to_string()))
+        );
+        assert_eq!(Ipv6AddrGenMode::None.to_string(), "none");
+    }
+
+    #[test]
+    fn test_verify_ipv6_addr_gen_mode_mismatch() {
+        let current = gen_addr_gen_mode_state("eui64");
+        let merged = MergedNetworkState::new(
+            gen_addr_gen_mode_state("none"),
+            current.clone(),
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            merged.verify(&current).unwrap_err().kind,
+            ErrorKind::VerificationError
+        );
+    }
+}
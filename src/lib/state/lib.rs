@@ -114,7 +114,7 @@ mod unit_tests;
 
 pub use crate::dispatch::DispatchConfig;
 pub use crate::dns::MergedDnsState;
-pub use crate::dns::{DnsClientState, DnsState};
+pub use crate::dns::{DnsClientState, DnsInterfaceConfig, DnsState};
 pub use crate::error::{ErrorKind, NipartError};
 pub use crate::hostname::HostNameState;
 pub use crate::hostname::MergedHostNameState;
@@ -126,15 +126,18 @@ pub use crate::iface::{
 };
 pub use crate::ifaces::MergedInterfaces;
 pub use crate::ifaces::{
-    BaseInterface, BondAdSelect, BondAllPortsActive, BondArpAllTargets,
+    BaseInterface, BondAdInfo, BondAdSelect, BondAllPortsActive, BondArpAllTargets,
     BondArpValidate, BondConfig, BondFailOverMac, BondInterface, BondLacpRate,
     BondMode, BondOptions, BondPortConfig, BondPrimaryReselect,
     BondXmitHashPolicy, BridgePortTrunkTag, BridgePortVlanConfig,
     BridgePortVlanMode, BridgePortVlanRange, DummyInterface, EthernetConfig,
     EthernetDuplex, EthernetInterface, EthtoolCoalesceConfig, EthtoolConfig,
-    EthtoolFeatureConfig, EthtoolPauseConfig, EthtoolRingConfig, HsrConfig,
+    EthtoolFeatureConfig, EthtoolChannelsConfig, EthtoolFecConfig, EthtoolFecMode,
+    EthtoolPauseConfig, EthtoolRingConfig, EthtoolRingSize, GreConfig,
+    GreInterface, GreTapInterface, HsrConfig,
     HsrInterface, HsrProtocol, InfiniBandConfig, InfiniBandInterface,
-    InfiniBandMode, Interfaces, IpsecInterface, LibreswanAddressFamily,
+    InfiniBandMode, Interfaces, IpVlanConfig, IpVlanFlag, IpVlanInterface,
+    IpVlanMode, IpsecInterface, LibreswanAddressFamily,
     LibreswanConfig, LibreswanConnectionType, LinuxBridgeConfig,
     LinuxBridgeInterface, LinuxBridgeMulticastRouterType, LinuxBridgeOptions,
     LinuxBridgePortConfig, LinuxBridgeStpOptions, LoopbackInterface,
@@ -143,13 +146,15 @@ pub use crate::ifaces::{
     MacVtapInterface, MacVtapMode, OvsBridgeBondConfig, OvsBridgeBondMode,
     OvsBridgeBondPortConfig, OvsBridgeConfig, OvsBridgeInterface,
     OvsBridgeOptions, OvsBridgePortConfig, OvsBridgeStpOptions, OvsDpdkConfig,
-    OvsInterface, OvsPatchConfig, SrIovConfig, SrIovVfConfig, VethConfig,
+    OvsInterface, OvsInterfaceType, OvsPatchConfig, SrIovConfig, SrIovVfConfig, TunConfig,
+    TunInterface, TunMode, VethConfig,
     VlanConfig, VlanInterface, VlanProtocol, VlanRegistrationProtocol,
-    VrfConfig, VrfInterface, VxlanConfig, VxlanInterface, XfrmInterface,
+    VrfConfig, VrfInterface, VxlanConfig, VxlanInterface, WireguardConfig,
+    WireguardInterface, WireguardPeerConfig, XfrmInterface,
 };
 pub use crate::ip::{
     AddressFamily, Dhcpv4ClientId, Dhcpv6Duid, InterfaceIpAddr, InterfaceIpv4,
-    InterfaceIpv6, Ipv6AddrGenMode, WaitIp,
+    InterfaceIpv6, Ipv4RpFilter, Ipv6AcceptRa, Ipv6AddrGenMode, WaitIp,
 };
 pub use crate::lldp::{
     LldpAddressFamily, LldpChassisId, LldpChassisIdType, LldpConfig,
@@ -171,7 +176,7 @@ pub use crate::policy::{
     NetworkCaptureRules, NetworkPolicy, NetworkStateTemplate,
 };
 pub use crate::route::MergedRoutes;
-pub use crate::route::{RouteEntry, RouteState, RouteType, Routes};
+pub use crate::route::{RouteEntry, RouteScope, RouteState, RouteType, Routes};
 pub use crate::route_rule::MergedRouteRules;
 pub use crate::route_rule::{
     RouteRuleAction, RouteRuleEntry, RouteRuleState, RouteRules,
@@ -15,6 +15,7 @@ mod net_state;
 #[allow(deprecated)]
 mod ovn;
 mod ovs;
+mod ovsdb;
 mod query_apply;
 mod revert;
 mod route;
@@ -25,7 +26,9 @@ mod statistic;
 mod not_synced;
 
 pub use self::dispatch::DispatchConfig;
-pub use self::dns::{DnsClientState, DnsState, MergedDnsState};
+pub use self::dns::{
+    DnsClientState, DnsInterfaceConfig, DnsState, MergedDnsState,
+};
 pub use self::hostname::{HostNameState, MergedHostNameState};
 pub use self::ieee8021x::Ieee8021XConfig;
 pub use self::iface::{
@@ -33,29 +36,35 @@ pub use self::iface::{
     MergedInterface, UnknownInterface,
 };
 pub use self::ifaces::{
-    BaseInterface, BondAdSelect, BondAllPortsActive, BondArpAllTargets,
-    BondArpValidate, BondConfig, BondFailOverMac, BondInterface, BondLacpRate,
-    BondMode, BondOptions, BondPortConfig, BondPrimaryReselect,
-    BondXmitHashPolicy, BridgePortTrunkTag, BridgePortVlanConfig,
-    BridgePortVlanMode, BridgePortVlanRange, DummyInterface, EthernetConfig,
-    EthernetDuplex, EthernetInterface, EthtoolCoalesceConfig, EthtoolConfig,
-    EthtoolFeatureConfig, EthtoolPauseConfig, EthtoolRingConfig, HsrConfig,
-    HsrInterface, InfiniBandConfig, InfiniBandInterface, InfiniBandMode,
-    Interfaces, IpsecInterface, LibreswanConfig, LinuxBridgeConfig,
-    LinuxBridgeInterface, LinuxBridgeMulticastRouterType, LinuxBridgeOptions,
-    LinuxBridgePortConfig, LinuxBridgeStpOptions, LoopbackInterface,
-    MacSecConfig, MacSecInterface, MacSecValidate, MacVlanConfig,
-    MacVlanInterface, MacVlanMode, MacVtapConfig, MacVtapInterface,
-    MacVtapMode, MergedInterfaces, OvsBridgeBondConfig, OvsBridgeBondMode,
-    OvsBridgeBondPortConfig, OvsBridgeConfig, OvsBridgeInterface,
-    OvsBridgeOptions, OvsBridgePortConfig, OvsBridgeStpOptions, OvsDpdkConfig,
-    OvsInterface, OvsPatchConfig, SrIovConfig, SrIovVfConfig, VethConfig,
-    VlanConfig, VlanInterface, VlanProtocol, VlanRegistrationProtocol,
-    VrfConfig, VrfInterface, VxlanConfig, VxlanInterface, XfrmInterface,
+    BaseInterface, BondAdInfo, BondAdSelect, BondAllPortsActive,
+    BondArpAllTargets, BondArpValidate, BondConfig, BondFailOverMac,
+    BondInterface, BondLacpRate, BondMode, BondOptions, BondPortConfig,
+    BondPrimaryReselect, BondXmitHashPolicy, BridgePortTrunkTag,
+    BridgePortVlanConfig, BridgePortVlanMode, BridgePortVlanRange,
+    DummyInterface, EthernetConfig, EthernetDuplex, EthernetInterface,
+    EthtoolChannelsConfig, EthtoolCoalesceConfig, EthtoolConfig,
+    EthtoolFeatureConfig, EthtoolFecConfig, EthtoolFecMode, EthtoolPauseConfig,
+    EthtoolRingConfig, EthtoolRingSize, GreConfig, GreInterface,
+    GreTapInterface, HsrConfig, HsrInterface, InfiniBandConfig,
+    InfiniBandInterface, InfiniBandMode, Interfaces, IpVlanConfig, IpVlanFlag,
+    IpVlanInterface, IpVlanMode, IpsecInterface, LibreswanConfig,
+    LinuxBridgeConfig, LinuxBridgeInterface, LinuxBridgeMulticastRouterType,
+    LinuxBridgeOptions, LinuxBridgePortConfig, LinuxBridgeStpOptions,
+    LoopbackInterface, MacSecConfig, MacSecInterface, MacSecValidate,
+    MacVlanConfig, MacVlanInterface, MacVlanMode, MacVtapConfig,
+    MacVtapInterface, MacVtapMode, MergedInterfaces, OvsBridgeBondConfig,
+    OvsBridgeBondMode, OvsBridgeBondPortConfig, OvsBridgeConfig,
+    OvsBridgeInterface, OvsBridgeOptions, OvsBridgePortConfig,
+    OvsBridgeStpOptions, OvsDpdkConfig, OvsInterface, OvsInterfaceType,
+    OvsPatchConfig, SrIovConfig, SrIovVfConfig, TunConfig, TunInterface,
+    TunMode, VethConfig, VlanConfig, VlanInterface, VlanProtocol,
+    VlanRegistrationProtocol, VrfConfig, VrfInterface, VxlanConfig,
+    VxlanInterface, WireguardConfig, WireguardInterface, WireguardPeerConfig,
+    XfrmInterface,
 };
 pub use self::ip::{
     AddressFamily, Dhcpv4ClientId, Dhcpv6Duid, InterfaceIpAddr, InterfaceIpv4,
-    InterfaceIpv6, Ipv6AddrGenMode, WaitIp,
+    InterfaceIpv6, Ipv4RpFilter, Ipv6AcceptRa, Ipv6AddrGenMode, WaitIp,
 };
 pub use self::lldp::{
     LldpAddressFamily, LldpChassisId, LldpChassisIdType, LldpConfig,
@@ -66,6 +75,7 @@ pub use self::lldp::{
 };
 pub use self::mptcp::{MptcpAddressFlag, MptcpConfig};
 pub use self::net_state::{MergedNetworkState, NetworkState};
+pub use self::not_synced::NetworkStateConflict;
 pub use self::ovn::{
     MergedOvnConfiguration, OvnBridgeMapping, OvnBridgeMappingState,
     OvnConfiguration,
@@ -73,8 +83,14 @@ pub use self::ovn::{
 pub use self::ovs::{
     MergedOvsDbGlobalConfig, OvsDbGlobalConfig, OvsDbIfaceConfig,
 };
+pub use self::ovsdb::{
+    ovsdb_apply, ovsdb_is_changed, ovsdb_is_running, ovsdb_retrieve,
+};
 pub use self::route::MergedRoutes;
-pub use self::route::{RouteEntry, RouteState, RouteType, Routes};
+pub use self::route::{
+    Ipv6RoutePref, RouteEntry, RouteNextHop, RouteScope, RouteState, RouteType,
+    Routes,
+};
 pub use self::route_rule::MergedRouteRules;
 pub use self::route_rule::{
     RouteRuleAction, RouteRuleEntry, RouteRuleState, RouteRules,
@@ -5,7 +5,10 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::{BaseInterface, ErrorKind, MergedInterface, NipartError};
+use crate::{
+    state::not_synced::log_warn, BaseInterface, ErrorKind, MergedInterface,
+    NipartError,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
@@ -107,16 +110,13 @@ fn validate_iface_mptcp_and_addr_mptcp_flags(iface: &BaseInterface) {
         if let Some(mut addr_flags) = ip_addr.mptcp_flags.as_ref().cloned() {
             addr_flags.sort_unstable();
             if iface_flags != addr_flags {
-                log::warn!(
+                log_warn(format!(
                     "Nmstate does not support setting different \
                     MPTCP flags within the interface. Ignoring MPTCP \
                     flags {:?} of IP address {}/{} as it is different \
                     from interface level MPTCP flags {:?}",
-                    addr_flags,
-                    ip_addr.ip,
-                    ip_addr.prefix_length,
-                    iface_flags
-                );
+                    addr_flags, ip_addr.ip, ip_addr.prefix_length, iface_flags
+                ));
             }
         }
     }
@@ -294,6 +294,25 @@ impl NetworkState {
         ))
     }
 
+    #[cfg(not(feature = "gen_conf"))]
+    pub fn from_running_config() -> Result<Self, NipartError> {
+        Err(NipartError::new(
+            ErrorKind::DependencyError,
+            "NetworkState::from_running_config() need `genconf` feature \
+            enabled"
+                .into(),
+        ))
+    }
+
+    #[cfg(not(feature = "gen_conf"))]
+    pub fn from_nm_keyfiles(_contents: &[&str]) -> Result<Self, NipartError> {
+        Err(NipartError::new(
+            ErrorKind::DependencyError,
+            "NetworkState::from_nm_keyfiles() need `genconf` feature enabled"
+                .into(),
+        ))
+    }
+
     #[cfg(not(feature = "query_apply"))]
     pub fn checkpoint_rollback(_checkpoint: &str) -> Result<(), NipartError> {
         Err(NipartError::new(
@@ -320,8 +339,8 @@ pub struct MergedNetworkState {
     pub interfaces: MergedInterfaces,
     pub(crate) hostname: MergedHostNameState,
     pub dns: MergedDnsState,
-    pub(crate) ovn: MergedOvnConfiguration,
-    pub(crate) ovsdb: MergedOvsDbGlobalConfig,
+    pub ovn: MergedOvnConfiguration,
+    pub ovsdb: MergedOvsDbGlobalConfig,
     pub routes: MergedRoutes,
     pub rules: MergedRouteRules,
     pub(crate) memory_only: bool,
@@ -360,7 +379,7 @@ impl MergedNetworkState {
             &ovn,
         )?;
 
-        let ret = Self {
+        let mut ret = Self {
             interfaces,
             routes,
             rules,
@@ -374,6 +393,12 @@ impl MergedNetworkState {
             memory_only,
         };
         ret.validate_ipv6_link_local_address_dns_srv()?;
+        ret.validate_dns_per_interface()?;
+        ret.validate_ovn_bridge_mappings()?;
+        ret.validate_ovs_patch_peers()?;
+        ret.warn_ovs_dpdk_not_initialized();
+        ret.process_loopback()?;
+        ret.process_iface_rename()?;
 
         Ok(ret)
     }
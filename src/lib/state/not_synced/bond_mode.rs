@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Interface, MergedInterfaces};
+
+impl MergedInterfaces {
+    // Changing bond mode requires detaching all its ports, hence we include
+    // unchanged ports into apply to reattach them once bond mode changed.
+    // The child interfaces (e.g. VLAN) using this bond as parent are also
+    // included to ensure they are applied after the bond.
+    // The apply order is determined by controller and parent relationship
+    // of interfaces for apply.
+    pub(crate) fn process_bond_mode_change(&mut self) {
+        let mut pending_changes: Vec<String> = Vec::new();
+        for (bond_name, merged_iface) in self
+            .kernel_ifaces
+            .iter()
+            .filter(|(_, i)| i.is_desired() && i.merged.is_up())
+        {
+            let (
+                Some(Interface::Bond(apply_iface)),
+                Some(Interface::Bond(cur_iface)),
+            ) = (
+                merged_iface.for_apply.as_ref(),
+                merged_iface.current.as_ref(),
+            )
+            else {
+                continue;
+            };
+            let Some(des_mode) =
+                apply_iface.bond.as_ref().and_then(|b| b.mode.as_ref())
+            else {
+                continue;
+            };
+            if cur_iface.bond.as_ref().and_then(|b| b.mode.as_ref())
+                == Some(des_mode)
+            {
+                continue;
+            }
+            log::debug!(
+                "Bond {bond_name} is changing mode to {des_mode}, will \
+                reattach its ports and reapply its child interfaces"
+            );
+            if let Some(ports) = merged_iface.merged.ports() {
+                pending_changes.extend(ports.iter().map(|p| p.to_string()));
+            }
+            pending_changes.extend(
+                self.kernel_ifaces
+                    .values()
+                    .filter(|i| i.merged.is_up())
+                    .filter(|i| i.merged.parent() == Some(bond_name.as_str()))
+                    .map(|i| i.merged.name().to_string()),
+            );
+        }
+
+        for iface_name in pending_changes {
+            if let Some(iface) = self
+                .kernel_ifaces
+                .get_mut(&iface_name)
+                .filter(|i| !i.is_changed() && !i.merged.is_absent())
+            {
+                log::debug!(
+                    "Including interface {iface_name} into apply for bond \
+                    mode change"
+                );
+                iface.mark_as_changed();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Interface, MergedNetworkState, NetworkState};
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              controller: bond99
+            - name: eth2
+              type: ethernet
+              state: up
+              controller: bond99
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: balance-rr
+                port:
+                - eth1
+                - eth2
+            - name: bond99.20
+              type: vlan
+              state: up
+              vlan:
+                base-iface: bond99
+                id: 20",
+        )
+        .unwrap()
+    }
+
+    fn is_changed(merged: &MergedNetworkState, iface_name: &str) -> bool {
+        merged
+            .interfaces
+            .kernel_ifaces
+            .get(iface_name)
+            .map(|i| i.is_changed())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_bond_mode_change_with_new_vlan() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: active-backup
+            - name: bond99.10
+              type: vlan
+              state: up
+              vlan:
+                base-iface: bond99
+                id: 10",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        for port_name in ["eth1", "eth2"] {
+            let port = &merged.interfaces.kernel_ifaces[port_name];
+            assert!(port.is_changed());
+            assert_eq!(
+                port.for_apply
+                    .as_ref()
+                    .and_then(|i| i.base_iface().controller.as_deref()),
+                Some("bond99")
+            );
+        }
+        assert!(is_changed(&merged, "bond99.10"));
+        assert!(is_changed(&merged, "bond99.20"));
+        let Some(Interface::Bond(bond_iface)) =
+            merged.interfaces.kernel_ifaces["bond99"].for_apply.as_ref()
+        else {
+            panic!("bond99 should be included in apply");
+        };
+        assert_eq!(
+            bond_iface
+                .bond
+                .as_ref()
+                .and_then(|b| b.mode.as_ref())
+                .map(|m| m.to_string()),
+            Some("active-backup".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bond_mode_unchanged_does_not_include_ports() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: balance-rr
+                options:
+                  miimon: 200",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        assert!(is_changed(&merged, "bond99"));
+        assert!(!is_changed(&merged, "eth1"));
+        assert!(!is_changed(&merged, "eth2"));
+        assert!(!is_changed(&merged, "bond99.20"));
+    }
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::BondOptions;
+
+impl BondOptions {
+    /// Kernel sysfs option name and value of defined bond options, which
+    /// is also used by NetworkManager keyfile `[bond]` section.
+    pub fn to_kernel_options(&self) -> Vec<(&'static str, String)> {
+        let mut ret: Vec<(&'static str, String)> = Vec::new();
+        if let Some(v) = self.ad_actor_sys_prio {
+            ret.push(("ad_actor_sys_prio", v.to_string()));
+        }
+        if let Some(v) = self.ad_actor_system.as_ref() {
+            ret.push(("ad_actor_system", v.to_string()));
+        }
+        if let Some(v) = self.ad_select.as_ref() {
+            ret.push(("ad_select", v.to_string()));
+        }
+        if let Some(v) = self.ad_user_port_key {
+            ret.push(("ad_user_port_key", v.to_string()));
+        }
+        if let Some(v) = self.all_slaves_active.as_ref() {
+            ret.push(("all_slaves_active", u8::from(v.clone()).to_string()));
+        }
+        if let Some(v) = self.arp_all_targets.as_ref() {
+            ret.push(("arp_all_targets", v.to_string()));
+        }
+        if let Some(v) = self.arp_interval {
+            ret.push(("arp_interval", v.to_string()));
+        }
+        if let Some(v) = self.arp_ip_target.as_ref() {
+            ret.push(("arp_ip_target", v.to_string()));
+        }
+        if let Some(v) = self.arp_missed_max {
+            ret.push(("arp_missed_max", v.to_string()));
+        }
+        if let Some(v) = self.arp_validate.as_ref() {
+            ret.push(("arp_validate", v.to_string()));
+        }
+        if let Some(v) = self.downdelay {
+            ret.push(("downdelay", v.to_string()));
+        }
+        if let Some(v) = self.fail_over_mac.as_ref() {
+            ret.push(("fail_over_mac", v.to_string()));
+        }
+        if let Some(v) = self.lacp_rate.as_ref() {
+            ret.push(("lacp_rate", v.to_string()));
+        }
+        if let Some(v) = self.lp_interval {
+            ret.push(("lp_interval", v.to_string()));
+        }
+        if let Some(v) = self.miimon {
+            ret.push(("miimon", v.to_string()));
+        }
+        if let Some(v) = self.min_links {
+            ret.push(("min_links", v.to_string()));
+        }
+        if let Some(v) = self.num_grat_arp {
+            ret.push(("num_grat_arp", v.to_string()));
+        }
+        if let Some(v) = self.num_unsol_na {
+            ret.push(("num_unsol_na", v.to_string()));
+        }
+        if let Some(v) = self.packets_per_slave {
+            ret.push(("packets_per_slave", v.to_string()));
+        }
+        if let Some(v) = self.primary.as_ref() {
+            ret.push(("primary", v.to_string()));
+        }
+        if let Some(v) = self.primary_reselect.as_ref() {
+            ret.push(("primary_reselect", v.to_string()));
+        }
+        if let Some(v) = self.resend_igmp {
+            ret.push(("resend_igmp", v.to_string()));
+        }
+        if let Some(v) = self.tlb_dynamic_lb {
+            ret.push(("tlb_dynamic_lb", if v { "1" } else { "0" }.to_string()));
+        }
+        if let Some(v) = self.updelay {
+            ret.push(("updelay", v.to_string()));
+        }
+        if let Some(v) = self.use_carrier {
+            ret.push(("use_carrier", if v { "1" } else { "0" }.to_string()));
+        }
+        if let Some(v) = self.xmit_hash_policy.as_ref() {
+            ret.push(("xmit_hash_policy", v.to_string()));
+        }
+        ret
+    }
+}
@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ErrorKind, InterfaceType, MergedInterfaces, NipartError};
+
+impl MergedInterfaces {
+    // Port interface cannot be attached to controller which is being removed
+    // in the same transaction. The ports of removed controller are detached
+    // by `handle_changed_ports()`, this is only for port explicitly
+    // requesting the removed controller via `controller` property.
+    pub(crate) fn validate_controller_not_removed(
+        &self,
+    ) -> Result<(), NipartError> {
+        for iface in self
+            .kernel_ifaces
+            .values()
+            .filter(|i| i.is_desired() && i.merged.is_up())
+        {
+            let Some(ctrl_name) = iface
+                .desired
+                .as_ref()
+                .and_then(|i| i.base_iface().controller.as_deref())
+                .filter(|c| !c.is_empty())
+            else {
+                continue;
+            };
+            let ctrl_iface = self
+                .user_ifaces
+                .get(&(ctrl_name.to_string(), InterfaceType::OvsBridge))
+                .or_else(|| self.kernel_ifaces.get(ctrl_name));
+            if let Some(ctrl_iface) = ctrl_iface {
+                if ctrl_iface.is_desired() && ctrl_iface.merged.is_absent() {
+                    let e = NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!(
+                            "Interface {} cannot be attached to controller \
+                            {ctrl_name} as the controller is marked as \
+                            absent, please remove the `controller` property \
+                            or set it to empty string to detach",
+                            iface.merged.name()
+                        ),
+                    );
+                    log::error!("{}", e);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, MergedNetworkState, NetworkState};
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: balance-rr
+                port:
+                - eth1
+                - eth2
+            - name: eth1
+              type: ethernet
+              state: up
+              controller: bond99
+            - name: eth2
+              type: ethernet
+              state: up
+              controller: bond99",
+        )
+        .unwrap()
+    }
+
+    fn assert_invalid_argument(desired: NetworkState) {
+        let result =
+            MergedNetworkState::new(desired, gen_current(), false, false);
+
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::InvalidArgument);
+        }
+    }
+
+    #[test]
+    fn test_remove_bond_detach_ports() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: bond99
+              type: bond
+              state: absent",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        for port_name in ["eth1", "eth2"] {
+            let port = &merged.interfaces.kernel_ifaces[port_name];
+            assert!(port.is_changed());
+            assert!(port.merged.is_up());
+            assert_eq!(
+                port.for_apply
+                    .as_ref()
+                    .and_then(|i| i.base_iface().controller.as_deref()),
+                Some("")
+            );
+        }
+    }
+
+    #[test]
+    fn test_missing_bridge_as_controller() {
+        assert_invalid_argument(
+            NetworkState::new_from_yaml(
+                r"---
+                interfaces:
+                - name: eth1
+                  type: ethernet
+                  state: up
+                  controller: br-not-exist",
+            )
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_attach_port_to_removed_controller() {
+        assert_invalid_argument(
+            NetworkState::new_from_yaml(
+                r"---
+                interfaces:
+                - name: bond99
+                  type: bond
+                  state: absent
+                - name: eth1
+                  type: ethernet
+                  state: up
+                  controller: bond99",
+            )
+            .unwrap(),
+        );
+    }
+}
@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    ErrorKind, InterfaceIpv4, InterfaceIpv6, MergedInterface, MergedInterfaces,
+    NipartError,
+};
+
+impl MergedInterfaces {
+    // For interface with `copy-ip-config-from` defined:
+    //  * Validate the source interface exists and holds IP configuration.
+    //  * Copy IPv4 and IPv6 configuration of merged source interface to
+    //    `for_apply` and `for_verify` of this interface.
+    //  * Disable IP of source interface if `copy-ip-config-disable-source`
+    //    is true.
+    pub(crate) fn apply_copy_ip_from(&mut self) -> Result<(), NipartError> {
+        // Vec<(dst_name, src_name, ipv4, ipv6, disable_source)>
+        let mut pending_changes: Vec<(
+            String,
+            String,
+            Option<InterfaceIpv4>,
+            Option<InterfaceIpv6>,
+            bool,
+        )> = Vec::new();
+        for (iface_name, merged_iface) in self
+            .kernel_ifaces
+            .iter()
+            .filter(|(_, i)| i.is_desired() && !i.merged.is_absent())
+        {
+            let base_iface = merged_iface.merged.base_iface();
+            let Some(src_iface_name) = base_iface.copy_ip_from.as_deref()
+            else {
+                continue;
+            };
+            if src_iface_name == iface_name {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Interface {iface_name} cannot copy IP \
+                        configuration from itself"
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+            if !base_iface.can_have_ip() {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Interface {iface_name} cannot hold IP, hence \
+                        cannot copy-ip-config-from {src_iface_name}"
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+            let Some(src_iface) = self
+                .kernel_ifaces
+                .get(src_iface_name)
+                .filter(|i| !i.merged.is_absent())
+                .map(|i| &i.merged)
+            else {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Failed to find interface {src_iface_name} for \
+                        copy-ip-config-from of iface {iface_name}"
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            };
+            let ipv4 = src_iface
+                .base_iface()
+                .ipv4
+                .as_ref()
+                .filter(|i| i.is_auto() || i.is_static())
+                .map(copy_ipv4_conf);
+            let ipv6 = src_iface
+                .base_iface()
+                .ipv6
+                .as_ref()
+                .filter(|i| i.is_auto() || i.is_static())
+                .map(copy_ipv6_conf);
+            if ipv4.is_none() && ipv6.is_none() {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Interface {src_iface_name} has no IP configuration \
+                        for copy-ip-config-from of iface {iface_name}"
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+            pending_changes.push((
+                iface_name.to_string(),
+                src_iface_name.to_string(),
+                ipv4,
+                ipv6,
+                base_iface.copy_ip_config_disable_source == Some(true),
+            ));
+        }
+
+        for (iface_name, src_iface_name, ipv4, ipv6, disable_source) in
+            pending_changes
+        {
+            if let Some(iface) = self.kernel_ifaces.get_mut(&iface_name) {
+                iface.set_copy_from_ip(
+                    ipv4.unwrap_or_else(disabled_ipv4),
+                    ipv6.unwrap_or_else(disabled_ipv6),
+                );
+            }
+            if disable_source {
+                if let Some(src_iface) =
+                    self.kernel_ifaces.get_mut(&src_iface_name)
+                {
+                    log::info!(
+                        "Disabling IP of interface {src_iface_name} as its \
+                        IP configuration is copied to {iface_name}"
+                    );
+                    src_iface.mark_as_changed();
+                    src_iface
+                        .set_copy_from_ip(disabled_ipv4(), disabled_ipv6());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MergedInterface {
+    fn set_copy_from_ip(&mut self, ipv4: InterfaceIpv4, ipv6: InterfaceIpv6) {
+        for iface in [
+            Some(&mut self.merged),
+            self.for_apply.as_mut(),
+            self.for_verify.as_mut(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            let base_iface = iface.base_iface_mut();
+            base_iface.copy_ip_from = None;
+            base_iface.copy_ip_config_disable_source = None;
+            base_iface.ipv4 = Some(ipv4.clone());
+            base_iface.ipv6 = Some(ipv6.clone());
+        }
+    }
+}
+
+fn copy_ipv4_conf(src: &InterfaceIpv4) -> InterfaceIpv4 {
+    let mut ret = src.clone();
+    ret.enabled_defined = true;
+    ret.dns = None;
+    ret.rules = None;
+    ret
+}
+
+fn copy_ipv6_conf(src: &InterfaceIpv6) -> InterfaceIpv6 {
+    let mut ret = src.clone();
+    ret.enabled_defined = true;
+    ret.dns = None;
+    ret.rules = None;
+    ret
+}
+
+fn disabled_ipv4() -> InterfaceIpv4 {
+    InterfaceIpv4 {
+        enabled: false,
+        enabled_defined: true,
+        ..Default::default()
+    }
+}
+
+fn disabled_ipv6() -> InterfaceIpv6 {
+    InterfaceIpv6 {
+        enabled: false,
+        enabled_defined: true,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Interface, MergedNetworkState, NetworkState};
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth0
+              type: ethernet
+              state: up
+              ipv4:
+                enabled: true
+                address:
+                - ip: 192.0.2.10
+                  prefix-length: 24
+            - name: eth1
+              type: ethernet
+              state: up
+              ipv4:
+                enabled: false
+              ipv6:
+                enabled: false",
+        )
+        .unwrap()
+    }
+
+    fn get_ipv4_addrs(iface: Option<&Interface>) -> Vec<String> {
+        iface
+            .and_then(|i| i.base_iface().ipv4.as_ref())
+            .and_then(|i| i.addresses.as_ref())
+            .map(|addrs| addrs.iter().map(|a| a.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_move_static_ip_from_eth0_to_br0() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: br0
+              type: linux-bridge
+              state: up
+              copy-ip-config-from: eth0
+              copy-ip-config-disable-source: true
+              bridge:
+                port:
+                - name: eth0",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        let br0 = &merged.interfaces.kernel_ifaces["br0"];
+        assert_eq!(
+            get_ipv4_addrs(br0.for_apply.as_ref()),
+            vec!["192.0.2.10/24".to_string()]
+        );
+        assert_eq!(
+            get_ipv4_addrs(br0.for_verify.as_ref()),
+            vec!["192.0.2.10/24".to_string()]
+        );
+        assert!(br0
+            .for_apply
+            .as_ref()
+            .map(|i| i.base_iface().copy_ip_from.is_none())
+            .unwrap_or_default());
+        let eth0 = &merged.interfaces.kernel_ifaces["eth0"];
+        assert!(eth0.is_changed());
+        assert!(get_ipv4_addrs(eth0.for_apply.as_ref()).is_empty());
+    }
+
+    #[test]
+    fn test_copy_ip_from_iface_without_ip() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: br0
+              type: linux-bridge
+              state: up
+              copy-ip-config-from: eth1
+              bridge:
+                port:
+                - name: eth1",
+        )
+        .unwrap();
+
+        let result =
+            MergedNetworkState::new(desired, gen_current(), false, false);
+
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::InvalidArgument);
+        }
+    }
+}
@@ -0,0 +1,546 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::{
+    BondConfig, ErrorKind, Interface, InterfaceIpv4, InterfaceIpv6,
+    InterfaceType, LinuxBridgeOptions, MergedNetworkState, NetworkState,
+    NipartError, RouteEntry,
+};
+
+const NM_BACKEND_NAME: &str = "NetworkManager";
+const NM_KEYFILE_SUFFIX: &str = ".nmconnection";
+
+impl NetworkState {
+    /// Generate backend configure files without touching the live system.
+    /// Return a map of backend name to a list of `(file_name, content)`.
+    /// Currently only NetworkManager keyfiles are generated. DNS and route
+    /// rules are not included.
+    pub fn gen_conf(
+        &self,
+    ) -> Result<HashMap<String, Vec<(String, String)>>, NipartError> {
+        let merged_state = MergedNetworkState::new(
+            self.clone(),
+            NetworkState::new(),
+            true,
+            false,
+        )?;
+        let routes: Vec<RouteEntry> = self
+            .routes
+            .flatten_next_hops()?
+            .config
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|r| !r.is_absent())
+            .collect();
+
+        let mut files = Vec::new();
+        for iface in merged_state
+            .interfaces
+            .iter()
+            .filter_map(|i| i.for_apply.as_ref())
+            .filter(|i| !i.is_absent() && !i.is_ignore())
+        {
+            let iface_routes: Vec<&RouteEntry> = routes
+                .iter()
+                .filter(|r| r.next_hop_iface.as_deref() == Some(iface.name()))
+                .collect();
+            validate_nm_profile_name(nm_profile_name(iface))?;
+            files.push((
+                format!("{}{NM_KEYFILE_SUFFIX}", nm_profile_name(iface)),
+                gen_nm_keyfile(iface, iface_routes.as_slice())?,
+            ));
+        }
+        let mut ret = HashMap::new();
+        ret.insert(NM_BACKEND_NAME.to_string(), files);
+        Ok(ret)
+    }
+}
+
+fn gen_nm_keyfile(
+    iface: &Interface,
+    routes: &[&RouteEntry],
+) -> Result<String, NipartError> {
+    let base_iface = iface.base_iface();
+    let mut ret = String::new();
+    let nm_iface_type = match iface {
+        Interface::Ethernet(eth_iface) if eth_iface.veth.is_some() => "veth",
+        Interface::Ethernet(_) => "ethernet",
+        Interface::Bond(_) => "bond",
+        Interface::LinuxBridge(_) => "bridge",
+        Interface::Vlan(_) => "vlan",
+        Interface::Dummy(_) => "dummy",
+        Interface::Loopback(_) => "loopback",
+        _ => {
+            return Err(NipartError::new(
+                ErrorKind::NotSupportedError,
+                format!(
+                    "Generating configure for interface {} of type {} \
+                    is not supported yet",
+                    iface.name(),
+                    iface.iface_type()
+                ),
+            ));
+        }
+    };
+
+    let profile_name = nm_profile_name(iface);
+    writeln!(ret, "[connection]").ok();
+    writeln!(ret, "id={profile_name}").ok();
+    writeln!(
+        ret,
+        "uuid={}",
+        uuid::Uuid::new_v5(&uuid::Uuid::NAMESPACE_OID, profile_name.as_bytes())
+    )
+    .ok();
+    writeln!(ret, "type={nm_iface_type}").ok();
+    writeln!(ret, "interface-name={}", iface.name()).ok();
+    writeln!(ret, "autoconnect={}", iface.is_up()).ok();
+    if let Some(ctrl) =
+        base_iface.controller.as_deref().filter(|c| !c.is_empty())
+    {
+        writeln!(ret, "controller={ctrl}").ok();
+        match base_iface.controller_type.as_ref() {
+            Some(InterfaceType::Bond) => {
+                writeln!(ret, "port-type=bond").ok();
+            }
+            Some(InterfaceType::LinuxBridge) => {
+                writeln!(ret, "port-type=bridge").ok();
+            }
+            _ => (),
+        }
+    }
+
+    match iface {
+        Interface::Ethernet(eth_iface) => {
+            if let Some(veth_conf) = eth_iface.veth.as_ref() {
+                writeln!(ret, "\n[veth]\npeer={}", veth_conf.peer).ok();
+            }
+        }
+        Interface::Bond(bond_iface) => {
+            if let Some(bond_conf) = bond_iface.bond.as_ref() {
+                gen_nm_bond_section(&mut ret, bond_conf);
+            }
+        }
+        Interface::LinuxBridge(br_iface) => {
+            if let Some(br_opts) =
+                br_iface.bridge.as_ref().and_then(|b| b.options.as_ref())
+            {
+                gen_nm_bridge_section(&mut ret, br_opts);
+            }
+        }
+        Interface::Vlan(vlan_iface) => {
+            if let Some(vlan_conf) = vlan_iface.vlan.as_ref() {
+                writeln!(ret, "\n[vlan]\nid={}", vlan_conf.id).ok();
+                if let Some(parent) = vlan_conf.base_iface.as_deref() {
+                    writeln!(ret, "parent={parent}").ok();
+                }
+            }
+        }
+        _ => (),
+    }
+
+    if base_iface.mac_address.is_some() || base_iface.mtu.is_some() {
+        writeln!(ret, "\n[{}]", nm_wired_setting_name(nm_iface_type)).ok();
+        if let Some(mac) = base_iface.mac_address.as_deref() {
+            writeln!(ret, "cloned-mac-address={mac}").ok();
+        }
+        if let Some(mtu) = base_iface.mtu {
+            writeln!(ret, "mtu={mtu}").ok();
+        }
+    }
+
+    if base_iface.can_have_ip() {
+        gen_nm_ipv4_section(
+            &mut ret,
+            base_iface.ipv4.as_ref(),
+            routes
+                .iter()
+                .filter(|r| !r.is_ipv6())
+                .copied()
+                .collect::<Vec<_>>()
+                .as_slice(),
+        );
+        gen_nm_ipv6_section(
+            &mut ret,
+            base_iface.ipv6.as_ref(),
+            routes
+                .iter()
+                .filter(|r| r.is_ipv6())
+                .copied()
+                .collect::<Vec<_>>()
+                .as_slice(),
+        );
+    }
+    Ok(ret)
+}
+
+// Use `profile-name` when defined, so multiple profiles could be generated
+// for the same interface.
+fn nm_profile_name(iface: &Interface) -> &str {
+    iface
+        .base_iface()
+        .profile_name
+        .as_deref()
+        .filter(|p| !p.is_empty())
+        .unwrap_or(iface.name())
+}
+
+// The profile name is used as both keyfile name and `[connection] id`,
+// reject names escaping the output folder or breaking the keyfile lines.
+fn validate_nm_profile_name(name: &str) -> Result<(), NipartError> {
+    if name.contains('/')
+        || name.contains("..")
+        || name.chars().any(|c| c.is_control())
+    {
+        let e = NipartError::new(
+            ErrorKind::InvalidArgument,
+            format!(
+                "Profile name {name:?} should not contain '/', '..' or \
+                control characters"
+            ),
+        );
+        log::error!("{}", e);
+        return Err(e);
+    }
+    Ok(())
+}
+
+// NetworkManager stores MTU of loopback interface in `loopback` setting,
+// other interfaces use `ethernet` setting.
+fn nm_wired_setting_name(nm_iface_type: &str) -> &'static str {
+    match nm_iface_type {
+        "loopback" => "loopback",
+        _ => "ethernet",
+    }
+}
+
+// NetworkManager is using kernel bond option names
+fn gen_nm_bond_section(ret: &mut String, bond_conf: &BondConfig) {
+    let mut lines: Vec<String> = Vec::new();
+    if let Some(mode) = bond_conf.mode.as_ref() {
+        lines.push(format!("mode={mode}"));
+    }
+    if let Some(opts) = bond_conf.options.as_ref() {
+        for (option, value) in opts.to_kernel_options() {
+            lines.push(format!("{option}={value}"));
+        }
+    }
+    if !lines.is_empty() {
+        writeln!(ret, "\n[bond]\n{}", lines.join("\n")).ok();
+    }
+}
+
+fn gen_nm_bridge_section(ret: &mut String, opts: &LinuxBridgeOptions) {
+    let mut lines: Vec<String> = Vec::new();
+    if let Some(stp_opts) = opts.stp.as_ref() {
+        if let Some(v) = stp_opts.enabled {
+            lines.push(format!("stp={v}"));
+        }
+        if let Some(v) = stp_opts.priority {
+            lines.push(format!("priority={v}"));
+        }
+        if let Some(v) = stp_opts.forward_delay {
+            lines.push(format!("forward-delay={v}"));
+        }
+        if let Some(v) = stp_opts.hello_time {
+            lines.push(format!("hello-time={v}"));
+        }
+        if let Some(v) = stp_opts.max_age {
+            lines.push(format!("max-age={v}"));
+        }
+    }
+    if let Some(v) = opts.mac_ageing_time {
+        lines.push(format!("ageing-time={v}"));
+    }
+    if let Some(v) = opts.group_forward_mask {
+        lines.push(format!("group-forward-mask={v}"));
+    }
+    if let Some(v) = opts.hash_max {
+        lines.push(format!("multicast-hash-max={v}"));
+    }
+    if let Some(v) = opts.multicast_snooping {
+        lines.push(format!("multicast-snooping={v}"));
+    }
+    if let Some(v) = opts.multicast_querier {
+        lines.push(format!("multicast-querier={v}"));
+    }
+    if let Some(v) = opts.multicast_query_use_ifaddr {
+        lines.push(format!("multicast-query-use-ifaddr={v}"));
+    }
+    if let Some(v) = opts.multicast_router.as_ref() {
+        lines.push(format!("multicast-router={v}"));
+    }
+    if !lines.is_empty() {
+        writeln!(ret, "\n[bridge]\n{}", lines.join("\n")).ok();
+    }
+}
+
+fn gen_nm_ipv4_section(
+    ret: &mut String,
+    ipv4: Option<&InterfaceIpv4>,
+    routes: &[&RouteEntry],
+) {
+    writeln!(ret, "\n[ipv4]").ok();
+    let ipv4 = match ipv4.filter(|i| i.enabled) {
+        Some(i) => i,
+        None => {
+            writeln!(ret, "method=disabled").ok();
+            return;
+        }
+    };
+    if ipv4.dhcp == Some(true) {
+        writeln!(ret, "method=auto").ok();
+    } else {
+        writeln!(ret, "method=manual").ok();
+    }
+    for (i, addr) in ipv4
+        .addresses
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .enumerate()
+    {
+        writeln!(ret, "address{}={}/{}", i + 1, addr.ip, addr.prefix_length)
+            .ok();
+    }
+    gen_nm_route_lines(ret, routes);
+}
+
+fn gen_nm_ipv6_section(
+    ret: &mut String,
+    ipv6: Option<&InterfaceIpv6>,
+    routes: &[&RouteEntry],
+) {
+    writeln!(ret, "\n[ipv6]").ok();
+    let ipv6 = match ipv6.filter(|i| i.enabled) {
+        Some(i) => i,
+        None => {
+            writeln!(ret, "method=disabled").ok();
+            return;
+        }
+    };
+    // NetworkManager `auto` method follows router advertisement to decide
+    // whether to start DHCPv6.
+    if ipv6.autoconf == Some(true) {
+        writeln!(ret, "method=auto").ok();
+    } else if ipv6.dhcp == Some(true) {
+        writeln!(ret, "method=dhcp").ok();
+    } else if ipv6.addresses.as_ref().map(|a| a.is_empty()) == Some(false) {
+        writeln!(ret, "method=manual").ok();
+    } else {
+        writeln!(ret, "method=link-local").ok();
+    }
+    for (i, addr) in ipv6
+        .addresses
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .enumerate()
+    {
+        writeln!(ret, "address{}={}/{}", i + 1, addr.ip, addr.prefix_length)
+            .ok();
+    }
+    gen_nm_route_lines(ret, routes);
+}
+
+fn gen_nm_route_lines(ret: &mut String, routes: &[&RouteEntry]) {
+    for (i, route) in routes.iter().enumerate() {
+        let mut line = format!(
+            "route{}={}",
+            i + 1,
+            route.destination.as_deref().unwrap_or_default()
+        );
+        // Empty gateway for on-link route still holding metric
+        match (route.next_hop_addr.as_deref(), route.metric) {
+            (Some(next_hop), Some(metric)) => {
+                line.push_str(&format!(",{next_hop},{metric}"));
+            }
+            (Some(next_hop), None) => {
+                line.push_str(&format!(",{next_hop}"));
+            }
+            (None, Some(metric)) => {
+                line.push_str(&format!(",,{metric}"));
+            }
+            (None, None) => (),
+        }
+        writeln!(ret, "{line}").ok();
+        if let Some(table_id) = route.table_id {
+            writeln!(ret, "route{}_options=table={table_id}", i + 1).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, NetworkState};
+
+    fn gen_keyfiles(yaml: &str) -> Vec<(String, String)> {
+        NetworkState::new_from_yaml(yaml)
+            .unwrap()
+            .gen_conf()
+            .unwrap()
+            .remove("NetworkManager")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_gen_conf_ipv4_section() {
+        let files = gen_keyfiles(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ipv4:
+                enabled: true
+                dhcp: false
+                address:
+                - ip: 192.0.2.1
+                  prefix-length: 24
+              ipv6:
+                enabled: false
+            routes:
+              config:
+              - destination: 198.51.100.0/24
+                next-hop-interface: eth1
+                next-hop-address: 192.0.2.2
+                metric: 100
+              - destination: 203.0.113.0/24
+                next-hop-interface: eth1
+                metric: 200",
+        );
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, "eth1.nmconnection");
+        assert!(files[0].1.contains(
+            "\n[ipv4]\n\
+            method=manual\n\
+            address1=192.0.2.1/24\n\
+            route1=198.51.100.0/24,192.0.2.2,100\n\
+            route2=203.0.113.0/24,,200\n"
+        ));
+        assert!(files[0].1.contains("\n[ipv6]\nmethod=disabled\n"));
+    }
+
+    #[test]
+    fn test_gen_conf_ipv6_method() {
+        for (dhcp, autoconf, method) in [
+            (false, true, "auto"),
+            (true, true, "auto"),
+            (true, false, "dhcp"),
+            (false, false, "link-local"),
+        ] {
+            let files = gen_keyfiles(&format!(
+                r"---
+                interfaces:
+                - name: eth1
+                  type: ethernet
+                  state: up
+                  ipv6:
+                    enabled: true
+                    dhcp: {dhcp}
+                    autoconf: {autoconf}"
+            ));
+
+            assert!(files[0]
+                .1
+                .contains(&format!("\n[ipv6]\nmethod={method}\n")));
+        }
+    }
+
+    #[test]
+    fn test_gen_conf_bond_options() {
+        let files = gen_keyfiles(
+            r"---
+            interfaces:
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: active-backup
+                options:
+                  miimon: 100
+                  updelay: 200
+                port: []",
+        );
+
+        assert!(files[0].1.contains(
+            "\n[bond]\n\
+            mode=active-backup\n\
+            miimon=100\n\
+            updelay=200\n"
+        ));
+    }
+
+    #[test]
+    fn test_gen_conf_bridge_options() {
+        let files = gen_keyfiles(
+            r"---
+            interfaces:
+            - name: br0
+              type: linux-bridge
+              state: up
+              bridge:
+                options:
+                  mac-ageing-time: 300
+                  multicast-snooping: true
+                  stp:
+                    enabled: false
+                port: []",
+        );
+        let content = files[0].1.as_str();
+
+        assert!(content.contains("\n[bridge]\n"));
+        assert!(content.contains("\nstp=false\n"));
+        assert!(content.contains("\nageing-time=300\n"));
+        assert!(content.contains("\nmulticast-snooping=true\n"));
+    }
+
+    #[test]
+    fn test_gen_conf_reject_profile_name_escaping_folder() {
+        for profile_name in ["../../../etc/cron.d/x", "eth1\nuuid=x"] {
+            let mut state = NetworkState::new_from_yaml(
+                r"---
+                interfaces:
+                - name: eth1
+                  type: ethernet
+                  state: up",
+            )
+            .unwrap();
+            state
+                .interfaces
+                .kernel_ifaces
+                .get_mut("eth1")
+                .unwrap()
+                .base_iface_mut()
+                .profile_name = Some(profile_name.to_string());
+
+            let result = state.gen_conf();
+            assert!(result.is_err());
+            if let Err(e) = result {
+                assert_eq!(e.kind, ErrorKind::InvalidArgument);
+            }
+        }
+    }
+
+    #[test]
+    fn test_gen_conf_custom_profile_name() {
+        let files = gen_keyfiles(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              profile-name: uplink-backup",
+        );
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, "uplink-backup.nmconnection");
+        assert!(files[0]
+            .1
+            .starts_with("[connection]\nid=uplink-backup\nuuid="));
+        assert!(files[0].1.contains("\ninterface-name=eth1\n"));
+    }
+}
@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ErrorKind, Interfaces, MergedInterfaces, NipartError};
+
+// Unlike nmstate, nipart does not remove virtual interface with `state: down`
+// but only set its link to administrative down. The nmstate
+// `MergedInterfaces::verify()` expects virtual down interface been removed,
+// hence we verify down interfaces here and hide them from it.
+impl MergedInterfaces {
+    // Down interface should not be found in up state.
+    pub(crate) fn verify_down_ifaces(
+        &self,
+        current: &Interfaces,
+    ) -> Result<(), NipartError> {
+        for des_iface in self
+            .iter()
+            .filter(|i| i.is_desired())
+            .filter_map(|i| i.for_verify.as_ref())
+            .filter(|i| i.is_down())
+        {
+            if let Some(cur_iface) =
+                current.get_iface(des_iface.name(), des_iface.iface_type())
+            {
+                if cur_iface.is_up() {
+                    let e = NipartError::new(
+                        ErrorKind::VerificationError,
+                        format!(
+                            "Down interface {}/{} still found in up state",
+                            des_iface.name(),
+                            des_iface.iface_type(),
+                        ),
+                    );
+                    log::error!("{}", e);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn without_down_ifaces_for_verify(&self) -> Self {
+        let mut ret = self.clone();
+        for merged_iface in ret.iter_mut() {
+            if merged_iface.for_verify.as_ref().map(|i| i.is_down())
+                == Some(true)
+            {
+                merged_iface.for_verify = None;
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, InterfaceType, MergedNetworkState, NetworkState};
+
+    const IFACE_CONFS: [(&str, &str); 5] = [
+        ("dummy1", "type: dummy"),
+        ("eth1", "type: ethernet"),
+        (
+            "br0",
+            "type: linux-bridge
+              bridge:
+                port: []",
+        ),
+        (
+            "bond0",
+            "type: bond
+              link-aggregation:
+                mode: balance-rr
+                port: []",
+        ),
+        (
+            "eth2.10",
+            "type: vlan
+              vlan:
+                base-iface: eth2
+                id: 10",
+        ),
+    ];
+
+    fn gen_state(iface_name: &str, conf: &str, state: &str) -> NetworkState {
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: eth2
+              type: ethernet
+              state: up
+            - name: {iface_name}
+              state: {state}
+              {conf}"
+        ))
+        .unwrap()
+    }
+
+    fn gen_desired(iface_name: &str, conf: &str) -> NetworkState {
+        let mut desired = gen_state(iface_name, conf, "down");
+        desired
+            .interfaces
+            .remove_iface("eth2", InterfaceType::Ethernet);
+        desired
+    }
+
+    #[test]
+    fn test_verify_down_iface_found_down() {
+        for (iface_name, conf) in IFACE_CONFS {
+            let desired = gen_desired(iface_name, conf);
+            let current = gen_state(iface_name, conf, "up");
+            let merged =
+                MergedNetworkState::new(desired, current, false, false)
+                    .unwrap();
+
+            // Virtual interface should be kept instead of removed
+            let new_current = gen_state(iface_name, conf, "down");
+            merged.verify(&new_current).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_verify_down_iface_still_up() {
+        for (iface_name, conf) in IFACE_CONFS {
+            let desired = gen_desired(iface_name, conf);
+            let current = gen_state(iface_name, conf, "up");
+            let merged =
+                MergedNetworkState::new(desired, current.clone(), false, false)
+                    .unwrap();
+
+            let result = merged.verify(&current);
+            assert!(result.is_err(), "{iface_name} should fail verification");
+            if let Err(e) = result {
+                assert_eq!(e.kind, ErrorKind::VerificationError);
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ErrorKind, NetworkState, NipartError};
+
+// Each round removes at least one unknown property, this is the safe guard
+// for a bug causing infinite loop.
+const LENIENT_MAX_ROUNDS: usize = 1024;
+
+impl NetworkState {
+    /// Similar to [NetworkState::new_from_yaml()], but unknown properties
+    /// are removed with warning message instead of failing the
+    /// deserialization. Useful when state is produced by newer version.
+    /// Return the deserialized [NetworkState] and the list of warnings.
+    pub fn new_from_yaml_lenient(
+        net_state_yaml: &str,
+    ) -> Result<(Self, Vec<String>), NipartError> {
+        let value: serde_json::Value = serde_yaml::from_str(net_state_yaml)
+            .map_err(|e| {
+                NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!("Invalid YAML string: {e}"),
+                )
+            })?;
+        Self::from_value_lenient(value)
+    }
+
+    /// Similar to [NetworkState::new_from_json()], but unknown properties
+    /// are removed with warning message instead of failing the
+    /// deserialization.
+    /// Return the deserialized [NetworkState] and the list of warnings.
+    pub fn new_from_json_lenient(
+        net_state_json: &str,
+    ) -> Result<(Self, Vec<String>), NipartError> {
+        let value: serde_json::Value = serde_json::from_str(net_state_json)
+            .map_err(|e| {
+                NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!("Invalid JSON string: {e}"),
+                )
+            })?;
+        Self::from_value_lenient(value)
+    }
+
+    // The serde error does not include the path of unknown field, hence
+    // we search all properties with the same name and remove the one which
+    // is causing the failure.
+    fn from_value_lenient(
+        mut value: serde_json::Value,
+    ) -> Result<(Self, Vec<String>), NipartError> {
+        let mut warnings: Vec<String> = Vec::new();
+        for _ in 0..LENIENT_MAX_ROUNDS {
+            let err = match serde_json::from_value::<Self>(value.clone()) {
+                Ok(state) => return Ok((state, warnings)),
+                Err(e) => e.to_string(),
+            };
+            let Some(field) = parse_unknown_field(err.as_str()) else {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!("Invalid network state: {err}"),
+                ));
+            };
+            let mut paths = Vec::new();
+            search_key(&value, field, &mut Vec::new(), &mut paths);
+
+            // Prefer the property whose removal changes the error, fallback
+            // to remove all the properties with this name.
+            let culprit = paths.iter().find(|path| {
+                let mut new_value = value.clone();
+                remove_by_path(&mut new_value, path);
+                match serde_json::from_value::<Self>(new_value) {
+                    Ok(_) => true,
+                    Err(e) => e.to_string() != err,
+                }
+            });
+            let to_remove = match culprit.cloned() {
+                Some(path) => vec![path],
+                None if !paths.is_empty() => paths,
+                None => {
+                    return Err(NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!("Invalid network state: {err}"),
+                    ));
+                }
+            };
+            for path in to_remove {
+                let msg =
+                    format!("Ignoring unknown property {}", path.join("."));
+                log::warn!("{msg}");
+                remove_by_path(&mut value, path.as_slice());
+                warnings.push(msg);
+            }
+        }
+        Err(NipartError::new(
+            ErrorKind::Bug,
+            format!(
+                "Still failed to deserialize network state after removing \
+                {LENIENT_MAX_ROUNDS} unknown properties"
+            ),
+        ))
+    }
+}
+
+// Extract `foo` from "unknown field `foo`, expected ..."
+fn parse_unknown_field(err: &str) -> Option<&str> {
+    const PREFIX: &str = "unknown field `";
+    let rest = &err[err.find(PREFIX)? + PREFIX.len()..];
+    rest.find('`').map(|end| &rest[..end])
+}
+
+// Array index is also treated as key of path.
+fn search_key(
+    value: &serde_json::Value,
+    key: &str,
+    prefix: &mut Vec<String>,
+    paths: &mut Vec<Vec<String>>,
+) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map.iter() {
+                prefix.push(k.to_string());
+                if k == key {
+                    paths.push(prefix.clone());
+                }
+                search_key(v, key, prefix, paths);
+                prefix.pop();
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                prefix.push(i.to_string());
+                search_key(v, key, prefix, paths);
+                prefix.pop();
+            }
+        }
+        _ => (),
+    }
+}
+
+fn remove_by_path(value: &mut serde_json::Value, path: &[String]) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut cur = value;
+    for key in parents {
+        let next = match cur {
+            serde_json::Value::Object(map) => map.get_mut(key),
+            serde_json::Value::Array(items) => {
+                key.parse::<usize>().ok().and_then(|i| items.get_mut(i))
+            }
+            _ => None,
+        };
+        match next {
+            Some(v) => cur = v,
+            None => return,
+        }
+    }
+    if let serde_json::Value::Object(map) = cur {
+        map.remove(last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::NetworkState;
+
+    const FUTURE_STATE: &str = r"---
+        future-section:
+          foo: bar
+        routes:
+          config:
+          - destination: 198.51.100.0/24
+            next-hop-interface: eth1
+            future-route-option: 1";
+
+    #[test]
+    fn test_lenient_ignore_future_fields() {
+        assert!(NetworkState::new_from_yaml(FUTURE_STATE).is_err());
+
+        let (state, warnings) =
+            NetworkState::new_from_yaml_lenient(FUTURE_STATE).unwrap();
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings
+            .contains(&"Ignoring unknown property future-section".to_string()));
+        assert!(warnings.contains(
+            &"Ignoring unknown property routes.config.0.future-route-option"
+                .to_string()
+        ));
+        let routes = state.routes.config.as_ref().unwrap();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].next_hop_iface.as_deref(), Some("eth1"));
+    }
+
+    #[test]
+    fn test_lenient_no_warning_for_valid_state() {
+        let (state, warnings) = NetworkState::new_from_json_lenient(
+            r#"{"routes": {"config": [{"destination": "198.51.100.0/24",
+            "next-hop-interface": "eth1"}]}}"#,
+        )
+        .unwrap();
+
+        assert!(warnings.is_empty());
+        assert!(!state.routes.is_empty());
+    }
+
+    #[test]
+    fn test_lenient_still_fail_on_invalid_value() {
+        assert!(NetworkState::new_from_yaml_lenient(
+            r"---
+            routes:
+              config:
+              - destination: 198.51.100.0/24
+                table-id: not-a-number",
+        )
+        .is_err());
+    }
+}
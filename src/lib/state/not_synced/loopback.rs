@@ -0,0 +1,191 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::{
+    ErrorKind, Interface, InterfaceIpAddr, LoopbackInterface,
+    MergedNetworkState, NipartError,
+};
+
+impl MergedNetworkState {
+    // Loopback interface cannot be set to down. Extra addresses(e.g. anycast
+    // `/32` or `/128` addresses) are appended to the default `127.0.0.1/8` and
+    // `::1/128` instead of replacing them.
+    pub(crate) fn process_loopback(&mut self) -> Result<(), NipartError> {
+        for merged_iface in self
+            .interfaces
+            .kernel_ifaces
+            .values_mut()
+            .filter(|i| i.is_desired())
+        {
+            if !matches!(merged_iface.merged, Interface::Loopback(_)) {
+                continue;
+            }
+            if merged_iface.desired.as_ref().map(|i| i.is_down()) == Some(true)
+            {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    "Loopback interface cannot be set to down".to_string(),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+            for iface in [
+                merged_iface.for_apply.as_mut(),
+                merged_iface.for_verify.as_mut(),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                if let Interface::Loopback(lo_iface) = iface {
+                    lo_iface.include_default_ip();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl LoopbackInterface {
+    // Append `127.0.0.1/8` and `::1/128` to explicitly defined static IP
+    // address list.
+    pub(crate) fn include_default_ip(&mut self) {
+        if let Some(addrs) =
+            self.base.ipv4.as_mut().and_then(|i| i.addresses.as_mut())
+        {
+            include_addr(
+                &self.base.name,
+                addrs,
+                InterfaceIpAddr {
+                    ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                    prefix_length: 8,
+                    ..Default::default()
+                },
+            );
+        }
+        if let Some(addrs) =
+            self.base.ipv6.as_mut().and_then(|i| i.addresses.as_mut())
+        {
+            include_addr(
+                &self.base.name,
+                addrs,
+                InterfaceIpAddr {
+                    ip: IpAddr::V6(Ipv6Addr::LOCALHOST),
+                    prefix_length: 128,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
+
+fn include_addr(
+    iface_name: &str,
+    addrs: &mut Vec<InterfaceIpAddr>,
+    default_addr: InterfaceIpAddr,
+) {
+    if !addrs.contains(&default_addr) {
+        log::info!(
+            "Including default address {default_addr} to loopback \
+            interface {iface_name}"
+        );
+        addrs.insert(0, default_addr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+    use std::str::FromStr;
+
+    use crate::{ErrorKind, Interface, MergedNetworkState, NetworkState};
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: lo
+              type: loopback
+              state: up
+              ipv4:
+                enabled: true
+                address:
+                - ip: 127.0.0.1
+                  prefix-length: 8
+              ipv6:
+                enabled: true
+                address:
+                - ip: ::1
+                  prefix-length: 128",
+        )
+        .unwrap()
+    }
+
+    fn get_addrs(iface: Option<&Interface>) -> Vec<(IpAddr, u8)> {
+        let base = iface.unwrap().base_iface();
+        base.ipv4
+            .as_ref()
+            .and_then(|i| i.addresses.as_ref())
+            .into_iter()
+            .chain(base.ipv6.as_ref().and_then(|i| i.addresses.as_ref()))
+            .flatten()
+            .map(|a| (a.ip, a.prefix_length))
+            .collect()
+    }
+
+    #[test]
+    fn test_loopback_anycast_addr_keep_default() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: lo
+              type: loopback
+              state: up
+              ipv4:
+                enabled: true
+                address:
+                - ip: 192.0.2.1
+                  prefix-length: 32
+              ipv6:
+                enabled: true
+                address:
+                - ip: 2001:db8::1
+                  prefix-length: 128",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+        let iface = &merged.interfaces.kernel_ifaces["lo"];
+
+        let expected = vec![
+            (IpAddr::from_str("127.0.0.1").unwrap(), 8),
+            (IpAddr::from_str("192.0.2.1").unwrap(), 32),
+            (IpAddr::from_str("::1").unwrap(), 128),
+            (IpAddr::from_str("2001:db8::1").unwrap(), 128),
+        ];
+        assert_eq!(get_addrs(iface.for_apply.as_ref()), expected);
+        assert_eq!(get_addrs(iface.for_verify.as_ref()), expected);
+    }
+
+    #[test]
+    fn test_loopback_down_not_allowed() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: lo
+              type: loopback
+              state: down",
+        )
+        .unwrap();
+
+        let result =
+            MergedNetworkState::new(desired, gen_current(), false, false);
+
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::InvalidArgument);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Interface;
+
+// Virtual interfaces like bond, VLAN and bridge might share the same MAC
+// address with the physical NIC they are built on. When multiple interfaces
+// hold the MAC address of `identifier: mac-address`, prefer:
+//  * Non-virtual interfaces.
+//  * Interface with the same name as desired.
+// Caller should treat remaining multiple matches as ambiguous.
+pub(crate) fn prefer_mac_identifier_matches<'a>(
+    desired_name: &str,
+    matches: Vec<&'a Interface>,
+) -> Vec<&'a Interface> {
+    if matches.len() <= 1 {
+        return matches;
+    }
+    let non_virtual: Vec<&Interface> = matches
+        .iter()
+        .copied()
+        .filter(|i| !i.is_virtual())
+        .collect();
+    let matches = if non_virtual.is_empty() {
+        matches
+    } else {
+        non_virtual
+    };
+    if matches.len() <= 1 {
+        return matches;
+    }
+    let same_name: Vec<&Interface> = matches
+        .iter()
+        .copied()
+        .filter(|i| i.name() == desired_name)
+        .collect();
+    if same_name.is_empty() {
+        matches
+    } else {
+        same_name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, MergedNetworkState, NetworkState};
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: ens3
+              type: ethernet
+              state: up
+              mac-address: 00:23:45:67:89:1A
+            - name: bond99
+              type: bond
+              state: up
+              mac-address: 00:23:45:67:89:1A
+              link-aggregation:
+                mode: balance-rr
+                port:
+                - ens3
+            - name: dummy1
+              type: dummy
+              state: up
+              mac-address: 00:23:45:67:89:1B
+            - name: dummy2
+              type: dummy
+              state: up
+              mac-address: 00:23:45:67:89:1B",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mac_identifier_with_different_kernel_name() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: wan0
+              type: ethernet
+              state: up
+              identifier: mac-address
+              mac-address: 00:23:45:67:89:1a
+              mtu: 9000",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        assert!(!merged.interfaces.kernel_ifaces.contains_key("wan0"));
+        let iface = &merged.interfaces.kernel_ifaces["ens3"];
+        let apply_iface = iface.for_apply.as_ref().unwrap();
+        assert_eq!(apply_iface.base_iface().mtu, Some(9000));
+        assert_eq!(
+            apply_iface.base_iface().profile_name.as_deref(),
+            Some("wan0")
+        );
+    }
+
+    #[test]
+    fn test_mac_identifier_prefer_non_virtual_iface() {
+        // Bond is sharing MAC address with its port
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: wan0
+              type: unknown
+              state: up
+              identifier: mac-address
+              mac-address: 00:23:45:67:89:1A
+              mtu: 9000",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        let iface = &merged.interfaces.kernel_ifaces["ens3"];
+        assert_eq!(
+            iface.for_apply.as_ref().and_then(|i| i.base_iface().mtu),
+            Some(9000)
+        );
+        assert!(!merged.interfaces.kernel_ifaces["bond99"].is_changed());
+    }
+
+    #[test]
+    fn test_mac_identifier_prefer_same_name() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy2
+              type: dummy
+              state: up
+              identifier: mac-address
+              mac-address: 00:23:45:67:89:1B
+              mtu: 9000",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        assert!(merged.interfaces.kernel_ifaces["dummy2"].is_changed());
+        assert!(!merged.interfaces.kernel_ifaces["dummy1"].is_changed());
+    }
+
+    #[test]
+    fn test_mac_identifier_ambiguous() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy9
+              type: dummy
+              state: up
+              identifier: mac-address
+              mac-address: 00:23:45:67:89:1B",
+        )
+        .unwrap();
+
+        let result =
+            MergedNetworkState::new(desired, gen_current(), false, false);
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidArgument);
+    }
+}
@@ -1,22 +1,188 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    HostNameState, MergedInterface, MergedNetworkState, NetworkState,
-    NipartDhcpConfig, NipartDhcpConfigV4, NipartDhcpConfigV6, NipartError,
+    state::not_synced::log_warn, HostNameState, Interface, InterfaceType,
+    MergedInterface, MergedNetworkState, NetworkState, NipartDhcpConfig,
+    NipartDhcpConfigV4, NipartDhcpConfigV6, NipartError,
 };
 
+/// Conflicting value found when merging network states with different
+/// priorities, the value from higher priority state wins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct NetworkStateConflict {
+    /// Reference path to the conflicting property, for example:
+    /// `interfaces.eth1.mtu`
+    pub path: String,
+    /// Priority of the overridden state
+    pub low_priority: u32,
+    /// Overridden value
+    pub low_value: serde_json::Value,
+    /// Priority of the winning state
+    pub high_priority: u32,
+    /// Winning value
+    pub high_value: serde_json::Value,
+}
+
+impl std::fmt::Display for NetworkStateConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} from priority {} overridden by {} from priority {}",
+            self.path,
+            self.low_value,
+            self.low_priority,
+            self.high_value,
+            self.high_priority
+        )
+    }
+}
+
 impl NetworkState {
-    pub fn merge_states(mut states: Vec<(NetworkState, u32)>) -> Self {
+    pub fn merge_states(states: Vec<(NetworkState, u32)>) -> Self {
+        Self::merge_states_with_conflicts(states).0
+    }
+
+    /// Merge desired states in order, later state overrides earlier one.
+    /// Useful for layering a base config with host-specific overrides.
+    /// Merge precedence:
+    ///  * Interfaces are merged by name and type, properties defined in later
+    ///    state override earlier ones.
+    ///  * Route and route rule entries are appended in order.
+    ///  * Other sections like DNS are overridden by later state as a whole.
+    pub fn merge_desired_states(states: &[NetworkState]) -> Self {
+        let mut ret = Self::merge_states(
+            states
+                .iter()
+                .enumerate()
+                .map(|(i, s)| (s.clone(), i as u32))
+                .collect(),
+        );
+        for state in states {
+            if let Some(routes) = state.routes.config.as_ref() {
+                ret.routes
+                    .config
+                    .get_or_insert_with(Vec::new)
+                    .extend_from_slice(routes);
+            }
+            if let Some(rules) = state.rules.config.as_ref() {
+                ret.rules
+                    .config
+                    .get_or_insert_with(Vec::new)
+                    .extend_from_slice(rules);
+            }
+        }
+        ret
+    }
+
+    /// Same as [NetworkState::merge_states()] but also return the conflicting
+    /// interface properties found during merging.
+    pub fn merge_states_with_conflicts(
+        mut states: Vec<(NetworkState, u32)>,
+    ) -> (Self, Vec<NetworkStateConflict>) {
         states.sort_unstable_by_key(|s| s.1);
         let mut ret = Self::default();
+        let mut conflicts = Vec::new();
+        // Priority of the state which updated the interface last time
+        let mut iface_priorities: HashMap<(String, InterfaceType), u32> =
+            HashMap::new();
         for state in states {
             log::trace!(
                 "Merging {state:?} into {ret:?} with priority {}",
                 state.1
             );
+            for iface in state.0.interfaces.iter() {
+                let key = (iface.name().to_string(), iface.iface_type());
+                if let (Some(old_iface), Some(old_priority)) = (
+                    ret.interfaces.get_iface(iface.name(), iface.iface_type()),
+                    iface_priorities.get(&key),
+                ) {
+                    conflicts.extend(get_iface_conflicts(
+                        old_iface,
+                        *old_priority,
+                        iface,
+                        state.1,
+                    ));
+                }
+                iface_priorities.insert(key, state.1);
+            }
             ret.update_state(&state.0)
         }
-        ret
+        for conflict in conflicts.as_slice() {
+            log_warn(format!(
+                "Conflict found when merging network states: {conflict}"
+            ));
+        }
+        (ret, conflicts)
+    }
+}
+
+fn get_iface_conflicts(
+    old_iface: &Interface,
+    old_priority: u32,
+    new_iface: &Interface,
+    new_priority: u32,
+) -> Vec<NetworkStateConflict> {
+    let mut ret = Vec::new();
+    if old_priority == new_priority {
+        return ret;
+    }
+    let (Ok(old_value), Ok(new_value)) = (
+        serde_json::to_value(old_iface),
+        serde_json::to_value(new_iface),
+    ) else {
+        return ret;
+    };
+    let mut diffs = Vec::new();
+    get_json_value_conflicts(
+        format!("interfaces.{}", new_iface.name()).as_str(),
+        &old_value,
+        &new_value,
+        &mut diffs,
+    );
+    for (path, low_value, high_value) in diffs {
+        ret.push(NetworkStateConflict {
+            path,
+            low_priority: old_priority,
+            low_value,
+            high_priority: new_priority,
+            high_value,
+        });
+    }
+    ret
+}
+
+// Only properties defined in both values are checked, arrays are compared
+// as a whole.
+fn get_json_value_conflicts(
+    path: &str,
+    old: &serde_json::Value,
+    new: &serde_json::Value,
+    diffs: &mut Vec<(String, serde_json::Value, serde_json::Value)>,
+) {
+    match (old, new) {
+        (serde_json::Value::Object(old), serde_json::Value::Object(new)) => {
+            for (key, new_value) in new.iter() {
+                if let Some(old_value) = old.get(key) {
+                    get_json_value_conflicts(
+                        format!("{path}.{key}").as_str(),
+                        old_value,
+                        new_value,
+                        diffs,
+                    );
+                }
+            }
+        }
+        (serde_json::Value::Null, _) | (_, serde_json::Value::Null) => (),
+        _ => {
+            if old != new {
+                diffs.push((path.to_string(), old.clone(), new.clone()));
+            }
+        }
     }
 }
 
@@ -46,20 +212,40 @@ impl NetworkState {
 }
 
 impl MergedNetworkState {
-    pub fn get_dhcp_changes(&self) -> Vec<NipartDhcpConfig> {
+    /// Generate DHCP configurations for DHCP plugin.
+    /// When `release_removed` is true, interfaces marked as absent with DHCP
+    /// enabled in current network state will have DHCP disabled, so that
+    /// DHCP plugin stops the DHCP client and drops its lease. Set to false
+    /// to keep the DHCP client for fast re-adding of the interface.
+    pub fn get_dhcp_changes(
+        &self,
+        release_removed: bool,
+    ) -> Vec<NipartDhcpConfig> {
         let mut ret: Vec<NipartDhcpConfig> = Vec::new();
-        for iface in self
-            .interfaces
-            .kernel_ifaces
-            .values()
-            .filter_map(|i| i.for_apply.as_ref())
-        {
+        for merged_iface in self.interfaces.kernel_ifaces.values() {
+            let Some(iface) = merged_iface.for_apply.as_ref() else {
+                continue;
+            };
+            if iface.is_absent() {
+                if release_removed {
+                    ret.extend(get_dhcp_releases(merged_iface));
+                }
+                continue;
+            }
             if iface.base_iface().can_have_ip() {
                 if let Some(ipv4) = iface.base_iface().ipv4.as_ref() {
-                    let dhcp_conf = NipartDhcpConfigV4::new(
+                    let mut dhcp_conf = NipartDhcpConfigV4::new(
                         iface.name().to_string(),
                         ipv4.enabled && ipv4.dhcp == Some(true),
                     );
+                    // Explicit route metric takes precedence over
+                    // autoconnect-priority
+                    dhcp_conf.route_metric =
+                        ipv4.auto_route_metric.or_else(|| {
+                            iface.base_iface().autoconnect_priority.map(
+                                NipartDhcpConfigV4::route_metric_from_priority,
+                            )
+                        });
                     if ipv4.dhcp_client_id.as_ref().is_some() {
                         todo!()
                     }
@@ -95,7 +281,11 @@ impl MergedNetworkState {
 
     pub fn verify(&self, current: &NetworkState) -> Result<(), NipartError> {
         self.hostname.verify(current.hostname.as_ref())?;
-        self.interfaces.verify(&current.interfaces)?;
+        self.interfaces.verify_down_ifaces(&current.interfaces)?;
+        self.interfaces
+            .without_down_ifaces_for_verify()
+            .verify(&current.interfaces)?;
+        self.verify_wait_ip(current)?;
         let ignored_kernel_ifaces: Vec<&str> = self
             .interfaces
             .ignored_ifaces
@@ -113,8 +303,225 @@ impl MergedNetworkState {
             .verify(&current.rules, ignored_kernel_ifaces.as_slice())?;
         self.dns.verify(current.dns.clone().unwrap_or_default())?;
         self.ovsdb
-            .verify(current.ovsdb.clone().unwrap_or_default())?;
+            .verify_normalized(current.ovsdb.clone().unwrap_or_default())?;
         self.ovn.verify(&current.ovn)?;
         Ok(())
     }
 }
+
+// DHCP configurations disabling DHCP of removed interface which has DHCP
+// enabled in current network state.
+fn get_dhcp_releases(merged_iface: &MergedInterface) -> Vec<NipartDhcpConfig> {
+    let mut ret = Vec::new();
+    let Some(cur_iface) = merged_iface.current.as_ref() else {
+        return ret;
+    };
+    let iface_name = cur_iface.name().to_string();
+    if cur_iface.base_iface().ipv4.as_ref().and_then(|i| i.dhcp) == Some(true) {
+        log::debug!("Releasing DHCPv4 lease of removed interface {iface_name}");
+        ret.push(NipartDhcpConfig::V4(NipartDhcpConfigV4::new(
+            iface_name.clone(),
+            false,
+        )));
+    }
+    if cur_iface.base_iface().ipv6.as_ref().and_then(|i| i.dhcp) == Some(true) {
+        log::debug!("Releasing DHCPv6 lease of removed interface {iface_name}");
+        ret.push(NipartDhcpConfig::V6(NipartDhcpConfigV6::new(
+            iface_name, false,
+        )));
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MergedNetworkState, NetworkState, NipartDhcpConfig};
+
+    fn gen_state(mtu: u64) -> NetworkState {
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              mtu: {mtu}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_merge_states_mtu_conflict() {
+        // Higher priority state placed first to confirm sorting
+        let (merged, conflicts) =
+            NetworkState::merge_states_with_conflicts(vec![
+                (gen_state(9000), 20),
+                (gen_state(1500), 10),
+            ]);
+
+        assert_eq!(
+            merged
+                .interfaces
+                .kernel_ifaces
+                .get("eth1")
+                .and_then(|i| i.base_iface().mtu),
+            Some(9000)
+        );
+        assert_eq!(conflicts.len(), 1);
+        let conflict = &conflicts[0];
+        assert_eq!(conflict.path, "interfaces.eth1.mtu");
+        assert_eq!(conflict.low_priority, 10);
+        assert_eq!(conflict.low_value, serde_json::json!(1500));
+        assert_eq!(conflict.high_priority, 20);
+        assert_eq!(conflict.high_value, serde_json::json!(9000));
+    }
+
+    #[test]
+    fn test_merge_states_same_mtu_no_conflict() {
+        let (merged, conflicts) =
+            NetworkState::merge_states_with_conflicts(vec![
+                (gen_state(1500), 10),
+                (gen_state(1500), 20),
+            ]);
+
+        assert_eq!(
+            merged
+                .interfaces
+                .kernel_ifaces
+                .get("eth1")
+                .and_then(|i| i.base_iface().mtu),
+            Some(1500)
+        );
+        assert!(conflicts.is_empty());
+    }
+
+    fn get_dhcp_route_metric(
+        dhcp_confs: &[NipartDhcpConfig],
+        iface_name: &str,
+    ) -> Option<u32> {
+        dhcp_confs.iter().find_map(|c| match c {
+            NipartDhcpConfig::V4(c) if c.iface == iface_name => c.route_metric,
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_dhcp_route_metric_ordered_by_autoconnect_priority() {
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+            - name: eth2
+              type: ethernet
+              state: up",
+        )
+        .unwrap();
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              autoconnect-priority: 10
+              ipv4:
+                enabled: true
+                dhcp: true
+            - name: eth2
+              type: ethernet
+              state: up
+              autoconnect-priority: 20
+              ipv4:
+                enabled: true
+                dhcp: true",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, current, false, false).unwrap();
+        let dhcp_confs = merged.get_dhcp_changes(false);
+
+        let eth1_metric = get_dhcp_route_metric(&dhcp_confs, "eth1").unwrap();
+        let eth2_metric = get_dhcp_route_metric(&dhcp_confs, "eth2").unwrap();
+        assert!(eth2_metric < eth1_metric);
+    }
+
+    #[test]
+    fn test_dhcp_explicit_route_metric_override_autoconnect_priority() {
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up",
+        )
+        .unwrap();
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              autoconnect-priority: 10
+              ipv4:
+                enabled: true
+                dhcp: true
+                auto-route-metric: 500",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, current, false, false).unwrap();
+
+        assert_eq!(
+            get_dhcp_route_metric(&merged.get_dhcp_changes(false), "eth1"),
+            Some(500)
+        );
+    }
+
+    #[test]
+    fn test_merge_desired_states_override_mtu() {
+        let base = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              mtu: 1500
+            routes:
+              config:
+              - destination: 198.51.100.0/24
+                next-hop-interface: eth1",
+        )
+        .unwrap();
+        let override_state = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              mtu: 9000
+            routes:
+              config:
+              - destination: 203.0.113.0/24
+                next-hop-interface: eth1",
+        )
+        .unwrap();
+
+        let desired =
+            NetworkState::merge_desired_states(&[base, override_state]);
+
+        assert_eq!(desired.routes.config.as_ref().map(|r| r.len()), Some(2));
+
+        let merged =
+            MergedNetworkState::new(desired, gen_state(1500), false, false)
+                .unwrap();
+
+        assert_eq!(
+            merged.interfaces.kernel_ifaces["eth1"]
+                .for_apply
+                .as_ref()
+                .and_then(|i| i.base_iface().mtu),
+            Some(9000)
+        );
+    }
+}
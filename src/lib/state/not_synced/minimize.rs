@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{BaseInterface, Interface, InterfaceIpAddr, NetworkState};
+
+impl NetworkState {
+    /// Strip query-only properties and properties equal to their kernel
+    /// defaults, producing the smallest desired state reproducing the
+    /// configuration of queried state. Complements [NetworkState::gen_diff].
+    ///
+    /// Properties whose undefined value means "preserve current" rather than
+    /// the default are kept, e.g. `ipv4: {enabled: false}`.
+    pub fn minimize(&self) -> Self {
+        let mut ret = self.clone();
+        if let Some(hostname) = ret.hostname.as_mut() {
+            hostname.running = None;
+            if hostname.config.is_none() {
+                ret.hostname = None;
+            }
+        }
+        if let Some(dns) = ret.dns.as_mut() {
+            dns.running = None;
+            if dns.is_empty() {
+                ret.dns = None;
+            }
+        }
+        ret.routes.running = None;
+        for iface in ret.interfaces.iter_mut() {
+            iface.base_iface_mut().minimize();
+            if let Interface::Bond(bond_iface) = iface {
+                if let Some(bond_conf) = bond_iface.bond.as_mut() {
+                    bond_conf.ad_info = None;
+                }
+            }
+            if let Interface::Wireguard(wg_iface) = iface {
+                if let Some(wg_conf) = wg_iface.wireguard.as_mut() {
+                    wg_conf.public_key = None;
+                }
+            }
+        }
+        ret
+    }
+}
+
+impl BaseInterface {
+    fn minimize(&mut self) {
+        self.driver = None;
+        self.permanent_mac_address = None;
+        self.min_mtu = None;
+        self.max_mtu = None;
+        // Kernel default
+        if self.accept_all_mac_addresses == Some(false) {
+            self.accept_all_mac_addresses = None;
+        }
+        if let Some(lldp_conf) = self.lldp.as_mut() {
+            lldp_conf.neighbors.clear();
+            if !lldp_conf.enabled {
+                self.lldp = None;
+            }
+        }
+        if let Some(ring) = self.ethtool.as_mut().and_then(|e| e.ring.as_mut())
+        {
+            ring.rx_max = None;
+            ring.rx_jumbo_max = None;
+            ring.rx_mini_max = None;
+            ring.tx_max = None;
+        }
+        if let Some(fec) = self.ethtool.as_mut().and_then(|e| e.fec.as_mut()) {
+            fec.supported = None;
+        }
+        if let Some(channels) =
+            self.ethtool.as_mut().and_then(|e| e.channels.as_mut())
+        {
+            channels.rx_max = None;
+            channels.tx_max = None;
+            channels.other_max = None;
+            channels.combined_max = None;
+        }
+        // Addresses from DHCP or IPv6 autoconf will be regained after apply
+        if let Some(ipv4_conf) = self.ipv4.as_mut() {
+            if ipv4_conf.dhcp == Some(true) {
+                ipv4_conf.addresses = remove_auto_addrs(
+                    ipv4_conf.addresses.as_deref().unwrap_or_default(),
+                );
+            }
+        }
+        if let Some(ipv6_conf) = self.ipv6.as_mut() {
+            if ipv6_conf.dhcp == Some(true) || ipv6_conf.autoconf == Some(true)
+            {
+                ipv6_conf.addresses = remove_auto_addrs(
+                    ipv6_conf.addresses.as_deref().unwrap_or_default(),
+                );
+            }
+        }
+    }
+}
+
+fn remove_auto_addrs(
+    addrs: &[InterfaceIpAddr],
+) -> Option<Vec<InterfaceIpAddr>> {
+    let addrs: Vec<InterfaceIpAddr> =
+        addrs.iter().filter(|a| !a.is_auto()).cloned().collect();
+    if addrs.is_empty() {
+        None
+    } else {
+        Some(addrs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MergedNetworkState, NetworkState};
+
+    fn gen_queried_state() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            dns-resolver:
+              running:
+                server:
+                - 192.0.2.1
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              driver: dummy
+              mtu: 1500
+              min-mtu: 0
+              max-mtu: 65535
+              accept-all-mac-addresses: false
+              ipv4:
+                enabled: true
+                address:
+                - ip: 192.0.2.2
+                  prefix-length: 24
+              ipv6:
+                enabled: false",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_minimize_strip_query_only_and_default() {
+        let state = gen_queried_state().minimize();
+
+        assert!(state.dns.is_none());
+        let iface = &state.interfaces.kernel_ifaces["dummy1"];
+        let base_iface = iface.base_iface();
+        assert_eq!(base_iface.driver, None);
+        assert_eq!(base_iface.min_mtu, None);
+        assert_eq!(base_iface.max_mtu, None);
+        assert_eq!(base_iface.accept_all_mac_addresses, None);
+        assert_eq!(base_iface.mtu, Some(1500));
+        // Disabled IPv6 means removing IPv6 instead of preserving current
+        assert_eq!(base_iface.ipv6.as_ref().map(|i| i.enabled), Some(false));
+    }
+
+    #[test]
+    fn test_minimize_reapply_to_same_result() {
+        let current = gen_queried_state();
+        let desired = current.minimize();
+
+        let merged =
+            MergedNetworkState::new(desired, current.clone(), false, false)
+                .unwrap();
+
+        assert!(!merged.interfaces.kernel_ifaces["dummy1"].is_changed());
+        merged.verify(&current).unwrap();
+    }
+}
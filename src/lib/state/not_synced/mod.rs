@@ -1,5 +1,36 @@
 // SPDX-License-Identifier: Apache-2.0
 
+mod bond_mode;
+mod bond_options;
+mod controller;
+mod copy_ip;
+#[cfg(feature = "gen_conf")]
+mod gen_conf;
+mod iface_down;
+mod lenient;
+mod loopback;
+mod mac_identifier;
 mod merge_state;
+mod minimize;
+mod mtu_inherit;
 mod net_state;
+#[cfg(feature = "gen_conf")]
+mod nm_keyfile;
 mod ovn;
+mod ovs_dpdk;
+mod ovs_patch;
+mod ovsdb;
+mod parallel;
+mod prune;
+mod rename;
+mod route_table;
+mod schema;
+mod unknown_iface;
+mod validate;
+mod wait_ip;
+mod warning;
+
+pub(crate) use self::mac_identifier::prefer_mac_identifier_matches;
+pub use self::merge_state::NetworkStateConflict;
+pub(crate) use self::route_table::option_route_table_id_or_name;
+pub(crate) use self::warning::log_warn;
@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    BaseInterface, ErrorKind, InterfaceType, MergedInterface, MergedInterfaces,
+    NipartError,
+};
+
+impl MergedInterfaces {
+    // For interface with `mtu: auto` or `mtu: inherit`, resolve the MTU to
+    // the merged MTU of its parent interface, or its controller if no parent.
+    // The parent might also be holding `auto` MTU, hence we resolve in
+    // multiple rounds until nothing changed.
+    pub(crate) fn resolve_mtu_inherit(&mut self) -> Result<(), NipartError> {
+        let mut pending: Vec<String> = self
+            .kernel_ifaces
+            .values()
+            .filter(|i| i.is_desired() && !i.merged.is_absent())
+            .filter(|i| {
+                i.for_apply.as_ref().and_then(|i| i.base_iface().mtu)
+                    == Some(BaseInterface::MTU_INHERIT)
+            })
+            .map(|i| i.merged.name().to_string())
+            .collect();
+        pending.sort_unstable();
+
+        while !pending.is_empty() {
+            let mut resolved: Vec<(String, u64)> = Vec::new();
+            for iface_name in pending.as_slice() {
+                let Some(iface) = self.kernel_ifaces.get(iface_name) else {
+                    continue;
+                };
+                let ref_iface_name = get_mtu_ref_iface_name(iface)?;
+                let Some(ref_mtu) = self
+                    .get_iface(ref_iface_name, InterfaceType::Unknown)
+                    .filter(|i| !i.merged.is_absent())
+                    .and_then(|i| i.merged.base_iface().mtu)
+                else {
+                    let e = NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!(
+                            "Cannot resolve `auto` MTU of interface \
+                            {iface_name} as the MTU of {ref_iface_name} \
+                            is unknown"
+                        ),
+                    );
+                    log::error!("{}", e);
+                    return Err(e);
+                };
+                if ref_mtu != BaseInterface::MTU_INHERIT {
+                    resolved.push((iface_name.to_string(), ref_mtu));
+                }
+            }
+            if resolved.is_empty() {
+                let e = NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Cannot resolve `auto` MTU of interfaces {} as they \
+                        are inheriting MTU from each other",
+                        pending.join(", ")
+                    ),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+            for (iface_name, mtu) in resolved {
+                if let Some(iface) = self.kernel_ifaces.get_mut(&iface_name) {
+                    log::debug!(
+                        "Resolved `auto` MTU of interface {iface_name} to \
+                        {mtu}"
+                    );
+                    iface.set_inherit_mtu(mtu);
+                }
+                pending.retain(|n| n != &iface_name);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MergedInterface {
+    fn set_inherit_mtu(&mut self, mtu: u64) {
+        for iface in [
+            Some(&mut self.merged),
+            self.for_apply.as_mut(),
+            self.for_verify.as_mut(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            iface.base_iface_mut().mtu = Some(mtu);
+        }
+    }
+}
+
+fn get_mtu_ref_iface_name(
+    iface: &MergedInterface,
+) -> Result<&str, NipartError> {
+    if let Some(parent) = iface.merged.parent().filter(|p| !p.is_empty()) {
+        return Ok(parent);
+    }
+    for ctrl in [
+        iface.merged.base_iface().controller.as_deref(),
+        iface
+            .for_apply
+            .as_ref()
+            .and_then(|i| i.base_iface().controller.as_deref()),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if !ctrl.is_empty() {
+            return Ok(ctrl);
+        }
+    }
+    let e = NipartError::new(
+        ErrorKind::InvalidArgument,
+        format!(
+            "Interface {} has no parent or controller to inherit `auto` MTU \
+            from",
+            iface.merged.name()
+        ),
+    );
+    log::error!("{}", e);
+    Err(e)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, MergedNetworkState, NetworkState};
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              mtu: 1500
+            - name: eth1.10
+              type: vlan
+              state: up
+              mtu: 1500
+              vlan:
+                base-iface: eth1
+                id: 10",
+        )
+        .unwrap()
+    }
+
+    fn get_mtus(
+        merged: &MergedNetworkState,
+        iface_name: &str,
+    ) -> (Option<u64>, Option<u64>, Option<u64>) {
+        let iface = &merged.interfaces.kernel_ifaces[iface_name];
+        (
+            iface.merged.base_iface().mtu,
+            iface.for_apply.as_ref().and_then(|i| i.base_iface().mtu),
+            iface.for_verify.as_ref().and_then(|i| i.base_iface().mtu),
+        )
+    }
+
+    #[test]
+    fn test_vlan_inherit_mtu_of_changed_base_iface() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              mtu: 9000
+            - name: eth1.10
+              type: vlan
+              state: up
+              mtu: inherit
+              vlan:
+                base-iface: eth1
+                id: 10",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        assert_eq!(
+            get_mtus(&merged, "eth1.10"),
+            (Some(9000), Some(9000), Some(9000))
+        );
+    }
+
+    #[test]
+    fn test_vlan_inherit_mtu_of_unchanged_base_iface() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1.10
+              type: vlan
+              state: up
+              mtu: auto",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        assert_eq!(
+            get_mtus(&merged, "eth1.10"),
+            (Some(1500), Some(1500), Some(1500))
+        );
+    }
+
+    #[test]
+    fn test_inherit_mtu_of_parent_with_unknown_mtu() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth2.10
+              type: vlan
+              state: up
+              mtu: inherit
+              vlan:
+                base-iface: eth2
+                id: 10
+            - name: eth2
+              type: ethernet
+              state: up",
+        )
+        .unwrap();
+
+        let result =
+            MergedNetworkState::new(desired, NetworkState::new(), false, false);
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidArgument);
+    }
+}
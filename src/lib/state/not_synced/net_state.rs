@@ -1,8 +1,92 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{NetworkState, NipartDhcpConfig};
+use crate::{
+    InterfaceState, MergedNetworkState, NetworkState, NipartDhcpConfig,
+    NipartError,
+};
 
 impl NetworkState {
+    /// Generate a [NetworkState] holding only the properties of `self`
+    /// which differ from `current`, `self` is treated as desired state.
+    /// Returned state is empty when `current` already satisfies `self`.
+    pub fn gen_diff(&self, current: &Self) -> Result<Self, NipartError> {
+        let mut ret = Self::default();
+        let merged_state = MergedNetworkState::new(
+            self.clone(),
+            current.clone(),
+            false,
+            false,
+        )?;
+        ret.interfaces = merged_state.interfaces.gen_diff()?;
+        if self.hostname.is_some() && merged_state.hostname.is_changed() {
+            ret.hostname.clone_from(&self.hostname);
+        }
+        if self.dns.is_some() && merged_state.dns.is_changed() {
+            ret.dns.clone_from(&self.dns);
+        }
+        ret.routes = merged_state.routes.gen_diff();
+        ret.rules = merged_state.rules.gen_diff();
+        Ok(ret)
+    }
+
+    /// Same as [NetworkState::gen_diff()] but also marks interfaces found
+    /// in `current` but not in `self` as absent. Useful for comparing two
+    /// full network states, for example two state files.
+    pub fn gen_full_diff(&self, current: &Self) -> Result<Self, NipartError> {
+        let mut ret = self.gen_diff(current)?;
+        for cur_iface in current.interfaces.to_vec() {
+            if self
+                .interfaces
+                .get_iface(cur_iface.name(), cur_iface.iface_type())
+                .is_none()
+            {
+                let mut iface = cur_iface.clone_name_type_only();
+                iface.base_iface_mut().state = InterfaceState::Absent;
+                ret.interfaces.push(iface);
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Whether applying `self` on top of `current` changes nothing.
+    /// OVS database and OVN configurations are not compared, any desired
+    /// of them is treated as change.
+    pub fn is_noop(&self, current: &Self) -> Result<bool, NipartError> {
+        if self.ovsdb.is_some() || !self.ovn.is_none() {
+            return Ok(false);
+        }
+        Ok(self.gen_diff(current)?.is_empty())
+    }
+
+    /// Names of kernel interfaces marked as `state: ignore`.
+    pub fn ignored_iface_names(&self) -> Vec<String> {
+        self.interfaces
+            .kernel_ifaces
+            .values()
+            .filter(|i| i.is_ignore())
+            .map(|i| i.name().to_string())
+            .collect()
+    }
+
+    /// Mark kernel interfaces named in `names` as `state: ignore` unless
+    /// `desired` mentions them, so that interface ignored in saved state
+    /// stays untouched and unverified until user explicitly manages it
+    /// again.
+    pub fn mark_ifaces_as_ignored(&mut self, names: &[String], desired: &Self) {
+        for name in names {
+            if desired.interfaces.kernel_ifaces.contains_key(name) {
+                continue;
+            }
+            if let Some(iface) = self.interfaces.kernel_ifaces.get_mut(name) {
+                log::info!(
+                    "Interface {name} is ignored in saved network state, \
+                    ignoring"
+                );
+                iface.base_iface_mut().state = InterfaceState::Ignore;
+            }
+        }
+    }
+
     pub fn fill_dhcp_config(&mut self, dhcp_configs: &[NipartDhcpConfig]) {
         for dhcp_config in dhcp_configs {
             if let NipartDhcpConfig::V4(dhcp_config) = dhcp_config {
@@ -24,3 +108,155 @@ impl NetworkState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::NetworkState;
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 1500",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_is_noop_identical_state() {
+        let current = gen_current();
+        let desired = current.clone();
+
+        assert!(desired.is_noop(&current).unwrap());
+    }
+
+    #[test]
+    fn test_is_noop_mtu_changed() {
+        let current = gen_current();
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 9000",
+        )
+        .unwrap();
+
+        assert!(!desired.is_noop(&current).unwrap());
+    }
+
+    #[test]
+    fn test_is_noop_ovsdb_defined() {
+        let current = gen_current();
+        let mut desired = current.clone();
+        desired.ovsdb = NetworkState::new_from_yaml(
+            r"---
+            ovs-db:
+              external_ids:
+                hostname: host.example.org",
+        )
+        .unwrap()
+        .ovsdb;
+
+        assert!(desired.ovsdb.is_some());
+        assert!(!desired.is_noop(&current).unwrap());
+    }
+
+    #[test]
+    fn test_is_noop_ovn_defined() {
+        let current = gen_current();
+        let mut desired = current.clone();
+        desired.ovn = NetworkState::new_from_yaml(
+            r"---
+            ovn:
+              bridge-mappings:
+              - localnet: tenantblue
+                bridge: ovsbr1",
+        )
+        .unwrap()
+        .ovn;
+
+        assert!(!desired.ovn.is_none());
+        assert!(!desired.is_noop(&current).unwrap());
+    }
+
+    #[test]
+    fn test_gen_diff_saved_state_drift() {
+        let saved = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 9000",
+        )
+        .unwrap();
+        let running = gen_current();
+
+        let diff_state = saved.gen_diff(&running).unwrap();
+
+        assert!(!diff_state.is_empty());
+        assert_eq!(
+            diff_state
+                .interfaces
+                .kernel_ifaces
+                .get("dummy1")
+                .and_then(|i| i.base_iface().mtu),
+            Some(9000)
+        );
+    }
+
+    #[test]
+    fn test_gen_diff_saved_state_no_drift() {
+        let saved = gen_current();
+        let running = gen_current();
+
+        assert!(saved.gen_diff(&running).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_gen_full_diff_identical_state() {
+        let state = gen_current();
+
+        assert!(state.gen_full_diff(&gen_current()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_gen_full_diff_missing_iface_on_either_side() {
+        let old_state = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 1500
+            - name: dummy3
+              type: dummy
+              state: up",
+        )
+        .unwrap();
+        let new_state = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 1500
+            - name: dummy2
+              type: dummy
+              state: up",
+        )
+        .unwrap();
+
+        let diff_state = new_state.gen_full_diff(&old_state).unwrap();
+
+        let ifaces = &diff_state.interfaces.kernel_ifaces;
+        assert!(!ifaces.contains_key("dummy1"));
+        assert!(ifaces["dummy2"].is_up());
+        assert!(ifaces["dummy3"].is_absent());
+    }
+}
@@ -0,0 +1,599 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use serde::{de::IntoDeserializer, Deserialize};
+
+use crate::{
+    state::not_synced::log_warn, BondConfig, BondInterface, BondMode,
+    DummyInterface, ErrorKind, EthernetInterface, Interface, InterfaceIpAddr,
+    InterfaceIpv4, InterfaceIpv6, InterfaceState, InterfaceType,
+    LinuxBridgeInterface, LoopbackInterface, NetworkState, NipartError,
+    RouteEntry, VethConfig, VlanConfig, VlanInterface,
+};
+
+const NM_KEYFILE_DIR: &str = "/etc/NetworkManager/system-connections";
+const NM_KEYFILE_SUFFIX: &str = ".nmconnection";
+
+type NmKeyfile = HashMap<String, HashMap<String, String>>;
+
+impl NetworkState {
+    /// Read the persistent configuration of backend as [NetworkState].
+    /// Currently only NetworkManager keyfiles stored in
+    /// `/etc/NetworkManager/system-connections` are supported.
+    ///
+    /// Unlike [NetworkState::retrieve()] which reports what kernel is running
+    /// with, this reports what backend will activate on next boot, hence
+    /// could be used to find out the drift between on-disk configuration
+    /// and running state. The returned state only holds properties stored in
+    /// keyfiles: no query only properties, no DHCP/autoconf addresses, no
+    /// DNS, and routes only for interfaces with profile. Profiles of
+    /// unsupported interface type are skipped with warning.
+    pub fn from_running_config() -> Result<Self, NipartError> {
+        let dir = match std::fs::read_dir(NM_KEYFILE_DIR) {
+            Ok(d) => d,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::new());
+            }
+            Err(e) => {
+                let e = NipartError::new(
+                    ErrorKind::Bug,
+                    format!("Failed to read folder {NM_KEYFILE_DIR}: {e}"),
+                );
+                log::error!("{}", e);
+                return Err(e);
+            }
+        };
+        let mut file_paths: Vec<std::path::PathBuf> = dir
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.to_str()
+                    .map(|p| p.ends_with(NM_KEYFILE_SUFFIX))
+                    .unwrap_or_default()
+            })
+            .collect();
+        file_paths.sort_unstable();
+
+        let mut contents = Vec::new();
+        for file_path in file_paths {
+            match std::fs::read_to_string(&file_path) {
+                Ok(c) => contents.push(c),
+                Err(e) => {
+                    log_warn(format!(
+                        "Ignoring NetworkManager keyfile {}: {e}",
+                        file_path.display()
+                    ));
+                }
+            }
+        }
+        let mut ret = Self::new();
+        let mut routes = Vec::new();
+        for content in contents {
+            match nm_keyfile_to_iface(content.as_str()) {
+                Ok((iface, iface_routes)) => {
+                    ret.interfaces.push(iface);
+                    routes.extend(iface_routes);
+                }
+                Err(e) if e.kind == ErrorKind::NotSupportedError => {
+                    log_warn(format!(
+                        "Ignoring NetworkManager keyfile: {}",
+                        e.msg
+                    ));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        if !routes.is_empty() {
+            ret.routes.config = Some(routes);
+        }
+        Ok(ret)
+    }
+
+    /// Parse the content of NetworkManager keyfiles into [NetworkState].
+    /// This is the reverse of [NetworkState::gen_conf()].
+    pub fn from_nm_keyfiles(contents: &[&str]) -> Result<Self, NipartError> {
+        let mut ret = Self::new();
+        let mut routes = Vec::new();
+        for content in contents {
+            let (iface, iface_routes) = nm_keyfile_to_iface(content)?;
+            ret.interfaces.push(iface);
+            routes.extend(iface_routes);
+        }
+        if !routes.is_empty() {
+            ret.routes.config = Some(routes);
+        }
+        Ok(ret)
+    }
+}
+
+fn parse_nm_keyfile(content: &str) -> Result<NmKeyfile, NipartError> {
+    let mut ret = NmKeyfile::new();
+    let mut section: Option<String> = None;
+    for line in content.lines().map(|l| l.trim()) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) =
+            line.strip_prefix('[').and_then(|l| l.strip_suffix(']'))
+        {
+            section = Some(name.to_string());
+            ret.entry(name.to_string()).or_default();
+        } else if let (Some(section), Some((key, value))) =
+            (section.as_ref(), line.split_once('='))
+        {
+            ret.entry(section.to_string())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        } else {
+            let e = NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!("Invalid line in NetworkManager keyfile: {line}"),
+            );
+            log::error!("{}", e);
+            return Err(e);
+        }
+    }
+    Ok(ret)
+}
+
+fn nm_keyfile_to_iface(
+    content: &str,
+) -> Result<(Interface, Vec<RouteEntry>), NipartError> {
+    let keyfile = parse_nm_keyfile(content)?;
+    let empty = HashMap::new();
+    let conn = keyfile.get("connection").unwrap_or(&empty);
+    let id = conn.get("id").map(|s| s.as_str()).unwrap_or_default();
+    let iface_name = match conn.get("interface-name") {
+        Some(n) => n.to_string(),
+        None => {
+            let e = NipartError::new(
+                ErrorKind::NotSupportedError,
+                format!(
+                    "Profile {id} has no interface-name defined, \
+                    interface matching by other properties is not supported"
+                ),
+            );
+            log::error!("{}", e);
+            return Err(e);
+        }
+    };
+    let nm_iface_type = conn.get("type").map(|s| s.as_str()).unwrap_or("");
+
+    let mut iface = match nm_iface_type {
+        "ethernet" | "802-3-ethernet" => {
+            Interface::Ethernet(Box::new(EthernetInterface::new()))
+        }
+        "veth" => {
+            let mut eth_iface = EthernetInterface::new();
+            if let Some(peer) = keyfile.get("veth").and_then(|v| v.get("peer"))
+            {
+                eth_iface.veth = Some(VethConfig {
+                    peer: peer.to_string(),
+                });
+            }
+            Interface::Ethernet(Box::new(eth_iface))
+        }
+        "bond" => {
+            let mut bond_iface = BondInterface::new();
+            let mut bond_conf = BondConfig::new();
+            if let Some(mode) = keyfile.get("bond").and_then(|b| b.get("mode"))
+            {
+                bond_conf.mode = Some(BondMode::deserialize(
+                    mode.as_str()
+                        .into_deserializer(),
+                )
+                .map_err(|e: serde::de::value::Error| {
+                    NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!("Invalid bond mode {mode} in profile {id}: {e}"),
+                    )
+                })?);
+            }
+            bond_iface.bond = Some(bond_conf);
+            Interface::Bond(Box::new(bond_iface))
+        }
+        "bridge" => {
+            Interface::LinuxBridge(Box::new(LinuxBridgeInterface::new()))
+        }
+        "vlan" => {
+            let mut vlan_iface = VlanInterface::new();
+            if let Some(vlan_section) = keyfile.get("vlan") {
+                let mut vlan_conf = VlanConfig::default();
+                vlan_conf.base_iface = vlan_section.get("parent").cloned();
+                if let Some(vlan_id) = vlan_section.get("id") {
+                    vlan_conf.id = vlan_id.parse::<u16>().map_err(|e| {
+                        NipartError::new(
+                            ErrorKind::InvalidArgument,
+                            format!(
+                                "Invalid VLAN ID {vlan_id} in profile {id}: {e}"
+                            ),
+                        )
+                    })?;
+                }
+                vlan_iface.vlan = Some(vlan_conf);
+            }
+            Interface::Vlan(Box::new(vlan_iface))
+        }
+        "dummy" => Interface::Dummy(Box::new(DummyInterface::new())),
+        "loopback" => Interface::Loopback(Box::new(LoopbackInterface::new())),
+        _ => {
+            let e = NipartError::new(
+                ErrorKind::NotSupportedError,
+                format!(
+                    "Profile {id} of NetworkManager connection type \
+                    {nm_iface_type} is not supported yet"
+                ),
+            );
+            log::error!("{}", e);
+            return Err(e);
+        }
+    };
+
+    let base_iface = iface.base_iface_mut();
+    base_iface.name.clone_from(&iface_name);
+    if !id.is_empty() && id != iface_name {
+        base_iface.profile_name = Some(id.to_string());
+    }
+    base_iface.state =
+        if conn.get("autoconnect").map(|s| s.as_str()) == Some("false") {
+            InterfaceState::Down
+        } else {
+            InterfaceState::Up
+        };
+    if let Some(ctrl) = conn.get("controller").or_else(|| conn.get("master")) {
+        base_iface.controller = Some(ctrl.to_string());
+        base_iface.controller_type = match conn
+            .get("port-type")
+            .or_else(|| conn.get("slave-type"))
+            .map(|s| s.as_str())
+        {
+            Some("bond") => Some(InterfaceType::Bond),
+            Some("bridge") => Some(InterfaceType::LinuxBridge),
+            _ => None,
+        };
+    }
+    let wired = keyfile
+        .get("ethernet")
+        .or_else(|| keyfile.get("802-3-ethernet"))
+        .or_else(|| keyfile.get("loopback"));
+    if let Some(wired) = wired {
+        base_iface.mac_address =
+            wired.get("cloned-mac-address").map(|m| m.to_uppercase());
+        if let Some(mtu) = wired.get("mtu") {
+            base_iface.mtu = mtu.parse::<u64>().ok();
+        }
+    }
+
+    let mut routes = Vec::new();
+    if base_iface.controller.is_none() {
+        if let Some(section) = keyfile.get("ipv4") {
+            base_iface.ipv4 = Some(nm_ipv4_section_to_nipart(section, id)?);
+            routes.extend(nm_route_lines_to_nipart(
+                section,
+                iface_name.as_str(),
+                id,
+            )?);
+        }
+        if let Some(section) = keyfile.get("ipv6") {
+            base_iface.ipv6 = Some(nm_ipv6_section_to_nipart(section, id)?);
+            routes.extend(nm_route_lines_to_nipart(
+                section,
+                iface_name.as_str(),
+                id,
+            )?);
+        }
+    }
+    Ok((iface, routes))
+}
+
+fn nm_ipv4_section_to_nipart(
+    section: &HashMap<String, String>,
+    profile_id: &str,
+) -> Result<InterfaceIpv4, NipartError> {
+    let mut ret = InterfaceIpv4::new();
+    ret.enabled_defined = true;
+    match section.get("method").map(|s| s.as_str()) {
+        Some("disabled") => {
+            ret.enabled = false;
+            return Ok(ret);
+        }
+        Some("auto") => {
+            ret.enabled = true;
+            ret.dhcp = Some(true);
+        }
+        _ => {
+            ret.enabled = true;
+            ret.dhcp = Some(false);
+        }
+    }
+    ret.addresses = Some(nm_addresses_to_nipart(section, profile_id)?);
+    Ok(ret)
+}
+
+fn nm_ipv6_section_to_nipart(
+    section: &HashMap<String, String>,
+    profile_id: &str,
+) -> Result<InterfaceIpv6, NipartError> {
+    let mut ret = InterfaceIpv6::new();
+    ret.enabled_defined = true;
+    let (dhcp, autoconf) = match section.get("method").map(|s| s.as_str()) {
+        Some("disabled") | Some("ignore") => {
+            ret.enabled = false;
+            return Ok(ret);
+        }
+        Some("auto") => (true, true),
+        Some("dhcp") => (true, false),
+        _ => (false, false),
+    };
+    ret.enabled = true;
+    ret.dhcp = Some(dhcp);
+    ret.autoconf = Some(autoconf);
+    ret.addresses = Some(nm_addresses_to_nipart(section, profile_id)?);
+    Ok(ret)
+}
+
+// NetworkManager stores `addressN=ip/prefix[,gateway]` with N starting
+// from 1, the gateway is ignored here.
+fn nm_addresses_to_nipart(
+    section: &HashMap<String, String>,
+    profile_id: &str,
+) -> Result<Vec<InterfaceIpAddr>, NipartError> {
+    let mut ret = Vec::new();
+    for value in indexed_values(section, "address") {
+        let addr_str = value.split(',').next().unwrap_or_default();
+        let (ip, prefix) = match addr_str.split_once('/') {
+            Some((ip, prefix)) => (ip, Some(prefix)),
+            None => (addr_str, None),
+        };
+        let ip = ip.parse::<IpAddr>().map_err(|e| {
+            NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Invalid IP address {addr_str} in profile \
+                    {profile_id}: {e}"
+                ),
+            )
+        })?;
+        let prefix_length = match prefix {
+            Some(p) => p.parse::<u8>().map_err(|e| {
+                NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Invalid IP prefix length {addr_str} in profile \
+                        {profile_id}: {e}"
+                    ),
+                )
+            })?,
+            None => {
+                if ip.is_ipv6() {
+                    128
+                } else {
+                    32
+                }
+            }
+        };
+        let mut addr = InterfaceIpAddr::default();
+        addr.ip = ip;
+        addr.prefix_length = prefix_length;
+        ret.push(addr);
+    }
+    Ok(ret)
+}
+
+// NetworkManager stores `routeN=dst[,next_hop[,metric]]` and
+// `routeN_options=table=N` with N starting from 1.
+fn nm_route_lines_to_nipart(
+    section: &HashMap<String, String>,
+    iface_name: &str,
+    profile_id: &str,
+) -> Result<Vec<RouteEntry>, NipartError> {
+    let mut ret = Vec::new();
+    for (index, value) in indexed_keys(section, "route") {
+        let mut route = RouteEntry::new();
+        let mut items = value.split(',').map(|s| s.trim());
+        route.destination = items.next().map(|d| d.to_string());
+        route.next_hop_iface = Some(iface_name.to_string());
+        route.next_hop_addr = items
+            .next()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+        if let Some(metric) = items.next() {
+            route.metric = Some(metric.parse::<i64>().map_err(|e| {
+                NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Invalid route metric {metric} in profile \
+                        {profile_id}: {e}"
+                    ),
+                )
+            })?);
+        }
+        if let Some(opts) = section.get(&format!("route{index}_options")) {
+            for opt in opts.split(',') {
+                if let Some(table_id) = opt.trim().strip_prefix("table=") {
+                    route.table_id = table_id.parse::<u32>().ok();
+                }
+            }
+        }
+        ret.push(route);
+    }
+    Ok(ret)
+}
+
+fn indexed_values<'a>(
+    section: &'a HashMap<String, String>,
+    prefix: &str,
+) -> Vec<&'a str> {
+    indexed_keys(section, prefix)
+        .into_iter()
+        .map(|(_, v)| v)
+        .collect()
+}
+
+// Return `(index, value)` of `<prefix>N` keys sorted by index.
+fn indexed_keys<'a>(
+    section: &'a HashMap<String, String>,
+    prefix: &str,
+) -> Vec<(u32, &'a str)> {
+    let mut ret: Vec<(u32, &str)> = section
+        .iter()
+        .filter_map(|(k, v)| {
+            k.strip_prefix(prefix)
+                .and_then(|i| i.parse::<u32>().ok())
+                .map(|i| (i, v.as_str()))
+        })
+        .collect();
+    ret.sort_unstable_by_key(|(i, _)| *i);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InterfaceState, InterfaceType, NetworkState};
+
+    #[test]
+    fn test_nm_keyfile_ethernet_static_ip() {
+        let state = NetworkState::from_nm_keyfiles(&[r"
+            [connection]
+            id=eth1
+            type=ethernet
+            interface-name=eth1
+
+            [ethernet]
+            mtu=1400
+
+            [ipv4]
+            method=manual
+            address1=192.0.2.1/24
+            route1=198.51.100.0/24,192.0.2.2,100
+            route1_options=table=100
+
+            [ipv6]
+            method=disabled
+            "])
+        .unwrap();
+
+        let iface = state.interfaces.kernel_ifaces.get("eth1").unwrap();
+        assert_eq!(iface.iface_type(), InterfaceType::Ethernet);
+        let base_iface = iface.base_iface();
+        assert_eq!(base_iface.state, InterfaceState::Up);
+        assert_eq!(base_iface.profile_name, None);
+        assert_eq!(base_iface.mtu, Some(1400));
+
+        let ipv4 = base_iface.ipv4.as_ref().unwrap();
+        assert!(ipv4.enabled);
+        assert_eq!(ipv4.dhcp, Some(false));
+        let addrs = ipv4.addresses.as_ref().unwrap();
+        assert_eq!(addrs.len(), 1);
+        assert_eq!(addrs[0].ip.to_string(), "192.0.2.1");
+        assert_eq!(addrs[0].prefix_length, 24);
+        assert!(!base_iface.ipv6.as_ref().unwrap().enabled);
+
+        let routes = state.routes.config.as_ref().unwrap();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].destination.as_deref(), Some("198.51.100.0/24"));
+        assert_eq!(routes[0].next_hop_iface.as_deref(), Some("eth1"));
+        assert_eq!(routes[0].next_hop_addr.as_deref(), Some("192.0.2.2"));
+        assert_eq!(routes[0].metric, Some(100));
+        assert_eq!(routes[0].table_id, Some(100));
+    }
+
+    #[test]
+    fn test_nm_keyfile_no_interface_name() {
+        assert!(NetworkState::from_nm_keyfiles(&[r"
+            [connection]
+            id=eth1
+            type=ethernet
+            "])
+        .is_err());
+    }
+
+    #[test]
+    fn test_nm_keyfile_gen_conf_round_trip() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              mtu: 1500
+              ipv4:
+                enabled: true
+                dhcp: false
+                address:
+                - ip: 192.0.2.1
+                  prefix-length: 24
+              ipv6:
+                enabled: true
+                dhcp: false
+                autoconf: false
+                address:
+                - ip: 2001:db8::1
+                  prefix-length: 64
+            - name: eth1.10
+              type: vlan
+              state: up
+              vlan:
+                base-iface: eth1
+                id: 10
+              ipv4:
+                enabled: false
+              ipv6:
+                enabled: false
+            routes:
+              config:
+              - destination: 198.51.100.0/24
+                next-hop-interface: eth1
+                next-hop-address: 192.0.2.2
+                metric: 100
+              - destination: 203.0.113.0/24
+                next-hop-interface: eth1
+                metric: 200",
+        )
+        .unwrap();
+
+        let keyfiles = desired
+            .gen_conf()
+            .unwrap()
+            .remove("NetworkManager")
+            .unwrap();
+        let contents: Vec<&str> =
+            keyfiles.iter().map(|(_, c)| c.as_str()).collect();
+        let state = NetworkState::from_nm_keyfiles(&contents).unwrap();
+
+        let eth1 = state.interfaces.kernel_ifaces.get("eth1").unwrap();
+        let des_eth1 = desired.interfaces.kernel_ifaces.get("eth1").unwrap();
+        assert_eq!(eth1.iface_type(), InterfaceType::Ethernet);
+        assert_eq!(eth1.base_iface().mtu, Some(1500));
+        assert_eq!(
+            eth1.base_iface().ipv4.as_ref().unwrap().addresses,
+            des_eth1.base_iface().ipv4.as_ref().unwrap().addresses
+        );
+        assert_eq!(
+            eth1.base_iface().ipv6.as_ref().unwrap().addresses,
+            des_eth1.base_iface().ipv6.as_ref().unwrap().addresses
+        );
+
+        let vlan = state.interfaces.kernel_ifaces.get("eth1.10").unwrap();
+        if let crate::Interface::Vlan(vlan) = vlan {
+            let vlan_conf = vlan.vlan.as_ref().unwrap();
+            assert_eq!(vlan_conf.base_iface.as_deref(), Some("eth1"));
+            assert_eq!(vlan_conf.id, 10);
+            assert!(!vlan.base.ipv4.as_ref().unwrap().enabled);
+        } else {
+            panic!("Expecting VLAN interface, got {vlan:?}");
+        }
+
+        let mut routes = state.routes.config.clone().unwrap();
+        routes.sort_unstable_by(|a, b| a.destination.cmp(&b.destination));
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].destination.as_deref(), Some("198.51.100.0/24"));
+        assert_eq!(routes[0].next_hop_addr.as_deref(), Some("192.0.2.2"));
+        assert_eq!(routes[0].metric, Some(100));
+        assert_eq!(routes[1].destination.as_deref(), Some("203.0.113.0/24"));
+        assert_eq!(routes[1].next_hop_addr, None);
+        assert_eq!(routes[1].metric, Some(200));
+    }
+}
@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::OvnConfiguration;
+use crate::{
+    ErrorKind, InterfaceType, MergedNetworkState, NipartError, OvnConfiguration,
+};
 
 impl OvnConfiguration {
     pub fn is_empty(&self) -> bool {
@@ -11,3 +13,162 @@ impl OvnConfiguration {
         }
     }
 }
+
+impl MergedNetworkState {
+    // Each desired OVN bridge mapping should point to OVS bridge which
+    // exists after apply.
+    pub(crate) fn validate_ovn_bridge_mappings(
+        &self,
+    ) -> Result<(), NipartError> {
+        let Some(maps) = self.ovn.desired.bridge_mappings.as_ref() else {
+            return Ok(());
+        };
+        for map in maps.iter().filter(|m| !m.is_absent()) {
+            let Some(br_name) = map.bridge.as_deref() else {
+                continue;
+            };
+            let br_exists = self
+                .interfaces
+                .get_iface(br_name, InterfaceType::OvsBridge)
+                .map(|iface| !iface.merged.is_absent())
+                .unwrap_or_default();
+            if !br_exists {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "OVN bridge mapping for localnet {} is referring \
+                        to OVS bridge {br_name} which does not exist",
+                        map.localnet
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ErrorKind, MergedNetworkState, NetworkState, OvsDbGlobalConfig,
+    };
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: ovsbr1
+              type: ovs-interface
+              state: up
+            - name: ovsbr1
+              type: ovs-bridge
+              state: up
+              bridge:
+                port:
+                - name: ovsbr1
+            - name: ovsbr2
+              type: ovs-interface
+              state: up
+            - name: ovsbr2
+              type: ovs-bridge
+              state: up
+              bridge:
+                port:
+                - name: ovsbr2
+            ovn:
+              bridge-mappings:
+              - localnet: tenantblue
+                bridge: ovsbr1",
+        )
+        .unwrap()
+    }
+
+    fn get_ovsdb_ovn_value(merged: &MergedNetworkState) -> Option<&str> {
+        merged
+            .ovsdb
+            .external_ids
+            .get(OvsDbGlobalConfig::OVN_BRIDGE_MAPPINGS_KEY)
+            .and_then(|v| v.as_deref())
+    }
+
+    #[test]
+    fn test_ovn_add_bridge_mapping() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            ovn:
+              bridge-mappings:
+              - localnet: tenantred
+                bridge: ovsbr2",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        assert!(merged.ovn.is_changed());
+        assert_eq!(
+            get_ovsdb_ovn_value(&merged),
+            Some("tenantblue:ovsbr1,tenantred:ovsbr2")
+        );
+    }
+
+    #[test]
+    fn test_ovn_modify_bridge_mapping() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            ovn:
+              bridge-mappings:
+              - localnet: tenantblue
+                bridge: ovsbr2",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        assert!(merged.ovn.is_changed());
+        assert_eq!(get_ovsdb_ovn_value(&merged), Some("tenantblue:ovsbr2"));
+    }
+
+    #[test]
+    fn test_ovn_remove_bridge_mapping() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            ovn:
+              bridge-mappings:
+              - localnet: tenantblue
+                state: absent",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        assert!(merged.ovn.is_changed());
+        assert!(merged.ovsdb.is_changed);
+        assert_eq!(get_ovsdb_ovn_value(&merged), None);
+    }
+
+    #[test]
+    fn test_ovn_bridge_mapping_to_non_exist_bridge() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            ovn:
+              bridge-mappings:
+              - localnet: tenantred
+                bridge: ovsbr3",
+        )
+        .unwrap();
+
+        let result =
+            MergedNetworkState::new(desired, gen_current(), false, false);
+
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::InvalidArgument);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    state::not_synced::log_warn, Interface, MergedNetworkState,
+    OvsBridgeOptions,
+};
+
+const OVS_DPDK_INIT_KEY: &str = "dpdk-init";
+
+impl MergedNetworkState {
+    // OVS DPDK interface and `netdev` datapath bridge only works when
+    // `dpdk-init` is enabled in global `other_config` of OVS database.
+    pub(crate) fn warn_ovs_dpdk_not_initialized(&self) {
+        let dpdk_init = self
+            .ovsdb
+            .other_config
+            .get(OVS_DPDK_INIT_KEY)
+            .and_then(|v| v.as_deref());
+        if matches!(dpdk_init, Some("true") | Some("try")) {
+            return;
+        }
+        for iface in self
+            .interfaces
+            .iter()
+            .filter(|i| i.is_desired() && !i.merged.is_absent())
+        {
+            if let Interface::OvsInterface(ovs_iface) = &iface.merged {
+                if ovs_iface.dpdk.is_some() {
+                    log_warn(format!(
+                        "OVS DPDK interface {} will not work as \
+                        {OVS_DPDK_INIT_KEY} is not enabled in OVS \
+                        database other_config",
+                        ovs_iface.base.name
+                    ));
+                }
+            } else if let Interface::OvsBridge(br_iface) = &iface.merged {
+                if br_iface
+                    .bridge
+                    .as_ref()
+                    .and_then(|b| b.options.as_ref())
+                    .and_then(|o| o.datapath.as_deref())
+                    == Some(OvsBridgeOptions::DATAPATH_NETDEV)
+                {
+                    log_warn(format!(
+                        "OVS bridge {} with datapath {} will not have DPDK \
+                        acceleration as {OVS_DPDK_INIT_KEY} is not enabled \
+                        in OVS database other_config",
+                        br_iface.base.name,
+                        OvsBridgeOptions::DATAPATH_NETDEV
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    ErrorKind, Interface, InterfaceType, MergedInterfaces, MergedNetworkState,
+    NipartError,
+};
+
+impl MergedNetworkState {
+    // Both ends of changed OVS patch port should point to each other and
+    // be attached to OVS bridges which exist after apply.
+    pub(crate) fn validate_ovs_patch_peers(&self) -> Result<(), NipartError> {
+        for iface in self
+            .interfaces
+            .iter()
+            .filter(|i| i.is_changed() && !i.merged.is_absent())
+        {
+            let Interface::OvsInterface(ovs_iface) = &iface.merged else {
+                continue;
+            };
+            let Some(peer_name) = ovs_iface.patch.as_ref().map(|p| &p.peer)
+            else {
+                continue;
+            };
+            let name = ovs_iface.base.name.as_str();
+            let peer_iface = match self
+                .interfaces
+                .get_iface(peer_name, InterfaceType::OvsInterface)
+                .filter(|i| !i.merged.is_absent())
+                .map(|i| &i.merged)
+            {
+                Some(Interface::OvsInterface(i)) => i,
+                _ => {
+                    return Err(NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!(
+                            "OVS patch port {name} is referring to peer \
+                            {peer_name} which does not exist"
+                        ),
+                    ));
+                }
+            };
+            if peer_iface.patch.as_ref().map(|p| p.peer.as_str()) != Some(name)
+            {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "OVS patch port {name} is referring to peer \
+                        {peer_name}, but {peer_name} is not a patch port \
+                        referring back to {name}"
+                    ),
+                ));
+            }
+            for port_name in [name, peer_name.as_str()] {
+                if !is_attached_to_ovs_bridge(&self.interfaces, port_name) {
+                    return Err(NipartError::new(
+                        ErrorKind::InvalidArgument,
+                        format!(
+                            "OVS patch port {port_name} is not attached to \
+                            any existing OVS bridge"
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_attached_to_ovs_bridge(
+    ifaces: &MergedInterfaces,
+    port_name: &str,
+) -> bool {
+    ifaces.iter().any(|i| {
+        i.merged.iface_type() == InterfaceType::OvsBridge
+            && !i.merged.is_absent()
+            && i.merged
+                .ports()
+                .map(|ports| ports.contains(&port_name))
+                .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, MergedNetworkState, NetworkState};
+
+    #[test]
+    fn test_ovs_patch_connect_two_bridges() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: patch0
+              type: ovs-interface
+              state: up
+              patch:
+                peer: patch1
+            - name: ovs-br0
+              type: ovs-bridge
+              state: up
+              bridge:
+                port:
+                - name: patch0
+            - name: patch1
+              type: ovs-interface
+              state: up
+              patch:
+                peer: patch0
+            - name: ovs-br1
+              type: ovs-bridge
+              state: up
+              bridge:
+                port:
+                - name: patch1",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, NetworkState::new(), false, false)
+                .unwrap();
+
+        assert!(merged.interfaces.kernel_ifaces["patch0"].is_changed());
+        assert!(merged.interfaces.kernel_ifaces["patch1"].is_changed());
+    }
+
+    #[test]
+    fn test_ovs_patch_dangling_peer() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: patch0
+              type: ovs-interface
+              state: up
+              patch:
+                peer: patch1
+            - name: ovs-br0
+              type: ovs-bridge
+              state: up
+              bridge:
+                port:
+                - name: patch0",
+        )
+        .unwrap();
+
+        let result =
+            MergedNetworkState::new(desired, NetworkState::new(), false, false);
+
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::InvalidArgument);
+        }
+    }
+}
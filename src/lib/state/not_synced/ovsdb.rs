@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use crate::{MergedOvsDbGlobalConfig, NipartError, OvsDbGlobalConfig};
+
+impl MergedOvsDbGlobalConfig {
+    // OVS parses boolean of `other_config` case-insensitively and ignores
+    // leading and trailing spaces of numbers, hence we normalize both
+    // desired and current `other_config` values before verification.
+    pub(crate) fn verify_normalized(
+        &self,
+        mut current: OvsDbGlobalConfig,
+    ) -> Result<(), NipartError> {
+        let mut normalized = self.clone();
+        if let Some(other_cfg) = normalized
+            .desired
+            .as_mut()
+            .and_then(|d| d.other_config.as_mut())
+        {
+            normalize_ovsdb_map(other_cfg);
+        }
+        if let Some(other_cfg) = current.other_config.as_mut() {
+            normalize_ovsdb_map(other_cfg);
+        }
+        normalized.verify(current)
+    }
+}
+
+fn normalize_ovsdb_map(map: &mut HashMap<String, Option<String>>) {
+    for value in map.values_mut().flatten() {
+        *value = normalize_ovsdb_value(value);
+    }
+}
+
+fn normalize_ovsdb_value(value: &str) -> String {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false")
+    {
+        value.to_ascii_lowercase()
+    } else if let Ok(i) = value.parse::<i64>() {
+        i.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        ErrorKind, MergedOvnConfiguration, MergedOvsDbGlobalConfig,
+        OvsDbGlobalConfig,
+    };
+
+    fn gen_conf(other_config: &[(&str, &str)]) -> OvsDbGlobalConfig {
+        OvsDbGlobalConfig {
+            external_ids: Some(HashMap::new()),
+            other_config: Some(
+                other_config
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), Some(v.to_string())))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn gen_merged(desired: &[(&str, &str)]) -> MergedOvsDbGlobalConfig {
+        MergedOvsDbGlobalConfig::new(
+            Some(gen_conf(desired)),
+            OvsDbGlobalConfig::default(),
+            &MergedOvnConfiguration::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_verify_ovsdb_other_config_normalized() {
+        let merged =
+            gen_merged(&[("hw-offload", "True"), ("n-handler-threads", "04")]);
+
+        merged
+            .verify_normalized(gen_conf(&[
+                ("hw-offload", "true"),
+                ("n-handler-threads", "4"),
+            ]))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_verify_ovsdb_other_config_mismatch() {
+        let merged = gen_merged(&[("hw-offload", "true")]);
+
+        let result =
+            merged.verify_normalized(gen_conf(&[("hw-offload", "false")]));
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::VerificationError);
+    }
+}
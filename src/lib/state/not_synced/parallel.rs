@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{Interface, MergedNetworkState};
+
+impl MergedNetworkState {
+    /// Split into at most `max` states for concurrent apply, each holding
+    /// interfaces to apply which have no dependency (controller, port,
+    /// parent, veth peer, `copy-mac-from` or `copy-ip-from`) on interfaces
+    /// of other states. Unchanged interfaces are kept in every state as
+    /// reference. Return single clone of `self` when nothing to split or
+    /// desired state changes anything beyond kernel interfaces.
+    pub fn split_for_parallel_apply(&self, max: usize) -> Vec<Self> {
+        if max <= 1 || !self.is_kernel_ifaces_only_change() {
+            return vec![self.clone()];
+        }
+        let mut groups = IfaceGroups::default();
+        for (name, merged_iface) in self.interfaces.kernel_ifaces.iter() {
+            for iface in
+                [Some(&merged_iface.merged), merged_iface.current.as_ref()]
+                    .into_iter()
+                    .flatten()
+            {
+                for dep in iface_dependencies(iface) {
+                    groups.union(name, dep);
+                }
+            }
+        }
+        // BTreeMap to make split result consistent between runs
+        let mut components: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        for (name, merged_iface) in self.interfaces.kernel_ifaces.iter() {
+            if merged_iface.for_apply.is_some() {
+                components
+                    .entry(groups.find(name))
+                    .or_default()
+                    .push(name.as_str());
+            }
+        }
+        if components.len() <= 1 {
+            return vec![self.clone()];
+        }
+        let bucket_count = std::cmp::min(max, components.len());
+        let mut buckets: Vec<Vec<&str>> = vec![Vec::new(); bucket_count];
+        for (i, names) in components.into_values().enumerate() {
+            buckets[i % bucket_count].extend(names);
+        }
+        log::info!(
+            "Splitting apply into {bucket_count} concurrent sub-applies: {}",
+            buckets
+                .iter()
+                .map(|b| b.join(","))
+                .collect::<Vec<String>>()
+                .join(" ")
+        );
+        buckets
+            .iter()
+            .map(|names| {
+                let mut state = self.clone();
+                for (name, merged_iface) in
+                    state.interfaces.kernel_ifaces.iter_mut()
+                {
+                    if !names.contains(&name.as_str()) {
+                        merged_iface.for_apply = None;
+                    }
+                }
+                state
+            })
+            .collect()
+    }
+
+    // User space interfaces are applied in single OVS transaction, routes,
+    // route rules and other global settings might refer to any interface,
+    // hence not split when any of them changed.
+    fn is_kernel_ifaces_only_change(&self) -> bool {
+        self.interfaces
+            .user_ifaces
+            .values()
+            .all(|i| i.for_apply.is_none())
+            && !self.routes.is_changed()
+            && !self.rules.is_changed()
+            && !self.dns.is_changed()
+            && !(self.hostname.desired.is_some() && self.hostname.is_changed())
+            && !self.ovsdb.is_changed()
+            && !self.ovn.is_changed()
+            && !self.interfaces.kernel_ifaces.values().any(|i| {
+                i.for_apply
+                    .as_ref()
+                    .map(|i| i.base_iface().rename_to.is_some())
+                    == Some(true)
+            })
+    }
+}
+
+fn iface_dependencies(iface: &Interface) -> Vec<&str> {
+    let mut ret: Vec<&str> = Vec::new();
+    let base_iface = iface.base_iface();
+    ret.extend(base_iface.controller.as_deref());
+    ret.extend(base_iface.copy_mac_from.as_deref());
+    ret.extend(base_iface.copy_ip_from.as_deref());
+    ret.extend(iface.parent());
+    ret.extend(iface.ports().unwrap_or_default());
+    if let Interface::Ethernet(eth_iface) = iface {
+        ret.extend(eth_iface.veth.as_ref().map(|v| v.peer.as_str()));
+    }
+    ret.retain(|name| !name.is_empty());
+    ret
+}
+
+#[derive(Debug, Default)]
+struct IfaceGroups {
+    parents: HashMap<String, String>,
+}
+
+impl IfaceGroups {
+    fn find(&self, name: &str) -> String {
+        let mut cur = name;
+        while let Some(parent) = self.parents.get(cur) {
+            if parent == cur {
+                break;
+            }
+            cur = parent.as_str();
+        }
+        cur.to_string()
+    }
+
+    fn union(&mut self, a: &str, b: &str) {
+        let root_a = self.find(a);
+        let root_b = self.find(b);
+        if root_a != root_b {
+            // Smaller name as root to make result consistent
+            if root_a < root_b {
+                self.parents.insert(root_b, root_a);
+            } else {
+                self.parents.insert(root_a, root_b);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MergedNetworkState, NetworkState};
+
+    const DUMMY_COUNT: usize = 64;
+
+    fn gen_dummies_state(state: &str, mtu: u64) -> NetworkState {
+        let mut yaml = String::from("interfaces:\n");
+        for i in 0..DUMMY_COUNT {
+            yaml.push_str(&format!(
+                "- name: dummy{i}\n  type: dummy\n  state: {state}\n  \
+                mtu: {mtu}\n"
+            ));
+        }
+        NetworkState::new_from_yaml(&yaml).unwrap()
+    }
+
+    fn for_apply_names(state: &MergedNetworkState) -> Vec<String> {
+        state
+            .interfaces
+            .kernel_ifaces
+            .values()
+            .filter(|i| i.for_apply.is_some())
+            .map(|i| i.merged.name().to_string())
+            .collect()
+    }
+
+    // Every interface to apply should be applied by exactly one sub-state
+    fn assert_applied_once(merged: &MergedNetworkState, max: usize) {
+        let states = merged.split_for_parallel_apply(max);
+        assert_eq!(states.len(), max);
+        let mut names: Vec<String> =
+            states.iter().flat_map(for_apply_names).collect();
+        names.sort_unstable();
+        let mut expected = for_apply_names(merged);
+        expected.sort_unstable();
+        assert_eq!(names, expected);
+        for state in states {
+            assert_eq!(
+                state.interfaces.kernel_ifaces.len(),
+                merged.interfaces.kernel_ifaces.len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_split_many_independent_dummies() {
+        let merged = MergedNetworkState::new(
+            gen_dummies_state("up", 9000),
+            gen_dummies_state("up", 1500),
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(for_apply_names(&merged).len(), DUMMY_COUNT);
+        assert_applied_once(&merged, 4);
+    }
+
+    #[test]
+    fn test_split_absent_dummies_deleted_once() {
+        let merged = MergedNetworkState::new(
+            gen_dummies_state("absent", 1500),
+            gen_dummies_state("up", 1500),
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_applied_once(&merged, 8);
+    }
+
+    #[test]
+    fn test_split_keep_controller_with_ports() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: br0
+              type: linux-bridge
+              state: up
+              bridge:
+                port:
+                - name: dummy0
+                - name: dummy1
+            - name: dummy0
+              type: dummy
+              state: up
+            - name: dummy1
+              type: dummy
+              state: up
+            - name: dummy2
+              type: dummy
+              state: up
+            - name: dummy3
+              type: dummy
+              state: up",
+        )
+        .unwrap();
+        let merged =
+            MergedNetworkState::new(desired, NetworkState::new(), false, false)
+                .unwrap();
+
+        let states = merged.split_for_parallel_apply(3);
+        assert_eq!(states.len(), 3);
+        let bucket_of = |name: &str| {
+            states
+                .iter()
+                .position(|s| for_apply_names(s).contains(&name.to_string()))
+                .unwrap()
+        };
+        assert_eq!(bucket_of("br0"), bucket_of("dummy0"));
+        assert_eq!(bucket_of("br0"), bucket_of("dummy1"));
+    }
+
+    #[test]
+    fn test_no_split_with_max_one() {
+        let merged = MergedNetworkState::new(
+            gen_dummies_state("up", 9000),
+            gen_dummies_state("up", 1500),
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(merged.split_for_parallel_apply(1).len(), 1);
+    }
+}
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Interface, InterfaceState, InterfaceType, NetworkState};
+
+impl NetworkState {
+    /// Generate [NetworkState] marking every virtual interface found in
+    /// current network state(`self`) as absent, except those named in
+    /// `keep`. Physical NICs, loopback, interfaces of unknown type and
+    /// interfaces with `state: ignore` are never included. Interfaces already
+    /// absent are skipped, hence applying the result multiple times is
+    /// idempotent.
+    pub fn absent_all_except(&self, keep: &[&str]) -> Self {
+        let mut ret = Self::new();
+        for iface in self.interfaces.iter().filter(|i| {
+            !i.is_absent()
+                && !i.is_ignore()
+                && (i.is_virtual() || i.iface_type() == InterfaceType::Veth)
+                && !keep.contains(&i.name())
+        }) {
+            let mut absent_iface = iface.clone_name_type_only();
+            if let Interface::Ethernet(_) = absent_iface {
+                // Internal clone always use ethernet interface type
+                absent_iface.base_iface_mut().iface_type = InterfaceType::Veth;
+            }
+            absent_iface.base_iface_mut().state = InterfaceState::Absent;
+            ret.append_interface_data(absent_iface);
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MergedNetworkState, NetworkState};
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+            - name: dummy2
+              type: dummy
+              state: up",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_absent_all_except_skip_ignored() {
+        let mut current = gen_current();
+        current.mark_ifaces_as_ignored(
+            &["dummy1".to_string()],
+            &NetworkState::new(),
+        );
+
+        let prune_state = current.absent_all_except(&[]);
+
+        assert!(prune_state.interfaces.kernel_ifaces.get("dummy1").is_none());
+        assert!(prune_state.interfaces.kernel_ifaces["dummy2"].is_absent());
+    }
+
+    #[test]
+    fn test_apply_prune_keep_ignored_iface_untouched() {
+        let mut current = gen_current();
+        current.mark_ifaces_as_ignored(
+            &["dummy1".to_string()],
+            &NetworkState::new(),
+        );
+        let desired = current.absent_all_except(&[]);
+
+        let merged =
+            MergedNetworkState::new(desired, current, false, false).unwrap();
+
+        assert!(merged
+            .interfaces
+            .ignored_ifaces
+            .iter()
+            .any(|(n, _)| n == "dummy1"));
+        assert!(merged.interfaces.kernel_ifaces.get("dummy1").is_none());
+        assert!(merged.interfaces.kernel_ifaces["dummy2"].is_changed());
+    }
+
+    #[test]
+    fn test_absent_all_except_keep_physical_nic() {
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: lo
+              type: loopback
+              state: up
+            - name: eth1
+              type: ethernet
+              state: up
+            - name: dummy1
+              type: dummy
+              state: up
+            - name: dummy2
+              type: dummy
+              state: up
+            - name: br0
+              type: linux-bridge
+              state: up",
+        )
+        .unwrap();
+
+        let prune_state = current.absent_all_except(&["dummy2"]);
+
+        let ifaces = &prune_state.interfaces.kernel_ifaces;
+        assert_eq!(ifaces.len(), 2);
+        assert!(ifaces["dummy1"].is_absent());
+        assert!(ifaces["br0"].is_absent());
+        assert!(ifaces.get("eth1").is_none());
+        assert!(ifaces.get("lo").is_none());
+        assert!(ifaces.get("dummy2").is_none());
+
+        // Nothing more to prune after applied
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: lo
+              type: loopback
+              state: up
+            - name: eth1
+              type: ethernet
+              state: up
+            - name: dummy2
+              type: dummy
+              state: up",
+        )
+        .unwrap();
+        assert!(current.absent_all_except(&["dummy2"]).is_empty());
+    }
+}
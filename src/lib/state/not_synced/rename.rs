@@ -0,0 +1,304 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    ErrorKind, Interface, InterfaceType, MergedNetworkState, NipartError,
+};
+
+impl MergedNetworkState {
+    // For interface with `rename-to` defined:
+    //  * Validate the new name is not used by any other interface.
+    //  * Verify against the new name.
+    //  * Update controller ports and port controller referring the old name.
+    //  * Update parent of child interfaces (e.g. VLAN) referring the old name.
+    // The `for_apply` still use old name with `rename-to` set, backend should
+    // rename the interface before applying other changes.
+    pub(crate) fn process_iface_rename(&mut self) -> Result<(), NipartError> {
+        // Vec<(old_name, new_name)>
+        let mut renames: Vec<(String, String)> = Vec::new();
+        for iface in self
+            .interfaces
+            .kernel_ifaces
+            .values()
+            .filter(|i| i.is_desired() && !i.merged.is_absent())
+        {
+            let Some(new_name) = iface
+                .for_apply
+                .as_ref()
+                .and_then(|i| i.base_iface().rename_to.as_deref())
+            else {
+                continue;
+            };
+            let old_name = iface.merged.name();
+            if new_name == old_name {
+                continue;
+            }
+            if new_name.is_empty() {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Interface {old_name} cannot be renamed to empty \
+                        string"
+                    ),
+                ));
+            }
+            if iface.current.is_none() {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Interface {old_name} does not exist, cannot \
+                        rename it to {new_name}"
+                    ),
+                ));
+            }
+            if self
+                .interfaces
+                .get_iface(new_name, InterfaceType::Unknown)
+                .is_some()
+                || renames.iter().any(|(_, n)| n == new_name)
+            {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Cannot rename interface {old_name} to {new_name} \
+                        as it is already used by other interface"
+                    ),
+                ));
+            }
+            renames.push((old_name.to_string(), new_name.to_string()));
+        }
+
+        for (old_name, new_name) in renames.iter() {
+            log::info!("Renaming interface {old_name} to {new_name}");
+            if let Some(iface) =
+                self.interfaces.kernel_ifaces.get_mut(old_name.as_str())
+            {
+                if let Some(verify_iface) = iface.for_verify.as_mut() {
+                    verify_iface.base_iface_mut().name.clone_from(new_name);
+                }
+            }
+            for iface in self.interfaces.iter_mut() {
+                if iface
+                    .merged
+                    .ports()
+                    .map(|ports| ports.contains(&old_name.as_str()))
+                    .unwrap_or_default()
+                {
+                    iface.merged.change_port_name(old_name, new_name.clone());
+                    for i in
+                        [iface.for_apply.as_mut(), iface.for_verify.as_mut()]
+                            .into_iter()
+                            .flatten()
+                    {
+                        i.change_port_name(old_name, new_name.clone());
+                    }
+                }
+                for i in [
+                    Some(&mut iface.merged),
+                    iface.for_apply.as_mut(),
+                    iface.for_verify.as_mut(),
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    if i.base_iface().controller.as_deref()
+                        == Some(old_name.as_str())
+                    {
+                        i.base_iface_mut().controller = Some(new_name.clone());
+                    }
+                    if i.parent() == Some(old_name.as_str()) {
+                        change_parent_name(i, new_name);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// The OVS internal interface is using controller as parent, which is already
+// handled by caller.
+fn change_parent_name(iface: &mut Interface, new_name: &str) {
+    match iface {
+        Interface::Vlan(i) => {
+            if let Some(cfg) = i.vlan.as_mut() {
+                cfg.base_iface = Some(new_name.to_string());
+            }
+        }
+        Interface::Vxlan(i) => {
+            if let Some(cfg) = i.vxlan.as_mut() {
+                cfg.base_iface = new_name.to_string();
+            }
+        }
+        Interface::IpVlan(i) => {
+            if let Some(cfg) = i.ip_vlan.as_mut() {
+                cfg.base_iface = new_name.to_string();
+            }
+        }
+        Interface::MacVlan(i) => {
+            if let Some(cfg) = i.mac_vlan.as_mut() {
+                cfg.base_iface = new_name.to_string();
+            }
+        }
+        Interface::MacVtap(i) => {
+            if let Some(cfg) = i.mac_vtap.as_mut() {
+                cfg.base_iface = new_name.to_string();
+            }
+        }
+        Interface::MacSec(i) => {
+            if let Some(cfg) = i.macsec.as_mut() {
+                cfg.base_iface = new_name.to_string();
+            }
+        }
+        Interface::InfiniBand(i) => {
+            if let Some(cfg) = i.ib.as_mut() {
+                cfg.base_iface = Some(new_name.to_string());
+            }
+        }
+        Interface::Gre(i) => {
+            if let Some(cfg) = i.gre.as_mut() {
+                cfg.base_iface = Some(new_name.to_string());
+            }
+        }
+        Interface::GreTap(i) => {
+            if let Some(cfg) = i.gre.as_mut() {
+                cfg.base_iface = Some(new_name.to_string());
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, Interface, MergedNetworkState, NetworkState};
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              controller: bond99
+            - name: dummy2
+              type: dummy
+              state: up
+              controller: bond99
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: balance-rr
+                port:
+                - dummy1
+                - dummy2
+            - name: dummy3
+              type: dummy
+              state: up",
+        )
+        .unwrap()
+    }
+
+    fn get_bond_ports(iface: Option<&Interface>) -> Vec<String> {
+        let mut ports: Vec<String> = iface
+            .and_then(|i| i.ports())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| p.to_string())
+            .collect();
+        ports.sort_unstable();
+        ports
+    }
+
+    #[test]
+    fn test_rename_bond_port() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              rename-to: port1
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: balance-rr
+                port:
+                - dummy1
+                - dummy2",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        let bond = &merged.interfaces.kernel_ifaces["bond99"];
+        let expected = vec!["dummy2".to_string(), "port1".to_string()];
+        assert_eq!(get_bond_ports(Some(&bond.merged)), expected);
+        assert_eq!(get_bond_ports(bond.for_apply.as_ref()), expected);
+        assert_eq!(get_bond_ports(bond.for_verify.as_ref()), expected);
+
+        let port = &merged.interfaces.kernel_ifaces["dummy1"];
+        assert_eq!(port.for_verify.as_ref().map(|i| i.name()), Some("port1"));
+        assert_eq!(
+            port.for_apply
+                .as_ref()
+                .and_then(|i| i.base_iface().controller.as_deref()),
+            Some("bond99")
+        );
+    }
+
+    #[test]
+    fn test_rename_vlan_parent() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy3
+              type: dummy
+              state: up
+              rename-to: wan0
+            - name: vlan10
+              type: vlan
+              state: up
+              vlan:
+                base-iface: dummy3
+                id: 10",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        let vlan = &merged.interfaces.kernel_ifaces["vlan10"];
+        assert_eq!(vlan.merged.parent(), Some("wan0"));
+        assert_eq!(
+            vlan.for_apply.as_ref().and_then(|i| i.parent()),
+            Some("wan0")
+        );
+        assert_eq!(
+            vlan.for_verify.as_ref().and_then(|i| i.parent()),
+            Some("wan0")
+        );
+    }
+
+    #[test]
+    fn test_rename_to_existing_iface() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy3
+              type: dummy
+              state: up
+              rename-to: dummy2",
+        )
+        .unwrap();
+
+        let result =
+            MergedNetworkState::new(desired, gen_current(), false, false);
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidArgument);
+    }
+}
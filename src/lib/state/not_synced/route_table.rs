@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{de, Deserialize, Deserializer};
+
+use crate::{ErrorKind, NipartError};
+
+const RT_TABLES_FILES: [&str; 2] =
+    ["/etc/iproute2/rt_tables", "/usr/share/iproute2/rt_tables"];
+const RT_TABLES_DIRS: [&str; 2] = [
+    "/etc/iproute2/rt_tables.d",
+    "/usr/share/iproute2/rt_tables.d",
+];
+
+// Kernel reserved route tables, used when not found in rt_tables files
+const BUILTIN_ROUTE_TABLES: [(&str, u32); 3] =
+    [("default", 253), ("main", 254), ("local", 255)];
+
+// Resolve route table name to numeric ID by searching iproute2 `rt_tables`
+// files. The files in `/etc` take precedence over `/usr/share`.
+pub(crate) fn resolve_route_table_name(name: &str) -> Result<u32, NipartError> {
+    let mut files: Vec<std::path::PathBuf> = Vec::new();
+    for (file, dir) in RT_TABLES_FILES.iter().zip(RT_TABLES_DIRS.iter()) {
+        files.push(file.into());
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            let mut conf_files: Vec<std::path::PathBuf> = entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.extension().and_then(|e| e.to_str()) == Some("conf")
+                })
+                .collect();
+            conf_files.sort_unstable();
+            files.extend(conf_files);
+        }
+    }
+    for file in files {
+        let Ok(content) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        if let Some(id) = find_table_id(content.as_str(), name) {
+            log::debug!(
+                "Resolved route table {name} to {id} via {}",
+                file.display()
+            );
+            return Ok(id);
+        }
+    }
+    if let Some((_, id)) = BUILTIN_ROUTE_TABLES.iter().find(|(n, _)| *n == name)
+    {
+        return Ok(*id);
+    }
+    Err(NipartError::new(
+        ErrorKind::InvalidArgument,
+        format!(
+            "Route table {name} is not defined in {}",
+            RT_TABLES_FILES.join(" or ")
+        ),
+    ))
+}
+
+// Each line of `rt_tables` is `<id> <name>`, `#` starts a comment.
+fn find_table_id(content: &str, name: &str) -> Option<u32> {
+    content.lines().find_map(|line| {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        let id = words.next()?;
+        if words.next() == Some(name) {
+            id.parse::<u32>().ok()
+        } else {
+            None
+        }
+    })
+}
+
+// Route table could be defined as numeric ID or name defined in iproute2
+// `rt_tables` file.
+pub(crate) fn option_route_table_id_or_name<'de, D>(
+    deserializer: D,
+) -> Result<Option<u32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<serde_json::Value>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(serde_json::Value::String(s))
+            if !s.is_empty() && !s.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            resolve_route_table_name(s.as_str())
+                .map(Some)
+                .map_err(de::Error::custom)
+        }
+        Some(v) => crate::state::deserializer::option_u32_or_string(v)
+            .map_err(de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_table_id, resolve_route_table_name};
+    use crate::{ErrorKind, RouteEntry};
+
+    #[test]
+    fn test_find_table_id_in_rt_tables() {
+        let content = "# reserved values\n\
+            255\tlocal\n\
+            254\tmain\n\
+            100 mgmt # management network\n\
+            #101 disabled\n";
+
+        assert_eq!(find_table_id(content, "mgmt"), Some(100));
+        assert_eq!(find_table_id(content, "main"), Some(254));
+        assert_eq!(find_table_id(content, "disabled"), None);
+        assert_eq!(find_table_id(content, "management"), None);
+    }
+
+    #[test]
+    fn test_resolve_route_table_name() {
+        assert_eq!(resolve_route_table_name("main").unwrap(), 254);
+        assert_eq!(
+            resolve_route_table_name("nipart_not_exist")
+                .unwrap_err()
+                .kind,
+            ErrorKind::InvalidArgument
+        );
+    }
+
+    #[test]
+    fn test_route_table_id_or_name() {
+        let route: RouteEntry = serde_yaml::from_str(
+            r"---
+            destination: 198.51.100.0/24
+            table-id: main",
+        )
+        .unwrap();
+        assert_eq!(route.table_id, Some(254));
+
+        let route: RouteEntry = serde_yaml::from_str(
+            r#"---
+            destination: 198.51.100.0/24
+            table-id: "100""#,
+        )
+        .unwrap();
+        assert_eq!(route.table_id, Some(100));
+
+        assert!(serde_yaml::from_str::<RouteEntry>(
+            r"---
+            destination: 198.51.100.0/24
+            table-id: nipart_not_exist",
+        )
+        .is_err());
+    }
+}
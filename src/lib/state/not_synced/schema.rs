@@ -0,0 +1,522 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::{InterfaceState, InterfaceType, NetworkState};
+
+const JSON_SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+// Interface specific sections are keyed by these names, their content is
+// not described in detail yet.
+const IFACE_SECTION_KEYS: [&str; 18] = [
+    "bridge",
+    "ethernet",
+    "hsr",
+    "infiniband",
+    "ipsec",
+    "link-aggregation",
+    "loopback",
+    "mac-vlan",
+    "mac-vtap",
+    "macsec",
+    "patch",
+    "dpdk",
+    "veth",
+    "vlan",
+    "vrf",
+    "vxlan",
+    "xfrm",
+    "ovs-db",
+];
+
+impl NetworkState {
+    /// Generate [JSON Schema](https://json-schema.org) of [NetworkState] for
+    /// editor to validate and complete network state YAML/JSON files.
+    /// Properties specific to interface type are only described as object.
+    pub fn json_schema() -> Value {
+        json!({
+            "$schema": JSON_SCHEMA_DRAFT,
+            "title": "NetworkState",
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "description": {"type": "string"},
+                "hostname": running_config_schema(json!({"type": "string"})),
+                "dns-resolver": running_config_schema(dns_schema()),
+                "route-rules": running_config_schema(array_schema(
+                    route_rule_schema()
+                )),
+                "routes": running_config_schema(array_schema(route_schema())),
+                "interfaces": array_schema(iface_schema()),
+                "ovs-db": {"type": "object"},
+                "ovn": {"type": "object"},
+            },
+        })
+    }
+}
+
+fn iface_schema() -> Value {
+    let mut props = props_of(&[
+        ("name", json!({"type": "string"})),
+        ("profile-name", json!({"type": "string"})),
+        ("description", json!({"type": "string"})),
+        (
+            "type",
+            enum_schema(&[
+                InterfaceType::Bond,
+                InterfaceType::LinuxBridge,
+                InterfaceType::Dummy,
+                InterfaceType::Ethernet,
+                InterfaceType::Gre,
+                InterfaceType::GreTap,
+                InterfaceType::Hsr,
+                InterfaceType::Loopback,
+                InterfaceType::IpVlan,
+                InterfaceType::MacVlan,
+                InterfaceType::MacVtap,
+                InterfaceType::OvsBridge,
+                InterfaceType::OvsInterface,
+                InterfaceType::Veth,
+                InterfaceType::Vlan,
+                InterfaceType::Vrf,
+                InterfaceType::Vxlan,
+                InterfaceType::InfiniBand,
+                InterfaceType::Tun,
+                InterfaceType::MacSec,
+                InterfaceType::Ipsec,
+                InterfaceType::Xfrm,
+                InterfaceType::Wireguard,
+                InterfaceType::Unknown,
+            ]),
+        ),
+        (
+            "state",
+            enum_schema(&[
+                InterfaceState::Up,
+                InterfaceState::Down,
+                InterfaceState::Absent,
+                InterfaceState::Unknown,
+                InterfaceState::Ignore,
+            ]),
+        ),
+        ("identifier", json!({"enum": ["name", "mac-address"]})),
+        ("mac-address", json!({"type": "string"})),
+        ("permanent-mac-address", json!({"type": "string"})),
+        (
+            "mtu",
+            json!({"anyOf": [uint_schema(), {"enum": ["auto", "inherit"]}]}),
+        ),
+        ("min-mtu", uint_schema()),
+        ("max-mtu", uint_schema()),
+        (
+            "wait-ip",
+            json!({"enum": ["any", "ipv4", "ipv6", "ipv4+ipv6"]}),
+        ),
+        ("wait-carrier", uint_schema()),
+        ("ipv4", ip_schema(false)),
+        ("ipv6", ip_schema(true)),
+        ("controller", json!({"type": "string"})),
+        ("accept-all-mac-addresses", bool_schema()),
+        ("copy-mac-from", json!({"type": "string"})),
+        ("copy-ip-config-from", json!({"type": "string"})),
+        ("copy-ip-config-disable-source", bool_schema()),
+        ("rename-to", json!({"type": "string"})),
+        ("autoconnect-priority", int_schema()),
+        ("mptcp", json!({"type": "object"})),
+        ("802.1x", json!({"type": "object"})),
+        ("lldp", json!({"type": "object"})),
+        ("ethtool", json!({"type": "object"})),
+        ("dispatch", json!({"type": "object"})),
+    ]);
+    for key in IFACE_SECTION_KEYS {
+        props.insert(key.to_string(), json!({"type": ["object", "null"]}));
+    }
+    json!({
+        "type": "object",
+        "required": ["name"],
+        "properties": props,
+    })
+}
+
+fn ip_schema(is_ipv6: bool) -> Value {
+    let mut props = props_of(&[
+        ("enabled", bool_schema()),
+        ("dhcp", bool_schema()),
+        (
+            "address",
+            array_schema(json!({
+                "type": "object",
+                "required": ["ip", "prefix-length"],
+                "additionalProperties": false,
+                "properties": {
+                    "ip": {"type": "string"},
+                    "prefix-length": uint_schema(),
+                    "mptcp-flags": array_schema(json!({"type": "string"})),
+                    "valid-life-time": {"type": "string"},
+                    "preferred-life-time": {"type": "string"},
+                },
+            })),
+        ),
+        ("auto-dns", bool_schema()),
+        ("auto-gateway", bool_schema()),
+        ("auto-routes", bool_schema()),
+        ("auto-route-table-id", uint_schema()),
+        ("auto-route-metric", uint_schema()),
+        ("allow-extra-address", bool_schema()),
+        ("dhcp-send-hostname", bool_schema()),
+        ("dhcp-custom-hostname", json!({"type": "string"})),
+        ("forwarding", bool_schema()),
+    ]);
+    let family_props = if is_ipv6 {
+        props_of(&[
+            ("autoconf", bool_schema()),
+            ("dhcp-duid", json!({"type": "string"})),
+            ("addr-gen-mode", json!({"type": "string"})),
+            ("stable-secret", json!({"type": "string"})),
+            ("token", json!({"type": "string"})),
+            ("dad-transmits", uint_schema()),
+            (
+                "accept-ra",
+                json!({"enum": ["never", "enabled-unless-forwarding", "always"]}),
+            ),
+        ])
+    } else {
+        props_of(&[
+            ("dhcp-client-id", json!({"type": "string"})),
+            (
+                "rp-filter",
+                json!({"enum": ["disabled", "strict", "loose"]}),
+            ),
+            ("arp-ignore", json!({"enum": [0, 1, 2, 3, 8]})),
+            (
+                "arp-announce",
+                json!({"type": "integer", "minimum": 0, "maximum": 2}),
+            ),
+        ])
+    };
+    props.extend(family_props);
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "properties": props,
+    })
+}
+
+fn route_schema() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "state": {"enum": ["absent"]},
+            "destination": {"type": "string"},
+            "next-hop-interface": {"type": "string"},
+            "next-hop-address": {"type": "string"},
+            "metric": int_schema(),
+            "table-id": route_table_schema(),
+            "weight": uint_schema(),
+            "route-type": {"type": "string"},
+            "cwnd": uint_schema(),
+            "pref": {"type": "string"},
+            "scope": {"enum": ["global", "universe", "link", "host", "nowhere"]},
+            "on-link": {"type": "boolean"},
+            "next-hops": array_schema(json!({"type": "object"})),
+        },
+    })
+}
+
+fn route_rule_schema() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "family": {"enum": ["ipv4", "ipv6"]},
+            "state": {"enum": ["absent"]},
+            "ip-from": {"type": "string"},
+            "ip-to": {"type": "string"},
+            "priority": int_schema(),
+            "route-table": route_table_schema(),
+            "fwmark": uint_schema(),
+            "fwmask": uint_schema(),
+            "action": {"type": "string"},
+            "iif": {"type": "string"},
+            "suppress-prefix-length": uint_schema(),
+        },
+    })
+}
+
+fn dns_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "server": array_schema(json!({"type": "string"})),
+            "search": array_schema(json!({"type": "string"})),
+            "options": array_schema(json!({"type": "string"})),
+            "per-interface": array_schema(json!({
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["name"],
+                "properties": {
+                    "name": {"type": "string"},
+                    "server": array_schema(json!({"type": "string"})),
+                    "search": array_schema(json!({"type": "string"})),
+                },
+            })),
+        },
+    })
+}
+
+// For section holding `running` and `config` properties.
+fn running_config_schema(item: Value) -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "running": item.clone(),
+            "config": item,
+        },
+    })
+}
+
+fn array_schema(item: Value) -> Value {
+    json!({"type": "array", "items": item})
+}
+
+// Nipart also accept string for boolean, e.g. `yes`, `off`, `1`.
+fn bool_schema() -> Value {
+    json!({"type": ["boolean", "string", "integer"]})
+}
+
+// Nipart also accept integer in string format.
+fn uint_schema() -> Value {
+    json!({"anyOf": [
+        {"type": "integer", "minimum": 0},
+        {"type": "string", "pattern": "^[0-9]+$"},
+    ]})
+}
+
+// Route table could also be referred by name defined in `rt_tables`.
+fn route_table_schema() -> Value {
+    json!({"anyOf": [uint_schema(), {"type": "string"}]})
+}
+
+fn int_schema() -> Value {
+    json!({"anyOf": [
+        {"type": "integer"},
+        {"type": "string", "pattern": "^-?[0-9]+$"},
+    ]})
+}
+
+fn enum_schema<T: Serialize>(variants: &[T]) -> Value {
+    let values: Vec<Value> = variants
+        .iter()
+        .filter_map(|v| serde_json::to_value(v).ok())
+        .collect();
+    json!({ "enum": values })
+}
+
+fn props_of(props: &[(&str, Value)]) -> Map<String, Value> {
+    props
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::NetworkState;
+
+    // Minimal validator covering the JSON schema keywords used by
+    // NetworkState::json_schema().
+    fn is_valid(schema: &Value, value: &Value) -> bool {
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Value::String(t) => vec![t.as_str()],
+                Value::Array(t) => {
+                    t.iter().filter_map(|t| t.as_str()).collect()
+                }
+                _ => panic!("Unsupported type {types}"),
+            };
+            if !types.iter().any(|t| is_type(t, value)) {
+                return false;
+            }
+        }
+        if let Some(Value::Array(values)) = schema.get("enum") {
+            if !values.contains(value) {
+                return false;
+            }
+        }
+        if let Some(Value::Array(schemas)) = schema.get("anyOf") {
+            if !schemas.iter().any(|s| is_valid(s, value)) {
+                return false;
+            }
+        }
+        if let Some(min) = schema.get("minimum").and_then(|m| m.as_i64()) {
+            if value.as_i64().map(|v| v < min).unwrap_or_default() {
+                return false;
+            }
+        }
+        if let Some(max) = schema.get("maximum").and_then(|m| m.as_i64()) {
+            if value.as_i64().map(|v| v > max).unwrap_or_default() {
+                return false;
+            }
+        }
+        if let (Some(pattern), Some(v)) = (
+            schema.get("pattern").and_then(|p| p.as_str()),
+            value.as_str(),
+        ) {
+            let digits = match pattern {
+                "^[0-9]+$" => v,
+                "^-?[0-9]+$" => v.strip_prefix('-').unwrap_or(v),
+                _ => panic!("Unsupported pattern {pattern}"),
+            };
+            if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit())
+            {
+                return false;
+            }
+        }
+        if let (Some(item_schema), Some(items)) =
+            (schema.get("items"), value.as_array())
+        {
+            if !items.iter().all(|i| is_valid(item_schema, i)) {
+                return false;
+            }
+        }
+        if let Some(obj) = value.as_object() {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                if !required
+                    .iter()
+                    .filter_map(|r| r.as_str())
+                    .all(|r| obj.contains_key(r))
+                {
+                    return false;
+                }
+            }
+            let props = schema.get("properties").and_then(|p| p.as_object());
+            for (key, prop_value) in obj {
+                match props.and_then(|p| p.get(key)) {
+                    Some(prop_schema) => {
+                        if !is_valid(prop_schema, prop_value) {
+                            return false;
+                        }
+                    }
+                    None => {
+                        if schema.get("additionalProperties")
+                            == Some(&Value::Bool(false))
+                        {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    fn is_type(schema_type: &str, value: &Value) -> bool {
+        match schema_type {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "null" => value.is_null(),
+            _ => panic!("Unsupported type {schema_type}"),
+        }
+    }
+
+    fn validate_yaml(yaml: &str) -> bool {
+        is_valid(
+            &NetworkState::json_schema(),
+            &serde_yaml::from_str::<Value>(yaml).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_json_schema_valid_state() {
+        let yaml = r"---
+            dns-resolver:
+              config:
+                server:
+                - 192.0.2.1
+            routes:
+              config:
+              - destination: 198.51.100.0/24
+                next-hop-interface: eth1
+                metric: 100
+                table-id: 254
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              mtu: 1500
+              ipv4:
+                enabled: true
+                dhcp: false
+                address:
+                - ip: 192.0.2.2
+                  prefix-length: 24
+              ipv6:
+                enabled: true
+                autoconf: true
+                accept-ra: always
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: active-backup
+                port:
+                - eth1";
+        // Make sure the state is valid for nipart also
+        NetworkState::new_from_yaml(yaml).unwrap();
+
+        assert!(validate_yaml(yaml));
+    }
+
+    #[test]
+    fn test_json_schema_invalid_state() {
+        for yaml in [
+            // Unknown top level property
+            r"---
+            interface:
+            - name: eth1",
+            // Invalid interface state
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: upp",
+            // Interface name is required
+            r"---
+            interfaces:
+            - type: ethernet
+              state: up",
+            // IPv6 only property in IPv4 section
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              ipv4:
+                enabled: true
+                accept-ra: always",
+            // Negative prefix length
+            r"---
+            interfaces:
+            - name: eth1
+              ipv4:
+                address:
+                - ip: 192.0.2.2
+                  prefix-length: -1",
+            // Unknown route property
+            r"---
+            routes:
+              config:
+              - destination: 198.51.100.0/24
+                via: 192.0.2.1",
+        ] {
+            assert!(!validate_yaml(yaml));
+        }
+    }
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    state::not_synced::log_warn, InterfaceType, MergedInterface,
+    MergedInterfaces, NipartError,
+};
+
+impl MergedInterfaces {
+    // Nipart cannot change interface of unsupported type, hence desired
+    // interface resolved to `InterfaceType::Unknown` is preserved as it is in
+    // current instead of being applied or removed.
+    pub(crate) fn preserve_unknown_type_ifaces(
+        &mut self,
+    ) -> Result<(), NipartError> {
+        for merged_iface in self.kernel_ifaces.values_mut().filter(|i| {
+            i.is_desired()
+                && i.current.is_some()
+                && i.merged.iface_type() == InterfaceType::Unknown
+        }) {
+            log_warn(format!(
+                "Preserving interface {} as it is, changing interface of \
+                unsupported type is not allowed",
+                merged_iface.merged.name()
+            ));
+            *merged_iface =
+                MergedInterface::new(None, merged_iface.current.clone())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MergedNetworkState, NetworkState};
+
+    fn gen_current() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 1500
+            - name: foo1
+              type: unknown
+              state: up
+              mtu: 1500",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unknown_type_iface_untouched_by_unrelated_change() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 9000",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        assert!(merged.interfaces.kernel_ifaces["dummy1"].is_changed());
+        let foo1 = &merged.interfaces.kernel_ifaces["foo1"];
+        assert!(foo1.for_apply.is_none());
+        assert!(!foo1.is_changed());
+    }
+
+    #[test]
+    fn test_desired_unknown_type_iface_preserved() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: foo1
+              state: up
+              mtu: 9000",
+        )
+        .unwrap();
+
+        let merged =
+            MergedNetworkState::new(desired, gen_current(), false, false)
+                .unwrap();
+
+        let foo1 = &merged.interfaces.kernel_ifaces["foo1"];
+        assert!(foo1.for_apply.is_none());
+        assert!(!foo1.is_changed());
+        assert_eq!(foo1.merged.base_iface().mtu, Some(1500));
+    }
+}
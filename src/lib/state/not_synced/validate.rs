@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ErrorKind, MergedNetworkState, NetworkState, NipartError};
+
+impl NetworkState {
+    /// Validate this network state as desired state without current network
+    /// state and without applying anything, useful for linting state files.
+    /// All the errors found are aggregated into single
+    /// [ErrorKind::InvalidArgument] error, one line for each.
+    pub fn validate(&self) -> Result<(), NipartError> {
+        let mut errors: Vec<String> = Vec::new();
+
+        for iface in self.interfaces.iter() {
+            let mut iface = iface.clone();
+            if let Err(e) = iface.sanitize(true) {
+                errors.push(format!(
+                    "interface {}/{}: {}",
+                    iface.name(),
+                    iface.iface_type(),
+                    e.msg
+                ));
+            }
+        }
+        if let Err(e) = self.routes.validate() {
+            errors.push(format!("routes: {}", e.msg));
+        }
+        for route in self.routes.config.as_deref().unwrap_or_default() {
+            if let Err(e) = route.clone().sanitize() {
+                errors.push(format!("route {route}: {}", e.msg));
+            }
+        }
+        for rule in self.rules.config.as_deref().unwrap_or_default() {
+            if let Err(e) = rule.clone().sanitize() {
+                errors.push(format!("route rule {rule}: {}", e.msg));
+            }
+        }
+        if let Some(dns) = self.dns.as_ref() {
+            if let Err(e) = dns.clone().sanitize() {
+                errors.push(format!("dns: {}", e.msg));
+            }
+        }
+
+        // The cross-section validations are done by merging with empty
+        // current state, only do so when every section is valid by itself,
+        // otherwise it will fail on error already reported.
+        if errors.is_empty() {
+            if let Err(e) = MergedNetworkState::new(
+                self.clone(),
+                NetworkState::new(),
+                true,
+                false,
+            ) {
+                errors.push(e.msg);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Found {} error(s) in network state:\n{}",
+                    errors.len(),
+                    errors.join("\n")
+                ),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, NetworkState};
+
+    #[test]
+    fn test_validate_valid_state() {
+        let state = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv4:
+                enabled: true
+                address:
+                - ip: 192.0.2.10
+                  prefix-length: 24
+            routes:
+              config:
+              - destination: 198.51.100.0/24
+                next-hop-interface: dummy1
+                next-hop-address: 192.0.2.1",
+        )
+        .unwrap();
+
+        state.validate().unwrap();
+    }
+
+    #[test]
+    fn test_validate_report_all_errors() {
+        let state = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: balance-rr
+                port:
+                - eth1
+                - eth2
+                ports-config:
+                - name: eth1
+                  queue-id: 2
+                - name: eth2
+                  queue-id: 2
+            routes:
+              config:
+              - destination: 198.51.100.0/24
+                next-hop-interface: eth1
+                pref: high",
+        )
+        .unwrap();
+
+        let e = state.validate().unwrap_err();
+
+        assert_eq!(e.kind, ErrorKind::InvalidArgument);
+        assert!(e.msg.starts_with("Found 2 error(s) in network state:"));
+        assert!(e.msg.contains("interface bond99/bond: "));
+        assert!(e.msg.contains("route destination: 198.51.100.0/24"));
+    }
+}
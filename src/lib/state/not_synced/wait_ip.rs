@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::net::IpAddr;
+
+use crate::{
+    state::ip::is_ipv6_unicast_link_local, BaseInterface, ErrorKind,
+    MergedNetworkState, NetworkState, NipartError, WaitIp,
+};
+
+impl MergedNetworkState {
+    // Raise VerificationError if any interface with `wait-ip` defined has
+    // not got IP address for requested IP family yet.
+    pub(crate) fn verify_wait_ip(
+        &self,
+        current: &NetworkState,
+    ) -> Result<(), NipartError> {
+        for apply_iface in self
+            .interfaces
+            .kernel_ifaces
+            .values()
+            .filter_map(|i| i.for_apply.as_ref())
+            .filter(|i| i.is_up())
+        {
+            let wait_ip = match apply_iface.base_iface().wait_ip {
+                Some(w) => w,
+                None => continue,
+            };
+            let cur_iface = current
+                .interfaces
+                .kernel_ifaces
+                .get(apply_iface.name())
+                .map(|i| i.base_iface());
+            let has_ipv4 = cur_iface.map(has_ipv4_addr).unwrap_or_default();
+            let has_ipv6 = cur_iface.map(has_ipv6_addr).unwrap_or_default();
+
+            let missing = match wait_ip {
+                WaitIp::Any if !has_ipv4 && !has_ipv6 => "IPv4 or IPv6",
+                WaitIp::Ipv4 if !has_ipv4 => "IPv4",
+                WaitIp::Ipv6 if !has_ipv6 => "IPv6",
+                WaitIp::Ipv4AndIpv6 if !has_ipv4 && !has_ipv6 => {
+                    "IPv4 and IPv6"
+                }
+                WaitIp::Ipv4AndIpv6 if !has_ipv4 => "IPv4",
+                WaitIp::Ipv4AndIpv6 if !has_ipv6 => "IPv6",
+                _ => continue,
+            };
+            return Err(NipartError::new(
+                ErrorKind::VerificationError,
+                format!(
+                    "Interface {} has no {missing} address while \
+                    wait-ip: {wait_ip} is requested",
+                    apply_iface.name()
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn has_ipv4_addr(base_iface: &BaseInterface) -> bool {
+    base_iface
+        .ipv4
+        .as_ref()
+        .and_then(|i| i.addresses.as_ref())
+        .map(|addrs| !addrs.is_empty())
+        .unwrap_or_default()
+}
+
+// IPv6 link local address does not count
+fn has_ipv6_addr(base_iface: &BaseInterface) -> bool {
+    base_iface
+        .ipv6
+        .as_ref()
+        .and_then(|i| i.addresses.as_ref())
+        .map(|addrs| {
+            addrs.iter().any(|a| match a.ip {
+                IpAddr::V6(ip) => !is_ipv6_unicast_link_local(&ip),
+                IpAddr::V4(_) => false,
+            })
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, MergedNetworkState, NetworkState};
+
+    fn gen_merged(wait_ip: &str) -> MergedNetworkState {
+        let desired = NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              wait-ip: {wait_ip}
+              ipv4:
+                enabled: true
+                dhcp: false
+                address:
+                - ip: 192.0.2.1
+                  prefix-length: 24
+              ipv6:
+                enabled: true
+                dhcp: false
+                autoconf: false
+                address:
+                - ip: 2001:db8::1
+                  prefix-length: 64"
+        ))
+        .unwrap();
+        MergedNetworkState::new(desired, NetworkState::default(), false, false)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_wait_ip_of_dummy_with_static_ipv4() {
+        let merged = gen_merged("ipv4");
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv4:
+                enabled: true
+                address:
+                - ip: 192.0.2.1
+                  prefix-length: 24",
+        )
+        .unwrap();
+
+        merged.verify_wait_ip(&current).unwrap();
+    }
+
+    #[test]
+    fn test_wait_ip_of_dummy_without_ip_yet() {
+        let merged = gen_merged("ipv4");
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv4:
+                enabled: true
+                address: []",
+        )
+        .unwrap();
+
+        let result = merged.verify_wait_ip(&current);
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::VerificationError);
+        }
+    }
+
+    #[test]
+    fn test_wait_ip_ipv4_and_ipv6_ignore_link_local() {
+        let merged = gen_merged("ipv4+ipv6");
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv4:
+                enabled: true
+                address:
+                - ip: 192.0.2.1
+                  prefix-length: 24
+              ipv6:
+                enabled: true
+                address:
+                - ip: fe80::1
+                  prefix-length: 64",
+        )
+        .unwrap();
+
+        let result = merged.verify_wait_ip(&current);
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::VerificationError);
+            assert!(e.msg.contains("IPv6"));
+        }
+    }
+}
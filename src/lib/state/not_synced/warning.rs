@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::cell::RefCell;
+
+use crate::{MergedNetworkState, NetworkState, NipartError};
+
+thread_local! {
+    // None means no collector started on this thread.
+    static WARNINGS: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+// Log the warning message and store it into the collector started by
+// [MergedNetworkState::new_with_warnings()] on current thread.
+pub(crate) fn log_warn(msg: String) {
+    log::warn!("{msg}");
+    WARNINGS.with(|w| {
+        if let Some(warnings) = w.borrow_mut().as_mut() {
+            warnings.push(msg);
+        }
+    });
+}
+
+impl MergedNetworkState {
+    /// Similar to [MergedNetworkState::new()], but also return the warnings
+    /// raised when sanitizing and merging network states.
+    pub fn new_with_warnings(
+        desired: NetworkState,
+        current: NetworkState,
+        gen_conf_mode: bool,
+        memory_only: bool,
+    ) -> Result<(Self, Vec<String>), NipartError> {
+        WARNINGS.with(|w| *w.borrow_mut() = Some(Vec::new()));
+        let result = Self::new(desired, current, gen_conf_mode, memory_only);
+        let warnings =
+            WARNINGS.with(|w| w.borrow_mut().take()).unwrap_or_default();
+        result.map(|state| (state, warnings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ErrorKind, MergedNetworkState, NetworkState};
+
+    fn merge_with_warnings(
+        desired_yaml: &str,
+    ) -> Result<(MergedNetworkState, Vec<String>), crate::NipartError> {
+        MergedNetworkState::new_with_warnings(
+            NetworkState::new_from_yaml(desired_yaml).unwrap(),
+            NetworkState::new(),
+            false,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_warning_of_ignored_desired_mac() {
+        let (_, warnings) = merge_with_warnings(
+            r"---
+            interfaces:
+            - name: vrf0
+              type: vrf
+              state: up
+              mac-address: 00:23:45:67:89:1A
+              vrf:
+                port: []
+                route-table-id: 100",
+        )
+        .unwrap();
+
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Ignoring MAC address"));
+        assert!(warnings[0].contains("vrf0"));
+    }
+
+    #[test]
+    fn test_warning_of_sanitized_route_destination() {
+        let (_, warnings) = merge_with_warnings(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv4:
+                enabled: true
+                address:
+                - ip: 198.51.100.2
+                  prefix-length: 24
+            routes:
+              config:
+              - destination: 198.51.100.1/24
+                next-hop-interface: dummy1",
+        )
+        .unwrap();
+
+        assert!(warnings.iter().any(|w| w
+            == "Route destination 198.51.100.1/24 sanitized to \
+            198.51.100.0/24"));
+    }
+
+    #[test]
+    fn test_bond_mac_restricted_mode_with_mac_not_warning() {
+        // MAC address in MAC restricted bond mode is rejected instead of
+        // being ignored with a warning.
+        let result = merge_with_warnings(
+            r"---
+            interfaces:
+            - name: bond99
+              type: bond
+              state: up
+              mac-address: 00:23:45:67:89:1A
+              link-aggregation:
+                mode: active-backup
+                options:
+                  fail_over_mac: active",
+        );
+        assert_eq!(result.unwrap_err().kind, ErrorKind::InvalidArgument);
+
+        // No warning leaked from previous collection
+        let (_, warnings) = merge_with_warnings(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up",
+        )
+        .unwrap();
+        assert!(warnings.is_empty());
+    }
+}
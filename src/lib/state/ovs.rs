@@ -9,10 +9,21 @@ use crate::{ErrorKind, MergedOvnConfiguration, NipartError};
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[non_exhaustive]
 #[serde(deny_unknown_fields)]
+/// The global configurations of `Open_vSwitch` table in OVSDB.
+/// Example yaml output of [crate::NetworkState] enabling hardware offload:
+/// ```yml
+/// ovs-db:
+///   other_config:
+///     hw-offload: "true"
+/// ```
+/// Some keys like `hw-offload` or `dpdk-init` only take effect after
+/// `ovs-vswitchd` restarted.
 pub struct OvsDbGlobalConfig {
     #[serde(
         skip_serializing_if = "Option::is_none",
-        serialize_with = "show_as_ordered_map"
+        serialize_with = "show_as_ordered_map",
+        deserialize_with = "deserialize_ovsdb_map",
+        default
     )]
     // When the value been set as None, specified key will be removed instead
     // of merging.
@@ -21,11 +32,32 @@ pub struct OvsDbGlobalConfig {
     pub external_ids: Option<HashMap<String, Option<String>>>,
     #[serde(
         skip_serializing_if = "Option::is_none",
-        serialize_with = "show_as_ordered_map"
+        serialize_with = "show_as_ordered_map",
+        deserialize_with = "deserialize_ovsdb_map",
+        default
     )]
     pub other_config: Option<HashMap<String, Option<String>>>,
 }
 
+// OVSDB store all values as string, convert boolean and integer to string.
+fn deserialize_ovsdb_map<'de, D>(
+    deserializer: D,
+) -> Result<Option<HashMap<String, Option<String>>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let v = serde_json::Value::deserialize(deserializer)?;
+    if v.is_null() {
+        Ok(None)
+    } else if v.is_object() {
+        Ok(Some(value_to_hash_map(&v)))
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "Expecting dict/HashMap, but got {v:?}"
+        )))
+    }
+}
+
 impl OvsDbGlobalConfig {
     pub(crate) const OVN_BRIDGE_MAPPINGS_KEY: &'static str =
         "ovn-bridge-mappings";
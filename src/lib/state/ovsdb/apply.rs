@@ -1,15 +1,82 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{ovsdb::db::OvsDbConnection, MergedNetworkState, NipartError};
+use crate::{
+    state::ovsdb::db::OvsDbConnection, Interface, MergedNetworkState,
+    NipartError, OvsBridgeOptions, OvsDpdkConfig,
+};
 
-pub(crate) fn ovsdb_apply(
+pub fn ovsdb_apply(
     merged_state: &MergedNetworkState,
 ) -> Result<(), NipartError> {
-    if merged_state.ovsdb.is_changed {
-        let mut cli = OvsDbConnection::new()?;
-        cli.apply_global_conf(&merged_state.ovsdb)
-    } else {
+    let br_opts = get_changed_ovs_bridge_options(merged_state);
+    let dpdk_confs = get_changed_ovs_iface_dpdk_confs(merged_state);
+    if !merged_state.ovsdb.is_changed
+        && br_opts.is_empty()
+        && dpdk_confs.is_empty()
+    {
         log::debug!("No OVSDB changes");
-        Ok(())
+        return Ok(());
+    }
+    let mut cli = OvsDbConnection::new()?;
+    if merged_state.ovsdb.is_changed {
+        cli.apply_global_conf(&merged_state.ovsdb)?;
+    }
+    for (br_name, opts) in br_opts {
+        cli.apply_ovs_bridge_options(br_name, opts)?;
+    }
+    for (iface_name, conf) in dpdk_confs {
+        cli.apply_ovs_iface_dpdk_options(iface_name, conf)?;
+    }
+    Ok(())
+}
+
+/// Whether OVS database need to be changed for specified
+/// [MergedNetworkState].
+pub fn ovsdb_is_changed(merged_state: &MergedNetworkState) -> bool {
+    merged_state.ovsdb.is_changed
+        || !get_changed_ovs_bridge_options(merged_state).is_empty()
+        || !get_changed_ovs_iface_dpdk_confs(merged_state).is_empty()
+}
+
+fn get_changed_ovs_bridge_options(
+    merged_state: &MergedNetworkState,
+) -> Vec<(&str, &OvsBridgeOptions)> {
+    let mut ret = Vec::new();
+    for iface in merged_state
+        .interfaces
+        .iter()
+        .filter(|i| i.is_desired() && !i.merged.is_absent())
+    {
+        if let Some(Interface::OvsBridge(br_iface)) = iface.for_apply.as_ref() {
+            if let Some(opts) = br_iface
+                .bridge
+                .as_ref()
+                .and_then(|b| b.options.as_ref())
+                .filter(|o| o.fail_mode.is_some() || o.datapath.is_some())
+            {
+                ret.push((br_iface.base.name.as_str(), opts));
+            }
+        }
+    }
+    ret
+}
+
+fn get_changed_ovs_iface_dpdk_confs(
+    merged_state: &MergedNetworkState,
+) -> Vec<(&str, &OvsDpdkConfig)> {
+    let mut ret = Vec::new();
+    for iface in merged_state
+        .interfaces
+        .iter()
+        .filter(|i| i.is_desired() && !i.merged.is_absent())
+    {
+        if let Some(Interface::OvsInterface(ovs_iface)) =
+            iface.for_apply.as_ref()
+        {
+            if let Some(conf) = ovs_iface.dpdk.as_ref() {
+                ret.push((ovs_iface.base.name.as_str(), conf));
+            }
+        }
     }
+    ret
 }
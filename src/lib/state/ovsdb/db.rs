@@ -7,7 +7,8 @@ use serde_json::{Map, Value};
 use super::json_rpc::OvsDbJsonRpc;
 
 use crate::{
-    ErrorKind, MergedOvsDbGlobalConfig, NipartError, OvsDbGlobalConfig,
+    ErrorKind, MergedOvsDbGlobalConfig, NipartError, OvsBridgeOptions,
+    OvsDbGlobalConfig, OvsDpdkConfig,
 };
 
 const OVS_DB_NAME: &str = "Open_vSwitch";
@@ -205,6 +206,7 @@ impl OvsDbConnection {
                 "mcast_snooping_enable",
                 "fail_mode",
                 "datapath_type",
+                "datapath_id",
             ],
         )
     }
@@ -273,6 +275,188 @@ impl OvsDbConnection {
         )?;
         Ok(())
     }
+
+    // Update `fail_mode` and `datapath_type` column of existing OVS bridge.
+    pub(crate) fn apply_ovs_bridge_options(
+        &mut self,
+        br_name: &str,
+        opts: &OvsBridgeOptions,
+    ) -> Result<(), NipartError> {
+        let row = ovs_bridge_options_row(opts);
+        if row.is_empty() {
+            return Ok(());
+        }
+        let update = OvsDbUpdate {
+            table: "Bridge".to_string(),
+            conditions: vec![OvsDbCondition {
+                column: "name".to_string(),
+                function: "==".to_string(),
+                value: Value::String(br_name.to_string()),
+            }],
+            row,
+        };
+        let reply = self.rpc.exec(
+            "transact",
+            &Value::Array(vec![
+                Value::String(OVS_DB_NAME.to_string()),
+                update.to_value(),
+            ]),
+        )?;
+        if reply
+            .as_array()
+            .and_then(|r| r.first())
+            .and_then(|r| r.get("count"))
+            .and_then(|c| c.as_u64())
+            == Some(0)
+        {
+            log::warn!(
+                "OVS bridge {br_name} not found in OVS database, ignoring \
+                its fail-mode and datapath options"
+            );
+        }
+        Ok(())
+    }
+
+    // Update DPDK settings in `options` and `other_config` column of
+    // existing OVS interface, other keys of these columns are untouched.
+    pub(crate) fn apply_ovs_iface_dpdk_options(
+        &mut self,
+        iface_name: &str,
+        conf: &OvsDpdkConfig,
+    ) -> Result<(), NipartError> {
+        let mutate = OvsDbMutate {
+            table: "Interface".to_string(),
+            conditions: vec![OvsDbCondition {
+                column: "name".to_string(),
+                function: "==".to_string(),
+                value: Value::String(iface_name.to_string()),
+            }],
+            mutations: ovs_dpdk_mutations(conf),
+        };
+        if mutate.mutations.is_empty() {
+            return Ok(());
+        }
+        let reply = self.rpc.exec(
+            "transact",
+            &Value::Array(vec![
+                Value::String(OVS_DB_NAME.to_string()),
+                mutate.to_value(),
+            ]),
+        )?;
+        if reply
+            .as_array()
+            .and_then(|r| r.first())
+            .and_then(|r| r.get("count"))
+            .and_then(|c| c.as_u64())
+            == Some(0)
+        {
+            log::warn!(
+                "OVS interface {iface_name} not found in OVS database, \
+                ignoring its DPDK options"
+            );
+        }
+        Ok(())
+    }
+}
+
+// Empty string means unset.
+pub(crate) fn ovs_bridge_options_row(
+    opts: &OvsBridgeOptions,
+) -> HashMap<String, Value> {
+    let mut row = HashMap::new();
+    if let Some(fail_mode) = opts.fail_mode.as_deref() {
+        row.insert(
+            "fail_mode".to_string(),
+            if fail_mode.is_empty() {
+                Value::Array(vec![
+                    Value::String("set".to_string()),
+                    Value::Array(Vec::new()),
+                ])
+            } else {
+                Value::String(fail_mode.to_string())
+            },
+        );
+    }
+    if let Some(datapath) = opts.datapath.as_deref() {
+        row.insert(
+            "datapath_type".to_string(),
+            Value::String(datapath.to_string()),
+        );
+    }
+    row
+}
+
+// The `insert` mutator does not override existing key of map, hence delete
+// all changed keys first.
+pub(crate) fn ovs_dpdk_mutations(conf: &OvsDpdkConfig) -> Vec<Value> {
+    let mut options: Vec<(&str, Option<String>)> = Vec::new();
+    let mut other_config: Vec<(&str, Option<String>)> = Vec::new();
+    if !conf.devargs.is_empty() {
+        options.push(("dpdk-devargs", Some(conf.devargs.clone())));
+    }
+    if let Some(n) = conf.rx_queue {
+        options.push(("n_rxq", Some(n.to_string())));
+    }
+    // Setting to 0 means removal
+    for (key, value) in [
+        ("n_rxq_desc", conf.n_rxq_desc),
+        ("n_txq_desc", conf.n_txq_desc),
+    ] {
+        if let Some(n) = value {
+            options.push((key, Some(n.to_string()).filter(|_| n != 0)));
+        }
+    }
+    // Setting to empty string means removal
+    if let Some(affinity) = conf.pmd_rxq_affinity.as_deref() {
+        other_config.push((
+            "pmd-rxq-affinity",
+            Some(affinity.to_string()).filter(|a| !a.is_empty()),
+        ));
+    }
+
+    let mut ret = Vec::new();
+    for (column, changes) in
+        [("options", options), ("other_config", other_config)]
+    {
+        if changes.is_empty() {
+            continue;
+        }
+        ret.push(Value::Array(vec![
+            Value::String(column.to_string()),
+            Value::String("delete".to_string()),
+            Value::Array(vec![
+                Value::String("set".to_string()),
+                Value::Array(
+                    changes
+                        .iter()
+                        .map(|(k, _)| Value::String(k.to_string()))
+                        .collect(),
+                ),
+            ]),
+        ]));
+        let new_entries: Vec<Value> = changes
+            .iter()
+            .filter_map(|(k, v)| {
+                v.as_ref().map(|v| {
+                    Value::Array(vec![
+                        Value::String(k.to_string()),
+                        Value::String(v.to_string()),
+                    ])
+                })
+            })
+            .collect();
+        if !new_entries.is_empty() {
+            ret.push(Value::Array(vec![
+                Value::String(column.to_string()),
+                Value::String("insert".to_string()),
+                Value::Array(vec![
+                    Value::String("map".to_string()),
+                    Value::Array(new_entries),
+                ]),
+            ]));
+        }
+    }
+    ret
 }
 
 #[derive(Debug, Default)]
@@ -419,3 +603,27 @@ impl OvsDbUpdate {
         Value::Object(ret)
     }
 }
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct OvsDbMutate {
+    pub(crate) table: String,
+    pub(crate) conditions: Vec<OvsDbCondition>,
+    // Each mutation is array of column, mutator and value
+    pub(crate) mutations: Vec<Value>,
+}
+
+impl OvsDbMutate {
+    fn to_value(&self) -> Value {
+        let mut ret = Map::new();
+        ret.insert("op".to_string(), Value::String("mutate".to_string()));
+        ret.insert("table".to_string(), Value::String(self.table.clone()));
+        let condition_values: Vec<Value> =
+            self.conditions.iter().map(|c| c.to_value()).collect();
+        ret.insert("where".to_string(), Value::Array(condition_values));
+        ret.insert(
+            "mutations".to_string(),
+            Value::Array(self.mutations.clone()),
+        );
+        Value::Object(ret)
+    }
+}
@@ -7,6 +7,6 @@ mod json_rpc;
 mod show;
 
 pub(crate) use self::db::DEFAULT_OVS_DB_SOCKET_PATH;
-pub(crate) use apply::ovsdb_apply;
-pub(crate) use show::ovsdb_is_running;
-pub(crate) use show::ovsdb_retrieve;
+pub use apply::{ovsdb_apply, ovsdb_is_changed};
+pub use show::ovsdb_is_running;
+pub use show::ovsdb_retrieve;
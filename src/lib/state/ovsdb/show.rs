@@ -10,13 +10,13 @@ use crate::{
     NipartError, OvsBridgeBondConfig, OvsBridgeBondMode,
     OvsBridgeBondPortConfig, OvsBridgeConfig, OvsBridgeInterface,
     OvsBridgeOptions, OvsBridgePortConfig, OvsBridgeStpOptions,
-    OvsDbIfaceConfig, OvsDpdkConfig, OvsInterface, OvsPatchConfig,
-    UnknownInterface,
+    OvsDbIfaceConfig, OvsDpdkConfig, OvsInterface, OvsInterfaceType,
+    OvsPatchConfig, UnknownInterface,
 };
 
 use super::db::{parse_str_map, OvsDbConnection, OvsDbEntry};
 
-pub(crate) fn ovsdb_is_running() -> bool {
+pub fn ovsdb_is_running() -> bool {
     if let Ok(mut cli) = OvsDbConnection::new() {
         cli.check_connection()
     } else {
@@ -24,7 +24,7 @@ pub(crate) fn ovsdb_is_running() -> bool {
     }
 }
 
-pub(crate) fn ovsdb_retrieve() -> Result<NetworkState, NipartError> {
+pub fn ovsdb_retrieve() -> Result<NetworkState, NipartError> {
     let mut ret = NetworkState::new();
     let mut cli = OvsDbConnection::new()?;
     let ovsdb_ifaces = cli.get_ovs_ifaces()?;
@@ -90,6 +90,12 @@ fn parse_ovs_bridge_conf(
         }
     }
     ret.options = Some(parse_ovs_bridge_options(&ovsdb_br.options));
+    // The `datapath_id` is empty set when ovs-vswitchd is not running
+    if let Some(Value::String(v)) = ovsdb_br.options.get("datapath_id") {
+        if !v.is_empty() {
+            ret.datapath_id = Some(v.to_string());
+        }
+    }
     port_confs.sort_unstable_by(|a, b| {
         (a.bond.is_some(), a.name.as_str())
             .cmp(&(b.bond.is_some(), b.name.as_str()))
@@ -322,6 +328,8 @@ fn parse_ovs_iface_dpdk_conf(
                     conf.n_txq_desc = Some(i)
                 }
             }
+            conf.pmd_rxq_affinity =
+                ovsdb_iface.other_config.get("pmd-rxq-affinity").cloned();
             return Some(conf);
         }
     }
@@ -347,14 +355,20 @@ fn ovsdb_iface_to_nmstate(
 
     let mut iface = match ovsdb_iface.iface_type.as_str() {
         "system" => Interface::Unknown(Box::new(UnknownInterface::new())),
-        "internal" => Interface::OvsInterface(Box::new(OvsInterface::new())),
+        "internal" => {
+            let mut ovs_iface = OvsInterface::new();
+            ovs_iface.ovs_iface_type = Some(OvsInterfaceType::Internal);
+            Interface::OvsInterface(Box::new(ovs_iface))
+        }
         "patch" => {
             let mut ovs_iface = OvsInterface::new();
+            ovs_iface.ovs_iface_type = Some(OvsInterfaceType::Patch);
             ovs_iface.patch = parse_ovs_patch_conf(ovsdb_iface);
             Interface::OvsInterface(Box::new(ovs_iface))
         }
         "dpdk" => {
             let mut ovs_iface = OvsInterface::new();
+            ovs_iface.ovs_iface_type = Some(OvsInterfaceType::Dpdk);
             ovs_iface.dpdk = parse_ovs_iface_dpdk_conf(ovsdb_iface);
             // DPDK interface does not have kernel representative, the MTU is
             // set in ovsdb.
@@ -403,3 +417,173 @@ fn get_dpdk_mtu(ovsdb_iface: &OvsDbEntry) -> Option<u64> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::{json, Value};
+
+    use super::{parse_ovs_bridge_options, parse_ovs_iface_dpdk_conf};
+    use crate::state::ovsdb::db::{
+        ovs_bridge_options_row, ovs_dpdk_mutations, OvsDbEntry,
+    };
+    use crate::{ErrorKind, OvsBridgeOptions, OvsDpdkConfig};
+
+    // Simulate OVSDB `delete` and `insert` mutators on map column
+    fn mutate_map(map: &mut HashMap<String, String>, mutation: &Value) {
+        let value = &mutation[2][1];
+        match mutation[1].as_str() {
+            Some("delete") => {
+                for key in value.as_array().unwrap() {
+                    map.remove(key.as_str().unwrap());
+                }
+            }
+            Some("insert") => {
+                for kv in value.as_array().unwrap() {
+                    map.entry(kv[0].as_str().unwrap().to_string())
+                        .or_insert(kv[1].as_str().unwrap().to_string());
+                }
+            }
+            m => panic!("Unexpected mutator {m:?}"),
+        }
+    }
+
+    fn to_ovsdb_map(map: &HashMap<String, String>) -> Value {
+        json!([
+            "map",
+            map.iter()
+                .map(|(k, v)| json!([k, v]))
+                .collect::<Vec<Value>>()
+        ])
+    }
+
+    #[test]
+    fn test_ovs_dpdk_two_rxq_round_trip() {
+        let desired: OvsDpdkConfig = serde_json::from_value(json!({
+            "devargs": "0000:af:00.1",
+            "rx-queue": 2,
+            "pmd-rxq-affinity": "0:3,1:7",
+        }))
+        .unwrap();
+        let mut options: HashMap<String, String> = HashMap::from([
+            ("dpdk-devargs".to_string(), "0000:af:00.0".to_string()),
+            ("n_rxq".to_string(), "1".to_string()),
+            ("n_rxq_desc".to_string(), "1024".to_string()),
+        ]);
+        let mut other_config: HashMap<String, String> =
+            HashMap::from([("foo".to_string(), "bar".to_string())]);
+
+        for mutation in ovs_dpdk_mutations(&desired) {
+            match mutation[0].as_str() {
+                Some("options") => mutate_map(&mut options, &mutation),
+                Some("other_config") => {
+                    mutate_map(&mut other_config, &mutation)
+                }
+                c => panic!("Unexpected column {c:?}"),
+            }
+        }
+        assert_eq!(other_config.get("foo").map(String::as_str), Some("bar"));
+
+        let mut ovsdb_iface = OvsDbEntry {
+            name: "dpdk0".to_string(),
+            iface_type: "dpdk".to_string(),
+            other_config,
+            ..Default::default()
+        };
+        ovsdb_iface
+            .options
+            .insert("options".to_string(), to_ovsdb_map(&options));
+
+        let current = parse_ovs_iface_dpdk_conf(&ovsdb_iface).unwrap();
+        assert_eq!(current.devargs, "0000:af:00.1");
+        assert_eq!(current.rx_queue, Some(2));
+        assert_eq!(current.n_rxq_desc, Some(1024));
+        assert_eq!(current.pmd_rxq_affinity.as_deref(), Some("0:3,1:7"));
+    }
+
+    #[test]
+    fn test_ovs_dpdk_remove_desc_and_affinity() {
+        let desired: OvsDpdkConfig = serde_json::from_value(json!({
+            "devargs": "0000:af:00.1",
+            "n_rxq_desc": 0,
+            "pmd-rxq-affinity": "",
+        }))
+        .unwrap();
+
+        let mutations = ovs_dpdk_mutations(&desired);
+
+        assert_eq!(
+            mutations,
+            vec![
+                json!([
+                    "options",
+                    "delete",
+                    ["set", ["dpdk-devargs", "n_rxq_desc"]]
+                ]),
+                json!([
+                    "options",
+                    "insert",
+                    ["map", [["dpdk-devargs", "0000:af:00.1"]]]
+                ]),
+                json!([
+                    "other_config",
+                    "delete",
+                    ["set", ["pmd-rxq-affinity"]]
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ovs_bridge_secure_fail_mode_round_trip() {
+        let desired: OvsBridgeOptions = serde_json::from_value(json!({
+            "fail-mode": "secure",
+            "datapath-type": "system",
+        }))
+        .unwrap();
+        desired.sanitize("br0").unwrap();
+
+        // Simulate OVSDB `update` on bridge row
+        let mut ovsdb_opts: HashMap<String, Value> = HashMap::from([
+            ("fail_mode".to_string(), json!(["set", []])),
+            ("stp_enable".to_string(), json!(false)),
+        ]);
+        ovsdb_opts.extend(ovs_bridge_options_row(&desired));
+
+        let current = parse_ovs_bridge_options(&ovsdb_opts);
+        assert_eq!(
+            current.fail_mode.as_deref(),
+            Some(OvsBridgeOptions::FAIL_MODE_SECURE)
+        );
+        assert_eq!(
+            current.datapath.as_deref(),
+            Some(OvsBridgeOptions::DATAPATH_SYSTEM)
+        );
+    }
+
+    #[test]
+    fn test_ovs_bridge_unset_fail_mode() {
+        let mut desired = OvsBridgeOptions::new();
+        desired.fail_mode = Some(String::new());
+
+        let row = ovs_bridge_options_row(&desired);
+
+        assert_eq!(row.get("fail_mode"), Some(&json!(["set", []])));
+        assert_eq!(
+            parse_ovs_bridge_options(&row).fail_mode,
+            Some(String::new())
+        );
+    }
+
+    #[test]
+    fn test_ovs_bridge_invalid_fail_mode() {
+        let mut desired = OvsBridgeOptions::new();
+        desired.fail_mode = Some("drop".to_string());
+
+        assert_eq!(
+            desired.sanitize("br0").unwrap_err().kind,
+            ErrorKind::InvalidArgument
+        );
+    }
+}
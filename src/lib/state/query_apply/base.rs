@@ -55,6 +55,19 @@ impl BaseInterface {
         if self.profile_name.as_deref() == Some(self.name.as_str()) {
             self.profile_name = None;
         }
+        // The 802.1x authentication is done by wpa_supplicant which does not
+        // report its configuration back to kernel, hence not verifiable.
+        self.ieee8021x = None;
+        // The dispatch scripts are executed by daemon, not stored in kernel.
+        self.dispatch = None;
+        // The wait-ip is verified by MergedNetworkState::verify_wait_ip()
+        self.wait_ip = None;
+        // The wait-carrier is one-time action of apply.
+        self.wait_carrier = None;
+        // The rename is one-time action, not a property of interface.
+        self.rename_to = None;
+        // The autoconnect-priority is daemon metadata, not stored in kernel.
+        self.autoconnect_priority = None;
     }
 
     pub(crate) fn update(&mut self, other: &BaseInterface) {
@@ -116,6 +129,12 @@ impl BaseInterface {
         if other.wait_ip.is_some() {
             self.wait_ip = other.wait_ip;
         }
+        if other.wait_carrier.is_some() {
+            self.wait_carrier = other.wait_carrier;
+        }
+        if other.autoconnect_priority.is_some() {
+            self.autoconnect_priority = other.autoconnect_priority;
+        }
 
         if other.ipv4.is_some() {
             if let Some(ref other_ipv4) = other.ipv4 {
@@ -30,6 +30,9 @@ impl BondConfig {
             if let Some(port) = other.port.as_ref() {
                 self.port = Some(port.clone());
             }
+            if other.ad_info.is_some() {
+                self.ad_info.clone_from(&other.ad_info);
+            }
         }
     }
 }
@@ -5,6 +5,12 @@ use crate::DispatchConfig;
 impl DispatchConfig {
     // For current in verify, None means empty string
     pub(crate) fn sanitize_current_for_verify(&mut self) {
+        if self.pre_activation.is_none() {
+            self.pre_activation = Some(String::new());
+        }
+        if self.pre_deactivation.is_none() {
+            self.pre_deactivation = Some(String::new());
+        }
         if self.post_activation.is_none() {
             self.post_activation = Some(String::new());
         }
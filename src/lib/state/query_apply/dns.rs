@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{DnsState, ErrorKind, MergedDnsState, NipartError};
+use crate::{
+    DnsInterfaceConfig, DnsState, ErrorKind, MergedDnsState, NipartError,
+};
 
 impl MergedDnsState {
     pub(crate) fn is_purge(&self) -> bool {
@@ -8,6 +10,7 @@ impl MergedDnsState {
             && self.servers.is_empty()
             && self.searches.is_empty()
             && self.options.is_empty()
+            && self.per_interface.is_empty()
     }
 
     pub(crate) fn verify(&self, current: DnsState) -> Result<(), NipartError> {
@@ -90,6 +93,24 @@ impl MergedDnsState {
             ));
         }
 
+        let cur_per_iface: Vec<DnsInterfaceConfig> = current
+            .config
+            .as_ref()
+            .and_then(|c| c.per_interface.as_ref())
+            .cloned()
+            .unwrap_or_default();
+
+        if cur_per_iface != self.per_interface {
+            return Err(NipartError::new(
+                ErrorKind::VerificationError,
+                format!(
+                    "Failed to apply DNS config: desire per-interface \
+                    config {:?}, got {:?}",
+                    self.per_interface, cur_per_iface,
+                ),
+            ));
+        }
+
         Ok(())
     }
 }
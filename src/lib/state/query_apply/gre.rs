@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{GreConfig, GreInterface, GreTapInterface};
+
+impl GreInterface {
+    pub(crate) fn update_gre(&mut self, other: &GreInterface) {
+        if let Some(conf) = &mut self.gre {
+            conf.update(other.gre.as_ref());
+        } else {
+            self.gre.clone_from(&other.gre);
+        }
+    }
+}
+
+impl GreTapInterface {
+    pub(crate) fn update_gre_tap(&mut self, other: &GreTapInterface) {
+        if let Some(conf) = &mut self.gre {
+            conf.update(other.gre.as_ref());
+        } else {
+            self.gre.clone_from(&other.gre);
+        }
+    }
+}
+
+impl GreConfig {
+    fn update(&mut self, other: Option<&Self>) {
+        if let Some(other) = other {
+            if other.base_iface.is_some() {
+                self.base_iface.clone_from(&other.base_iface);
+            }
+            if other.local.is_some() {
+                self.local = other.local;
+            }
+            if other.remote.is_some() {
+                self.remote = other.remote;
+            }
+            if other.ttl.is_some() {
+                self.ttl = other.ttl;
+            }
+            if other.tos.is_some() {
+                self.tos = other.tos;
+            }
+            if other.key.is_some() {
+                self.key = other.key;
+                self.ikey = None;
+                self.okey = None;
+            } else if other.ikey.is_some() || other.okey.is_some() {
+                self.key = None;
+                if other.ikey.is_some() {
+                    self.ikey = other.ikey;
+                }
+                if other.okey.is_some() {
+                    self.okey = other.okey;
+                }
+            }
+        }
+    }
+}
@@ -24,6 +24,8 @@ impl Interface {
             iface.sanitize_desired_for_verify();
         } else if let Interface::Hsr(iface) = self {
             iface.sanitize_desired_for_verify();
+        } else if let Interface::Wireguard(iface) = self {
+            iface.sanitize_desired_for_verify();
         }
     }
 
@@ -68,13 +70,7 @@ impl Interface {
                 }
             }
 
-            Err(NipartError::new(
-                ErrorKind::VerificationError,
-                format!(
-                    "Verification failure: {reference} desire '{desire}', \
-                    current '{current}'"
-                ),
-            ))
+            Err(NipartError::new_verify_diff(reference, desire, current))
         } else {
             Ok(())
         }
@@ -153,6 +149,61 @@ impl Interface {
                     );
                 }
             }
+            Self::Wireguard(iface) => {
+                if let Self::Wireguard(other_iface) = other {
+                    iface.update_wireguard(other_iface);
+                } else {
+                    log::warn!(
+                        "Don't know how to update iface {:?} with {:?}",
+                        iface,
+                        other
+                    );
+                }
+            }
+            Self::Gre(iface) => {
+                if let Self::Gre(other_iface) = other {
+                    iface.update_gre(other_iface);
+                } else {
+                    log::warn!(
+                        "Don't know how to update iface {:?} with {:?}",
+                        iface,
+                        other
+                    );
+                }
+            }
+            Self::GreTap(iface) => {
+                if let Self::GreTap(other_iface) = other {
+                    iface.update_gre_tap(other_iface);
+                } else {
+                    log::warn!(
+                        "Don't know how to update iface {:?} with {:?}",
+                        iface,
+                        other
+                    );
+                }
+            }
+            Self::Tun(iface) => {
+                if let Self::Tun(other_iface) = other {
+                    iface.update_tun(other_iface);
+                } else {
+                    log::warn!(
+                        "Don't know how to update iface {:?} with {:?}",
+                        iface,
+                        other
+                    );
+                }
+            }
+            Self::IpVlan(iface) => {
+                if let Self::IpVlan(other_iface) = other {
+                    iface.update_ip_vlan(other_iface);
+                } else {
+                    log::warn!(
+                        "Don't know how to update iface {:?} with {:?}",
+                        iface,
+                        other
+                    );
+                }
+            }
             Self::MacVlan(iface) => {
                 if let Self::MacVlan(other_iface) = other {
                     iface.update_mac_vlan(other_iface);
@@ -252,7 +303,7 @@ impl Interface {
 }
 
 impl InterfaceType {
-    pub const SUPPORTED_LIST: [InterfaceType; 18] = [
+    pub const SUPPORTED_LIST: [InterfaceType; 23] = [
         InterfaceType::Bond,
         InterfaceType::LinuxBridge,
         InterfaceType::Dummy,
@@ -260,6 +311,7 @@ impl InterfaceType {
         InterfaceType::Veth,
         InterfaceType::MacVtap,
         InterfaceType::MacVlan,
+        InterfaceType::IpVlan,
         InterfaceType::OvsBridge,
         InterfaceType::OvsInterface,
         InterfaceType::Vlan,
@@ -271,5 +323,9 @@ impl InterfaceType {
         InterfaceType::Hsr,
         InterfaceType::Ipsec,
         InterfaceType::Xfrm,
+        InterfaceType::Tun,
+        InterfaceType::Gre,
+        InterfaceType::GreTap,
+        InterfaceType::Wireguard,
     ];
 }
@@ -82,6 +82,18 @@ impl InterfaceIpv4 {
             self.dhcp_custom_hostname
                 .clone_from(&other.dhcp_custom_hostname);
         }
+        if other.forwarding.is_some() {
+            self.forwarding = other.forwarding;
+        }
+        if other.rp_filter.is_some() {
+            self.rp_filter = other.rp_filter;
+        }
+        if other.arp_ignore.is_some() {
+            self.arp_ignore = other.arp_ignore;
+        }
+        if other.arp_announce.is_some() {
+            self.arp_announce = other.arp_announce;
+        }
     }
 }
 
@@ -123,6 +135,8 @@ impl InterfaceIpv6 {
             addrs.sort_unstable();
             addrs.dedup();
         }
+        // Kernel does not allow reading back the stable secret.
+        self.stable_secret = None;
     }
     pub(crate) fn update(&mut self, other: &Self) {
         if other.enabled_defined {
@@ -170,6 +184,18 @@ impl InterfaceIpv6 {
         if other.token.is_some() {
             self.token.clone_from(&other.token);
         }
+        if other.stable_secret.is_some() {
+            self.stable_secret.clone_from(&other.stable_secret);
+        }
+        if other.accept_ra.is_some() {
+            self.accept_ra = other.accept_ra;
+        }
+        if other.dad_transmits.is_some() {
+            self.dad_transmits = other.dad_transmits;
+        }
+        if other.forwarding.is_some() {
+            self.forwarding = other.forwarding;
+        }
         if other.dhcp_send_hostname.is_some() {
             self.dhcp_send_hostname = other.dhcp_send_hostname;
         }
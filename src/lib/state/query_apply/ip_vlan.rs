@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{IpVlanConfig, IpVlanInterface};
+
+impl IpVlanInterface {
+    pub(crate) fn update_ip_vlan(&mut self, other: &IpVlanInterface) {
+        if let Some(conf) = &mut self.ip_vlan {
+            conf.update(other.ip_vlan.as_ref());
+        } else {
+            self.ip_vlan.clone_from(&other.ip_vlan);
+        }
+    }
+}
+
+impl IpVlanConfig {
+    fn update(&mut self, other: Option<&Self>) {
+        if let Some(other) = other {
+            self.base_iface.clone_from(&other.base_iface);
+            self.mode = other.mode;
+            if other.flags.is_some() {
+                self.flags.clone_from(&other.flags);
+            }
+        }
+    }
+}
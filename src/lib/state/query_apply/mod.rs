@@ -5,12 +5,14 @@ mod bond;
 mod dispatch;
 mod dns;
 mod ethernet;
+mod gre;
 mod hostname;
 mod hsr;
 mod iface;
 mod infiniband;
 mod inter_ifaces;
 mod ip;
+mod ip_vlan;
 mod ipsec;
 mod linux_bridge;
 mod mac_vlan;
@@ -22,9 +24,11 @@ mod ovs;
 mod route;
 mod route_rule;
 mod sriov;
+mod tun;
 mod vlan;
 mod vrf;
 mod vxlan;
+mod wireguard;
 
 #[cfg(test)]
 pub(crate) use route::is_route_delayed_by_nm;
@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    state::json::get_json_value_difference, ErrorKind, MergedOvnConfiguration,
+    state::json::get_json_value_difference, MergedOvnConfiguration,
     NipartError, OvnConfiguration,
 };
 
@@ -48,13 +48,7 @@ impl MergedOvnConfiguration {
             &desired_value,
             &current_value,
         ) {
-            Err(NipartError::new(
-                ErrorKind::VerificationError,
-                format!(
-                    "Verification failure: {reference} desire '{desire}', \
-                    current '{current}'"
-                ),
-            ))
+            Err(NipartError::new_verify_diff(reference, desire, current))
         } else {
             Ok(())
         }
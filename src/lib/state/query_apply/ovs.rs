@@ -3,11 +3,10 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::{
-    state::json::get_json_value_difference, ErrorKind, Interface,
-    InterfaceState, InterfaceType, Interfaces, MergedInterfaces,
-    MergedOvsDbGlobalConfig, NetworkState, NipartError, OvsBridgeBondConfig,
-    OvsBridgeConfig, OvsBridgeInterface, OvsDbGlobalConfig, OvsDbIfaceConfig,
-    OvsInterface,
+    state::json::get_json_value_difference, Interface, InterfaceState,
+    InterfaceType, Interfaces, MergedInterfaces, MergedOvsDbGlobalConfig,
+    NetworkState, NipartError, OvsBridgeBondConfig, OvsBridgeConfig,
+    OvsBridgeInterface, OvsDbGlobalConfig, OvsDbIfaceConfig, OvsInterface,
 };
 
 impl MergedOvsDbGlobalConfig {
@@ -73,13 +72,7 @@ impl MergedOvsDbGlobalConfig {
             &desired_value,
             &current_value,
         ) {
-            Err(NipartError::new(
-                ErrorKind::VerificationError,
-                format!(
-                    "Verification failure: {reference} desire '{desire}', \
-                    current '{current}'"
-                ),
-            ))
+            Err(NipartError::new_verify_diff(reference, desire, current))
         } else {
             Ok(())
         }
@@ -120,6 +113,9 @@ impl OvsBridgeInterface {
 
 impl OvsInterface {
     pub(crate) fn update_ovs_iface(&mut self, other: &Self) {
+        if other.ovs_iface_type.is_some() {
+            self.ovs_iface_type = other.ovs_iface_type;
+        }
         if other.patch.is_some() {
             self.patch.clone_from(&other.patch);
         }
@@ -213,7 +209,7 @@ impl MergedInterfaces {
 }
 
 impl NetworkState {
-    pub(crate) fn isolate_ovn(&mut self) -> Result<(), NipartError> {
+    pub fn isolate_ovn(&mut self) -> Result<(), NipartError> {
         if let Some(ovn_maps_str) = self
             .ovsdb
             .as_mut()
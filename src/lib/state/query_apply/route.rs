@@ -118,8 +118,8 @@ impl MergedRoutes {
                 let mut rt2;
                 if rt.route_type.is_some() && !rt.is_ipv6() {
                     // In nispor, the IPv4 route with route type `Blackhole`,
-                    // `Unreachable`, `Prohibit` does not have the route oif
-                    // setting.
+                    // `Unreachable`, `Prohibit`, `Throw` does not have the
+                    // route oif setting.
                     rt2 = rt.clone();
                     rt2.next_hop_iface = None;
                     rt = &rt2
@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{TunConfig, TunInterface};
+
+impl TunInterface {
+    pub(crate) fn update_tun(&mut self, other: &TunInterface) {
+        if let Some(conf) = &mut self.tun {
+            conf.update(other.tun.as_ref());
+        } else {
+            self.tun.clone_from(&other.tun);
+        }
+    }
+}
+
+impl TunConfig {
+    fn update(&mut self, other: Option<&Self>) {
+        if let Some(other) = other {
+            if other.mode.is_some() {
+                self.mode = other.mode;
+            }
+            if other.owner.is_some() {
+                self.owner = other.owner;
+            }
+            if other.group.is_some() {
+                self.group = other.group;
+            }
+            if other.multi_queue.is_some() {
+                self.multi_queue = other.multi_queue;
+            }
+            if other.pi.is_some() {
+                self.pi = other.pi;
+            }
+            if other.vnet_hdr.is_some() {
+                self.vnet_hdr = other.vnet_hdr;
+            }
+        }
+    }
+}
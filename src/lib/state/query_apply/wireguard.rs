@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{WireguardConfig, WireguardInterface};
+
+impl WireguardInterface {
+    pub(crate) fn update_wireguard(&mut self, other: &WireguardInterface) {
+        if let Some(conf) = &mut self.wireguard {
+            conf.update(other.wireguard.as_ref());
+        } else {
+            self.wireguard.clone_from(&other.wireguard);
+        }
+    }
+
+    // Kernel never expose private key and preshared key in query result,
+    // only verify public keys, endpoints and other plain settings.
+    pub(crate) fn sanitize_desired_for_verify(&mut self) {
+        if let Some(conf) = self.wireguard.as_mut() {
+            conf.private_key = None;
+            for peer in conf.peers.as_mut().into_iter().flatten() {
+                peer.preshared_key = None;
+            }
+        }
+    }
+}
+
+impl WireguardConfig {
+    fn update(&mut self, other: Option<&Self>) {
+        if let Some(other) = other {
+            if other.private_key.is_some() {
+                self.private_key.clone_from(&other.private_key);
+            }
+            if other.public_key.is_some() {
+                self.public_key.clone_from(&other.public_key);
+            }
+            if other.listen_port.is_some() {
+                self.listen_port = other.listen_port;
+            }
+            if other.fwmark.is_some() {
+                self.fwmark = other.fwmark;
+            }
+            if other.peers.is_some() {
+                self.peers.clone_from(&other.peers);
+            }
+        }
+    }
+}
@@ -9,6 +9,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     state::ip::{is_ipv6_addr, sanitize_ip_network},
+    state::not_synced::log_warn,
     ErrorKind, InterfaceType, MergedInterfaces, NipartError,
 };
 
@@ -67,9 +68,24 @@ impl Routes {
         self.running.is_none() && self.config.is_none()
     }
 
+    /// Expand route entries holding [RouteEntry.next_hops] into equivalent
+    /// route entries with single next hop each.
+    pub(crate) fn flatten_next_hops(&self) -> Result<Self, NipartError> {
+        let mut ret = self.clone();
+        if let Some(config_routes) = self.config.as_ref() {
+            let mut new_routes = Vec::new();
+            for route in config_routes {
+                new_routes.extend(route.flatten_next_hops()?);
+            }
+            ret.config = Some(new_routes);
+        }
+        Ok(ret)
+    }
+
     pub fn validate(&self) -> Result<(), NipartError> {
         // All desire non-absent route should have next hop interface except
-        // for route with route type `Blackhole`, `Unreachable`, `Prohibit`.
+        // for route with route type `Blackhole`, `Unreachable`, `Prohibit`,
+        // `Throw`.
         if let Some(config_routes) = self.config.as_ref() {
             for route in config_routes.iter() {
                 if !route.is_absent() {
@@ -140,7 +156,7 @@ pub struct RouteEntry {
     /// Route next hop interface name.
     /// Serialize and deserialize to/from `next-hop-interface`.
     /// Mandatory for every non-absent routes except for route with
-    /// route type `Blackhole`, `Unreachable`, `Prohibit`.
+    /// route type `Blackhole`, `Unreachable`, `Prohibit`, `Throw`.
     pub next_hop_iface: Option<String>,
     #[serde(
         skip_serializing_if = "Option::is_none",
@@ -162,10 +178,12 @@ pub struct RouteEntry {
     #[serde(
         skip_serializing_if = "Option::is_none",
         default,
-        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+        deserialize_with = "crate::state::not_synced::option_route_table_id_or_name"
     )]
     /// Route table id. [RouteEntry::USE_DEFAULT_ROUTE_TABLE] for main
-    /// route table 254.
+    /// route table 254. Could also be table name defined in iproute2
+    /// `rt_tables` file, which is resolved to numeric ID when deserializing.
+    /// Query always shows numeric ID.
     pub table_id: Option<u32>,
 
     /// ECMP(Equal-Cost Multi-Path) route weight
@@ -183,6 +201,127 @@ pub struct RouteEntry {
     /// Congestion window clamp
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cwnd: Option<u32>,
+    /// IPv6 route preference defined in RFC 4191, commonly received from
+    /// IPv6 router advertisement. Only valid for IPv6 route.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pref: Option<Ipv6RoutePref>,
+    /// Route scope. When undefined, `link` is used for IPv4 unicast route
+    /// without next hop address, `global` for others.
+    /// Only `global` is valid for IPv6 route.
+    /// Hidden in query when equal to the inferred scope.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<RouteScope>,
+    /// Pretend the next hop is directly attached to the
+    /// `next-hop-interface` even when no prefix of the interface covers it,
+    /// i.e. `RTNH_F_ONLINK`. Only valid for unicast route with
+    /// `next-hop-address`.
+    /// Serialize and deserialize to/from `on-link`.
+    /// Hidden in query when not enabled.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_bool_or_string"
+    )]
+    pub on_link: Option<bool>,
+    /// ECMP(Equal-Cost Multi-Path) next hops of single multipath route.
+    /// Cannot be used along with `next-hop-interface`, `next-hop-address`
+    /// or `weight`. This is equivalent to multiple route entries sharing the
+    /// same properties, each holding the `next-hop-interface`,
+    /// `next-hop-address` and `weight` of a next hop. For example:
+    /// ```yaml
+    /// routes:
+    ///   config:
+    ///   - destination: 198.51.100.0/24
+    ///     next-hops:
+    ///     - next-hop-interface: eth1
+    ///       next-hop-address: 192.0.2.1
+    ///       weight: 1
+    ///     - next-hop-interface: eth2
+    ///       next-hop-address: 192.0.2.2
+    ///       weight: 2
+    /// ```
+    /// is identical to:
+    /// ```yaml
+    /// routes:
+    ///   config:
+    ///   - destination: 198.51.100.0/24
+    ///     next-hop-interface: eth1
+    ///     next-hop-address: 192.0.2.1
+    ///     weight: 1
+    ///   - destination: 198.51.100.0/24
+    ///     next-hop-interface: eth2
+    ///     next-hop-address: 192.0.2.2
+    ///     weight: 2
+    /// ```
+    /// Serialize and deserialize to/from `next-hops`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_hops: Option<Vec<RouteNextHop>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+#[serde(deny_unknown_fields)]
+/// Next hop of ECMP(Equal-Cost Multi-Path) route
+pub struct RouteNextHop {
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        rename = "next-hop-interface"
+    )]
+    /// Next hop interface name.
+    /// Serialize and deserialize to/from `next-hop-interface`.
+    pub next_hop_iface: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        rename = "next-hop-address"
+    )]
+    /// Next hop IP address.
+    /// Serialize and deserialize to/from `next-hop-address`.
+    pub next_hop_addr: Option<String>,
+    /// Weight of this next hop, the valid range is 1-256.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "crate::state::deserializer::option_u16_or_string"
+    )]
+    pub weight: Option<u16>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+/// IPv6 route preference
+pub enum Ipv6RoutePref {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl std::fmt::Display for Ipv6RoutePref {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Low => "low",
+                Self::Medium => "medium",
+                Self::High => "high",
+            }
+        )
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -193,6 +332,10 @@ pub enum RouteType {
     Blackhole,
     Unreachable,
     Prohibit,
+    /// Stop the lookup in current route table and continue route rule
+    /// lookup, commonly used for policy routing.
+    /// Serialize and deserialize to/from `throw`.
+    Throw,
 }
 
 impl std::fmt::Display for RouteType {
@@ -204,6 +347,53 @@ impl std::fmt::Display for RouteType {
                 Self::Blackhole => "blackhole",
                 Self::Unreachable => "unreachable",
                 Self::Prohibit => "prohibit",
+                Self::Throw => "throw",
+            }
+        )
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+/// Scope of route destination
+pub enum RouteScope {
+    /// Destination is reachable via gateway.
+    /// Serialize and deserialize to/from `global`.
+    #[serde(alias = "universe")]
+    Global,
+    /// Destination is directly attached to the next hop interface.
+    /// Serialize and deserialize to/from `link`.
+    Link,
+    /// Destination is address of local host.
+    /// Serialize and deserialize to/from `host`.
+    Host,
+    /// Destination does not exist.
+    /// Serialize and deserialize to/from `nowhere`.
+    Nowhere,
+}
+
+impl std::fmt::Display for RouteScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Global => "global",
+                Self::Link => "link",
+                Self::Host => "host",
+                Self::Nowhere => "nowhere",
             }
         )
     }
@@ -213,6 +403,7 @@ const RTN_UNICAST: u8 = 1;
 const RTN_BLACKHOLE: u8 = 6;
 const RTN_UNREACHABLE: u8 = 7;
 const RTN_PROHIBIT: u8 = 8;
+const RTN_THROW: u8 = 9;
 
 impl From<RouteType> for u8 {
     fn from(v: RouteType) -> u8 {
@@ -220,6 +411,7 @@ impl From<RouteType> for u8 {
             RouteType::Blackhole => RTN_BLACKHOLE,
             RouteType::Unreachable => RTN_UNREACHABLE,
             RouteType::Prohibit => RTN_PROHIBIT,
+            RouteType::Throw => RTN_THROW,
         }
     }
 }
@@ -271,13 +463,70 @@ impl RouteEntry {
         if self.cwnd.is_some() && self.cwnd != other.cwnd {
             return false;
         }
+        if self.pref.is_some() && self.pref != other.pref {
+            return false;
+        }
+        if self.scope.is_some() && self.scope() != other.scope() {
+            return false;
+        }
+        if self.on_link.is_some()
+            && self.on_link.unwrap_or_default()
+                != other.on_link.unwrap_or_default()
+        {
+            return false;
+        }
         true
     }
 
+    /// The scope kernel will use when route `scope` is undefined:
+    /// `link` for IPv4 unicast route without next hop address, `global`
+    /// for others.
+    pub fn default_scope(&self) -> RouteScope {
+        if self.is_unicast()
+            && !self.is_ipv6()
+            && !self.has_next_hop_addr()
+            && self.next_hops.is_none()
+        {
+            RouteScope::Link
+        } else {
+            RouteScope::Global
+        }
+    }
+
+    /// Explicit route scope or the default one.
+    pub fn scope(&self) -> RouteScope {
+        self.scope.unwrap_or_else(|| self.default_scope())
+    }
+
+    fn has_next_hop_addr(&self) -> bool {
+        match self.next_hop_addr.as_deref() {
+            Some(addr) => addr
+                .parse::<std::net::IpAddr>()
+                .map(|a| !a.is_unspecified())
+                .unwrap_or(true),
+            None => false,
+        }
+    }
+
     // Return tuple of (no_absent, is_ipv4, table_id, next_hop_iface,
-    // destination, next_hop_addr, weight, cwnd)
+    // destination, next_hop_addr, weight, cwnd, pref, scope, on_link)
     // Metric is ignored
-    fn sort_key(&self) -> (bool, bool, u32, &str, &str, &str, u16, u32) {
+    #[allow(clippy::type_complexity)]
+    fn sort_key(
+        &self,
+    ) -> (
+        bool,
+        bool,
+        u32,
+        &str,
+        &str,
+        &str,
+        u16,
+        u32,
+        Option<Ipv6RoutePref>,
+        RouteScope,
+        bool,
+    ) {
         (
             !matches!(self.state, Some(RouteState::Absent)),
             !self
@@ -293,6 +542,9 @@ impl RouteEntry {
             self.next_hop_addr.as_deref().unwrap_or(""),
             self.weight.unwrap_or_default(),
             self.cwnd.unwrap_or_default(),
+            self.pref,
+            self.scope(),
+            self.on_link.unwrap_or_default(),
         )
     }
 
@@ -303,11 +555,10 @@ impl RouteEntry {
             } else {
                 let new_dst = sanitize_ip_network(dst)?;
                 if dst != &new_dst {
-                    log::warn!(
+                    log_warn(format!(
                         "Route destination {} sanitized to {}",
-                        dst,
-                        new_dst
-                    );
+                        dst, new_dst
+                    ));
                     self.destination = Some(new_dst);
                 }
             }
@@ -315,11 +566,10 @@ impl RouteEntry {
         if let Some(via) = self.next_hop_addr.as_ref() {
             let new_via = format!("{}", via.parse::<std::net::IpAddr>()?);
             if via != &new_via {
-                log::warn!(
+                log_warn(format!(
                     "Route next-hop-address {} sanitized to {}",
-                    via,
-                    new_via
-                );
+                    via, new_via
+                ));
                 self.next_hop_addr = Some(new_via);
             }
         }
@@ -343,6 +593,17 @@ impl RouteEntry {
                 }
             }
         }
+        if let Some(pref) = self.pref {
+            if !self.is_absent() && !self.is_ipv6() {
+                return Err(NipartError::new(
+                    ErrorKind::InvalidArgument,
+                    format!(
+                        "Route preference '{pref}' is only valid for IPv6 \
+                        route: {self}"
+                    ),
+                ));
+            }
+        }
         if let Some(cwnd) = self.cwnd {
             if cwnd == 0 {
                 return Err(NipartError::new(
@@ -351,9 +612,112 @@ impl RouteEntry {
                 ));
             }
         }
+        if !self.is_absent() {
+            self.validate_scope()?;
+            self.validate_on_link()?;
+        }
+        Ok(())
+    }
+
+    fn validate_on_link(&self) -> Result<(), NipartError> {
+        if self.on_link != Some(true) {
+            return Ok(());
+        }
+        if !self.is_unicast()
+            || (self.next_hops.is_none() && !self.has_next_hop_addr())
+        {
+            let e = NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "The 'on-link' flag is only valid for unicast route \
+                    with next-hop-address: {self}"
+                ),
+            );
+            log::error!("{}", e);
+            return Err(e);
+        }
         Ok(())
     }
 
+    fn validate_scope(&self) -> Result<(), NipartError> {
+        let scope = match self.scope {
+            Some(s) => s,
+            None => return Ok(()),
+        };
+        let e = if self.is_ipv6() && scope != RouteScope::Global {
+            NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Route scope '{scope}' is not valid for IPv6 route, \
+                    only 'global' is supported: {self}"
+                ),
+            )
+        } else if scope != RouteScope::Global && self.has_next_hop_addr() {
+            NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Route with scope '{scope}' cannot have \
+                    next-hop-address: {self}"
+                ),
+            )
+        } else if scope == RouteScope::Global
+            && self.is_unicast()
+            && !self.has_next_hop_addr()
+            && self.next_hop_iface.is_none()
+        {
+            NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Route with scope 'global' and no next-hop-address \
+                    should have next-hop-interface to reach the \
+                    destination: {self}"
+                ),
+            )
+        } else {
+            return Ok(());
+        };
+        log::error!("{}", e);
+        Err(e)
+    }
+
+    /// Expand route holding [RouteEntry.next_hops] into equivalent route
+    /// entries with single next hop each.
+    pub(crate) fn flatten_next_hops(&self) -> Result<Vec<Self>, NipartError> {
+        let next_hops = match self.next_hops.as_ref() {
+            Some(n) => n,
+            None => return Ok(vec![self.clone()]),
+        };
+        if self.next_hop_iface.is_some()
+            || self.next_hop_addr.is_some()
+            || self.weight.is_some()
+        {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Route with next-hops defined cannot have \
+                    next-hop-interface, next-hop-address or weight: {self}"
+                ),
+            ));
+        }
+        if next_hops.is_empty() {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!("Route next-hops cannot be empty list: {self}"),
+            ));
+        }
+        Ok(next_hops
+            .iter()
+            .map(|next_hop| {
+                let mut rt = self.clone();
+                rt.next_hops = None;
+                rt.next_hop_iface.clone_from(&next_hop.next_hop_iface);
+                rt.next_hop_addr.clone_from(&next_hop.next_hop_addr);
+                rt.weight = next_hop.weight;
+                rt
+            })
+            .collect())
+    }
+
     pub(crate) fn is_ipv6(&self) -> bool {
         self.destination.as_ref().map(|d| is_ipv6_addr(d.as_str()))
             == Some(true)
@@ -422,6 +786,30 @@ impl std::fmt::Display for RouteEntry {
         if let Some(v) = self.cwnd {
             props.push(format!("cwnd: {v}"));
         }
+        if let Some(v) = self.pref {
+            props.push(format!("pref: {v}"));
+        }
+        if let Some(v) = self.scope {
+            props.push(format!("scope: {v}"));
+        }
+        if let Some(v) = self.on_link {
+            props.push(format!("on-link: {v}"));
+        }
+        if let Some(next_hops) = self.next_hops.as_ref() {
+            for next_hop in next_hops {
+                let mut hop_props = Vec::new();
+                if let Some(v) = next_hop.next_hop_iface.as_ref() {
+                    hop_props.push(format!("next-hop-interface: {v}"));
+                }
+                if let Some(v) = next_hop.next_hop_addr.as_ref() {
+                    hop_props.push(format!("next-hop-address: {v}"));
+                }
+                if let Some(v) = next_hop.weight {
+                    hop_props.push(format!("weight: {v}"));
+                }
+                props.push(format!("next-hop: {{{}}}", hop_props.join(" ")));
+            }
+        }
 
         write!(f, "{}", props.join(" "))
     }
@@ -449,6 +837,7 @@ impl MergedRoutes {
         current: Routes,
         merged_ifaces: &MergedInterfaces,
     ) -> Result<Self, NipartError> {
+        let desired = desired.flatten_next_hops()?;
         desired.validate()?;
         let mut desired_routes = Vec::new();
         if let Some(rts) = desired.config.as_ref() {
@@ -702,3 +1091,404 @@ fn validate_route_dst(route: &RouteEntry) -> Result<(), NipartError> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Ipv6RoutePref, RouteEntry, RouteScope, RouteType};
+    use crate::{ErrorKind, MergedNetworkState, NetworkState};
+
+    fn gen_ecmp_current(next_hops: &[(&str, u16)]) -> NetworkState {
+        let mut yaml = String::from(
+            "interfaces:\n\
+            - name: eth1\n  type: ethernet\n  state: up\n  ipv4:\n    \
+            enabled: true\n    address:\n    - ip: 192.0.2.10\n      \
+            prefix-length: 24\n\
+            routes:\n  config:\n",
+        );
+        for (addr, weight) in next_hops {
+            yaml.push_str(&format!(
+                "  - destination: 198.51.100.0/24\n    \
+                next-hop-interface: eth1\n    \
+                next-hop-address: {addr}\n    weight: {weight}\n"
+            ));
+        }
+        NetworkState::new_from_yaml(&yaml).unwrap()
+    }
+
+    fn gen_ecmp_desired(next_hops: &[(&str, u16)]) -> NetworkState {
+        let mut yaml = String::from(
+            "routes:\n  config:\n  - destination: 198.51.100.0/24\n    \
+            next-hops:\n",
+        );
+        for (addr, weight) in next_hops {
+            yaml.push_str(&format!(
+                "    - next-hop-interface: eth1\n      \
+                next-hop-address: {addr}\n      weight: {weight}\n"
+            ));
+        }
+        NetworkState::new_from_yaml(&yaml).unwrap()
+    }
+
+    fn verify_ecmp(
+        desired: &[(&str, u16)],
+        current: &[(&str, u16)],
+    ) -> Result<(), crate::NipartError> {
+        let current = gen_ecmp_current(current);
+        MergedNetworkState::new(
+            gen_ecmp_desired(desired),
+            current.clone(),
+            false,
+            false,
+        )?
+        .verify(&current)
+    }
+
+    #[test]
+    fn test_verify_ecmp_route_ignore_next_hop_order() {
+        let next_hops = [("192.0.2.1", 1), ("192.0.2.2", 2)];
+        let reversed = [("192.0.2.2", 2), ("192.0.2.1", 1)];
+
+        verify_ecmp(&next_hops, &reversed).unwrap();
+        verify_ecmp(&reversed, &next_hops).unwrap();
+    }
+
+    #[test]
+    fn test_apply_three_way_ecmp_route() {
+        let next_hops = [("192.0.2.1", 1), ("192.0.2.2", 2), ("192.0.2.3", 3)];
+
+        let merged = MergedNetworkState::new(
+            gen_ecmp_desired(&next_hops),
+            gen_ecmp_current(&[]),
+            false,
+            false,
+        )
+        .unwrap();
+
+        let mut changed: Vec<(&str, u16)> = merged
+            .routes
+            .changed_routes
+            .iter()
+            .filter_map(|rt| {
+                assert!(rt.next_hops.is_none());
+                assert_eq!(rt.next_hop_iface.as_deref(), Some("eth1"));
+                Some((rt.next_hop_addr.as_deref()?, rt.weight?))
+            })
+            .collect();
+        changed.sort_unstable();
+        assert_eq!(changed, next_hops);
+        merged.verify(&gen_ecmp_current(&next_hops)).unwrap();
+    }
+
+    #[test]
+    fn test_verify_ecmp_route_missing_next_hop() {
+        let result = verify_ecmp(
+            &[("192.0.2.1", 1), ("192.0.2.2", 2)],
+            &[("192.0.2.2", 2)],
+        );
+
+        assert_eq!(result.unwrap_err().kind, ErrorKind::VerificationError);
+    }
+
+    #[test]
+    fn test_ipv6_route_with_weight_not_supported() {
+        let mut route = RouteEntry::new();
+        route.destination = Some("2001:db8:1::/64".to_string());
+        route.next_hop_iface = Some("eth1".to_string());
+        route.next_hop_addr = Some("2001:db8::1".to_string());
+        route.weight = Some(2);
+
+        assert_eq!(
+            route.sanitize().unwrap_err().kind,
+            ErrorKind::NotSupportedError
+        );
+    }
+
+    #[test]
+    fn test_route_type_serde_round_trip() {
+        for (route_type, name) in [
+            (RouteType::Blackhole, "blackhole"),
+            (RouteType::Unreachable, "unreachable"),
+            (RouteType::Prohibit, "prohibit"),
+            (RouteType::Throw, "throw"),
+        ] {
+            let value = serde_json::to_value(route_type).unwrap();
+            assert_eq!(value, serde_json::Value::String(name.to_string()));
+            assert_eq!(route_type.to_string(), name);
+            assert_eq!(
+                serde_json::from_value::<RouteType>(value).unwrap(),
+                route_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_route_type_invalid() {
+        assert!(serde_json::from_str::<RouteType>("\"drop\"").is_err());
+    }
+
+    #[test]
+    fn test_route_entry_throw_round_trip() {
+        let state = NetworkState::new_from_yaml(
+            r"---
+            routes:
+              config:
+              - destination: 198.51.100.0/24
+                route-type: throw
+                table-id: 100",
+        )
+        .unwrap();
+        let routes = state.routes.config.as_ref().unwrap();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].route_type, Some(RouteType::Throw));
+
+        let yaml = serde_yaml::to_string(&routes[0]).unwrap();
+        assert!(yaml.contains("route-type: throw"));
+        let route: RouteEntry = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(route, routes[0]);
+    }
+
+    fn gen_ipv6_pref_state(pref: &str) -> NetworkState {
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ipv6:
+                enabled: true
+                address:
+                - ip: 2001:db8::10
+                  prefix-length: 64
+            routes:
+              config:
+              - destination: 2001:db8:1::/64
+                next-hop-interface: eth1
+                next-hop-address: 2001:db8::1
+                pref: {pref}"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ipv6_route_pref_round_trip() {
+        let state = gen_ipv6_pref_state("high");
+        let route = &state.routes.config.as_ref().unwrap()[0];
+        assert_eq!(route.pref, Some(Ipv6RoutePref::High));
+
+        let yaml = serde_yaml::to_string(route).unwrap();
+        assert!(yaml.contains("pref: high"));
+        let new_route: RouteEntry = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(&new_route, route);
+
+        let merged = MergedNetworkState::new(
+            state.clone(),
+            gen_ipv6_pref_state("low"),
+            false,
+            false,
+        )
+        .unwrap();
+        merged.verify(&state).unwrap();
+        assert_eq!(
+            merged.verify(&gen_ipv6_pref_state("low")).unwrap_err().kind,
+            ErrorKind::VerificationError
+        );
+    }
+
+    #[test]
+    fn test_ipv4_route_with_pref_not_allowed() {
+        let mut route = RouteEntry::new();
+        route.destination = Some("198.51.100.0/24".to_string());
+        route.next_hop_iface = Some("eth1".to_string());
+        route.pref = Some(Ipv6RoutePref::High);
+
+        assert_eq!(
+            route.sanitize().unwrap_err().kind,
+            ErrorKind::InvalidArgument
+        );
+    }
+
+    // Route to directly connected network, `scope` line appended when
+    // defined.
+    fn gen_link_route_state(scope: Option<&str>) -> NetworkState {
+        let mut yaml = String::from(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ipv4:
+                enabled: true
+                address:
+                - ip: 192.0.2.10
+                  prefix-length: 24
+            routes:
+              config:
+              - destination: 198.51.100.0/24
+                next-hop-interface: eth1",
+        );
+        if let Some(scope) = scope {
+            yaml.push_str(&format!("\n                scope: {scope}"));
+        }
+        NetworkState::new_from_yaml(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_route_link_scope_round_trip() {
+        let state = gen_link_route_state(Some("link"));
+        let route = &state.routes.config.as_ref().unwrap()[0];
+        assert_eq!(route.scope, Some(RouteScope::Link));
+
+        let yaml = serde_yaml::to_string(route).unwrap();
+        assert!(yaml.contains("scope: link"));
+        let new_route: RouteEntry = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(&new_route, route);
+
+        let merged = MergedNetworkState::new(
+            state.clone(),
+            gen_link_route_state(None),
+            false,
+            false,
+        )
+        .unwrap();
+        merged.verify(&state).unwrap();
+        // Queried route without scope means the inferred `link` scope
+        merged.verify(&gen_link_route_state(None)).unwrap();
+        assert_eq!(
+            merged
+                .verify(&gen_link_route_state(Some("host")))
+                .unwrap_err()
+                .kind,
+            ErrorKind::VerificationError
+        );
+    }
+
+    #[test]
+    fn test_route_default_scope() {
+        let mut route = RouteEntry::new();
+        route.destination = Some("198.51.100.0/24".to_string());
+        route.next_hop_iface = Some("eth1".to_string());
+        assert_eq!(route.scope(), RouteScope::Link);
+
+        route.next_hop_addr = Some("192.0.2.1".to_string());
+        assert_eq!(route.scope(), RouteScope::Global);
+    }
+
+    #[test]
+    fn test_route_invalid_scope() {
+        let mut route = RouteEntry::new();
+        route.destination = Some("198.51.100.0/24".to_string());
+        route.next_hop_iface = Some("eth1".to_string());
+        route.next_hop_addr = Some("192.0.2.1".to_string());
+        route.scope = Some(RouteScope::Link);
+        assert_eq!(
+            route.sanitize().unwrap_err().kind,
+            ErrorKind::InvalidArgument
+        );
+
+        let mut route = RouteEntry::new();
+        route.destination = Some("2001:db8:1::/64".to_string());
+        route.next_hop_iface = Some("eth1".to_string());
+        route.scope = Some(RouteScope::Link);
+        assert_eq!(
+            route.sanitize().unwrap_err().kind,
+            ErrorKind::InvalidArgument
+        );
+    }
+
+    // Gateway 203.0.113.1 is not covered by any prefix of eth1, `on-link`
+    // line appended when defined.
+    fn gen_on_link_route_state(on_link: Option<bool>) -> NetworkState {
+        let mut yaml = String::from(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ipv4:
+                enabled: true
+                address:
+                - ip: 192.0.2.10
+                  prefix-length: 24
+            routes:
+              config:
+              - destination: 198.51.100.0/24
+                next-hop-interface: eth1
+                next-hop-address: 203.0.113.1",
+        );
+        if let Some(on_link) = on_link {
+            yaml.push_str(&format!("\n                on-link: {on_link}"));
+        }
+        NetworkState::new_from_yaml(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_route_on_link_round_trip() {
+        let state = gen_on_link_route_state(Some(true));
+        let route = &state.routes.config.as_ref().unwrap()[0];
+        assert_eq!(route.on_link, Some(true));
+
+        let yaml = serde_yaml::to_string(route).unwrap();
+        assert!(yaml.contains("on-link: true"));
+        let new_route: RouteEntry = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(&new_route, route);
+
+        let merged = MergedNetworkState::new(
+            state.clone(),
+            gen_on_link_route_state(None),
+            false,
+            false,
+        )
+        .unwrap();
+        merged.verify(&state).unwrap();
+        assert_eq!(
+            merged
+                .verify(&gen_on_link_route_state(None))
+                .unwrap_err()
+                .kind,
+            ErrorKind::VerificationError
+        );
+    }
+
+    #[test]
+    fn test_route_on_link_is_match() {
+        let with_on_link =
+            gen_on_link_route_state(Some(true)).routes.config.unwrap()[0]
+                .clone();
+        let without_on_link =
+            gen_on_link_route_state(None).routes.config.unwrap()[0].clone();
+
+        assert!(with_on_link.is_match(&with_on_link));
+        assert!(!with_on_link.is_match(&without_on_link));
+        // Undefined `on-link` matches any route
+        assert!(without_on_link.is_match(&with_on_link));
+        assert_ne!(with_on_link.sort_key(), without_on_link.sort_key());
+    }
+
+    #[test]
+    fn test_route_on_link_without_next_hop_addr() {
+        let mut route = RouteEntry::new();
+        route.destination = Some("198.51.100.0/24".to_string());
+        route.next_hop_iface = Some("eth1".to_string());
+        route.on_link = Some(true);
+        assert_eq!(
+            route.sanitize().unwrap_err().kind,
+            ErrorKind::InvalidArgument
+        );
+
+        let mut route = RouteEntry::new();
+        route.destination = Some("198.51.100.0/24".to_string());
+        route.route_type = Some(RouteType::Blackhole);
+        route.on_link = Some(true);
+        assert_eq!(
+            route.sanitize().unwrap_err().kind,
+            ErrorKind::InvalidArgument
+        );
+
+        // `on-link: false` is allowed everywhere
+        let mut route = RouteEntry::new();
+        route.destination = Some("198.51.100.0/24".to_string());
+        route.next_hop_iface = Some("eth1".to_string());
+        route.on_link = Some(false);
+        route.sanitize().unwrap();
+    }
+}
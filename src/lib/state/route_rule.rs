@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     state::ip::{is_ipv6_addr, sanitize_ip_network, AddressFamily},
+    state::not_synced::log_warn,
     ErrorKind, InterfaceIpAddr, InterfaceType, NipartError,
 };
 
@@ -93,9 +94,11 @@ pub struct RouteRuleEntry {
         skip_serializing_if = "Option::is_none",
         rename = "route-table",
         default,
-        deserialize_with = "crate::state::deserializer::option_u32_or_string"
+        deserialize_with = "crate::state::not_synced::option_route_table_id_or_name"
     )]
     /// The routing table ID to lookup if the rule selector matches.
+    /// Could also be table name defined in iproute2 `rt_tables` file, which
+    /// is resolved to numeric ID when deserializing.
     /// Serialize and deserialize to/from `route-table`.
     pub table_id: Option<u32>,
     #[serde(
@@ -326,9 +329,10 @@ impl RouteRuleEntry {
                 } else if let Some(family) = self.family.as_ref() {
                     *family == AddressFamily::IPv4
                 } else {
-                    log::warn!(
+                    log_warn(
                         "Neither ip-from, ip-to nor family \
                         is defined, treating it a IPv4 route rule"
+                            .to_string(),
                     );
                     true
                 }
@@ -359,11 +363,10 @@ impl RouteRuleEntry {
                     };
                 }
                 if ip != &new_ip {
-                    log::warn!(
+                    log_warn(format!(
                         "Route rule ip-from {} sanitized to {}",
-                        ip,
-                        new_ip
-                    );
+                        ip, new_ip
+                    ));
                     self.ip_from = Some(new_ip);
                 }
             }
@@ -380,11 +383,10 @@ impl RouteRuleEntry {
                     };
                 }
                 if ip != &new_ip {
-                    log::warn!(
+                    log_warn(format!(
                         "Route rule ip-to {} sanitized to {}",
-                        ip,
-                        new_ip
-                    );
+                        ip, new_ip
+                    ));
                     self.ip_to = Some(new_ip);
                 }
             }
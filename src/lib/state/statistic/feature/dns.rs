@@ -17,6 +17,9 @@ impl MergedDnsState {
             if dns_config.options.is_some() {
                 ret.push(NmstateFeature::StaticDnsOption);
             }
+            if dns_config.per_interface.is_some() {
+                ret.push(NmstateFeature::StaticDnsPerInterface);
+            }
         }
         ret
     }
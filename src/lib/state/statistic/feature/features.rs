@@ -24,6 +24,7 @@ pub enum NmstateFeature {
     Sriov,
     StaticDnsNameServer,
     StaticDnsOption,
+    StaticDnsPerInterface,
     StaticDnsSearch,
     StaticHostname,
     StaticRoute,
@@ -6,6 +6,23 @@ use serde::{Deserialize, Serialize};
 #[non_exhaustive]
 pub struct NipartQueryOption {
     pub kernel_only: bool,
+    /// Daemon serves query from its cached network state when possible.
+    /// The cache is invalidated on any link, address or route change
+    /// reported by monitor plugin or any apply action, is discarded on
+    /// hostname, DNS or OVS database change, and is never older than 30
+    /// seconds. Set to true to bypass the cache and query plugins
+    /// directly.
+    #[serde(default)]
+    pub force_refresh: bool,
+    /// When set to commit ID, daemon replies with properties of network
+    /// state stored in specified commit which running network state
+    /// no longer matches, in the same way of
+    /// [crate::NetworkState::gen_diff()]. Since every commit holds the
+    /// network state merged with all its ancestors, this is the network
+    /// state as of that commit. Error if specified commit not found.
+    /// Never served from cache.
+    #[serde(default)]
+    pub since_commit: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
@@ -13,4 +30,60 @@ pub struct NipartQueryOption {
 pub struct NipartApplyOption {
     pub memory_only: bool,
     pub no_verify: bool,
+    /// Seconds to wait for interfaces with `wait-ip` defined to get IP
+    /// address of requested family. Default to 5 seconds.
+    pub wait_ip_timeout: Option<u32>,
+    /// Seconds to wait for user confirmation after desired state applied and
+    /// verified. If user connection dropped, user requested rollback or no
+    /// confirmation received in time, daemon will revert to the network
+    /// state before this apply. The timeout check is done every 5 seconds,
+    /// hence rollback might happen up to 5 seconds later than requested.
+    /// Default to None which means no confirmation required.
+    pub confirm_timeout: Option<u32>,
+    /// Restore the permanent MAC address of interface when it is brought
+    /// down or detached from bond. Ignored if desired state explicitly
+    /// defined `mac-address` of that interface. Default to false.
+    #[serde(default)]
+    pub restore_permanent_mac: bool,
+    /// Daemon skips lock, apply, verify and commit when desired state
+    /// changes nothing to current network state. Set to true to apply
+    /// regardless. Default to false.
+    #[serde(default)]
+    pub force: bool,
+    /// Only store desired state as new commit of tracking plugin without
+    /// touching the running network state. Useful for pre-staging network
+    /// configuration before reboot: the running network state stays
+    /// unchanged until committed state been applied, for example by
+    /// boot-up service. Dispatch scripts and verification are skipped and
+    /// [NipartApplyOption::confirm_timeout] is ignored. Default to false.
+    #[serde(default)]
+    pub persistent_only: bool,
+    /// Skip querying current network state before apply, the desired state
+    /// is merged against empty network state instead. This trades safety
+    /// for speed on known-good desired state, for example kiosk or embedded
+    /// system applying the same state on every boot. Desired state should
+    /// be fully defined as unmentioned properties are not merged from
+    /// current network state. Verification still runs. Cannot be used
+    /// with [NipartApplyOption::confirm_timeout] as rollback requires the
+    /// pre-apply network state. Ignored when
+    /// [NipartApplyOption::persistent_only] is set. Default to false.
+    #[serde(default)]
+    pub skip_pre_query: bool,
+    /// Keep DHCP client running for interfaces marked as absent, useful
+    /// for fast re-adding of the interface. By default, DHCP of removed
+    /// interface is disabled and its lease dropped. Default to false.
+    #[serde(default)]
+    pub keep_dhcp_lease: bool,
+    /// Apply interfaces having no dependency between each other in up to
+    /// specified count of concurrent sub-applies. Only effective when
+    /// desired state changes nothing beyond kernel interfaces, see
+    /// [crate::MergedNetworkState::split_for_parallel_apply()]. Default to
+    /// None, meaning single apply.
+    #[serde(default)]
+    pub parallel: Option<u32>,
+    /// Record timeline of events exchanged between daemon and plugins
+    /// during this apply, replied via
+    /// [crate::NipartUserEvent::ApplyNetStateTraceReply]. Default to false.
+    #[serde(default)]
+    pub trace: bool,
 }
@@ -2,5 +2,6 @@
 
 mod link;
 mod plugin;
+mod route;
 
 pub use self::plugin::NipartPluginBaize;
@@ -9,17 +9,25 @@ use netlink_sys::AsyncSocket;
 use nipart::{
     ErrorKind, NipartError, NipartEvent, NipartEventAddress,
     NipartLinkMonitorKind, NipartLinkMonitorRule, NipartMonitorEvent,
-    NipartNativePlugin, NipartPluginEvent, NipartUserEvent,
+    NipartNativePlugin, NipartPluginEvent, NipartRouteMonitorRule,
+    NipartUserEvent,
 };
 use tokio::{
     sync::mpsc::{Receiver, Sender},
     task::JoinHandle,
 };
 
+use crate::route::{
+    is_route_rule_match, parse_route_change_from_netlink_message,
+    send_route_notify, validate_route_rule,
+};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum BaizeLinkMonitorCmd {
     AddLinkRule(NipartLinkMonitorRule),
     DelLinkRule(NipartLinkMonitorRule),
+    AddRouteRule(NipartRouteMonitorRule),
+    DelRouteRule(NipartRouteMonitorRule),
 }
 
 #[derive(Debug)]
@@ -116,9 +124,44 @@ impl BaizeLinkMonitor {
                 )
             })
     }
+
+    pub(crate) async fn add_route_rule(
+        &mut self,
+        rule: NipartRouteMonitorRule,
+    ) -> Result<(), NipartError> {
+        validate_route_rule(&rule)?;
+        self.send_cmd(BaizeLinkMonitorCmd::AddRouteRule(rule)).await
+    }
+
+    pub(crate) async fn del_route_rule(
+        &mut self,
+        rule: NipartRouteMonitorRule,
+    ) -> Result<(), NipartError> {
+        self.send_cmd(BaizeLinkMonitorCmd::DelRouteRule(rule)).await
+    }
+
+    async fn send_cmd(
+        &mut self,
+        cmd: BaizeLinkMonitorCmd,
+    ) -> Result<(), NipartError> {
+        self.to_monitor.send(cmd).await.map_err(|e| {
+            NipartError::new(
+                ErrorKind::Bug,
+                format!("Failed to send command to monitor thread: {e}"),
+            )
+        })
+    }
 }
 
 const RTNLGRP_LINK: u32 = 1;
+const RTNLGRP_IPV4_IFADDR: u32 = 5;
+const RTNLGRP_IPV4_ROUTE: u32 = 7;
+const RTNLGRP_IPV6_IFADDR: u32 = 9;
+const RTNLGRP_IPV6_ROUTE: u32 = 11;
+
+// Coalesce network changes into single notification to commander every 200
+// milliseconds.
+const NET_STATE_CHANGED_NOTIFY_INTERVAL: u64 = 200;
 
 struct LinkMonitorThread;
 
@@ -129,6 +172,7 @@ impl LinkMonitorThread {
     ) {
         let mut link_rules: HashMap<String, HashSet<NipartLinkMonitorRule>> =
             HashMap::new();
+        let mut route_rules: HashSet<NipartRouteMonitorRule> = HashSet::new();
 
         let (mut conn, mut _handle, mut messages) =
             match rtnetlink::new_connection() {
@@ -139,23 +183,45 @@ impl LinkMonitorThread {
                 }
             };
 
-        let addr = netlink_sys::SocketAddr::new(0, 1 << (RTNLGRP_LINK - 1));
+        let groups = [
+            RTNLGRP_LINK,
+            RTNLGRP_IPV4_IFADDR,
+            RTNLGRP_IPV4_ROUTE,
+            RTNLGRP_IPV6_IFADDR,
+            RTNLGRP_IPV6_ROUTE,
+        ]
+        .iter()
+        .fold(0u32, |groups, group| groups | 1 << (group - 1));
+        let addr = netlink_sys::SocketAddr::new(0, groups);
 
         if let Err(e) = conn.socket_mut().socket_mut().bind(&addr) {
-            log::error!("Failed to bind RTNLGRP_LINK: {e}");
+            log::error!("Failed to bind netlink multicast groups: {e}");
             return;
         }
 
         tokio::spawn(conn);
 
+        let mut net_state_changed = false;
+        let mut notify_interval = tokio::time::interval(
+            std::time::Duration::from_millis(NET_STATE_CHANGED_NOTIFY_INTERVAL),
+        );
+
         loop {
             // TODO: Only start netlink monitor after rules is not empty
             //       Stop netlink monitor after rules is empty.
             tokio::select! {
+                _ = notify_interval.tick() => {
+                    if net_state_changed {
+                        net_state_changed = false;
+                        send_net_state_changed(&to_daemon).await;
+                    }
+                },
                 Some((message, _)) = messages.next() => {
+                    net_state_changed = true;
                     Self::process_netlink_message(
                         message,
                         &mut link_rules,
+                        &route_rules,
                         &to_daemon).await;
                 },
                 Some(cmd) = from_plugin.recv() => {
@@ -175,6 +241,12 @@ impl LinkMonitorThread {
                                 rules.retain(|r| r != &rule);
                             }
                         }
+                        BaizeLinkMonitorCmd::AddRouteRule(rule) => {
+                            route_rules.insert(rule);
+                        }
+                        BaizeLinkMonitorCmd::DelRouteRule(rule) => {
+                            route_rules.remove(&rule);
+                        }
                     }
                 }
             }
@@ -184,9 +256,26 @@ impl LinkMonitorThread {
     async fn process_netlink_message(
         message: NetlinkMessage<RouteNetlinkMessage>,
         rules: &mut HashMap<String, HashSet<NipartLinkMonitorRule>>,
+        route_rules: &HashSet<NipartRouteMonitorRule>,
         to_daemon: &Sender<NipartEvent>,
     ) {
         log::trace!("Got netlink message {message:?}");
+        if let Some(change) = parse_route_change_from_netlink_message(&message)
+        {
+            for rule in route_rules
+                .iter()
+                .filter(|r| is_route_rule_match(r, &change))
+            {
+                if let Err(e) =
+                    send_route_notify(to_daemon, rule, &change).await
+                {
+                    log::error!(
+                        "BUG: process_netlink_message failed to notify {e}"
+                    );
+                }
+            }
+            return;
+        }
         if let Some((iface, kind)) =
             parse_link_state_from_netlink_message(&message)
         {
@@ -207,6 +296,22 @@ impl LinkMonitorThread {
     }
 }
 
+// Notify commander to invalidate its cached network state
+async fn send_net_state_changed(to_daemon: &Sender<NipartEvent>) {
+    let event = NipartEvent::new(
+        NipartUserEvent::None,
+        NipartPluginEvent::NetStateChanged,
+        NipartEventAddress::Unicast(
+            crate::NipartPluginBaize::PLUGIN_NAME.to_string(),
+        ),
+        NipartEventAddress::Commander,
+        nipart::DEFAULT_TIMEOUT,
+    );
+    if let Err(e) = to_daemon.send(event).await {
+        log::error!("Failed to notify commander on network change: {e}");
+    }
+}
+
 // If interface does not exist, return false.
 async fn is_link_up(iface: &str) -> Result<bool, NipartError> {
     let mut iface_filter = nispor::NetStateIfaceFilter::minimum();
@@ -80,6 +80,9 @@ impl NipartPluginBaize {
             NipartMonitorRule::Link(rule) => {
                 self.link_monitor.add_link_rule(rule).await
             }
+            NipartMonitorRule::Route(rule) => {
+                self.link_monitor.add_route_rule(rule).await
+            }
             _ => {
                 log::error!("TODO: register_monitor_rule() {rule}");
                 Ok(())
@@ -96,6 +99,9 @@ impl NipartPluginBaize {
                 self.link_monitor.del_link_rule(rule).await?;
                 Ok(())
             }
+            NipartMonitorRule::Route(rule) => {
+                self.link_monitor.del_route_rule(rule).await
+            }
             _ => {
                 log::error!("TODO: remove_monitor_rule() {rule}");
                 Ok(())
@@ -0,0 +1,285 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use netlink_packet_core::{NetlinkMessage, NetlinkPayload};
+use netlink_packet_route::{
+    route::{RouteAddress, RouteAttribute, RouteMessage},
+    AddressFamily, RouteNetlinkMessage,
+};
+use nipart::{
+    ErrorKind, NipartError, NipartEvent, NipartEventAddress,
+    NipartMonitorEvent, NipartNativePlugin, NipartPluginEvent,
+    NipartRouteMonitorKind, NipartRouteMonitorRule, NipartUserEvent,
+};
+use tokio::sync::mpsc::Sender;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BaizeRouteChange {
+    pub(crate) kind: NipartRouteMonitorKind,
+    pub(crate) destination: IpAddr,
+    pub(crate) prefix_length: u8,
+    pub(crate) table_id: u32,
+}
+
+impl std::fmt::Display for BaizeRouteChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.destination, self.prefix_length)
+    }
+}
+
+pub(crate) fn validate_route_rule(
+    rule: &NipartRouteMonitorRule,
+) -> Result<(), NipartError> {
+    if let Some(dst) = rule.destination.as_deref() {
+        parse_prefix(dst)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_route_change_from_netlink_message(
+    message: &NetlinkMessage<RouteNetlinkMessage>,
+) -> Option<BaizeRouteChange> {
+    let (kind, route_msg) = match &message.payload {
+        NetlinkPayload::InnerMessage(RouteNetlinkMessage::NewRoute(m)) => {
+            (NipartRouteMonitorKind::Add, m)
+        }
+        NetlinkPayload::InnerMessage(RouteNetlinkMessage::DelRoute(m)) => {
+            (NipartRouteMonitorKind::Remove, m)
+        }
+        _ => return None,
+    };
+    let destination = get_route_destination(route_msg)?;
+    // The header only holds the lower 8 bits of table ID
+    let table_id = route_msg
+        .attributes
+        .iter()
+        .find_map(|attr| {
+            if let RouteAttribute::Table(t) = attr {
+                Some(*t)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(route_msg.header.table.into());
+    Some(BaizeRouteChange {
+        kind,
+        destination,
+        prefix_length: route_msg.header.destination_prefix_length,
+        table_id,
+    })
+}
+
+// Default route has no destination attribute
+fn get_route_destination(route_msg: &RouteMessage) -> Option<IpAddr> {
+    let dst = route_msg.attributes.iter().find_map(|attr| {
+        if let RouteAttribute::Destination(d) = attr {
+            Some(d)
+        } else {
+            None
+        }
+    });
+    match dst {
+        Some(RouteAddress::Inet(ip)) => Some(IpAddr::V4(*ip)),
+        Some(RouteAddress::Inet6(ip)) => Some(IpAddr::V6(*ip)),
+        Some(_) => None,
+        None => match route_msg.header.address_family {
+            AddressFamily::Inet => Some(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            AddressFamily::Inet6 => Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+            _ => None,
+        },
+    }
+}
+
+pub(crate) fn is_route_rule_match(
+    rule: &NipartRouteMonitorRule,
+    change: &BaizeRouteChange,
+) -> bool {
+    if rule.kind != change.kind {
+        return false;
+    }
+    if let Some(table_id) = rule.table_id {
+        if table_id != change.table_id {
+            return false;
+        }
+    }
+    match rule.destination.as_deref().map(parse_prefix) {
+        Some(Ok((ip, prefix_length))) => {
+            prefix_length <= change.prefix_length
+                && is_same_network(ip, change.destination, prefix_length)
+        }
+        Some(Err(_)) => false,
+        None => true,
+    }
+}
+
+fn is_same_network(a: IpAddr, b: IpAddr, prefix_length: u8) -> bool {
+    match (a, b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => {
+            let mask = u32::MAX
+                .checked_shl(32 - u32::from(prefix_length))
+                .unwrap_or(0);
+            u32::from(a) & mask == u32::from(b) & mask
+        }
+        (IpAddr::V6(a), IpAddr::V6(b)) => {
+            let mask = u128::MAX
+                .checked_shl(128 - u32::from(prefix_length))
+                .unwrap_or(0);
+            u128::from(a) & mask == u128::from(b) & mask
+        }
+        _ => false,
+    }
+}
+
+fn parse_prefix(prefix: &str) -> Result<(IpAddr, u8), NipartError> {
+    let e = NipartError::new(
+        ErrorKind::InvalidArgument,
+        format!(
+            "Invalid destination {prefix} of route monitor rule, \
+            expecting format like 198.51.100.0/24"
+        ),
+    );
+    let Some((ip, prefix_length)) = prefix.split_once('/') else {
+        return Err(e);
+    };
+    let (Ok(ip), Ok(prefix_length)) =
+        (ip.parse::<IpAddr>(), prefix_length.parse::<u8>())
+    else {
+        return Err(e);
+    };
+    let max_prefix_length = if ip.is_ipv6() { 128 } else { 32 };
+    if prefix_length > max_prefix_length {
+        return Err(e);
+    }
+    Ok((ip, prefix_length))
+}
+
+pub(crate) async fn send_route_notify(
+    to_daemon: &Sender<NipartEvent>,
+    rule: &NipartRouteMonitorRule,
+    change: &BaizeRouteChange,
+) -> Result<(), NipartError> {
+    let monitor_event = match rule.kind {
+        NipartRouteMonitorKind::Add => {
+            NipartMonitorEvent::RouteAdd(change.to_string(), change.table_id)
+        }
+        NipartRouteMonitorKind::Remove => {
+            NipartMonitorEvent::RouteRemove(change.to_string(), change.table_id)
+        }
+        kind => {
+            return Err(NipartError::new(
+                ErrorKind::Bug,
+                format!("Unknown NipartRouteMonitorKind {kind}"),
+            ));
+        }
+    };
+    let mut reply = NipartEvent::new(
+        NipartUserEvent::None,
+        NipartPluginEvent::GotMonitorEvent(Box::new(monitor_event)),
+        NipartEventAddress::Unicast(
+            crate::NipartPluginBaize::PLUGIN_NAME.to_string(),
+        ),
+        rule.requester.clone(),
+        nipart::DEFAULT_TIMEOUT,
+    );
+    reply.uuid = rule.uuid;
+    to_daemon.send(reply.clone()).await.map_err(|e| {
+        NipartError::new(
+            ErrorKind::Bug,
+            format!("Failed to send event {reply}: {e}"),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use netlink_packet_core::NetlinkMessage;
+    use netlink_packet_route::{
+        route::{RouteAddress, RouteAttribute, RouteMessage},
+        AddressFamily, RouteNetlinkMessage,
+    };
+    use nipart::{
+        NipartEventAddress, NipartMonitorEvent, NipartPluginEvent,
+        NipartRouteMonitorKind, NipartRouteMonitorRule,
+    };
+
+    use super::{
+        is_route_rule_match, parse_route_change_from_netlink_message,
+        send_route_notify,
+    };
+
+    fn gen_new_route_message(
+        dst: Ipv4Addr,
+        prefix_length: u8,
+        table_id: u32,
+    ) -> NetlinkMessage<RouteNetlinkMessage> {
+        let mut route_msg = RouteMessage::default();
+        route_msg.header.address_family = AddressFamily::Inet;
+        route_msg.header.destination_prefix_length = prefix_length;
+        route_msg
+            .attributes
+            .push(RouteAttribute::Destination(RouteAddress::Inet(dst)));
+        route_msg.attributes.push(RouteAttribute::Table(table_id));
+        NetlinkMessage::from(RouteNetlinkMessage::NewRoute(route_msg))
+    }
+
+    fn gen_rule(
+        kind: NipartRouteMonitorKind,
+        destination: Option<&str>,
+        table_id: Option<u32>,
+    ) -> NipartRouteMonitorRule {
+        NipartRouteMonitorRule::new(
+            kind,
+            NipartEventAddress::Unicast("test".to_string()),
+            1,
+            destination.map(|d| d.to_string()),
+            table_id,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_route_add_fires_matching_rule() {
+        let message =
+            gen_new_route_message(Ipv4Addr::new(198, 51, 100, 0), 25, 100);
+        let change = parse_route_change_from_netlink_message(&message).unwrap();
+        assert_eq!(change.kind, NipartRouteMonitorKind::Add);
+        assert_eq!(change.table_id, 100);
+
+        let rule = gen_rule(
+            NipartRouteMonitorKind::Add,
+            Some("198.51.100.0/24"),
+            Some(100),
+        );
+        assert!(is_route_rule_match(&rule, &change));
+        // Route in other table, other prefix or removal rule does not match
+        for rule in [
+            gen_rule(NipartRouteMonitorKind::Add, None, Some(200)),
+            gen_rule(NipartRouteMonitorKind::Add, Some("203.0.113.0/24"), None),
+            gen_rule(
+                NipartRouteMonitorKind::Add,
+                Some("198.51.100.0/26"),
+                None,
+            ),
+            gen_rule(NipartRouteMonitorKind::Remove, None, None),
+        ] {
+            assert!(!is_route_rule_match(&rule, &change));
+        }
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        send_route_notify(&tx, &rule, &change).await.unwrap();
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.uuid, rule.uuid);
+        assert_eq!(event.dst, rule.requester);
+        assert_eq!(
+            event.plugin,
+            NipartPluginEvent::GotMonitorEvent(Box::new(
+                NipartMonitorEvent::RouteAdd(
+                    "198.51.100.0/25".to_string(),
+                    100
+                )
+            ))
+        );
+    }
+}
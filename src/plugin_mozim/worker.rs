@@ -43,6 +43,7 @@ pub(crate) struct MozimWorkerV4Thread {}
 impl MozimWorkerV4Thread {
     pub(crate) async fn new(
         iface_name: String,
+        route_metric: Option<u32>,
         mut mozim_client: DhcpV4Client,
         to_daemon: Sender<NipartEvent>,
         event_uuid: u128,
@@ -86,6 +87,7 @@ impl MozimWorkerV4Thread {
                         reply_events.push(gen_dhcp_lease_event(
                             lease,
                             iface_name.as_str(),
+                            route_metric,
                         ));
                         has_lease = true;
                     }
@@ -199,9 +201,16 @@ impl MozimWorkerV4 {
         let to_daemon = self.to_daemon.clone();
         let event_uuid = self.event_uuid;
         let iface_name = self.config.iface.clone();
+        let route_metric = self.config.route_metric;
         self.thread_handler = Some(tokio::task::spawn(async move {
-            MozimWorkerV4Thread::new(iface_name, cli, to_daemon, event_uuid)
-                .await
+            MozimWorkerV4Thread::new(
+                iface_name,
+                route_metric,
+                cli,
+                to_daemon,
+                event_uuid,
+            )
+            .await
         }));
 
         Ok(())
@@ -246,8 +255,9 @@ fn get_prefix_len(ip: &std::net::Ipv4Addr) -> u8 {
 fn gen_dhcp_lease_event(
     mozim_lease: DhcpV4Lease,
     iface_name: &str,
+    route_metric: Option<u32>,
 ) -> NipartEvent {
-    let lease = mozim_lease_to_nipart(mozim_lease, iface_name);
+    let lease = mozim_lease_to_nipart(mozim_lease, iface_name, route_metric);
     NipartEvent::new(
         NipartUserEvent::None,
         NipartPluginEvent::GotDhcpLease(Box::new(lease)),
@@ -260,14 +270,18 @@ fn gen_dhcp_lease_event(
 fn mozim_lease_to_nipart(
     mozim_lease: DhcpV4Lease,
     iface_name: &str,
+    route_metric: Option<u32>,
 ) -> NipartDhcpLease {
-    NipartDhcpLease::V4(NipartDhcpLeaseV4::new(
+    let mut lease = NipartDhcpLeaseV4::new(
         iface_name.to_string(),
         mozim_lease.yiaddr,
         get_prefix_len(&mozim_lease.subnet_mask),
         mozim_lease.siaddr,
         mozim_lease.lease_time,
-    ))
+    );
+    lease.gateways = mozim_lease.gateways.clone().unwrap_or_default();
+    lease.route_metric = route_metric;
+    NipartDhcpLease::V4(lease)
 }
 
 fn gen_mozim_config(conf: &NipartDhcpConfigV4) -> DhcpV4Config {
@@ -1,20 +1,40 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use nipart::{
-    ErrorKind, Interface, InterfaceType, MergedInterface, MergedInterfaces,
-    MergedNetworkState, NipartApplyOption, NipartDhcpLease, NipartError,
+    ErrorKind, Ieee8021XConfig, Interface, InterfaceType, MergedInterface,
+    MergedInterfaces, MergedNetworkState, NipartApplyOption, NipartDhcpLease,
+    NipartError,
 };
 
 use crate::{
+    bond::{
+        apply_bond_mode_after_stage, apply_bond_mode_before_stage,
+        apply_bond_options,
+    },
+    carrier::{get_wait_carrier_ifaces, wait_carrier},
     hostname::set_running_hostname,
-    ip::{nipart_ipv4_to_np, nipart_ipv6_to_np},
+    ieee8021x::{start_wpa_supplicant, stop_wpa_supplicant},
+    ip::{
+        apply_ip_sysctl, apply_ipv6_addr_gen_mode,
+        apply_ipv6_dad_transmits_before_ip, apply_ipv6_token,
+        nipart_ipv4_to_np, nipart_ipv6_to_np,
+    },
+    loopback::np_loopback_default_conf,
+    mtu::apply_mtu,
+    rename::rename_ifaces,
+    sriov::{apply_sriov_total_vfs, nipart_sriov_vfs_to_np},
+    stage::gen_apply_stages,
+    tun::apply_tun_ifaces,
     veth::nms_veth_conf_to_np,
     vlan::nms_vlan_conf_to_np,
+    wireguard::apply_wireguard_ifaces,
 };
 
+const NP_APPLY_CHUNK_COUNT: usize = 4;
+
 pub(crate) async fn nispor_apply(
     merged_state: MergedNetworkState,
-    _opt: NipartApplyOption,
+    opt: NipartApplyOption,
 ) -> Result<(), NipartError> {
     if let Some(hostname) = merged_state
         .get_desired_hostname()
@@ -42,25 +62,212 @@ pub(crate) async fn nispor_apply(
         }
     });
 
+    rename_ifaces(ifaces.as_slice()).await?;
+    apply_sriov_total_vfs(ifaces.as_slice()).await?;
+
+    let apply_ifaces: Vec<(&str, &MergedInterface)> = ifaces
+        .iter()
+        .filter(|i| {
+            i.merged.iface_type() != InterfaceType::Unknown
+                && !i.merged.is_absent()
+        })
+        .filter_map(|i| {
+            i.for_apply
+                .as_ref()
+                .map(|apply_iface| (iface_name_after_rename(apply_iface), *i))
+        })
+        .collect();
+
+    // TODO: Purge DHCP/autoconf IP/routes if DHCP/autoconf disabled
+
+    apply_tun_ifaces(apply_ifaces.as_slice())?;
+    apply_wireguard_ifaces(apply_ifaces.as_slice()).await?;
+    apply_ipv6_dad_transmits_before_ip(apply_ifaces.as_slice())?;
+
+    for stage in gen_apply_stages(apply_ifaces.as_slice())? {
+        apply_bond_mode_before_stage(stage.as_slice()).await?;
+        bring_up_and_wait_carrier(stage.as_slice()).await?;
+        let mut np_ifaces: Vec<nispor::IfaceConf> = Vec::new();
+        for apply_iface in stage.iter().copied() {
+            let mut np_iface = nipart_iface_to_np(apply_iface)?;
+            if opt.restore_permanent_mac {
+                if let Some(mac) = get_permanent_mac_to_restore(apply_iface) {
+                    log::debug!(
+                        "Restoring permanent MAC address {mac} of \
+                        interface {}",
+                        np_iface.name
+                    );
+                    np_iface.mac_address = Some(mac.to_string());
+                }
+            }
+            np_ifaces.push(np_iface);
+        }
+        apply_np_ifaces_concurrently(np_ifaces).await?;
+
+        // New bond is kept down by `apply_bond_mode_after_stage()` when
+        // changing its mode, which allows changing any bond option.
+        let new_bonds = apply_bond_mode_after_stage(stage.as_slice()).await?;
+        apply_bond_options(stage.as_slice())?;
+        if !new_bonds.is_empty() {
+            let mut np_ifaces: Vec<nispor::IfaceConf> = Vec::new();
+            for new_bond in new_bonds {
+                np_ifaces.push(nipart_iface_to_np(new_bond)?);
+            }
+            apply_np_ifaces_concurrently(np_ifaces).await?;
+        }
+        apply_mtu(stage.as_slice()).await?;
+    }
+
+    apply_ipv6_addr_gen_mode(apply_ifaces.as_slice())?;
+    apply_ipv6_token(apply_ifaces.as_slice()).await?;
+    apply_ip_sysctl(apply_ifaces.as_slice())?;
+
+    apply_ieee8021x(ifaces.as_slice()).await
+}
+
+// For interfaces with `wait-carrier` defined, bring link up without IP
+// configuration and wait for carrier before IP configuration is applied.
+async fn bring_up_and_wait_carrier(
+    stage: &[&MergedInterface],
+) -> Result<(), NipartError> {
+    let ifaces: Vec<(&str, &MergedInterface)> = stage
+        .iter()
+        .filter_map(|i| {
+            i.for_apply
+                .as_ref()
+                .map(|apply_iface| (iface_name_after_rename(apply_iface), *i))
+        })
+        .collect();
+    let wait_ifaces = get_wait_carrier_ifaces(ifaces.as_slice());
+    if wait_ifaces.is_empty() {
+        return Ok(());
+    }
     let mut np_ifaces: Vec<nispor::IfaceConf> = Vec::new();
-    for merged_iface in ifaces.iter().filter(|i| {
-        i.merged.iface_type() != InterfaceType::Unknown && !i.merged.is_absent()
-    }) {
-        np_ifaces.push(nipart_iface_to_np(merged_iface)?);
+    for (iface_name, iface) in ifaces {
+        if wait_ifaces.iter().any(|(n, _)| *n == iface_name) {
+            let mut np_iface = nipart_iface_to_np(iface)?;
+            np_iface.ipv4 = None;
+            np_iface.ipv6 = None;
+            np_ifaces.push(np_iface);
+        }
     }
+    apply_np_ifaces_concurrently(np_ifaces).await?;
+    futures::future::join_all(
+        wait_ifaces
+            .into_iter()
+            .map(|(iface_name, timeout)| wait_carrier(iface_name, timeout)),
+    )
+    .await;
+    Ok(())
+}
 
-    // TODO: Purge DHCP/autoconf IP/routes if DHCP/autoconf disabled
+// Each nispor NetConf apply retrieves full network state, hence we split
+// interfaces of the same stage into at most `NP_APPLY_CHUNK_COUNT` NetConfs
+// and apply them concurrently. All failures are aggregated into single error.
+async fn apply_np_ifaces_concurrently(
+    np_ifaces: Vec<nispor::IfaceConf>,
+) -> Result<(), NipartError> {
+    apply_np_ifaces_in_chunks(np_ifaces, NP_APPLY_CHUNK_COUNT).await
+}
 
-    let mut net_conf = nispor::NetConf::default();
-    net_conf.ifaces = Some(np_ifaces);
+async fn apply_np_ifaces_in_chunks(
+    np_ifaces: Vec<nispor::IfaceConf>,
+    chunk_count: usize,
+) -> Result<(), NipartError> {
+    let results = futures::future::join_all(
+        split_np_ifaces(np_ifaces, chunk_count).into_iter().map(
+            |chunk| async move {
+                let iface_names: Vec<String> =
+                    chunk.iter().map(|i| i.name.clone()).collect();
+                let mut net_conf = nispor::NetConf::default();
+                net_conf.ifaces = Some(chunk);
+                net_conf.apply_async().await.map_err(|e| {
+                    format!("{}: {}, {}", iface_names.join(","), e.kind, e.msg)
+                })
+            },
+        ),
+    )
+    .await;
 
-    if let Err(e) = net_conf.apply_async().await {
+    let errors: Vec<String> =
+        results.into_iter().filter_map(|r| r.err()).collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
         Err(NipartError::new(
             ErrorKind::PluginFailure,
-            format!("Unknown error from nipsor plugin: {}, {}", e.kind, e.msg),
+            format!("Unknown error from nipsor plugin: {}", errors.join("; ")),
         ))
-    } else {
+    }
+}
+
+// Split into at most `chunk_count` chunks with similar size
+fn split_np_ifaces(
+    np_ifaces: Vec<nispor::IfaceConf>,
+    chunk_count: usize,
+) -> Vec<Vec<nispor::IfaceConf>> {
+    if np_ifaces.is_empty() {
+        return Vec::new();
+    }
+    let chunk_size = np_ifaces.len().div_ceil(chunk_count.max(1));
+    let mut ret: Vec<Vec<nispor::IfaceConf>> = Vec::new();
+    let mut np_ifaces = np_ifaces.into_iter().peekable();
+    while np_ifaces.peek().is_some() {
+        ret.push(np_ifaces.by_ref().take(chunk_size).collect());
+    }
+    ret
+}
+
+async fn apply_ieee8021x(
+    ifaces: &[&MergedInterface],
+) -> Result<(), NipartError> {
+    // Interface name with configuration to start or None to stop
+    let mut changes: Vec<(String, Option<Ieee8021XConfig>)> = Vec::new();
+    for merged_iface in ifaces {
+        let apply_iface = match merged_iface.for_apply.as_ref() {
+            Some(i) => i,
+            None => continue,
+        };
+        let iface_name = iface_name_after_rename(apply_iface).to_string();
+        if apply_iface.is_absent() || apply_iface.is_down() {
+            changes.push((iface_name, None));
+        } else if let Some(conf) = apply_iface.base_iface().ieee8021x.as_ref() {
+            changes.push((iface_name, Some(conf.clone())));
+        }
+    }
+    if changes.is_empty() {
+        return Ok(());
+    }
+    // Managing wpa_supplicant involves blocking file and process operations
+    match tokio::task::spawn_blocking(move || -> Result<(), NipartError> {
+        for (iface_name, conf) in changes.iter() {
+            match conf {
+                Some(conf) => start_wpa_supplicant(iface_name, conf)?,
+                None => stop_wpa_supplicant(iface_name)?,
+            }
+        }
         Ok(())
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(e) => Err(NipartError::new(
+            ErrorKind::Bug,
+            format!("Failed to join 802.1X apply thread: {e}"),
+        )),
+    }
+}
+
+// The interface is renamed by `rename_ifaces()` before other changes
+pub(crate) fn iface_name_after_rename(iface: &Interface) -> &str {
+    if iface.is_absent() {
+        iface.name()
+    } else {
+        iface
+            .base_iface()
+            .rename_to
+            .as_deref()
+            .unwrap_or(iface.name())
     }
 }
 
@@ -70,9 +277,12 @@ fn nipart_iface_type_to_np(
     match nms_iface_type {
         InterfaceType::LinuxBridge => nispor::IfaceType::Bridge,
         InterfaceType::Bond => nispor::IfaceType::Bond,
+        InterfaceType::Dummy => nispor::IfaceType::Dummy,
         InterfaceType::Ethernet => nispor::IfaceType::Ethernet,
         InterfaceType::Veth => nispor::IfaceType::Veth,
         InterfaceType::Vlan => nispor::IfaceType::Vlan,
+        InterfaceType::Loopback => nispor::IfaceType::Loopback,
+        InterfaceType::Tun => nispor::IfaceType::Tun,
         _ => nispor::IfaceType::Unknown,
     }
 }
@@ -103,13 +313,37 @@ fn nipart_iface_to_np(
         }
     }
 
-    np_iface.name = for_apply.name().to_string();
+    np_iface.name = iface_name_after_rename(for_apply).to_string();
     np_iface.iface_type = Some(np_iface_type);
     if for_apply.is_absent() {
         np_iface.state = nispor::IfaceState::Absent;
         return Ok(np_iface);
     }
 
+    // The `state: down` only set link to administrative down, the virtual
+    // interface and its configuration are preserved.
+    if for_apply.is_down() {
+        np_iface.state = nispor::IfaceState::Down;
+        return Ok(np_iface);
+    }
+
+    if matches!(
+        for_apply.iface_type(),
+        InterfaceType::Gre | InterfaceType::GreTap
+    ) && merged_iface.current.is_none()
+    {
+        let e = NipartError::new(
+            ErrorKind::NotSupportedError,
+            format!(
+                "Creating {} interface {} is not supported yet",
+                for_apply.iface_type(),
+                for_apply.name()
+            ),
+        );
+        log::error!("{}", e);
+        return Err(e);
+    }
+
     np_iface.state = nispor::IfaceState::Up;
 
     let base_iface = &for_apply.base_iface();
@@ -125,6 +359,9 @@ fn nipart_iface_to_np(
 
     if let Interface::Ethernet(eth_iface) = for_apply {
         np_iface.veth = nms_veth_conf_to_np(eth_iface.veth.as_ref());
+        np_iface.sriov = nipart_sriov_vfs_to_np(
+            eth_iface.ethernet.as_ref().and_then(|e| e.sr_iov.as_ref()),
+        );
     } else if let Interface::Vlan(vlan_iface) = &merged_iface.merged {
         np_iface.vlan = nms_vlan_conf_to_np(vlan_iface.vlan.as_ref());
     }
@@ -132,15 +369,50 @@ fn nipart_iface_to_np(
     Ok(np_iface)
 }
 
+// Return permanent MAC address if interface is changing to down state or
+// detaching from bond while its MAC address differs from permanent one.
+fn get_permanent_mac_to_restore(
+    merged_iface: &MergedInterface,
+) -> Option<&str> {
+    let (for_apply, cur_iface) = match (
+        merged_iface.for_apply.as_ref(),
+        merged_iface.current.as_ref(),
+    ) {
+        (Some(d), Some(c)) => (d, c),
+        _ => return None,
+    };
+    if for_apply.base_iface().mac_address.is_some() {
+        return None;
+    }
+    let cur_base = cur_iface.base_iface();
+    let perm_mac = cur_base.permanent_mac_address.as_deref()?;
+    if cur_base.mac_address.as_deref().map(|m| m.to_uppercase())
+        == Some(perm_mac.to_uppercase())
+    {
+        return None;
+    }
+    let detach_from_bond = cur_base.controller_type
+        == Some(InterfaceType::Bond)
+        && for_apply.base_iface().controller.is_some()
+        && for_apply.base_iface().controller != cur_base.controller;
+    if for_apply.is_down() || detach_from_bond {
+        Some(perm_mac)
+    } else {
+        None
+    }
+}
+
 async fn delete_ifaces(
     merged_ifaces: &MergedInterfaces,
 ) -> Result<(), NipartError> {
     let mut deleted_veths: Vec<&str> = Vec::new();
     let mut np_ifaces: Vec<nispor::IfaceConf> = Vec::new();
+    // Only delete interfaces marked for apply, as sub-states of parallel
+    // apply hold all absent interfaces but only one of them applies it.
     for iface in merged_ifaces
         .kernel_ifaces
         .values()
-        .filter(|i| i.merged.is_absent())
+        .filter(|i| i.merged.is_absent() && i.for_apply.is_some())
     {
         // Deleting one end of veth peer is enough
         if deleted_veths.contains(&iface.merged.name()) {
@@ -157,6 +429,23 @@ async fn delete_ifaces(
                 deleted_veths.push(peer_name);
             }
         }
+        // Interface of unsupported type might be marked as absent by its
+        // parent or controller removal, kernel will handle it.
+        if iface.merged.iface_type() == InterfaceType::Unknown {
+            log::debug!(
+                "Not deleting interface {} of unsupported type",
+                iface.merged.name()
+            );
+            continue;
+        }
+        if iface.merged.iface_type() == InterfaceType::Loopback {
+            log::debug!(
+                "Restoring loopback interface {} to default",
+                iface.merged.name()
+            );
+            np_ifaces.push(np_loopback_default_conf(iface.merged.name()));
+            continue;
+        }
         log::debug!("Deleting interface {}", iface.merged.name());
         np_ifaces.push(nipart_iface_to_np(iface)?);
     }
@@ -193,6 +482,21 @@ pub(crate) async fn nispor_apply_dhcp_lease(
             np_iface.ipv4 = Some(ip_conf);
             np_iface.state = nispor::IfaceState::Up;
             net_conf.ifaces = Some(vec![np_iface]);
+            net_conf.routes = Some(
+                lease
+                    .gateways
+                    .iter()
+                    .map(|gw| {
+                        let mut rt = nispor::RouteConf::default();
+                        rt.dst = "0.0.0.0/0".to_string();
+                        rt.oif = Some(lease.iface.to_string());
+                        rt.via = Some(gw.to_string());
+                        rt.metric = lease.route_metric;
+                        rt.protocol = Some(nispor::RouteProtocol::Dhcp);
+                        rt
+                    })
+                    .collect(),
+            );
 
             log::debug!("Plugin nispor apply {net_conf:?}");
 
@@ -213,3 +517,209 @@ pub(crate) async fn nispor_apply_dhcp_lease(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use nipart::{MergedNetworkState, NetworkState};
+
+    use super::{
+        apply_np_ifaces_in_chunks, get_permanent_mac_to_restore,
+        nipart_iface_to_np, split_np_ifaces,
+    };
+
+    const DUMMY_COUNT: usize = 300;
+
+    fn gen_np_dummies(state: nispor::IfaceState) -> Vec<nispor::IfaceConf> {
+        (0..DUMMY_COUNT)
+            .map(|i| {
+                let mut np_iface = nispor::IfaceConf::default();
+                np_iface.name = format!("npbench{i}");
+                np_iface.iface_type = Some(nispor::IfaceType::Dummy);
+                np_iface.state = state.clone();
+                np_iface
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_new_dummy_as_bridge_port_to_np() {
+        let merged = MergedNetworkState::new(
+            NetworkState::new_from_yaml(
+                r"---
+                interfaces:
+                - name: br0
+                  type: linux-bridge
+                  state: up
+                  bridge:
+                    port:
+                    - name: dummy1
+                - name: dummy1
+                  type: dummy
+                  state: up",
+            )
+            .unwrap(),
+            NetworkState::new(),
+            false,
+            false,
+        )
+        .unwrap();
+
+        let np_iface =
+            nipart_iface_to_np(&merged.interfaces.kernel_ifaces["dummy1"])
+                .unwrap();
+
+        assert!(matches!(
+            np_iface.iface_type,
+            Some(nispor::IfaceType::Dummy)
+        ));
+        assert!(matches!(np_iface.state, nispor::IfaceState::Up));
+        assert_eq!(np_iface.controller.as_deref(), Some("br0"));
+    }
+
+    fn gen_mac_changed_eth1() -> NetworkState {
+        NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              mac-address: 00:23:45:67:89:1B
+              permanent-mac-address: 00:23:45:67:89:1A",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_restore_permanent_mac_on_down() {
+        let merged = MergedNetworkState::new(
+            NetworkState::new_from_yaml(
+                r"---
+                interfaces:
+                - name: eth1
+                  type: ethernet
+                  state: down",
+            )
+            .unwrap(),
+            gen_mac_changed_eth1(),
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            get_permanent_mac_to_restore(
+                &merged.interfaces.kernel_ifaces["eth1"]
+            ),
+            Some("00:23:45:67:89:1A")
+        );
+    }
+
+    #[test]
+    fn test_no_restore_permanent_mac_with_explicit_mac() {
+        let merged = MergedNetworkState::new(
+            NetworkState::new_from_yaml(
+                r"---
+                interfaces:
+                - name: eth1
+                  type: ethernet
+                  state: down
+                  mac-address: 00:23:45:67:89:1C",
+            )
+            .unwrap(),
+            gen_mac_changed_eth1(),
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert!(get_permanent_mac_to_restore(
+            &merged.interfaces.kernel_ifaces["eth1"]
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_no_restore_permanent_mac_on_up() {
+        let merged = MergedNetworkState::new(
+            NetworkState::new_from_yaml(
+                r"---
+                interfaces:
+                - name: eth1
+                  type: ethernet
+                  state: up
+                  mtu: 9000",
+            )
+            .unwrap(),
+            gen_mac_changed_eth1(),
+            false,
+            false,
+        )
+        .unwrap();
+
+        assert!(get_permanent_mac_to_restore(
+            &merged.interfaces.kernel_ifaces["eth1"]
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_split_np_ifaces() {
+        let chunks = split_np_ifaces(gen_np_dummies(nispor::IfaceState::Up), 4);
+
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks.iter().all(|c| c.len() == DUMMY_COUNT / 4));
+        let names: Vec<String> =
+            chunks.into_iter().flatten().map(|i| i.name).collect();
+        let expected: Vec<String> =
+            (0..DUMMY_COUNT).map(|i| format!("npbench{i}")).collect();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn test_split_np_ifaces_less_than_chunk_count() {
+        let mut np_ifaces = gen_np_dummies(nispor::IfaceState::Up);
+        np_ifaces.truncate(3);
+
+        let chunks = split_np_ifaces(np_ifaces, 4);
+
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|c| c.len() == 1));
+        assert!(split_np_ifaces(Vec::new(), 4).is_empty());
+    }
+
+    async fn time_apply_dummies(chunk_count: usize) -> Duration {
+        let now = Instant::now();
+        apply_np_ifaces_in_chunks(
+            gen_np_dummies(nispor::IfaceState::Up),
+            chunk_count,
+        )
+        .await
+        .unwrap();
+        let elapsed = now.elapsed();
+        apply_np_ifaces_in_chunks(
+            gen_np_dummies(nispor::IfaceState::Absent),
+            chunk_count,
+        )
+        .await
+        .unwrap();
+        elapsed
+    }
+
+    // Compare with the old behavior of applying each interface in its own
+    // NetConf, which retrieves full network state for every interface.
+    #[tokio::test]
+    #[ignore = "requires root to create dummy interfaces"]
+    async fn bench_apply_many_dummies() {
+        let per_iface = time_apply_dummies(DUMMY_COUNT).await;
+        let chunked = time_apply_dummies(super::NP_APPLY_CHUNK_COUNT).await;
+
+        println!(
+            "Creating {DUMMY_COUNT} dummies took {per_iface:?} using one \
+            NetConf per interface, {chunked:?} using {} NetConfs",
+            super::NP_APPLY_CHUNK_COUNT
+        );
+        assert!(chunked < per_iface);
+    }
+}
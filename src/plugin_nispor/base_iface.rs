@@ -4,7 +4,7 @@ use nipart::{BaseInterface, InterfaceState, InterfaceType};
 
 use crate::{
     ethtool::np_ethtool_to_nipart,
-    ip::{np_ipv4_to_nipart, np_ipv6_to_nipart},
+    ip::{fill_ip_sysctl, np_ipv4_to_nipart, np_ipv6_to_nipart},
     mptcp::get_iface_mptcp_conf,
 };
 
@@ -14,6 +14,7 @@ fn np_iface_type_to_nipart(np_iface_type: &nispor::IfaceType) -> InterfaceType {
         nispor::IfaceType::Bridge => InterfaceType::LinuxBridge,
         nispor::IfaceType::Dummy => InterfaceType::Dummy,
         nispor::IfaceType::Ethernet => InterfaceType::Ethernet,
+        nispor::IfaceType::IpVlan => InterfaceType::IpVlan,
         nispor::IfaceType::Loopback => InterfaceType::Loopback,
         nispor::IfaceType::MacSec => InterfaceType::MacSec,
         nispor::IfaceType::MacVlan => InterfaceType::MacVlan,
@@ -26,6 +27,15 @@ fn np_iface_type_to_nipart(np_iface_type: &nispor::IfaceType) -> InterfaceType {
         nispor::IfaceType::Ipoib => InterfaceType::InfiniBand,
         nispor::IfaceType::Tun => InterfaceType::Tun,
         nispor::IfaceType::Xfrm => InterfaceType::Xfrm,
+        nispor::IfaceType::Other(v) if v.eq_ignore_ascii_case("gre") => {
+            InterfaceType::Gre
+        }
+        nispor::IfaceType::Other(v) if v.eq_ignore_ascii_case("gretap") => {
+            InterfaceType::GreTap
+        }
+        nispor::IfaceType::Other(v) if v.eq_ignore_ascii_case("wireguard") => {
+            InterfaceType::Wireguard
+        }
         nispor::IfaceType::Other(v) => InterfaceType::Other(v.to_lowercase()),
         _ => InterfaceType::Other(format!("{np_iface_type:?}").to_lowercase()),
     }
@@ -59,6 +69,7 @@ pub(crate) fn np_iface_to_base_iface(
     base_iface.iface_type = np_iface_type_to_nipart(&np_iface.iface_type);
     base_iface.ipv4 = np_ipv4_to_nipart(np_iface, running_config_only);
     base_iface.ipv6 = np_ipv6_to_nipart(np_iface, running_config_only);
+    fill_ip_sysctl(&mut base_iface);
     base_iface.mac_address = Some(np_iface.mac_address.to_uppercase());
     base_iface.permanent_mac_address = get_permanent_mac_address(np_iface);
     base_iface.controller = np_iface.controller.as_ref().map(|c| c.to_string());
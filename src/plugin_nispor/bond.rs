@@ -1,13 +1,19 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use futures::stream::TryStreamExt;
 use log::warn;
 use nipart::{
-    BaseInterface, BondAdSelect, BondAllPortsActive, BondArpAllTargets,
-    BondArpValidate, BondConfig, BondFailOverMac, BondInterface, BondLacpRate,
-    BondMode, BondOptions, BondPortConfig, BondPrimaryReselect,
-    BondXmitHashPolicy,
+    BaseInterface, BondAdInfo, BondAdSelect, BondAllPortsActive,
+    BondArpAllTargets, BondArpValidate, BondConfig, BondFailOverMac,
+    BondInterface, BondLacpRate, BondMode, BondOptions, BondPortConfig,
+    BondPrimaryReselect, BondXmitHashPolicy, ErrorKind, Interface,
+    MergedInterface, NipartError,
 };
 
+use crate::apply::iface_name_after_rename;
+
+const SYSFS_CLASS_NET: &str = "/sys/class/net";
+
 pub(crate) fn np_bond_to_nipart(
     np_iface: &nispor::Iface,
     base_iface: BaseInterface,
@@ -51,6 +57,16 @@ pub(crate) fn np_bond_to_nipart(
                 Some(BondMode::Unknown)
             }
         };
+        if let Some(np_ad_info) = np_bond.ad_info.as_ref() {
+            let mut ad_info = BondAdInfo::new();
+            ad_info.aggregator = Some(np_ad_info.aggregator);
+            ad_info.num_ports = Some(np_ad_info.num_ports);
+            ad_info.actor_system = np_bond.ad_actor_system.clone();
+            ad_info.actor_key = Some(np_ad_info.actor_key);
+            ad_info.partner_mac = Some(np_ad_info.partner_mac.to_uppercase());
+            ad_info.partner_key = Some(np_ad_info.partner_key);
+            bond_conf.ad_info = Some(ad_info);
+        }
     }
     bond_iface.bond = Some(bond_conf);
     bond_iface
@@ -221,3 +237,447 @@ fn np_bond_options_to_nipart(np_iface: &nispor::Iface) -> BondOptions {
     }
     options
 }
+
+// Nispor does not support changing bond mode and options yet, use sysfs
+// bonding interface directly.
+// Kernel only allows changing bond mode when bond is administratively down
+// and holds no port, hence for existing bond changing mode, we detach all
+// its ports and bring it down before the stage holding the bond is applied.
+// The apply of that stage brings the bond up again, the ports are reattached
+// by later stage as they are marked as changed by
+// `MergedInterfaces::process_bond_mode_change()`.
+pub(crate) async fn apply_bond_mode_before_stage(
+    stage: &[&MergedInterface],
+) -> Result<(), NipartError> {
+    let changes: Vec<(&str, &BondMode, Vec<&str>)> = stage
+        .iter()
+        .filter(|i| i.current.is_some())
+        .filter_map(|i| get_bond_mode_change(i))
+        .collect();
+    if changes.is_empty() {
+        return Ok(());
+    }
+    for (bond_name, _, ports) in changes.as_slice() {
+        for port in ports {
+            log::info!(
+                "Detaching port {port} from bond {bond_name} for bond mode \
+                change"
+            );
+            write_bond_sysfs(bond_name, "slaves", &format!("-{port}"))?;
+        }
+    }
+    set_links_down(
+        changes
+            .iter()
+            .map(|(bond_name, _, _)| *bond_name)
+            .collect::<Vec<&str>>()
+            .as_slice(),
+    )
+    .await?;
+    for (bond_name, mode, _) in changes {
+        log::info!("Changing mode of bond {bond_name} to {mode}");
+        write_bond_sysfs(bond_name, "mode", mode.to_string().as_str())?;
+    }
+    Ok(())
+}
+
+// New bond is created by nispor in kernel default mode `balance-rr` and
+// brought up, bring it down to change its mode. Return the bonds changed,
+// caller should apply them again to bring them up.
+pub(crate) async fn apply_bond_mode_after_stage<'a>(
+    stage: &[&'a MergedInterface],
+) -> Result<Vec<&'a MergedInterface>, NipartError> {
+    let mut new_bonds: Vec<&MergedInterface> = Vec::new();
+    let mut changes: Vec<(&str, &BondMode)> = Vec::new();
+    for merged_iface in stage.iter().filter(|i| i.current.is_none()) {
+        if let Some((bond_name, mode, _)) = get_bond_mode_change(merged_iface) {
+            new_bonds.push(merged_iface);
+            changes.push((bond_name, mode));
+        }
+    }
+    if changes.is_empty() {
+        return Ok(Vec::new());
+    }
+    set_links_down(
+        changes
+            .iter()
+            .map(|(bond_name, _)| *bond_name)
+            .collect::<Vec<&str>>()
+            .as_slice(),
+    )
+    .await?;
+    for (bond_name, mode) in changes {
+        log::info!("Changing mode of new bond {bond_name} to {mode}");
+        write_bond_sysfs(bond_name, "mode", mode.to_string().as_str())?;
+    }
+    Ok(new_bonds)
+}
+
+// Only write bond options differing from current value, as some options
+// cannot be changed when bond is up or has ports.
+pub(crate) fn apply_bond_options(
+    stage: &[&MergedInterface],
+) -> Result<(), NipartError> {
+    for merged_iface in stage {
+        let Some(for_apply) = merged_iface.for_apply.as_ref() else {
+            continue;
+        };
+        let Interface::Bond(apply_iface) = for_apply else {
+            continue;
+        };
+        if !for_apply.is_up() {
+            continue;
+        }
+        let Some(des_opts) =
+            apply_iface.bond.as_ref().and_then(|b| b.options.as_ref())
+        else {
+            continue;
+        };
+        let cur_opts = match merged_iface.current.as_ref() {
+            Some(Interface::Bond(cur_iface)) => {
+                cur_iface.bond.as_ref().and_then(|b| b.options.as_ref())
+            }
+            _ => None,
+        };
+        let bond_name = iface_name_after_rename(for_apply);
+        for (option, value) in get_changed_bond_options(des_opts, cur_opts) {
+            log::debug!(
+                "Setting option {option} of bond {bond_name} to {value}"
+            );
+            write_bond_sysfs(bond_name, option, value.as_str())?;
+        }
+    }
+    Ok(())
+}
+
+// Return bond name, desired mode and current ports if bond is changing mode.
+// New bond using kernel default mode `balance-rr` is not included.
+fn get_bond_mode_change(
+    merged_iface: &MergedInterface,
+) -> Option<(&str, &BondMode, Vec<&str>)> {
+    let for_apply = merged_iface.for_apply.as_ref()?;
+    let Interface::Bond(apply_iface) = for_apply else {
+        return None;
+    };
+    if !for_apply.is_up() {
+        return None;
+    }
+    let des_mode = apply_iface.bond.as_ref().and_then(|b| b.mode.as_ref())?;
+    let bond_name = iface_name_after_rename(for_apply);
+    match merged_iface.current.as_ref() {
+        Some(Interface::Bond(cur_iface)) => {
+            if cur_iface.bond.as_ref().and_then(|b| b.mode.as_ref())
+                == Some(des_mode)
+            {
+                None
+            } else {
+                Some((
+                    bond_name,
+                    des_mode,
+                    cur_iface.ports().unwrap_or_default(),
+                ))
+            }
+        }
+        None if des_mode != &BondMode::RoundRobin => {
+            Some((bond_name, des_mode, Vec::new()))
+        }
+        _ => None,
+    }
+}
+
+// The `arp_ip_target` is handled as list of `+<ip>` and `-<ip>` actions.
+fn get_changed_bond_options(
+    des_opts: &BondOptions,
+    cur_opts: Option<&BondOptions>,
+) -> Vec<(&'static str, String)> {
+    let cur_values =
+        cur_opts.map(|o| o.to_kernel_options()).unwrap_or_default();
+    let mut ret = Vec::new();
+    for (option, value) in des_opts.to_kernel_options() {
+        let cur_value = cur_values
+            .iter()
+            .find(|(o, _)| *o == option)
+            .map(|(_, v)| v.as_str());
+        if cur_value == Some(value.as_str()) {
+            continue;
+        }
+        if option == "arp_ip_target" {
+            let des_targets: Vec<&str> =
+                value.split(',').filter(|t| !t.is_empty()).collect();
+            let cur_targets: Vec<&str> = cur_value
+                .map(|v| v.split(',').filter(|t| !t.is_empty()).collect())
+                .unwrap_or_default();
+            for target in cur_targets.iter() {
+                if !des_targets.contains(target) {
+                    ret.push((option, format!("-{target}")));
+                }
+            }
+            for target in des_targets.iter() {
+                if !cur_targets.contains(target) {
+                    ret.push((option, format!("+{target}")));
+                }
+            }
+        } else {
+            ret.push((option, value));
+        }
+    }
+    ret
+}
+
+fn write_bond_sysfs(
+    bond_name: &str,
+    option: &str,
+    value: &str,
+) -> Result<(), NipartError> {
+    let path = format!("{SYSFS_CLASS_NET}/{bond_name}/bonding/{option}");
+    std::fs::write(&path, value).map_err(|e| {
+        NipartError::new(
+            ErrorKind::PluginFailure,
+            format!("Failed to write {value} to {path}: {e}"),
+        )
+    })
+}
+
+async fn set_links_down(iface_names: &[&str]) -> Result<(), NipartError> {
+    let (conn, handle, _) = rtnetlink::new_connection().map_err(|e| {
+        NipartError::new(
+            ErrorKind::PluginFailure,
+            format!("Failed to create rtnetlink connection: {e}"),
+        )
+    })?;
+    tokio::spawn(conn);
+
+    for iface_name in iface_names {
+        let index = match handle
+            .link()
+            .get()
+            .match_name(iface_name.to_string())
+            .execute()
+            .try_next()
+            .await
+        {
+            Ok(Some(link)) => link.header.index,
+            Ok(None) => {
+                return Err(NipartError::new(
+                    ErrorKind::PluginFailure,
+                    format!("Interface {iface_name} not found"),
+                ));
+            }
+            Err(e) => {
+                return Err(NipartError::new(
+                    ErrorKind::PluginFailure,
+                    format!("Failed to query interface {iface_name}: {e}"),
+                ));
+            }
+        };
+        if let Err(e) = handle.link().set(index).down().execute().await {
+            return Err(NipartError::new(
+                ErrorKind::PluginFailure,
+                format!("Failed to set interface {iface_name} down: {e}"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use nipart::{
+        BaseInterface, BondMode, BondOptions, Interface, InterfaceState,
+        InterfaceType, MergedInterface, MergedNetworkState, NetworkState,
+    };
+
+    use super::{
+        get_bond_mode_change, get_changed_bond_options, np_bond_to_nipart,
+    };
+
+    fn gen_merged_state(desired_yaml: &str) -> MergedNetworkState {
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              controller: bond99
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: balance-rr
+                port:
+                - eth1",
+        )
+        .unwrap();
+        MergedNetworkState::new(
+            NetworkState::new_from_yaml(desired_yaml).unwrap(),
+            current,
+            false,
+            false,
+        )
+        .unwrap()
+    }
+
+    fn get_iface<'a>(
+        merged: &'a MergedNetworkState,
+        iface_name: &str,
+    ) -> &'a MergedInterface {
+        &merged.interfaces.kernel_ifaces[iface_name]
+    }
+
+    #[test]
+    fn test_bond_mode_change_of_existing_bond() {
+        let merged = gen_merged_state(
+            r"---
+            interfaces:
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: active-backup",
+        );
+
+        assert_eq!(
+            get_bond_mode_change(get_iface(&merged, "bond99")),
+            Some(("bond99", &BondMode::ActiveBackup, vec!["eth1"]))
+        );
+    }
+
+    #[test]
+    fn test_bond_mode_change_of_new_bond() {
+        let merged = gen_merged_state(
+            r"---
+            interfaces:
+            - name: bond1
+              type: bond
+              state: up
+              link-aggregation:
+                mode: 802.3ad
+            - name: bond2
+              type: bond
+              state: up
+              link-aggregation:
+                mode: balance-rr",
+        );
+
+        assert_eq!(
+            get_bond_mode_change(get_iface(&merged, "bond1")),
+            Some(("bond1", &BondMode::LACP, Vec::new()))
+        );
+        // Kernel default mode needs no change
+        assert_eq!(get_bond_mode_change(get_iface(&merged, "bond2")), None);
+    }
+
+    #[test]
+    fn test_bond_mode_unchanged() {
+        let merged = gen_merged_state(
+            r"---
+            interfaces:
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: balance-rr",
+        );
+
+        assert_eq!(get_bond_mode_change(get_iface(&merged, "bond99")), None);
+    }
+
+    #[test]
+    fn test_changed_bond_options() {
+        let mut cur_opts = BondOptions::default();
+        cur_opts.miimon = Some(100);
+        cur_opts.updelay = Some(0);
+        cur_opts.arp_ip_target = Some("192.0.2.1,192.0.2.2".to_string());
+        let mut des_opts = BondOptions::default();
+        des_opts.miimon = Some(200);
+        des_opts.updelay = Some(0);
+        des_opts.use_carrier = Some(false);
+        des_opts.arp_ip_target = Some("192.0.2.2,192.0.2.3".to_string());
+
+        assert_eq!(
+            get_changed_bond_options(&des_opts, Some(&cur_opts)),
+            vec![
+                ("arp_ip_target", "-192.0.2.1".to_string()),
+                ("arp_ip_target", "+192.0.2.3".to_string()),
+                ("miimon", "200".to_string()),
+                ("use_carrier", "0".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_bond_options_of_new_bond() {
+        let mut des_opts = BondOptions::default();
+        des_opts.miimon = Some(200);
+        des_opts.arp_ip_target = Some("192.0.2.1".to_string());
+
+        assert_eq!(
+            get_changed_bond_options(&des_opts, None),
+            vec![
+                ("arp_ip_target", "+192.0.2.1".to_string()),
+                ("miimon", "200".to_string()),
+            ]
+        );
+    }
+
+    // Mock of nispor reported LACP bond without partner
+    fn gen_np_lacp_bond() -> nispor::Iface {
+        let mut np_ad_info = nispor::BondAdInfo::default();
+        np_ad_info.aggregator = 1;
+        np_ad_info.num_ports = 2;
+        np_ad_info.actor_key = 9;
+        np_ad_info.partner_key = 1;
+        np_ad_info.partner_mac = "00:00:00:00:00:00".to_string();
+        let mut np_bond = nispor::BondInfo::default();
+        np_bond.mode = nispor::BondMode::Ieee8021AD;
+        np_bond.ad_actor_system = Some("00:23:45:67:89:1A".to_string());
+        np_bond.ad_info = Some(np_ad_info);
+        let mut np_iface = nispor::Iface::default();
+        np_iface.name = "bond99".to_string();
+        np_iface.bond = Some(np_bond);
+        np_iface
+    }
+
+    #[test]
+    fn test_bond_ad_info_query_only() {
+        let mut base_iface = BaseInterface::new();
+        base_iface.name = "bond99".to_string();
+        base_iface.iface_type = InterfaceType::Bond;
+        base_iface.state = InterfaceState::Up;
+        let bond_iface = np_bond_to_nipart(&gen_np_lacp_bond(), base_iface);
+
+        let ad_info = bond_iface
+            .bond
+            .as_ref()
+            .and_then(|b| b.ad_info.as_ref())
+            .unwrap();
+        assert_eq!(ad_info.aggregator, Some(1));
+        assert_eq!(ad_info.num_ports, Some(2));
+        assert_eq!(ad_info.actor_system.as_deref(), Some("00:23:45:67:89:1A"));
+        assert_eq!(ad_info.actor_key, Some(9));
+        assert_eq!(ad_info.partner_mac.as_deref(), Some("00:00:00:00:00:00"));
+        assert_eq!(ad_info.partner_key, Some(1));
+
+        // Queried state used as desired state should not apply ad-info
+        let mut state = NetworkState::new();
+        state.interfaces.push(Interface::Bond(Box::new(bond_iface)));
+        let merged =
+            MergedNetworkState::new(state, NetworkState::new(), false, false)
+                .unwrap();
+        let apply_iface = merged.interfaces.kernel_ifaces["bond99"]
+            .for_apply
+            .as_ref()
+            .unwrap();
+        if let Interface::Bond(apply_bond_iface) = apply_iface {
+            assert_eq!(
+                apply_bond_iface
+                    .bond
+                    .as_ref()
+                    .and_then(|b| b.ad_info.as_ref()),
+                None
+            );
+        } else {
+            panic!("Expecting bond interface, got {apply_iface:?}");
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::{Duration, Instant};
+
+use nipart::MergedInterface;
+
+const CARRIER_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+// Return list of (interface name, timeout in milliseconds) for interfaces
+// with `wait-carrier` defined and going to be up.
+pub(crate) fn get_wait_carrier_ifaces<'a>(
+    ifaces: &[(&'a str, &MergedInterface)],
+) -> Vec<(&'a str, u32)> {
+    ifaces
+        .iter()
+        .filter_map(|(iface_name, iface)| {
+            iface
+                .for_apply
+                .as_ref()
+                .filter(|i| i.is_up())
+                .and_then(|i| i.base_iface().wait_carrier)
+                .filter(|t| *t > 0)
+                .map(|t| (*iface_name, t))
+        })
+        .collect()
+}
+
+// Timeout is not treated as failure, the IP configuration still applies
+// and `wait-ip` verification will complain if IP not acquired.
+pub(crate) async fn wait_carrier(iface_name: &str, timeout_ms: u32) {
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms.into());
+    loop {
+        if has_carrier(iface_name) {
+            log::debug!("Interface {iface_name} got carrier");
+            return;
+        }
+        if Instant::now() >= deadline {
+            log::warn!(
+                "Interface {iface_name} got no carrier after waiting \
+                {timeout_ms} milliseconds, continue IP configuration anyway"
+            );
+            return;
+        }
+        tokio::time::sleep(CARRIER_CHECK_INTERVAL).await;
+    }
+}
+
+// Kernel fails the read of `carrier` file when link is administrative down.
+fn has_carrier(iface_name: &str) -> bool {
+    std::fs::read_to_string(format!("/sys/class/net/{iface_name}/carrier"))
+        .map(|c| c.trim() == "1")
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use nipart::{MergedInterface, MergedNetworkState, NetworkState};
+
+    use super::{get_wait_carrier_ifaces, wait_carrier};
+
+    #[test]
+    fn test_get_wait_carrier_ifaces() {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              wait-carrier: 3000
+            - name: dummy2
+              type: dummy
+              state: up",
+        )
+        .unwrap();
+        let merged =
+            MergedNetworkState::new(desired, NetworkState::new(), false, false)
+                .unwrap();
+        let ifaces: Vec<(&str, &MergedInterface)> = ["dummy1", "dummy2"]
+            .iter()
+            .map(|n| (*n, &merged.interfaces.kernel_ifaces[*n]))
+            .collect();
+
+        assert_eq!(get_wait_carrier_ifaces(&ifaces), vec![("dummy1", 3000)]);
+    }
+
+    // Loopback interface always has carrier like dummy interface, but
+    // exists without root permission.
+    #[tokio::test]
+    async fn test_wait_carrier_no_delay_when_carrier_up() {
+        let start = Instant::now();
+        wait_carrier("lo", 5000).await;
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_wait_carrier_timeout_not_fail() {
+        let start = Instant::now();
+        wait_carrier("not_exist0", 200).await;
+
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(200));
+        assert!(elapsed < Duration::from_secs(5));
+    }
+}
@@ -1,7 +1,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use nipart::{
-    EthtoolCoalesceConfig, EthtoolConfig, EthtoolPauseConfig, EthtoolRingConfig,
+    EthtoolCoalesceConfig, EthtoolConfig, EthtoolPauseConfig,
+    EthtoolRingConfig, EthtoolRingSize,
 };
 
 pub(crate) fn np_ethtool_to_nipart(
@@ -50,13 +51,13 @@ fn gen_ethtool_config(ethtool_info: &nispor::EthtoolInfo) -> EthtoolConfig {
     }
     if let Some(ring) = &ethtool_info.ring {
         let mut ring_config = EthtoolRingConfig::new();
-        ring_config.rx = ring.rx;
+        ring_config.rx = ring.rx.map(EthtoolRingSize::Value);
         ring_config.rx_max = ring.rx_max;
         ring_config.rx_jumbo = ring.rx_jumbo;
         ring_config.rx_jumbo_max = ring.rx_jumbo_max;
         ring_config.rx_mini = ring.rx_mini;
         ring_config.rx_mini_max = ring.rx_mini_max;
-        ring_config.tx = ring.tx;
+        ring_config.tx = ring.tx.map(EthtoolRingSize::Value);
         ring_config.tx_max = ring.tx_max;
 
         ret.ring = Some(ring_config);
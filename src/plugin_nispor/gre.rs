@@ -0,0 +1,208 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use futures::stream::TryStreamExt;
+use nipart::{
+    BaseInterface, ErrorKind, GreConfig, GreInterface, GreTapInterface,
+    NipartError,
+};
+use rtnetlink::packet_route::link::{
+    InfoData, InfoGreTap, InfoGreTun, InfoKind, LinkAttribute, LinkInfo,
+};
+use rtnetlink::packet_utils::nla::{DefaultNla, Nla};
+
+// Defined in linux/if_tunnel.h
+const IFLA_GRE_LINK: u16 = 1;
+const IFLA_GRE_IFLAGS: u16 = 2;
+const IFLA_GRE_OFLAGS: u16 = 3;
+const IFLA_GRE_IKEY: u16 = 4;
+const IFLA_GRE_OKEY: u16 = 5;
+const IFLA_GRE_LOCAL: u16 = 6;
+const IFLA_GRE_REMOTE: u16 = 7;
+const IFLA_GRE_TTL: u16 = 8;
+const IFLA_GRE_TOS: u16 = 9;
+
+const GRE_KEY: u16 = 0x2000;
+
+pub(crate) fn is_np_gre_iface(np_iface: &nispor::Iface) -> bool {
+    if let nispor::IfaceType::Other(t) = &np_iface.iface_type {
+        t.eq_ignore_ascii_case("gre") || t.eq_ignore_ascii_case("gretap")
+    } else {
+        false
+    }
+}
+
+pub(crate) fn np_gre_to_nipart(
+    base_iface: BaseInterface,
+    gre_confs: &HashMap<String, GreConfig>,
+) -> GreInterface {
+    let mut ret = GreInterface::default();
+    ret.gre = gre_confs.get(base_iface.name.as_str()).cloned();
+    ret.base = base_iface;
+    ret
+}
+
+pub(crate) fn np_gre_tap_to_nipart(
+    base_iface: BaseInterface,
+    gre_confs: &HashMap<String, GreConfig>,
+) -> GreTapInterface {
+    let mut ret = GreTapInterface::default();
+    ret.gre = gre_confs.get(base_iface.name.as_str()).cloned();
+    ret.base = base_iface;
+    ret
+}
+
+#[derive(Debug, Default)]
+struct GreLinkInfo {
+    link: Option<u32>,
+    iflags: u16,
+    oflags: u16,
+    ikey: Option<u32>,
+    okey: Option<u32>,
+    local: Option<IpAddr>,
+    remote: Option<IpAddr>,
+    ttl: Option<u8>,
+    tos: Option<u8>,
+}
+
+// Nispor does not support GRE tunnel yet, use rtnetlink directly.
+pub(crate) async fn get_gre_confs(
+) -> Result<HashMap<String, GreConfig>, NipartError> {
+    let (conn, handle, _) = rtnetlink::new_connection().map_err(|e| {
+        NipartError::new(
+            ErrorKind::PluginFailure,
+            format!("Failed to create rtnetlink connection: {e}"),
+        )
+    })?;
+    tokio::spawn(conn);
+
+    let mut index_to_name: HashMap<u32, String> = HashMap::new();
+    let mut gre_infos: Vec<(String, GreLinkInfo)> = Vec::new();
+
+    let mut links = handle.link().get().execute();
+    loop {
+        let link = match links.try_next().await {
+            Ok(Some(l)) => l,
+            Ok(None) => break,
+            Err(e) => {
+                return Err(NipartError::new(
+                    ErrorKind::PluginFailure,
+                    format!("Failed to query interfaces: {e}"),
+                ));
+            }
+        };
+        let mut name: Option<String> = None;
+        let mut is_gre = false;
+        let mut gre_info = GreLinkInfo::default();
+        for attr in link.attributes {
+            match attr {
+                LinkAttribute::IfName(n) => name = Some(n),
+                LinkAttribute::LinkInfo(infos) => {
+                    for info in infos {
+                        match info {
+                            LinkInfo::Kind(InfoKind::GreTun)
+                            | LinkInfo::Kind(InfoKind::GreTap) => is_gre = true,
+                            LinkInfo::Data(InfoData::GreTun(nlas)) => {
+                                for nla in nlas {
+                                    if let InfoGreTun::Other(nla) = nla {
+                                        parse_gre_nla(&nla, &mut gre_info);
+                                    }
+                                }
+                            }
+                            LinkInfo::Data(InfoData::GreTap(nlas)) => {
+                                for nla in nlas {
+                                    if let InfoGreTap::Other(nla) = nla {
+                                        parse_gre_nla(&nla, &mut gre_info);
+                                    }
+                                }
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        if let Some(name) = name {
+            index_to_name.insert(link.header.index, name.clone());
+            if is_gre {
+                gre_infos.push((name, gre_info));
+            }
+        }
+    }
+
+    Ok(gre_infos
+        .into_iter()
+        .map(|(name, info)| {
+            let mut conf = GreConfig::default();
+            conf.base_iface = info
+                .link
+                .filter(|i| *i != 0)
+                .and_then(|i| index_to_name.get(&i).cloned());
+            conf.local = info.local.filter(|i| !i.is_unspecified());
+            conf.remote = info.remote.filter(|i| !i.is_unspecified());
+            conf.ttl = info.ttl;
+            conf.tos = info.tos;
+            let ikey = info.ikey.filter(|_| info.iflags & GRE_KEY > 0);
+            let okey = info.okey.filter(|_| info.oflags & GRE_KEY > 0);
+            if ikey.is_some() && ikey == okey {
+                conf.key = ikey;
+            } else {
+                conf.ikey = ikey;
+                conf.okey = okey;
+            }
+            (name, conf)
+        })
+        .collect())
+}
+
+fn parse_gre_nla(nla: &DefaultNla, info: &mut GreLinkInfo) {
+    let mut buf = vec![0u8; nla.value_len()];
+    nla.emit_value(&mut buf);
+    match nla.kind() {
+        IFLA_GRE_LINK => {
+            info.link = buf
+                .get(..4)
+                .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+        }
+        IFLA_GRE_IFLAGS => {
+            info.iflags = buf
+                .get(..2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                .unwrap_or_default()
+        }
+        IFLA_GRE_OFLAGS => {
+            info.oflags = buf
+                .get(..2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                .unwrap_or_default()
+        }
+        IFLA_GRE_IKEY => {
+            info.ikey = buf
+                .get(..4)
+                .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        }
+        IFLA_GRE_OKEY => {
+            info.okey = buf
+                .get(..4)
+                .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        }
+        IFLA_GRE_LOCAL => info.local = parse_ip_addr(&buf),
+        IFLA_GRE_REMOTE => info.remote = parse_ip_addr(&buf),
+        IFLA_GRE_TTL => info.ttl = buf.first().copied(),
+        IFLA_GRE_TOS => info.tos = buf.first().copied(),
+        _ => (),
+    }
+}
+
+fn parse_ip_addr(buf: &[u8]) -> Option<IpAddr> {
+    if let Ok(b) = <[u8; 4]>::try_from(buf) {
+        Some(IpAddr::V4(Ipv4Addr::from(b)))
+    } else if let Ok(b) = <[u8; 16]>::try_from(buf) {
+        Some(IpAddr::V6(Ipv6Addr::from(b)))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,209 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+
+use nipart::{ErrorKind, Ieee8021XConfig, NipartError};
+
+const WPA_SUPPLICANT_BIN: &str = "wpa_supplicant";
+const WPA_SUPPLICANT_RUN_DIR: &str = "/run/nipart/wpa_supplicant";
+
+fn conf_path(iface_name: &str) -> String {
+    format!("{WPA_SUPPLICANT_RUN_DIR}/{iface_name}.conf")
+}
+
+fn pid_path(iface_name: &str) -> String {
+    format!("{WPA_SUPPLICANT_RUN_DIR}/{iface_name}.pid")
+}
+
+// The wpa_supplicant does not support escaping in quoted string, values
+// holding double quote or control characters are rejected by
+// `Ieee8021XConfig` sanitize already.
+fn quote(value: &str) -> String {
+    format!("\"{value}\"")
+}
+
+// Desired state holding hidden secrets (e.g. rollback to stored commit)
+// has them removed, reuse the ones in existing wpa_supplicant configuration.
+fn reuse_existing_secrets(
+    iface_name: &str,
+    conf: &Ieee8021XConfig,
+) -> Ieee8021XConfig {
+    let mut conf = conf.clone();
+    let content = match std::fs::read_to_string(conf_path(iface_name)) {
+        Ok(c) => c,
+        Err(_) => return conf,
+    };
+    for line in content.lines() {
+        let (key, value) = match line.trim().split_once('=') {
+            Some((k, v)) => (k, v.trim_matches('"').to_string()),
+            None => continue,
+        };
+        match key {
+            "password" if conf.password.is_none() => {
+                conf.password = Some(value);
+            }
+            "private_key_passwd" if conf.private_key_password.is_none() => {
+                conf.private_key_password = Some(value);
+            }
+            _ => (),
+        }
+    }
+    conf
+}
+
+fn gen_wpa_supplicant_conf(conf: &Ieee8021XConfig) -> String {
+    let mut lines: Vec<String> = vec![
+        "ap_scan=0".to_string(),
+        "network={".to_string(),
+        "    key_mgmt=IEEE8021X".to_string(),
+    ];
+    if let Some(eaps) = conf.eap.as_ref() {
+        lines.push(format!("    eap={}", eaps.join(" ").to_uppercase()));
+    }
+    for (key, value) in [
+        ("identity", conf.identity.as_deref()),
+        ("ca_cert", conf.ca_cert.as_deref()),
+        ("client_cert", conf.client_cert.as_deref()),
+        ("private_key", conf.private_key.as_deref()),
+        ("private_key_passwd", conf.private_key_password.as_deref()),
+        ("password", conf.password.as_deref()),
+    ] {
+        if let Some(value) = value {
+            lines.push(format!("    {key}={}", quote(value)));
+        }
+    }
+    if let Some(phase2) = conf.phase2_auth.as_deref() {
+        lines.push(format!("    phase2={}", quote(&format!("auth={phase2}"))));
+    }
+    lines.push("}".to_string());
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+// Start wpa_supplicant in wired mode for specified interface. Existing
+// wpa_supplicant instance of this interface will be stopped first.
+pub(crate) fn start_wpa_supplicant(
+    iface_name: &str,
+    conf: &Ieee8021XConfig,
+) -> Result<(), NipartError> {
+    let conf = reuse_existing_secrets(iface_name, conf);
+    stop_wpa_supplicant(iface_name)?;
+
+    std::fs::create_dir_all(WPA_SUPPLICANT_RUN_DIR).map_err(|e| {
+        NipartError::new(
+            ErrorKind::PluginFailure,
+            format!("Failed to create folder {WPA_SUPPLICANT_RUN_DIR}: {e}"),
+        )
+    })?;
+
+    let conf_path = conf_path(iface_name);
+    // The configuration holds secrets, only root can read it
+    let mut fd = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&conf_path)
+        .map_err(|e| {
+            NipartError::new(
+                ErrorKind::PluginFailure,
+                format!("Failed to open {conf_path}: {e}"),
+            )
+        })?;
+    fd.write_all(gen_wpa_supplicant_conf(&conf).as_bytes())
+        .map_err(|e| {
+            NipartError::new(
+                ErrorKind::PluginFailure,
+                format!("Failed to write {conf_path}: {e}"),
+            )
+        })?;
+
+    log::debug!("Starting {WPA_SUPPLICANT_BIN} for interface {iface_name}");
+    let output = std::process::Command::new(WPA_SUPPLICANT_BIN)
+        .args([
+            "-B",
+            "-D",
+            "wired",
+            "-i",
+            iface_name,
+            "-c",
+            conf_path.as_str(),
+            "-P",
+            pid_path(iface_name).as_str(),
+        ])
+        .output()
+        .map_err(|e| {
+            NipartError::new(
+                ErrorKind::DependencyError,
+                format!("Failed to execute {WPA_SUPPLICANT_BIN}: {e}"),
+            )
+        })?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let e = NipartError::new(
+            ErrorKind::PluginFailure,
+            format!(
+                "Failed to start {WPA_SUPPLICANT_BIN} for interface \
+                {iface_name}: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ),
+        );
+        log::error!("{}", e);
+        Err(e)
+    }
+}
+
+pub(crate) fn stop_wpa_supplicant(iface_name: &str) -> Result<(), NipartError> {
+    let pid_path = pid_path(iface_name);
+    let pid = match std::fs::read_to_string(&pid_path) {
+        Ok(c) => match c.trim().parse::<i32>() {
+            Ok(p) => p,
+            Err(e) => {
+                log::warn!("Invalid PID in {pid_path}: {e}");
+                return Ok(());
+            }
+        },
+        Err(_) => return Ok(()),
+    };
+    log::debug!("Stopping {WPA_SUPPLICANT_BIN} of interface {iface_name}");
+    if let Err(e) = nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(pid),
+        nix::sys::signal::Signal::SIGTERM,
+    ) {
+        log::warn!(
+            "Failed to stop {WPA_SUPPLICANT_BIN} PID {pid} of interface \
+            {iface_name}: {e}"
+        );
+    }
+    std::fs::remove_file(&pid_path).ok();
+    std::fs::remove_file(conf_path(iface_name)).ok();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::gen_wpa_supplicant_conf;
+
+    #[test]
+    fn test_gen_wpa_supplicant_conf() {
+        let mut conf = nipart::Ieee8021XConfig::default();
+        conf.eap = Some(vec!["peap".to_string()]);
+        conf.identity = Some("user".to_string());
+        conf.password = Some("pass".to_string());
+        conf.phase2_auth = Some("mschapv2".to_string());
+
+        assert_eq!(
+            gen_wpa_supplicant_conf(&conf),
+            "ap_scan=0\n\
+            network={\n    \
+            key_mgmt=IEEE8021X\n    \
+            eap=PEAP\n    \
+            identity=\"user\"\n    \
+            password=\"pass\"\n    \
+            phase2=\"auth=mschapv2\"\n\
+            }\n"
+        );
+    }
+}
@@ -2,7 +2,13 @@
 
 use std::str::FromStr;
 
-use nipart::{InterfaceIpAddr, InterfaceIpv4, InterfaceIpv6};
+use futures::stream::TryStreamExt;
+use nipart::{
+    BaseInterface, ErrorKind, InterfaceIpAddr, InterfaceIpv4, InterfaceIpv6,
+    Ipv4RpFilter, Ipv6AcceptRa, Ipv6AddrGenMode, MergedInterface, NipartError,
+};
+
+use rtnetlink::packet_route::link::{AfSpecInet6, AfSpecUnspec, LinkAttribute};
 
 use crate::mptcp::get_mptcp_flags;
 
@@ -85,6 +91,7 @@ pub(crate) fn np_ipv6_to_nipart(
         if let Some(token) = np_ip.token.as_ref() {
             ip.token = Some(token.to_string());
         }
+        ip.addr_gen_mode = get_ipv6_addr_gen_mode(np_iface.name.as_str());
 
         let mut addresses = Vec::new();
         for np_addr in &np_ip.addresses {
@@ -169,3 +176,531 @@ pub(crate) fn nipart_ipv6_to_np(
     }
     np_ip_conf
 }
+
+const IN6_ADDR_GEN_MODE_EUI64: &str = "0";
+const IN6_ADDR_GEN_MODE_NONE: &str = "1";
+const IN6_ADDR_GEN_MODE_STABLE_PRIVACY: &str = "2";
+const IN6_ADDR_GEN_MODE_RANDOM: &str = "3";
+
+fn ipv6_sysctl_path(iface_name: &str, key: &str) -> String {
+    format!("/proc/sys/net/ipv6/conf/{iface_name}/{key}")
+}
+
+fn get_ipv6_addr_gen_mode(iface_name: &str) -> Option<Ipv6AddrGenMode> {
+    let path = ipv6_sysctl_path(iface_name, "addr_gen_mode");
+    match std::fs::read_to_string(&path) {
+        Ok(content) => match content.trim() {
+            IN6_ADDR_GEN_MODE_EUI64 => Some(Ipv6AddrGenMode::Eui64),
+            IN6_ADDR_GEN_MODE_STABLE_PRIVACY => {
+                Some(Ipv6AddrGenMode::StablePrivacy)
+            }
+            IN6_ADDR_GEN_MODE_RANDOM => Some(Ipv6AddrGenMode::Random),
+            IN6_ADDR_GEN_MODE_NONE => Some(Ipv6AddrGenMode::None),
+            v => {
+                log::debug!("Unknown IPv6 addr_gen_mode {v} in {path}");
+                None
+            }
+        },
+        Err(e) => {
+            log::debug!("Failed to read {path}: {e}");
+            None
+        }
+    }
+}
+
+fn write_ipv6_sysctl(
+    iface_name: &str,
+    key: &str,
+    value: &str,
+) -> Result<(), NipartError> {
+    write_sysctl(&ipv6_sysctl_path(iface_name, key), value)
+}
+
+// The stable secret is written before addr_gen_mode because kernel will
+// switch addr_gen_mode to stable-privacy when stable secret changed.
+pub(crate) fn apply_ipv6_addr_gen_mode(
+    ifaces: &[(&str, &MergedInterface)],
+) -> Result<(), NipartError> {
+    for (iface_name, merged_iface) in ifaces {
+        let Some(ipv6) = merged_iface
+            .for_apply
+            .as_ref()
+            .and_then(|i| i.base_iface().ipv6.as_ref())
+            .filter(|i| i.enabled)
+        else {
+            continue;
+        };
+        if let Some(secret) = ipv6.stable_secret.as_deref() {
+            log::debug!("Setting IPv6 stable secret of interface {iface_name}");
+            write_ipv6_sysctl(iface_name, "stable_secret", secret)?;
+        }
+        if let Some(mode) = ipv6.addr_gen_mode.as_ref() {
+            let value = match mode {
+                Ipv6AddrGenMode::Eui64 => IN6_ADDR_GEN_MODE_EUI64,
+                Ipv6AddrGenMode::StablePrivacy => {
+                    IN6_ADDR_GEN_MODE_STABLE_PRIVACY
+                }
+                Ipv6AddrGenMode::Random => IN6_ADDR_GEN_MODE_RANDOM,
+                Ipv6AddrGenMode::None => IN6_ADDR_GEN_MODE_NONE,
+                Ipv6AddrGenMode::Other(s) => {
+                    return Err(NipartError::new(
+                        ErrorKind::NotSupportedError,
+                        format!(
+                            "IPv6 addr-gen-mode {s} of interface \
+                            {iface_name} is not supported"
+                        ),
+                    ));
+                }
+            };
+            log::debug!(
+                "Setting IPv6 addr-gen-mode of interface {iface_name} to \
+                {mode}"
+            );
+            write_ipv6_sysctl(iface_name, "addr_gen_mode", value)?;
+        }
+    }
+    Ok(())
+}
+
+const ACCEPT_RA_NEVER: &str = "0";
+const ACCEPT_RA_ENABLED_UNLESS_FORWARDING: &str = "1";
+const ACCEPT_RA_ALWAYS: &str = "2";
+
+const RP_FILTER_DISABLED: &str = "0";
+const RP_FILTER_STRICT: &str = "1";
+const RP_FILTER_LOOSE: &str = "2";
+
+fn ipv4_sysctl_path(iface_name: &str, key: &str) -> String {
+    format!("/proc/sys/net/ipv4/conf/{iface_name}/{key}")
+}
+
+fn read_sysctl(path: &str) -> Option<String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Some(content.trim().to_string()),
+        Err(e) => {
+            log::debug!("Failed to read {path}: {e}");
+            None
+        }
+    }
+}
+
+fn write_sysctl(path: &str, value: &str) -> Result<(), NipartError> {
+    std::fs::write(path, value).map_err(|e| {
+        NipartError::new(
+            ErrorKind::PluginFailure,
+            format!("Failed to write {path}: {e}"),
+        )
+    })
+}
+
+// The sysctl files still exist when IP is disabled, hence filling them
+// regardless IP is enabled or not.
+pub(crate) fn fill_ip_sysctl(base_iface: &mut BaseInterface) {
+    let iface_name = base_iface.name.clone();
+    if let Some(ipv4) = base_iface.ipv4.as_mut() {
+        ipv4.forwarding =
+            read_sysctl(&ipv4_sysctl_path(&iface_name, "forwarding"))
+                .map(|v| v != "0");
+        let path = ipv4_sysctl_path(&iface_name, "rp_filter");
+        ipv4.rp_filter = match read_sysctl(&path).as_deref() {
+            Some(v) => {
+                let rp_filter = sysctl_to_rp_filter(v);
+                if rp_filter.is_none() {
+                    log::debug!("Unknown IPv4 rp_filter {v} in {path}");
+                }
+                rp_filter
+            }
+            None => None,
+        };
+        ipv4.arp_ignore =
+            read_sysctl(&ipv4_sysctl_path(&iface_name, "arp_ignore"))
+                .and_then(|v| v.parse::<u8>().ok());
+        ipv4.arp_announce =
+            read_sysctl(&ipv4_sysctl_path(&iface_name, "arp_announce"))
+                .and_then(|v| v.parse::<u8>().ok());
+    }
+    if let Some(ipv6) = base_iface.ipv6.as_mut() {
+        ipv6.forwarding =
+            read_sysctl(&ipv6_sysctl_path(&iface_name, "forwarding"))
+                .map(|v| v != "0");
+        let path = ipv6_sysctl_path(&iface_name, "accept_ra");
+        ipv6.accept_ra = match read_sysctl(&path).as_deref() {
+            Some(v) => {
+                let accept_ra = sysctl_to_accept_ra(v);
+                if accept_ra.is_none() {
+                    log::debug!("Unknown IPv6 accept_ra {v} in {path}");
+                }
+                accept_ra
+            }
+            None => None,
+        };
+        ipv6.dad_transmits =
+            read_sysctl(&ipv6_sysctl_path(&iface_name, "dad_transmits"))
+                .and_then(|v| v.parse::<u32>().ok());
+    }
+}
+
+fn sysctl_to_rp_filter(value: &str) -> Option<Ipv4RpFilter> {
+    match value {
+        RP_FILTER_DISABLED => Some(Ipv4RpFilter::Disabled),
+        RP_FILTER_STRICT => Some(Ipv4RpFilter::Strict),
+        RP_FILTER_LOOSE => Some(Ipv4RpFilter::Loose),
+        _ => None,
+    }
+}
+
+fn rp_filter_to_sysctl(rp_filter: Ipv4RpFilter) -> &'static str {
+    match rp_filter {
+        Ipv4RpFilter::Disabled => RP_FILTER_DISABLED,
+        Ipv4RpFilter::Strict => RP_FILTER_STRICT,
+        Ipv4RpFilter::Loose => RP_FILTER_LOOSE,
+    }
+}
+
+fn sysctl_to_accept_ra(value: &str) -> Option<Ipv6AcceptRa> {
+    match value {
+        ACCEPT_RA_NEVER => Some(Ipv6AcceptRa::Never),
+        ACCEPT_RA_ENABLED_UNLESS_FORWARDING => {
+            Some(Ipv6AcceptRa::EnabledUnlessForwarding)
+        }
+        ACCEPT_RA_ALWAYS => Some(Ipv6AcceptRa::Always),
+        _ => None,
+    }
+}
+
+fn accept_ra_to_sysctl(accept_ra: Ipv6AcceptRa) -> &'static str {
+    match accept_ra {
+        Ipv6AcceptRa::Never => ACCEPT_RA_NEVER,
+        Ipv6AcceptRa::EnabledUnlessForwarding => {
+            ACCEPT_RA_ENABLED_UNLESS_FORWARDING
+        }
+        Ipv6AcceptRa::Always => ACCEPT_RA_ALWAYS,
+    }
+}
+
+// Forwarding is applied before accept_ra, as kernel checks forwarding when
+// `accept_ra` is `enabled-unless-forwarding`.
+pub(crate) fn apply_ip_sysctl(
+    ifaces: &[(&str, &MergedInterface)],
+) -> Result<(), NipartError> {
+    for (iface_name, merged_iface) in ifaces {
+        let Some(base_iface) =
+            merged_iface.for_apply.as_ref().map(|i| i.base_iface())
+        else {
+            continue;
+        };
+        if let Some(ipv4) = base_iface.ipv4.as_ref() {
+            apply_ipv4_sysctl(iface_name, ipv4)?;
+        }
+        let Some(ipv6) = base_iface.ipv6.as_ref() else {
+            continue;
+        };
+        if let Some(forwarding) = ipv6.forwarding {
+            log::debug!(
+                "Setting IPv6 forwarding of interface {iface_name} to \
+                {forwarding}"
+            );
+            write_ipv6_sysctl(
+                iface_name,
+                "forwarding",
+                if forwarding { "1" } else { "0" },
+            )?;
+        }
+        if let Some(accept_ra) = ipv6.accept_ra {
+            log::debug!(
+                "Setting IPv6 accept-ra of interface {iface_name} to \
+                {accept_ra}"
+            );
+            write_ipv6_sysctl(
+                iface_name,
+                "accept_ra",
+                accept_ra_to_sysctl(accept_ra),
+            )?;
+        }
+        if let Some(dad_transmits) = ipv6.dad_transmits {
+            apply_ipv6_dad_transmits(iface_name, dad_transmits)?;
+        }
+    }
+    Ok(())
+}
+
+// DAD is performed when IPv6 address is added, hence for existing
+// interfaces `dad_transmits` should be applied before IP addresses. Interfaces
+// not created yet are skipped and handled by `apply_ip_sysctl()`.
+pub(crate) fn apply_ipv6_dad_transmits_before_ip(
+    ifaces: &[(&str, &MergedInterface)],
+) -> Result<(), NipartError> {
+    for (iface_name, merged_iface) in ifaces {
+        if let Some(dad_transmits) = merged_iface
+            .for_apply
+            .as_ref()
+            .and_then(|i| i.base_iface().ipv6.as_ref())
+            .and_then(|i| i.dad_transmits)
+        {
+            if std::path::Path::new(&ipv6_sysctl_path(
+                iface_name,
+                "dad_transmits",
+            ))
+            .exists()
+            {
+                apply_ipv6_dad_transmits(iface_name, dad_transmits)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_ipv6_dad_transmits(
+    iface_name: &str,
+    dad_transmits: u32,
+) -> Result<(), NipartError> {
+    log::debug!(
+        "Setting IPv6 dad-transmits of interface {iface_name} to \
+        {dad_transmits}"
+    );
+    write_ipv6_sysctl(
+        iface_name,
+        "dad_transmits",
+        dad_transmits.to_string().as_str(),
+    )
+}
+
+fn apply_ipv4_sysctl(
+    iface_name: &str,
+    ipv4: &InterfaceIpv4,
+) -> Result<(), NipartError> {
+    if let Some(forwarding) = ipv4.forwarding {
+        log::debug!(
+            "Setting IPv4 forwarding of interface {iface_name} to \
+            {forwarding}"
+        );
+        write_sysctl(
+            &ipv4_sysctl_path(iface_name, "forwarding"),
+            if forwarding { "1" } else { "0" },
+        )?;
+    }
+    if let Some(rp_filter) = ipv4.rp_filter {
+        log::debug!(
+            "Setting IPv4 rp-filter of interface {iface_name} to {rp_filter}"
+        );
+        write_sysctl(
+            &ipv4_sysctl_path(iface_name, "rp_filter"),
+            rp_filter_to_sysctl(rp_filter),
+        )?;
+    }
+    if let Some(arp_ignore) = ipv4.arp_ignore {
+        log::debug!(
+            "Setting IPv4 arp-ignore of interface {iface_name} to \
+            {arp_ignore}"
+        );
+        write_sysctl(
+            &ipv4_sysctl_path(iface_name, "arp_ignore"),
+            arp_ignore.to_string().as_str(),
+        )?;
+    }
+    if let Some(arp_announce) = ipv4.arp_announce {
+        log::debug!(
+            "Setting IPv4 arp-announce of interface {iface_name} to \
+            {arp_announce}"
+        );
+        write_sysctl(
+            &ipv4_sysctl_path(iface_name, "arp_announce"),
+            arp_announce.to_string().as_str(),
+        )?;
+    }
+    Ok(())
+}
+
+fn get_ipv6_tokens<'a>(
+    ifaces: &[(&'a str, &MergedInterface)],
+) -> Vec<(&'a str, std::net::Ipv6Addr)> {
+    ifaces
+        .iter()
+        .filter_map(|(iface_name, merged_iface)| {
+            merged_iface
+                .for_apply
+                .as_ref()
+                .and_then(|i| i.base_iface().ipv6.as_ref())
+                .filter(|i| i.enabled)
+                .and_then(|i| i.token.as_deref())
+                .and_then(|t| std::net::Ipv6Addr::from_str(t).ok())
+                .map(|t| (*iface_name, t))
+        })
+        .collect()
+}
+
+// Nispor does not support setting IPv6 token yet, use rtnetlink directly.
+// Token `::` means removing the token.
+pub(crate) async fn apply_ipv6_token(
+    ifaces: &[(&str, &MergedInterface)],
+) -> Result<(), NipartError> {
+    let tokens = get_ipv6_tokens(ifaces);
+    if tokens.is_empty() {
+        return Ok(());
+    }
+
+    let (conn, handle, _) = rtnetlink::new_connection().map_err(|e| {
+        NipartError::new(
+            ErrorKind::PluginFailure,
+            format!("Failed to create rtnetlink connection: {e}"),
+        )
+    })?;
+    tokio::spawn(conn);
+
+    for (iface_name, token) in tokens {
+        let index = match handle
+            .link()
+            .get()
+            .match_name(iface_name.to_string())
+            .execute()
+            .try_next()
+            .await
+        {
+            Ok(Some(link)) => link.header.index,
+            Ok(None) => {
+                return Err(NipartError::new(
+                    ErrorKind::PluginFailure,
+                    format!(
+                        "Interface {iface_name} not found for setting \
+                        IPv6 token"
+                    ),
+                ));
+            }
+            Err(e) => {
+                return Err(NipartError::new(
+                    ErrorKind::PluginFailure,
+                    format!("Failed to query interface {iface_name}: {e}"),
+                ));
+            }
+        };
+        log::debug!("Setting IPv6 token of interface {iface_name} to {token}");
+        let mut request = handle.link().set(index);
+        request
+            .message_mut()
+            .attributes
+            .push(LinkAttribute::AfSpecUnspec(vec![AfSpecUnspec::Inet6(
+                vec![AfSpecInet6::Token(token)],
+            )]));
+        if let Err(e) = request.execute().await {
+            return Err(NipartError::new(
+                ErrorKind::PluginFailure,
+                format!(
+                    "Failed to set IPv6 token {token} on interface \
+                    {iface_name}: {e}"
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use nipart::{
+        ErrorKind, Ipv4RpFilter, Ipv6AcceptRa, MergedInterface,
+        MergedNetworkState, NetworkState,
+    };
+
+    use super::{
+        accept_ra_to_sysctl, get_ipv6_tokens, rp_filter_to_sysctl,
+        sysctl_to_accept_ra, sysctl_to_rp_filter,
+    };
+
+    #[test]
+    fn test_accept_ra_sysctl_round_trip() {
+        for accept_ra in [
+            Ipv6AcceptRa::Never,
+            Ipv6AcceptRa::EnabledUnlessForwarding,
+            Ipv6AcceptRa::Always,
+        ] {
+            assert_eq!(
+                sysctl_to_accept_ra(accept_ra_to_sysctl(accept_ra)),
+                Some(accept_ra)
+            );
+        }
+        assert_eq!(sysctl_to_accept_ra("2"), Some(Ipv6AcceptRa::Always));
+        assert_eq!(sysctl_to_accept_ra("3"), None);
+    }
+
+    #[test]
+    fn test_rp_filter_sysctl_round_trip() {
+        for rp_filter in [
+            Ipv4RpFilter::Disabled,
+            Ipv4RpFilter::Strict,
+            Ipv4RpFilter::Loose,
+        ] {
+            assert_eq!(
+                sysctl_to_rp_filter(rp_filter_to_sysctl(rp_filter)),
+                Some(rp_filter)
+            );
+        }
+        assert_eq!(sysctl_to_rp_filter("2"), Some(Ipv4RpFilter::Loose));
+        assert_eq!(sysctl_to_rp_filter("3"), None);
+    }
+
+    fn gen_token_merged_state(
+        token: &str,
+        autoconf: bool,
+    ) -> Result<MergedNetworkState, nipart::NipartError> {
+        let desired = NetworkState::new_from_yaml(&format!(
+            r#"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv6:
+                enabled: true
+                dhcp: {autoconf}
+                autoconf: {autoconf}
+                token: "{token}"
+            - name: dummy2
+              type: dummy
+              state: up
+              ipv6:
+                enabled: false"#
+        ))
+        .unwrap();
+        MergedNetworkState::new(desired, NetworkState::default(), false, false)
+    }
+
+    fn get_apply_ifaces(
+        merged: &MergedNetworkState,
+    ) -> Vec<(&str, &MergedInterface)> {
+        merged
+            .interfaces
+            .kernel_ifaces
+            .iter()
+            .map(|(name, iface)| (name.as_str(), iface))
+            .collect()
+    }
+
+    #[test]
+    fn test_ipv6_token_for_apply() {
+        let merged = gen_token_merged_state("::0.0.250.193", true).unwrap();
+
+        assert_eq!(
+            get_ipv6_tokens(get_apply_ifaces(&merged).as_slice()),
+            vec![("dummy1", "::fac1".parse().unwrap())]
+        );
+    }
+
+    #[test]
+    fn test_ipv6_token_remove() {
+        let merged = gen_token_merged_state("", true).unwrap();
+
+        assert_eq!(
+            get_ipv6_tokens(get_apply_ifaces(&merged).as_slice()),
+            vec![("dummy1", std::net::Ipv6Addr::UNSPECIFIED)]
+        );
+    }
+
+    #[test]
+    fn test_ipv6_token_without_autoconf() {
+        let result = gen_token_merged_state("::fac1", false);
+
+        assert_eq!(
+            result.err().map(|e| e.kind),
+            Some(ErrorKind::InvalidArgument)
+        );
+    }
+}
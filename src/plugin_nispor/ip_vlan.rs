@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use nipart::{
+    BaseInterface, IpVlanConfig, IpVlanFlag, IpVlanInterface, IpVlanMode,
+};
+
+pub(crate) fn np_ip_vlan_to_nipart(
+    np_iface: &nispor::Iface,
+    base_iface: BaseInterface,
+) -> IpVlanInterface {
+    let vlan_conf = np_iface.ip_vlan.as_ref().map(|np_vlan_info| {
+        let mut conf = IpVlanConfig::default();
+        conf.mode = match &np_vlan_info.mode {
+            nispor::IpVlanMode::L2 => IpVlanMode::L2,
+            nispor::IpVlanMode::L3 => IpVlanMode::L3,
+            nispor::IpVlanMode::L3S => IpVlanMode::L3S,
+            _ => {
+                log::warn!(
+                    "Unknown supported IpVlan mode {:?}",
+                    np_vlan_info.mode
+                );
+                IpVlanMode::Unknown
+            }
+        };
+        let mut flags = Vec::new();
+        for np_flag in np_vlan_info.flags.as_slice() {
+            match np_flag {
+                nispor::IpVlanFlag::Bridge => flags.push(IpVlanFlag::Bridge),
+                nispor::IpVlanFlag::Private => flags.push(IpVlanFlag::Private),
+                nispor::IpVlanFlag::Vepa => flags.push(IpVlanFlag::Vepa),
+                _ => {
+                    log::warn!("Unknown supported IpVlan flag {:?}", np_flag);
+                }
+            }
+        }
+        conf.flags = Some(flags);
+        conf.base_iface = np_vlan_info.base_iface.clone();
+        conf
+    });
+
+    let mut ret = IpVlanInterface::default();
+    ret.base = base_iface;
+    ret.ip_vlan = vlan_conf;
+    ret
+}
@@ -3,24 +3,35 @@
 mod apply;
 mod base_iface;
 mod bond;
+mod carrier;
 mod error;
 mod ethernet;
 mod ethtool;
+mod gre;
 mod hostname;
+mod ieee8021x;
 mod infiniband;
 mod ip;
+mod ip_vlan;
 mod linux_bridge;
 mod linux_bridge_port_vlan;
+mod loopback;
 mod mac_vlan;
 mod macsec;
 mod mptcp;
+mod mtu;
 mod plugin;
+mod rename;
 mod route;
 mod route_rule;
 mod show;
+mod sriov;
+mod stage;
+mod tun;
 mod veth;
 mod vlan;
 mod vrf;
 mod vxlan;
+mod wireguard;
 
 pub use self::plugin::NipartPluginNispor;
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Loopback interface cannot be deleted, the `state: absent` means restoring
+// it back to default configure.
+pub(crate) fn np_loopback_default_conf(name: &str) -> nispor::IfaceConf {
+    let mut np_iface = nispor::IfaceConf::default();
+    np_iface.name = name.to_string();
+    np_iface.iface_type = Some(nispor::IfaceType::Loopback);
+    np_iface.state = nispor::IfaceState::Up;
+    np_iface.ipv4 = Some(np_loopback_ip_conf("127.0.0.1", 8));
+    np_iface.ipv6 = Some(np_loopback_ip_conf("::1", 128));
+    np_iface
+}
+
+fn np_loopback_ip_conf(address: &str, prefix_len: u8) -> nispor::IpConf {
+    let mut ip_conf = nispor::IpConf::default();
+    ip_conf.addresses.push({
+        let mut addr_conf = nispor::IpAddrConf::default();
+        addr_conf.address = address.to_string();
+        addr_conf.prefix_len = prefix_len;
+        addr_conf
+    });
+    ip_conf
+}
@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::stream::TryStreamExt;
+use nipart::{ErrorKind, MergedInterface, NipartError};
+
+use crate::apply::iface_name_after_rename;
+
+// Nispor does not support changing MTU yet, use rtnetlink directly.
+// Should be invoked after each apply stage, as the stages are ordered by
+// parent and controller relationship, the MTU of parent is changed before
+// its child, which is required when increasing MTU.
+pub(crate) async fn apply_mtu(
+    stage: &[&MergedInterface],
+) -> Result<(), NipartError> {
+    let changes = get_mtu_changes(stage);
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let (conn, handle, _) = rtnetlink::new_connection().map_err(|e| {
+        NipartError::new(
+            ErrorKind::PluginFailure,
+            format!("Failed to create rtnetlink connection: {e}"),
+        )
+    })?;
+    tokio::spawn(conn);
+
+    for (iface_name, mtu) in changes {
+        log::info!("Setting MTU of interface {iface_name} to {mtu}");
+        let index = match handle
+            .link()
+            .get()
+            .match_name(iface_name.to_string())
+            .execute()
+            .try_next()
+            .await
+        {
+            Ok(Some(link)) => link.header.index,
+            Ok(None) => {
+                return Err(NipartError::new(
+                    ErrorKind::PluginFailure,
+                    format!("Interface {iface_name} not found for MTU change"),
+                ));
+            }
+            Err(e) => {
+                return Err(NipartError::new(
+                    ErrorKind::PluginFailure,
+                    format!("Failed to query interface {iface_name}: {e}"),
+                ));
+            }
+        };
+        if let Err(e) = handle.link().set(index).mtu(mtu).execute().await {
+            return Err(NipartError::new(
+                ErrorKind::PluginFailure,
+                format!(
+                    "Failed to set MTU of interface {iface_name} to {mtu}: {e}"
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Return interface name and desired MTU if differ from current.
+fn get_mtu_changes<'a>(stage: &[&'a MergedInterface]) -> Vec<(&'a str, u32)> {
+    let mut ret = Vec::new();
+    for merged_iface in stage {
+        let Some(for_apply) = merged_iface.for_apply.as_ref() else {
+            continue;
+        };
+        if !for_apply.is_up() {
+            continue;
+        }
+        let Some(mtu) = for_apply.base_iface().mtu else {
+            continue;
+        };
+        let cur_mtu = merged_iface
+            .current
+            .as_ref()
+            .and_then(|i| i.base_iface().mtu);
+        if cur_mtu == Some(mtu) {
+            continue;
+        }
+        match u32::try_from(mtu) {
+            Ok(mtu) => ret.push((iface_name_after_rename(for_apply), mtu)),
+            Err(_) => {
+                log::warn!(
+                    "Ignoring invalid MTU {mtu} of interface {}",
+                    for_apply.name()
+                );
+            }
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use nipart::{MergedInterface, MergedNetworkState, NetworkState};
+
+    use super::get_mtu_changes;
+
+    #[test]
+    fn test_vlan_inherit_mtu_after_base_mtu_changed() {
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              mtu: 1500
+            - name: eth1.10
+              type: vlan
+              state: up
+              mtu: 1500
+              vlan:
+                base-iface: eth1
+                id: 10",
+        )
+        .unwrap();
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              mtu: 9000
+            - name: eth1.10
+              type: vlan
+              state: up
+              mtu: inherit",
+        )
+        .unwrap();
+        let merged =
+            MergedNetworkState::new(desired, current, false, false).unwrap();
+        let ifaces: Vec<&MergedInterface> = ["eth1", "eth1.10"]
+            .iter()
+            .map(|n| &merged.interfaces.kernel_ifaces[*n])
+            .collect();
+
+        assert_eq!(
+            get_mtu_changes(ifaces.as_slice()),
+            vec![("eth1", 9000), ("eth1.10", 9000)]
+        );
+    }
+
+    #[test]
+    fn test_unchanged_mtu_not_applied() {
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 1500",
+        )
+        .unwrap();
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              mtu: 1500
+              accept-all-mac-addresses: true",
+        )
+        .unwrap();
+        let merged =
+            MergedNetworkState::new(desired, current, false, false).unwrap();
+        let ifaces = vec![&merged.interfaces.kernel_ifaces["dummy1"]];
+
+        assert!(get_mtu_changes(ifaces.as_slice()).is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::stream::TryStreamExt;
+use nipart::{ErrorKind, MergedInterface, NipartError};
+
+// Nispor does not support interface renaming yet, use rtnetlink directly.
+pub(crate) async fn rename_ifaces(
+    ifaces: &[&MergedInterface],
+) -> Result<(), NipartError> {
+    let renames: Vec<(&str, &str)> = ifaces
+        .iter()
+        .filter_map(|i| i.for_apply.as_ref())
+        .filter(|i| !i.is_absent())
+        .filter_map(|i| {
+            i.base_iface()
+                .rename_to
+                .as_deref()
+                .filter(|n| *n != i.name())
+                .map(|n| (i.name(), n))
+        })
+        .collect();
+    if renames.is_empty() {
+        return Ok(());
+    }
+
+    let (conn, handle, _) = rtnetlink::new_connection().map_err(|e| {
+        NipartError::new(
+            ErrorKind::PluginFailure,
+            format!("Failed to create rtnetlink connection: {e}"),
+        )
+    })?;
+    tokio::spawn(conn);
+
+    for (old_name, new_name) in renames {
+        log::info!("Renaming interface {old_name} to {new_name}");
+        let index = match handle
+            .link()
+            .get()
+            .match_name(old_name.to_string())
+            .execute()
+            .try_next()
+            .await
+        {
+            Ok(Some(link)) => link.header.index,
+            Ok(None) => {
+                return Err(NipartError::new(
+                    ErrorKind::PluginFailure,
+                    format!("Interface {old_name} not found for renaming"),
+                ));
+            }
+            Err(e) => {
+                return Err(NipartError::new(
+                    ErrorKind::PluginFailure,
+                    format!("Failed to query interface {old_name}: {e}"),
+                ));
+            }
+        };
+        // Kernel only allows renaming interface in administrative down
+        // state. The later apply will bring it up.
+        if let Err(e) = handle.link().set(index).down().execute().await {
+            return Err(NipartError::new(
+                ErrorKind::PluginFailure,
+                format!("Failed to set interface {old_name} down: {e}"),
+            ));
+        }
+        if let Err(e) = handle
+            .link()
+            .set(index)
+            .name(new_name.to_string())
+            .execute()
+            .await
+        {
+            return Err(NipartError::new(
+                ErrorKind::PluginFailure,
+                format!(
+                    "Failed to rename interface {old_name} to \
+                    {new_name}: {e}"
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
@@ -1,10 +1,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use log::warn;
-use nipart::{RouteEntry, RouteType, Routes};
+use nipart::{Ipv6RoutePref, RouteEntry, RouteScope, RouteType, Routes};
 
-const SUPPORTED_ROUTE_SCOPE: [nispor::RouteScope; 2] =
-    [nispor::RouteScope::Universe, nispor::RouteScope::Link];
+const SUPPORTED_ROUTE_SCOPE: [nispor::RouteScope; 4] = [
+    nispor::RouteScope::Universe,
+    nispor::RouteScope::Link,
+    nispor::RouteScope::Host,
+    nispor::RouteScope::NoWhere,
+];
 
 const SUPPORTED_ROUTE_PROTOCOL: [nispor::RouteProtocol; 7] = [
     nispor::RouteProtocol::Boot,
@@ -23,6 +27,8 @@ const IPV4_DEFAULT_GATEWAY: &str = "0.0.0.0/0";
 const IPV6_DEFAULT_GATEWAY: &str = "::/0";
 const IPV4_EMPTY_NEXT_HOP_ADDRESS: &str = "0.0.0.0";
 const IPV6_EMPTY_NEXT_HOP_ADDRESS: &str = "::";
+// Defined in linux/rtnetlink.h
+const RTNH_F_ONLINK: u32 = 4;
 
 pub(crate) async fn get_routes(running_config_only: bool) -> Routes {
     let mut ret = Routes::new();
@@ -31,6 +37,7 @@ pub(crate) async fn get_routes(running_config_only: bool) -> Routes {
         nispor::RouteType::BlackHole,
         nispor::RouteType::Unreachable,
         nispor::RouteType::Prohibit,
+        nispor::RouteType::Throw,
     ];
     let protocols = if running_config_only {
         SUPPORTED_STATIC_ROUTE_PROTOCOL.as_slice()
@@ -134,10 +141,14 @@ fn np_routetype_to_nipart(np_route: &nispor::Route) -> RouteEntry {
         nispor::RouteType::Prohibit => {
             route_entry.route_type = Some(RouteType::Prohibit)
         }
+        nispor::RouteType::Throw => {
+            route_entry.route_type = Some(RouteType::Throw)
+        }
         _ => {
             log::debug!("Got unsupported route {:?}", np_route);
         }
     }
+    set_route_scope(&mut route_entry, np_route);
     route_entry
 }
 
@@ -189,10 +200,48 @@ fn np_route_to_nipart(np_route: &nispor::Route) -> RouteEntry {
     route_entry.next_hop_addr = next_hop_addr;
     route_entry.metric = np_route.metric.map(i64::from);
     route_entry.table_id = Some(np_route.table);
+    if np_route.address_family == nispor::AddressFamily::IPv6 {
+        route_entry.pref = np_route.perf.and_then(np_route_pref_to_nipart);
+    }
+    set_route_scope(&mut route_entry, np_route);
+    if np_route.flags & RTNH_F_ONLINK > 0 {
+        route_entry.on_link = Some(true);
+    }
 
     route_entry
 }
 
+// Only store scope not equal to the one kernel would use by default, so
+// desired state without explicit scope still matches.
+fn set_route_scope(route_entry: &mut RouteEntry, np_route: &nispor::Route) {
+    let scope = match np_route.scope {
+        nispor::RouteScope::Universe => RouteScope::Global,
+        nispor::RouteScope::Link => RouteScope::Link,
+        nispor::RouteScope::Host => RouteScope::Host,
+        nispor::RouteScope::NoWhere => RouteScope::Nowhere,
+        _ => {
+            log::debug!("Got unsupported route scope {:?}", np_route.scope);
+            return;
+        }
+    };
+    if scope != route_entry.default_scope() {
+        route_entry.scope = Some(scope);
+    }
+}
+
+// RFC 4191: 01 High, 00 Medium, 11 Low, 10 Reserved
+fn np_route_pref_to_nipart(pref: u8) -> Option<Ipv6RoutePref> {
+    match pref {
+        0 => Some(Ipv6RoutePref::Medium),
+        1 => Some(Ipv6RoutePref::High),
+        3 => Some(Ipv6RoutePref::Low),
+        _ => {
+            log::debug!("Got unknown IPv6 route preference {pref}");
+            None
+        }
+    }
+}
+
 fn is_multipath(np_route: &nispor::Route) -> bool {
     np_route
         .multipath
@@ -208,12 +257,45 @@ fn flat_multipath_route(np_route: &nispor::Route) -> Vec<RouteEntry> {
             let mut new_np_route = np_route.clone();
             new_np_route.via = Some(mp_route.via.to_string());
             new_np_route.oif = Some(mp_route.iface.to_string());
-            let mut route = np_route_to_nipart(&new_np_route);
-            if np_route.address_family == nispor::AddressFamily::IPv4 {
-                route.weight = Some(mp_route.weight);
+            if mp_route
+                .flags
+                .contains(&nispor::MultipathRouteFlags::Onlink)
+            {
+                new_np_route.flags |= RTNH_F_ONLINK;
             }
+            let mut route = np_route_to_nipart(&new_np_route);
+            route.weight = Some(mp_route.weight);
             ret.push(route);
         }
     }
     ret
 }
+
+#[cfg(test)]
+mod tests {
+    use nipart::Ipv6RoutePref;
+
+    use super::{np_route_pref_to_nipart, np_route_to_nipart, RTNH_F_ONLINK};
+
+    #[test]
+    fn test_np_route_pref_to_nipart() {
+        assert_eq!(np_route_pref_to_nipart(1), Some(Ipv6RoutePref::High));
+        assert_eq!(np_route_pref_to_nipart(0), Some(Ipv6RoutePref::Medium));
+        assert_eq!(np_route_pref_to_nipart(3), Some(Ipv6RoutePref::Low));
+        assert_eq!(np_route_pref_to_nipart(2), None);
+    }
+
+    #[test]
+    fn test_np_route_on_link_flag() {
+        let mut np_route = nispor::Route::default();
+        np_route.address_family = nispor::AddressFamily::IPv4;
+        np_route.dst = Some("198.51.100.0/24".to_string());
+        np_route.oif = Some("eth1".to_string());
+        np_route.via = Some("203.0.113.1".to_string());
+
+        assert_eq!(np_route_to_nipart(&np_route).on_link, None);
+
+        np_route.flags |= RTNH_F_ONLINK;
+        assert_eq!(np_route_to_nipart(&np_route).on_link, Some(true));
+    }
+}
@@ -12,17 +12,25 @@ use crate::{
     bond::{append_bond_port_config, np_bond_to_nipart},
     error::np_error_to_nipart,
     ethernet::np_ethernet_to_nipart,
+    gre::{
+        get_gre_confs, is_np_gre_iface, np_gre_tap_to_nipart, np_gre_to_nipart,
+    },
     hostname::get_hostname_state,
     infiniband::np_ib_to_nipart,
+    ip_vlan::np_ip_vlan_to_nipart,
     linux_bridge::{append_bridge_port_config, np_bridge_to_nipart},
     mac_vlan::{np_mac_vlan_to_nipart, np_mac_vtap_to_nipart},
     macsec::np_macsec_to_nipart,
     route::get_routes,
     route_rule::get_route_rules,
+    tun::np_tun_to_nipart,
     veth::np_veth_to_nipart,
     vlan::np_vlan_to_nipart,
     vrf::np_vrf_to_nipart,
     vxlan::np_vxlan_to_nipart,
+    wireguard::{
+        get_wireguard_confs, is_np_wireguard_iface, np_wireguard_to_nipart,
+    },
 };
 
 pub(crate) async fn nispor_retrieve(
@@ -38,6 +46,28 @@ pub(crate) async fn nispor_retrieve(
         .await
         .map_err(np_error_to_nipart)?;
 
+    let gre_confs = if np_state.ifaces.values().any(is_np_gre_iface) {
+        get_gre_confs().await.unwrap_or_else(|e| {
+            log::warn!("Failed to query GRE tunnel configuration: {e}");
+            HashMap::new()
+        })
+    } else {
+        HashMap::new()
+    };
+
+    let wg_iface_names: Vec<&str> = np_state
+        .ifaces
+        .values()
+        .filter(|i| is_np_wireguard_iface(i))
+        .map(|i| i.name.as_str())
+        .collect();
+    let wg_confs = get_wireguard_confs(wg_iface_names.as_slice())
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to query WireGuard configuration: {e}");
+            HashMap::new()
+        });
+
     for (_, np_iface) in np_state.ifaces.iter() {
         // The `ovs-system` is reserved for OVS kernel datapath
         if np_iface.name == "ovs-system" {
@@ -102,6 +132,9 @@ pub(crate) async fn nispor_retrieve(
                 iface.base = base_iface;
                 Box::new(iface)
             }),
+            InterfaceType::IpVlan => Interface::IpVlan(Box::new(
+                np_ip_vlan_to_nipart(np_iface, base_iface),
+            )),
             InterfaceType::MacVlan => Interface::MacVlan(Box::new(
                 np_mac_vlan_to_nipart(np_iface, base_iface),
             )),
@@ -133,6 +166,18 @@ pub(crate) async fn nispor_retrieve(
             InterfaceType::MacSec => Interface::MacSec(Box::new(
                 np_macsec_to_nipart(np_iface, base_iface),
             )),
+            InterfaceType::Tun => {
+                Interface::Tun(Box::new(np_tun_to_nipart(np_iface, base_iface)))
+            }
+            InterfaceType::Gre => Interface::Gre(Box::new(np_gre_to_nipart(
+                base_iface, &gre_confs,
+            ))),
+            InterfaceType::GreTap => Interface::GreTap(Box::new(
+                np_gre_tap_to_nipart(base_iface, &gre_confs),
+            )),
+            InterfaceType::Wireguard => Interface::Wireguard(Box::new(
+                np_wireguard_to_nipart(base_iface, &wg_confs),
+            )),
             InterfaceType::Xfrm => {
                 let mut iface = XfrmInterface::new();
                 iface.base = base_iface;
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use nipart::{ErrorKind, Interface, MergedInterface, NipartError, SrIovConfig};
+
+// Changing VF count will recreate all VFs with default configuration, hence
+// we change VF count before applying VF configurations.
+pub(crate) async fn apply_sriov_total_vfs(
+    ifaces: &[&MergedInterface],
+) -> Result<(), NipartError> {
+    let mut np_ifaces: Vec<nispor::IfaceConf> = Vec::new();
+    for iface in ifaces {
+        let total_vfs = match get_sriov_conf(iface.for_apply.as_ref())
+            .and_then(|c| c.total_vfs)
+        {
+            Some(t) => t,
+            None => continue,
+        };
+        if get_sriov_conf(iface.current.as_ref()).and_then(|c| c.total_vfs)
+            == Some(total_vfs)
+        {
+            continue;
+        }
+        log::info!(
+            "Changing SR-IOV VF count of interface {} to {total_vfs}",
+            iface.merged.name()
+        );
+        let mut np_iface = nispor::IfaceConf::default();
+        np_iface.name = iface.merged.name().to_string();
+        np_iface.iface_type = Some(nispor::IfaceType::Ethernet);
+        np_iface.state = nispor::IfaceState::Up;
+        let mut np_sriov = nispor::SriovConf::default();
+        np_sriov.total_vfs = Some(total_vfs);
+        np_iface.sriov = Some(np_sriov);
+        np_ifaces.push(np_iface);
+    }
+    if np_ifaces.is_empty() {
+        return Ok(());
+    }
+
+    let mut net_conf = nispor::NetConf::default();
+    net_conf.ifaces = Some(np_ifaces);
+    if let Err(e) = net_conf.apply_async().await {
+        Err(NipartError::new(
+            ErrorKind::PluginFailure,
+            format!("Failed to change SR-IOV VF count: {}, {}", e.kind, e.msg),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// Only include VF configurations, the VF count is handled by
+// `apply_sriov_total_vfs()`.
+pub(crate) fn nipart_sriov_vfs_to_np(
+    sriov_conf: Option<&SrIovConfig>,
+) -> Option<nispor::SriovConf> {
+    let vfs = sriov_conf.and_then(|c| c.vfs.as_ref())?;
+    let mut np_vfs: Vec<nispor::VfConf> = Vec::new();
+    for vf in vfs {
+        let mut np_vf = nispor::VfConf::default();
+        np_vf.id = vf.id;
+        np_vf.mac = vf.mac_address.clone();
+        np_vf.spoof_check = vf.spoof_check;
+        np_vf.trust = vf.trust;
+        np_vf.min_tx_rate = vf.min_tx_rate;
+        np_vf.max_tx_rate = vf.max_tx_rate;
+        np_vf.vlan_id = vf.vlan_id;
+        np_vf.qos = vf.qos;
+        np_vfs.push(np_vf);
+    }
+    let mut np_sriov = nispor::SriovConf::default();
+    np_sriov.vfs = Some(np_vfs);
+    Some(np_sriov)
+}
+
+fn get_sriov_conf(iface: Option<&Interface>) -> Option<&SrIovConfig> {
+    if let Some(Interface::Ethernet(eth_iface)) = iface {
+        eth_iface.ethernet.as_ref().and_then(|e| e.sr_iov.as_ref())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nipart::{ErrorKind, MergedNetworkState, NetworkState};
+
+    use super::*;
+
+    fn gen_merged() -> MergedNetworkState {
+        let desired = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ethernet:
+                sr-iov:
+                  total-vfs: 1
+                  vfs:
+                  - id: 0
+                    trust: true",
+        )
+        .unwrap();
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: eth1
+              type: ethernet
+              state: up
+              ethernet:
+                sr-iov:
+                  total-vfs: 0",
+        )
+        .unwrap();
+        MergedNetworkState::new(desired, current, false, false).unwrap()
+    }
+
+    // Network state queried after VF count and VF configurations applied
+    fn gen_post_apply_state(trust: bool) -> NetworkState {
+        NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: eth1v0
+              type: ethernet
+              state: up
+            - name: eth1
+              type: ethernet
+              state: up
+              ethernet:
+                sr-iov:
+                  total-vfs: 1
+                  vfs:
+                  - id: 0
+                    iface-name: eth1v0
+                    mac-address: '00:11:22:33:44:55'
+                    spoof-check: true
+                    trust: {trust}
+                    min-tx-rate: 0
+                    max-tx-rate: 0
+                    vlan-id: 0
+                    qos: 0"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sriov_single_vf_trust_round_trip() {
+        let merged = gen_merged();
+        let for_apply =
+            merged.interfaces.kernel_ifaces["eth1"].for_apply.as_ref();
+        let sriov_conf = get_sriov_conf(for_apply);
+        assert_eq!(sriov_conf.and_then(|c| c.total_vfs), Some(1));
+
+        let np_sriov = nipart_sriov_vfs_to_np(sriov_conf).unwrap();
+        // VF count is applied by apply_sriov_total_vfs() beforehand
+        assert_eq!(np_sriov.total_vfs, None);
+        let np_vfs = np_sriov.vfs.unwrap();
+        assert_eq!(np_vfs.len(), 1);
+        assert_eq!(np_vfs[0].id, 0);
+        assert_eq!(np_vfs[0].trust, Some(true));
+        assert_eq!(np_vfs[0].spoof_check, None);
+
+        merged.verify(&gen_post_apply_state(true)).unwrap();
+    }
+
+    #[test]
+    fn test_sriov_vf_trust_not_applied() {
+        let merged = gen_merged();
+
+        let result = merged.verify(&gen_post_apply_state(false));
+
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert_eq!(e.kind, ErrorKind::VerificationError);
+        }
+    }
+}
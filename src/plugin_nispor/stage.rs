@@ -0,0 +1,302 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use nipart::{ErrorKind, Interface, MergedInterface, NipartError};
+
+// Split interfaces into stages where interfaces in the same stage have no
+// controller/port, parent/child or veth peer relationship between each other,
+// hence can be applied concurrently. Stages should be applied in order.
+// The input interfaces are expected to be sorted by `up_priority`, the
+// original order is preserved within each stage.
+// Port always lands in later stage than its controller, hence new port, for
+// example dummy interface, is created and attached to new bridge or bond in
+// single apply regardless of the declaration order in desired state.
+pub(crate) fn gen_apply_stages<'a>(
+    ifaces: &[(&'a str, &'a MergedInterface)],
+) -> Result<Vec<Vec<&'a MergedInterface>>, NipartError> {
+    let indexes: HashMap<&str, usize> = ifaces
+        .iter()
+        .enumerate()
+        .map(|(index, (name, _))| (*name, index))
+        .collect();
+
+    // The stage of each interface is one after its latest dependency.
+    // Loop until stable, the dependency chain cannot be longer than
+    // interface count unless there is a loop.
+    let mut stages: Vec<usize> = vec![0; ifaces.len()];
+    let mut changed = true;
+    let mut loop_count = 0usize;
+    while changed {
+        if loop_count > ifaces.len() {
+            return Err(NipartError::new(
+                ErrorKind::InvalidArgument,
+                "Found dependency loop among interfaces to apply".to_string(),
+            ));
+        }
+        loop_count += 1;
+        changed = false;
+        for (index, (name, iface)) in ifaces.iter().enumerate() {
+            let Some(apply_iface) = iface.for_apply.as_ref() else {
+                continue;
+            };
+            for dep in get_dependencies(name, apply_iface) {
+                if let Some(dep_index) = indexes.get(dep) {
+                    if stages[index] <= stages[*dep_index] {
+                        stages[index] = stages[*dep_index] + 1;
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut ret: Vec<Vec<&MergedInterface>> =
+        vec![Vec::new(); stages.iter().max().map(|s| s + 1).unwrap_or(0)];
+    for (index, (_, iface)) in ifaces.iter().enumerate() {
+        ret[stages[index]].push(iface);
+    }
+    Ok(ret)
+}
+
+fn get_dependencies<'a>(name: &str, iface: &'a Interface) -> Vec<&'a str> {
+    let mut ret = Vec::new();
+    if let Some(ctrl) = iface
+        .base_iface()
+        .controller
+        .as_deref()
+        .filter(|c| !c.is_empty())
+    {
+        ret.push(ctrl);
+    }
+    if let Some(parent) = iface.parent() {
+        ret.push(parent);
+    }
+    // Only one end of veth pair is in charge of creating the pair
+    if let Interface::Ethernet(eth_iface) = iface {
+        if let Some(peer) = eth_iface
+            .veth
+            .as_ref()
+            .map(|v| v.peer.as_str())
+            .filter(|peer| *peer < name)
+        {
+            ret.push(peer);
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use nipart::{MergedInterface, MergedNetworkState, NetworkState};
+
+    use super::gen_apply_stages;
+
+    // Mimic the interface sorting and filtering done by `nispor_apply()`
+    fn gen_stage_names(desired_yaml: &str) -> Vec<Vec<String>> {
+        gen_stage_names_with_current(desired_yaml, NetworkState::new())
+    }
+
+    fn gen_stage_names_with_current(
+        desired_yaml: &str,
+        current: NetworkState,
+    ) -> Vec<Vec<String>> {
+        let merged_state = MergedNetworkState::new(
+            NetworkState::new_from_yaml(desired_yaml).unwrap(),
+            current,
+            false,
+            false,
+        )
+        .unwrap();
+        let mut ifaces: Vec<&MergedInterface> = merged_state
+            .interfaces
+            .iter()
+            .filter(|i| i.is_changed() && !i.merged.is_absent())
+            .collect();
+        ifaces.sort_unstable_by_key(|iface| iface.merged.name());
+        ifaces.sort_by_key(|iface| {
+            if let Some(i) = iface.for_apply.as_ref() {
+                i.base_iface().up_priority
+            } else {
+                u32::MAX
+            }
+        });
+        let apply_ifaces: Vec<(&str, &MergedInterface)> = ifaces
+            .iter()
+            .filter_map(|i| i.for_apply.as_ref().map(|a| (a.name(), *i)))
+            .collect();
+
+        gen_apply_stages(apply_ifaces.as_slice())
+            .unwrap()
+            .into_iter()
+            .map(|stage| {
+                stage
+                    .into_iter()
+                    .map(|i| i.merged.name().to_string())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn stage_of(stages: &[Vec<String>], iface_name: &str) -> usize {
+        stages
+            .iter()
+            .position(|s| s.iter().any(|n| n == iface_name))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_stage_controller_before_ports() {
+        // Ports declared before controller in desired state
+        let stages = gen_stage_names(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+            - name: dummy2
+              type: dummy
+              state: up
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: balance-rr
+                port:
+                - dummy2
+                - dummy1
+            - name: br0
+              type: linux-bridge
+              state: up
+              bridge:
+                port:
+                - name: bond99",
+        );
+
+        assert_eq!(stages.len(), 3);
+        assert!(stage_of(&stages, "br0") < stage_of(&stages, "bond99"));
+        assert!(stage_of(&stages, "bond99") < stage_of(&stages, "dummy1"));
+        assert!(stage_of(&stages, "bond99") < stage_of(&stages, "dummy2"));
+        assert_eq!(stage_of(&stages, "dummy1"), stage_of(&stages, "dummy2"));
+    }
+
+    #[test]
+    fn test_stage_new_dummy_and_bridge_in_any_order() {
+        let dummy_yaml = r"
+            - name: dummy1
+              type: dummy
+              state: up";
+        let bridge_yaml = r"
+            - name: br0
+              type: linux-bridge
+              state: up
+              bridge:
+                port:
+                - name: dummy1";
+        for desired_yaml in [
+            format!("---\ninterfaces:{dummy_yaml}{bridge_yaml}"),
+            format!("---\ninterfaces:{bridge_yaml}{dummy_yaml}"),
+        ] {
+            let stages = gen_stage_names(&desired_yaml);
+
+            // Bridge created first, then dummy created and attached to it
+            assert_eq!(stages, vec![vec!["br0"], vec!["dummy1"]]);
+        }
+    }
+
+    #[test]
+    fn test_stage_parent_before_vlan() {
+        let stages = gen_stage_names(
+            r"---
+            interfaces:
+            - name: dummy1.10
+              type: vlan
+              state: up
+              vlan:
+                base-iface: dummy1
+                id: 10
+            - name: dummy1
+              type: dummy
+              state: up",
+        );
+
+        assert_eq!(stages, vec![vec!["dummy1"], vec!["dummy1.10"]]);
+    }
+
+    #[test]
+    fn test_stage_bond_mode_change_with_new_vlan() {
+        let current = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              controller: bond99
+            - name: dummy2
+              type: dummy
+              state: up
+              controller: bond99
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: balance-rr
+                port:
+                - dummy1
+                - dummy2",
+        )
+        .unwrap();
+        let stages = gen_stage_names_with_current(
+            r"---
+            interfaces:
+            - name: bond99.10
+              type: vlan
+              state: up
+              vlan:
+                base-iface: bond99
+                id: 10
+            - name: bond99
+              type: bond
+              state: up
+              link-aggregation:
+                mode: active-backup",
+            current,
+        );
+
+        // Ports are reattached after bond changed its mode
+        assert!(stage_of(&stages, "bond99") < stage_of(&stages, "dummy1"));
+        assert!(stage_of(&stages, "bond99") < stage_of(&stages, "dummy2"));
+        assert!(stage_of(&stages, "bond99") < stage_of(&stages, "bond99.10"));
+    }
+
+    #[test]
+    fn test_stage_many_dummies() {
+        const DUMMY_COUNT: usize = 2000;
+        const PORT_COUNT: usize = 200;
+        let mut yaml = String::from("interfaces:\n");
+        for i in 0..DUMMY_COUNT {
+            yaml.push_str(&format!(
+                "- name: dummy{i}\n  type: dummy\n  state: up\n"
+            ));
+        }
+        yaml.push_str(
+            "- name: br0\n  type: linux-bridge\n  state: up\n  \
+            bridge:\n    port:\n",
+        );
+        for i in 0..PORT_COUNT {
+            yaml.push_str(&format!("    - name: dummy{i}\n"));
+        }
+
+        let stages = gen_stage_names(&yaml);
+
+        // Stand-alone dummies are applied concurrently with the bridge,
+        // bridge ports are applied after.
+        assert_eq!(stages.len(), 2);
+        assert_eq!(stages[0].len(), DUMMY_COUNT - PORT_COUNT + 1);
+        assert_eq!(stages[1].len(), PORT_COUNT);
+        assert!(stages[0].iter().any(|n| n == "br0"));
+        for i in 0..PORT_COUNT {
+            assert_eq!(stage_of(&stages, &format!("dummy{i}")), 1);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::os::fd::AsRawFd;
+
+use nipart::{
+    BaseInterface, ErrorKind, Interface, MergedInterface, NipartError,
+    TunConfig, TunInterface, TunMode,
+};
+
+const TUN_DEV_PATH: &str = "/dev/net/tun";
+
+// Defined in linux/if_tun.h
+const IFF_TUN: i16 = 0x0001;
+const IFF_TAP: i16 = 0x0002;
+const IFF_MULTI_QUEUE: i16 = 0x0100;
+const IFF_NO_PI: i16 = 0x1000;
+const IFF_VNET_HDR: i16 = 0x4000;
+
+nix::ioctl_write_int!(tun_set_iff, b'T', 202);
+nix::ioctl_write_int!(tun_set_persist, b'T', 203);
+nix::ioctl_write_int!(tun_set_owner, b'T', 204);
+nix::ioctl_write_int!(tun_set_group, b'T', 206);
+
+pub(crate) fn np_tun_to_nipart(
+    np_iface: &nispor::Iface,
+    base_iface: BaseInterface,
+) -> TunInterface {
+    let tun_conf = np_iface.tun.as_ref().map(|np_tun_info| {
+        let mut conf = TunConfig::default();
+        conf.mode = match &np_tun_info.mode {
+            nispor::TunMode::Tun => Some(TunMode::Tun),
+            nispor::TunMode::Tap => Some(TunMode::Tap),
+            _ => {
+                log::warn!("Unknown supported TUN mode {:?}", np_tun_info.mode);
+                None
+            }
+        };
+        conf.owner = np_tun_info.owner;
+        conf.group = np_tun_info.group;
+        conf.multi_queue = Some(np_tun_info.multi_queue);
+        conf.pi = Some(np_tun_info.pi);
+        conf.vnet_hdr = Some(np_tun_info.vnet_hdr);
+        conf
+    });
+
+    let mut ret = TunInterface::default();
+    ret.base = base_iface;
+    ret.tun = tun_conf;
+    ret
+}
+
+// Nispor cannot create TUN/TAP interface, hence we create persistent TUN/TAP
+// device via ioctl before other settings are applied by nispor.
+// For existing interface, only owner and group can be changed, other flags
+// are checked by nipart library already.
+pub(crate) fn apply_tun_ifaces(
+    ifaces: &[(&str, &MergedInterface)],
+) -> Result<(), NipartError> {
+    for (iface_name, merged_iface) in ifaces {
+        let Some(Interface::Tun(apply_iface)) = merged_iface.for_apply.as_ref()
+        else {
+            continue;
+        };
+        let cur_conf = if let Some(Interface::Tun(cur_iface)) =
+            merged_iface.current.as_ref()
+        {
+            cur_iface.tun.as_ref()
+        } else {
+            None
+        };
+        let apply_conf = apply_iface.tun.clone().unwrap_or_default();
+
+        if merged_iface.current.is_some() {
+            let owner_changed = apply_conf.owner.is_some()
+                && apply_conf.owner != cur_conf.and_then(|c| c.owner);
+            let group_changed = apply_conf.group.is_some()
+                && apply_conf.group != cur_conf.and_then(|c| c.group);
+            if !owner_changed && !group_changed {
+                continue;
+            }
+            // Use current flags as kernel refuses to attach with different
+            // flags
+            let mut conf = cur_conf.cloned().unwrap_or_default();
+            conf.owner = apply_conf.owner;
+            conf.group = apply_conf.group;
+            log::debug!(
+                "Changing owner/group of TUN/TAP interface {iface_name}"
+            );
+            set_tun_iface(iface_name, &conf)?;
+        } else {
+            log::debug!("Creating TUN/TAP interface {iface_name}");
+            set_tun_iface(iface_name, &apply_conf)?;
+        }
+    }
+    Ok(())
+}
+
+fn set_tun_iface(
+    iface_name: &str,
+    conf: &TunConfig,
+) -> Result<(), NipartError> {
+    let fd = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(TUN_DEV_PATH)
+        .map_err(|e| {
+            let e = NipartError::new(
+                ErrorKind::PluginFailure,
+                format!("Failed to open {TUN_DEV_PATH}: {e}"),
+            );
+            log::error!("{}", e);
+            e
+        })?;
+
+    let mut flags = match conf.mode.unwrap_or_default() {
+        TunMode::Tap => IFF_TAP,
+        _ => IFF_TUN,
+    };
+    if conf.multi_queue == Some(true) {
+        flags |= IFF_MULTI_QUEUE;
+    }
+    if conf.pi != Some(true) {
+        flags |= IFF_NO_PI;
+    }
+    if conf.vnet_hdr == Some(true) {
+        flags |= IFF_VNET_HDR;
+    }
+
+    let mut ifr: nix::libc::ifreq = unsafe { std::mem::zeroed() };
+    for (i, c) in iface_name
+        .as_bytes()
+        .iter()
+        .take(nix::libc::IFNAMSIZ - 1)
+        .enumerate()
+    {
+        ifr.ifr_name[i] = *c as nix::libc::c_char;
+    }
+    ifr.ifr_ifru.ifru_flags = flags;
+
+    let raw_fd = fd.as_raw_fd();
+    unsafe {
+        tun_set_iff(raw_fd, &ifr as *const _ as nix::libc::c_ulong)
+            .map_err(|e| tun_ioctl_error(iface_name, "TUNSETIFF", e))?;
+        if let Some(owner) = conf.owner {
+            tun_set_owner(raw_fd, owner as nix::libc::c_ulong)
+                .map_err(|e| tun_ioctl_error(iface_name, "TUNSETOWNER", e))?;
+        }
+        if let Some(group) = conf.group {
+            tun_set_group(raw_fd, group as nix::libc::c_ulong)
+                .map_err(|e| tun_ioctl_error(iface_name, "TUNSETGROUP", e))?;
+        }
+        tun_set_persist(raw_fd, 1)
+            .map_err(|e| tun_ioctl_error(iface_name, "TUNSETPERSIST", e))?;
+    }
+    Ok(())
+}
+
+fn tun_ioctl_error(
+    iface_name: &str,
+    action: &str,
+    e: nix::errno::Errno,
+) -> NipartError {
+    let e = NipartError::new(
+        ErrorKind::PluginFailure,
+        format!("Failed to {action} on TUN/TAP interface {iface_name}: {e}"),
+    );
+    log::error!("{}", e);
+    e
+}
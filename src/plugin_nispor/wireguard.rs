@@ -0,0 +1,354 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::stream::{StreamExt, TryStreamExt};
+use netlink_packet_core::{
+    NetlinkMessage, NetlinkPayload, NLM_F_ACK, NLM_F_DUMP, NLM_F_REQUEST,
+};
+use netlink_packet_generic::GenlMessage;
+use netlink_packet_wireguard::{
+    nlas::{WgAllowedIp, WgAllowedIpAttrs, WgDeviceAttrs, WgPeer, WgPeerAttrs},
+    Wireguard, WireguardCmd,
+};
+use nipart::{
+    BaseInterface, ErrorKind, Interface, MergedInterface, NipartError,
+    WireguardConfig, WireguardInterface, WireguardPeerConfig,
+};
+
+// Defined in linux/wireguard.h
+const WGDEVICE_F_REPLACE_PEERS: u32 = 1;
+const WGPEER_F_REPLACE_ALLOWEDIPS: u32 = 2;
+
+const WG_KEY_LEN: usize = 32;
+
+pub(crate) fn is_np_wireguard_iface(np_iface: &nispor::Iface) -> bool {
+    if let nispor::IfaceType::Other(t) = &np_iface.iface_type {
+        t.eq_ignore_ascii_case("wireguard")
+    } else {
+        false
+    }
+}
+
+pub(crate) fn np_wireguard_to_nipart(
+    base_iface: BaseInterface,
+    wg_confs: &HashMap<String, WireguardConfig>,
+) -> WireguardInterface {
+    let mut ret = WireguardInterface::default();
+    ret.wireguard = wg_confs.get(base_iface.name.as_str()).cloned();
+    ret.base = base_iface;
+    ret
+}
+
+// Nispor does not support WireGuard yet, use generic netlink directly.
+// The private key and preshared keys are never included in the result.
+pub(crate) async fn get_wireguard_confs(
+    iface_names: &[&str],
+) -> Result<HashMap<String, WireguardConfig>, NipartError> {
+    let mut ret = HashMap::new();
+    if iface_names.is_empty() {
+        return Ok(ret);
+    }
+    let (conn, mut handle, _) = genetlink::new_connection()
+        .map_err(|e| wg_error(format!("Failed to create genetlink: {e}")))?;
+    tokio::spawn(conn);
+
+    for iface_name in iface_names {
+        let mut nl_msg =
+            NetlinkMessage::from(GenlMessage::from_payload(Wireguard {
+                cmd: WireguardCmd::GetDevice,
+                nlas: vec![WgDeviceAttrs::IfName(iface_name.to_string())],
+            }));
+        nl_msg.header.flags = NLM_F_REQUEST | NLM_F_DUMP;
+        let mut responses = handle.request(nl_msg).await.map_err(|e| {
+            wg_error(format!(
+                "Failed to query WireGuard interface {iface_name}: {e}"
+            ))
+        })?;
+
+        let mut conf = WireguardConfig::default();
+        while let Some(response) = responses.next().await {
+            let response = response.map_err(|e| {
+                wg_error(format!(
+                    "Failed to query WireGuard interface {iface_name}: {e}"
+                ))
+            })?;
+            if let NetlinkPayload::InnerMessage(genl_msg) = response.payload {
+                parse_wg_device_attrs(genl_msg.payload.nlas, &mut conf);
+            }
+        }
+        if let Some(peers) = conf.peers.as_mut() {
+            peers.sort_unstable_by(|a, b| a.public_key.cmp(&b.public_key));
+        }
+        ret.insert(iface_name.to_string(), conf);
+    }
+    Ok(ret)
+}
+
+fn parse_wg_device_attrs(nlas: Vec<WgDeviceAttrs>, conf: &mut WireguardConfig) {
+    for nla in nlas {
+        match nla {
+            WgDeviceAttrs::PublicKey(k) => {
+                conf.public_key = Some(STANDARD.encode(k))
+            }
+            WgDeviceAttrs::ListenPort(p) => conf.listen_port = Some(p),
+            WgDeviceAttrs::Fwmark(m) => conf.fwmark = Some(m),
+            WgDeviceAttrs::Peers(np_peers) => {
+                let peers = conf.peers.get_or_insert_with(Vec::new);
+                for np_peer in np_peers {
+                    peers.push(parse_wg_peer(np_peer));
+                }
+            }
+            _ => (),
+        }
+    }
+    if conf.peers.is_none() {
+        conf.peers = Some(Vec::new());
+    }
+}
+
+fn parse_wg_peer(np_peer: WgPeer) -> WireguardPeerConfig {
+    let mut peer = WireguardPeerConfig::default();
+    for nla in np_peer.0 {
+        match nla {
+            WgPeerAttrs::PublicKey(k) => peer.public_key = STANDARD.encode(k),
+            WgPeerAttrs::Endpoint(e) => peer.endpoint = Some(e),
+            WgPeerAttrs::PersistentKeepalive(k) => {
+                peer.persistent_keepalive = Some(k)
+            }
+            WgPeerAttrs::AllowedIps(np_ips) => {
+                let mut allowed_ips = Vec::new();
+                for np_ip in np_ips {
+                    let mut addr = None;
+                    let mut cidr = None;
+                    for ip_nla in np_ip.0 {
+                        match ip_nla {
+                            WgAllowedIpAttrs::IpAddr(a) => addr = Some(a),
+                            WgAllowedIpAttrs::Cidr(c) => cidr = Some(c),
+                            _ => (),
+                        }
+                    }
+                    if let (Some(addr), Some(cidr)) = (addr, cidr) {
+                        allowed_ips.push(format!("{addr}/{cidr}"));
+                    }
+                }
+                allowed_ips.sort_unstable();
+                peer.allowed_ips = Some(allowed_ips);
+            }
+            _ => (),
+        }
+    }
+    peer
+}
+
+// Create WireGuard interface if not exist and apply the WireGuard specific
+// settings. Nispor will handle the remaining settings like IP and state.
+pub(crate) async fn apply_wireguard_ifaces(
+    ifaces: &[(&str, &MergedInterface)],
+) -> Result<(), NipartError> {
+    let wg_ifaces: Vec<(&str, &MergedInterface, &WireguardInterface)> = ifaces
+        .iter()
+        .filter_map(|(iface_name, merged_iface)| {
+            if let Some(Interface::Wireguard(wg_iface)) =
+                merged_iface.for_apply.as_ref()
+            {
+                Some((*iface_name, *merged_iface, wg_iface.as_ref()))
+            } else {
+                None
+            }
+        })
+        .collect();
+    if wg_ifaces.is_empty() {
+        return Ok(());
+    }
+
+    for (iface_name, merged_iface, _) in wg_ifaces.as_slice() {
+        if merged_iface.current.is_none() {
+            create_wireguard_iface(iface_name).await?;
+        }
+    }
+
+    let (conn, mut handle, _) = genetlink::new_connection()
+        .map_err(|e| wg_error(format!("Failed to create genetlink: {e}")))?;
+    tokio::spawn(conn);
+
+    for (iface_name, _, wg_iface) in wg_ifaces {
+        let Some(conf) = wg_iface.wireguard.as_ref() else {
+            continue;
+        };
+        let nlas = nipart_wg_conf_to_nlas(iface_name, conf)?;
+        log::debug!("Applying WireGuard config to interface {iface_name}");
+        let mut nl_msg =
+            NetlinkMessage::from(GenlMessage::from_payload(Wireguard {
+                cmd: WireguardCmd::SetDevice,
+                nlas,
+            }));
+        nl_msg.header.flags = NLM_F_REQUEST | NLM_F_ACK;
+        let mut responses = handle.request(nl_msg).await.map_err(|e| {
+            wg_error(format!(
+                "Failed to apply WireGuard interface {iface_name}: {e}"
+            ))
+        })?;
+        while let Some(response) = responses.next().await {
+            let response = response.map_err(|e| {
+                wg_error(format!(
+                    "Failed to apply WireGuard interface {iface_name}: {e}"
+                ))
+            })?;
+            if let NetlinkPayload::Error(e) = response.payload {
+                return Err(wg_error(format!(
+                    "Failed to apply WireGuard interface {iface_name}: {e}"
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn create_wireguard_iface(iface_name: &str) -> Result<(), NipartError> {
+    log::debug!("Creating WireGuard interface {iface_name}");
+    let (conn, handle, _) = rtnetlink::new_connection().map_err(|e| {
+        wg_error(format!("Failed to create rtnetlink connection: {e}"))
+    })?;
+    tokio::spawn(conn);
+    handle
+        .link()
+        .add()
+        .wireguard(iface_name.to_string())
+        .execute()
+        .await
+        .map_err(|e| {
+            wg_error(format!(
+                "Failed to create WireGuard interface {iface_name}: {e}"
+            ))
+        })?;
+    // Make sure interface is created before we move on
+    let _ = handle
+        .link()
+        .get()
+        .match_name(iface_name.to_string())
+        .execute()
+        .try_next()
+        .await;
+    Ok(())
+}
+
+fn nipart_wg_conf_to_nlas(
+    iface_name: &str,
+    conf: &WireguardConfig,
+) -> Result<Vec<WgDeviceAttrs>, NipartError> {
+    let mut nlas = vec![WgDeviceAttrs::IfName(iface_name.to_string())];
+    if let Some(k) = conf.private_key.as_deref() {
+        nlas.push(WgDeviceAttrs::PrivateKey(decode_wg_key(
+            iface_name,
+            "private-key",
+            k,
+        )?));
+    }
+    if let Some(p) = conf.listen_port {
+        nlas.push(WgDeviceAttrs::ListenPort(p));
+    }
+    if let Some(m) = conf.fwmark {
+        nlas.push(WgDeviceAttrs::Fwmark(m));
+    }
+    if let Some(peers) = conf.peers.as_ref() {
+        nlas.push(WgDeviceAttrs::Flags(WGDEVICE_F_REPLACE_PEERS));
+        let mut np_peers = Vec::new();
+        for peer in peers {
+            let mut peer_nlas = vec![WgPeerAttrs::PublicKey(decode_wg_key(
+                iface_name,
+                "public-key",
+                peer.public_key.as_str(),
+            )?)];
+            if let Some(k) = peer.preshared_key.as_deref() {
+                peer_nlas.push(WgPeerAttrs::PresharedKey(decode_wg_key(
+                    iface_name,
+                    "preshared-key",
+                    k,
+                )?));
+            }
+            if let Some(e) = peer.endpoint {
+                peer_nlas.push(WgPeerAttrs::Endpoint(e));
+            }
+            if let Some(k) = peer.persistent_keepalive {
+                peer_nlas.push(WgPeerAttrs::PersistentKeepalive(k));
+            }
+            if let Some(allowed_ips) = peer.allowed_ips.as_ref() {
+                peer_nlas.push(WgPeerAttrs::Flags(WGPEER_F_REPLACE_ALLOWEDIPS));
+                let mut np_ips = Vec::new();
+                for allowed_ip in allowed_ips {
+                    np_ips.push(parse_allowed_ip(iface_name, allowed_ip)?);
+                }
+                peer_nlas.push(WgPeerAttrs::AllowedIps(np_ips));
+            }
+            np_peers.push(WgPeer(peer_nlas));
+        }
+        nlas.push(WgDeviceAttrs::Peers(np_peers));
+    }
+    Ok(nlas)
+}
+
+fn decode_wg_key(
+    iface_name: &str,
+    prop_name: &str,
+    key: &str,
+) -> Result<[u8; WG_KEY_LEN], NipartError> {
+    STANDARD
+        .decode(key)
+        .ok()
+        .and_then(|k| <[u8; WG_KEY_LEN]>::try_from(k).ok())
+        .ok_or_else(|| {
+            let e = NipartError::new(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Invalid {prop_name} of WireGuard interface \
+                    {iface_name}: expecting base64 encoded {WG_KEY_LEN} bytes"
+                ),
+            );
+            log::error!("{}", e);
+            e
+        })
+}
+
+fn parse_allowed_ip(
+    iface_name: &str,
+    allowed_ip: &str,
+) -> Result<WgAllowedIp, NipartError> {
+    let (addr, cidr) = allowed_ip.split_once('/').unwrap_or((allowed_ip, ""));
+    let addr = addr.parse::<std::net::IpAddr>().ok();
+    let cidr = match (addr, cidr) {
+        (Some(std::net::IpAddr::V4(_)), "") => Some(32),
+        (Some(std::net::IpAddr::V6(_)), "") => Some(128),
+        (Some(_), c) => c.parse::<u8>().ok(),
+        (None, _) => None,
+    };
+    if let (Some(addr), Some(cidr)) = (addr, cidr) {
+        let family = if addr.is_ipv4() {
+            nix::libc::AF_INET as u16
+        } else {
+            nix::libc::AF_INET6 as u16
+        };
+        Ok(WgAllowedIp(vec![
+            WgAllowedIpAttrs::Family(family),
+            WgAllowedIpAttrs::IpAddr(addr),
+            WgAllowedIpAttrs::Cidr(cidr),
+        ]))
+    } else {
+        let e = NipartError::new(
+            ErrorKind::InvalidArgument,
+            format!(
+                "Invalid allowed-ips {allowed_ip} of WireGuard interface \
+                {iface_name}"
+            ),
+        );
+        log::error!("{}", e);
+        Err(e)
+    }
+}
+
+fn wg_error(msg: String) -> NipartError {
+    let e = NipartError::new(ErrorKind::PluginFailure, msg);
+    log::error!("{}", e);
+    e
+}
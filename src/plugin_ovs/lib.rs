@@ -0,0 +1,5 @@
+// SPDX-License-Identifier: Apache-2.0
+
+mod plugin;
+
+pub use self::plugin::NipartPluginOvs;
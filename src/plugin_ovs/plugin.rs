@@ -0,0 +1,380 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use nipart::{
+    ovsdb_apply, ovsdb_is_changed, ovsdb_is_running, ovsdb_retrieve, ErrorKind,
+    Interface, MergedNetworkState, NetworkState, NipartError, NipartEvent,
+    NipartEventAddress, NipartLogLevel, NipartNativePlugin, NipartPluginEvent,
+    NipartRole, NipartUserEvent, DEFAULT_TIMEOUT,
+};
+use tokio::sync::mpsc::{Receiver, Sender};
+
+// Higher than nispor plugin as OVSDB is the source of truth for OVS
+// configurations
+const STATE_PRIORITY: u32 = 60;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct NipartPluginOvs {
+    log_level: NipartLogLevel,
+    to_daemon: Sender<NipartEvent>,
+    from_daemon: Receiver<NipartEvent>,
+}
+
+impl NipartNativePlugin for NipartPluginOvs {
+    const PLUGIN_NAME: &'static str = "ovs";
+
+    fn roles() -> Vec<NipartRole> {
+        vec![NipartRole::QueryAndApply, NipartRole::Ovs]
+    }
+
+    fn recver_from_daemon(&mut self) -> &mut Receiver<NipartEvent> {
+        &mut self.from_daemon
+    }
+
+    fn sender_to_daemon(&self) -> &Sender<NipartEvent> {
+        &self.to_daemon
+    }
+
+    fn get_log_level(&self) -> NipartLogLevel {
+        self.log_level
+    }
+
+    fn set_log_level(&mut self, level: NipartLogLevel) {
+        self.log_level = level;
+    }
+
+    async fn init(
+        log_level: NipartLogLevel,
+        to_daemon: Sender<NipartEvent>,
+        from_daemon: Receiver<NipartEvent>,
+    ) -> Result<Self, NipartError> {
+        Ok(Self {
+            log_level,
+            to_daemon,
+            from_daemon,
+        })
+    }
+
+    async fn handle_event(
+        &mut self,
+        event: NipartEvent,
+    ) -> Result<(), NipartError> {
+        match event.plugin {
+            NipartPluginEvent::QueryNetState(_)
+            | NipartPluginEvent::QueryRelatedNetState(_) => {
+                // OVSDB socket and ovs-ofctl are blocking
+                let result =
+                    match tokio::task::spawn_blocking(ovs_retrieve).await {
+                        Ok(r) => r,
+                        Err(e) => Err(NipartError::new(
+                            ErrorKind::Bug,
+                            format!("Failed to join OVS query thread: {e}"),
+                        )),
+                    };
+                // Reply empty state on failure, so the commander does not
+                // wait for us till timeout
+                let (user_event, state) = match result {
+                    Ok(state) => (NipartUserEvent::None, state),
+                    Err(e) => {
+                        log::error!("{e}");
+                        (NipartUserEvent::Error(e), NetworkState::new())
+                    }
+                };
+                let mut reply = NipartEvent::new(
+                    user_event,
+                    NipartPluginEvent::QueryNetStateReply(
+                        Box::new(state),
+                        STATE_PRIORITY,
+                    ),
+                    NipartEventAddress::Unicast(Self::PLUGIN_NAME.to_string()),
+                    NipartEventAddress::Commander,
+                    DEFAULT_TIMEOUT,
+                );
+                reply.uuid = event.uuid;
+                self.sender_to_daemon().send(reply).await?;
+                Ok(())
+            }
+            NipartPluginEvent::ApplyNetState(merged_state, _) => {
+                let to_daemon_clone = self.sender_to_daemon().clone();
+                tokio::spawn(async move {
+                    handle_apply(*merged_state, to_daemon_clone, event.uuid)
+                        .await
+                });
+                Ok(())
+            }
+            _ => {
+                log::warn!("Plugin ovs got unknown event {event:?}");
+                Ok(())
+            }
+        }
+    }
+}
+
+// Return empty NetworkState if OVS daemon is not running
+fn ovs_retrieve() -> Result<NetworkState, NipartError> {
+    if ovsdb_is_running() {
+        let mut state = ovsdb_retrieve()?;
+        // Report OVN bridge mappings in `ovn` section instead of raw
+        // `external_ids` entry of OVSDB global configuration
+        state.isolate_ovn()?;
+        fill_ovs_flow_count(&mut state);
+        Ok(state)
+    } else {
+        log::debug!("OVS daemon is not running");
+        Ok(NetworkState::new())
+    }
+}
+
+// The OpenFlow flows are not stored in OVSDB, hence use `ovs-ofctl` to
+// query. Flow count is omitted if failed to query, for example
+// ovs-vswitchd is not running.
+fn fill_ovs_flow_count(state: &mut NetworkState) {
+    for iface in state.interfaces.iter_mut() {
+        if let Interface::OvsBridge(br_iface) = iface {
+            let flow_count = get_ovs_flow_count(br_iface.base.name.as_str());
+            if let Some(br_conf) = br_iface.bridge.as_mut() {
+                br_conf.flow_count = flow_count;
+            }
+        }
+    }
+}
+
+fn get_ovs_flow_count(br_name: &str) -> Option<u64> {
+    let output = match std::process::Command::new("ovs-ofctl")
+        .args(["dump-aggregate", br_name])
+        .output()
+    {
+        Ok(o) => o,
+        Err(e) => {
+            log::debug!("Failed to invoke ovs-ofctl: {e}");
+            return None;
+        }
+    };
+    if !output.status.success() {
+        log::debug!(
+            "Failed to query flow count of OVS bridge {br_name}: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        return None;
+    }
+    parse_ovs_flow_count(&String::from_utf8_lossy(&output.stdout))
+}
+
+// Output example:
+//  NXST_AGGREGATE reply (xid=0x4): packet_count=0 byte_count=0
+//  flow_count=1
+fn parse_ovs_flow_count(output: &str) -> Option<u64> {
+    output
+        .split_whitespace()
+        .find_map(|s| s.strip_prefix("flow_count="))
+        .and_then(|s| s.parse::<u64>().ok())
+}
+
+fn ovs_apply(merged_state: &MergedNetworkState) -> Result<(), NipartError> {
+    // The OVN bridge mappings are stored as `external_ids` entry of
+    // OVSDB global configuration, hence included in this check.
+    if !ovsdb_is_changed(merged_state) {
+        return Ok(());
+    }
+    if ovsdb_is_running() {
+        ovsdb_apply(merged_state)
+    } else {
+        Err(NipartError::new(
+            ErrorKind::DependencyError,
+            "OVS daemon is not running, cannot apply OVSDB \
+            global configurations, OVN bridge mappings, OVS bridge options \
+            or OVS interface DPDK options"
+                .to_string(),
+        ))
+    }
+}
+
+async fn handle_apply(
+    merged_state: MergedNetworkState,
+    to_daemon: Sender<NipartEvent>,
+    uuid: u128,
+) {
+    let result =
+        match tokio::task::spawn_blocking(move || ovs_apply(&merged_state))
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => Err(NipartError::new(
+                ErrorKind::Bug,
+                format!("Failed to join OVS apply thread: {e}"),
+            )),
+        };
+    let mut reply = match result {
+        Ok(()) => NipartEvent::new(
+            NipartUserEvent::None,
+            NipartPluginEvent::ApplyNetStateReply,
+            NipartEventAddress::Unicast(
+                NipartPluginOvs::PLUGIN_NAME.to_string(),
+            ),
+            NipartEventAddress::Commander,
+            DEFAULT_TIMEOUT,
+        ),
+        Err(e) => NipartEvent::new(
+            NipartUserEvent::Error(e),
+            NipartPluginEvent::ApplyNetStateReply,
+            NipartEventAddress::Unicast(
+                NipartPluginOvs::PLUGIN_NAME.to_string(),
+            ),
+            NipartEventAddress::Commander,
+            DEFAULT_TIMEOUT,
+        ),
+    };
+    reply.uuid = uuid;
+    log::trace!("Sending reply {reply:?}");
+    if let Err(e) = to_daemon.send(reply).await {
+        log::error!("Failed to reply {e}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nipart::NipartQueryOption;
+    use tokio::sync::mpsc::{channel, Receiver};
+
+    use super::*;
+
+    async fn new_ovs_plugin() -> (NipartPluginOvs, Receiver<NipartEvent>) {
+        let (to_daemon, from_ovs) = channel(64);
+        let (_to_ovs, from_daemon) = channel(64);
+        let plugin =
+            NipartPluginOvs::init(NipartLogLevel::Off, to_daemon, from_daemon)
+                .await
+                .unwrap();
+        (plugin, from_ovs)
+    }
+
+    fn gen_merged_state(desired_yaml: &str) -> MergedNetworkState {
+        MergedNetworkState::new(
+            NetworkState::new_from_yaml(desired_yaml).unwrap(),
+            NetworkState::new(),
+            false,
+            false,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_ovs_flow_count() {
+        assert_eq!(
+            parse_ovs_flow_count(
+                "NXST_AGGREGATE reply (xid=0x4): packet_count=0 \
+                byte_count=0 flow_count=12\n"
+            ),
+            Some(12)
+        );
+        assert_eq!(
+            parse_ovs_flow_count("ovs-ofctl: br0 is not a bridge"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ovs_bridge_status_absent_when_ovs_unavailable() {
+        if ovsdb_is_running() {
+            return;
+        }
+        assert!(ovs_retrieve().unwrap().interfaces.is_empty());
+
+        let mut state = NetworkState::new_from_yaml(
+            r"---
+            interfaces:
+            - name: br0
+              type: ovs-bridge
+              state: up
+              bridge:
+                port:
+                - name: eth1",
+        )
+        .unwrap();
+        fill_ovs_flow_count(&mut state);
+
+        let br_conf = state
+            .interfaces
+            .iter()
+            .find_map(|i| {
+                if let Interface::OvsBridge(br_iface) = i {
+                    br_iface.bridge.clone()
+                } else {
+                    None
+                }
+            })
+            .unwrap();
+        assert_eq!(br_conf.flow_count, None);
+        assert_eq!(br_conf.datapath_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_query_always_replied() {
+        let (mut plugin, mut from_ovs) = new_ovs_plugin().await;
+        let event = NipartEvent::new_with_uuid(
+            1,
+            NipartUserEvent::None,
+            NipartPluginEvent::QueryNetState(NipartQueryOption::default()),
+            NipartEventAddress::Commander,
+            NipartEventAddress::Unicast(
+                NipartPluginOvs::PLUGIN_NAME.to_string(),
+            ),
+            DEFAULT_TIMEOUT,
+        );
+
+        plugin.handle_event(event).await.unwrap();
+
+        let reply = from_ovs.recv().await.unwrap();
+        assert_eq!(reply.uuid, 1);
+        assert!(matches!(
+            reply.plugin,
+            NipartPluginEvent::QueryNetStateReply(_, STATE_PRIORITY)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_apply_without_ovsdb_change() {
+        let (to_daemon, mut from_ovs) = channel(64);
+        let merged_state = gen_merged_state(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up",
+        );
+
+        handle_apply(merged_state, to_daemon, 2).await;
+
+        let reply = from_ovs.recv().await.unwrap();
+        assert_eq!(reply.uuid, 2);
+        assert!(matches!(reply.user, NipartUserEvent::None));
+        assert!(matches!(
+            reply.plugin,
+            NipartPluginEvent::ApplyNetStateReply
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_apply_ovsdb_change_replies_error() {
+        if ovsdb_is_running() {
+            return;
+        }
+        let (to_daemon, mut from_ovs) = channel(64);
+        let merged_state = gen_merged_state(
+            r#"---
+            ovs-db:
+              other_config:
+                hw-offload: "true""#,
+        );
+
+        handle_apply(merged_state, to_daemon, 3).await;
+
+        let reply = from_ovs.recv().await.unwrap();
+        assert_eq!(reply.uuid, 3);
+        if let NipartUserEvent::Error(e) = reply.user {
+            assert_eq!(e.kind, ErrorKind::DependencyError);
+        } else {
+            panic!("Expecting error reply, but got {:?}", reply.user);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::Write;
+
+use nipart::{ErrorKind, NetworkAuditRecord, NipartError};
+
+// Failure of audit log is only logged as the commit is already done.
+pub(crate) fn append_audit_record(record: NetworkAuditRecord) {
+    if let Err(e) =
+        write_audit_record(NetworkAuditRecord::DEFAULT_PATH, &record)
+    {
+        log::error!("{e}");
+    }
+}
+
+fn write_audit_record(
+    path: &str,
+    record: &NetworkAuditRecord,
+) -> Result<(), NipartError> {
+    let path = std::path::Path::new(path);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| {
+            NipartError::new(
+                ErrorKind::Bug,
+                format!(
+                    "Failed to create audit log folder {}: {e}",
+                    dir.display()
+                ),
+            )
+        })?;
+    }
+    let mut line = serde_json::to_string(record).map_err(|e| {
+        NipartError::new(
+            ErrorKind::Bug,
+            format!("Failed to generate JSON for {record:?}: {e}"),
+        )
+    })?;
+    line.push('\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut fd| fd.write_all(line.as_bytes()))
+        .map_err(|e| {
+            NipartError::new(
+                ErrorKind::Bug,
+                format!(
+                    "Failed to append audit record to {}: {e}",
+                    path.display()
+                ),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use nipart::{NetworkAuditRecord, NetworkState};
+
+    use super::write_audit_record;
+
+    const TEST_SECRET: &str = "2001:db8:1::3";
+
+    fn read_records(path: &str) -> Vec<NetworkAuditRecord> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_commit_append_one_audit_record() {
+        let dir = std::env::temp_dir()
+            .join(format!("nipart_sima_audit_{}", std::process::id()));
+        let path = dir.join("audit.jsonl");
+        let path = path.to_str().unwrap();
+        let state = NetworkState::new_from_yaml(&format!(
+            r"---
+            interfaces:
+            - name: dummy1
+              type: dummy
+              state: up
+              ipv6:
+                enabled: true
+                addr-gen-mode: stable-privacy
+                stable-secret: {TEST_SECRET}"
+        ))
+        .unwrap();
+
+        write_audit_record(
+            path,
+            &NetworkAuditRecord::new("commit1".to_string(), state.clone()),
+        )
+        .unwrap();
+        let records = read_records(path);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].commit_id, "commit1");
+        assert!(!std::fs::read_to_string(path).unwrap().contains(TEST_SECRET));
+
+        write_audit_record(
+            path,
+            &NetworkAuditRecord::new("commit2".to_string(), state),
+        )
+        .unwrap();
+        let records = read_records(path);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].commit_id, "commit2");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
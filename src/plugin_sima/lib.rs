@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
+mod audit;
 mod plugin;
 mod repo;
 mod state;
@@ -2,12 +2,13 @@
 
 use gix::ThreadSafeRepository;
 use nipart::{
-    NipartError, NipartEvent, NipartEventAddress, NipartLogLevel,
-    NipartNativePlugin, NipartPluginEvent, NipartRole, NipartUserEvent,
+    NetworkAuditRecord, NipartError, NipartEvent, NipartEventAddress,
+    NipartLogLevel, NipartNativePlugin, NipartPluginEvent, NipartRole,
+    NipartUserEvent,
 };
 use tokio::sync::mpsc::{Receiver, Sender};
 
-use crate::repo::load_config_repo;
+use crate::{audit::append_audit_record, repo::load_config_repo};
 
 #[derive(Debug)]
 pub struct NipartPluginSima {
@@ -60,7 +61,8 @@ impl NipartNativePlugin for NipartPluginSima {
         match event.plugin {
             NipartPluginEvent::Commit(state) => {
                 log::trace!("Committing NetworkState {state:?}");
-                self.commit(*state)?;
+                let commit_id = self.commit(*state.clone())?;
+                append_audit_record(NetworkAuditRecord::new(commit_id, *state));
                 let mut reply = NipartEvent::new(
                     NipartUserEvent::None,
                     NipartPluginEvent::CommitReply,
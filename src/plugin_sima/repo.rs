@@ -16,10 +16,11 @@ const GIT_USER_NAME: &str = "Gris Ge";
 const GIT_USER_EMAIL: &str = "fge@redhat.com";
 
 impl NipartPluginSima {
+    // Return the ID of new commit
     pub(crate) fn commit(
         &mut self,
         state: NetworkState,
-    ) -> Result<(), NipartError> {
+    ) -> Result<String, NipartError> {
         log::trace!("Plugin sima: Committing {state:?}");
         let new_state =
             if let Some(cur_etc_commit) = self.get_saved_current()? {
@@ -95,7 +96,7 @@ impl NipartPluginSima {
                 )
             })?;
 
-        Ok(())
+        Ok(new_head_id.to_string())
     }
 
     pub(crate) fn get_saved_current(
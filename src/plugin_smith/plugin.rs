@@ -1,37 +1,91 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::{hash_map::Entry, HashMap};
+use std::collections::HashMap;
 use std::time::{Duration, SystemTime};
 
 use nipart::{
     ErrorKind, NipartError, NipartEvent, NipartEventAddress, NipartLockEntry,
-    NipartLockOption, NipartLogLevel, NipartNativePlugin, NipartPluginEvent,
-    NipartRole, NipartUserEvent,
+    NipartLockMode, NipartLockOption, NipartLogLevel, NipartNativePlugin,
+    NipartPluginEvent, NipartRole, NipartUserEvent,
 };
 use tokio::sync::mpsc::{Receiver, Sender};
 
+// Interval for checking whether pending lock requests timeout
+const PENDING_LOCK_CHECK_INTERVAL: u64 = 500;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct SmithLockOwner {
     pub(crate) uuid: u128,
     pub(crate) timeout: SystemTime,
+    pub(crate) mode: NipartLockMode,
 }
 
 impl SmithLockOwner {
     pub(crate) fn new(
         uuid: u128,
-        timeout_seconds: u32,
+        opt: &NipartLockOption,
     ) -> Result<Self, NipartError> {
         if let Some(timeout) = SystemTime::now()
-            .checked_add(Duration::from_secs(timeout_seconds.into()))
+            .checked_add(Duration::from_secs(opt.timeout_seconds.into()))
         {
-            Ok(Self { uuid, timeout })
+            Ok(Self {
+                uuid,
+                timeout,
+                mode: opt.mode,
+            })
         } else {
             Err(NipartError::new(
                 ErrorKind::InvalidArgument,
-                format!("Overflow caused by lock timeout {timeout_seconds}s"),
+                format!(
+                    "Overflow caused by lock timeout {}s",
+                    opt.timeout_seconds
+                ),
             ))
         }
     }
+
+    fn is_expired(&self) -> bool {
+        self.timeout < SystemTime::now()
+    }
+}
+
+// Lock request waiting for conflicting locks to be released
+#[derive(Debug, Clone)]
+pub(crate) struct SmithLockRequest {
+    pub(crate) uuid: u128,
+    pub(crate) entries: Vec<(NipartLockEntry, NipartLockOption)>,
+    pub(crate) src: NipartEventAddress,
+    pub(crate) timeout: u32,
+    pub(crate) deadline: SystemTime,
+}
+
+impl SmithLockRequest {
+    fn new(
+        uuid: u128,
+        entries: Vec<(NipartLockEntry, NipartLockOption)>,
+        src: NipartEventAddress,
+        timeout: u32,
+    ) -> Self {
+        Self {
+            uuid,
+            entries,
+            src,
+            timeout,
+            deadline: SystemTime::now()
+                .checked_add(Duration::from_millis(timeout.into()))
+                .unwrap_or_else(SystemTime::now),
+        }
+    }
+
+    // Two requests conflict when sharing any entry in incompatible lock
+    // modes.
+    fn is_conflict(&self, other: &Self) -> bool {
+        self.entries.iter().any(|(entry, opt)| {
+            other.entries.iter().any(|(other_entry, other_opt)| {
+                entry == other_entry && !opt.mode.is_compatible(&other_opt.mode)
+            })
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -39,7 +93,8 @@ pub struct NipartPluginSmith {
     log_level: NipartLogLevel,
     to_daemon: Sender<NipartEvent>,
     from_daemon: Receiver<NipartEvent>,
-    vault: HashMap<NipartLockEntry, SmithLockOwner>,
+    vault: HashMap<NipartLockEntry, Vec<SmithLockOwner>>,
+    pending: Vec<SmithLockRequest>,
 }
 
 impl NipartNativePlugin for NipartPluginSmith {
@@ -63,6 +118,7 @@ impl NipartNativePlugin for NipartPluginSmith {
             to_daemon: to_daemon.clone(),
             from_daemon,
             vault: HashMap::new(),
+            pending: Vec::new(),
         })
     }
 
@@ -78,6 +134,37 @@ impl NipartNativePlugin for NipartPluginSmith {
         vec![NipartRole::Locker]
     }
 
+    fn run(&mut self) -> impl std::future::Future<Output = ()> + Send {
+        async move {
+            let mut check_interval = tokio::time::interval(
+                Duration::from_millis(PENDING_LOCK_CHECK_INTERVAL),
+            );
+            // The first tick just completes instantly
+            check_interval.tick().await;
+            loop {
+                tokio::select! {
+                    _ = check_interval.tick() => {
+                        if let Err(e) = self.process_pending().await {
+                            log::error!("{e}");
+                        }
+                    }
+                    event = self.recver_from_daemon().recv() => match event {
+                        Some(event)
+                            if event.plugin == NipartPluginEvent::Quit =>
+                        {
+                            break;
+                        }
+                        Some(event) => self.handle_plugin_event(event).await,
+                        None => {
+                            log::debug!("MPSC channel remote end closed");
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     async fn handle_event(
         &mut self,
         event: NipartEvent,
@@ -85,16 +172,12 @@ impl NipartNativePlugin for NipartPluginSmith {
         match event.plugin {
             NipartPluginEvent::Lock(lock_entries) => {
                 log::trace!("Locking {lock_entries:?}");
-                self.lock(*lock_entries, event.uuid)?;
-                let mut reply = NipartEvent::new(
-                    NipartUserEvent::None,
-                    NipartPluginEvent::LockReply,
-                    NipartEventAddress::Locker,
-                    NipartEventAddress::Commander,
+                self.pending.push(SmithLockRequest::new(
+                    event.uuid,
+                    *lock_entries,
+                    event.src,
                     event.timeout,
-                );
-                reply.uuid = event.uuid;
-                self.sender_to_daemon().send(reply).await?;
+                ));
             }
             NipartPluginEvent::Unlock(lock_entries) => {
                 log::trace!("Unlocking {lock_entries:?}");
@@ -102,50 +185,145 @@ impl NipartNativePlugin for NipartPluginSmith {
             }
             _ => log::warn!("Plugin smith got unknown event {event}"),
         }
-        Ok(())
+        self.process_pending().await
     }
 }
 
 impl NipartPluginSmith {
-    fn lock(
-        &mut self,
-        lock_entries: Vec<(NipartLockEntry, NipartLockOption)>,
-        uuid: u128,
-    ) -> Result<(), NipartError> {
-        for (lock_entry, lock_opt) in lock_entries {
-            let lock_owner =
-                SmithLockOwner::new(uuid, lock_opt.timeout_seconds)?;
-
-            match self.vault.entry(lock_entry.clone()) {
-                Entry::Occupied(o) => {
-                    let cur_lock_owner = o.into_mut();
-                    // Whether current owner expired
-                    if cur_lock_owner.timeout >= SystemTime::now() {
-                        return Err(NipartError::new(
-                            ErrorKind::InvalidArgument,
-                            format!(
-                                "{lock_entry} is already locked by session {}",
-                                cur_lock_owner.uuid
-                            ),
-                        ));
-                    } else {
-                        log::debug!("Locking {lock_entry} to session {uuid}");
-                        cur_lock_owner.clone_from(&lock_owner);
-                    }
-                }
-                Entry::Vacant(v) => {
-                    log::debug!("Locking {lock_entry} to session {uuid}");
-                    v.insert(lock_owner);
+    // Grant pending requests in the order of arrival. Request is skipped
+    // if it conflicts with any earlier request still pending.
+    async fn process_pending(&mut self) -> Result<(), NipartError> {
+        let mut remains: Vec<SmithLockRequest> = Vec::new();
+        let mut granted: Vec<SmithLockRequest> = Vec::new();
+        let mut failures: Vec<NipartEvent> = Vec::new();
+        for request in std::mem::take(&mut self.pending) {
+            if request.deadline < SystemTime::now() {
+                let e = NipartError::new(
+                    ErrorKind::Timeout,
+                    format!(
+                        "Timeout on acquiring lock for session {}: {}",
+                        uuid_string(request.uuid),
+                        self.get_lock_holders(&request, remains.as_slice())
+                    ),
+                );
+                log::warn!("{e}");
+                let mut reply = NipartEvent::new(
+                    NipartUserEvent::Error(e),
+                    NipartPluginEvent::LockReply,
+                    NipartEventAddress::Locker,
+                    request.src,
+                    request.timeout,
+                );
+                reply.uuid = request.uuid;
+                failures.push(reply);
+                continue;
+            }
+            if remains.iter().any(|r| r.is_conflict(&request))
+                || !self.can_lock(&request)
+            {
+                remains.push(request);
+                continue;
+            }
+            match self.lock(&request) {
+                Ok(()) => granted.push(request),
+                Err(e) => log::error!("{e}"),
+            }
+        }
+        self.pending = remains;
+
+        for request in granted {
+            let mut reply = NipartEvent::new(
+                NipartUserEvent::None,
+                NipartPluginEvent::LockReply,
+                NipartEventAddress::Locker,
+                request.src,
+                request.timeout,
+            );
+            reply.uuid = request.uuid;
+            self.sender_to_daemon().send(reply).await?;
+        }
+        for reply in failures {
+            self.sender_to_daemon().send(reply).await?;
+        }
+        Ok(())
+    }
+
+    // Describe the locks or earlier pending requests blocking specified
+    // request.
+    fn get_lock_holders(
+        &self,
+        request: &SmithLockRequest,
+        earlier_pending: &[SmithLockRequest],
+    ) -> String {
+        let mut holders: Vec<String> = Vec::new();
+        for (entry, opt) in request.entries.as_slice() {
+            if let Some(owners) = self.vault.get(entry) {
+                for owner in owners.iter().filter(|o| {
+                    !o.is_expired()
+                        && o.uuid != request.uuid
+                        && !o.mode.is_compatible(&opt.mode)
+                }) {
+                    holders.push(format!(
+                        "{entry} is held by session {} in {} mode",
+                        uuid_string(owner.uuid),
+                        owner.mode
+                    ));
                 }
             }
         }
+        for pending in earlier_pending.iter().filter(|p| p.is_conflict(request))
+        {
+            holders.push(format!(
+                "queued behind pending request of session {}",
+                uuid_string(pending.uuid)
+            ));
+        }
+        if holders.is_empty() {
+            "no conflicting lock holder found".to_string()
+        } else {
+            holders.join(", ")
+        }
+    }
+
+    fn can_lock(&self, request: &SmithLockRequest) -> bool {
+        request.entries.iter().all(|(entry, opt)| {
+            self.vault
+                .get(entry)
+                .map(|owners| {
+                    owners
+                        .iter()
+                        .filter(|o| !o.is_expired() && o.uuid != request.uuid)
+                        .all(|o| o.mode.is_compatible(&opt.mode))
+                })
+                .unwrap_or(true)
+        })
+    }
+
+    fn lock(&mut self, request: &SmithLockRequest) -> Result<(), NipartError> {
+        let mut new_owners = Vec::new();
+        for (lock_entry, lock_opt) in request.entries.as_slice() {
+            new_owners.push((
+                lock_entry,
+                SmithLockOwner::new(request.uuid, lock_opt)?,
+            ));
+        }
+        for (lock_entry, lock_owner) in new_owners {
+            log::debug!(
+                "Locking {lock_entry} in {} mode to session {}",
+                lock_owner.mode,
+                request.uuid
+            );
+            let owners = self.vault.entry(lock_entry.clone()).or_default();
+            owners.retain(|o| !o.is_expired() && o.uuid != request.uuid);
+            owners.push(lock_owner);
+        }
         Ok(())
     }
 
     fn unlock(&mut self, lock_entries: &[NipartLockEntry], uuid: u128) {
         for lock_entry in lock_entries {
-            if let Some(cur_owner) = self.vault.get(lock_entry) {
-                if cur_owner.uuid == uuid {
+            if let Some(owners) = self.vault.get_mut(lock_entry) {
+                if owners.iter().any(|o| o.uuid == uuid) {
                     log::debug!(
                         "Unlocking {lock_entry} owned by session {uuid}"
                     );
@@ -155,12 +333,262 @@ impl NipartPluginSmith {
                     // warning or error.
                     log::debug!(
                         "Cannot unlock {lock_entry} on behave of \
-                        session {} because it is owned by other session {}",
-                        uuid,
-                        cur_owner.uuid
+                        session {uuid} because it is not owned by it",
                     );
                 }
+                owners.retain(|o| !o.is_expired() && o.uuid != uuid);
+                if owners.is_empty() {
+                    self.vault.remove(lock_entry);
+                }
             }
         }
     }
 }
+
+fn uuid_string(id: u128) -> String {
+    uuid::Uuid::from_u128(id).hyphenated().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc::{channel, Receiver};
+
+    use super::*;
+
+    async fn new_smith() -> (NipartPluginSmith, Receiver<NipartEvent>) {
+        let (to_daemon, from_smith) = channel(64);
+        let (_to_smith, from_daemon) = channel(64);
+        let smith = NipartPluginSmith::init(
+            NipartLogLevel::Off,
+            to_daemon,
+            from_daemon,
+        )
+        .await
+        .unwrap();
+        (smith, from_smith)
+    }
+
+    fn gen_lock_event(
+        uuid: u128,
+        mode: NipartLockMode,
+        lock_timeout: u32,
+        timeout: u32,
+    ) -> NipartEvent {
+        NipartEvent::new_with_uuid(
+            uuid,
+            NipartUserEvent::None,
+            NipartPluginEvent::Lock(Box::new(vec![(
+                NipartLockEntry::NetState,
+                NipartLockOption::new_with_mode(lock_timeout, mode),
+            )])),
+            NipartEventAddress::Commander,
+            NipartEventAddress::Locker,
+            timeout,
+        )
+    }
+
+    fn gen_unlock_event(uuid: u128) -> NipartEvent {
+        NipartEvent::new_with_uuid(
+            uuid,
+            NipartUserEvent::None,
+            NipartPluginEvent::Unlock(Box::new(vec![
+                NipartLockEntry::NetState,
+            ])),
+            NipartEventAddress::Commander,
+            NipartEventAddress::Locker,
+            1000,
+        )
+    }
+
+    // Return uuid of sessions granted with lock, panic on error reply
+    fn get_granted(from_smith: &mut Receiver<NipartEvent>) -> Vec<u128> {
+        let mut ret = Vec::new();
+        while let Ok(reply) = from_smith.try_recv() {
+            assert_eq!(reply.plugin, NipartPluginEvent::LockReply);
+            assert_eq!(reply.user, NipartUserEvent::None);
+            ret.push(reply.uuid);
+        }
+        ret
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_readers() {
+        let (mut smith, mut from_smith) = new_smith().await;
+
+        for uuid in [1, 2, 3] {
+            smith
+                .handle_event(gen_lock_event(
+                    uuid,
+                    NipartLockMode::Shared,
+                    10,
+                    5000,
+                ))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(get_granted(&mut from_smith), vec![1, 2, 3]);
+        assert!(smith.pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_intent_exclusive() {
+        let (mut smith, mut from_smith) = new_smith().await;
+
+        for uuid in [1, 2] {
+            smith
+                .handle_event(gen_lock_event(
+                    uuid,
+                    NipartLockMode::IntentExclusive,
+                    10,
+                    5000,
+                ))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(get_granted(&mut from_smith), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_writer_wait_readers_drain() {
+        let (mut smith, mut from_smith) = new_smith().await;
+
+        for uuid in [1, 2] {
+            smith
+                .handle_event(gen_lock_event(
+                    uuid,
+                    NipartLockMode::Shared,
+                    10,
+                    5000,
+                ))
+                .await
+                .unwrap();
+        }
+        assert_eq!(get_granted(&mut from_smith), vec![1, 2]);
+
+        smith
+            .handle_event(gen_lock_event(
+                3,
+                NipartLockMode::IntentExclusive,
+                10,
+                5000,
+            ))
+            .await
+            .unwrap();
+        // Reader arrived after pending writer should not starve the writer
+        smith
+            .handle_event(gen_lock_event(4, NipartLockMode::Shared, 10, 5000))
+            .await
+            .unwrap();
+        assert!(get_granted(&mut from_smith).is_empty());
+
+        smith.handle_event(gen_unlock_event(1)).await.unwrap();
+        assert!(get_granted(&mut from_smith).is_empty());
+
+        smith.handle_event(gen_unlock_event(2)).await.unwrap();
+        assert_eq!(get_granted(&mut from_smith), vec![3]);
+
+        smith.handle_event(gen_unlock_event(3)).await.unwrap();
+        assert_eq!(get_granted(&mut from_smith), vec![4]);
+        assert!(smith.pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_grant_pending_after_owner_expired() {
+        let (mut smith, mut from_smith) = new_smith().await;
+
+        smith
+            .handle_event(gen_lock_event(1, NipartLockMode::Exclusive, 1, 5000))
+            .await
+            .unwrap();
+        smith
+            .handle_event(gen_lock_event(2, NipartLockMode::Shared, 10, 5000))
+            .await
+            .unwrap();
+        assert_eq!(get_granted(&mut from_smith), vec![1]);
+
+        // No more event, the periodic check should grant the lock
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        smith.process_pending().await.unwrap();
+        assert_eq!(get_granted(&mut from_smith), vec![2]);
+    }
+
+    #[tokio::test]
+    async fn test_reply_error_on_pending_timeout() {
+        let (mut smith, mut from_smith) = new_smith().await;
+
+        smith
+            .handle_event(gen_lock_event(1, NipartLockMode::Shared, 10, 5000))
+            .await
+            .unwrap();
+        smith
+            .handle_event(gen_lock_event(2, NipartLockMode::Exclusive, 10, 100))
+            .await
+            .unwrap();
+        assert_eq!(get_granted(&mut from_smith), vec![1]);
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        smith.process_pending().await.unwrap();
+
+        let reply = from_smith.try_recv().unwrap();
+        assert_eq!(reply.uuid, 2);
+        assert_eq!(reply.plugin, NipartPluginEvent::LockReply);
+        if let NipartUserEvent::Error(e) = reply.user {
+            assert_eq!(e.kind, ErrorKind::Timeout);
+        } else {
+            panic!("Expecting timeout error, got {reply:?}");
+        }
+        assert!(smith.pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_lock_timeout_name_holder() {
+        let (mut smith, mut from_smith) = new_smith().await;
+        let entry = NipartLockEntry::new_iface(
+            "eth1".to_string(),
+            nipart::InterfaceType::Ethernet,
+        );
+        let gen_iface_lock_event = |uuid: u128, timeout: u32| {
+            NipartEvent::new_with_uuid(
+                uuid,
+                NipartUserEvent::None,
+                NipartPluginEvent::Lock(Box::new(vec![(
+                    entry.clone(),
+                    NipartLockOption::new_with_mode(
+                        10,
+                        NipartLockMode::Exclusive,
+                    ),
+                )])),
+                NipartEventAddress::Commander,
+                NipartEventAddress::Locker,
+                timeout,
+            )
+        };
+
+        smith
+            .handle_event(gen_iface_lock_event(1, 5000))
+            .await
+            .unwrap();
+        smith
+            .handle_event(gen_iface_lock_event(2, 100))
+            .await
+            .unwrap();
+        assert_eq!(get_granted(&mut from_smith), vec![1]);
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        smith.process_pending().await.unwrap();
+
+        let reply = from_smith.try_recv().unwrap();
+        assert_eq!(reply.uuid, 2);
+        if let NipartUserEvent::Error(e) = reply.user {
+            assert_eq!(e.kind, ErrorKind::Timeout);
+            assert!(e.msg.contains(&format!(
+                "{entry} is held by session {} in",
+                uuid_string(1)
+            )));
+        } else {
+            panic!("Expecting timeout error, got {reply:?}");
+        }
+    }
+}